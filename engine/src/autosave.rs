@@ -0,0 +1,178 @@
+// Editor Autosave
+// Periodic background saves of the scene to a recovery file, and detection of one left by a crash
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+const DEFAULT_INTERVAL: Duration = Duration::from_secs(60);
+
+#[derive(Serialize)]
+struct RecoveryFileRef<'a, T> {
+    clean_exit: bool,
+    scene: &'a T,
+}
+
+#[derive(Deserialize)]
+struct RecoveryFile<T> {
+    /// Set by the final save of a session that closed normally
+    clean_exit: bool,
+    scene: T,
+}
+
+/// Autosave state. Scenes are serialized on the calling thread, which is quick for
+/// editor-sized scenes, and written to disk on a background thread.
+#[derive(Debug)]
+pub struct Autosave {
+    pub interval: Duration,
+    path: PathBuf,
+    last_save: Instant,
+    pending_write: Option<JoinHandle<io::Result<()>>>,
+}
+
+impl Autosave {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            interval: DEFAULT_INTERVAL,
+            path: path.into(),
+            last_save: Instant::now(),
+            pending_write: None,
+        }
+    }
+
+    /// Recovery file location used by the editor
+    pub fn default_path() -> PathBuf {
+        std::env::temp_dir().join("physics_editor_recovery.json")
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn is_due(&self) -> bool {
+        self.last_save.elapsed() >= self.interval
+    }
+
+    /// Save the scene in the background; a write still in progress is waited for
+    /// first so saves land in order
+    pub fn save<T: Serialize>(&mut self, scene: &T) -> io::Result<()> {
+        self.write(scene, false)
+    }
+
+    /// Save a final time and wait for it, marking the file as left by a clean exit
+    pub fn save_on_exit<T: Serialize>(&mut self, scene: &T) -> io::Result<()> {
+        self.write(scene, true)?;
+        self.flush()
+    }
+
+    fn write<T: Serialize>(&mut self, scene: &T, clean_exit: bool) -> io::Result<()> {
+        let contents = serde_json::to_string_pretty(&RecoveryFileRef { clean_exit, scene })?;
+        self.flush()?;
+
+        let path = self.path.clone();
+        self.pending_write = Some(std::thread::spawn(move || {
+            write_atomically(&path, &contents)
+        }));
+        self.last_save = Instant::now();
+        Ok(())
+    }
+
+    /// Wait for a background write to finish
+    pub fn flush(&mut self) -> io::Result<()> {
+        match self.pending_write.take() {
+            Some(handle) => handle
+                .join()
+                .unwrap_or_else(|_| Err(io::Error::other("autosave thread panicked"))),
+            None => Ok(()),
+        }
+    }
+
+    /// Whether a recovery file was left by a session that did not exit cleanly
+    pub fn has_pending_recovery(&self) -> bool {
+        fs::read_to_string(&self.path)
+            .ok()
+            .and_then(|contents| {
+                serde_json::from_str::<RecoveryFile<serde_json::Value>>(&contents).ok()
+            })
+            .is_some_and(|file| !file.clean_exit)
+    }
+
+    pub fn load_recovery<T: DeserializeOwned>(&self) -> io::Result<T> {
+        let contents = fs::read_to_string(&self.path)?;
+        let file: RecoveryFile<T> = serde_json::from_str(&contents)?;
+        Ok(file.scene)
+    }
+
+    /// Remove the recovery file once it has been restored or declined
+    pub fn discard(&mut self) -> io::Result<()> {
+        self.flush()?;
+        match fs::remove_file(&self.path) {
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(()),
+            result => result,
+        }
+    }
+}
+
+/// Write to a temporary sibling and rename it into place, so a crash mid-write
+/// never leaves a truncated file behind
+pub(crate) fn write_atomically(path: &Path, contents: &str) -> io::Result<()> {
+    let temporary = path.with_extension("tmp");
+    fs::write(&temporary, contents)?;
+    fs::rename(&temporary, path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Scene {
+        names: Vec<String>,
+        frame: u32,
+    }
+
+    fn scene() -> Scene {
+        Scene {
+            names: vec!["Cube".to_string(), "Sphere".to_string()],
+            frame: 12,
+        }
+    }
+
+    fn recovery_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("autosave_{}_{}.json", name, std::process::id()))
+    }
+
+    #[test]
+    fn test_autosave_writes_valid_recovery_file() {
+        let mut autosave = Autosave::new(recovery_path("write"));
+        assert!(!autosave.has_pending_recovery());
+
+        autosave.save(&scene()).unwrap();
+        autosave.flush().unwrap();
+
+        let contents = fs::read_to_string(autosave.path()).unwrap();
+        assert!(serde_json::from_str::<serde_json::Value>(&contents).is_ok());
+        assert_eq!(autosave.load_recovery::<Scene>().unwrap(), scene());
+
+        // A fresh session finds the file left behind
+        let restarted = Autosave::new(autosave.path());
+        assert!(restarted.has_pending_recovery());
+
+        autosave.discard().unwrap();
+        assert!(!autosave.path().exists());
+    }
+
+    #[test]
+    fn test_clean_exit_leaves_no_pending_recovery() {
+        let mut autosave = Autosave::new(recovery_path("exit"));
+        autosave.save(&scene()).unwrap();
+        autosave.save_on_exit(&scene()).unwrap();
+
+        assert!(!Autosave::new(autosave.path()).has_pending_recovery());
+        autosave.discard().unwrap();
+    }
+}
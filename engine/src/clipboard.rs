@@ -0,0 +1,116 @@
+// Object Clipboard
+// Copying selected objects and pasting duplicates of them into the scene
+
+use crate::grid::GridSettings;
+use crate::gui::{GameObject, Vec3};
+use crate::undo::EditorAction;
+use std::collections::HashMap;
+
+/// How far each paste lands from the previous copy, so duplicates don't overlap
+const PASTE_OFFSET: Vec3 = Vec3 {
+    x: 0.5,
+    y: 0.0,
+    z: 0.5,
+};
+
+/// Objects copied in the editor, with all their components
+#[derive(Debug, Clone, Default)]
+pub struct Clipboard {
+    objects: Vec<GameObject>,
+}
+
+impl Clipboard {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Replace the clipboard with copies of the objects in `ids` that exist
+    pub fn copy(&mut self, objects: &HashMap<usize, GameObject>, ids: &[usize]) {
+        self.objects = ids
+            .iter()
+            .filter_map(|id| objects.get(id))
+            .cloned()
+            .collect();
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.objects.is_empty()
+    }
+
+    /// Insert duplicates of the copied objects under fresh ids taken from `next_id`,
+    /// offset from where they were copied and snapped to `grid`. Pasting again offsets
+    /// further. Returns the ids of the new objects and one action that removes them all.
+    pub fn paste(
+        &mut self,
+        objects: &mut HashMap<usize, GameObject>,
+        next_id: &mut usize,
+        grid: &GridSettings,
+    ) -> Option<(Vec<usize>, EditorAction)> {
+        if self.objects.is_empty() {
+            return None;
+        }
+
+        let mut created = Vec::new();
+        for copied in &mut self.objects {
+            copied.transform.position = copied.transform.position + PASTE_OFFSET;
+            let mut obj = copied.clone();
+            obj.transform.position = grid.snap_position(obj.transform.position);
+            obj.id = *next_id;
+            *next_id += 1;
+            objects.insert(obj.id, obj.clone());
+            created.push(obj);
+        }
+
+        let ids = created.iter().map(|obj| obj.id).collect();
+        Some((ids, EditorAction::CreateObjects { objects: created }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gui::{GameObjectType, RigidBody};
+    use crate::undo::UndoStack;
+
+    #[test]
+    fn test_paste_duplicates_components_under_new_id() {
+        let mut cube = GameObject::new(4, "Crate".to_string(), GameObjectType::Cube);
+        cube.transform.position = Vec3::new(1.0, 2.0, 3.0);
+        cube.transform.scale = Vec3::new(2.0, 1.0, 1.0);
+        cube.rigid_body = Some(RigidBody {
+            mass: 7.5,
+            use_gravity: false,
+            ..Default::default()
+        });
+        let mut objects = HashMap::from([(cube.id, cube.clone())]);
+        let mut next_id = 5;
+        let grid = GridSettings::default();
+
+        let mut clipboard = Clipboard::new();
+        clipboard.copy(&objects, &[4]);
+        let (ids, action) = clipboard.paste(&mut objects, &mut next_id, &grid).unwrap();
+        assert_eq!(ids, vec![5]);
+        assert_eq!(next_id, 6);
+
+        let pasted = &objects[&5];
+        assert_eq!(pasted.name, cube.name);
+        assert_eq!(pasted.object_type, cube.object_type);
+        assert_eq!(pasted.rigid_body, cube.rigid_body);
+        assert_eq!(pasted.transform.scale, cube.transform.scale);
+        assert_eq!(pasted.transform.position, Vec3::new(1.5, 2.0, 3.5));
+        assert_eq!(objects[&4], cube);
+
+        // A second paste steps further along instead of stacking on the first
+        let (ids, _) = clipboard.paste(&mut objects, &mut next_id, &grid).unwrap();
+        assert_eq!(
+            objects[&ids[0]].transform.position,
+            Vec3::new(2.0, 2.0, 4.0)
+        );
+
+        let mut stack = UndoStack::new();
+        stack.push(action);
+        stack.undo().unwrap().apply(&mut objects, true);
+        assert!(!objects.contains_key(&5));
+        assert!(objects.contains_key(&4));
+    }
+}
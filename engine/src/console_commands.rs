@@ -0,0 +1,317 @@
+// Console Command Input
+// Command line state for the editor console: registered commands, history recall, tab completion,
+// and formatting of tables and key/value listings for output
+
+/// Object types that can be passed to `spawn`
+pub const SPAWNABLE_TYPES: &[&str] = &[
+    "cube", "sphere", "cylinder", "plane", "light", "camera", "empty",
+];
+
+/// Result of a tab-completion request
+#[derive(Debug, Clone, PartialEq)]
+pub enum Completion {
+    /// Nothing matched the current input
+    NoMatch,
+    /// A single candidate matched and the input was completed
+    Completed(String),
+    /// Several candidates matched; the input was extended to their common prefix
+    Ambiguous(Vec<String>),
+}
+
+/// Console input line with history and completion
+#[derive(Debug, Clone)]
+pub struct ConsoleInput {
+    pub buffer: String,
+    history: Vec<String>,
+    history_index: Option<usize>,
+    commands: Vec<String>,
+    max_history: usize,
+}
+
+impl ConsoleInput {
+    pub fn new() -> Self {
+        let mut input = Self {
+            buffer: String::new(),
+            history: Vec::new(),
+            history_index: None,
+            commands: Vec::new(),
+            max_history: 100,
+        };
+
+        for command in [
+            "help", "clear", "spawn", "delete", "list", "select", "inspect", "play", "pause",
+            "stats",
+        ] {
+            input.register_command(command);
+        }
+
+        input
+    }
+
+    /// Register a command name for tab completion
+    pub fn register_command(&mut self, name: &str) {
+        if !self.commands.iter().any(|c| c == name) {
+            self.commands.push(name.to_string());
+            self.commands.sort();
+        }
+    }
+
+    pub fn commands(&self) -> &[String] {
+        &self.commands
+    }
+
+    pub fn history(&self) -> &[String] {
+        &self.history
+    }
+
+    /// Take the current input as a command, recording it in the history
+    pub fn submit(&mut self) -> Option<String> {
+        let command = self.buffer.trim().to_string();
+        self.buffer.clear();
+        self.history_index = None;
+
+        if command.is_empty() {
+            return None;
+        }
+
+        if self.history.last() != Some(&command) {
+            self.history.push(command.clone());
+            if self.history.len() > self.max_history {
+                self.history.remove(0);
+            }
+        }
+
+        Some(command)
+    }
+
+    /// Recall the previous (older) history entry (arrow up)
+    pub fn history_previous(&mut self) -> Option<&str> {
+        if self.history.is_empty() {
+            return None;
+        }
+
+        let index = match self.history_index {
+            Some(0) => 0,
+            Some(i) => i - 1,
+            None => self.history.len() - 1,
+        };
+
+        self.history_index = Some(index);
+        self.buffer = self.history[index].clone();
+        Some(&self.history[index])
+    }
+
+    /// Recall the next (newer) history entry (arrow down)
+    pub fn history_next(&mut self) -> Option<&str> {
+        let index = self.history_index?;
+
+        if index + 1 < self.history.len() {
+            self.history_index = Some(index + 1);
+            self.buffer = self.history[index + 1].clone();
+            Some(&self.history[index + 1])
+        } else {
+            // Walked past the newest entry: back to an empty prompt
+            self.history_index = None;
+            self.buffer.clear();
+            None
+        }
+    }
+
+    /// Complete the command name, or the object type for `spawn`
+    pub fn complete(&mut self) -> Completion {
+        let (head, partial, candidates): (String, String, Vec<String>) =
+            match self.buffer.split_once(' ') {
+                None => (String::new(), self.buffer.clone(), self.commands.clone()),
+                Some((command, rest)) if command == "spawn" && !rest.contains(' ') => (
+                    "spawn ".to_string(),
+                    rest.to_string(),
+                    SPAWNABLE_TYPES.iter().map(|t| t.to_string()).collect(),
+                ),
+                Some(_) => return Completion::NoMatch,
+            };
+
+        let matches: Vec<String> = candidates
+            .into_iter()
+            .filter(|c| c.starts_with(&partial))
+            .collect();
+
+        match matches.len() {
+            0 => Completion::NoMatch,
+            1 => {
+                self.buffer = format!("{}{} ", head, matches[0]);
+                Completion::Completed(matches[0].clone())
+            }
+            _ => {
+                let prefix = common_prefix(&matches);
+                self.buffer = format!("{}{}", head, prefix);
+                Completion::Ambiguous(matches)
+            }
+        }
+    }
+}
+
+impl Default for ConsoleInput {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Lay out `rows` under `headers` as left-aligned columns, with a rule below the
+/// header. Each column is as wide as its widest cell; short rows get empty cells.
+pub fn format_table(headers: &[&str], rows: &[Vec<String>]) -> Vec<String> {
+    let columns = rows.iter().map(Vec::len).fold(headers.len(), usize::max);
+    let mut widths = vec![0; columns];
+    for (i, header) in headers.iter().enumerate() {
+        widths[i] = header.chars().count();
+    }
+    for row in rows {
+        for (i, cell) in row.iter().enumerate() {
+            widths[i] = widths[i].max(cell.chars().count());
+        }
+    }
+
+    let format_row = |cells: Vec<&str>| {
+        let padded: Vec<String> = widths
+            .iter()
+            .enumerate()
+            .map(|(i, &width)| format!("{:<width$}", cells.get(i).copied().unwrap_or("")))
+            .collect();
+        padded.join("  ").trim_end().to_string()
+    };
+
+    let mut lines = vec![format_row(headers.to_vec())];
+    lines.push(
+        widths
+            .iter()
+            .map(|&width| "-".repeat(width))
+            .collect::<Vec<_>>()
+            .join("  "),
+    );
+    for row in rows {
+        lines.push(format_row(row.iter().map(String::as_str).collect()));
+    }
+    lines
+}
+
+/// One `key: value` line per pair, with the values lined up
+pub fn format_kv(pairs: &[(&str, String)]) -> Vec<String> {
+    let width = pairs
+        .iter()
+        .map(|(key, _)| key.chars().count() + 1)
+        .max()
+        .unwrap_or(0);
+    pairs
+        .iter()
+        .map(|(key, value)| format!("{:<width$}  {}", format!("{}:", key), value))
+        .collect()
+}
+
+fn common_prefix(words: &[String]) -> String {
+    let Some(first) = words.first() else {
+        return String::new();
+    };
+
+    let mut len = first.len();
+    for word in &words[1..] {
+        len = first
+            .chars()
+            .zip(word.chars())
+            .take_while(|(a, b)| a == b)
+            .count()
+            .min(len);
+    }
+
+    first.chars().take(len).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_history_recall_returns_previous_command() {
+        let mut input = ConsoleInput::new();
+        input.buffer = "spawn cube".to_string();
+        input.submit();
+        input.buffer = "list".to_string();
+        input.submit();
+
+        assert_eq!(input.history_previous(), Some("list"));
+        assert_eq!(input.history_previous(), Some("spawn cube"));
+        assert_eq!(input.history_previous(), Some("spawn cube"));
+        assert_eq!(input.history_next(), Some("list"));
+        assert_eq!(input.history_next(), None);
+        assert!(input.buffer.is_empty());
+    }
+
+    #[test]
+    fn test_tab_completes_command_name() {
+        let mut input = ConsoleInput::new();
+        input.buffer = "spa".to_string();
+
+        assert_eq!(input.complete(), Completion::Completed("spawn".to_string()));
+        assert_eq!(input.buffer, "spawn ");
+    }
+
+    #[test]
+    fn test_tab_completes_spawn_argument() {
+        let mut input = ConsoleInput::new();
+        input.buffer = "spawn sp".to_string();
+
+        assert_eq!(
+            input.complete(),
+            Completion::Completed("sphere".to_string())
+        );
+        assert_eq!(input.buffer, "spawn sphere ");
+    }
+
+    #[test]
+    fn test_ambiguous_prefix_lists_candidates() {
+        let mut input = ConsoleInput::new();
+        input.buffer = "spawn c".to_string();
+
+        match input.complete() {
+            Completion::Ambiguous(candidates) => {
+                assert!(candidates.contains(&"cube".to_string()));
+                assert!(candidates.contains(&"cylinder".to_string()));
+                assert!(candidates.contains(&"camera".to_string()));
+            }
+            other => panic!("Expected ambiguous completion, got {:?}", other),
+        }
+        assert_eq!(input.buffer, "spawn c");
+    }
+
+    #[test]
+    fn test_table_columns_are_aligned() {
+        let rows = vec![
+            vec!["0".to_string(), "Cube".to_string(), "Cube".to_string()],
+            vec![
+                "12".to_string(),
+                "Ground plane".to_string(),
+                "Plane".to_string(),
+            ],
+            vec!["3".to_string()],
+        ];
+        let lines = format_table(&["ID", "Name", "Type"], &rows);
+
+        assert_eq!(
+            lines,
+            vec![
+                "ID  Name          Type",
+                "--  ------------  -----",
+                "0   Cube          Cube",
+                "12  Ground plane  Plane",
+                "3",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_key_value_pairs_are_aligned() {
+        let lines = format_kv(&[
+            ("Objects", "4".to_string()),
+            ("Sim time", "1.50s".to_string()),
+        ]);
+        assert_eq!(lines, vec!["Objects:   4", "Sim time:  1.50s"]);
+    }
+}
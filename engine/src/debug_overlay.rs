@@ -0,0 +1,163 @@
+// Physics Debug Overlay
+// Scene view toggles for drawing bounds, contacts and velocities over the simulated bodies
+
+use matrix_lang::stdlib::{BodyDescriptor, DebugDrawOptions, DebugShape, PhysicsWorld};
+use std::collections::HashMap;
+
+/// Debug overlay state. Scene bodies are mirrored into a physics world so the
+/// geometry comes from the same bounds and contact tests as the simulation.
+#[derive(Debug)]
+pub struct DebugOverlay {
+    pub show_aabbs: bool,
+    pub show_contacts: bool,
+    pub show_velocities: bool,
+    /// Draw while paused as well, not only while simulating
+    pub always_visible: bool,
+    world: PhysicsWorld,
+    /// Mirrored physics body of each scene GameObject, by GameObject id
+    bodies: HashMap<usize, usize>,
+}
+
+impl Default for DebugOverlay {
+    fn default() -> Self {
+        Self {
+            show_aabbs: false,
+            show_contacts: false,
+            show_velocities: false,
+            always_visible: false,
+            world: PhysicsWorld::new(),
+            bodies: HashMap::new(),
+        }
+    }
+}
+
+impl DebugOverlay {
+    pub fn is_enabled(&self) -> bool {
+        self.show_aabbs || self.show_contacts || self.show_velocities
+    }
+
+    /// Replace the mirrored bodies, given with the ids of the GameObjects they stand
+    /// for, and recompute their contacts. Bodies are added in GameObject id order, so
+    /// the same scene always mirrors to the same physics body ids.
+    pub fn sync(&mut self, mut bodies: Vec<(usize, BodyDescriptor)>) {
+        bodies.sort_by_key(|&(object_id, _)| object_id);
+        let (object_ids, descriptors): (Vec<usize>, Vec<BodyDescriptor>) =
+            bodies.into_iter().unzip();
+
+        self.world.objects.clear();
+        let body_ids = self.world.add_objects(descriptors);
+        self.bodies = object_ids.into_iter().zip(body_ids).collect();
+        self.world.collision_events = self.world.detect_collisions();
+    }
+
+    /// Physics body mirroring a GameObject, if it was in the last sync
+    pub fn body_of(&self, object_id: usize) -> Option<usize> {
+        self.bodies.get(&object_id).copied()
+    }
+
+    /// GameObject a mirrored physics body stands for
+    pub fn object_of(&self, body: usize) -> Option<usize> {
+        self.bodies
+            .iter()
+            .find(|&(_, &b)| b == body)
+            .map(|(&object_id, _)| object_id)
+    }
+
+    /// Shapes to draw this frame; empty while paused unless `always_visible` is set
+    pub fn draw_list(&self, simulating: bool) -> Vec<DebugShape> {
+        if !simulating && !self.always_visible {
+            return Vec::new();
+        }
+        self.world.debug_geometry(&DebugDrawOptions {
+            aabbs: self.show_aabbs,
+            contacts: self.show_contacts,
+            velocities: self.show_velocities,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use matrix_lang::stdlib::Vec3;
+
+    fn body(shape: &str, x: f64) -> BodyDescriptor {
+        BodyDescriptor {
+            shape: shape.to_string(),
+            mass: 1.0,
+            position: Vec3::new(x, 2.0, 0.0),
+            velocity: Vec3::new(0.0, -1.0, 0.0),
+            half_extents: Some(Vec3::new(0.5, 0.5, 0.5)),
+        }
+    }
+
+    #[test]
+    fn test_aabb_overlay_draws_one_box_per_body() {
+        let mut overlay = DebugOverlay {
+            show_aabbs: true,
+            ..Default::default()
+        };
+        overlay.sync(vec![
+            (1, body("box", 0.0)),
+            (2, body("sphere", 3.0)),
+            (3, body("box", 6.0)),
+        ]);
+
+        let shapes = overlay.draw_list(true);
+        assert_eq!(shapes.len(), 3);
+        assert!(shapes
+            .iter()
+            .all(|shape| matches!(shape, DebugShape::Aabb { .. })));
+
+        // Re-syncing replaces the bodies rather than adding to them
+        overlay.sync(vec![(1, body("box", 0.0))]);
+        assert_eq!(overlay.draw_list(true).len(), 1);
+    }
+
+    #[test]
+    fn test_overlay_hidden_while_paused_unless_always_visible() {
+        let mut overlay = DebugOverlay {
+            show_aabbs: true,
+            show_velocities: true,
+            ..Default::default()
+        };
+        overlay.sync(vec![(1, body("box", 0.0))]);
+
+        assert!(overlay.draw_list(false).is_empty());
+        overlay.always_visible = true;
+        assert_eq!(overlay.draw_list(false).len(), 2);
+    }
+
+    #[test]
+    fn test_each_game_object_tracks_its_own_body() {
+        let mut overlay = DebugOverlay {
+            show_aabbs: true,
+            ..Default::default()
+        };
+        // Listed out of id order, as scene objects come out of a HashMap
+        overlay.sync(vec![(9, body("sphere", 4.0)), (2, body("box", -4.0))]);
+
+        let aabb_center = |overlay: &DebugOverlay, object_id: usize| {
+            let body = overlay.body_of(object_id).unwrap();
+            overlay
+                .draw_list(true)
+                .into_iter()
+                .find_map(|shape| match shape {
+                    DebugShape::Aabb { body: b, min, max } if b == body => Some((min + max) * 0.5),
+                    _ => None,
+                })
+                .unwrap()
+        };
+        assert_eq!(aabb_center(&overlay, 2).x, -4.0);
+        assert_eq!(aabb_center(&overlay, 9).x, 4.0);
+        for object_id in [2, 9] {
+            let body = overlay.body_of(object_id).unwrap();
+            assert_eq!(overlay.object_of(body), Some(object_id));
+        }
+
+        // Removing an object drops it from the mapping and keeps the other right
+        overlay.sync(vec![(9, body("sphere", 4.0))]);
+        assert_eq!(overlay.body_of(2), None);
+        assert_eq!(aabb_center(&overlay, 9).x, 4.0);
+    }
+}
@@ -0,0 +1,251 @@
+// Viewport Grid and Snapping
+// Grid display settings and snap-to-grid rounding for object placement
+
+use crate::gui::{GameObject, GizmoMode, Quaternion, Transform, Vec3};
+use crate::undo::EditorAction;
+
+/// Degrees of rotation per pixel dragged
+const ROTATE_DEGREES_PER_PIXEL: f32 = 0.5;
+/// Fractional scale change per pixel dragged
+const SCALE_PER_PIXEL: f32 = 0.01;
+/// Smallest scale a drag can shrink an axis to
+const MIN_SCALE: f32 = 0.01;
+
+/// Scene view grid configuration
+#[derive(Debug, Clone)]
+pub struct GridSettings {
+    pub visible: bool,
+    pub snap_enabled: bool,
+    /// Grid spacing and snap increment, in world units
+    pub increment: f32,
+}
+
+impl Default for GridSettings {
+    fn default() -> Self {
+        Self {
+            visible: true,
+            snap_enabled: false,
+            increment: 1.0,
+        }
+    }
+}
+
+impl GridSettings {
+    /// Round a coordinate to the nearest grid increment when snapping is enabled
+    pub fn snap(&self, value: f32) -> f32 {
+        if !self.snap_enabled || self.increment <= 0.0 {
+            return value;
+        }
+        (value / self.increment).round() * self.increment
+    }
+
+    pub fn snap_position(&self, position: Vec3) -> Vec3 {
+        Vec3::new(
+            self.snap(position.x),
+            self.snap(position.y),
+            self.snap(position.z),
+        )
+    }
+}
+
+/// An in-progress gizmo drag on one object.
+/// Tracks the unsnapped position so small mouse moves accumulate across frames.
+#[derive(Debug, Clone)]
+pub struct TransformDrag {
+    pub object_id: usize,
+    start: Transform,
+    raw_position: Vec3,
+}
+
+impl TransformDrag {
+    pub fn begin(object_id: usize, transform: &Transform) -> Self {
+        Self {
+            object_id,
+            start: transform.clone(),
+            raw_position: transform.position,
+        }
+    }
+
+    /// Apply one frame's mouse movement of `pixels` (screen x right, y down) to `obj`
+    /// according to `mode`. Translation moves in the view plane by `world_per_pixel`,
+    /// rotation turns about Y and scaling grows with rightward movement. A rigid body
+    /// is stopped and has its orientation matched so the simulation resumes from the
+    /// dragged pose.
+    pub fn drag(
+        &mut self,
+        obj: &mut GameObject,
+        mode: GizmoMode,
+        pixels: (f32, f32),
+        world_per_pixel: f32,
+        grid: &GridSettings,
+    ) {
+        let (dx, dy) = pixels;
+        let transform = &mut obj.transform;
+        match mode {
+            GizmoMode::Translate => {
+                let delta = Vec3::new(dx * world_per_pixel, -dy * world_per_pixel, 0.0);
+                transform.position = self.translate(delta, grid);
+            }
+            GizmoMode::Rotate => {
+                transform.rotation.y += dx * ROTATE_DEGREES_PER_PIXEL;
+            }
+            GizmoMode::Scale => {
+                let factor = 1.0 + dx * SCALE_PER_PIXEL;
+                let scale = transform.scale * factor;
+                transform.scale = Vec3::new(
+                    scale.x.max(MIN_SCALE),
+                    scale.y.max(MIN_SCALE),
+                    scale.z.max(MIN_SCALE),
+                );
+            }
+        }
+
+        if let Some(body) = &mut obj.rigid_body {
+            body.velocity = Vec3::zero();
+            body.angular_velocity = Vec3::zero();
+            body.orientation = Quaternion::from_euler_degrees(obj.transform.rotation);
+        }
+    }
+
+    /// Move by a world-space delta, returning the (possibly snapped) new position
+    pub fn translate(&mut self, delta: Vec3, grid: &GridSettings) -> Vec3 {
+        self.raw_position = self.raw_position + delta;
+        grid.snap_position(self.raw_position)
+    }
+
+    /// End the drag, producing a single undo action if the transform changed
+    pub fn finish(self, current: &Transform) -> Option<EditorAction> {
+        (self.start != *current).then(|| EditorAction::ModifyTransform {
+            object_id: self.object_id,
+            before: self.start,
+            after: current.clone(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gui::GameObjectType;
+    use crate::undo::UndoStack;
+
+    #[test]
+    fn test_snap_rounds_to_increment() {
+        let mut grid = GridSettings {
+            snap_enabled: true,
+            increment: 0.5,
+            ..Default::default()
+        };
+        assert_eq!(grid.snap(1.3), 1.5);
+
+        grid.increment = 1.0;
+        assert_eq!(grid.snap(1.3), 1.0);
+
+        grid.snap_enabled = false;
+        assert_eq!(grid.snap(1.3), 1.3);
+    }
+
+    #[test]
+    fn test_drag_records_snapped_transform() {
+        let grid = GridSettings {
+            snap_enabled: true,
+            increment: 0.5,
+            ..Default::default()
+        };
+        let mut transform = Transform::default();
+        let mut drag = TransformDrag::begin(7, &transform);
+
+        // Several small mouse moves adding up to 1.3
+        for _ in 0..13 {
+            transform.position = drag.translate(Vec3::new(0.1, 0.0, 0.0), &grid);
+        }
+        assert!((transform.position.x - 1.5).abs() < 1e-6);
+
+        match drag.finish(&transform) {
+            Some(EditorAction::ModifyTransform {
+                object_id,
+                before,
+                after,
+            }) => {
+                assert_eq!(object_id, 7);
+                assert_eq!(before.position.x, 0.0);
+                assert!((after.position.x - 1.5).abs() < 1e-6);
+            }
+            other => panic!("Expected a ModifyTransform action, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_gizmo_drag_updates_transform_and_records_undo() {
+        let grid = GridSettings::default();
+        let mut cube = GameObject::new(3, "Cube".to_string(), GameObjectType::Cube);
+        cube.rigid_body.as_mut().unwrap().velocity = Vec3::new(0.0, -4.0, 0.0);
+        let mut undo = UndoStack::new();
+
+        // 100 pixels right and 50 up at 0.02 world units per pixel
+        let mut drag = TransformDrag::begin(cube.id, &cube.transform);
+        drag.drag(&mut cube, GizmoMode::Translate, (100.0, -50.0), 0.02, &grid);
+        let moved = cube.transform.clone();
+        assert!((moved.position - Vec3::new(2.0, 1.0, 0.0)).length() < 1e-5);
+        assert_eq!(cube.rigid_body.as_ref().unwrap().velocity, Vec3::zero());
+        undo.push(drag.finish(&cube.transform).unwrap());
+
+        let mut drag = TransformDrag::begin(cube.id, &cube.transform);
+        drag.drag(&mut cube, GizmoMode::Rotate, (60.0, 0.0), 0.02, &grid);
+        let turned = cube.transform.clone();
+        assert_eq!(turned.rotation, Vec3::new(0.0, 30.0, 0.0));
+        let orientation = cube.rigid_body.as_ref().unwrap().orientation;
+        assert!((orientation.to_euler_degrees() - turned.rotation).length() < 1e-3);
+        undo.push(drag.finish(&cube.transform).unwrap());
+
+        // Two frames of 50 pixels compound
+        let mut drag = TransformDrag::begin(cube.id, &cube.transform);
+        drag.drag(&mut cube, GizmoMode::Scale, (50.0, 0.0), 0.02, &grid);
+        drag.drag(&mut cube, GizmoMode::Scale, (50.0, 0.0), 0.02, &grid);
+        assert!((cube.transform.scale - Vec3::new(2.25, 2.25, 2.25)).length() < 1e-5);
+        undo.push(drag.finish(&cube.transform).unwrap());
+
+        let modified = |before: &Transform, after: &Transform| EditorAction::ModifyTransform {
+            object_id: 3,
+            before: before.clone(),
+            after: after.clone(),
+        };
+        assert_eq!(
+            undo.actions(),
+            &[
+                modified(&Transform::default(), &moved),
+                modified(&moved, &turned),
+                modified(&turned, &cube.transform),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_snapped_drag_records_the_snapped_position() {
+        let grid = GridSettings {
+            snap_enabled: true,
+            increment: 0.5,
+            ..Default::default()
+        };
+        let mut sphere = GameObject::new(2, "Sphere".to_string(), GameObjectType::Sphere);
+
+        // 123 pixels at 0.01 world units per pixel is a drag to 1.23
+        let mut drag = TransformDrag::begin(sphere.id, &sphere.transform);
+        drag.drag(&mut sphere, GizmoMode::Translate, (123.0, 0.0), 0.01, &grid);
+        assert_eq!(sphere.transform.position.x, 1.0);
+
+        match drag.finish(&sphere.transform) {
+            Some(EditorAction::ModifyTransform { after, .. }) => {
+                assert_eq!(after.position, Vec3::new(1.0, 0.0, 0.0));
+            }
+            other => panic!("Expected a ModifyTransform action, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_drag_without_change_records_nothing() {
+        let transform = Transform::default();
+        let drag = TransformDrag::begin(1, &transform);
+        assert!(drag.finish(&transform).is_none());
+    }
+}
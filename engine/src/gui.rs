@@ -3,9 +3,25 @@
 
 #![allow(dead_code)] // Allow dead code for development - these will be used in future features
 
+use crate::autosave::Autosave;
+use crate::clipboard::Clipboard;
+use crate::console_commands::{format_kv, format_table, Completion, ConsoleInput};
+use crate::debug_overlay::DebugOverlay;
+use crate::grid::{GridSettings, TransformDrag};
+use crate::playback::PlaybackControls;
+use crate::scene_file;
+use crate::selection::Selection;
+use crate::undo::{EditorAction, UndoStack};
 use eframe::egui;
 use egui_dock::{DockArea, DockState, NodeIndex, TabViewer};
+use matrix_lang::stdlib::{
+    world_statuses, BodyDescriptor, DebugShape, PhysicsSettings, QualityPreset,
+};
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::io;
+use std::path::Path;
+use std::time::Duration;
 
 /// Temporary simple scripting panel for Matrix Language integration
 #[derive(Debug, Clone)]
@@ -95,6 +111,7 @@ pub enum DockTab {
     SceneView,
     MatrixScript,
     PhysicsAnimation,
+    Performance,
 }
 
 impl std::fmt::Display for DockTab {
@@ -106,6 +123,7 @@ impl std::fmt::Display for DockTab {
             DockTab::SceneView => write!(f, "Scene"),
             DockTab::MatrixScript => write!(f, "Matrix Script"),
             DockTab::PhysicsAnimation => write!(f, "Physics Animation"),
+            DockTab::Performance => write!(f, "Performance"),
         }
     }
 }
@@ -131,6 +149,7 @@ impl<'a> TabViewer for EditorTabViewer<'a> {
             DockTab::SceneView => self.app.show_scene_view_content(ui),
             DockTab::MatrixScript => self.app.show_matrix_script_content(ui),
             DockTab::PhysicsAnimation => self.app.show_physics_animation_content(ui),
+            DockTab::Performance => self.app.show_performance_content(ui),
         });
     }
 
@@ -208,7 +227,7 @@ impl<'a> TabViewer for EditorTabViewer<'a> {
             }
             DockTab::Inspector => {
                 if ui.button("Reset Transform").clicked() {
-                    if let Some(selected_id) = self.app.selected_object {
+                    for selected_id in self.app.selection.ids() {
                         if let Some(obj) = self.app.game_objects.get_mut(&selected_id) {
                             obj.transform = Transform::default();
                         }
@@ -236,12 +255,13 @@ impl<'a> TabViewer for EditorTabViewer<'a> {
                     ui.close_menu();
                 }
             }
+            DockTab::Performance => {}
         }
     }
 }
 
 /// 3D Vector for positions, rotations, scale
-#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub struct Vec3 {
     pub x: f32,
     pub y: f32,
@@ -306,6 +326,93 @@ impl std::ops::Mul<f32> for Vec3 {
     }
 }
 
+/// Unit quaternion for rigid body orientation
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Quaternion {
+    pub w: f32,
+    pub x: f32,
+    pub y: f32,
+    pub z: f32,
+}
+
+impl Quaternion {
+    pub fn identity() -> Self {
+        Self {
+            w: 1.0,
+            x: 0.0,
+            y: 0.0,
+            z: 0.0,
+        }
+    }
+
+    /// Rotation by `angle` radians about a unit `axis`
+    pub fn from_axis_angle(axis: Vec3, angle: f32) -> Self {
+        let (sin, cos) = (angle / 2.0).sin_cos();
+        Self {
+            w: cos,
+            x: axis.x * sin,
+            y: axis.y * sin,
+            z: axis.z * sin,
+        }
+    }
+
+    /// Orientation from Euler angles in degrees, applied about X, then Y, then Z
+    pub fn from_euler_degrees(degrees: Vec3) -> Self {
+        let (sx, cx) = (degrees.x.to_radians() / 2.0).sin_cos();
+        let (sy, cy) = (degrees.y.to_radians() / 2.0).sin_cos();
+        let (sz, cz) = (degrees.z.to_radians() / 2.0).sin_cos();
+        Self {
+            w: cx * cy * cz + sx * sy * sz,
+            x: sx * cy * cz - cx * sy * sz,
+            y: cx * sy * cz + sx * cy * sz,
+            z: cx * cy * sz - sx * sy * cz,
+        }
+    }
+
+    /// Euler angles in degrees, in the convention of `from_euler_degrees`
+    pub fn to_euler_degrees(&self) -> Vec3 {
+        let Self { w, x, y, z } = *self;
+        let roll = (2.0 * (w * x + y * z)).atan2(1.0 - 2.0 * (x * x + y * y));
+        let pitch = (2.0 * (w * y - z * x)).clamp(-1.0, 1.0).asin();
+        let yaw = (2.0 * (w * z + x * y)).atan2(1.0 - 2.0 * (y * y + z * z));
+        Vec3::new(roll.to_degrees(), pitch.to_degrees(), yaw.to_degrees())
+    }
+
+    /// Rescale to unit length, undoing drift from repeated products
+    pub fn normalize(&self) -> Self {
+        let length = (self.w * self.w + self.x * self.x + self.y * self.y + self.z * self.z).sqrt();
+        if length > 0.0 {
+            Self {
+                w: self.w / length,
+                x: self.x / length,
+                y: self.y / length,
+                z: self.z / length,
+            }
+        } else {
+            Self::identity()
+        }
+    }
+}
+
+impl Default for Quaternion {
+    fn default() -> Self {
+        Self::identity()
+    }
+}
+
+impl std::ops::Mul for Quaternion {
+    type Output = Quaternion;
+    /// Rotation by `other` followed by `self`
+    fn mul(self, other: Quaternion) -> Quaternion {
+        Quaternion {
+            w: self.w * other.w - self.x * other.x - self.y * other.y - self.z * other.z,
+            x: self.w * other.x + self.x * other.w + self.y * other.z - self.z * other.y,
+            y: self.w * other.y - self.x * other.z + self.y * other.w + self.z * other.x,
+            z: self.w * other.z + self.x * other.y - self.y * other.x + self.z * other.w,
+        }
+    }
+}
+
 /// Camera component for 3D rendering
 #[derive(Debug, Clone)]
 pub struct Camera {
@@ -331,7 +438,7 @@ impl Default for Camera {
 }
 
 /// Transform component with position, rotation, and scale
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Transform {
     pub position: Vec3,
     pub rotation: Vec3, // Euler angles in degrees
@@ -349,7 +456,7 @@ impl Default for Transform {
 }
 
 /// Different types of game objects
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum GameObjectType {
     Cube,
     Sphere,
@@ -361,13 +468,26 @@ pub enum GameObjectType {
 }
 
 /// Rigid body physics component
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct RigidBody {
     pub mass: f32,
     pub velocity: Vec3,
     pub angular_velocity: Vec3,
     pub is_kinematic: bool,
     pub use_gravity: bool,
+    /// Integrated orientation, written to the transform rotation each step
+    #[serde(default)]
+    pub orientation: Quaternion,
+    /// Torque accumulated for the next step, then cleared
+    #[serde(default)]
+    pub torque: Vec3,
+}
+
+impl RigidBody {
+    /// Add a torque to be applied during the next physics step
+    pub fn apply_torque(&mut self, torque: Vec3) {
+        self.torque = self.torque + torque;
+    }
 }
 
 impl Default for RigidBody {
@@ -378,12 +498,14 @@ impl Default for RigidBody {
             angular_velocity: Vec3::zero(),
             is_kinematic: false,
             use_gravity: true,
+            orientation: Quaternion::identity(),
+            torque: Vec3::zero(),
         }
     }
 }
 
 /// Game object with components
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct GameObject {
     pub id: usize,
     pub name: String,
@@ -428,6 +550,11 @@ pub struct PhysicsWorld {
     pub gravity: Vec3,
     pub time_step: f32,
     pub is_paused: bool,
+    pub quality: QualityPreset,
+    /// Number of simulation steps taken
+    pub frame: u64,
+    /// Simulated seconds accumulated by `step`
+    pub sim_time: f64,
 }
 
 impl Default for PhysicsWorld {
@@ -436,6 +563,78 @@ impl Default for PhysicsWorld {
             gravity: Vec3::new(0.0, -9.81, 0.0),
             time_step: 1.0 / 60.0,
             is_paused: true,
+            quality: QualityPreset::Balanced,
+            frame: 0,
+            sim_time: 0.0,
+        }
+    }
+}
+
+impl PhysicsWorld {
+    /// Paused at frame zero, keeping gravity, time step and quality
+    pub fn reset(&mut self) {
+        self.is_paused = true;
+        self.frame = 0;
+        self.sim_time = 0.0;
+    }
+
+    /// Advance rigid bodies by one time step, resting them on the ground plane. The
+    /// step is split into the substeps of the world's quality preset.
+    pub fn step(&mut self, objects: &mut HashMap<usize, GameObject>) {
+        let substeps = PhysicsSettings::preset(self.quality).substeps.max(1);
+        let h = self.time_step / substeps as f32;
+        for _ in 0..substeps {
+            self.substep(objects, h);
+        }
+        // Torque applied before the step acts over all of its substeps
+        for body in objects
+            .values_mut()
+            .filter_map(|obj| obj.rigid_body.as_mut())
+        {
+            body.torque = Vec3::zero();
+        }
+        self.frame += 1;
+        self.sim_time += self.time_step as f64;
+    }
+
+    fn substep(&self, objects: &mut HashMap<usize, GameObject>, dt: f32) {
+        for obj in objects.values_mut() {
+            let Some(body) = obj.rigid_body.as_mut() else {
+                continue;
+            };
+            if body.is_kinematic {
+                continue;
+            }
+
+            if body.use_gravity {
+                body.velocity = body.velocity + self.gravity * dt;
+            }
+            obj.transform.position = obj.transform.position + body.velocity * dt;
+
+            if obj.transform.position.y < 0.0 {
+                obj.transform.position.y = 0.0;
+                body.velocity.y = 0.0;
+            }
+
+            // Torque turns the body with unit inertia per unit mass
+            if body.mass > 0.0 {
+                body.angular_velocity = body.angular_velocity + body.torque * (dt / body.mass);
+            }
+
+            // Rotation edited in the inspector since the last step takes over
+            let synced = body.orientation.to_euler_degrees();
+            let edited = obj.transform.rotation - synced;
+            if edited.length() > 1e-3 {
+                body.orientation = Quaternion::from_euler_degrees(obj.transform.rotation);
+            }
+
+            let speed = body.angular_velocity.length();
+            if speed > 0.0 {
+                let turn =
+                    Quaternion::from_axis_angle(body.angular_velocity * (1.0 / speed), speed * dt);
+                body.orientation = (turn * body.orientation).normalize();
+                obj.transform.rotation = body.orientation.to_euler_degrees();
+            }
         }
     }
 }
@@ -501,8 +700,8 @@ pub struct PhysicsEditorApp {
     is_playing: bool,
     /// Console messages
     console_messages: Vec<String>,
-    /// Selected object ID
-    selected_object: Option<usize>,
+    /// Selected objects; the primary one is shown in the inspector
+    selection: Selection,
     /// Scene objects
     game_objects: HashMap<usize, GameObject>,
     next_id: usize,
@@ -522,6 +721,36 @@ pub struct PhysicsEditorApp {
     physics_plotter: PhysicsPlotter,
     /// IPC manager for Matrix Language communication
     ipc_manager: crate::ipc::IpcManager,
+    /// Console command line with history and tab completion
+    console_input: ConsoleInput,
+    /// Scene view grid and snap settings
+    grid: GridSettings,
+    /// Gizmo drag in progress on the selected object
+    gizmo_drag: Option<TransformDrag>,
+    /// Corners of a Shift-drag selection box in the scene view, in screen space
+    box_select: Option<(egui::Pos2, egui::Pos2)>,
+    /// Objects copied with Ctrl+C, pasted as duplicates with Ctrl+V
+    clipboard: Clipboard,
+    /// Scene view play/pause/step, separate from the toolbar play toggle
+    scene_playback: PlaybackControls,
+    /// Physics debug drawing (bounds, contacts, velocities) in the scene view
+    debug_overlay: DebugOverlay,
+    /// Undo/redo history
+    undo_stack: UndoStack,
+    /// Periodic scene saves for crash recovery
+    autosave: Autosave,
+    /// A recovery file from a crashed session is waiting to be restored or discarded
+    recovery_prompt: bool,
+    /// File the scene is saved to and opened from
+    scene_path: String,
+    /// Whether the Open Scene dialog is visible
+    show_open_scene: bool,
+    /// Name of the selected object when its name field was focused
+    rename_start: Option<String>,
+    /// Rigid body settings when an inspector field edit began
+    component_edit_start: Option<RigidBody>,
+    /// Transforms of the selected objects when an inspector transform edit began
+    transform_edit_start: Option<Vec<(usize, Transform)>>,
 }
 
 impl PhysicsEditorApp {
@@ -545,7 +774,11 @@ impl PhysicsEditorApp {
         dock_state.main_surface_mut().split_right(
             NodeIndex::root(),
             0.75,
-            vec![DockTab::MatrixScript, DockTab::PhysicsAnimation],
+            vec![
+                DockTab::MatrixScript,
+                DockTab::PhysicsAnimation,
+                DockTab::Performance,
+            ],
         );
 
         let mut app = Self {
@@ -557,7 +790,7 @@ impl PhysicsEditorApp {
             show_preferences: false,
             is_playing: false,
             console_messages: vec!["3D Physics Editor Started".to_string()],
-            selected_object: None,
+            selection: Selection::new(),
             game_objects: HashMap::new(),
             next_id: 1,
             gizmo_mode: GizmoMode::Translate,
@@ -568,7 +801,23 @@ impl PhysicsEditorApp {
             animation_data: PhysicsAnimationData::default(),
             physics_plotter: PhysicsPlotter::default(),
             ipc_manager: crate::ipc::IpcManager::new(),
+            console_input: ConsoleInput::new(),
+            grid: GridSettings::default(),
+            gizmo_drag: None,
+            box_select: None,
+            clipboard: Clipboard::new(),
+            scene_playback: PlaybackControls::default(),
+            debug_overlay: DebugOverlay::default(),
+            undo_stack: UndoStack::new(),
+            autosave: Autosave::new(Autosave::default_path()),
+            recovery_prompt: false,
+            scene_path: scene_file::default_path().display().to_string(),
+            show_open_scene: false,
+            rename_start: None,
+            component_edit_start: None,
+            transform_edit_start: None,
         };
+        app.recovery_prompt = app.autosave.has_pending_recovery();
 
         // Create default scene objects
         app.create_default_scene();
@@ -611,6 +860,71 @@ impl PhysicsEditorApp {
         );
     }
 
+    /// Scene objects in id order, as written to the autosave recovery file
+    fn scene_snapshot(&self) -> Vec<GameObject> {
+        let mut objects: Vec<GameObject> = self.game_objects.values().cloned().collect();
+        objects.sort_by_key(|obj| obj.id);
+        objects
+    }
+
+    /// Replace the scene with recovered objects; history refers to the old scene so it is dropped
+    fn restore_scene(&mut self, objects: Vec<GameObject>) {
+        self.next_id = objects.iter().map(|obj| obj.id + 1).max().unwrap_or(1);
+        self.game_objects = objects.into_iter().map(|obj| (obj.id, obj)).collect();
+        self.selection.clear();
+        self.gizmo_drag = None;
+        self.undo_stack = UndoStack::new();
+    }
+
+    /// Replace the scene with one saved on disk and restart the simulation from it
+    fn open_scene(&mut self, path: &Path) -> io::Result<()> {
+        let objects = scene_file::load_scene::<Vec<GameObject>>(path)?;
+        self.restore_scene(objects);
+        self.physics_world.reset();
+        self.scene_playback.pause();
+        self.is_playing = false;
+        Ok(())
+    }
+
+    /// Delete every selected object as a single undo step
+    fn delete_selected(&mut self) {
+        let count = self.selection.len();
+        if let Some(action) = self.selection.delete_from(&mut self.game_objects) {
+            self.undo_stack.push(action);
+            self.add_console_message(format!("Deleted {} object(s)", count));
+        }
+    }
+
+    /// Copy the selected objects. Their names also go to the system clipboard, which
+    /// lets the platform report the following Ctrl+V as a paste.
+    fn copy_selected(&mut self, ctx: &egui::Context) {
+        let ids = self.selection.ids();
+        self.clipboard.copy(&self.game_objects, &ids);
+        let names: Vec<&str> = ids
+            .iter()
+            .filter_map(|id| self.game_objects.get(id))
+            .map(|obj| obj.name.as_str())
+            .collect();
+        let count = names.len();
+        if count > 0 {
+            ctx.copy_text(names.join("\n"));
+            self.add_console_message(format!("Copied {} object(s)", count));
+        }
+    }
+
+    /// Paste duplicates of the copied objects and select them
+    fn paste(&mut self) {
+        if let Some((ids, action)) =
+            self.clipboard
+                .paste(&mut self.game_objects, &mut self.next_id, &self.grid)
+        {
+            self.undo_stack.push(action);
+            self.selection.clear();
+            self.add_console_message(format!("Pasted {} object(s)", ids.len()));
+            self.selection.extend(ids);
+        }
+    }
+
     fn create_object(&mut self, object_type: GameObjectType, base_name: String) {
         let mut name = base_name.clone();
         let mut counter = 1;
@@ -619,9 +933,10 @@ impl PhysicsEditorApp {
             counter += 1;
         }
 
-        let obj = GameObject::new(self.next_id, name.clone(), object_type);
+        let mut obj = GameObject::new(self.next_id, name.clone(), object_type);
+        obj.transform.position = self.grid.snap_position(obj.transform.position);
         self.game_objects.insert(self.next_id, obj);
-        self.selected_object = Some(self.next_id);
+        self.selection.select(self.next_id);
         self.next_id += 1;
 
         self.add_console_message(format!("Created {}", name));
@@ -634,6 +949,46 @@ impl PhysicsEditorApp {
         }
     }
 
+    /// Print rows as a table with aligned columns
+    fn log_table(&mut self, headers: &[&str], rows: &[Vec<String>]) {
+        for line in format_table(headers, rows) {
+            self.add_console_message(line);
+        }
+    }
+
+    /// Print `key: value` pairs with the values lined up
+    fn log_kv(&mut self, pairs: &[(&str, String)]) {
+        for line in format_kv(pairs) {
+            self.add_console_message(line);
+        }
+    }
+
+    /// Print an object's transform and each of its components
+    fn log_object(&mut self, id: usize) {
+        let Some(obj) = self.game_objects.get(&id) else {
+            return;
+        };
+        let mut pairs = vec![
+            ("ID", obj.id.to_string()),
+            ("Name", obj.name.clone()),
+            ("Type", format!("{:?}", obj.object_type)),
+            ("Position", format_vec3(obj.transform.position)),
+            ("Rotation", format_vec3(obj.transform.rotation)),
+            ("Scale", format_vec3(obj.transform.scale)),
+        ];
+        match &obj.rigid_body {
+            Some(body) => pairs.extend([
+                ("RigidBody mass", format!("{:.2}", body.mass)),
+                ("RigidBody velocity", format_vec3(body.velocity)),
+                ("RigidBody spin", format_vec3(body.angular_velocity)),
+                ("RigidBody gravity", body.use_gravity.to_string()),
+                ("RigidBody kinematic", body.is_kinematic.to_string()),
+            ]),
+            None => pairs.push(("Components", "none".to_string())),
+        }
+        self.log_kv(&pairs);
+    }
+
     /// Show hierarchy panel content
     fn show_hierarchy_content(&mut self, ui: &mut egui::Ui) {
         ui.push_id(format!("hierarchy_panel_{}", self.instance_id), |ui| {
@@ -645,10 +1000,11 @@ impl PhysicsEditorApp {
                 .show(ui, |ui| {
                     let mut to_select = None;
                     let mut to_delete = None;
+                    let toggle = ui.input(|i| i.modifiers.command);
 
                     for (&id, obj) in &self.game_objects {
                         ui.horizontal(|ui| {
-                            let selected = self.selected_object == Some(id);
+                            let selected = self.selection.contains(id);
                             if ui.selectable_label(selected, &obj.name).clicked() {
                                 to_select = Some(id);
                             }
@@ -660,15 +1016,19 @@ impl PhysicsEditorApp {
                     }
 
                     if let Some(id) = to_select {
-                        self.selected_object = Some(id);
+                        if toggle {
+                            self.selection.toggle(id);
+                        } else {
+                            self.selection.select(id);
+                        }
                     }
 
+                    // Deleting part of the selection deletes all of it
                     if let Some(id) = to_delete {
-                        self.game_objects.remove(&id);
-                        if self.selected_object == Some(id) {
-                            self.selected_object = None;
+                        if !self.selection.contains(id) {
+                            self.selection.select(id);
                         }
-                        self.add_console_message(format!("Deleted object with ID {}", id));
+                        self.delete_selected();
                     }
                 });
         });
@@ -680,111 +1040,247 @@ impl PhysicsEditorApp {
             ui.heading("Inspector");
             ui.separator();
 
-            if let Some(selected_id) = self.selected_object {
+            if let Some(selected_id) = self.selection.primary() {
+                let grid = &self.grid;
+                let mut performed = Vec::new();
+                let mut transform_edit = None;
+                let mut transform_edit_started = None;
+                let mut transform_edit_ended = false;
+                if self.selection.len() > 1 {
+                    ui.label(format!("{} objects selected", self.selection.len()));
+                }
                 if let Some(obj) = self.game_objects.get_mut(&selected_id) {
+                    let transform_before = obj.transform.clone();
                     ui.horizontal(|ui| {
                         ui.label("Name:");
-                        ui.text_edit_singleline(&mut obj.name);
+                        let response = ui.text_edit_singleline(&mut obj.name);
+                        if response.gained_focus() {
+                            self.rename_start = Some(obj.name.clone());
+                        }
+                        if response.lost_focus() {
+                            if let Some(before) = self.rename_start.take() {
+                                if before != obj.name {
+                                    performed.push(EditorAction::RenameObject {
+                                        object_id: selected_id,
+                                        before,
+                                        after: obj.name.clone(),
+                                    });
+                                }
+                            }
+                        }
                     });
 
                     ui.separator();
                     ui.label("Transform");
 
-                    ui.horizontal(|ui| {
-                        ui.label("Position:");
-                        ui.add(
-                            egui::DragValue::new(&mut obj.transform.position.x)
-                                .speed(0.1)
-                                .prefix("X: "),
-                        );
-                        ui.add(
-                            egui::DragValue::new(&mut obj.transform.position.y)
-                                .speed(0.1)
-                                .prefix("Y: "),
-                        );
-                        ui.add(
-                            egui::DragValue::new(&mut obj.transform.position.z)
-                                .speed(0.1)
-                                .prefix("Z: "),
-                        );
-                    });
+                    // Step a whole grid increment per pixel while snapping
+                    let position_speed = if grid.snap_enabled {
+                        grid.increment as f64
+                    } else {
+                        0.1
+                    };
+                    let position = ui
+                        .horizontal(|ui| {
+                            ui.label("Position:");
+                            let x = ui.add(
+                                egui::DragValue::new(&mut obj.transform.position.x)
+                                    .speed(position_speed)
+                                    .prefix("X: "),
+                            );
+                            let y = ui.add(
+                                egui::DragValue::new(&mut obj.transform.position.y)
+                                    .speed(position_speed)
+                                    .prefix("Y: "),
+                            );
+                            let z = ui.add(
+                                egui::DragValue::new(&mut obj.transform.position.z)
+                                    .speed(position_speed)
+                                    .prefix("Z: "),
+                            );
+                            [x, y, z]
+                        })
+                        .inner;
+                    if position.iter().any(|r| r.changed()) {
+                        obj.transform.position = grid.snap_position(obj.transform.position);
+                    }
+                    let mut fields = Vec::from(position);
 
                     ui.horizontal(|ui| {
                         ui.label("Rotation:");
-                        ui.add(
-                            egui::DragValue::new(&mut obj.transform.rotation.x)
-                                .speed(1.0)
-                                .prefix("X: "),
+                        fields.push(
+                            ui.add(
+                                egui::DragValue::new(&mut obj.transform.rotation.x)
+                                    .speed(1.0)
+                                    .prefix("X: "),
+                            ),
                         );
-                        ui.add(
-                            egui::DragValue::new(&mut obj.transform.rotation.y)
-                                .speed(1.0)
-                                .prefix("Y: "),
+                        fields.push(
+                            ui.add(
+                                egui::DragValue::new(&mut obj.transform.rotation.y)
+                                    .speed(1.0)
+                                    .prefix("Y: "),
+                            ),
                         );
-                        ui.add(
-                            egui::DragValue::new(&mut obj.transform.rotation.z)
-                                .speed(1.0)
-                                .prefix("Z: "),
+                        fields.push(
+                            ui.add(
+                                egui::DragValue::new(&mut obj.transform.rotation.z)
+                                    .speed(1.0)
+                                    .prefix("Z: "),
+                            ),
                         );
                     });
 
                     ui.horizontal(|ui| {
                         ui.label("Scale:");
-                        ui.add(
-                            egui::DragValue::new(&mut obj.transform.scale.x)
-                                .speed(0.01)
-                                .prefix("X: "),
+                        fields.push(
+                            ui.add(
+                                egui::DragValue::new(&mut obj.transform.scale.x)
+                                    .speed(0.01)
+                                    .prefix("X: "),
+                            ),
                         );
-                        ui.add(
-                            egui::DragValue::new(&mut obj.transform.scale.y)
-                                .speed(0.01)
-                                .prefix("Y: "),
+                        fields.push(
+                            ui.add(
+                                egui::DragValue::new(&mut obj.transform.scale.y)
+                                    .speed(0.01)
+                                    .prefix("Y: "),
+                            ),
                         );
-                        ui.add(
-                            egui::DragValue::new(&mut obj.transform.scale.z)
-                                .speed(0.01)
-                                .prefix("Z: "),
+                        fields.push(
+                            ui.add(
+                                egui::DragValue::new(&mut obj.transform.scale.z)
+                                    .speed(0.01)
+                                    .prefix("Z: "),
+                            ),
                         );
                     });
+                    // Drags and typed values are recorded once, when the edit ends
+                    if fields.iter().any(|r| r.drag_started() || r.gained_focus()) {
+                        transform_edit_started = Some(transform_before.clone());
+                    }
+                    transform_edit_ended =
+                        fields.iter().any(|r| r.drag_stopped() || r.lost_focus());
+                    if obj.transform != transform_before {
+                        transform_edit = Some((transform_before, obj.transform.clone()));
+                    }
 
+                    let mut remove_body = false;
                     if let Some(rigid_body) = &mut obj.rigid_body {
+                        let before = rigid_body.clone();
                         ui.separator();
-                        ui.label("RigidBody");
+                        ui.horizontal(|ui| {
+                            ui.label("RigidBody");
+                            remove_body = ui.small_button("Remove").clicked();
+                        });
 
+                        let mut fields = Vec::new();
                         ui.horizontal(|ui| {
                             ui.label("Mass:");
-                            ui.add(
-                                egui::DragValue::new(&mut rigid_body.mass)
-                                    .speed(0.1)
-                                    .range(0.1..=100.0),
+                            fields.push(
+                                ui.add(
+                                    egui::DragValue::new(&mut rigid_body.mass)
+                                        .speed(0.1)
+                                        .range(0.1..=100.0),
+                                ),
                             );
                         });
 
                         ui.horizontal(|ui| {
                             ui.label("Velocity:");
-                            ui.add(
-                                egui::DragValue::new(&mut rigid_body.velocity.x)
-                                    .speed(0.1)
-                                    .prefix("X: "),
+                            fields.push(
+                                ui.add(
+                                    egui::DragValue::new(&mut rigid_body.velocity.x)
+                                        .speed(0.1)
+                                        .prefix("X: "),
+                                ),
                             );
-                            ui.add(
-                                egui::DragValue::new(&mut rigid_body.velocity.y)
-                                    .speed(0.1)
-                                    .prefix("Y: "),
+                            fields.push(
+                                ui.add(
+                                    egui::DragValue::new(&mut rigid_body.velocity.y)
+                                        .speed(0.1)
+                                        .prefix("Y: "),
+                                ),
                             );
-                            ui.add(
-                                egui::DragValue::new(&mut rigid_body.velocity.z)
-                                    .speed(0.1)
-                                    .prefix("Z: "),
+                            fields.push(
+                                ui.add(
+                                    egui::DragValue::new(&mut rigid_body.velocity.z)
+                                        .speed(0.1)
+                                        .prefix("Z: "),
+                                ),
                             );
                         });
 
-                        ui.checkbox(&mut rigid_body.use_gravity, "Use Gravity");
-                        ui.checkbox(&mut rigid_body.is_kinematic, "Is Kinematic");
+                        let gravity = ui.checkbox(&mut rigid_body.use_gravity, "Use Gravity");
+                        let kinematic = ui.checkbox(&mut rigid_body.is_kinematic, "Is Kinematic");
+
+                        // Drags and typed values are recorded once, when the edit ends
+                        if fields.iter().any(|r| r.drag_started() || r.gained_focus()) {
+                            self.component_edit_start = Some(before.clone());
+                        }
+                        let edit_ended = fields.iter().any(|r| r.drag_stopped() || r.lost_focus());
+                        let edit_before = if gravity.changed() || kinematic.changed() {
+                            Some(before)
+                        } else if edit_ended {
+                            self.component_edit_start.take()
+                        } else {
+                            None
+                        };
+                        if let Some(before) = edit_before.filter(|b| b != rigid_body) {
+                            performed.push(EditorAction::ModifyComponent {
+                                object_id: selected_id,
+                                before,
+                                after: rigid_body.clone(),
+                            });
+                        }
+                    } else {
+                        ui.separator();
+                        if ui.button("Add RigidBody").clicked() {
+                            let component = RigidBody::default();
+                            obj.rigid_body = Some(component.clone());
+                            performed.push(EditorAction::AddComponent {
+                                object_id: selected_id,
+                                component,
+                            });
+                        }
+                    }
+
+                    if remove_body {
+                        if let Some(component) = obj.rigid_body.take() {
+                            performed.push(EditorAction::RemoveComponent {
+                                object_id: selected_id,
+                                component,
+                            });
+                        }
                     }
                 } else {
                     ui.label("Selected object not found");
                 }
+                if let Some(primary_before) = transform_edit_started {
+                    let mut start = self.selection.transforms(&self.game_objects);
+                    for (id, transform) in &mut start {
+                        if *id == selected_id {
+                            *transform = primary_before.clone();
+                        }
+                    }
+                    self.transform_edit_start = Some(start);
+                }
+                // Transform edits apply to the whole selection
+                if let Some((before, after)) = transform_edit {
+                    self.selection
+                        .apply_to_others(&mut self.game_objects, &before, &after);
+                }
+                if transform_edit_ended {
+                    let edit = self
+                        .transform_edit_start
+                        .take()
+                        .and_then(|start| Selection::transform_changes(start, &self.game_objects));
+                    if let Some(action) = edit {
+                        performed.push(action);
+                    }
+                }
+                for action in performed {
+                    self.undo_stack.push(action);
+                }
             } else {
                 ui.label("No object selected");
             }
@@ -799,12 +1295,18 @@ impl PhysicsEditorApp {
 
             egui::ScrollArea::vertical()
                 .id_salt(format!("console_scroll_{}", self.instance_id))
+                .max_height(ui.available_height() - 60.0)
+                .stick_to_bottom(true)
                 .show(ui, |ui| {
+                    // Monospace keeps table columns aligned
                     for message in &self.console_messages {
-                        ui.label(message);
+                        ui.label(egui::RichText::new(message).monospace());
                     }
                 });
 
+            ui.separator();
+            self.show_console_input(ui);
+
             ui.horizontal(|ui| {
                 if ui.button("Clear").clicked() {
                     self.console_messages.clear();
@@ -816,11 +1318,193 @@ impl PhysicsEditorApp {
         });
     }
 
+    /// Show the console command line with history recall and tab completion
+    fn show_console_input(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.label(">");
+            let response = ui.add(
+                egui::TextEdit::singleline(&mut self.console_input.buffer)
+                    .id_salt(format!("console_input_{}", self.instance_id))
+                    .font(egui::TextStyle::Monospace)
+                    .lock_focus(true)
+                    .desired_width(f32::INFINITY),
+            );
+
+            if response.has_focus() {
+                if ui.input_mut(|i| i.consume_key(egui::Modifiers::NONE, egui::Key::ArrowUp)) {
+                    self.console_input.history_previous();
+                }
+                if ui.input_mut(|i| i.consume_key(egui::Modifiers::NONE, egui::Key::ArrowDown)) {
+                    self.console_input.history_next();
+                }
+                if ui.input_mut(|i| i.consume_key(egui::Modifiers::NONE, egui::Key::Tab)) {
+                    if let Completion::Ambiguous(candidates) = self.console_input.complete() {
+                        self.add_console_message(candidates.join("  "));
+                    }
+                }
+            }
+
+            if response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter)) {
+                if let Some(command) = self.console_input.submit() {
+                    self.execute_console_command(&command);
+                }
+                response.request_focus();
+            }
+        });
+    }
+
+    /// Execute a command entered in the console
+    fn execute_console_command(&mut self, command: &str) {
+        self.add_console_message(format!("> {}", command));
+
+        let mut parts = command.split_whitespace();
+        match parts.next() {
+            Some("help") => {
+                let commands = self.console_input.commands().join(", ");
+                self.add_console_message(format!("Available commands: {}", commands));
+            }
+            Some("clear") => self.console_messages.clear(),
+            Some("spawn") => match parts.next().and_then(object_type_from_name) {
+                Some((object_type, name)) => self.create_object(object_type, name.to_string()),
+                None => self.add_console_message(format!(
+                    "Usage: spawn <{}>",
+                    crate::console_commands::SPAWNABLE_TYPES.join("|")
+                )),
+            },
+            Some("delete") => {
+                if self.selection.is_empty() {
+                    self.add_console_message("No object selected".to_string());
+                } else {
+                    self.delete_selected();
+                }
+            }
+            Some("list") => {
+                let mut ids: Vec<usize> = self.game_objects.keys().copied().collect();
+                ids.sort();
+                let rows: Vec<Vec<String>> = ids
+                    .iter()
+                    .map(|id| {
+                        let obj = &self.game_objects[id];
+                        vec![
+                            id.to_string(),
+                            obj.name.clone(),
+                            format!("{:?}", obj.object_type),
+                        ]
+                    })
+                    .collect();
+                self.log_table(&["ID", "Name", "Type"], &rows);
+            }
+            Some("stats") => {
+                let world = &self.physics_world;
+                let pairs = [
+                    ("Objects", self.game_objects.len().to_string()),
+                    ("Frame", world.frame.to_string()),
+                    ("Sim time", format!("{:.2}s", world.sim_time)),
+                    ("Time step", format!("{:.4}s", world.time_step)),
+                    (
+                        "Gravity",
+                        format!(
+                            "({:.2}, {:.2}, {:.2})",
+                            world.gravity.x, world.gravity.y, world.gravity.z
+                        ),
+                    ),
+                    ("Quality", format!("{:?}", world.quality)),
+                    (
+                        "Simulation",
+                        if self.is_playing { "playing" } else { "paused" }.to_string(),
+                    ),
+                ];
+                self.log_kv(&pairs);
+            }
+            Some("inspect") => {
+                let target = parts.collect::<Vec<_>>().join(" ");
+                let found = target
+                    .parse::<usize>()
+                    .ok()
+                    .filter(|id| self.game_objects.contains_key(id))
+                    .or_else(|| {
+                        self.game_objects
+                            .values()
+                            .find(|obj| obj.name == target)
+                            .map(|obj| obj.id)
+                    });
+                match found {
+                    Some(id) => self.log_object(id),
+                    None if target.is_empty() => {
+                        self.add_console_message("Usage: inspect <id|name>".to_string())
+                    }
+                    None => self.add_console_message(format!(
+                        "Warning: no object with id or name '{}'",
+                        target
+                    )),
+                }
+            }
+            Some("select") => {
+                let name = parts.collect::<Vec<_>>().join(" ");
+                let found = self
+                    .game_objects
+                    .values()
+                    .find(|obj| obj.name == name)
+                    .map(|obj| obj.id);
+                match found {
+                    Some(id) => {
+                        self.selection.select(id);
+                        self.add_console_message(format!("Selected object: {}", name));
+                    }
+                    None => self.add_console_message(format!("No object named '{}'", name)),
+                }
+            }
+            Some("play") => {
+                self.is_playing = true;
+                self.add_console_message("Physics simulation started".to_string());
+            }
+            Some("pause") => {
+                self.is_playing = false;
+                self.add_console_message("Physics simulation paused".to_string());
+            }
+            Some(other) => {
+                self.add_console_message(format!("Unknown command: {}. Type 'help'", other))
+            }
+            None => {}
+        }
+    }
+
     /// Show scene view panel content
     fn show_scene_view_content(&mut self, ui: &mut egui::Ui) {
         ui.push_id(format!("scene_view_panel_{}", self.instance_id), |ui| {
             ui.horizontal(|ui| {
                 ui.heading("Scene View");
+                ui.separator();
+
+                // Simulation controls for frame-by-frame debugging
+                if self.scene_playback.is_simulating {
+                    if ui.button("⏸").on_hover_text("Pause").clicked() {
+                        self.scene_playback.pause();
+                    }
+                } else if ui.button("▶").on_hover_text("Simulate").clicked() {
+                    self.scene_playback.play();
+                }
+                if ui
+                    .add_enabled(!self.scene_playback.is_simulating, egui::Button::new("⏭"))
+                    .on_hover_text("Step one frame")
+                    .clicked()
+                {
+                    self.scene_playback.request_step();
+                }
+                ui.label(format!(
+                    "Frame {} ({:.2}s)",
+                    self.physics_world.frame, self.physics_world.sim_time
+                ));
+                ui.separator();
+
+                ui.menu_button("Debug", |ui| {
+                    ui.checkbox(&mut self.debug_overlay.show_aabbs, "AABBs");
+                    ui.checkbox(&mut self.debug_overlay.show_contacts, "Contacts");
+                    ui.checkbox(&mut self.debug_overlay.show_velocities, "Velocities");
+                    ui.separator();
+                    ui.checkbox(&mut self.debug_overlay.always_visible, "Show while paused");
+                });
+
                 ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
                     ui.label(format!(
                         "Camera: Orbit Distance {:.1}",
@@ -836,26 +1520,90 @@ impl PhysicsEditorApp {
                 egui::Sense::click_and_drag(),
             );
 
-            // Handle object selection by clicking
+            // Handle object selection by clicking; Ctrl-click adds or removes
+            let (shift, toggle) = ui.input(|i| (i.modifiers.shift, i.modifiers.command));
             if scene_response.clicked() {
                 if let Some(click_pos) = scene_response.interact_pointer_pos() {
                     if let Some(clicked_object_id) =
                         self.pick_object_at_screen_pos(click_pos, scene_response.rect)
                     {
-                        self.selected_object = Some(clicked_object_id);
+                        if toggle {
+                            self.selection.toggle(clicked_object_id);
+                        } else {
+                            self.selection.select(clicked_object_id);
+                        }
                         if let Some(obj) = self.game_objects.get(&clicked_object_id) {
                             self.add_console_message(format!("Selected object: {}", obj.name));
                         }
-                    } else {
-                        self.selected_object = None;
+                    } else if !toggle {
+                        self.selection.clear();
                         self.add_console_message("Deselected object".to_string());
                     }
                 }
             }
 
-            // Handle camera controls (only when not clicking objects)
-            if scene_response.dragged_by(egui::PointerButton::Primary) && !scene_response.clicked()
+            // Start a gizmo drag when grabbing the selected object, or a selection
+            // box when Shift-dragging anywhere else
+            if scene_response.drag_started_by(egui::PointerButton::Primary) {
+                if let Some(pointer) = scene_response.interact_pointer_pos() {
+                    self.gizmo_drag = self.selection.primary().and_then(|id| {
+                        let obj = self.game_objects.get(&id)?;
+                        let screen =
+                            self.world_to_screen(obj.transform.position, scene_response.rect)?;
+                        ((screen - pointer).length() < 40.0)
+                            .then(|| TransformDrag::begin(id, &obj.transform))
+                    });
+                    if self.gizmo_drag.is_none() && shift {
+                        self.box_select = Some((pointer, pointer));
+                    }
+                }
+            }
+
+            if scene_response.drag_stopped() {
+                if let Some((start, end)) = self.box_select.take() {
+                    let boxed = self.objects_in_screen_rect(
+                        egui::Rect::from_two_pos(start, end),
+                        scene_response.rect,
+                    );
+                    if !toggle {
+                        self.selection.clear();
+                    }
+                    self.add_console_message(format!("Selected {} object(s)", boxed.len()));
+                    self.selection.extend(boxed);
+                }
+            }
+
+            if scene_response.drag_stopped() {
+                if let Some(drag) = self.gizmo_drag.take() {
+                    let finished = self
+                        .game_objects
+                        .get(&drag.object_id)
+                        .and_then(|obj| drag.finish(&obj.transform));
+                    if let Some(action) = finished {
+                        self.undo_stack.push(action);
+                    }
+                }
+            }
+
+            if let Some(drag) = &mut self.gizmo_drag {
+                let delta = scene_response.drag_delta();
+                // Screen pixels to world units at the current zoom
+                let world_per_pixel = self.camera.orbit_distance * 0.005;
+                if let Some(obj) = self.game_objects.get_mut(&drag.object_id) {
+                    drag.drag(
+                        obj,
+                        self.gizmo_mode,
+                        (delta.x, delta.y),
+                        world_per_pixel,
+                        &self.grid,
+                    );
+                }
+            } else if let Some((_, end)) = &mut self.box_select {
+                *end += scene_response.drag_delta();
+            } else if scene_response.dragged_by(egui::PointerButton::Primary)
+                && !scene_response.clicked()
             {
+                // Handle camera controls (only when not dragging a gizmo)
                 let delta = scene_response.drag_delta();
                 self.camera.orbit_angle_x += delta.y * 0.01;
                 self.camera.orbit_angle_y += delta.x * 0.01;
@@ -876,6 +1624,14 @@ impl PhysicsEditorApp {
             // Draw 3D scene
             let painter = ui.painter_at(scene_response.rect);
             self.draw_3d_scene(&painter, scene_response.rect);
+            if let Some((start, end)) = self.box_select {
+                painter.rect_stroke(
+                    egui::Rect::from_two_pos(start, end),
+                    0.0,
+                    egui::Stroke::new(1.0, egui::Color32::LIGHT_BLUE),
+                    egui::StrokeKind::Inside,
+                );
+            }
         });
     }
 
@@ -887,6 +1643,46 @@ impl PhysicsEditorApp {
         });
     }
 
+    /// Show live statistics from the last step of every script-created physics world
+    fn show_performance_content(&mut self, ui: &mut egui::Ui) {
+        ui.push_id(format!("performance_panel_{}", self.instance_id), |ui| {
+            ui.heading("Performance");
+            ui.separator();
+
+            let worlds = world_statuses();
+            if worlds.is_empty() {
+                ui.label("No physics worlds (run a script that creates one)");
+            }
+            for world in worlds {
+                let stats = world.stats;
+                ui.collapsing(format!("World {}", world.id), |ui| {
+                    egui::Grid::new(format!("performance_world_{}", world.id))
+                        .num_columns(2)
+                        .striped(true)
+                        .show(ui, |ui| {
+                            let mut row = |label: &str, value: String| {
+                                ui.label(label);
+                                ui.label(value);
+                                ui.end_row();
+                            };
+                            row("Step time", format!("{:.3} ms", stats.step_time_ms));
+                            row("Active bodies", stats.active_bodies.to_string());
+                            row("Sleeping bodies", stats.sleeping_bodies.to_string());
+                            row("Pairs tested", stats.pairs_tested.to_string());
+                            row("Contacts", stats.contacts_resolved.to_string());
+                            row(
+                                "Constraint iterations",
+                                stats.constraint_iterations.to_string(),
+                            );
+                        });
+                });
+            }
+
+            // Worlds are stepped by scripts outside the UI's input events
+            ui.ctx().request_repaint_after(Duration::from_millis(250));
+        });
+    }
+
     /// Show Physics Animation panel content - matplotlib-like visualization
     fn show_physics_animation_content(&mut self, ui: &mut egui::Ui) {
         ui.push_id(
@@ -1216,6 +2012,25 @@ impl PhysicsEditorApp {
         self.add_console_message("📊 Generated sample physics animation data".to_string());
     }
 
+    /// Objects whose centers appear inside `screen_rect`, in ascending id order
+    fn objects_in_screen_rect(
+        &self,
+        screen_rect: egui::Rect,
+        scene_rect: egui::Rect,
+    ) -> Vec<usize> {
+        let mut ids: Vec<usize> = self
+            .game_objects
+            .values()
+            .filter(|obj| {
+                self.world_to_screen(obj.transform.position, scene_rect)
+                    .is_some_and(|pos| screen_rect.contains(pos))
+            })
+            .map(|obj| obj.id)
+            .collect();
+        ids.sort_unstable();
+        ids
+    }
+
     /// Pick object at screen position for selection
     fn pick_object_at_screen_pos(
         &self,
@@ -1263,25 +2078,112 @@ impl PhysicsEditorApp {
         painter.rect_filled(rect, 0.0, egui::Color32::from_gray(40));
 
         // Draw grid
-        self.draw_3d_grid(painter, rect);
+        if self.grid.visible {
+            self.draw_3d_grid(painter, rect);
+        }
 
         // Draw all GameObjects
         for (&id, obj) in &self.game_objects {
-            self.draw_3d_object(painter, rect, obj, id == self.selected_object.unwrap_or(0));
+            self.draw_3d_object(painter, rect, obj, self.selection.contains(id));
         }
 
-        // Draw gizmos for selected object
-        if let Some(selected_id) = self.selected_object {
+        // Draw gizmos for the primary selected object
+        if let Some(selected_id) = self.selection.primary() {
             if let Some(obj) = self.game_objects.get(&selected_id) {
                 self.draw_3d_gizmo(painter, rect, &obj.transform.position);
             }
         }
+
+        if self.debug_overlay.is_enabled() {
+            self.draw_debug_overlay(painter, rect);
+        }
+    }
+
+    /// Draw physics debug geometry for the scene's rigid bodies
+    fn draw_debug_overlay(&mut self, painter: &egui::Painter, rect: egui::Rect) {
+        let to_physics =
+            |v: Vec3| matrix_lang::stdlib::Vec3::new(v.x as f64, v.y as f64, v.z as f64);
+        let bodies = self
+            .game_objects
+            .values()
+            .filter_map(|obj| {
+                let shape = match obj.object_type {
+                    GameObjectType::Cube => "box",
+                    GameObjectType::Sphere => "sphere",
+                    _ => return None,
+                };
+                let rigid_body = obj.rigid_body.as_ref()?;
+                let descriptor = BodyDescriptor {
+                    shape: shape.to_string(),
+                    mass: rigid_body.mass as f64,
+                    position: to_physics(obj.transform.position),
+                    velocity: to_physics(rigid_body.velocity),
+                    half_extents: Some(to_physics(obj.transform.scale * 0.5)),
+                };
+                Some((obj.id, descriptor))
+            })
+            .collect();
+        self.debug_overlay.sync(bodies);
+
+        let to_screen = |v: matrix_lang::stdlib::Vec3| {
+            self.world_to_screen(Vec3::new(v.x as f32, v.y as f32, v.z as f32), rect)
+        };
+        for shape in self
+            .debug_overlay
+            .draw_list(self.scene_playback.is_simulating)
+        {
+            match shape {
+                DebugShape::Aabb { body, min, max } => {
+                    // The selected object's bounds stand out
+                    let selected = self
+                        .debug_overlay
+                        .object_of(body)
+                        .is_some_and(|id| self.selection.contains(id));
+                    let color = if selected {
+                        egui::Color32::YELLOW
+                    } else {
+                        egui::Color32::LIGHT_GREEN
+                    };
+                    let corner = |i: usize| {
+                        matrix_lang::stdlib::Vec3::new(
+                            if i & 1 == 0 { min.x } else { max.x },
+                            if i & 2 == 0 { min.y } else { max.y },
+                            if i & 4 == 0 { min.z } else { max.z },
+                        )
+                    };
+                    // Each edge joins two corners differing in exactly one axis bit
+                    for a in 0..8 {
+                        for bit in [1, 2, 4] {
+                            if a & bit == 0 {
+                                if let (Some(p1), Some(p2)) =
+                                    (to_screen(corner(a)), to_screen(corner(a | bit)))
+                                {
+                                    painter.line_segment([p1, p2], egui::Stroke::new(1.0, color));
+                                }
+                            }
+                        }
+                    }
+                }
+                DebugShape::ContactPoint { position } => {
+                    if let Some(pos) = to_screen(position) {
+                        painter.circle_filled(pos, 3.0, egui::Color32::RED);
+                    }
+                }
+                DebugShape::Line { from, to } => {
+                    if let (Some(p1), Some(p2)) = (to_screen(from), to_screen(to)) {
+                        painter
+                            .line_segment([p1, p2], egui::Stroke::new(1.5, egui::Color32::YELLOW));
+                    }
+                }
+            }
+        }
     }
 
     /// Draw a 3D grid on the ground plane
     fn draw_3d_grid(&self, painter: &egui::Painter, rect: egui::Rect) {
-        let grid_size = 20;
-        let grid_spacing = 1.0;
+        let grid_spacing = self.grid.increment.max(0.1);
+        // Cover roughly 20 world units either side, whatever the spacing
+        let grid_size = ((20.0 / grid_spacing) as i32).clamp(1, 80);
 
         for i in -grid_size..=grid_size {
             for j in -grid_size..=grid_size {
@@ -1549,11 +2451,80 @@ impl PhysicsEditorApp {
                 ui.menu_button("File", |ui| {
                     if ui.button("New Scene").clicked() {
                         self.game_objects.clear();
-                        self.selected_object = None;
+                        self.selection.clear();
                         self.create_default_scene();
                         self.add_console_message("New scene created".to_string());
                         ui.close_menu();
                     }
+                    if ui.button("Open Scene...").clicked() {
+                        self.show_open_scene = true;
+                        ui.close_menu();
+                    }
+                    if ui.button("Save Scene").clicked() {
+                        let path = Path::new(&self.scene_path);
+                        let message = match scene_file::save_scene(path, &self.scene_snapshot()) {
+                            Ok(()) => format!("Saved scene to {}", path.display()),
+                            Err(e) => format!("Could not save scene: {}", e),
+                        };
+                        self.add_console_message(message);
+                        ui.close_menu();
+                    }
+                });
+
+                ui.menu_button("Edit", |ui| {
+                    if ui
+                        .add_enabled(
+                            self.undo_stack.can_undo(),
+                            egui::Button::new("Undo (Ctrl+Z)"),
+                        )
+                        .clicked()
+                    {
+                        self.undo();
+                        ui.close_menu();
+                    }
+                    if ui
+                        .add_enabled(
+                            self.undo_stack.can_redo(),
+                            egui::Button::new("Redo (Ctrl+Y)"),
+                        )
+                        .clicked()
+                    {
+                        self.redo();
+                        ui.close_menu();
+                    }
+
+                    ui.separator();
+                    ui.checkbox(&mut self.grid.snap_enabled, "Snap to Grid");
+                    ui.horizontal(|ui| {
+                        ui.label("Snap Size:");
+                        ui.add(
+                            egui::DragValue::new(&mut self.grid.increment)
+                                .speed(0.05)
+                                .range(0.05..=10.0),
+                        );
+                    });
+
+                    ui.separator();
+                    if ui
+                        .add_enabled(
+                            !self.selection.is_empty(),
+                            egui::Button::new("Copy (Ctrl+C)"),
+                        )
+                        .clicked()
+                    {
+                        self.copy_selected(ui.ctx());
+                        ui.close_menu();
+                    }
+                    if ui
+                        .add_enabled(
+                            !self.clipboard.is_empty(),
+                            egui::Button::new("Paste (Ctrl+V)"),
+                        )
+                        .clicked()
+                    {
+                        self.paste();
+                        ui.close_menu();
+                    }
                 });
 
                 ui.menu_button("GameObject", |ui| {
@@ -1636,10 +2607,42 @@ impl PhysicsEditorApp {
                 {
                     self.gizmo_mode = GizmoMode::Scale;
                 }
+
+                ui.separator();
+
+                // Grid display and snapping
+                ui.checkbox(&mut self.grid.visible, "Grid");
+                ui.checkbox(&mut self.grid.snap_enabled, "Snap");
+                ui.add(
+                    egui::DragValue::new(&mut self.grid.increment)
+                        .speed(0.05)
+                        .range(0.05..=10.0)
+                        .prefix("Step: "),
+                );
             });
         });
     }
 
+    /// Revert the most recent editor action
+    fn undo(&mut self) {
+        if let Some(action) = self.undo_stack.undo() {
+            self.apply_action(&action, true);
+            self.add_console_message("Undo".to_string());
+        }
+    }
+
+    /// Re-apply the most recently undone editor action
+    fn redo(&mut self) {
+        if let Some(action) = self.undo_stack.redo() {
+            self.apply_action(&action, false);
+            self.add_console_message("Redo".to_string());
+        }
+    }
+
+    fn apply_action(&mut self, action: &EditorAction, reverse: bool) {
+        action.apply(&mut self.game_objects, reverse);
+    }
+
     /// Show the main layout with dockable panels
     fn show_main_layout(&mut self, ctx: &egui::Context) {
         egui::CentralPanel::default().show(ctx, |ui| {
@@ -1664,6 +2667,14 @@ impl Default for PhysicsEditorApp {
 }
 
 impl eframe::App for PhysicsEditorApp {
+    fn on_exit(&mut self, _gl: Option<&eframe::glow::Context>) {
+        // Leave an unanswered recovery in place for the next session
+        if !self.recovery_prompt {
+            let snapshot = self.scene_snapshot();
+            let _ = self.autosave.save_on_exit(&snapshot);
+        }
+    }
+
     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
         // Check for new simulation data from Matrix Language (automatic polling)
         if let Some(sim_data) = self.ipc_manager.check_for_simulation_data() {
@@ -1673,6 +2684,34 @@ impl eframe::App for PhysicsEditorApp {
             );
         }
 
+        // Undo/redo shortcuts
+        let undo_shortcut = egui::KeyboardShortcut::new(egui::Modifiers::COMMAND, egui::Key::Z);
+        let redo_shortcut = egui::KeyboardShortcut::new(egui::Modifiers::COMMAND, egui::Key::Y);
+        if ctx.input_mut(|i| i.consume_shortcut(&undo_shortcut)) {
+            self.undo();
+        }
+        if ctx.input_mut(|i| i.consume_shortcut(&redo_shortcut)) {
+            self.redo();
+        }
+        // Left to text fields while one has focus
+        if !ctx.wants_keyboard_input() {
+            if ctx.input_mut(|i| i.consume_key(egui::Modifiers::NONE, egui::Key::Delete)) {
+                self.delete_selected();
+            }
+            // Platforms report Ctrl+C/Ctrl+V as copy and paste events rather than keys
+            let (copy, paste) = ctx.input(|i| {
+                let copy = i.events.iter().any(|e| matches!(e, egui::Event::Copy));
+                let paste = i.events.iter().any(|e| matches!(e, egui::Event::Paste(_)));
+                (copy, paste)
+            });
+            if copy {
+                self.copy_selected(ctx);
+            }
+            if paste {
+                self.paste();
+            }
+        }
+
         // Menu bar
         self.show_menu_bar(ctx);
 
@@ -1703,6 +2742,32 @@ impl eframe::App for PhysicsEditorApp {
                 });
         }
 
+        if self.show_open_scene {
+            egui::Window::new(format!("Open Scene##{}", self.instance_id))
+                .collapsible(false)
+                .resizable(false)
+                .show(ctx, |ui| {
+                    ui.horizontal(|ui| {
+                        ui.label("File:");
+                        ui.text_edit_singleline(&mut self.scene_path);
+                    });
+                    ui.horizontal(|ui| {
+                        if ui.button("Open").clicked() {
+                            let path = self.scene_path.clone();
+                            let message = match self.open_scene(Path::new(&path)) {
+                                Ok(()) => format!("Opened scene {}", path),
+                                Err(e) => format!("Could not open scene: {}", e),
+                            };
+                            self.add_console_message(message);
+                            self.show_open_scene = false;
+                        }
+                        if ui.button("Cancel").clicked() {
+                            self.show_open_scene = false;
+                        }
+                    });
+                });
+        }
+
         if self.show_preferences {
             egui::Window::new(format!("Preferences##{}", self.instance_id))
                 .collapsible(false)
@@ -1729,6 +2794,44 @@ impl eframe::App for PhysicsEditorApp {
                         );
                     });
 
+                    ui.add_space(8.0);
+                    ui.label("Physics Settings");
+                    ui.separator();
+
+                    // Only the editor's world; script-created worlds keep their own preset
+                    ui.horizontal(|ui| {
+                        ui.label("Physics Quality:");
+                        egui::ComboBox::from_id_salt(format!(
+                            "physics_quality_{}",
+                            self.instance_id
+                        ))
+                        .selected_text(self.physics_world.quality.name())
+                        .show_ui(ui, |ui| {
+                            for preset in QualityPreset::ALL {
+                                ui.selectable_value(
+                                    &mut self.physics_world.quality,
+                                    preset,
+                                    preset.name(),
+                                );
+                            }
+                        });
+                    });
+
+                    ui.add_space(8.0);
+                    ui.label("Editor Settings");
+                    ui.separator();
+
+                    ui.horizontal(|ui| {
+                        ui.label("Autosave Interval (s):");
+                        let mut seconds = self.autosave.interval.as_secs();
+                        if ui
+                            .add(egui::DragValue::new(&mut seconds).range(5..=3600))
+                            .changed()
+                        {
+                            self.autosave.interval = Duration::from_secs(seconds);
+                        }
+                    });
+
                     if ui.button("Close").clicked() {
                         self.show_preferences = false;
                     }
@@ -1758,13 +2861,72 @@ impl eframe::App for PhysicsEditorApp {
             ctx.request_repaint();
         }
 
+        if self.recovery_prompt {
+            egui::Window::new(format!("Recover Scene##{}", self.instance_id))
+                .collapsible(false)
+                .resizable(false)
+                .show(ctx, |ui| {
+                    ui.label("The editor did not close cleanly last time.");
+                    ui.label("Restore the autosaved scene?");
+                    ui.horizontal(|ui| {
+                        if ui.button("Restore").clicked() {
+                            match self.autosave.load_recovery::<Vec<GameObject>>() {
+                                Ok(objects) => {
+                                    self.restore_scene(objects);
+                                    self.add_console_message(
+                                        "Recovered autosaved scene".to_string(),
+                                    );
+                                }
+                                Err(e) => self
+                                    .add_console_message(format!("Could not recover scene: {}", e)),
+                            }
+                            self.recovery_prompt = false;
+                        }
+                        if ui.button("Discard").clicked() {
+                            let _ = self.autosave.discard();
+                            self.recovery_prompt = false;
+                        }
+                    });
+                });
+        } else if self.autosave.is_due() {
+            // Held back while a recovery is pending so the crashed session's file survives
+            let snapshot = self.scene_snapshot();
+            if let Err(e) = self.autosave.save(&snapshot) {
+                self.add_console_message(format!("Autosave failed: {}", e));
+            }
+        }
+        ctx.request_repaint_after(self.autosave.interval);
+
+        // Scene view simulation
+        let (world, objects) = (&mut self.physics_world, &mut self.game_objects);
+        self.scene_playback.tick(|| world.step(objects));
+
         // Request repaint for smooth animation when playing
-        if self.is_playing {
+        if self.is_playing || self.scene_playback.is_simulating {
             ctx.request_repaint();
         }
     }
 }
 
+/// A vector as `(x, y, z)` to two decimal places, for console output
+fn format_vec3(v: Vec3) -> String {
+    format!("({:.2}, {:.2}, {:.2})", v.x, v.y, v.z)
+}
+
+/// Map a console object type name to its GameObject type and default name
+fn object_type_from_name(name: &str) -> Option<(GameObjectType, &'static str)> {
+    match name {
+        "cube" => Some((GameObjectType::Cube, "Cube")),
+        "sphere" => Some((GameObjectType::Sphere, "Sphere")),
+        "cylinder" => Some((GameObjectType::Cylinder, "Cylinder")),
+        "plane" => Some((GameObjectType::Plane, "Plane")),
+        "light" => Some((GameObjectType::Light, "Light")),
+        "camera" => Some((GameObjectType::Camera, "Camera")),
+        "empty" => Some((GameObjectType::Empty, "Empty")),
+        _ => None,
+    }
+}
+
 /// Launch the Physics Editor application
 pub fn launch_physics_editor() -> Result<(), Box<dyn std::error::Error>> {
     let options = eframe::NativeOptions {
@@ -1781,3 +2943,139 @@ pub fn launch_physics_editor() -> Result<(), Box<dyn std::error::Error>> {
     )
     .map_err(|e| Box::new(e) as Box<dyn std::error::Error>)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f32::consts::FRAC_PI_2;
+
+    #[test]
+    fn test_torque_rotates_scene_transform() {
+        let mut objects = HashMap::new();
+        let mut cube = GameObject::new(1, "Cube".to_string(), GameObjectType::Cube);
+        cube.transform.position = Vec3::new(0.0, 2.0, 0.0);
+        cube.rigid_body.as_mut().unwrap().use_gravity = false;
+        objects.insert(cube.id, cube);
+
+        let mut world = PhysicsWorld::default();
+        for _ in 0..30 {
+            let body = objects.get_mut(&1).unwrap().rigid_body.as_mut().unwrap();
+            body.apply_torque(Vec3::new(0.0, 0.0, 2.0));
+            world.step(&mut objects);
+        }
+
+        // Angular speed grows by 2 dt per step, and each step turns by speed * dt
+        let dt = world.time_step;
+        let expected: f32 = (1..=30).map(|n| n as f32 * 2.0 * dt * dt).sum();
+        let cube = &objects[&1];
+        let rotation = cube.transform.rotation;
+        assert!(
+            (rotation.z - expected.to_degrees()).abs() < 0.01,
+            "{:?}",
+            rotation
+        );
+        assert!(
+            rotation.x.abs() < 1e-3 && rotation.y.abs() < 1e-3,
+            "{:?}",
+            rotation
+        );
+        assert_eq!(cube.rigid_body.as_ref().unwrap().torque, Vec3::zero());
+
+        // A rotation set in the editor is carried on from, not overwritten
+        objects.get_mut(&1).unwrap().transform.rotation.x = 30.0;
+        world.step(&mut objects);
+        let rotation = objects[&1].transform.rotation;
+        assert!((rotation.x - 30.0).abs() < 0.01, "{:?}", rotation);
+        assert!(rotation.z > expected.to_degrees(), "{:?}", rotation);
+    }
+
+    #[test]
+    fn test_quality_preset_substeps_the_editor_world() {
+        let falling = || {
+            let mut cube = GameObject::new(1, "Cube".to_string(), GameObjectType::Cube);
+            cube.transform.position = Vec3::new(0.0, 10.0, 0.0);
+            HashMap::from([(cube.id, cube)])
+        };
+        let mut fast = PhysicsWorld {
+            quality: QualityPreset::Fast,
+            ..Default::default()
+        };
+        let mut accurate = PhysicsWorld {
+            quality: QualityPreset::Accurate,
+            ..Default::default()
+        };
+
+        let (mut coarse, mut fine) = (falling(), falling());
+        fast.step(&mut coarse);
+        accurate.step(&mut fine);
+
+        // Smaller substeps lag less behind the exact fall of g t^2 / 2
+        let exact = 10.0 - 0.5 * 9.81 * fast.time_step * fast.time_step;
+        let error =
+            |objects: &HashMap<usize, GameObject>| (objects[&1].transform.position.y - exact).abs();
+        assert!(error(&fine) < error(&coarse));
+        assert_eq!(accurate.frame, 1);
+        assert_eq!(accurate.sim_time, fast.sim_time);
+    }
+
+    #[test]
+    fn test_scene_file_round_trip_preserves_objects() {
+        let mut cube = GameObject::new(1, "Cube".to_string(), GameObjectType::Cube);
+        cube.transform.position = Vec3::new(1.0, 2.5, -3.0);
+        cube.transform.rotation = Vec3::new(0.0, 45.0, 10.0);
+        let body = cube.rigid_body.as_mut().unwrap();
+        body.mass = 4.0;
+        body.velocity = Vec3::new(0.5, -1.0, 0.0);
+        body.is_kinematic = true;
+        body.orientation = Quaternion::from_euler_degrees(Vec3::new(0.0, 45.0, 10.0));
+        body.apply_torque(Vec3::new(0.0, 1.0, 0.0));
+
+        let mut sphere = GameObject::new(2, "Sphere".to_string(), GameObjectType::Sphere);
+        sphere.transform.scale = Vec3::new(2.0, 2.0, 2.0);
+        sphere.rigid_body.as_mut().unwrap().use_gravity = false;
+        let mut light = GameObject::new(7, "Key Light".to_string(), GameObjectType::Light);
+        light.transform.position = Vec3::new(2.0, 4.0, 2.0);
+        let scene = vec![cube, sphere, light];
+
+        let path =
+            std::env::temp_dir().join(format!("scene_round_trip_{}.json", std::process::id()));
+        scene_file::save_scene(&path, &scene).unwrap();
+        let loaded: Vec<GameObject> = scene_file::load_scene(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(loaded, scene);
+    }
+
+    #[test]
+    fn test_inspect_logs_object_transform_and_components() {
+        let mut app = PhysicsEditorApp::new();
+        app.execute_console_command("spawn cube");
+        let id = app.selection.primary().unwrap();
+        app.game_objects.get_mut(&id).unwrap().transform.position = Vec3::new(1.5, 2.0, -3.25);
+
+        app.console_messages.clear();
+        app.execute_console_command(&format!("inspect {}", id));
+        let output = app.console_messages.join("\n");
+        assert!(output.contains("(1.50, 2.00, -3.25)"), "{}", output);
+        assert!(output.contains("RigidBody mass"), "{}", output);
+
+        app.execute_console_command("inspect 9999");
+        assert!(app.console_messages.last().unwrap().starts_with("Warning"));
+    }
+
+    #[test]
+    fn test_quaternion_euler_round_trip() {
+        let degrees = Vec3::new(10.0, -35.0, 120.0);
+        let back = Quaternion::from_euler_degrees(degrees).to_euler_degrees();
+        assert!((back - degrees).length() < 1e-3, "{:?}", back);
+
+        // A quarter turn about Z on top of a tilt about X
+        let quarter = Quaternion::from_axis_angle(Vec3::new(0.0, 0.0, 1.0), FRAC_PI_2);
+        let tilted = Quaternion::from_euler_degrees(Vec3::new(20.0, 0.0, 0.0));
+        let turned = (quarter * tilted).to_euler_degrees();
+        assert!(
+            (turned - Vec3::new(20.0, 0.0, 90.0)).length() < 1e-3,
+            "{:?}",
+            turned
+        );
+    }
+}
@@ -2,9 +2,19 @@
 // Provides Unity-style physics simulation interface as a standalone library
 
 // Core GUI Module
+pub mod autosave;
+pub mod clipboard;
+pub mod console_commands;
+pub mod debug_overlay;
+pub mod grid;
 pub mod gui;
 pub mod ipc;
+pub mod parse_cache;
+pub mod playback;
+pub mod scene_file;
 pub mod scripting_panel;
+pub mod selection;
+pub mod undo;
 
 /// Launch the Unity-style physics simulation GUI
 pub fn launch_physics_gui() -> Result<(), Box<dyn std::error::Error>> {
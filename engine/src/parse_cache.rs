@@ -0,0 +1,102 @@
+// Script Parse Cache
+// Reuses a script's parsed program until its source text changes
+
+use matrix_lang::ast::Program;
+use matrix_lang::lexer::Lexer;
+use matrix_lang::parser::{ParseError, Parser};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+
+/// Parse outcome for one version of a script's source
+struct CachedParse {
+    source_hash: u64,
+    result: Result<Program, ParseError>,
+}
+
+/// Parse results per script name, keyed by a hash of the source they came from
+#[derive(Default)]
+pub struct ParseCache {
+    entries: HashMap<String, CachedParse>,
+    parses: usize,
+}
+
+impl ParseCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Lex and parse `source`, or return the previous result for `script` if the
+    /// source is unchanged. Parse errors are cached too.
+    pub fn parse(&mut self, script: &str, source: &str) -> &Result<Program, ParseError> {
+        let source_hash = hash_source(source);
+        let fresh = matches!(
+            self.entries.get(script),
+            Some(entry) if entry.source_hash == source_hash
+        );
+
+        if !fresh {
+            self.parses += 1;
+            let result = Parser::new(Lexer::new(source)).and_then(|mut p| p.parse_program());
+            self.entries.insert(
+                script.to_string(),
+                CachedParse {
+                    source_hash,
+                    result,
+                },
+            );
+        }
+        &self.entries[script].result
+    }
+
+    /// Forget a script, e.g. when it is closed
+    pub fn invalidate(&mut self, script: &str) {
+        self.entries.remove(script);
+    }
+
+    /// Number of times a source has actually been parsed
+    pub fn parse_count(&self) -> usize {
+        self.parses
+    }
+}
+
+fn hash_source(source: &str) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    source.hash(&mut hasher);
+    hasher.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unchanged_source_parses_once() {
+        let mut cache = ParseCache::new();
+        let source = "let x = 1\nlet y = x + 2";
+
+        let first = cache.parse("Main", source).clone().unwrap();
+        let second = cache.parse("Main", source).clone().unwrap();
+        assert_eq!(first, second);
+        assert_eq!(cache.parse_count(), 1);
+    }
+
+    #[test]
+    fn test_edited_source_is_reparsed() {
+        let mut cache = ParseCache::new();
+        cache.parse("Main", "let x = 1").as_ref().unwrap();
+        let edited = cache.parse("Main", "let x = 1\nlet y = 2").clone().unwrap();
+        assert_eq!(edited.items.len(), 2);
+        assert_eq!(cache.parse_count(), 2);
+
+        // Scripts are cached independently, and invalidation forces a fresh parse
+        cache.parse("Other", "let x = 1").as_ref().unwrap();
+        assert_eq!(cache.parse_count(), 3);
+        cache.invalidate("Main");
+        cache
+            .parse("Main", "let x = 1\nlet y = 2")
+            .as_ref()
+            .unwrap();
+        assert_eq!(cache.parse_count(), 4);
+    }
+}
@@ -0,0 +1,79 @@
+// Scene Playback Controls
+// Play/pause/single-step state for the scene view, independent of the toolbar play toggle
+
+/// Scene view simulation controls. While paused, single steps can be queued
+/// to advance the world one frame at a time.
+#[derive(Debug, Clone, Default)]
+pub struct PlaybackControls {
+    pub is_simulating: bool,
+    pending_steps: usize,
+}
+
+impl PlaybackControls {
+    pub fn play(&mut self) {
+        self.is_simulating = true;
+        self.pending_steps = 0;
+    }
+
+    pub fn pause(&mut self) {
+        self.is_simulating = false;
+    }
+
+    /// Queue one frame to be simulated; ignored while already simulating
+    pub fn request_step(&mut self) {
+        if !self.is_simulating {
+            self.pending_steps += 1;
+        }
+    }
+
+    /// Run `step` for this frame: once while simulating, otherwise once per queued step
+    pub fn tick(&mut self, mut step: impl FnMut()) {
+        let steps = if self.is_simulating {
+            1
+        } else {
+            std::mem::take(&mut self.pending_steps)
+        };
+        for _ in 0..steps {
+            step();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_single_step_advances_once_while_paused() {
+        let mut controls = PlaybackControls::default();
+        let mut steps = 0;
+
+        controls.tick(|| steps += 1);
+        assert_eq!(steps, 0);
+
+        controls.request_step();
+        controls.tick(|| steps += 1);
+        assert_eq!(steps, 1);
+        assert!(!controls.is_simulating);
+
+        // The step is consumed, so later frames stay paused
+        controls.tick(|| steps += 1);
+        assert_eq!(steps, 1);
+    }
+
+    #[test]
+    fn test_play_steps_every_frame_until_paused() {
+        let mut controls = PlaybackControls::default();
+        let mut steps = 0;
+
+        controls.play();
+        controls.request_step();
+        controls.tick(|| steps += 1);
+        controls.tick(|| steps += 1);
+        assert_eq!(steps, 2);
+
+        controls.pause();
+        controls.tick(|| steps += 1);
+        assert_eq!(steps, 2);
+    }
+}
@@ -0,0 +1,70 @@
+// Scene Files
+// Saving editor scenes to JSON files on disk and loading them back
+
+use crate::autosave::write_atomically;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Bumped whenever a change to the scene types would misread older files
+pub const SCENE_FORMAT_VERSION: u32 = 1;
+
+#[derive(Serialize)]
+struct SceneFileRef<'a, T> {
+    version: u32,
+    scene: &'a T,
+}
+
+#[derive(Deserialize)]
+struct SceneFile<T> {
+    version: u32,
+    scene: T,
+}
+
+/// Scene file the editor saves to and opens from until another path is entered
+pub fn default_path() -> PathBuf {
+    PathBuf::from("scene.json")
+}
+
+/// Write `scene` to `path` as pretty-printed JSON:
+/// `{ "version": 1, "scene": <scene> }`
+pub fn save_scene<T: Serialize>(path: &Path, scene: &T) -> io::Result<()> {
+    let contents = serde_json::to_string_pretty(&SceneFileRef {
+        version: SCENE_FORMAT_VERSION,
+        scene,
+    })?;
+    write_atomically(path, &contents)
+}
+
+/// Read a scene written by `save_scene`; files from a newer format are rejected
+pub fn load_scene<T: DeserializeOwned>(path: &Path) -> io::Result<T> {
+    let contents = fs::read_to_string(path)?;
+    let file: SceneFile<serde_json::Value> = serde_json::from_str(&contents)?;
+    if file.version > SCENE_FORMAT_VERSION {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!(
+                "scene format version {} is newer than supported version {}",
+                file.version, SCENE_FORMAT_VERSION
+            ),
+        ));
+    }
+    Ok(serde_json::from_value(file.scene)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_newer_format_versions_are_rejected() {
+        let path = std::env::temp_dir().join(format!("scene_future_{}.json", std::process::id()));
+        fs::write(&path, r#"{ "version": 99, "scene": [] }"#).unwrap();
+
+        let error = load_scene::<Vec<u32>>(&path).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+        fs::remove_file(&path).unwrap();
+    }
+}
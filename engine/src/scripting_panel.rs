@@ -1,11 +1,10 @@
+use crate::parse_cache::ParseCache;
 use eframe::egui;
 // use std::collections::HashMap; // Unused for now
 
 // Import Matrix Language components
 use matrix_lang::ast::Program;
 use matrix_lang::eval::Interpreter;
-use matrix_lang::lexer::Lexer;
-use matrix_lang::parser::Parser;
 
 /// Scripting panel for writing and editing Matrix Language scripts
 pub struct ScriptingPanel {
@@ -26,6 +25,8 @@ pub struct ScriptingPanel {
     pub script_execution_callback: Option<Box<dyn Fn(&str, &Program) + Send + Sync>>,
     // Track last executed script for integration with viewport
     last_executed_script: Option<Program>,
+    // Parsed scripts, reused until their source is edited
+    parse_cache: ParseCache,
 }
 
 #[derive(Debug, Clone)]
@@ -81,6 +82,7 @@ impl ScriptingPanel {
             regex_search: false,
             script_execution_callback: None,
             last_executed_script: None,
+            parse_cache: ParseCache::new(),
         };
 
         // Create a default script with proper 3D object creation
@@ -596,6 +598,7 @@ impl ScriptingPanel {
         }
 
         self.scripts.remove(&name);
+        self.parse_cache.invalidate(&name);
 
         if self.active_script.as_ref() == Some(&name) {
             self.active_script = self.scripts.keys().next().cloned();
@@ -639,37 +642,31 @@ impl ScriptingPanel {
             if let Some(script) = self.scripts.get(active_name) {
                 println!("Running script: {}", script.name);
 
-                // Parse and execute the script
-                let lexer = Lexer::new(&script.code);
-                match Parser::new(lexer) {
-                    Ok(mut parser) => match parser.parse_program() {
-                        Ok(ast) => {
-                            // Store AST for integration with viewport
-                            self.last_executed_script = Some(ast.clone());
-
-                            // Call callback with AST for 3D object creation
-                            if let Some(ref callback) = self.script_execution_callback {
-                                callback(&script.name, &ast);
-                            }
+                // Parse (or reuse the cached parse) and execute the script
+                match self.parse_cache.parse(&script.name, &script.code) {
+                    Ok(ast) => {
+                        // Store AST for integration with viewport
+                        self.last_executed_script = Some(ast.clone());
 
-                            // Execute script in interpreter
-                            let mut interpreter = Interpreter::new();
-                            match interpreter.eval_program(&ast) {
-                                Ok(result) => {
-                                    println!("✅ Script executed successfully");
-                                    println!("Result: {:?}", result);
-                                }
-                                Err(e) => {
-                                    println!("❌ Runtime error: {:?}", e);
-                                }
-                            }
+                        // Call callback with AST for 3D object creation
+                        if let Some(ref callback) = self.script_execution_callback {
+                            callback(&script.name, ast);
                         }
-                        Err(e) => {
-                            println!("❌ Parse error: {:?}", e);
+
+                        // Execute script in interpreter
+                        let mut interpreter = Interpreter::new();
+                        match interpreter.eval_program(ast) {
+                            Ok(result) => {
+                                println!("✅ Script executed successfully");
+                                println!("Result: {:?}", result);
+                            }
+                            Err(e) => {
+                                println!("❌ Runtime error: {:?}", e);
+                            }
                         }
-                    },
+                    }
                     Err(e) => {
-                        println!("❌ Parser creation error: {:?}", e);
+                        println!("❌ Parse error: {:?}", e);
                     }
                 }
             }
@@ -682,35 +679,28 @@ impl ScriptingPanel {
                 println!("🐛 Debugging script: {}", script.name);
 
                 // Parse and analyze the script for debugging
-                let lexer = Lexer::new(&script.code);
-                match Parser::new(lexer) {
-                    Ok(mut parser) => match parser.parse_program() {
-                        Ok(ast) => {
-                            println!("📋 AST Analysis:");
-                            println!("{:#?}", ast);
-
-                            // Step-by-step execution with debug info
-                            let mut interpreter = Interpreter::new();
-                            println!("🔍 Starting step-by-step execution...");
-
-                            match interpreter.eval_program(&ast) {
-                                Ok(result) => {
-                                    println!("✅ Debug execution completed successfully");
-                                    println!("Result: {:?}", result);
-                                }
-                                Err(e) => {
-                                    println!("❌ Debug execution failed:");
-                                    println!("Runtime error: {:?}", e);
-                                }
+                match self.parse_cache.parse(&script.name, &script.code) {
+                    Ok(ast) => {
+                        println!("📋 AST Analysis:");
+                        println!("{:#?}", ast);
+
+                        // Step-by-step execution with debug info
+                        let mut interpreter = Interpreter::new();
+                        println!("🔍 Starting step-by-step execution...");
+
+                        match interpreter.eval_program(ast) {
+                            Ok(result) => {
+                                println!("✅ Debug execution completed successfully");
+                                println!("Result: {:?}", result);
+                            }
+                            Err(e) => {
+                                println!("❌ Debug execution failed:");
+                                println!("Runtime error: {:?}", e);
                             }
                         }
-                        Err(e) => {
-                            println!("❌ Parse error during debugging:");
-                            println!("{:?}", e);
-                        }
-                    },
+                    }
                     Err(e) => {
-                        println!("❌ Parser creation error during debugging:");
+                        println!("❌ Parse error during debugging:");
                         println!("{:?}", e);
                     }
                 }
@@ -724,32 +714,15 @@ impl ScriptingPanel {
         if let Some(script) = self.scripts.get_mut(script_name) {
             script.syntax_errors.clear();
 
-            let lexer = Lexer::new(&script.code);
-            match Parser::new(lexer) {
-                Ok(mut parser) => {
-                    if let Err(e) = parser.parse_program() {
-                        let error_msg = format!("{:?}", e);
-                        // Extract line number before borrowing script mutably again
-                        let line_num =
-                            ScriptingPanel::extract_line_number_from_error_static(&error_msg);
-                        script.syntax_errors.push(SyntaxError {
-                            line: line_num,
-                            column: 1,
-                            message: error_msg,
-                            error_type: ErrorType::Parser,
-                        });
-                    }
-                }
-                Err(e) => {
-                    let error_message = format!("{:?}", e);
-                    let line_number = Self::extract_line_number_from_error_static(&error_message);
-                    script.syntax_errors.push(SyntaxError {
-                        line: line_number,
-                        column: 1,
-                        message: error_message,
-                        error_type: ErrorType::Parser,
-                    });
-                }
+            if let Err(e) = self.parse_cache.parse(script_name, &script.code) {
+                let error_msg = format!("{:?}", e);
+                let line_num = ScriptingPanel::extract_line_number_from_error_static(&error_msg);
+                script.syntax_errors.push(SyntaxError {
+                    line: line_num,
+                    column: 1,
+                    message: error_msg,
+                    error_type: ErrorType::Parser,
+                });
             }
         }
     }
@@ -0,0 +1,224 @@
+// Editor Selection
+// The set of selected objects, with the most recently picked one as primary
+
+use crate::gui::{GameObject, Transform};
+use crate::undo::EditorAction;
+use std::collections::{HashMap, HashSet};
+
+/// Selected objects. The primary object is the one shown in the inspector and
+/// carrying the gizmo; it is always part of the selection.
+#[derive(Debug, Clone, Default)]
+pub struct Selection {
+    ids: HashSet<usize>,
+    primary: Option<usize>,
+}
+
+impl Selection {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Select only `id`
+    pub fn select(&mut self, id: usize) {
+        self.ids.clear();
+        self.ids.insert(id);
+        self.primary = Some(id);
+    }
+
+    /// Add `id` to the selection or take it out again, as with Ctrl-click
+    pub fn toggle(&mut self, id: usize) {
+        if self.ids.remove(&id) {
+            if self.primary == Some(id) {
+                self.primary = self.ids.iter().min().copied();
+            }
+        } else {
+            self.ids.insert(id);
+            self.primary = Some(id);
+        }
+    }
+
+    /// Add several objects, making the last one primary
+    pub fn extend(&mut self, ids: impl IntoIterator<Item = usize>) {
+        for id in ids {
+            self.ids.insert(id);
+            self.primary = Some(id);
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.ids.clear();
+        self.primary = None;
+    }
+
+    /// Forget an object that no longer exists
+    pub fn remove(&mut self, id: usize) {
+        if self.ids.remove(&id) && self.primary == Some(id) {
+            self.primary = self.ids.iter().min().copied();
+        }
+    }
+
+    pub fn contains(&self, id: usize) -> bool {
+        self.ids.contains(&id)
+    }
+
+    pub fn primary(&self) -> Option<usize> {
+        self.primary
+    }
+
+    /// Selected ids in ascending order
+    pub fn ids(&self) -> Vec<usize> {
+        let mut ids: Vec<usize> = self.ids.iter().copied().collect();
+        ids.sort_unstable();
+        ids
+    }
+
+    pub fn len(&self) -> usize {
+        self.ids.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ids.is_empty()
+    }
+
+    /// Remove every selected object from `objects` and clear the selection,
+    /// returning one action that restores them all
+    pub fn delete_from(
+        &mut self,
+        objects: &mut HashMap<usize, GameObject>,
+    ) -> Option<EditorAction> {
+        let deleted: Vec<GameObject> = self
+            .ids()
+            .into_iter()
+            .filter_map(|id| objects.remove(&id))
+            .collect();
+        self.clear();
+        (!deleted.is_empty()).then_some(EditorAction::DeleteObjects { objects: deleted })
+    }
+
+    /// Current transforms of the selected objects, to record an edit against
+    pub fn transforms(&self, objects: &HashMap<usize, GameObject>) -> Vec<(usize, Transform)> {
+        self.ids()
+            .into_iter()
+            .filter_map(|id| Some((id, objects.get(&id)?.transform.clone())))
+            .collect()
+    }
+
+    /// One action covering every object in `before` whose transform has since
+    /// changed, or `None` if none did
+    pub fn transform_changes(
+        before: Vec<(usize, Transform)>,
+        objects: &HashMap<usize, GameObject>,
+    ) -> Option<EditorAction> {
+        let changes: Vec<_> = before
+            .into_iter()
+            .filter_map(|(id, before)| {
+                let after = &objects.get(&id)?.transform;
+                (*after != before).then(|| (id, before, after.clone()))
+            })
+            .collect();
+        (!changes.is_empty()).then_some(EditorAction::ModifyTransforms { changes })
+    }
+
+    /// Carry an inspector edit of the primary object's transform from `before` to
+    /// `after` over to the rest of the selection, as the same change in each value
+    pub fn apply_to_others(
+        &self,
+        objects: &mut HashMap<usize, GameObject>,
+        before: &Transform,
+        after: &Transform,
+    ) {
+        for id in self.ids.iter().filter(|&&id| Some(id) != self.primary) {
+            if let Some(obj) = objects.get_mut(id) {
+                let transform = &mut obj.transform;
+                transform.position = transform.position + (after.position - before.position);
+                transform.rotation = transform.rotation + (after.rotation - before.rotation);
+                transform.scale = transform.scale + (after.scale - before.scale);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gui::{GameObjectType, Vec3};
+    use crate::undo::UndoStack;
+
+    fn scene() -> HashMap<usize, GameObject> {
+        (1..=4)
+            .map(|id| {
+                let obj = GameObject::new(id, format!("Cube {}", id), GameObjectType::Cube);
+                (id, obj)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_deleting_selection_is_one_undo_step() {
+        let mut objects = scene();
+        let original = objects.clone();
+        let mut selection = Selection::new();
+        selection.select(1);
+        selection.toggle(2);
+        selection.extend([4]);
+        assert_eq!(selection.ids(), vec![1, 2, 4]);
+        assert_eq!(selection.primary(), Some(4));
+
+        let mut stack = UndoStack::new();
+        stack.push(selection.delete_from(&mut objects).unwrap());
+        assert_eq!(objects.keys().collect::<Vec<_>>(), vec![&3]);
+        assert!(selection.is_empty());
+
+        stack.undo().unwrap().apply(&mut objects, true);
+        assert_eq!(objects, original);
+        assert!(!stack.can_undo());
+    }
+
+    #[test]
+    fn test_toggle_moves_primary_and_edits_follow() {
+        let mut objects = scene();
+        let mut selection = Selection::new();
+        selection.select(1);
+        selection.toggle(3);
+        selection.toggle(2);
+        selection.toggle(2);
+        assert_eq!(selection.ids(), vec![1, 3]);
+        assert_eq!(selection.primary(), Some(1));
+
+        let before = objects[&1].transform.clone();
+        let mut after = before.clone();
+        after.position = Vec3::new(0.0, 2.0, 0.0);
+        objects.get_mut(&1).unwrap().transform = after.clone();
+        selection.apply_to_others(&mut objects, &before, &after);
+        assert_eq!(objects[&3].transform.position, Vec3::new(0.0, 2.0, 0.0));
+        assert_eq!(objects[&2].transform.position, Vec3::zero());
+    }
+
+    #[test]
+    fn test_selection_transform_edit_is_one_undo_step() {
+        let mut objects = scene();
+        let original = objects.clone();
+        let mut selection = Selection::new();
+        selection.select(2);
+        selection.extend([3, 4]);
+
+        let start = selection.transforms(&objects);
+        let before = objects[&4].transform.clone();
+        let mut after = before.clone();
+        after.scale = Vec3::new(3.0, 1.0, 1.0);
+        objects.get_mut(&4).unwrap().transform = after.clone();
+        selection.apply_to_others(&mut objects, &before, &after);
+
+        let mut stack = UndoStack::new();
+        stack.push(Selection::transform_changes(start, &objects).unwrap());
+        assert_eq!(objects[&2].transform.scale, Vec3::new(3.0, 1.0, 1.0));
+
+        stack.undo().unwrap().apply(&mut objects, true);
+        assert_eq!(objects, original);
+        assert!(!stack.can_undo());
+        assert_eq!(
+            Selection::transform_changes(selection.transforms(&objects), &objects),
+            None
+        );
+    }
+}
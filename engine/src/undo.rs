@@ -0,0 +1,288 @@
+// Editor Undo History
+// Reversible editor actions recorded on undo/redo stacks
+
+use crate::gui::{GameObject, RigidBody, Transform};
+use std::collections::HashMap;
+
+/// Maximum number of actions kept for undo
+const MAX_UNDO_DEPTH: usize = 100;
+
+/// A reversible change made in the editor
+#[derive(Debug, Clone, PartialEq)]
+pub enum EditorAction {
+    /// An object's transform changed from `before` to `after`
+    ModifyTransform {
+        object_id: usize,
+        before: Transform,
+        after: Transform,
+    },
+    /// Several objects' transforms changed together, as `(object_id, before, after)`
+    ModifyTransforms {
+        changes: Vec<(usize, Transform, Transform)>,
+    },
+    /// A rigid body was added to an object that had none
+    AddComponent {
+        object_id: usize,
+        component: RigidBody,
+    },
+    /// An object's rigid body was removed
+    RemoveComponent {
+        object_id: usize,
+        component: RigidBody,
+    },
+    /// An object's rigid body settings changed from `before` to `after`
+    ModifyComponent {
+        object_id: usize,
+        before: RigidBody,
+        after: RigidBody,
+    },
+    /// An object was renamed from `before` to `after`
+    RenameObject {
+        object_id: usize,
+        before: String,
+        after: String,
+    },
+    /// Objects were created together, such as by a paste
+    CreateObjects { objects: Vec<GameObject> },
+    /// Objects were deleted together, as they were just before
+    DeleteObjects { objects: Vec<GameObject> },
+}
+
+impl EditorAction {
+    /// Perform the action on `objects`, or revert it when `reverse` is set.
+    /// Actions on objects that no longer exist do nothing.
+    pub fn apply(&self, objects: &mut HashMap<usize, GameObject>, reverse: bool) {
+        match self {
+            EditorAction::ModifyTransform {
+                object_id,
+                before,
+                after,
+            } => {
+                if let Some(obj) = objects.get_mut(object_id) {
+                    obj.transform = if reverse { before } else { after }.clone();
+                }
+            }
+            EditorAction::ModifyTransforms { changes } => {
+                for (object_id, before, after) in changes {
+                    if let Some(obj) = objects.get_mut(object_id) {
+                        obj.transform = if reverse { before } else { after }.clone();
+                    }
+                }
+            }
+            EditorAction::AddComponent {
+                object_id,
+                component,
+            } => {
+                if let Some(obj) = objects.get_mut(object_id) {
+                    obj.rigid_body = (!reverse).then(|| component.clone());
+                }
+            }
+            EditorAction::RemoveComponent {
+                object_id,
+                component,
+            } => {
+                if let Some(obj) = objects.get_mut(object_id) {
+                    obj.rigid_body = reverse.then(|| component.clone());
+                }
+            }
+            EditorAction::ModifyComponent {
+                object_id,
+                before,
+                after,
+            } => {
+                if let Some(obj) = objects.get_mut(object_id) {
+                    obj.rigid_body = Some(if reverse { before } else { after }.clone());
+                }
+            }
+            EditorAction::RenameObject {
+                object_id,
+                before,
+                after,
+            } => {
+                if let Some(obj) = objects.get_mut(object_id) {
+                    obj.name = if reverse { before } else { after }.clone();
+                }
+            }
+            EditorAction::CreateObjects { objects: created } => {
+                for obj in created {
+                    if reverse {
+                        objects.remove(&obj.id);
+                    } else {
+                        objects.insert(obj.id, obj.clone());
+                    }
+                }
+            }
+            EditorAction::DeleteObjects { objects: deleted } => {
+                for obj in deleted {
+                    if reverse {
+                        objects.insert(obj.id, obj.clone());
+                    } else {
+                        objects.remove(&obj.id);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Undo/redo history of editor actions
+#[derive(Debug, Clone, Default)]
+pub struct UndoStack {
+    undo: Vec<EditorAction>,
+    redo: Vec<EditorAction>,
+}
+
+impl UndoStack {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record a freshly performed action; clears the redo history
+    pub fn push(&mut self, action: EditorAction) {
+        self.undo.push(action);
+        if self.undo.len() > MAX_UNDO_DEPTH {
+            self.undo.remove(0);
+        }
+        self.redo.clear();
+    }
+
+    /// Take the most recent action to revert
+    pub fn undo(&mut self) -> Option<EditorAction> {
+        let action = self.undo.pop()?;
+        self.redo.push(action.clone());
+        Some(action)
+    }
+
+    /// Take the most recently undone action to re-apply
+    pub fn redo(&mut self) -> Option<EditorAction> {
+        let action = self.redo.pop()?;
+        self.undo.push(action.clone());
+        Some(action)
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+
+    /// Recorded actions, oldest first
+    pub fn actions(&self) -> &[EditorAction] {
+        &self.undo
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gui::{GameObjectType, Vec3};
+
+    fn moved(object_id: usize, x: f32) -> EditorAction {
+        let before = Transform::default();
+        let mut after = Transform::default();
+        after.position = Vec3::new(x, 0.0, 0.0);
+        EditorAction::ModifyTransform {
+            object_id,
+            before,
+            after,
+        }
+    }
+
+    #[test]
+    fn test_undo_then_redo_returns_same_action() {
+        let mut stack = UndoStack::new();
+        stack.push(moved(1, 2.0));
+
+        assert_eq!(stack.undo(), Some(moved(1, 2.0)));
+        assert!(!stack.can_undo());
+        assert_eq!(stack.redo(), Some(moved(1, 2.0)));
+        assert!(!stack.can_redo());
+    }
+
+    #[test]
+    fn test_push_clears_redo() {
+        let mut stack = UndoStack::new();
+        stack.push(moved(1, 2.0));
+        stack.undo();
+        stack.push(moved(2, 3.0));
+
+        assert!(!stack.can_redo());
+        assert_eq!(stack.actions(), &[moved(2, 3.0)]);
+    }
+
+    #[test]
+    fn test_each_action_undoes_and_redoes() {
+        let heavy = RigidBody {
+            mass: 5.0,
+            use_gravity: false,
+            ..Default::default()
+        };
+
+        // Cube 1 starts with a default body, the light 2 with none
+        let scaled = Transform {
+            scale: Vec3::new(2.0, 2.0, 2.0),
+            ..Default::default()
+        };
+        let actions = [
+            moved(1, 2.0),
+            EditorAction::ModifyTransforms {
+                changes: vec![
+                    (1, Transform::default(), scaled.clone()),
+                    (2, Transform::default(), scaled),
+                ],
+            },
+            EditorAction::AddComponent {
+                object_id: 2,
+                component: RigidBody::default(),
+            },
+            EditorAction::RemoveComponent {
+                object_id: 1,
+                component: RigidBody::default(),
+            },
+            EditorAction::ModifyComponent {
+                object_id: 1,
+                before: RigidBody::default(),
+                after: heavy,
+            },
+            EditorAction::RenameObject {
+                object_id: 2,
+                before: "Light".to_string(),
+                after: "Sun".to_string(),
+            },
+            EditorAction::CreateObjects {
+                objects: vec![GameObject::new(
+                    3,
+                    "Cube (1)".to_string(),
+                    GameObjectType::Cube,
+                )],
+            },
+            EditorAction::DeleteObjects {
+                objects: vec![GameObject::new(1, "Cube".to_string(), GameObjectType::Cube)],
+            },
+        ];
+
+        for action in actions {
+            let mut objects: HashMap<usize, GameObject> = [
+                GameObject::new(1, "Cube".to_string(), GameObjectType::Cube),
+                GameObject::new(2, "Light".to_string(), GameObjectType::Light),
+            ]
+            .into_iter()
+            .map(|obj| (obj.id, obj))
+            .collect();
+            let original = objects.clone();
+
+            let mut stack = UndoStack::new();
+            action.apply(&mut objects, false);
+            stack.push(action.clone());
+            let modified = objects.clone();
+            assert_ne!(modified, original, "{:?} changed nothing", action);
+
+            stack.undo().unwrap().apply(&mut objects, true);
+            assert_eq!(objects, original, "undoing {:?}", action);
+            stack.redo().unwrap().apply(&mut objects, false);
+            assert_eq!(objects, modified, "redoing {:?}", action);
+        }
+    }
+}
@@ -42,6 +42,7 @@ pub enum Item {
     FunctionDef(FunctionDef),
     LetBinding(LetBinding),
     Import(Import),
+    Module(ModuleDef),
 }
 
 /// Struct definition with optional fields and defaults
@@ -108,10 +109,23 @@ pub struct FunctionDef {
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Parameter {
     pub name: String,
+    /// `UNTYPED_PARAMETER` when written without an annotation, e.g. `(x) => x + 1`
     pub type_annotation: Type,
+    /// Expression evaluated at call time when the argument is omitted
+    pub default: Option<Expression>,
     pub span: Span,
 }
 
+/// Type variable standing for a parameter written without an annotation; the type
+/// checker replaces it with a fresh type variable per parameter
+pub const UNTYPED_PARAMETER: &str = "_";
+
+impl Parameter {
+    pub fn is_untyped(&self) -> bool {
+        matches!(&self.type_annotation, Type::TypeVar(name) if name == UNTYPED_PARAMETER)
+    }
+}
+
 /// Function/variable attributes (@gpu, etc.)
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Attribute {
@@ -137,12 +151,25 @@ pub struct Import {
     pub span: Span,
 }
 
+/// Module declaration: `module Physics { ... }`. Members are reached from outside as
+/// `Physics::name`, and only those declared with `pub` are visible there.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ModuleDef {
+    pub name: String,
+    pub items: Vec<Item>,
+    /// Names of the `pub` members
+    pub exports: Vec<String>,
+    pub span: Span,
+}
+
 /// Type system
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum Type {
     /// Primitive types
     Int,
     Float,
+    /// Float tagged with a physical dimension, e.g. `Float<m/s>`
+    Quantity(crate::types::units::Dimension),
     Bool,
     String,
     Unit,
@@ -207,6 +234,9 @@ pub enum Expression {
     ArrayLiteral(Vec<Expression>, Span),
     MatrixLiteral(Vec<Vec<Expression>>, Span),
 
+    // Dictionary literal: { key: value, "other key": value }
+    DictLiteral(Vec<(String, Expression)>, Span),
+
     // Matrix comprehensions: [[expr | var in range] | var in range]
     MatrixComprehension {
         element: Box<Expression>,
@@ -266,6 +296,23 @@ pub enum Expression {
         span: Span,
     },
 
+    // Loop running the body block while the condition holds
+    While {
+        condition: Box<Expression>,
+        body: Box<Expression>,
+        span: Span,
+    },
+
+    // Loop running the body block once per element of an array or range, with the
+    // element bound to `variable`. There is no fresh scope per iteration: the body
+    // shares the enclosing scope, so a `let` in it stays visible after the loop
+    For {
+        variable: String,
+        iterable: Box<Expression>,
+        body: Box<Expression>,
+        span: Span,
+    },
+
     // Pattern matching
     Match {
         expression: Box<Expression>,
@@ -364,6 +411,10 @@ pub enum Pattern {
     Some(Box<Pattern>, Span),
     None(Span),
 
+    // Result patterns
+    Ok(Box<Pattern>, Span),
+    Err(Box<Pattern>, Span),
+
     // Struct patterns
     Struct {
         name: String,
@@ -467,6 +518,7 @@ impl AstNode for Item {
             Item::FunctionDef(f) => &f.span,
             Item::LetBinding(l) => &l.span,
             Item::Import(i) => &i.span,
+            Item::Module(m) => &m.span,
         }
     }
 }
@@ -482,6 +534,7 @@ impl Expression {
             Expression::StructCreation { span, .. } => span,
             Expression::ArrayLiteral(_, span) => span,
             Expression::MatrixLiteral(_, span) => span,
+            Expression::DictLiteral(_, span) => span,
             Expression::MatrixComprehension { span, .. } => span,
             Expression::FunctionCall { span, .. } => span,
             Expression::Lambda { span, .. } => span,
@@ -490,6 +543,8 @@ impl Expression {
             Expression::FieldAccess { span, .. } => span,
             Expression::OptionalAccess { span, .. } => span,
             Expression::IfExpression { span, .. } => span,
+            Expression::While { span, .. } => span,
+            Expression::For { span, .. } => span,
             Expression::Match { span, .. } => span,
             Expression::Let { span, .. } => span,
             Expression::Block { span, .. } => span,
@@ -567,6 +622,7 @@ mod tests {
         let param = Parameter {
             name: "x".to_string(),
             type_annotation: Type::Int,
+            default: None,
             span: param_span,
         };
 
@@ -54,6 +54,11 @@ pub fn walk_expression<V: AstVisitor>(visitor: &mut V, expr: &Expression) -> V::
                 }
             }
         }
+        Expression::DictLiteral(entries, _) => {
+            for (_, value) in entries {
+                visitor.visit_expression(value);
+            }
+        }
         Expression::MatrixComprehension {
             element,
             generators,
@@ -104,6 +109,16 @@ pub fn walk_expression<V: AstVisitor>(visitor: &mut V, expr: &Expression) -> V::
                 visitor.visit_expression(else_expr);
             }
         }
+        Expression::While {
+            condition, body, ..
+        } => {
+            visitor.visit_expression(condition);
+            visitor.visit_expression(body);
+        }
+        Expression::For { iterable, body, .. } => {
+            visitor.visit_expression(iterable);
+            visitor.visit_expression(body);
+        }
         Expression::Match {
             expression, arms, ..
         } => {
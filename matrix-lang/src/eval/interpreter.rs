@@ -1,8 +1,10 @@
 use crate::ast::*;
 #[cfg(feature = "jit")]
-use crate::jit::{JitContext, JitError, JitStats}; // Add JIT import conditionally
+use crate::jit::{JitContext, JitError}; // Add JIT import conditionally
 use crate::types::*;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::ops::ControlFlow;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, Mutex};
 use std::thread;
 use std::time::{Duration, Instant};
@@ -87,6 +89,50 @@ pub enum RuntimeError {
 
     #[error("Physics error: {message}")]
     PhysicsError { message: String },
+
+    #[error("Domain error: {message}")]
+    DomainError { message: String },
+
+    #[error("{member} is private to module {module}")]
+    PrivateMember { module: String, member: String },
+
+    /// Another error, raised while evaluating the expression at this position
+    #[error("{error} at line {line}, column {column}")]
+    Located {
+        error: Box<RuntimeError>,
+        line: usize,
+        column: usize,
+    },
+}
+
+impl RuntimeError {
+    /// Attach the position of `span`, unless an inner expression already did
+    pub fn at(self, span: &Span) -> Self {
+        match self {
+            RuntimeError::Located { .. } => self,
+            error => RuntimeError::Located {
+                error: Box::new(error),
+                line: span.line,
+                column: span.column,
+            },
+        }
+    }
+
+    /// Line and column of the expression that failed, if known
+    pub fn location(&self) -> Option<(usize, usize)> {
+        match self {
+            RuntimeError::Located { line, column, .. } => Some((*line, *column)),
+            _ => None,
+        }
+    }
+
+    /// The error itself, without its position
+    pub fn without_location(&self) -> &RuntimeError {
+        match self {
+            RuntimeError::Located { error, .. } => error,
+            error => error,
+        }
+    }
 }
 
 pub type RuntimeResult<T> = Result<T, RuntimeError>;
@@ -132,6 +178,12 @@ pub enum GpuMode {
     Parallel, // Use parallel threads
 }
 
+/// Builtin arity that accepts any number of arguments
+pub const VARIADIC: usize = usize::MAX;
+
+/// Body of a builtin that needs the running interpreter
+pub type InterpreterFn = fn(&mut Interpreter, &[Value]) -> RuntimeResult<Value>;
+
 /// Runtime values
 #[derive(Debug, Clone)]
 pub enum Value {
@@ -142,23 +194,44 @@ pub enum Value {
     Unit,
     Array(Vec<Value>),
     Matrix(Vec<Vec<Value>>),
+    /// String-keyed dictionary, written `{ key: value }`
+    Dict(HashMap<String, Value>),
+    /// `Some(value)` or `None`
+    Option(Option<Box<Value>>),
+    /// `Ok(value)` or `Err(error)`
+    Result(Result<Box<Value>, Box<Value>>),
     Struct {
         name: String,
         fields: HashMap<String, Value>,
     },
     Function {
         params: Vec<Parameter>,
-        body: Expression,
+        /// Shared between copies of the function, so a call can tell by identity
+        /// whether a name still refers to the function being run
+        body: Arc<Expression>,
+        /// Snapshot of the scope the function was created in. Capture is by value:
+        /// the function sees each outer binding as it was at creation, later
+        /// rebindings of the same name are not visible, and returning the function
+        /// from its defining scope keeps the captured values alive.
         closure: Environment,
     },
     BuiltinFunction {
         name: String,
+        /// Expected argument count, or `VARIADIC` to accept any number
         arity: usize,
         func: fn(&[Value]) -> RuntimeResult<Value>,
     },
+    /// Builtin that needs the running interpreter, to call a function argument or
+    /// evaluate source. Called the same way as `BuiltinFunction`.
+    InterpreterFunction {
+        name: String,
+        /// Expected argument count
+        arity: usize,
+        func: InterpreterFn,
+    },
     AsyncHandle(AsyncTask), // Handle to async computation
-    PhysicsWorld(crate::stdlib::PhysicsWorld),
-    PhysicsObject(crate::stdlib::PhysicsObject),
+    PhysicsWorld(Box<crate::stdlib::PhysicsWorld>),
+    PhysicsObject(Box<crate::stdlib::PhysicsObject>),
 }
 
 impl Value {
@@ -171,9 +244,12 @@ impl Value {
             Value::Unit => "Unit",
             Value::Array(_) => "Array",
             Value::Matrix(_) => "Matrix",
+            Value::Dict(_) => "Dict",
+            Value::Option(_) => "Option",
+            Value::Result(_) => "Result",
             Value::Struct { .. } => "Struct",
             Value::Function { .. } => "Function",
-            Value::BuiltinFunction { .. } => "BuiltinFunction",
+            Value::BuiltinFunction { .. } | Value::InterpreterFunction { .. } => "BuiltinFunction",
             Value::AsyncHandle(_) => "AsyncHandle",
             Value::PhysicsWorld(_) => "PhysicsWorld",
             Value::PhysicsObject(_) => "PhysicsObject",
@@ -188,6 +264,7 @@ impl Value {
             Value::Unit => false,
             Value::Array(arr) => !arr.is_empty(),
             Value::Matrix(mat) => !mat.is_empty(),
+            Value::Dict(dict) => !dict.is_empty(),
             _ => true,
         }
     }
@@ -353,6 +430,114 @@ impl Value {
         };
         Ok(Value::Bool(result))
     }
+
+    /// Apply a binary operator to two evaluated operands
+    pub fn apply_binary_op(left: Value, op: &BinaryOperator, right: Value) -> RuntimeResult<Value> {
+        match op {
+            BinaryOperator::Add => left.add(&right),
+            BinaryOperator::Sub => left.subtract(&right),
+            BinaryOperator::Mul => left.multiply(&right),
+            BinaryOperator::Div => left.divide(&right),
+            BinaryOperator::Pow => match (&left, &right) {
+                (Value::Int(base), Value::Int(exp)) => {
+                    if *exp >= 0 {
+                        Ok(Value::Int((*base as f64).powf(*exp as f64) as i64))
+                    } else {
+                        Ok(Value::Float((*base as f64).powf(*exp as f64)))
+                    }
+                }
+                (Value::Float(base), Value::Float(exp)) => Ok(Value::Float(base.powf(*exp))),
+                (Value::Int(base), Value::Float(exp)) => {
+                    Ok(Value::Float((*base as f64).powf(*exp)))
+                }
+                (Value::Float(base), Value::Int(exp)) => Ok(Value::Float(base.powf(*exp as f64))),
+                _ => Err(RuntimeError::TypeError {
+                    message: format!(
+                        "Cannot compute power of {} and {}",
+                        left.type_name(),
+                        right.type_name()
+                    ),
+                }),
+            },
+            BinaryOperator::Mod => match (&left, &right) {
+                (Value::Int(_), Value::Int(0)) => Err(RuntimeError::DivisionByZero),
+                (Value::Int(a), Value::Int(b)) => Ok(Value::Int(a % b)),
+                (Value::Int(_) | Value::Float(_), Value::Int(_) | Value::Float(_)) => {
+                    let a = crate::stdlib::physics::expect_number(&left, "Dividend")?;
+                    let b = crate::stdlib::physics::expect_number(&right, "Divisor")?;
+                    if b == 0.0 {
+                        Err(RuntimeError::DivisionByZero)
+                    } else {
+                        Ok(Value::Float(a % b))
+                    }
+                }
+                _ => Err(RuntimeError::TypeError {
+                    message: format!(
+                        "Cannot compute remainder of {} and {}",
+                        left.type_name(),
+                        right.type_name()
+                    ),
+                }),
+            },
+            BinaryOperator::MatMul => left.matrix_multiply(&right),
+            BinaryOperator::Eq => left.equals(&right),
+            BinaryOperator::Ne => {
+                let eq_result = left.equals(&right)?;
+                match eq_result {
+                    Value::Bool(b) => Ok(Value::Bool(!b)),
+                    _ => unreachable!(),
+                }
+            }
+            BinaryOperator::Lt => left.less_than(&right),
+            BinaryOperator::Le => {
+                let lt = left.less_than(&right)?;
+                let eq = left.equals(&right)?;
+                match (lt, eq) {
+                    (Value::Bool(a), Value::Bool(b)) => Ok(Value::Bool(a || b)),
+                    _ => unreachable!(),
+                }
+            }
+            BinaryOperator::Gt => {
+                let lt = left.less_than(&right)?;
+                let eq = left.equals(&right)?;
+                match (lt, eq) {
+                    (Value::Bool(a), Value::Bool(b)) => Ok(Value::Bool(!a && !b)),
+                    _ => unreachable!(),
+                }
+            }
+            BinaryOperator::Ge => {
+                let lt = left.less_than(&right)?;
+                match lt {
+                    Value::Bool(b) => Ok(Value::Bool(!b)),
+                    _ => unreachable!(),
+                }
+            }
+            BinaryOperator::And => {
+                if left.is_truthy() {
+                    Ok(right)
+                } else {
+                    Ok(left)
+                }
+            }
+            BinaryOperator::Or => {
+                if left.is_truthy() {
+                    Ok(left)
+                } else {
+                    Ok(right)
+                }
+            }
+            BinaryOperator::OptionalOr => {
+                // Implement proper Option handling for ?? operator
+                match &left {
+                    Value::Unit => Ok(right), // None ?? value = value
+                    _ => Ok(left),            // Some(value) ?? fallback = value
+                }
+            }
+            _ => Err(RuntimeError::TypeError {
+                message: format!("Unimplemented binary operator: {:?}", op),
+            }),
+        }
+    }
 }
 
 /// Runtime environment for variable bindings
@@ -378,6 +563,27 @@ impl Environment {
         self.bindings.insert(name, value);
     }
 
+    /// Names and arities of the builtin functions visible from this scope, sorted by name
+    pub fn builtins(&self) -> Vec<(String, usize)> {
+        let mut builtins: Vec<(String, usize)> = Vec::new();
+        let mut scope = Some(self);
+        while let Some(env) = scope {
+            for (name, value) in &env.bindings {
+                if let Value::BuiltinFunction { arity, .. }
+                | Value::InterpreterFunction { arity, .. } = value
+                {
+                    // Inner scopes shadow outer ones
+                    if !builtins.iter().any(|(seen, _)| seen == name) {
+                        builtins.push((name.clone(), *arity));
+                    }
+                }
+            }
+            scope = env.parent.as_deref();
+        }
+        builtins.sort();
+        builtins
+    }
+
     pub fn get(&self, name: &str) -> Option<&Value> {
         self.bindings
             .get(name)
@@ -412,17 +618,41 @@ impl Environment {
     }
 }
 
+/// A declared module: everything its body defined, and which of those are `pub`
+#[derive(Debug, Clone, Default)]
+pub struct Module {
+    pub members: HashMap<String, Value>,
+    pub exports: HashSet<String>,
+}
+
+impl Module {
+    /// An exported member, or an error saying why `member` can't be reached from outside
+    pub fn export(&self, module: &str, member: &str) -> RuntimeResult<Value> {
+        match self.members.get(member) {
+            Some(value) if self.exports.contains(member) => Ok(value.clone()),
+            Some(_) => Err(RuntimeError::PrivateMember {
+                module: module.to_string(),
+                member: member.to_string(),
+            }),
+            None => Err(RuntimeError::UndefinedVariable {
+                name: format!("{}::{}", module, member),
+            }),
+        }
+    }
+}
+
 /// Main interpreter for the matrix language
 pub struct Interpreter {
     pub environment: Environment,
     struct_registry: StructRegistry,
-    module_cache: HashMap<String, Environment>, // Cache for loaded modules
+    module_cache: HashMap<String, Module>, // Declared and loaded modules
     #[cfg(feature = "jit")]
-    jit_context: Option<JitContext>, // JIT compilation context
-    async_tasks: HashMap<usize, AsyncTask>,     // Track async tasks
-    next_task_id: usize,                        // Counter for task IDs
-    gpu_mode: GpuMode,                          // Current GPU computation mode
-    simulation_mode: Option<SimulationMode>,    // Current simulation mode for directives
+    jit_context: Option<JitContext<'static>>, // JIT compilation context
+    async_tasks: HashMap<usize, AsyncTask>, // Track async tasks
+    next_task_id: usize,                   // Counter for task IDs
+    gpu_mode: GpuMode,                     // Current GPU computation mode
+    simulation_mode: Option<SimulationMode>, // Current simulation mode for directives
+    cancel_flag: Option<Arc<AtomicBool>>,  // Set from outside to abort evaluation
 }
 
 impl Default for Interpreter {
@@ -443,6 +673,7 @@ impl Interpreter {
             next_task_id: 0,
             gpu_mode: GpuMode::Cpu,
             simulation_mode: None,
+            cancel_flag: None,
         };
 
         interpreter.register_builtins();
@@ -475,6 +706,52 @@ impl Interpreter {
             },
         );
 
+        self.environment.define(
+            "eval".to_string(),
+            Value::InterpreterFunction {
+                name: "eval".to_string(),
+                arity: 1,
+                func: |interpreter, args| match &args[0] {
+                    Value::String(source) => interpreter.eval_source(source),
+                    other => Err(RuntimeError::TypeError {
+                        message: format!("eval expects a string, found {}", other.type_name()),
+                    }),
+                },
+            },
+        );
+
+        self.environment.define(
+            "matrix_from_fn".to_string(),
+            Value::InterpreterFunction {
+                name: "matrix_from_fn".to_string(),
+                arity: 3,
+                func: |interpreter, args| interpreter.matrix_from_fn(&args[0], &args[1], &args[2]),
+            },
+        );
+
+        // Array combinators: map(f, array), filter(predicate, array), fold(f, initial, array)
+        let combinators: [(&str, usize, InterpreterFn); 3] = [
+            ("map", 2, |interpreter, args| {
+                interpreter.array_combinator("map", args)
+            }),
+            ("filter", 2, |interpreter, args| {
+                interpreter.array_combinator("filter", args)
+            }),
+            ("fold", 3, |interpreter, args| {
+                interpreter.array_combinator("fold", args)
+            }),
+        ];
+        for (name, arity, func) in combinators {
+            self.environment.define(
+                name.to_string(),
+                Value::InterpreterFunction {
+                    name: name.to_string(),
+                    arity,
+                    func,
+                },
+            );
+        }
+
         self.environment.define(
             "len".to_string(),
             Value::BuiltinFunction {
@@ -484,6 +761,7 @@ impl Interpreter {
                     Value::Array(arr) => Ok(Value::Int(arr.len() as i64)),
                     Value::Matrix(mat) => Ok(Value::Int(mat.len() as i64)),
                     Value::String(s) => Ok(Value::Int(s.len() as i64)),
+                    Value::Dict(dict) => Ok(Value::Int(dict.len() as i64)),
                     _ => Err(RuntimeError::TypeError {
                         message: format!("Cannot get length of {}", args[0].type_name()),
                     }),
@@ -491,6 +769,66 @@ impl Interpreter {
             },
         );
 
+        // `a[i]` is parsed as `index(a, i)`: array elements, matrix rows and dictionary
+        // entries by key
+        self.environment.define(
+            "index".to_string(),
+            Value::BuiltinFunction {
+                name: "index".to_string(),
+                arity: 2,
+                func: |args| match (&args[0], &args[1]) {
+                    (Value::Dict(dict), Value::String(key)) => {
+                        crate::stdlib::dict::lookup(dict, key)
+                    }
+                    (Value::Array(items), Value::Int(i)) => {
+                        Ok(items[checked_index(*i, items.len())?].clone())
+                    }
+                    (Value::Matrix(rows), Value::Int(i)) => {
+                        Ok(Value::Array(rows[checked_index(*i, rows.len())?].clone()))
+                    }
+                    (collection, index) => Err(RuntimeError::TypeError {
+                        message: format!(
+                            "Cannot index {} with {}",
+                            collection.type_name(),
+                            index.type_name()
+                        ),
+                    }),
+                },
+            },
+        );
+
+        // `a[start..end]` is parsed as `slice(a, start, end, false)` and `a[start..=end]`
+        // as `slice(a, start, end, true)`. Negative bounds count from the end.
+        self.environment.define(
+            "slice".to_string(),
+            Value::BuiltinFunction {
+                name: "slice".to_string(),
+                arity: 4,
+                func: |args| {
+                    let (Value::Int(start), Value::Int(end), Value::Bool(inclusive)) =
+                        (&args[1], &args[2], &args[3])
+                    else {
+                        return Err(RuntimeError::TypeError {
+                            message: "Slice bounds must be integers".to_string(),
+                        });
+                    };
+                    match &args[0] {
+                        Value::Array(items) => {
+                            let range = slice_range(*start, *end, *inclusive, items.len())?;
+                            Ok(Value::Array(items[range].to_vec()))
+                        }
+                        Value::Matrix(rows) => {
+                            let range = slice_range(*start, *end, *inclusive, rows.len())?;
+                            Ok(Value::Matrix(rows[range].to_vec()))
+                        }
+                        other => Err(RuntimeError::TypeError {
+                            message: format!("Cannot slice {}", other.type_name()),
+                        }),
+                    }
+                },
+            },
+        );
+
         self.environment.define(
             "abs".to_string(),
             Value::BuiltinFunction {
@@ -597,7 +935,7 @@ impl Interpreter {
             Item::FunctionDef(func_def) => {
                 let function_value = Value::Function {
                     params: func_def.params.clone(),
-                    body: func_def.body.clone(),
+                    body: Arc::new(func_def.body.clone()),
                     closure: self.environment.clone(),
                 };
 
@@ -630,10 +968,65 @@ impl Interpreter {
             }
 
             Item::Import(import) => self.eval_import(import),
+
+            Item::Module(module_def) => self.eval_module(module_def),
         }
     }
 
+    /// Evaluate a module body in its own scope, which sees everything defined before
+    /// the module, and record what it defined under the module's name
+    fn eval_module(&mut self, module_def: &ModuleDef) -> RuntimeResult<Value> {
+        let scope = Environment::with_parent(self.environment.clone());
+        let outer = std::mem::replace(&mut self.environment, scope);
+        let result = module_def
+            .items
+            .iter()
+            .try_for_each(|item| self.eval_item(item).map(drop));
+        let scope = std::mem::replace(&mut self.environment, outer);
+        result?;
+
+        self.module_cache.insert(
+            module_def.name.clone(),
+            Module {
+                members: scope.bindings,
+                exports: module_def.exports.iter().cloned().collect(),
+            },
+        );
+        Ok(Value::Unit)
+    }
+
+    /// Look up `Module::member`
+    fn eval_qualified_name(&self, name: &str) -> Option<RuntimeResult<Value>> {
+        let (module, member) = name.rsplit_once("::")?;
+        let module_value = self.module_cache.get(module)?;
+        Some(module_value.export(module, member))
+    }
+
+    /// Share a flag that aborts evaluation with a "cancelled" error once set.
+    /// It is checked before every expression, so long scripts stop promptly.
+    pub fn set_cancel_flag(&mut self, flag: Arc<AtomicBool>) {
+        self.cancel_flag = Some(flag);
+    }
+
+    fn check_cancelled(&self) -> RuntimeResult<()> {
+        match &self.cancel_flag {
+            Some(flag) if flag.load(Ordering::Relaxed) => Err(RuntimeError::Generic {
+                message: "cancelled".to_string(),
+            }),
+            _ => Ok(()),
+        }
+    }
+
+    /// Evaluate an expression. Errors carry the position of the innermost expression
+    /// that failed.
     pub fn eval_expression(&mut self, expr: &Expression) -> RuntimeResult<Value> {
+        self.eval_unlocated(expr)
+            .map_err(|error| error.at(expr.span()))
+    }
+
+    fn eval_unlocated(&mut self, expr: &Expression) -> RuntimeResult<Value> {
+        self.check_cancelled()?;
+
         match expr {
             Expression::IntLiteral(value, _) => Ok(Value::Int(*value)),
             Expression::FloatLiteral(value, _) => Ok(Value::Float(*value)),
@@ -643,6 +1036,8 @@ impl Interpreter {
             Expression::Identifier(name, _) => {
                 if let Some(value) = self.environment.get(name) {
                     Ok(value.clone())
+                } else if let Some(result) = self.eval_qualified_name(name) {
+                    result
                 } else {
                     Err(RuntimeError::UndefinedVariable { name: name.clone() })
                 }
@@ -673,6 +1068,8 @@ impl Interpreter {
 
             Expression::MatrixLiteral(rows, _) => self.eval_matrix_literal(rows),
 
+            Expression::DictLiteral(entries, _) => self.eval_dict_literal(entries),
+
             Expression::MatrixComprehension {
                 element,
                 generators,
@@ -686,6 +1083,17 @@ impl Interpreter {
                 ..
             } => self.eval_if_expression(condition, then_branch, else_branch),
 
+            Expression::While {
+                condition, body, ..
+            } => self.eval_while_expression(condition, body),
+
+            Expression::For {
+                variable,
+                iterable,
+                body,
+                ..
+            } => self.eval_for_expression(variable, iterable, body),
+
             Expression::Match {
                 expression, arms, ..
             } => self.eval_match_expression(expression, arms),
@@ -694,7 +1102,7 @@ impl Interpreter {
 
             Expression::Lambda { params, body, .. } => Ok(Value::Function {
                 params: params.clone(),
-                body: (**body).clone(),
+                body: Arc::new((**body).clone()),
                 closure: self.environment.clone(),
             }),
 
@@ -739,90 +1147,7 @@ impl Interpreter {
         let left_val = self.eval_expression(left)?;
         let right_val = self.eval_expression(right)?;
 
-        match op {
-            BinaryOperator::Add => left_val.add(&right_val),
-            BinaryOperator::Sub => left_val.subtract(&right_val),
-            BinaryOperator::Mul => left_val.multiply(&right_val),
-            BinaryOperator::Div => left_val.divide(&right_val),
-            BinaryOperator::Pow => match (&left_val, &right_val) {
-                (Value::Int(base), Value::Int(exp)) => {
-                    if *exp >= 0 {
-                        Ok(Value::Int((*base as f64).powf(*exp as f64) as i64))
-                    } else {
-                        Ok(Value::Float((*base as f64).powf(*exp as f64)))
-                    }
-                }
-                (Value::Float(base), Value::Float(exp)) => Ok(Value::Float(base.powf(*exp))),
-                (Value::Int(base), Value::Float(exp)) => {
-                    Ok(Value::Float((*base as f64).powf(*exp)))
-                }
-                (Value::Float(base), Value::Int(exp)) => Ok(Value::Float(base.powf(*exp as f64))),
-                _ => Err(RuntimeError::TypeError {
-                    message: format!(
-                        "Cannot compute power of {} and {}",
-                        left_val.type_name(),
-                        right_val.type_name()
-                    ),
-                }),
-            },
-            BinaryOperator::MatMul => left_val.matrix_multiply(&right_val),
-            BinaryOperator::Eq => left_val.equals(&right_val),
-            BinaryOperator::Ne => {
-                let eq_result = left_val.equals(&right_val)?;
-                match eq_result {
-                    Value::Bool(b) => Ok(Value::Bool(!b)),
-                    _ => unreachable!(),
-                }
-            }
-            BinaryOperator::Lt => left_val.less_than(&right_val),
-            BinaryOperator::Le => {
-                let lt = left_val.less_than(&right_val)?;
-                let eq = left_val.equals(&right_val)?;
-                match (lt, eq) {
-                    (Value::Bool(a), Value::Bool(b)) => Ok(Value::Bool(a || b)),
-                    _ => unreachable!(),
-                }
-            }
-            BinaryOperator::Gt => {
-                let lt = left_val.less_than(&right_val)?;
-                let eq = left_val.equals(&right_val)?;
-                match (lt, eq) {
-                    (Value::Bool(a), Value::Bool(b)) => Ok(Value::Bool(!a && !b)),
-                    _ => unreachable!(),
-                }
-            }
-            BinaryOperator::Ge => {
-                let lt = left_val.less_than(&right_val)?;
-                match lt {
-                    Value::Bool(b) => Ok(Value::Bool(!b)),
-                    _ => unreachable!(),
-                }
-            }
-            BinaryOperator::And => {
-                if left_val.is_truthy() {
-                    Ok(right_val)
-                } else {
-                    Ok(left_val)
-                }
-            }
-            BinaryOperator::Or => {
-                if left_val.is_truthy() {
-                    Ok(left_val)
-                } else {
-                    Ok(right_val)
-                }
-            }
-            BinaryOperator::OptionalOr => {
-                // Implement proper Option handling for ?? operator
-                match &left_val {
-                    Value::Unit => Ok(right_val), // None ?? value = value
-                    _ => Ok(left_val),            // Some(value) ?? fallback = value
-                }
-            }
-            _ => Err(RuntimeError::TypeError {
-                message: format!("Unimplemented binary operator: {:?}", op),
-            }),
-        }
+        Value::apply_binary_op(left_val, op, right_val)
     }
 
     fn eval_unary_op(&mut self, op: &UnaryOperator, expr: &Expression) -> RuntimeResult<Value> {
@@ -887,58 +1212,177 @@ impl Interpreter {
             args.iter().map(|arg| self.eval_expression(arg)).collect();
         let arg_values = arg_values?;
 
+        // Try JIT execution first if function name is available
+        if let (Value::Function { params, body, .. }, Expression::Identifier(func_name, _)) =
+            (&func_value, func)
+        {
+            #[cfg(feature = "jit")]
+            if let Some(ref jit) = self.jit_context {
+                // Check if function is JIT compiled
+                if let Ok(result) = jit.execute_function(func_name, &arg_values) {
+                    return Ok(result);
+                }
+            }
+
+            let (params, body) = (params.clone(), body.clone());
+            return self.call_named_function(func_name, func_value, &params, &body, arg_values);
+        }
+
+        self.call_value(func_value, &arg_values)
+    }
+
+    /// Call a function through the name it is bound to. Inside the call that name refers
+    /// to the function itself, so let-bound lambdas can recurse even though their
+    /// closure was captured before the binding existed. A call to that name in tail
+    /// position (the whole body, a branch of an `if`, or a block's result) loops in the
+    /// same frame instead of recursing, so tail recursion runs in constant stack.
+    fn call_named_function(
+        &mut self,
+        name: &str,
+        function: Value,
+        params: &[Parameter],
+        body: &Arc<Expression>,
+        mut arg_values: Vec<Value>,
+    ) -> RuntimeResult<Value> {
+        let Value::Function { closure, .. } = &function else {
+            unreachable!("call_named_function is only given functions");
+        };
+        let mut frame = Environment::with_parent(closure.clone());
+        frame.define(name.to_string(), function);
+        let old_env = std::mem::replace(&mut self.environment, frame);
+
+        let result = loop {
+            if let Err(e) = Self::check_arity(params, arg_values.len()) {
+                break Err(e);
+            }
+            // Each iteration starts from a fresh frame holding only the function itself
+            self.environment
+                .bindings
+                .retain(|binding, _| binding == name);
+            let step = self
+                .bind_parameters(params, &arg_values)
+                .and_then(|()| self.eval_tail(body, name, body));
+            match step {
+                Ok(ControlFlow::Break(value)) => break Ok(value),
+                Ok(ControlFlow::Continue(next_args)) => arg_values = next_args,
+                Err(e) => break Err(e),
+            }
+        };
+
+        self.environment = old_env;
+        result
+    }
+
+    /// Evaluate an expression in tail position of the function `name`. A tail self-call
+    /// is not made; its arguments are returned as `Continue` instead.
+    fn eval_tail(
+        &mut self,
+        expr: &Expression,
+        name: &str,
+        body: &Arc<Expression>,
+    ) -> RuntimeResult<ControlFlow<Value, Vec<Value>>> {
+        match expr {
+            Expression::IfExpression {
+                condition,
+                then_branch,
+                else_branch,
+                ..
+            } => {
+                self.check_cancelled()?;
+                if self.eval_expression(condition)?.is_truthy() {
+                    self.eval_tail(then_branch, name, body)
+                } else if let Some(else_branch) = else_branch {
+                    self.eval_tail(else_branch, name, body)
+                } else {
+                    Ok(ControlFlow::Break(Value::Unit))
+                }
+            }
+            Expression::Block {
+                statements,
+                result: Some(result),
+                ..
+            } => {
+                self.eval_statements(statements)?;
+                self.eval_tail(result, name, body)
+            }
+            Expression::FunctionCall { function, args, .. }
+                if self.is_self_call(function, name, body) =>
+            {
+                self.check_cancelled()?;
+                let arg_values = args
+                    .iter()
+                    .map(|arg| self.eval_expression(arg))
+                    .collect::<RuntimeResult<_>>()?;
+                Ok(ControlFlow::Continue(arg_values))
+            }
+            _ => self.eval_expression(expr).map(ControlFlow::Break),
+        }
+    }
+
+    /// Whether `function` names the function being run, i.e. `name` has not been
+    /// rebound to something else in the call's frame
+    fn is_self_call(&self, function: &Expression, name: &str, body: &Arc<Expression>) -> bool {
+        match (function, self.environment.bindings.get(name)) {
+            (Expression::Identifier(called, _), Some(Value::Function { body: bound, .. })) => {
+                called == name && Arc::ptr_eq(bound, body)
+            }
+            _ => false,
+        }
+    }
+
+    fn check_arity(params: &[Parameter], count: usize) -> RuntimeResult<()> {
+        let required = params.iter().filter(|p| p.default.is_none()).count();
+        if count < required || count > params.len() {
+            let expected = if required == params.len() {
+                required.to_string()
+            } else {
+                format!("{} to {}", required, params.len())
+            };
+            return Err(RuntimeError::FunctionCallError {
+                message: format!("Expected {} arguments, got {}", expected, count),
+            });
+        }
+        Ok(())
+    }
+
+    /// Call a function value with already-evaluated arguments
+    pub fn call_value(&mut self, func_value: Value, arg_values: &[Value]) -> RuntimeResult<Value> {
         match func_value {
             Value::Function {
                 params,
                 body,
                 closure,
             } => {
-                if params.len() != arg_values.len() {
-                    return Err(RuntimeError::FunctionCallError {
-                        message: format!(
-                            "Expected {} arguments, got {}",
-                            params.len(),
-                            arg_values.len()
-                        ),
-                    });
-                }
-
-                // Try JIT execution first if function name is available
-                if let Expression::Identifier(_func_name, _) = func {
-                    #[cfg(feature = "jit")]
-                    if let Some(ref jit) = self.jit_context {
-                        // Check if function is JIT compiled
-                        if let Ok(result) = jit.execute_function(_func_name, &arg_values) {
-                            return Ok(result);
-                        }
-                    }
-                }
-
-                // Fall back to interpreter execution
-                // Create new environment with closure as parent
-                let mut new_env = Environment::with_parent(closure);
-
-                // Bind parameters
-                for (param, arg_value) in params.iter().zip(arg_values.iter()) {
-                    new_env.define(param.name.clone(), arg_value.clone());
-                }
+                Self::check_arity(&params, arg_values.len())?;
 
-                // Swap environments and evaluate body
+                // Swap in a new environment with closure as parent, bind parameters and
+                // evaluate body
+                let new_env = Environment::with_parent(closure);
                 let old_env = std::mem::replace(&mut self.environment, new_env);
-                let result = self.eval_expression(&body);
+                let result = self
+                    .bind_parameters(&params, arg_values)
+                    .and_then(|()| self.eval_expression(&body));
                 self.environment = old_env;
 
                 result
             }
 
-            Value::BuiltinFunction { func, arity, .. } => {
+            Value::InterpreterFunction { func, arity, .. } => {
                 if arg_values.len() != arity {
                     return Err(RuntimeError::FunctionCallError {
                         message: format!("Expected {} arguments, got {}", arity, arg_values.len()),
                     });
                 }
+                func(self, arg_values)
+            }
 
-                func(&arg_values)
+            Value::BuiltinFunction { func, arity, .. } => {
+                if arity != VARIADIC && arg_values.len() != arity {
+                    return Err(RuntimeError::FunctionCallError {
+                        message: format!("Expected {} arguments, got {}", arity, arg_values.len()),
+                    });
+                }
+                func(arg_values)
             }
 
             _ => Err(RuntimeError::TypeError {
@@ -947,6 +1391,201 @@ impl Interpreter {
         }
     }
 
+    /// Lex, parse and evaluate source in the current environment. A lone expression
+    /// yields its value; otherwise the source is run as a program (so `let` bindings
+    /// stay defined) and yields its last item's value.
+    pub fn eval_source(&mut self, source: &str) -> RuntimeResult<Value> {
+        let parse_error = |e: crate::parser::ParseError| RuntimeError::Generic {
+            message: format!("Parse error in eval: {}", e),
+        };
+        let parser = || crate::parser::Parser::new(crate::lexer::Lexer::new(source));
+
+        let mut expression_parser = parser().map_err(parse_error)?;
+        if let Ok(expr) = expression_parser.parse_expression() {
+            if expression_parser.is_at_end() {
+                return self.eval_expression(&expr);
+            }
+        }
+
+        let program = parser()
+            .and_then(|mut p| p.parse_program())
+            .map_err(parse_error)?;
+        self.eval_program(&program)
+    }
+
+    /// Build a matrix by calling `cell(row, col)` for every cell, row by row
+    fn matrix_from_fn(&mut self, rows: &Value, cols: &Value, cell: &Value) -> RuntimeResult<Value> {
+        let (rows, cols) = crate::stdlib::linalg::expect_dimensions(rows, cols)?;
+        let mut matrix = Vec::with_capacity(rows);
+        for i in 0..rows {
+            let mut row = Vec::with_capacity(cols);
+            for j in 0..cols {
+                row.push(
+                    self.call_value(cell.clone(), &[Value::Int(i as i64), Value::Int(j as i64)])?,
+                );
+            }
+            matrix.push(row);
+        }
+        Ok(Value::Matrix(matrix))
+    }
+
+    /// `map`, `filter` or `fold` over the array in the last argument
+    fn array_combinator(&mut self, name: &str, args: &[Value]) -> RuntimeResult<Value> {
+        let items = match args.last() {
+            Some(Value::Array(items)) => items,
+            Some(other) => {
+                return Err(RuntimeError::TypeError {
+                    message: format!("{} expects an array, found {}", name, other.type_name()),
+                })
+            }
+            None => unreachable!("arity is checked by call_value"),
+        };
+        let function = &args[0];
+
+        match name {
+            "map" => items
+                .iter()
+                .map(|item| self.call_value(function.clone(), std::slice::from_ref(item)))
+                .collect::<RuntimeResult<_>>()
+                .map(Value::Array),
+            "filter" => {
+                let mut kept = Vec::new();
+                for item in items {
+                    match self.call_value(function.clone(), std::slice::from_ref(item))? {
+                        Value::Bool(true) => kept.push(item.clone()),
+                        Value::Bool(false) => {}
+                        other => {
+                            return Err(RuntimeError::TypeError {
+                                message: format!(
+                                    "filter predicate must return Bool, found {}",
+                                    other.type_name()
+                                ),
+                            })
+                        }
+                    }
+                }
+                Ok(Value::Array(kept))
+            }
+            _ => {
+                let mut acc = args[1].clone();
+                for item in items {
+                    acc = self.call_value(function.clone(), &[acc, item.clone()])?;
+                }
+                Ok(acc)
+            }
+        }
+    }
+
+    /// Bind arguments to parameters in the current environment. Omitted trailing
+    /// arguments take their parameter's default, evaluated after the earlier
+    /// parameters are bound so defaults can refer to them.
+    fn bind_parameters(&mut self, params: &[Parameter], arg_values: &[Value]) -> RuntimeResult<()> {
+        for (i, param) in params.iter().enumerate() {
+            let value = match (arg_values.get(i), &param.default) {
+                (Some(value), _) => value.clone(),
+                (None, Some(default)) => self.eval_expression(default)?,
+                (None, None) => {
+                    return Err(RuntimeError::FunctionCallError {
+                        message: format!("Missing argument '{}'", param.name),
+                    })
+                }
+            };
+            self.environment.define(param.name.clone(), value);
+        }
+        Ok(())
+    }
+
+    /// Step a physics world for `physics_step`. The script functions the world consults
+    /// during a step run first and its collision callbacks afterwards, since the world
+    /// cannot call back into the interpreter.
+    pub(crate) fn physics_step(&mut self, args: &[Value]) -> RuntimeResult<Value> {
+        self.prepare_physics_step(args)?;
+        let result = crate::stdlib::physics::step_world(&args[0])?;
+        self.run_collision_callbacks(args)?;
+        Ok(result)
+    }
+
+    /// Run the script force field and collision filter of the world about to be stepped
+    fn prepare_physics_step(&mut self, args: &[Value]) -> RuntimeResult<()> {
+        let Some(&Value::Int(world_id)) = args.first() else {
+            return Ok(());
+        };
+        let world_id = world_id as usize;
+        self.run_force_field(world_id)?;
+        self.run_collision_filter(world_id)
+    }
+
+    /// Evaluate a world's script force field at each movable body and apply the forces
+    fn run_force_field(&mut self, world_id: usize) -> RuntimeResult<()> {
+        let Some((field, targets)) = crate::stdlib::physics::script_force_field_targets(world_id)
+        else {
+            return Ok(());
+        };
+
+        let mut forces = Vec::with_capacity(targets.len());
+        for (id, position) in targets {
+            let force = self.call_value(
+                field.clone(),
+                &[crate::stdlib::physics::vec3_to_value(position)],
+            )?;
+            forces.push((
+                id,
+                crate::stdlib::physics::expect_vec3(&force, "Force field result")?,
+            ));
+        }
+        crate::stdlib::physics::apply_script_forces(world_id, forces);
+        Ok(())
+    }
+
+    /// Ask a world's script collision filter about each pair that may touch and hand
+    /// the rejected pairs to the world
+    fn run_collision_filter(&mut self, world_id: usize) -> RuntimeResult<()> {
+        let Some((filter, candidates)) =
+            crate::stdlib::physics::collision_filter_candidates(world_id)
+        else {
+            return Ok(());
+        };
+
+        let mut rejected = HashSet::new();
+        for (a, b) in candidates {
+            match self.call_value(
+                filter.clone(),
+                &[Value::Int(a as i64), Value::Int(b as i64)],
+            )? {
+                Value::Bool(true) => {}
+                Value::Bool(false) => {
+                    rejected.insert((a, b));
+                }
+                other => {
+                    return Err(RuntimeError::TypeError {
+                        message: format!(
+                            "Collision filter must return a boolean, found {}",
+                            other.type_name()
+                        ),
+                    })
+                }
+            }
+        }
+        crate::stdlib::physics::set_filtered_pairs(world_id, rejected);
+        Ok(())
+    }
+
+    /// Run the collision callbacks of the world `physics_step` just stepped. Every
+    /// callback runs even if an earlier one fails; the first error is returned.
+    fn run_collision_callbacks(&mut self, args: &[Value]) -> RuntimeResult<()> {
+        let Some(&Value::Int(world_id)) = args.first() else {
+            return Ok(());
+        };
+
+        let mut first_error = None;
+        for (callback, args) in crate::stdlib::physics::collision_callbacks(world_id as usize) {
+            if let Err(err) = self.call_value(callback, &args) {
+                first_error.get_or_insert(err);
+            }
+        }
+        first_error.map_or(Ok(()), Err)
+    }
+
     fn eval_field_access(&mut self, expr: &Expression, field: &str) -> RuntimeResult<Value> {
         let value = self.eval_expression(expr)?;
 
@@ -972,6 +1611,14 @@ impl Interpreter {
         Ok(Value::Array(values?))
     }
 
+    fn eval_dict_literal(&mut self, entries: &[(String, Expression)]) -> RuntimeResult<Value> {
+        let mut dict = HashMap::new();
+        for (key, value) in entries {
+            dict.insert(key.clone(), self.eval_expression(value)?);
+        }
+        Ok(Value::Dict(dict))
+    }
+
     fn eval_matrix_literal(&mut self, rows: &[Vec<Expression>]) -> RuntimeResult<Value> {
         let mut matrix_rows = Vec::new();
 
@@ -1001,9 +1648,83 @@ impl Interpreter {
         }
     }
 
+    fn eval_while_expression(
+        &mut self,
+        condition: &Expression,
+        body: &Expression,
+    ) -> RuntimeResult<Value> {
+        loop {
+            match self.eval_expression(condition)? {
+                Value::Bool(true) => {
+                    self.eval_expression(body)?;
+                }
+                Value::Bool(false) => return Ok(Value::Unit),
+                other => {
+                    return Err(RuntimeError::TypeError {
+                        message: format!(
+                            "While condition must be Bool, found {}",
+                            other.type_name()
+                        ),
+                    })
+                }
+            }
+        }
+    }
+
+    fn eval_for_expression(
+        &mut self,
+        variable: &str,
+        iterable: &Expression,
+        body: &Expression,
+    ) -> RuntimeResult<Value> {
+        let items = match self.eval_expression(iterable)? {
+            Value::Array(items) => items,
+            other => {
+                return Err(RuntimeError::TypeError {
+                    message: format!(
+                        "For loop needs an array or range, found {}",
+                        other.type_name()
+                    ),
+                })
+            }
+        };
+
+        // Like a while body, the body runs in the enclosing scope so its `let`s carry
+        // over between iterations; only the loop variable is put back afterwards.
+        // Closures still capture each iteration's value, as they copy their scope.
+        let shadowed = self.environment.bindings.get(variable).cloned();
+        let result = items.into_iter().try_for_each(|item| {
+            self.environment.define(variable.to_string(), item);
+            self.eval_expression(body).map(|_| ())
+        });
+        match shadowed {
+            Some(value) => self.environment.define(variable.to_string(), value),
+            None => {
+                self.environment.bindings.remove(variable);
+            }
+        }
+        result.map(|_| Value::Unit)
+    }
+
     /// Evaluate import statement
     fn eval_import(&mut self, import: &Import) -> RuntimeResult<Value> {
-        // For now, just return Unit - full module system would be implemented here
+        // A declared module's exports come into scope unqualified, all of them or only
+        // the listed ones
+        if let Some(module) = self.module_cache.get(&import.module_path) {
+            let names: Vec<String> = match &import.items {
+                Some(items) => items.clone(),
+                None => module.exports.iter().cloned().collect(),
+            };
+            let values = names
+                .iter()
+                .map(|name| module.export(&import.module_path, name))
+                .collect::<RuntimeResult<Vec<_>>>()?;
+            for (name, value) in names.into_iter().zip(values) {
+                self.environment.define(name, value);
+            }
+            return Ok(Value::Unit);
+        }
+
         match import.module_path.as_str() {
             "std" => {
                 // Load standard library
@@ -1058,17 +1779,42 @@ impl Interpreter {
         let value = self.eval_expression(expression)?;
 
         for arm in arms {
-            // Simplified pattern matching - just check if patterns match
-            if self.pattern_matches(&arm.pattern, &value)? {
-                if let Some(ref guard) = arm.guard {
-                    let guard_result = self.eval_expression(guard)?;
-                    if let Value::Bool(true) = guard_result {
-                        return self.eval_expression(&arm.body);
+            let mut bindings = Vec::new();
+            if !Self::pattern_matches(&arm.pattern, &value, &mut bindings) {
+                continue;
+            }
+
+            // The arm's variables shadow outer bindings only while the guard and body run
+            let shadowed: Vec<(String, Option<Value>)> = bindings
+                .iter()
+                .map(|(name, _)| (name.clone(), self.environment.bindings.get(name).cloned()))
+                .collect();
+            for (name, bound) in bindings {
+                self.environment.define(name, bound);
+            }
+
+            let result = match &arm.guard {
+                Some(guard) => self.eval_expression(guard).and_then(|passed| {
+                    if passed == Value::Bool(true) {
+                        self.eval_expression(&arm.body).map(Some)
+                    } else {
+                        Ok(None)
+                    }
+                }),
+                None => self.eval_expression(&arm.body).map(Some),
+            };
+
+            for (name, previous) in shadowed.into_iter().rev() {
+                match previous {
+                    Some(previous) => self.environment.define(name, previous),
+                    None => {
+                        self.environment.bindings.remove(&name);
                     }
-                } else {
-                    return self.eval_expression(&arm.body);
                 }
             }
+            if let Some(value) = result? {
+                return Ok(value);
+            }
         }
 
         Err(RuntimeError::Generic {
@@ -1076,18 +1822,31 @@ impl Interpreter {
         })
     }
 
-    /// Check if a pattern matches a value
-    fn pattern_matches(&self, pattern: &Pattern, value: &Value) -> RuntimeResult<bool> {
+    /// Check if a pattern matches a value, collecting the variables it binds
+    fn pattern_matches(
+        pattern: &Pattern,
+        value: &Value,
+        bindings: &mut Vec<(String, Value)>,
+    ) -> bool {
         match (pattern, value) {
-            (Pattern::Wildcard(_), _) => Ok(true),
-            (Pattern::Identifier(_, _), _) => Ok(true), // Variables always match
-            (Pattern::IntLiteral(pat_val, _), Value::Int(val)) => Ok(pat_val == val),
+            (Pattern::Wildcard(_), _) => true,
+            (Pattern::Identifier(name, _), _) => {
+                bindings.push((name.clone(), value.clone()));
+                true
+            }
+            (Pattern::IntLiteral(pat_val, _), Value::Int(val)) => pat_val == val,
             (Pattern::FloatLiteral(pat_val, _), Value::Float(val)) => {
-                Ok((pat_val - val).abs() < f64::EPSILON)
+                (pat_val - val).abs() < f64::EPSILON
             }
-            (Pattern::BoolLiteral(pat_val, _), Value::Bool(val)) => Ok(pat_val == val),
-            (Pattern::StringLiteral(pat_val, _), Value::String(val)) => Ok(pat_val == val),
-            _ => Ok(false),
+            (Pattern::BoolLiteral(pat_val, _), Value::Bool(val)) => pat_val == val,
+            (Pattern::StringLiteral(pat_val, _), Value::String(val)) => pat_val == val,
+            (Pattern::Some(inner, _), Value::Option(Some(val)))
+            | (Pattern::Ok(inner, _), Value::Result(Ok(val)))
+            | (Pattern::Err(inner, _), Value::Result(Err(val))) => {
+                Self::pattern_matches(inner, val, bindings)
+            }
+            (Pattern::None(_), Value::Option(None)) => true,
+            _ => false,
         }
     }
 
@@ -1214,9 +1973,19 @@ impl Interpreter {
         statements: &[Statement],
         result: &Option<Box<Expression>>,
     ) -> RuntimeResult<Value> {
-        let mut last_value = Value::Unit;
+        let last_value = self.eval_statements(statements)?;
 
-        // Execute all statements in sequence
+        // If there's a result expression, evaluate it; otherwise return last statement value
+        if let Some(result_expr) = result {
+            self.eval_expression(result_expr)
+        } else {
+            Ok(last_value)
+        }
+    }
+
+    /// Execute statements in sequence in the current scope, returning the last one's value
+    fn eval_statements(&mut self, statements: &[Statement]) -> RuntimeResult<Value> {
+        let mut last_value = Value::Unit;
         for statement in statements {
             match statement {
                 Statement::Expression(expr) => {
@@ -1230,13 +1999,7 @@ impl Interpreter {
                 }
             }
         }
-
-        // If there's a result expression, evaluate it; otherwise return last statement value
-        if let Some(result_expr) = result {
-            self.eval_expression(result_expr)
-        } else {
-            Ok(last_value)
-        }
+        Ok(last_value)
     }
 
     /// Evaluate parallel expressions using simplified approach
@@ -1531,26 +2294,31 @@ impl Interpreter {
     #[cfg(feature = "jit")]
     fn contains_mathematical_operations(&self, expr: &Expression) -> bool {
         match expr {
-            Expression::Binary {
-                op, left, right, ..
+            Expression::BinaryOp {
+                operator,
+                left,
+                right,
+                ..
             } => {
                 matches!(
-                    op,
+                    operator,
                     BinaryOperator::Add
-                        | BinaryOperator::Subtract
-                        | BinaryOperator::Multiply
-                        | BinaryOperator::Divide
-                        | BinaryOperator::Modulo
-                        | BinaryOperator::Power
+                        | BinaryOperator::Sub
+                        | BinaryOperator::Mul
+                        | BinaryOperator::Div
+                        | BinaryOperator::Mod
+                        | BinaryOperator::Pow
                 ) || self.contains_mathematical_operations(left)
                     || self.contains_mathematical_operations(right)
             }
-            Expression::Unary { op, expr, .. } => {
-                matches!(op, UnaryOperator::Minus | UnaryOperator::Plus)
-                    || self.contains_mathematical_operations(expr)
+            Expression::UnaryOp {
+                operator, operand, ..
+            } => {
+                matches!(operator, UnaryOperator::Neg)
+                    || self.contains_mathematical_operations(operand)
             }
             Expression::FunctionCall { .. } => true, // Function calls can benefit from JIT
-            Expression::If {
+            Expression::IfExpression {
                 condition,
                 then_branch,
                 else_branch,
@@ -1560,17 +2328,19 @@ impl Interpreter {
                     || self.contains_mathematical_operations(then_branch)
                     || else_branch
                         .as_ref()
-                        .map_or(false, |e| self.contains_mathematical_operations(e))
+                        .is_some_and(|e| self.contains_mathematical_operations(e))
             }
             Expression::Block {
                 statements, result, ..
             } => {
-                statements
-                    .iter()
-                    .any(|s| self.contains_mathematical_operations(s))
-                    || result
-                        .as_ref()
-                        .map_or(false, |r| self.contains_mathematical_operations(r))
+                statements.iter().any(|s| match s {
+                    Statement::Expression(expr) => self.contains_mathematical_operations(expr),
+                    Statement::LetBinding(binding) => {
+                        self.contains_mathematical_operations(&binding.value)
+                    }
+                }) || result
+                    .as_ref()
+                    .is_some_and(|r| self.contains_mathematical_operations(r))
             }
             _ => false,
         }
@@ -1598,18 +2368,20 @@ impl Interpreter {
             return Ok(());
         }
 
-        // For now, create a simple mock module environment
-        let mut module_env = Environment::new();
-        module_env.set_variable(format!("{}_loaded", module_name), Value::Bool(true));
+        // For now, create a simple mock module
+        let loaded = format!("{}_loaded", module_name);
+        let module = Module {
+            members: HashMap::from([(loaded.clone(), Value::Bool(true))]),
+            exports: HashSet::from([loaded]),
+        };
 
         // Cache the module
-        self.module_cache
-            .insert(module_name.to_string(), module_env);
+        self.module_cache.insert(module_name.to_string(), module);
         Ok(())
     }
 
-    /// Get a cached module environment
-    pub fn get_module(&self, module_name: &str) -> Option<&Environment> {
+    /// Get a declared or cached module
+    pub fn get_module(&self, module_name: &str) -> Option<&Module> {
         self.module_cache.get(module_name)
     }
 
@@ -1706,7 +2478,7 @@ impl Interpreter {
         // Launch 3D simulation
         self.launch_3d_simulation(&physics_world)?;
 
-        Ok(Value::PhysicsWorld(physics_world))
+        Ok(Value::PhysicsWorld(Box::new(physics_world)))
     }
 
     /// Run plot animation from expression
@@ -1727,7 +2499,7 @@ impl Interpreter {
         // Launch plot animation
         self.launch_plot_animation(&physics_world)?;
 
-        Ok(Value::PhysicsWorld(physics_world))
+        Ok(Value::PhysicsWorld(Box::new(physics_world)))
     }
 
     /// Launch simulation engine with given context
@@ -1741,6 +2513,61 @@ impl Interpreter {
     }
 }
 
+/// `index` as a position in a collection of `length` items
+fn checked_index(index: i64, length: usize) -> RuntimeResult<usize> {
+    let Ok(position) = usize::try_from(index) else {
+        return Err(RuntimeError::Generic {
+            message: format!("Index must not be negative, got {}", index),
+        });
+    };
+    if position >= length {
+        return Err(RuntimeError::IndexOutOfBounds {
+            index: position,
+            length,
+        });
+    }
+    Ok(position)
+}
+
+/// Positions covered by a slice of a collection of `length` elements. Negative bounds
+/// count back from the end, so `-1` is the last element.
+fn slice_range(
+    start: i64,
+    end: i64,
+    inclusive: bool,
+    length: usize,
+) -> RuntimeResult<std::ops::Range<usize>> {
+    let resolve = |bound: i64| {
+        let position = if bound < 0 {
+            length as i64 + bound
+        } else {
+            bound
+        };
+        usize::try_from(position).map_err(|_| RuntimeError::Generic {
+            message: format!(
+                "Slice bound {} is before the start of {} elements",
+                bound, length
+            ),
+        })
+    };
+
+    let start = resolve(start)?;
+    let end = if inclusive {
+        checked_index(resolve(end)? as i64, length)? + 1
+    } else {
+        resolve(end)?
+    };
+    if end > length {
+        return Err(RuntimeError::IndexOutOfBounds { index: end, length });
+    }
+    if start > end {
+        return Err(RuntimeError::Generic {
+            message: format!("Slice starts at {} after it ends at {}", start, end),
+        });
+    }
+    Ok(start..end)
+}
+
 fn format_value(value: &Value) -> String {
     match value {
         Value::Int(i) => i.to_string(),
@@ -1762,6 +2589,18 @@ fn format_value(value: &Value) -> String {
                 .collect();
             format!("[{}]", rows.join(", "))
         }
+        Value::Dict(dict) => {
+            let mut entries: Vec<String> = dict
+                .iter()
+                .map(|(k, v)| format!("{}: {}", k, format_value(v)))
+                .collect();
+            entries.sort();
+            format!("{{{}}}", entries.join(", "))
+        }
+        Value::Option(Some(value)) => format!("Some({})", format_value(value)),
+        Value::Option(None) => "None".to_string(),
+        Value::Result(Ok(value)) => format!("Ok({})", format_value(value)),
+        Value::Result(Err(error)) => format!("Err({})", format_value(error)),
         Value::Struct { name, fields } => {
             let field_strs: Vec<String> = fields
                 .iter()
@@ -1770,7 +2609,9 @@ fn format_value(value: &Value) -> String {
             format!("{} {{ {} }}", name, field_strs.join(", "))
         }
         Value::Function { .. } => "<function>".to_string(),
-        Value::BuiltinFunction { name, .. } => format!("<builtin: {}>", name),
+        Value::BuiltinFunction { name, .. } | Value::InterpreterFunction { name, .. } => {
+            format!("<builtin: {}>", name)
+        }
         Value::AsyncHandle(_) => "<async handle>".to_string(),
         Value::PhysicsWorld(world) => format!("<physics_world:{}>", world.id),
         Value::PhysicsObject(obj) => format!("<physics_object:{}>", obj.id),
@@ -1799,6 +2640,16 @@ impl std::fmt::Display for Value {
                     .collect();
                 write!(f, "[{}]", rows.join(", "))
             }
+            Value::Dict(dict) => {
+                let mut entries: Vec<String> =
+                    dict.iter().map(|(k, v)| format!("{}: {}", k, v)).collect();
+                entries.sort();
+                write!(f, "{{{}}}", entries.join(", "))
+            }
+            Value::Option(Some(value)) => write!(f, "Some({})", value),
+            Value::Option(None) => write!(f, "None"),
+            Value::Result(Ok(value)) => write!(f, "Ok({})", value),
+            Value::Result(Err(error)) => write!(f, "Err({})", error),
             Value::Struct { name, fields } => {
                 let field_strs: Vec<String> = fields
                     .iter()
@@ -1809,11 +2660,21 @@ impl std::fmt::Display for Value {
             Value::Function { params, .. } => {
                 let param_strs: Vec<String> = params
                     .iter()
-                    .map(|p| format!("{}: {:?}", p.name, p.type_annotation))
+                    .map(|p| {
+                        if p.is_untyped() {
+                            p.name.clone()
+                        } else {
+                            format!("{}: {:?}", p.name, p.type_annotation)
+                        }
+                    })
                     .collect();
                 write!(f, "fn({})", param_strs.join(", "))
             }
-            Value::BuiltinFunction { name, arity, .. } => {
+            Value::BuiltinFunction { name, arity, .. } if *arity == VARIADIC => {
+                write!(f, "builtin {}(...)", name)
+            }
+            Value::BuiltinFunction { name, arity, .. }
+            | Value::InterpreterFunction { name, arity, .. } => {
                 write!(f, "builtin {}({})", name, arity)
             }
             Value::AsyncHandle(task) => {
@@ -1848,6 +2709,9 @@ impl PartialEq for Value {
             (Value::Unit, Value::Unit) => true,
             (Value::Array(a), Value::Array(b)) => a == b,
             (Value::Matrix(a), Value::Matrix(b)) => a == b,
+            (Value::Dict(a), Value::Dict(b)) => a == b,
+            (Value::Option(a), Value::Option(b)) => a == b,
+            (Value::Result(a), Value::Result(b)) => a == b,
             (
                 Value::Struct {
                     name: n1,
@@ -1859,9 +2723,11 @@ impl PartialEq for Value {
                 },
             ) => n1 == n2 && f1 == f2,
             (Value::Function { .. }, Value::Function { .. }) => false, // Functions can't be compared
-            (Value::BuiltinFunction { name: n1, .. }, Value::BuiltinFunction { name: n2, .. }) => {
-                n1 == n2
-            }
+            (Value::BuiltinFunction { name: n1, .. }, Value::BuiltinFunction { name: n2, .. })
+            | (
+                Value::InterpreterFunction { name: n1, .. },
+                Value::InterpreterFunction { name: n2, .. },
+            ) => n1 == n2,
             (Value::AsyncHandle(a), Value::AsyncHandle(b)) => a.id == b.id,
             (Value::PhysicsWorld(a), Value::PhysicsWorld(b)) => a.id == b.id,
             (Value::PhysicsObject(a), Value::PhysicsObject(b)) => a.id == b.id,
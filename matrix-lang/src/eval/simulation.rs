@@ -308,20 +308,10 @@ fn write_simulation_data_to_ipc(
 
 /// Create a physics object for demonstration
 pub fn create_demo_physics_object(id: usize) -> crate::stdlib::PhysicsObject {
-    crate::stdlib::PhysicsObject {
+    crate::stdlib::PhysicsObject::new(
         id,
-        shape: format!("sphere_{}", id),
-        mass: 1.0 + (id as f64 * 0.5),
-        position: crate::stdlib::Vec3 {
-            x: (id as f64).sin(),
-            y: (id as f64).cos(),
-            z: 0.0,
-        },
-        velocity: crate::stdlib::Vec3 {
-            x: 0.0,
-            y: 0.0,
-            z: 0.0,
-        },
-        is_static: false,
-    }
+        format!("sphere_{}", id),
+        1.0 + (id as f64 * 0.5),
+        crate::stdlib::Vec3::new((id as f64).sin(), (id as f64).cos(), 0.0),
+    )
 }
@@ -1,5 +1,5 @@
 use crate::ast::nodes::*;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 /// Intermediate Representation for Matrix Language
 #[derive(Debug, Clone)]
@@ -35,9 +35,18 @@ pub struct IrGlobal {
 pub struct BasicBlock {
     pub label: String,
     pub instructions: Vec<IrInstruction>,
+    /// Source span of the AST node behind each instruction (parallel to `instructions`)
+    pub spans: Vec<Option<Span>>,
     pub terminator: IrTerminator,
 }
 
+impl BasicBlock {
+    /// Source span recorded for the instruction at `index`, if any
+    pub fn span_of(&self, index: usize) -> Option<&Span> {
+        self.spans.get(index)?.as_ref()
+    }
+}
+
 /// IR Instructions
 #[derive(Debug, Clone)]
 pub enum IrInstruction {
@@ -151,6 +160,102 @@ pub enum IrInstruction {
     },
 }
 
+impl IrInstruction {
+    /// Register the instruction defines, if any
+    pub fn result(&self) -> Option<&IrValue> {
+        match self {
+            IrInstruction::Add { result, .. }
+            | IrInstruction::Sub { result, .. }
+            | IrInstruction::Mul { result, .. }
+            | IrInstruction::Div { result, .. }
+            | IrInstruction::Pow { result, .. }
+            | IrInstruction::Load { result, .. }
+            | IrInstruction::Alloca { result, .. }
+            | IrInstruction::VectorCreate { result, .. }
+            | IrInstruction::MatrixCreate { result, .. }
+            | IrInstruction::VectorIndex { result, .. }
+            | IrInstruction::MatrixIndex { result, .. }
+            | IrInstruction::GetPosition { result, .. }
+            | IrInstruction::Cast { result, .. }
+            | IrInstruction::ICmp { result, .. }
+            | IrInstruction::FCmp { result, .. } => Some(result),
+            IrInstruction::Call { result, .. } => result.as_ref(),
+            IrInstruction::Store { .. }
+            | IrInstruction::PhysicsStep { .. }
+            | IrInstruction::ApplyForce { .. }
+            | IrInstruction::SetPosition { .. } => None,
+        }
+    }
+
+    /// Whether the instruction does anything besides defining its result
+    pub fn has_side_effects(&self) -> bool {
+        matches!(
+            self,
+            IrInstruction::Store { .. }
+                | IrInstruction::Call { .. }
+                | IrInstruction::PhysicsStep { .. }
+                | IrInstruction::ApplyForce { .. }
+                | IrInstruction::SetPosition { .. }
+        )
+    }
+
+    /// Values the instruction reads, not including the register it defines
+    pub fn operands(&self) -> Vec<&IrValue> {
+        match self {
+            IrInstruction::Add { left, right, .. }
+            | IrInstruction::Sub { left, right, .. }
+            | IrInstruction::Mul { left, right, .. }
+            | IrInstruction::Div { left, right, .. }
+            | IrInstruction::ICmp { left, right, .. }
+            | IrInstruction::FCmp { left, right, .. } => vec![left, right],
+            IrInstruction::Pow { base, exp, .. } => vec![base, exp],
+            IrInstruction::Load { address, .. } => vec![address],
+            IrInstruction::Store { value, address } => vec![value, address],
+            IrInstruction::Alloca { .. } => Vec::new(),
+            IrInstruction::VectorCreate { elements, .. }
+            | IrInstruction::MatrixCreate { elements, .. } => elements.iter().collect(),
+            IrInstruction::VectorIndex { vector, index, .. } => vec![vector, index],
+            IrInstruction::MatrixIndex {
+                matrix, row, col, ..
+            } => vec![matrix, row, col],
+            IrInstruction::PhysicsStep { timestep } => vec![timestep],
+            IrInstruction::ApplyForce { object, force } => vec![object, force],
+            IrInstruction::GetPosition { object, .. } => vec![object],
+            IrInstruction::SetPosition { object, position } => vec![object, position],
+            IrInstruction::Call { args, .. } => args.iter().collect(),
+            IrInstruction::Cast { value, .. } => vec![value],
+        }
+    }
+
+    /// Values the instruction reads, not including the register it defines
+    pub fn operands_mut(&mut self) -> Vec<&mut IrValue> {
+        match self {
+            IrInstruction::Add { left, right, .. }
+            | IrInstruction::Sub { left, right, .. }
+            | IrInstruction::Mul { left, right, .. }
+            | IrInstruction::Div { left, right, .. }
+            | IrInstruction::ICmp { left, right, .. }
+            | IrInstruction::FCmp { left, right, .. } => vec![left, right],
+            IrInstruction::Pow { base, exp, .. } => vec![base, exp],
+            IrInstruction::Load { address, .. } => vec![address],
+            IrInstruction::Store { value, address } => vec![value, address],
+            IrInstruction::Alloca { .. } => Vec::new(),
+            IrInstruction::VectorCreate { elements, .. }
+            | IrInstruction::MatrixCreate { elements, .. } => elements.iter_mut().collect(),
+            IrInstruction::VectorIndex { vector, index, .. } => vec![vector, index],
+            IrInstruction::MatrixIndex {
+                matrix, row, col, ..
+            } => vec![matrix, row, col],
+            IrInstruction::PhysicsStep { timestep } => vec![timestep],
+            IrInstruction::ApplyForce { object, force } => vec![object, force],
+            IrInstruction::GetPosition { object, .. } => vec![object],
+            IrInstruction::SetPosition { object, position } => vec![object, position],
+            IrInstruction::Call { args, .. } => args.iter_mut().collect(),
+            IrInstruction::Cast { value, .. } => vec![value],
+        }
+    }
+}
+
 /// IR Terminators (end basic blocks)
 #[derive(Debug, Clone)]
 pub enum IrTerminator {
@@ -164,6 +269,30 @@ pub enum IrTerminator {
     Unreachable,
 }
 
+impl IrTerminator {
+    /// Value the terminator reads, if any
+    pub fn operand(&self) -> Option<&IrValue> {
+        match self {
+            IrTerminator::Return(value) => value.as_ref(),
+            IrTerminator::Branch { condition, .. } => Some(condition),
+            IrTerminator::Jump(_) | IrTerminator::Unreachable => None,
+        }
+    }
+
+    /// Labels of the blocks control can move to
+    pub fn successors(&self) -> Vec<&str> {
+        match self {
+            IrTerminator::Branch {
+                true_block,
+                false_block,
+                ..
+            } => vec![true_block, false_block],
+            IrTerminator::Jump(target) => vec![target],
+            IrTerminator::Return(_) | IrTerminator::Unreachable => Vec::new(),
+        }
+    }
+}
+
 /// IR Values
 #[derive(Debug, Clone)]
 pub enum IrValue {
@@ -223,6 +352,8 @@ pub struct IrGenerator {
     register_counter: u32,
     block_counter: u32,
     symbol_table: HashMap<String, IrValue>,
+    /// Spans of emitted instructions, in emission order
+    instruction_spans: Vec<Option<Span>>,
 }
 
 impl Default for IrGenerator {
@@ -239,6 +370,7 @@ impl IrGenerator {
             register_counter: 0,
             block_counter: 0,
             symbol_table: HashMap::new(),
+            instruction_spans: Vec::new(),
         }
     }
 
@@ -284,9 +416,12 @@ impl IrGenerator {
         }
 
         // Create the entry block using the new method
+        let spans = std::mem::take(&mut self.instruction_spans);
+        debug_assert_eq!(spans.len(), instructions.len());
         let entry_block = BasicBlock {
             label: "entry".to_string(),
             instructions,
+            spans,
             terminator: IrTerminator::Return(None),
         };
         basic_blocks.push(entry_block);
@@ -311,17 +446,25 @@ impl IrGenerator {
 
         // Allocate space for variable
         let alloc_reg = self.next_register();
-        instructions.push(IrInstruction::Alloca {
-            result: IrValue::Register(alloc_reg.clone()),
-            alloc_type: IrType::Float(64),
-        });
+        self.emit(
+            &mut instructions,
+            IrInstruction::Alloca {
+                result: IrValue::Register(alloc_reg.clone()),
+                alloc_type: IrType::Float(64),
+            },
+            &binding.span,
+        );
 
         // Generate expression and store result
         let expr_value = self.generate_expression(&binding.value, &mut instructions);
-        instructions.push(IrInstruction::Store {
-            value: expr_value,
-            address: IrValue::Register(alloc_reg.clone()),
-        });
+        self.emit(
+            &mut instructions,
+            IrInstruction::Store {
+                value: expr_value,
+                address: IrValue::Register(alloc_reg.clone()),
+            },
+            &binding.span,
+        );
 
         self.symbol_table
             .insert(binding.name.clone(), IrValue::Register(alloc_reg));
@@ -358,13 +501,17 @@ impl IrGenerator {
 
             Expression::BoolLiteral(b, _) => IrValue::Constant(IrConstant::Boolean(*b)),
 
-            Expression::Identifier(name, _) => {
+            Expression::Identifier(name, span) => {
                 if let Some(var_addr) = self.symbol_table.get(name).cloned() {
                     let result_reg = self.next_register();
-                    instructions.push(IrInstruction::Load {
-                        result: IrValue::Register(result_reg.clone()),
-                        address: var_addr,
-                    });
+                    self.emit(
+                        instructions,
+                        IrInstruction::Load {
+                            result: IrValue::Register(result_reg.clone()),
+                            address: var_addr,
+                        },
+                        span,
+                    );
                     IrValue::Register(result_reg)
                 } else {
                     // Undefined variable - should be caught in semantic analysis
@@ -376,7 +523,7 @@ impl IrGenerator {
                 left,
                 operator,
                 right,
-                ..
+                span,
             } => {
                 let left_val = self.generate_expression(left, instructions);
                 let right_val = self.generate_expression(right, instructions);
@@ -430,11 +577,16 @@ impl IrGenerator {
                     }
                 };
 
-                instructions.push(instruction);
+                self.emit(instructions, instruction, span);
                 IrValue::Register(result_reg)
             }
 
-            Expression::FunctionCall { function, args, .. } => {
+            Expression::FunctionCall {
+                function,
+                args,
+                span,
+                ..
+            } => {
                 let mut arg_values = Vec::new();
                 for arg in args {
                     arg_values.push(self.generate_expression(arg, instructions));
@@ -448,29 +600,37 @@ impl IrGenerator {
                 };
 
                 let result_reg = self.next_register();
-                instructions.push(IrInstruction::Call {
-                    result: Some(IrValue::Register(result_reg.clone())),
-                    function: function_name,
-                    args: arg_values,
-                });
+                self.emit(
+                    instructions,
+                    IrInstruction::Call {
+                        result: Some(IrValue::Register(result_reg.clone())),
+                        function: function_name,
+                        args: arg_values,
+                    },
+                    span,
+                );
                 IrValue::Register(result_reg)
             }
 
-            Expression::ArrayLiteral(elements, _) => {
+            Expression::ArrayLiteral(elements, span) => {
                 let mut element_values = Vec::new();
                 for elem in elements {
                     element_values.push(self.generate_expression(elem, instructions));
                 }
 
                 let result_reg = self.next_register();
-                instructions.push(IrInstruction::VectorCreate {
-                    result: IrValue::Register(result_reg.clone()),
-                    elements: element_values,
-                });
+                self.emit(
+                    instructions,
+                    IrInstruction::VectorCreate {
+                        result: IrValue::Register(result_reg.clone()),
+                        elements: element_values,
+                    },
+                    span,
+                );
                 IrValue::Register(result_reg)
             }
 
-            Expression::MatrixLiteral(rows, _) => {
+            Expression::MatrixLiteral(rows, span) => {
                 let mut all_elements = Vec::new();
                 let num_rows = rows.len() as u32;
                 let num_cols = if !rows.is_empty() {
@@ -486,12 +646,16 @@ impl IrGenerator {
                 }
 
                 let result_reg = self.next_register();
-                instructions.push(IrInstruction::MatrixCreate {
-                    result: IrValue::Register(result_reg.clone()),
-                    rows: num_rows,
-                    cols: num_cols,
-                    elements: all_elements,
-                });
+                self.emit(
+                    instructions,
+                    IrInstruction::MatrixCreate {
+                        result: IrValue::Register(result_reg.clone()),
+                        rows: num_rows,
+                        cols: num_cols,
+                        elements: all_elements,
+                    },
+                    span,
+                );
                 IrValue::Register(result_reg)
             }
 
@@ -502,6 +666,17 @@ impl IrGenerator {
         }
     }
 
+    /// Append an instruction, remembering the source span that produced it
+    fn emit(
+        &mut self,
+        instructions: &mut Vec<IrInstruction>,
+        instruction: IrInstruction,
+        span: &Span,
+    ) {
+        instructions.push(instruction);
+        self.instruction_spans.push(Some(span.clone()));
+    }
+
     fn next_register(&mut self) -> String {
         let reg = format!("%{}", self.register_counter);
         self.register_counter += 1;
@@ -520,6 +695,7 @@ impl IrGenerator {
         BasicBlock {
             label: format!("{}_{}", prefix, block_id),
             instructions: Vec::new(),
+            spans: Vec::new(),
             terminator: IrTerminator::Return(None),
         }
     }
@@ -582,61 +758,249 @@ impl IrOptimizer {
         module
     }
 
-    /// Constant folding optimization
+    /// Constant folding: arithmetic on constants is evaluated here, and every later
+    /// use of its result register gets the constant instead. Registers are assigned
+    /// once, so a folded register can be replaced throughout the function.
     fn constant_folding(&self, mut module: IrModule) -> IrModule {
         for function in &mut module.functions {
+            let mut folded: HashMap<String, IrValue> = HashMap::new();
+
             for block in &mut function.basic_blocks {
-                let mut optimized_instructions = Vec::new();
+                let mut instructions = Vec::new();
+                let mut spans = Vec::new();
 
-                for instruction in &block.instructions {
-                    match instruction {
-                        IrInstruction::Add {
-                            result,
-                            left,
-                            right,
-                        } => {
-                            if let (
-                                IrValue::Constant(IrConstant::Float(a)),
-                                IrValue::Constant(IrConstant::Float(b)),
-                            ) = (left, right)
-                            {
-                                // Fold constant addition
-                                optimized_instructions.push(IrInstruction::Load {
-                                    result: result.clone(),
-                                    address: IrValue::Constant(IrConstant::Float(a + b)),
-                                });
-                            } else {
-                                optimized_instructions.push(instruction.clone());
-                            }
+                for (index, mut instruction) in block.instructions.drain(..).enumerate() {
+                    for operand in instruction.operands_mut() {
+                        Self::substitute(operand, &folded);
+                    }
+                    match Self::fold(&instruction) {
+                        Some((register, constant)) => {
+                            folded.insert(register, IrValue::Constant(constant));
+                        }
+                        None => {
+                            instructions.push(instruction);
+                            spans.push(block.spans.get(index).cloned().flatten());
                         }
-                        _ => optimized_instructions.push(instruction.clone()),
                     }
                 }
 
-                block.instructions = optimized_instructions;
+                match &mut block.terminator {
+                    IrTerminator::Return(Some(value)) => Self::substitute(value, &folded),
+                    IrTerminator::Branch { condition, .. } => Self::substitute(condition, &folded),
+                    _ => {}
+                }
+                block.instructions = instructions;
+                block.spans = spans;
             }
         }
         module
     }
 
-    /// Dead code elimination
+    /// Replace a register that was optimized away with the value standing in for it
+    fn substitute(value: &mut IrValue, replacements: &HashMap<String, IrValue>) {
+        if let IrValue::Register(register) = value {
+            if let Some(replacement) = replacements.get(register) {
+                *value = replacement.clone();
+            }
+        }
+    }
+
+    /// Result register and value of arithmetic on two numeric constants. Division by
+    /// zero and integer overflow are left for run time.
+    fn fold(instruction: &IrInstruction) -> Option<(String, IrConstant)> {
+        let (result, left, right, op) = match instruction {
+            IrInstruction::Add {
+                result,
+                left,
+                right,
+            } => (result, left, right, BinaryOperator::Add),
+            IrInstruction::Sub {
+                result,
+                left,
+                right,
+            } => (result, left, right, BinaryOperator::Sub),
+            IrInstruction::Mul {
+                result,
+                left,
+                right,
+            } => (result, left, right, BinaryOperator::Mul),
+            IrInstruction::Div {
+                result,
+                left,
+                right,
+            } => (result, left, right, BinaryOperator::Div),
+            _ => return None,
+        };
+        let (IrValue::Register(register), IrValue::Constant(a), IrValue::Constant(b)) =
+            (result, left, right)
+        else {
+            return None;
+        };
+
+        let constant = match (a, b) {
+            (IrConstant::Integer(a), IrConstant::Integer(b)) => IrConstant::Integer(match op {
+                BinaryOperator::Add => a.checked_add(*b)?,
+                BinaryOperator::Sub => a.checked_sub(*b)?,
+                BinaryOperator::Mul => a.checked_mul(*b)?,
+                _ => a.checked_div(*b)?,
+            }),
+            (
+                IrConstant::Integer(_) | IrConstant::Float(_),
+                IrConstant::Integer(_) | IrConstant::Float(_),
+            ) => {
+                let as_float = |constant: &IrConstant| match constant {
+                    IrConstant::Integer(n) => *n as f64,
+                    IrConstant::Float(x) => *x,
+                    _ => unreachable!(),
+                };
+                let (a, b) = (as_float(a), as_float(b));
+                IrConstant::Float(match op {
+                    BinaryOperator::Add => a + b,
+                    BinaryOperator::Sub => a - b,
+                    BinaryOperator::Mul => a * b,
+                    _ if b == 0.0 => return None,
+                    _ => a / b,
+                })
+            }
+            _ => return None,
+        };
+        Some((register.clone(), constant))
+    }
+
+    /// Dead code elimination: blocks unreachable from the entry block are dropped, then
+    /// instructions without side effects whose result register is never read are
+    /// removed until none are left, since each removal can leave its operands unused.
+    /// Stores, calls and physics operations are always kept.
     fn dead_code_elimination(&self, mut module: IrModule) -> IrModule {
-        // Remove unused instructions and basic blocks
-        // This is a simplified implementation
         for function in &mut module.functions {
-            function
-                .basic_blocks
-                .retain(|block| !block.instructions.is_empty());
+            Self::remove_unreachable_blocks(function);
+
+            loop {
+                let used: HashSet<String> = function
+                    .basic_blocks
+                    .iter()
+                    .flat_map(|block| {
+                        let operands = block.instructions.iter().flat_map(|i| i.operands());
+                        operands.chain(block.terminator.operand())
+                    })
+                    .filter_map(|value| match value {
+                        IrValue::Register(register) => Some(register.clone()),
+                        _ => None,
+                    })
+                    .collect();
+
+                let mut removed = false;
+                for block in &mut function.basic_blocks {
+                    let mut instructions = Vec::new();
+                    let mut spans = Vec::new();
+                    for (index, instruction) in block.instructions.drain(..).enumerate() {
+                        if Self::is_live(&instruction, &used) {
+                            instructions.push(instruction);
+                            spans.push(block.spans.get(index).cloned().flatten());
+                        } else {
+                            removed = true;
+                        }
+                    }
+                    block.instructions = instructions;
+                    block.spans = spans;
+                }
+                if !removed {
+                    break;
+                }
+            }
         }
         module
     }
 
-    /// Common subexpression elimination
-    fn common_subexpression_elimination(&self, module: IrModule) -> IrModule {
-        // Identify and eliminate redundant computations
-        // This would require more sophisticated analysis
+    /// Whether an instruction has to stay: it has side effects or its result is read
+    fn is_live(instruction: &IrInstruction, used: &HashSet<String>) -> bool {
+        instruction.has_side_effects()
+            || match instruction.result() {
+                Some(IrValue::Register(register)) => used.contains(register),
+                _ => true,
+            }
+    }
+
+    /// Drop blocks that no chain of branches and jumps from the first block reaches
+    fn remove_unreachable_blocks(function: &mut IrFunction) {
+        let Some(entry) = function.basic_blocks.first() else {
+            return;
+        };
+        let mut reachable = HashSet::from([entry.label.clone()]);
+        let mut pending = vec![entry.label.clone()];
+        while let Some(label) = pending.pop() {
+            let Some(block) = function.basic_blocks.iter().find(|b| b.label == label) else {
+                continue;
+            };
+            for successor in block.terminator.successors() {
+                if reachable.insert(successor.to_string()) {
+                    pending.push(successor.to_string());
+                }
+            }
+        }
+        function
+            .basic_blocks
+            .retain(|block| reachable.contains(&block.label));
+    }
+
+    /// Common subexpression elimination by local value numbering: within a block, a
+    /// pure instruction computing the same operation on the same operands as an earlier
+    /// one is removed, and later uses of its result read the earlier result instead
+    fn common_subexpression_elimination(&self, mut module: IrModule) -> IrModule {
+        for function in &mut module.functions {
+            let mut replaced: HashMap<String, IrValue> = HashMap::new();
+
+            for block in &mut function.basic_blocks {
+                let mut computed: HashMap<String, IrValue> = HashMap::new();
+                let mut instructions = Vec::new();
+                let mut spans = Vec::new();
+
+                for (index, mut instruction) in block.instructions.drain(..).enumerate() {
+                    for operand in instruction.operands_mut() {
+                        Self::substitute(operand, &replaced);
+                    }
+                    if let (Some(key), Some(IrValue::Register(register))) =
+                        (Self::value_number(&instruction), instruction.result())
+                    {
+                        if let Some(earlier) = computed.get(&key) {
+                            replaced.insert(register.clone(), earlier.clone());
+                            continue;
+                        }
+                        computed.insert(key, IrValue::Register(register.clone()));
+                    }
+                    instructions.push(instruction);
+                    spans.push(block.spans.get(index).cloned().flatten());
+                }
+
+                match &mut block.terminator {
+                    IrTerminator::Return(Some(value)) => Self::substitute(value, &replaced),
+                    IrTerminator::Branch { condition, .. } => {
+                        Self::substitute(condition, &replaced)
+                    }
+                    _ => {}
+                }
+                block.instructions = instructions;
+                block.spans = spans;
+            }
+        }
         module
     }
+
+    /// Operation and operands of an arithmetic or comparison instruction, equal for two
+    /// instructions exactly when they compute the same value
+    fn value_number(instruction: &IrInstruction) -> Option<String> {
+        let operation = match instruction {
+            IrInstruction::Add { .. } => "add".to_string(),
+            IrInstruction::Sub { .. } => "sub".to_string(),
+            IrInstruction::Mul { .. } => "mul".to_string(),
+            IrInstruction::Div { .. } => "div".to_string(),
+            IrInstruction::Pow { .. } => "pow".to_string(),
+            IrInstruction::ICmp { predicate, .. } => format!("icmp {:?}", predicate),
+            IrInstruction::FCmp { predicate, .. } => format!("fcmp {:?}", predicate),
+            _ => return None,
+        };
+        Some(format!("{} {:?}", operation, instruction.operands()))
+    }
 }
 
 /// IR Printer for debugging
@@ -710,8 +1074,12 @@ impl IrPrinter {
         for block in &function.basic_blocks {
             output.push_str(&format!("{}:\n", block.label));
 
-            for instruction in &block.instructions {
-                output.push_str(&format!("  {}\n", self.instruction_to_string(instruction)));
+            for (i, instruction) in block.instructions.iter().enumerate() {
+                let text = self.instruction_to_string(instruction);
+                match block.span_of(i) {
+                    Some(span) => output.push_str(&format!("  {}  ; line {}\n", text, span.line)),
+                    None => output.push_str(&format!("  {}\n", text)),
+                }
             }
 
             output.push_str(&format!(
@@ -1050,3 +1418,243 @@ impl IrPrinter {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lexer::Lexer;
+    use crate::parser::Parser;
+
+    #[test]
+    fn test_instructions_carry_source_lines() {
+        let source = "let a = 1.0 + 2.0\nlet b = a * 3.0\nlet c = [a, b]\n";
+        let lexer = Lexer::new(source);
+        let mut parser = Parser::new(lexer).unwrap();
+        let program = parser.parse_program().unwrap();
+
+        let module = IrGenerator::new().generate(&program);
+        let main = module.functions.iter().find(|f| f.name == "main").unwrap();
+        let entry = &main.basic_blocks[0];
+        assert_eq!(entry.spans.len(), entry.instructions.len());
+
+        let line_of = |predicate: fn(&IrInstruction) -> bool| {
+            let index = entry.instructions.iter().position(predicate).unwrap();
+            entry.span_of(index).unwrap().line
+        };
+        assert_eq!(line_of(|i| matches!(i, IrInstruction::Add { .. })), 1);
+        assert_eq!(line_of(|i| matches!(i, IrInstruction::Mul { .. })), 2);
+        assert_eq!(
+            line_of(|i| matches!(i, IrInstruction::VectorCreate { .. })),
+            3
+        );
+
+        let printed = IrPrinter.print_module(&module);
+        assert!(printed.contains("; line 2"));
+    }
+
+    #[test]
+    fn test_constant_folding_propagates_results() {
+        let source = "let a = 1.0 + 2.0\nlet b = 4 * 3 - 2.5\nlet c = a / 2.0\nlet d = 7 / 0\n";
+        let mut parser = Parser::new(Lexer::new(source)).unwrap();
+        let program = parser.parse_program().unwrap();
+        let module = IrGenerator::new().generate(&program);
+        let unoptimized = IrPrinter.print_module(&module);
+        let optimized_module = IrOptimizer::new().optimize(module);
+        let optimized = IrPrinter.print_module(&optimized_module);
+
+        assert!(unoptimized.contains("%1 = fadd 1, 2"), "{}", unoptimized);
+        assert!(unoptimized.contains("store %1, %0"), "{}", unoptimized);
+        assert!(unoptimized.contains("%3 = fmul 4, 3"), "{}", unoptimized);
+        assert!(unoptimized.contains("%4 = fsub %3, 2.5"), "{}", unoptimized);
+
+        // Folded results are stored directly and their instructions are gone
+        for folded in ["fadd", "fmul", "fsub", "load 3"] {
+            assert!(!optimized.contains(folded), "{}", optimized);
+        }
+        assert!(optimized.contains("store 3, %0"), "{}", optimized);
+        assert!(optimized.contains("store 9.5, %2"), "{}", optimized);
+        // A loaded variable is not a constant, and division by zero is left alone
+        assert!(optimized.contains("fdiv %6, 2"), "{}", optimized);
+        assert!(optimized.contains("fdiv 7, 0"), "{}", optimized);
+
+        let main = optimized_module
+            .functions
+            .iter()
+            .find(|f| f.name == "main")
+            .unwrap();
+        let entry = &main.basic_blocks[0];
+        assert_eq!(entry.spans.len(), entry.instructions.len());
+        let stores_to = |i: &IrInstruction, register: &str| match i {
+            IrInstruction::Store {
+                address: IrValue::Register(address),
+                ..
+            } => address == register,
+            _ => false,
+        };
+        let index = entry
+            .instructions
+            .iter()
+            .position(|i| stores_to(i, "%2"))
+            .unwrap();
+        assert_eq!(entry.span_of(index).unwrap().line, 2);
+    }
+
+    fn register(name: &str) -> IrValue {
+        IrValue::Register(name.to_string())
+    }
+
+    fn block(
+        label: &str,
+        instructions: Vec<IrInstruction>,
+        terminator: IrTerminator,
+    ) -> BasicBlock {
+        BasicBlock {
+            label: label.to_string(),
+            spans: vec![None; instructions.len()],
+            instructions,
+            terminator,
+        }
+    }
+
+    /// Module whose only function is `main` with the given blocks
+    fn module_of(basic_blocks: Vec<BasicBlock>) -> IrModule {
+        IrModule {
+            functions: vec![IrFunction {
+                name: "main".to_string(),
+                params: Vec::new(),
+                return_type: IrType::Float(64),
+                basic_blocks,
+                is_external: false,
+            }],
+            globals: Vec::new(),
+            types: Vec::new(),
+        }
+    }
+
+    fn eliminate_dead_code(basic_blocks: Vec<BasicBlock>) -> Vec<BasicBlock> {
+        let module = IrOptimizer::new().dead_code_elimination(module_of(basic_blocks));
+        module.functions.into_iter().next().unwrap().basic_blocks
+    }
+
+    #[test]
+    fn test_dead_code_elimination_removes_unused_pure_instructions() {
+        let one = IrValue::Constant(IrConstant::Float(1.0));
+        let blocks = eliminate_dead_code(vec![block(
+            "entry",
+            vec![
+                // %1 only feeds %2, which nothing reads
+                IrInstruction::Add {
+                    result: register("%1"),
+                    left: register("%x"),
+                    right: one.clone(),
+                },
+                IrInstruction::Mul {
+                    result: register("%2"),
+                    left: register("%1"),
+                    right: one.clone(),
+                },
+                IrInstruction::Call {
+                    result: Some(register("%3")),
+                    function: "print".to_string(),
+                    args: vec![register("%x")],
+                },
+                IrInstruction::PhysicsStep {
+                    timestep: one.clone(),
+                },
+                IrInstruction::Sub {
+                    result: register("%4"),
+                    left: register("%x"),
+                    right: one,
+                },
+            ],
+            IrTerminator::Return(Some(register("%4"))),
+        )]);
+
+        let entry = &blocks[0];
+        assert_eq!(entry.spans.len(), entry.instructions.len());
+        assert!(matches!(
+            entry.instructions.as_slice(),
+            [
+                IrInstruction::Call { .. },
+                IrInstruction::PhysicsStep { .. },
+                IrInstruction::Sub { .. }
+            ]
+        ));
+    }
+
+    #[test]
+    fn test_dead_code_elimination_keeps_reachable_blocks() {
+        let store = |block: &str| IrInstruction::Store {
+            value: IrValue::Constant(IrConstant::Integer(1)),
+            address: register(block),
+        };
+        let blocks = eliminate_dead_code(vec![
+            block(
+                "entry",
+                Vec::new(),
+                IrTerminator::Branch {
+                    condition: register("%c"),
+                    true_block: "then".to_string(),
+                    false_block: "done".to_string(),
+                },
+            ),
+            block(
+                "orphan",
+                vec![store("%o")],
+                IrTerminator::Jump("done".to_string()),
+            ),
+            block(
+                "then",
+                vec![store("%t")],
+                IrTerminator::Jump("done".to_string()),
+            ),
+            block("done", Vec::new(), IrTerminator::Return(None)),
+        ]);
+
+        // Empty blocks stay when branched to; only the unreachable one is dropped
+        let labels: Vec<&str> = blocks.iter().map(|b| b.label.as_str()).collect();
+        assert_eq!(labels, ["entry", "then", "done"]);
+    }
+
+    #[test]
+    fn test_common_subexpressions_are_computed_once() {
+        let mul = |result: &str| IrInstruction::Mul {
+            result: register(result),
+            left: register("%a"),
+            right: register("%b"),
+        };
+        let module = module_of(vec![block(
+            "entry",
+            vec![
+                mul("%1"),
+                mul("%2"),
+                // Same operation on different operands is kept
+                IrInstruction::Mul {
+                    result: register("%3"),
+                    left: register("%b"),
+                    right: register("%a"),
+                },
+                IrInstruction::Add {
+                    result: register("%4"),
+                    left: register("%1"),
+                    right: register("%2"),
+                },
+                IrInstruction::Add {
+                    result: register("%5"),
+                    left: register("%4"),
+                    right: register("%3"),
+                },
+            ],
+            IrTerminator::Return(Some(register("%5"))),
+        )]);
+
+        let before = IrPrinter.print_module(&module);
+        assert_eq!(before.matches("= fmul %a, %b").count(), 2, "{}", before);
+
+        let after = IrPrinter.print_module(&IrOptimizer::new().optimize(module));
+        assert_eq!(after.matches("= fmul %a, %b").count(), 1, "{}", after);
+        assert!(after.contains("%4 = fadd %1, %1"), "{}", after);
+        assert!(after.contains("%3 = fmul %b, %a"), "{}", after);
+        assert!(after.contains("ret %5"), "{}", after);
+    }
+}
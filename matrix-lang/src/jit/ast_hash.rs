@@ -0,0 +1,79 @@
+// AST Hashing for the JIT Cache
+// Hashes a definition's serialized form with its source spans cleared
+
+use crate::ast::nodes::FunctionDef;
+use serde_json::{Map, Value};
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
+/// Hash of a function definition that ignores source positions, so the same definition
+/// entered again elsewhere hashes the same and any change to it hashes differently.
+/// `None` if the definition could not be serialized; such definitions are never cached.
+pub fn ast_hash(func_def: &FunctionDef) -> Option<u64> {
+    let mut value = serde_json::to_value(func_def).ok()?;
+    clear_spans(&mut value);
+    let mut state = DefaultHasher::new();
+    // Object keys print sorted, so the struct literal fields a `HashMap` holds in any
+    // order print the same way every time
+    value.to_string().hash(&mut state);
+    Some(state.finish())
+}
+
+/// Replace every serialized `Span` with null
+fn clear_spans(value: &mut Value) {
+    match value {
+        Value::Object(fields) if is_span(fields) => *value = Value::Null,
+        Value::Object(fields) => fields.values_mut().for_each(clear_spans),
+        Value::Array(items) => items.iter_mut().for_each(clear_spans),
+        _ => {}
+    }
+}
+
+/// A `Span` is the only node serializing to an object of four integers. A struct
+/// literal with the same field names holds expressions, which serialize to objects.
+fn is_span(fields: &Map<String, Value>) -> bool {
+    fields.len() == 4
+        && ["start", "end", "line", "column"]
+            .iter()
+            .all(|name| fields.get(*name).is_some_and(Value::is_u64))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ast::nodes::Item;
+    use crate::{Lexer, Parser};
+
+    fn function_def(source: &str) -> FunctionDef {
+        let program = Parser::new(Lexer::new(source))
+            .and_then(|mut parser| parser.parse_program())
+            .unwrap();
+        match program.items.into_iter().next() {
+            Some(Item::FunctionDef(func_def)) => func_def,
+            other => panic!("expected a function definition, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_ast_hash_ignores_positions() {
+        let square = function_def("@jit\nlet square = (x: Float) -> Float => x * x");
+        let moved = function_def("\n\n@jit\nlet square = (x: Float)  ->  Float => x * x");
+        let cube = function_def("@jit\nlet square = (x: Float) -> Float => x * x * x");
+        assert_eq!(ast_hash(&square), ast_hash(&moved));
+        assert_ne!(ast_hash(&square), ast_hash(&cube));
+    }
+
+    #[test]
+    fn test_struct_literals_shaped_like_spans_are_hashed() {
+        let source = |line: i64| {
+            format!(
+                "@jit\nlet at = (x: Int) => Pos {{ start: x, end: 2, line: {}, column: 4 }}",
+                line
+            )
+        };
+        let first = function_def(&source(3));
+        let second = function_def(&source(30));
+        assert_ne!(ast_hash(&first), ast_hash(&second));
+        assert_eq!(ast_hash(&first), ast_hash(&function_def(&source(3))));
+    }
+}
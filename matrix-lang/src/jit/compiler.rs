@@ -1,18 +1,18 @@
 // JIT Compiler Implementation
 // Handles compilation of Matrix Language AST to LLVM IR
 
-use super::{JitError, OptimizationLevel};
+use super::JitError;
 use crate::ast::nodes::*;
-use crate::types::types::Type;
 use std::collections::HashMap;
 
 #[cfg(feature = "jit")]
 use inkwell::{
     builder::Builder,
     context::Context,
+    intrinsics::Intrinsic,
     module::Module,
-    types::{BasicMetadataTypeEnum, BasicTypeEnum, FunctionType},
-    values::{BasicMetadataValueEnum, BasicValueEnum, FunctionValue, PointerValue},
+    types::{BasicType, BasicTypeEnum},
+    values::{BasicValueEnum, FunctionValue, PointerValue},
     AddressSpace, FloatPredicate, IntPredicate,
 };
 
@@ -22,8 +22,25 @@ pub struct JitCompiler<'ctx> {
     pub context: &'ctx Context,
     pub module: Module<'ctx>,
     pub builder: Builder<'ctx>,
-    variables: HashMap<String, PointerValue<'ctx>>,
+    /// Stack slot of each variable in scope, with the type stored in it
+    variables: HashMap<String, (PointerValue<'ctx>, BasicTypeEnum<'ctx>)>,
+    /// Latest compiled definition of each function, by source name. Definitions from
+    /// earlier modules are declared in the current one when it calls them.
+    functions: HashMap<String, FunctionValue<'ctx>>,
     current_function: Option<FunctionValue<'ctx>>,
+    /// Array creations and element reads compiled so far
+    pub array_ops_compiled: usize,
+}
+
+/// Type of the value a compiled program writes to its output buffer
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ResultKind {
+    Unit,
+    Int,
+    Float,
+    Bool,
+    /// Array of this many floats
+    Array(u32),
 }
 
 #[cfg(feature = "jit")]
@@ -37,39 +54,125 @@ impl<'ctx> JitCompiler<'ctx> {
             module,
             builder,
             variables: HashMap::new(),
+            functions: HashMap::new(),
             current_function: None,
+            array_ops_compiled: 0,
         }
     }
 
-    /// Compile a function definition to LLVM IR
+    /// Compile a program into a function `name(out: ptr)` that runs its let bindings in
+    /// order and writes the value of the last one to `out`. Its function definitions
+    /// must already be compiled.
+    pub fn compile_program(
+        &mut self,
+        program: &Program,
+        name: &str,
+    ) -> Result<ResultKind, JitError> {
+        let out_type = self.context.i8_type().ptr_type(AddressSpace::default());
+        let fn_type = self.context.void_type().fn_type(&[out_type.into()], false);
+        let function = self.module.add_function(name, fn_type, None);
+        self.current_function = Some(function);
+        self.variables.clear();
+        let entry_block = self.context.append_basic_block(function, "entry");
+        self.builder.position_at_end(entry_block);
+
+        let mut result = None;
+        for item in &program.items {
+            if let Item::LetBinding(binding) = item {
+                result = Some(self.compile_let_binding(binding)?);
+            }
+        }
+
+        let out = function
+            .get_nth_param(0)
+            .ok_or_else(|| JitError::CompilationFailed("Missing output parameter".to_string()))?
+            .into_pointer_value();
+        let kind = match result {
+            None => ResultKind::Unit,
+            Some(BasicValueEnum::IntValue(value)) if value.get_type().get_bit_width() == 1 => {
+                ResultKind::Bool
+            }
+            Some(BasicValueEnum::IntValue(_)) => ResultKind::Int,
+            Some(BasicValueEnum::FloatValue(_)) => ResultKind::Float,
+            Some(BasicValueEnum::ArrayValue(value))
+                if value.get_type().get_element_type().is_float_type() =>
+            {
+                ResultKind::Array(value.get_type().len())
+            }
+            Some(_) => {
+                return Err(JitError::UnsupportedType(
+                    "Program result must be a number, boolean or float array".to_string(),
+                ))
+            }
+        };
+        if let Some(value) = result {
+            // A no-op with opaque pointers; typed pointers need the store's own type
+            let out = self
+                .builder
+                .build_pointer_cast(
+                    out,
+                    value.get_type().ptr_type(AddressSpace::default()),
+                    "out",
+                )
+                .map_err(|e| JitError::CompilationFailed(e.to_string()))?;
+            self.builder
+                .build_store(out, value)
+                .map_err(|e| JitError::CompilationFailed(e.to_string()))?;
+        }
+
+        self.builder
+            .build_return(None)
+            .map_err(|e| JitError::CompilationFailed(e.to_string()))?;
+        Ok(kind)
+    }
+
+    /// Compile a function definition to LLVM IR under the symbol `symbol`. Calls to its
+    /// name compile to calls to this definition from then on.
     pub fn compile_function_def(
         &mut self,
         func_def: &FunctionDef,
+        symbol: &str,
     ) -> Result<FunctionValue<'ctx>, JitError> {
         // Map Matrix types to LLVM types
         let param_types: Result<Vec<_>, _> = func_def
             .params
             .iter()
-            .map(|p| self.matrix_type_to_llvm(&p.param_type.clone().unwrap_or(Type::Any)))
+            .map(|p| self.matrix_type_to_llvm(&p.type_annotation).map(Into::into))
             .collect();
         let param_types = param_types?;
 
-        let return_type =
-            self.matrix_type_to_llvm(&func_def.return_type.clone().unwrap_or(Type::Any))?;
-
-        // Create function type
-        let fn_type = match return_type {
-            BasicTypeEnum::IntType(int_type) => int_type.fn_type(&param_types, false),
-            BasicTypeEnum::FloatType(float_type) => float_type.fn_type(&param_types, false),
-            BasicTypeEnum::PointerType(ptr_type) => ptr_type.fn_type(&param_types, false),
-            BasicTypeEnum::ArrayType(array_type) => array_type.fn_type(&param_types, false),
-            BasicTypeEnum::StructType(struct_type) => struct_type.fn_type(&param_types, false),
-            BasicTypeEnum::VectorType(vec_type) => vec_type.fn_type(&param_types, false),
-        };
+        let return_type = func_def.return_type.as_ref().ok_or_else(|| {
+            JitError::TypeInferenceFailed(format!(
+                "'{}' needs a return type annotation to be JIT compiled",
+                func_def.name
+            ))
+        })?;
+        let fn_type = self
+            .matrix_type_to_llvm(return_type)?
+            .fn_type(&param_types, false);
 
         // Create function
-        let function = self.module.add_function(&func_def.name, fn_type, None);
+        let function = self.module.add_function(symbol, fn_type, None);
+
+        // Registered before the body is compiled so the function can call itself
+        let previous = self.functions.insert(func_def.name.clone(), function);
+        let compiled = self.compile_function_body(func_def, function);
+        if compiled.is_err() {
+            match previous {
+                Some(previous) => self.functions.insert(func_def.name.clone(), previous),
+                None => self.functions.remove(&func_def.name),
+            };
+        }
+        compiled.map(|()| function)
+    }
+
+    fn compile_function_body(
+        &mut self,
+        func_def: &FunctionDef,
+        function: FunctionValue<'ctx>,
+    ) -> Result<(), JitError> {
         self.current_function = Some(function);
+        self.variables.clear();
 
         // Create entry block
         let entry_block = self.context.append_basic_block(function, "entry");
@@ -86,18 +189,19 @@ impl<'ctx> JitCompiler<'ctx> {
                 .build_store(alloca, param_value)
                 .map_err(|e| JitError::CompilationFailed(e.to_string()))?;
 
-            self.variables.insert(param.name.clone(), alloca);
+            self.variables
+                .insert(param.name.clone(), (alloca, param_value.get_type()));
         }
 
         // Compile function body
         let body_value = self.compile_expression(&func_def.body)?;
 
         // Build return
-        if let BasicMetadataValueEnum::IntValue(int_val) = body_value {
+        if let BasicValueEnum::IntValue(int_val) = body_value {
             self.builder
                 .build_return(Some(&int_val))
                 .map_err(|e| JitError::CompilationFailed(e.to_string()))?;
-        } else if let BasicMetadataValueEnum::FloatValue(float_val) = body_value {
+        } else if let BasicValueEnum::FloatValue(float_val) = body_value {
             self.builder
                 .build_return(Some(&float_val))
                 .map_err(|e| JitError::CompilationFailed(e.to_string()))?;
@@ -107,14 +211,21 @@ impl<'ctx> JitCompiler<'ctx> {
             ));
         }
 
-        Ok(function)
+        if function.verify(false) {
+            Ok(())
+        } else {
+            Err(JitError::CompilationFailed(format!(
+                "'{}' does not match its declared types",
+                func_def.name
+            )))
+        }
     }
 
     /// Compile an expression to LLVM IR
     pub fn compile_expression(
         &mut self,
         expr: &Expression,
-    ) -> Result<BasicMetadataValueEnum<'ctx>, JitError> {
+    ) -> Result<BasicValueEnum<'ctx>, JitError> {
         match expr {
             Expression::IntLiteral(i, _) => {
                 Ok(self.context.i64_type().const_int(*i as u64, false).into())
@@ -147,109 +258,96 @@ impl<'ctx> JitCompiler<'ctx> {
                 ..
             } => self.compile_if_expression(condition, then_branch, else_branch.as_deref()),
             Expression::ArrayLiteral(elements, _) => self.compile_array(elements),
-            Expression::Block { statements, .. } => self.compile_block(statements),
+            Expression::Block {
+                statements, result, ..
+            } => self.compile_block(statements, result.as_deref()),
             _ => Err(JitError::UnsupportedType(
                 "Expression not implemented yet".to_string(),
             )),
         }
     }
 
-    fn compile_literal(&self, lit: &Literal) -> Result<BasicMetadataValueEnum<'ctx>, JitError> {
-        match lit {
-            Literal::Int(i) => Ok(self.context.i64_type().const_int(*i as u64, false).into()),
-            Literal::Float(f) => Ok(self.context.f64_type().const_float(*f).into()),
-            Literal::Bool(b) => Ok(self.context.bool_type().const_int(*b as u64, false).into()),
-            Literal::String(s) => {
-                let string_val = self.context.const_string(s.as_bytes(), false);
-                Ok(string_val.into())
-            }
-            _ => Err(JitError::UnsupportedType(
-                "Literal type not supported".to_string(),
-            )),
-        }
-    }
-
     fn compile_binary_op(
         &mut self,
         left: &Expression,
         op: &BinaryOperator,
         right: &Expression,
-    ) -> Result<BasicMetadataValueEnum<'ctx>, JitError> {
+    ) -> Result<BasicValueEnum<'ctx>, JitError> {
         let left_val = self.compile_expression(left)?;
         let right_val = self.compile_expression(right)?;
 
         match op {
             BinaryOperator::Add => {
-                self.compile_arithmetic_op(left_val, right_val, "add", |builder, l, r| {
-                    if let (
-                        BasicMetadataValueEnum::IntValue(l),
-                        BasicMetadataValueEnum::IntValue(r),
-                    ) = (l, r)
-                    {
-                        builder.build_int_add(l, r, "add").map(|v| v.into())
-                    } else if let (
-                        BasicMetadataValueEnum::FloatValue(l),
-                        BasicMetadataValueEnum::FloatValue(r),
-                    ) = (l, r)
+                self.compile_arithmetic_op(left_val, right_val, |builder, l, r| {
+                    if let (BasicValueEnum::IntValue(l), BasicValueEnum::IntValue(r)) = (l, r) {
+                        builder
+                            .build_int_add(l, r, "add")
+                            .map(|v| v.into())
+                            .map_err(|e| e.to_string())
+                    } else if let (BasicValueEnum::FloatValue(l), BasicValueEnum::FloatValue(r)) =
+                        (l, r)
                     {
-                        builder.build_float_add(l, r, "fadd").map(|v| v.into())
+                        builder
+                            .build_float_add(l, r, "fadd")
+                            .map(|v| v.into())
+                            .map_err(|e| e.to_string())
                     } else {
                         Err("Type mismatch in addition".to_string())
                     }
                 })
             }
             BinaryOperator::Sub => {
-                self.compile_arithmetic_op(left_val, right_val, "sub", |builder, l, r| {
-                    if let (
-                        BasicMetadataValueEnum::IntValue(l),
-                        BasicMetadataValueEnum::IntValue(r),
-                    ) = (l, r)
-                    {
-                        builder.build_int_sub(l, r, "sub").map(|v| v.into())
-                    } else if let (
-                        BasicMetadataValueEnum::FloatValue(l),
-                        BasicMetadataValueEnum::FloatValue(r),
-                    ) = (l, r)
+                self.compile_arithmetic_op(left_val, right_val, |builder, l, r| {
+                    if let (BasicValueEnum::IntValue(l), BasicValueEnum::IntValue(r)) = (l, r) {
+                        builder
+                            .build_int_sub(l, r, "sub")
+                            .map(|v| v.into())
+                            .map_err(|e| e.to_string())
+                    } else if let (BasicValueEnum::FloatValue(l), BasicValueEnum::FloatValue(r)) =
+                        (l, r)
                     {
-                        builder.build_float_sub(l, r, "fsub").map(|v| v.into())
+                        builder
+                            .build_float_sub(l, r, "fsub")
+                            .map(|v| v.into())
+                            .map_err(|e| e.to_string())
                     } else {
                         Err("Type mismatch in subtraction".to_string())
                     }
                 })
             }
             BinaryOperator::Mul => {
-                self.compile_arithmetic_op(left_val, right_val, "mul", |builder, l, r| {
-                    if let (
-                        BasicMetadataValueEnum::IntValue(l),
-                        BasicMetadataValueEnum::IntValue(r),
-                    ) = (l, r)
-                    {
-                        builder.build_int_mul(l, r, "mul").map(|v| v.into())
-                    } else if let (
-                        BasicMetadataValueEnum::FloatValue(l),
-                        BasicMetadataValueEnum::FloatValue(r),
-                    ) = (l, r)
+                self.compile_arithmetic_op(left_val, right_val, |builder, l, r| {
+                    if let (BasicValueEnum::IntValue(l), BasicValueEnum::IntValue(r)) = (l, r) {
+                        builder
+                            .build_int_mul(l, r, "mul")
+                            .map(|v| v.into())
+                            .map_err(|e| e.to_string())
+                    } else if let (BasicValueEnum::FloatValue(l), BasicValueEnum::FloatValue(r)) =
+                        (l, r)
                     {
-                        builder.build_float_mul(l, r, "fmul").map(|v| v.into())
+                        builder
+                            .build_float_mul(l, r, "fmul")
+                            .map(|v| v.into())
+                            .map_err(|e| e.to_string())
                     } else {
                         Err("Type mismatch in multiplication".to_string())
                     }
                 })
             }
             BinaryOperator::Div => {
-                self.compile_arithmetic_op(left_val, right_val, "div", |builder, l, r| {
-                    if let (
-                        BasicMetadataValueEnum::IntValue(l),
-                        BasicMetadataValueEnum::IntValue(r),
-                    ) = (l, r)
-                    {
-                        builder.build_int_signed_div(l, r, "sdiv").map(|v| v.into())
-                    } else if let (
-                        BasicMetadataValueEnum::FloatValue(l),
-                        BasicMetadataValueEnum::FloatValue(r),
-                    ) = (l, r)
+                self.compile_arithmetic_op(left_val, right_val, |builder, l, r| {
+                    if let (BasicValueEnum::IntValue(l), BasicValueEnum::IntValue(r)) = (l, r) {
+                        builder
+                            .build_int_signed_div(l, r, "sdiv")
+                            .map(|v| v.into())
+                            .map_err(|e| e.to_string())
+                    } else if let (BasicValueEnum::FloatValue(l), BasicValueEnum::FloatValue(r)) =
+                        (l, r)
                     {
-                        builder.build_float_div(l, r, "fdiv").map(|v| v.into())
+                        builder
+                            .build_float_div(l, r, "fdiv")
+                            .map(|v| v.into())
+                            .map_err(|e| e.to_string())
                     } else {
                         Err("Type mismatch in division".to_string())
                     }
@@ -257,22 +355,18 @@ impl<'ctx> JitCompiler<'ctx> {
             }
             BinaryOperator::Eq => {
                 self.compile_comparison_op(left_val, right_val, |builder, l, r| {
-                    if let (
-                        BasicMetadataValueEnum::IntValue(l),
-                        BasicMetadataValueEnum::IntValue(r),
-                    ) = (l, r)
-                    {
+                    if let (BasicValueEnum::IntValue(l), BasicValueEnum::IntValue(r)) = (l, r) {
                         builder
                             .build_int_compare(IntPredicate::EQ, l, r, "eq")
                             .map(|v| v.into())
-                    } else if let (
-                        BasicMetadataValueEnum::FloatValue(l),
-                        BasicMetadataValueEnum::FloatValue(r),
-                    ) = (l, r)
+                            .map_err(|e| e.to_string())
+                    } else if let (BasicValueEnum::FloatValue(l), BasicValueEnum::FloatValue(r)) =
+                        (l, r)
                     {
                         builder
                             .build_float_compare(FloatPredicate::OEQ, l, r, "feq")
                             .map(|v| v.into())
+                            .map_err(|e| e.to_string())
                     } else {
                         Err("Type mismatch in comparison".to_string())
                     }
@@ -280,22 +374,18 @@ impl<'ctx> JitCompiler<'ctx> {
             }
             BinaryOperator::Ne => {
                 self.compile_comparison_op(left_val, right_val, |builder, l, r| {
-                    if let (
-                        BasicMetadataValueEnum::IntValue(l),
-                        BasicMetadataValueEnum::IntValue(r),
-                    ) = (l, r)
-                    {
+                    if let (BasicValueEnum::IntValue(l), BasicValueEnum::IntValue(r)) = (l, r) {
                         builder
                             .build_int_compare(IntPredicate::NE, l, r, "ne")
                             .map(|v| v.into())
-                    } else if let (
-                        BasicMetadataValueEnum::FloatValue(l),
-                        BasicMetadataValueEnum::FloatValue(r),
-                    ) = (l, r)
+                            .map_err(|e| e.to_string())
+                    } else if let (BasicValueEnum::FloatValue(l), BasicValueEnum::FloatValue(r)) =
+                        (l, r)
                     {
                         builder
                             .build_float_compare(FloatPredicate::ONE, l, r, "fne")
                             .map(|v| v.into())
+                            .map_err(|e| e.to_string())
                     } else {
                         Err("Type mismatch in comparison".to_string())
                     }
@@ -303,22 +393,18 @@ impl<'ctx> JitCompiler<'ctx> {
             }
             BinaryOperator::Lt => {
                 self.compile_comparison_op(left_val, right_val, |builder, l, r| {
-                    if let (
-                        BasicMetadataValueEnum::IntValue(l),
-                        BasicMetadataValueEnum::IntValue(r),
-                    ) = (l, r)
-                    {
+                    if let (BasicValueEnum::IntValue(l), BasicValueEnum::IntValue(r)) = (l, r) {
                         builder
                             .build_int_compare(IntPredicate::SLT, l, r, "lt")
                             .map(|v| v.into())
-                    } else if let (
-                        BasicMetadataValueEnum::FloatValue(l),
-                        BasicMetadataValueEnum::FloatValue(r),
-                    ) = (l, r)
+                            .map_err(|e| e.to_string())
+                    } else if let (BasicValueEnum::FloatValue(l), BasicValueEnum::FloatValue(r)) =
+                        (l, r)
                     {
                         builder
                             .build_float_compare(FloatPredicate::OLT, l, r, "flt")
                             .map(|v| v.into())
+                            .map_err(|e| e.to_string())
                     } else {
                         Err("Type mismatch in comparison".to_string())
                     }
@@ -326,22 +412,18 @@ impl<'ctx> JitCompiler<'ctx> {
             }
             BinaryOperator::Le => {
                 self.compile_comparison_op(left_val, right_val, |builder, l, r| {
-                    if let (
-                        BasicMetadataValueEnum::IntValue(l),
-                        BasicMetadataValueEnum::IntValue(r),
-                    ) = (l, r)
-                    {
+                    if let (BasicValueEnum::IntValue(l), BasicValueEnum::IntValue(r)) = (l, r) {
                         builder
                             .build_int_compare(IntPredicate::SLE, l, r, "le")
                             .map(|v| v.into())
-                    } else if let (
-                        BasicMetadataValueEnum::FloatValue(l),
-                        BasicMetadataValueEnum::FloatValue(r),
-                    ) = (l, r)
+                            .map_err(|e| e.to_string())
+                    } else if let (BasicValueEnum::FloatValue(l), BasicValueEnum::FloatValue(r)) =
+                        (l, r)
                     {
                         builder
                             .build_float_compare(FloatPredicate::OLE, l, r, "fle")
                             .map(|v| v.into())
+                            .map_err(|e| e.to_string())
                     } else {
                         Err("Type mismatch in comparison".to_string())
                     }
@@ -349,22 +431,18 @@ impl<'ctx> JitCompiler<'ctx> {
             }
             BinaryOperator::Gt => {
                 self.compile_comparison_op(left_val, right_val, |builder, l, r| {
-                    if let (
-                        BasicMetadataValueEnum::IntValue(l),
-                        BasicMetadataValueEnum::IntValue(r),
-                    ) = (l, r)
-                    {
+                    if let (BasicValueEnum::IntValue(l), BasicValueEnum::IntValue(r)) = (l, r) {
                         builder
                             .build_int_compare(IntPredicate::SGT, l, r, "gt")
                             .map(|v| v.into())
-                    } else if let (
-                        BasicMetadataValueEnum::FloatValue(l),
-                        BasicMetadataValueEnum::FloatValue(r),
-                    ) = (l, r)
+                            .map_err(|e| e.to_string())
+                    } else if let (BasicValueEnum::FloatValue(l), BasicValueEnum::FloatValue(r)) =
+                        (l, r)
                     {
                         builder
                             .build_float_compare(FloatPredicate::OGT, l, r, "fgt")
                             .map(|v| v.into())
+                            .map_err(|e| e.to_string())
                     } else {
                         Err("Type mismatch in comparison".to_string())
                     }
@@ -372,22 +450,18 @@ impl<'ctx> JitCompiler<'ctx> {
             }
             BinaryOperator::Ge => {
                 self.compile_comparison_op(left_val, right_val, |builder, l, r| {
-                    if let (
-                        BasicMetadataValueEnum::IntValue(l),
-                        BasicMetadataValueEnum::IntValue(r),
-                    ) = (l, r)
-                    {
+                    if let (BasicValueEnum::IntValue(l), BasicValueEnum::IntValue(r)) = (l, r) {
                         builder
                             .build_int_compare(IntPredicate::SGE, l, r, "ge")
                             .map(|v| v.into())
-                    } else if let (
-                        BasicMetadataValueEnum::FloatValue(l),
-                        BasicMetadataValueEnum::FloatValue(r),
-                    ) = (l, r)
+                            .map_err(|e| e.to_string())
+                    } else if let (BasicValueEnum::FloatValue(l), BasicValueEnum::FloatValue(r)) =
+                        (l, r)
                     {
                         builder
                             .build_float_compare(FloatPredicate::OGE, l, r, "fge")
                             .map(|v| v.into())
+                            .map_err(|e| e.to_string())
                     } else {
                         Err("Type mismatch in comparison".to_string())
                     }
@@ -403,19 +477,19 @@ impl<'ctx> JitCompiler<'ctx> {
         &mut self,
         op: &UnaryOperator,
         expr: &Expression,
-    ) -> Result<BasicMetadataValueEnum<'ctx>, JitError> {
+    ) -> Result<BasicValueEnum<'ctx>, JitError> {
         let val = self.compile_expression(expr)?;
 
         match op {
             UnaryOperator::Neg => {
-                if let BasicMetadataValueEnum::IntValue(int_val) = val {
+                if let BasicValueEnum::IntValue(int_val) = val {
                     let zero = self.context.i64_type().const_int(0, false);
                     Ok(self
                         .builder
                         .build_int_sub(zero, int_val, "neg")
                         .map_err(|e| JitError::CompilationFailed(e.to_string()))?
                         .into())
-                } else if let BasicMetadataValueEnum::FloatValue(float_val) = val {
+                } else if let BasicValueEnum::FloatValue(float_val) = val {
                     Ok(self
                         .builder
                         .build_float_neg(float_val, "fneg")
@@ -428,7 +502,7 @@ impl<'ctx> JitCompiler<'ctx> {
                 }
             }
             UnaryOperator::Not => {
-                if let BasicMetadataValueEnum::IntValue(int_val) = val {
+                if let BasicValueEnum::IntValue(int_val) = val {
                     // Assuming boolean is represented as i1 or i64
                     let zero = self.context.i64_type().const_int(0, false);
                     Ok(self
@@ -451,13 +525,11 @@ impl<'ctx> JitCompiler<'ctx> {
         }
     }
 
-    fn compile_variable(&self, name: &str) -> Result<BasicMetadataValueEnum<'ctx>, JitError> {
-        if let Some(ptr) = self.variables.get(name) {
-            let loaded_val = self
-                .builder
-                .build_load(ptr.get_type().get_element_type(), *ptr, name)
-                .map_err(|e| JitError::CompilationFailed(e.to_string()))?;
-            Ok(loaded_val.into())
+    fn compile_variable(&self, name: &str) -> Result<BasicValueEnum<'ctx>, JitError> {
+        if let Some(&(ptr, value_type)) = self.variables.get(name) {
+            self.builder
+                .build_load(value_type, ptr, name)
+                .map_err(|e| JitError::CompilationFailed(e.to_string()))
         } else {
             Err(JitError::CompilationFailed(format!(
                 "Variable '{}' not found",
@@ -470,22 +542,27 @@ impl<'ctx> JitCompiler<'ctx> {
         &mut self,
         func: &Expression,
         args: &[Expression],
-    ) -> Result<BasicMetadataValueEnum<'ctx>, JitError> {
+    ) -> Result<BasicValueEnum<'ctx>, JitError> {
         if let Expression::Identifier(func_name, _) = func {
+            // `a[i]` is parsed as a call to `index`
+            if func_name == "index" && args.len() == 2 {
+                return self.compile_index(&args[0], &args[1]);
+            }
+
             let compiled_args: Result<Vec<_>, _> = args
                 .iter()
-                .map(|arg| self.compile_expression(arg))
+                .map(|arg| self.compile_expression(arg).map(Into::into))
                 .collect();
             let compiled_args = compiled_args?;
 
-            if let Some(function) = self.module.get_function(func_name) {
+            if let Some(function) = self.declare_function(func_name) {
                 let call_site = self
                     .builder
                     .build_call(function, &compiled_args, "call")
                     .map_err(|e| JitError::CompilationFailed(e.to_string()))?;
 
-                if let Some(return_val) = call_site.try_as_basic_value() {
-                    Ok(return_val.into())
+                if let Some(return_val) = call_site.try_as_basic_value().left() {
+                    Ok(return_val)
                 } else {
                     // Function returns void, return unit value (0)
                     Ok(self.context.i64_type().const_int(0, false).into())
@@ -500,12 +577,24 @@ impl<'ctx> JitCompiler<'ctx> {
         }
     }
 
+    /// The latest definition of `name`, declared in the current module if it was
+    /// compiled into an earlier one
+    fn declare_function(&self, name: &str) -> Option<FunctionValue<'ctx>> {
+        let function = *self.functions.get(name)?;
+        let symbol = function.get_name().to_str().ok()?;
+        Some(
+            self.module
+                .get_function(symbol)
+                .unwrap_or_else(|| self.module.add_function(symbol, function.get_type(), None)),
+        )
+    }
+
     fn compile_if_expression(
         &mut self,
         condition: &Expression,
         then_branch: &Expression,
         else_branch: Option<&Expression>,
-    ) -> Result<BasicMetadataValueEnum<'ctx>, JitError> {
+    ) -> Result<BasicValueEnum<'ctx>, JitError> {
         let cond_val = self.compile_expression(condition)?;
 
         let function = self
@@ -517,11 +606,15 @@ impl<'ctx> JitCompiler<'ctx> {
         let cont_block = self.context.append_basic_block(function, "cont");
 
         // Convert condition to boolean
-        let cond_bool = if let BasicMetadataValueEnum::IntValue(int_val) = cond_val {
-            let zero = self.context.i64_type().const_int(0, false);
-            self.builder
-                .build_int_compare(IntPredicate::NE, int_val, zero, "tobool")
-                .map_err(|e| JitError::CompilationFailed(e.to_string()))?
+        let cond_bool = if let BasicValueEnum::IntValue(int_val) = cond_val {
+            if int_val.get_type().get_bit_width() == 1 {
+                int_val
+            } else {
+                let zero = int_val.get_type().const_zero();
+                self.builder
+                    .build_int_compare(IntPredicate::NE, int_val, zero, "tobool")
+                    .map_err(|e| JitError::CompilationFailed(e.to_string()))?
+            }
         } else {
             return Err(JitError::UnsupportedType(
                 "Condition must be integer type".to_string(),
@@ -557,27 +650,21 @@ impl<'ctx> JitCompiler<'ctx> {
         self.builder.position_at_end(cont_block);
 
         match (then_val, else_val) {
-            (
-                BasicMetadataValueEnum::IntValue(then_int),
-                BasicMetadataValueEnum::IntValue(else_int),
-            ) => {
+            (BasicValueEnum::IntValue(then_int), BasicValueEnum::IntValue(else_int)) => {
                 let phi = self
                     .builder
-                    .build_phi(self.context.i64_type(), "iftmp")
+                    .build_phi(then_int.get_type(), "iftmp")
                     .map_err(|e| JitError::CompilationFailed(e.to_string()))?;
                 phi.add_incoming(&[(&then_int, then_end_block), (&else_int, else_end_block)]);
-                Ok(phi.as_basic_value().into())
+                Ok(phi.as_basic_value())
             }
-            (
-                BasicMetadataValueEnum::FloatValue(then_float),
-                BasicMetadataValueEnum::FloatValue(else_float),
-            ) => {
+            (BasicValueEnum::FloatValue(then_float), BasicValueEnum::FloatValue(else_float)) => {
                 let phi = self
                     .builder
                     .build_phi(self.context.f64_type(), "iftmp")
                     .map_err(|e| JitError::CompilationFailed(e.to_string()))?;
                 phi.add_incoming(&[(&then_float, then_end_block), (&else_float, else_end_block)]);
-                Ok(phi.as_basic_value().into())
+                Ok(phi.as_basic_value())
             }
             _ => Err(JitError::UnsupportedType(
                 "Type mismatch in if expression branches".to_string(),
@@ -585,29 +672,117 @@ impl<'ctx> JitCompiler<'ctx> {
         }
     }
 
-    fn compile_array(
-        &mut self,
-        elements: &[Expression],
-    ) -> Result<BasicMetadataValueEnum<'ctx>, JitError> {
+    fn compile_array(&mut self, elements: &[Expression]) -> Result<BasicValueEnum<'ctx>, JitError> {
         if elements.is_empty() {
             return Err(JitError::UnsupportedType(
                 "Empty arrays not supported".to_string(),
             ));
         }
 
-        // For now, return a simple placeholder
-        // Full array implementation would require more complex type management
-        Ok(self
+        // Arrays of floats are `[N x double]` values, spilled to the stack when indexed
+        // and when bound with `let`
+        let array_type = self.context.f64_type().array_type(elements.len() as u32);
+        let mut array = array_type.get_undef();
+        for (i, element) in elements.iter().enumerate() {
+            let BasicValueEnum::FloatValue(value) = self.compile_expression(element)? else {
+                return Err(JitError::UnsupportedType(
+                    "Only arrays of floats are supported".to_string(),
+                ));
+            };
+            array = self
+                .builder
+                .build_insert_value(array, value, i as u32, "elem")
+                .map_err(|e| JitError::CompilationFailed(e.to_string()))?
+                .into_array_value();
+        }
+
+        self.array_ops_compiled += 1;
+        Ok(array.into())
+    }
+
+    /// Element of a float array. Constant indexes are bounds-checked here; others are
+    /// checked when the function runs, trapping when out of range.
+    fn compile_index(
+        &mut self,
+        array: &Expression,
+        index: &Expression,
+    ) -> Result<BasicValueEnum<'ctx>, JitError> {
+        let BasicValueEnum::ArrayValue(array) = self.compile_expression(array)? else {
+            return Err(JitError::UnsupportedType(
+                "Only arrays can be indexed".to_string(),
+            ));
+        };
+        let BasicValueEnum::IntValue(index) = self.compile_expression(index)? else {
+            return Err(JitError::UnsupportedType(
+                "Array index must be an integer".to_string(),
+            ));
+        };
+
+        let array_type = array.get_type();
+        if let Some(constant) = index.get_sign_extended_constant() {
+            if constant < 0 || constant >= array_type.len() as i64 {
+                return Err(JitError::CompilationFailed(format!(
+                    "Index {} out of bounds for array of length {}",
+                    constant,
+                    array_type.len()
+                )));
+            }
+        }
+
+        let function = self
+            .current_function
+            .ok_or_else(|| JitError::CompilationFailed("No current function".to_string()))?;
+        let in_bounds_block = self.context.append_basic_block(function, "index_ok");
+        let out_of_bounds_block = self.context.append_basic_block(function, "index_oob");
+        // Unsigned comparison also rejects negative indexes
+        let len = self
             .context
             .i64_type()
-            .const_int(elements.len() as u64, false)
-            .into())
+            .const_int(array_type.len() as u64, false);
+        let in_bounds = self
+            .builder
+            .build_int_compare(IntPredicate::ULT, index, len, "in_bounds")
+            .map_err(|e| JitError::CompilationFailed(e.to_string()))?;
+        self.builder
+            .build_conditional_branch(in_bounds, in_bounds_block, out_of_bounds_block)
+            .map_err(|e| JitError::CompilationFailed(e.to_string()))?;
+
+        self.builder.position_at_end(out_of_bounds_block);
+        let trap = Intrinsic::find("llvm.trap")
+            .and_then(|intrinsic| intrinsic.get_declaration(&self.module, &[]))
+            .ok_or_else(|| JitError::CompilationFailed("llvm.trap is unavailable".to_string()))?;
+        self.builder
+            .build_call(trap, &[], "")
+            .map_err(|e| JitError::CompilationFailed(e.to_string()))?;
+        self.builder
+            .build_unreachable()
+            .map_err(|e| JitError::CompilationFailed(e.to_string()))?;
+
+        self.builder.position_at_end(in_bounds_block);
+        let slot = self.create_entry_block_alloca("array", array_type.into());
+        self.builder
+            .build_store(slot, array)
+            .map_err(|e| JitError::CompilationFailed(e.to_string()))?;
+        let zero = self.context.i64_type().const_zero();
+        let element_ptr = unsafe {
+            self.builder
+                .build_in_bounds_gep(array_type, slot, &[zero, index], "elem_ptr")
+                .map_err(|e| JitError::CompilationFailed(e.to_string()))?
+        };
+        let element = self
+            .builder
+            .build_load(array_type.get_element_type(), element_ptr, "elem")
+            .map_err(|e| JitError::CompilationFailed(e.to_string()))?;
+
+        self.array_ops_compiled += 1;
+        Ok(element)
     }
 
     fn compile_block(
         &mut self,
         statements: &[Statement],
-    ) -> Result<BasicMetadataValueEnum<'ctx>, JitError> {
+        result: Option<&Expression>,
+    ) -> Result<BasicValueEnum<'ctx>, JitError> {
         let mut last_val = self.context.i64_type().const_int(0, false).into();
 
         for statement in statements {
@@ -623,103 +798,65 @@ impl<'ctx> JitCompiler<'ctx> {
             }
         }
 
-        Ok(last_val)
+        match result {
+            Some(expr) => self.compile_expression(expr),
+            None => Ok(last_val),
+        }
     }
 
-    fn compile_let_binding(&mut self, let_binding: &LetBinding) -> Result<(), JitError> {
+    fn compile_let_binding(
+        &mut self,
+        let_binding: &LetBinding,
+    ) -> Result<BasicValueEnum<'ctx>, JitError> {
         let value = self.compile_expression(&let_binding.value)?;
 
-        // Determine the type from the value
-        let value_type = match value {
-            BasicMetadataValueEnum::IntValue(int_val) => int_val.get_type().into(),
-            BasicMetadataValueEnum::FloatValue(float_val) => float_val.get_type().into(),
-            BasicMetadataValueEnum::PointerValue(ptr_val) => ptr_val.get_type().into(),
-            BasicMetadataValueEnum::ArrayValue(array_val) => array_val.get_type().into(),
-            BasicMetadataValueEnum::StructValue(struct_val) => struct_val.get_type().into(),
-            BasicMetadataValueEnum::VectorValue(vec_val) => vec_val.get_type().into(),
-        };
-
-        // Create alloca for the variable
-        let alloca = self.create_entry_block_alloca(&let_binding.name, value_type);
-
-        // Store the value
-        match value {
-            BasicMetadataValueEnum::IntValue(int_val) => {
-                self.builder
-                    .build_store(alloca, int_val)
-                    .map_err(|e| JitError::CompilationFailed(e.to_string()))?;
-            }
-            BasicMetadataValueEnum::FloatValue(float_val) => {
-                self.builder
-                    .build_store(alloca, float_val)
-                    .map_err(|e| JitError::CompilationFailed(e.to_string()))?;
-            }
-            BasicMetadataValueEnum::PointerValue(ptr_val) => {
-                self.builder
-                    .build_store(alloca, ptr_val)
-                    .map_err(|e| JitError::CompilationFailed(e.to_string()))?;
-            }
-            BasicMetadataValueEnum::ArrayValue(array_val) => {
-                self.builder
-                    .build_store(alloca, array_val)
-                    .map_err(|e| JitError::CompilationFailed(e.to_string()))?;
-            }
-            BasicMetadataValueEnum::StructValue(struct_val) => {
-                self.builder
-                    .build_store(alloca, struct_val)
-                    .map_err(|e| JitError::CompilationFailed(e.to_string()))?;
-            }
-            BasicMetadataValueEnum::VectorValue(vec_val) => {
-                self.builder
-                    .build_store(alloca, vec_val)
-                    .map_err(|e| JitError::CompilationFailed(e.to_string()))?;
-            }
-        }
+        // Create alloca for the variable and store the value
+        let alloca = self.create_entry_block_alloca(&let_binding.name, value.get_type());
+        self.builder
+            .build_store(alloca, value)
+            .map_err(|e| JitError::CompilationFailed(e.to_string()))?;
 
         // Store the variable for later use
-        self.variables.insert(let_binding.name.clone(), alloca);
+        self.variables
+            .insert(let_binding.name.clone(), (alloca, value.get_type()));
 
-        Ok(())
+        Ok(value)
     }
 
     // Helper methods
     fn compile_arithmetic_op<F>(
         &self,
-        left: BasicMetadataValueEnum<'ctx>,
-        right: BasicMetadataValueEnum<'ctx>,
-        name: &str,
+        left: BasicValueEnum<'ctx>,
+        right: BasicValueEnum<'ctx>,
         op: F,
-    ) -> Result<BasicMetadataValueEnum<'ctx>, JitError>
+    ) -> Result<BasicValueEnum<'ctx>, JitError>
     where
         F: Fn(
             &Builder<'ctx>,
-            BasicMetadataValueEnum<'ctx>,
-            BasicMetadataValueEnum<'ctx>,
-        ) -> Result<BasicMetadataValueEnum<'ctx>, String>,
+            BasicValueEnum<'ctx>,
+            BasicValueEnum<'ctx>,
+        ) -> Result<BasicValueEnum<'ctx>, String>,
     {
-        op(&self.builder, left, right).map_err(|e| JitError::CompilationFailed(e))
+        op(&self.builder, left, right).map_err(JitError::CompilationFailed)
     }
 
     fn compile_comparison_op<F>(
         &self,
-        left: BasicMetadataValueEnum<'ctx>,
-        right: BasicMetadataValueEnum<'ctx>,
+        left: BasicValueEnum<'ctx>,
+        right: BasicValueEnum<'ctx>,
         op: F,
-    ) -> Result<BasicMetadataValueEnum<'ctx>, JitError>
+    ) -> Result<BasicValueEnum<'ctx>, JitError>
     where
         F: Fn(
             &Builder<'ctx>,
-            BasicMetadataValueEnum<'ctx>,
-            BasicMetadataValueEnum<'ctx>,
-        ) -> Result<BasicMetadataValueEnum<'ctx>, String>,
+            BasicValueEnum<'ctx>,
+            BasicValueEnum<'ctx>,
+        ) -> Result<BasicValueEnum<'ctx>, String>,
     {
-        op(&self.builder, left, right).map_err(|e| JitError::CompilationFailed(e))
+        op(&self.builder, left, right).map_err(JitError::CompilationFailed)
     }
 
-    fn matrix_type_to_llvm(
-        &self,
-        matrix_type: &Type,
-    ) -> Result<BasicMetadataTypeEnum<'ctx>, JitError> {
+    fn matrix_type_to_llvm(&self, matrix_type: &Type) -> Result<BasicTypeEnum<'ctx>, JitError> {
         match matrix_type {
             Type::Int => Ok(self.context.i64_type().into()),
             Type::Float => Ok(self.context.f64_type().into()),
@@ -731,9 +868,9 @@ impl<'ctx> JitCompiler<'ctx> {
                 .into()),
             Type::Array(element_type) => {
                 let element_llvm_type = self.matrix_type_to_llvm(element_type)?;
-                if let BasicMetadataTypeEnum::IntType(int_type) = element_llvm_type {
+                if let BasicTypeEnum::IntType(int_type) = element_llvm_type {
                     Ok(int_type.ptr_type(AddressSpace::default()).into())
-                } else if let BasicMetadataTypeEnum::FloatType(float_type) = element_llvm_type {
+                } else if let BasicTypeEnum::FloatType(float_type) = element_llvm_type {
                     Ok(float_type.ptr_type(AddressSpace::default()).into())
                 } else {
                     Err(JitError::UnsupportedType(
@@ -741,7 +878,7 @@ impl<'ctx> JitCompiler<'ctx> {
                     ))
                 }
             }
-            Type::Any => Ok(self.context.i64_type().into()), // Default to i64 for Any type
+            Type::Spanned(inner, _) => self.matrix_type_to_llvm(inner),
             _ => Err(JitError::UnsupportedType(format!(
                 "Type {:?} not supported in JIT",
                 matrix_type
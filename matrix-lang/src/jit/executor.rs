@@ -1,7 +1,7 @@
 // JIT Execution Engine
 // Handles execution of JIT compiled functions
 
-use super::{CompiledFunction, JitError};
+use super::{JitError, JitValue, ResultKind};
 use crate::eval::interpreter::{RuntimeError, RuntimeResult, Value};
 
 #[cfg(feature = "jit")]
@@ -57,6 +57,37 @@ impl<'ctx> JitExecutor<'ctx> {
             }),
         }
     }
+
+    /// Run a program compiled by `JitCompiler::compile_program` and decode the result
+    /// it writes to its output buffer
+    pub fn run_program(&self, name: &str, kind: ResultKind) -> Result<JitValue, JitError> {
+        let slots = match kind {
+            ResultKind::Array(len) => (len as usize).max(1),
+            _ => 1,
+        };
+        // Eight-byte slots hold one i64, f64 or i1 each; arrays fill one per element
+        let mut out = vec![0u64; slots];
+        unsafe {
+            let program: JitFunction<unsafe extern "C" fn(*mut u64)> = self
+                .execution_engine
+                .get_function(name)
+                .map_err(|e| JitError::FunctionNotFound(format!("{}: {}", name, e)))?;
+            program.call(out.as_mut_ptr());
+        }
+
+        Ok(match kind {
+            ResultKind::Unit => JitValue::Unit,
+            ResultKind::Int => JitValue::Int(out[0] as i64),
+            ResultKind::Float => JitValue::Float(f64::from_bits(out[0])),
+            ResultKind::Bool => JitValue::Bool(out[0] & 0xff != 0),
+            ResultKind::Array(len) => JitValue::Array(
+                out[..len as usize]
+                    .iter()
+                    .map(|&bits| f64::from_bits(bits))
+                    .collect(),
+            ),
+        })
+    }
 }
 
 #[cfg(not(feature = "jit"))]
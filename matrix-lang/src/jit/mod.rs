@@ -2,15 +2,19 @@
 // Provides Just-In-Time compilation using LLVM backend
 
 use crate::ast::nodes::*;
+#[cfg(feature = "jit")]
+use inkwell::targets::{InitializationConfig, Target};
 use std::collections::HashMap;
 use thiserror::Error;
 
 // Re-export submodules
+pub mod ast_hash;
 pub mod compiler;
 pub mod executor;
 pub mod optimization;
 
-pub use compiler::JitCompiler;
+pub use ast_hash::ast_hash;
+pub use compiler::{JitCompiler, ResultKind};
 pub use executor::JitExecutor;
 pub use optimization::{JitOptimizer, OptimizationLevel};
 
@@ -51,6 +55,16 @@ pub enum JitError {
     LlvmError(String),
 }
 
+/// Result of a JIT compiled program
+#[derive(Debug, Clone, PartialEq)]
+pub enum JitValue {
+    Int(i64),
+    Float(f64),
+    Bool(bool),
+    Unit,
+    Array(Vec<f64>),
+}
+
 /// Statistics about JIT compilation
 #[derive(Debug, Clone)]
 pub struct JitStats {
@@ -59,6 +73,8 @@ pub struct JitStats {
     pub optimizations_applied: usize,
     pub cache_hits: usize,
     pub cache_misses: usize,
+    /// Array creations and element reads compiled
+    pub array_ops_compiled: usize,
 }
 
 impl Default for JitStats {
@@ -69,6 +85,7 @@ impl Default for JitStats {
             optimizations_applied: 0,
             cache_hits: 0,
             cache_misses: 0,
+            array_ops_compiled: 0,
         }
     }
 }
@@ -77,9 +94,14 @@ impl Default for JitStats {
 #[cfg(feature = "jit")]
 pub struct CompiledFunction<'ctx> {
     pub name: String,
+    /// `ast_hash` of the definition the function was compiled from; `None` is never
+    /// matched, so the definition is compiled again each time
+    pub ast_hash: Option<u64>,
+    /// The definition's function, named `<name>.<n>` so each definition of a name has
+    /// its own symbol in the execution engine
     pub function_value: FunctionValue<'ctx>,
-    pub param_types: Vec<crate::types::types::Type>,
-    pub return_type: crate::types::types::Type,
+    pub param_types: Vec<Type>,
+    pub return_type: Type,
     pub optimization_level: OptimizationLevel,
 }
 
@@ -88,13 +110,18 @@ pub struct CompiledFunction {
     pub name: String,
 }
 
-/// Main JIT context that manages compilation and execution
+/// Main JIT context that manages compilation and execution. Every function definition
+/// and every program is compiled into a module of its own and added to the execution
+/// engine, because MCJIT generates a module's code once, the first time anything in the
+/// engine is looked up, and ignores whatever is added to that module afterwards.
 #[cfg(feature = "jit")]
 pub struct JitContext<'ctx> {
     context: &'ctx Context,
     execution_engine: ExecutionEngine<'ctx>,
     compiler: JitCompiler<'ctx>,
-    optimizer: JitOptimizer<'ctx>,
+    optimizer: JitOptimizer,
+    /// Modules handed to the execution engine, oldest first
+    modules: Vec<Module<'ctx>>,
     compiled_functions: HashMap<String, CompiledFunction<'ctx>>,
     stats: JitStats,
     optimization_level: OptimizationLevel,
@@ -109,62 +136,64 @@ impl<'ctx> JitContext<'ctx> {
 
     /// Create a new JIT context with specified optimization level
     pub fn with_optimization(opt_level: OptimizationLevel) -> Result<Self, JitError> {
+        Target::initialize_native(&InitializationConfig::default()).map_err(JitError::LlvmError)?;
         let context = Box::leak(Box::new(Context::create()));
         let compiler = JitCompiler::new(context, "matrix_jit");
         let execution_engine = compiler
             .module
             .create_jit_execution_engine(inkwell::OptimizationLevel::Default)
             .map_err(|e| JitError::LlvmError(e.to_string()))?;
-        let optimizer = JitOptimizer::new(opt_level.clone());
+        let optimizer = JitOptimizer::new(opt_level)?;
 
         Ok(JitContext {
             context,
             execution_engine,
             compiler,
             optimizer,
+            modules: Vec::new(),
             compiled_functions: HashMap::new(),
             stats: JitStats::default(),
             optimization_level: opt_level,
         })
     }
 
-    /// Compile a function to LLVM IR and native code
+    /// Compile a function to LLVM IR and native code. A definition identical to the one
+    /// last compiled under its name reuses that code; a changed one replaces it for
+    /// code compiled afterwards, while earlier callers keep the definition they were
+    /// compiled against, as closures do in the interpreter.
     pub fn compile_function(&mut self, func_def: &FunctionDef) -> Result<String, JitError> {
         let start_time = std::time::Instant::now();
 
-        // Check if function is already compiled
-        if self.compiled_functions.contains_key(&func_def.name) {
-            self.stats.cache_hits += 1;
-            return Ok(func_def.name.clone());
+        let ast_hash = ast_hash(func_def);
+        if let Some(compiled) = self.compiled_functions.get(&func_def.name) {
+            if ast_hash.is_some() && compiled.ast_hash == ast_hash {
+                self.stats.cache_hits += 1;
+                return Ok(func_def.name.clone());
+            }
         }
 
         self.stats.cache_misses += 1;
 
-        // Compile function to LLVM IR
-        let function_value = self.compiler.compile_function_def(func_def)?;
-
-        // Apply optimizations
-        self.optimizer.optimize_module(&self.compiler.module)?;
-        self.stats.optimizations_applied += 1;
+        // Compile function to LLVM IR. Functions compiled against an earlier definition
+        // of the name keep calling that one, under its own symbol.
+        let symbol = format!("{}.{}", func_def.name, self.modules.len());
+        let function_value = self.compile_module(&symbol, |compiler| {
+            compiler.compile_function_def(func_def, &symbol)
+        })?;
 
         // Create compiled function record
         let compiled_func = CompiledFunction {
             name: func_def.name.clone(),
+            ast_hash,
             function_value,
             param_types: func_def
                 .params
                 .iter()
-                .map(|p| {
-                    p.param_type
-                        .clone()
-                        .unwrap_or(crate::types::types::Type::Any)
-                })
+                .map(|p| p.type_annotation.clone())
                 .collect(),
-            return_type: func_def
-                .return_type
-                .clone()
-                .unwrap_or(crate::types::types::Type::Any),
-            optimization_level: self.optimization_level.clone(),
+            // Compilation fails without a return type annotation
+            return_type: func_def.return_type.clone().unwrap_or(Type::Unit),
+            optimization_level: self.optimization_level,
         };
 
         // Store compiled function
@@ -185,15 +214,60 @@ impl<'ctx> JitContext<'ctx> {
         args: &[crate::eval::interpreter::Value],
     ) -> Result<crate::eval::interpreter::Value, JitError> {
         if let Some(compiled_func) = self.compiled_functions.get(name) {
-            let executor = JitExecutor::new(&self.execution_engine);
+            let symbol = compiled_func.function_value.get_name().to_string_lossy();
+            let executor = JitExecutor::new(self.execution_engine.clone());
             executor
-                .execute_function(name, args)
+                .execute_function(&symbol, args)
                 .map_err(|e| JitError::ExecutionFailed(e.to_string()))
         } else {
             Err(JitError::FunctionNotFound(name.to_string()))
         }
     }
 
+    /// Compile a whole program and run it, returning the value of its last let binding
+    pub fn compile_and_execute(&mut self, program: &Program) -> Result<JitValue, JitError> {
+        let start_time = std::time::Instant::now();
+
+        // Definitions go through the cache, so unchanged ones are not compiled again
+        for item in &program.items {
+            if let Item::FunctionDef(func_def) = item {
+                self.compile_function(func_def)?;
+            }
+        }
+
+        let name = format!("__jit_program_{}", self.modules.len());
+        let kind =
+            self.compile_module(&name, |compiler| compiler.compile_program(program, &name))?;
+
+        self.stats.functions_compiled += 1;
+        self.stats.array_ops_compiled = self.compiler.array_ops_compiled;
+        self.stats.total_compilation_time_ms += start_time.elapsed().as_millis() as u64;
+
+        JitExecutor::new(self.execution_engine.clone()).run_program(&name, kind)
+    }
+
+    /// Compile into a fresh module named `name`, then optimize it and add it to the
+    /// execution engine. A module that fails to compile is dropped without reaching
+    /// the engine.
+    fn compile_module<T>(
+        &mut self,
+        name: &str,
+        compile: impl FnOnce(&mut JitCompiler<'ctx>) -> Result<T, JitError>,
+    ) -> Result<T, JitError> {
+        let base = std::mem::replace(&mut self.compiler.module, self.context.create_module(name));
+        let compiled = compile(&mut self.compiler);
+        let module = std::mem::replace(&mut self.compiler.module, base);
+        let compiled = compiled?;
+
+        self.optimizer.optimize_module(&module)?;
+        self.stats.optimizations_applied += 1;
+        self.execution_engine
+            .add_module(&module)
+            .map_err(|()| JitError::LlvmError(format!("Could not add module '{}'", name)))?;
+        self.modules.push(module);
+        Ok(compiled)
+    }
+
     /// Check if a function is compiled
     pub fn is_function_compiled(&self, name: &str) -> bool {
         self.compiled_functions.contains_key(name)
@@ -214,17 +288,21 @@ impl<'ctx> JitContext<'ctx> {
     /// Set optimization level for future compilations
     pub fn set_optimization_level(&mut self, level: OptimizationLevel) {
         self.optimization_level = level;
-        self.optimizer = JitOptimizer::new(level);
+        self.optimizer.set_optimization_level(level);
     }
 
-    /// Get the LLVM module for inspection
-    pub fn get_module(&self) -> &Module<'ctx> {
-        &self.compiler.module
+    /// Get the LLVM modules compiled so far for inspection, oldest first
+    pub fn get_modules(&self) -> &[Module<'ctx>] {
+        &self.modules
     }
 
     /// Dump LLVM IR to string for debugging
     pub fn dump_ir(&self) -> String {
-        self.compiler.module.print_to_string().to_string()
+        self.modules
+            .iter()
+            .map(|module| module.print_to_string().to_string())
+            .collect::<Vec<_>>()
+            .join("\n")
     }
 }
 
@@ -254,6 +332,10 @@ impl JitContext {
         Err(JitError::NotAvailable)
     }
 
+    pub fn compile_and_execute(&mut self, _program: &Program) -> Result<JitValue, JitError> {
+        Err(JitError::NotAvailable)
+    }
+
     pub fn is_function_compiled(&self, _name: &str) -> bool {
         false
     }
@@ -270,3 +352,125 @@ impl JitContext {
         "JIT not available".to_string()
     }
 }
+
+#[cfg(all(test, feature = "jit"))]
+mod tests {
+    use super::*;
+    use crate::{Lexer, Parser};
+
+    #[test]
+    fn test_float_arrays_are_created_and_indexed() {
+        let program = Parser::new(Lexer::new("let r = [1.0, 2.0, 3.0][1]"))
+            .and_then(|mut parser| parser.parse_program())
+            .unwrap();
+        let mut jit = JitContext::new().unwrap();
+        assert_eq!(
+            jit.compile_and_execute(&program).unwrap(),
+            JitValue::Float(2.0)
+        );
+        // One array created and one element read
+        assert_eq!(jit.get_stats().array_ops_compiled, 2);
+
+        let program = Parser::new(Lexer::new("let xs = [0.5, 1.5]"))
+            .and_then(|mut parser| parser.parse_program())
+            .unwrap();
+        assert_eq!(
+            jit.compile_and_execute(&program).unwrap(),
+            JitValue::Array(vec![0.5, 1.5])
+        );
+        assert_eq!(jit.get_stats().array_ops_compiled, 3);
+    }
+
+    fn program(source: &str) -> Program {
+        Parser::new(Lexer::new(source))
+            .and_then(|mut parser| parser.parse_program())
+            .unwrap()
+    }
+
+    fn function_def(source: &str) -> FunctionDef {
+        match program(source).items.into_iter().next() {
+            Some(Item::FunctionDef(func_def)) => func_def,
+            other => panic!("expected a function definition, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_functions_compiled_after_a_program_ran_can_be_called() {
+        let mut jit = JitContext::new().unwrap();
+        assert_eq!(
+            jit.compile_and_execute(&program("let a = 1.0")).unwrap(),
+            JitValue::Float(1.0)
+        );
+
+        let half = function_def("@jit\nlet half = (x: Float) -> Float => x / 2.0");
+        jit.compile_function(&half).unwrap();
+        assert_eq!(
+            jit.compile_and_execute(&program("let b = half(3.0)"))
+                .unwrap(),
+            JitValue::Float(1.5)
+        );
+        assert!(jit
+            .get_modules()
+            .iter()
+            .all(|module| module.verify().is_ok()));
+    }
+
+    #[test]
+    fn test_runtime_indexes_are_bounds_checked() {
+        let pick = function_def("@jit\nlet pick = (i: Int) -> Float => [1.0, 2.0, 3.0][i]");
+        let mut jit = JitContext::new().unwrap();
+        jit.compile_function(&pick).unwrap();
+        // An index only known at run time branches to a trap when out of range
+        assert!(jit.dump_ir().contains("llvm.trap"), "{}", jit.dump_ir());
+    }
+
+    #[test]
+    fn test_unchanged_functions_come_from_the_cache() {
+        let square = function_def("@jit\nlet square = (x: Float) -> Float => x * x");
+        let mut jit = JitContext::new().unwrap();
+
+        jit.compile_function(&square).unwrap();
+        jit.compile_function(&square).unwrap();
+        assert_eq!(jit.get_stats().cache_misses, 1);
+        assert_eq!(jit.get_stats().cache_hits, 1);
+        assert_eq!(jit.get_stats().functions_compiled, 1);
+
+        // A changed definition under the same name is compiled again
+        let cube = function_def("@jit\nlet square = (x: Float) -> Float => x * x * x");
+        jit.compile_function(&cube).unwrap();
+        assert_eq!(jit.get_stats().cache_misses, 2);
+        assert_eq!(jit.get_stats().functions_compiled, 2);
+    }
+
+    #[test]
+    fn test_callers_keep_the_definition_they_were_compiled_against() {
+        let source = concat!(
+            "@jit\nlet square = (x: Float) -> Float => x * x\n",
+            "@jit\nlet twice = (x: Float) -> Float => square(x) * 2.0\n",
+            "@jit\nlet square = (x: Float) -> Float => x * x * x\n",
+            "let r = twice(3.0)",
+        );
+        let mut jit = JitContext::new().unwrap();
+
+        // Like the interpreter, where a function captures the definitions in scope when
+        // it is defined, `twice` keeps calling the first `square`
+        assert_eq!(
+            jit.compile_and_execute(&program(source)).unwrap(),
+            JitValue::Float(18.0)
+        );
+        assert_eq!(
+            crate::execute(source).unwrap(),
+            crate::eval::interpreter::Value::Float(18.0)
+        );
+        // New callers get the new definition
+        assert_eq!(
+            jit.compile_and_execute(&program("let s = square(3.0)"))
+                .unwrap(),
+            JitValue::Float(27.0)
+        );
+        assert!(jit
+            .get_modules()
+            .iter()
+            .all(|module| module.verify().is_ok()));
+    }
+}
@@ -1,77 +1,75 @@
 // JIT Optimization Passes
 // Handles optimization of JIT compiled code
 
-use super::{JitError, OptimizationLevel};
+use super::JitError;
 
 #[cfg(feature = "jit")]
 use inkwell::{
     module::Module,
-    passes::{PassManager, PassManagerBuilder},
+    passes::PassBuilderOptions,
+    targets::{CodeModel, RelocMode, Target, TargetMachine},
     OptimizationLevel as InkwellOptLevel,
 };
 
+/// How much optimization JIT compiled code receives
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OptimizationLevel {
+    None,
+    Less,
+    Default,
+    Aggressive,
+    Size,
+}
+
 /// Optimizer for JIT compiled code
 #[cfg(feature = "jit")]
-pub struct JitOptimizer<'ctx> {
-    pass_manager: PassManager<Module<'ctx>>,
+pub struct JitOptimizer {
+    target_machine: TargetMachine,
     optimization_level: OptimizationLevel,
 }
 
 #[cfg(feature = "jit")]
-impl<'ctx> JitOptimizer<'ctx> {
-    pub fn new(optimization_level: OptimizationLevel) -> Self {
-        let pass_manager = PassManager::create(());
+impl JitOptimizer {
+    /// Create an optimizer for the host. The native target must already be initialized.
+    pub fn new(optimization_level: OptimizationLevel) -> Result<Self, JitError> {
+        let triple = TargetMachine::get_default_triple();
+        let target =
+            Target::from_triple(&triple).map_err(|e| JitError::LlvmError(e.to_string()))?;
+        let target_machine = target
+            .create_target_machine(
+                &triple,
+                &TargetMachine::get_host_cpu_name().to_string(),
+                &TargetMachine::get_host_cpu_features().to_string(),
+                InkwellOptLevel::Default,
+                RelocMode::Default,
+                CodeModel::JITDefault,
+            )
+            .ok_or_else(|| {
+                JitError::LlvmError("Could not create a target machine for the host".to_string())
+            })?;
 
-        JitOptimizer {
-            pass_manager,
+        Ok(JitOptimizer {
+            target_machine,
             optimization_level,
-        }
-    }
-
-    /// Apply optimization passes to a module
-    pub fn optimize_module(&self, module: &Module<'ctx>) -> Result<(), JitError> {
-        match self.optimization_level {
-            OptimizationLevel::None => Ok(()),
-            OptimizationLevel::Less => self.apply_basic_optimizations(module),
-            OptimizationLevel::Default => self.apply_standard_optimizations(module),
-            OptimizationLevel::Aggressive => self.apply_aggressive_optimizations(module),
-            OptimizationLevel::Size => self.apply_size_optimizations(module),
-        }
+        })
     }
 
-    fn apply_basic_optimizations(&self, module: &Module<'ctx>) -> Result<(), JitError> {
-        // Basic optimizations: constant folding, dead code elimination
-        self.pass_manager.add_constant_merge_pass();
-        self.pass_manager.add_dead_store_elimination_pass();
-        self.pass_manager.run_on(module);
-        Ok(())
+    pub fn set_optimization_level(&mut self, optimization_level: OptimizationLevel) {
+        self.optimization_level = optimization_level;
     }
 
-    fn apply_standard_optimizations(&self, module: &Module<'ctx>) -> Result<(), JitError> {
-        // Standard optimizations
-        let pass_manager_builder = PassManagerBuilder::create();
-        pass_manager_builder.set_optimization_level(InkwellOptLevel::Default);
-        pass_manager_builder.populate_module_pass_manager(&self.pass_manager);
-        self.pass_manager.run_on(module);
-        Ok(())
-    }
-
-    fn apply_aggressive_optimizations(&self, module: &Module<'ctx>) -> Result<(), JitError> {
-        // Aggressive optimizations
-        let pass_manager_builder = PassManagerBuilder::create();
-        pass_manager_builder.set_optimization_level(InkwellOptLevel::Aggressive);
-        pass_manager_builder.populate_module_pass_manager(&self.pass_manager);
-        self.pass_manager.run_on(module);
-        Ok(())
-    }
-
-    fn apply_size_optimizations(&self, module: &Module<'ctx>) -> Result<(), JitError> {
-        // Size optimizations
-        self.pass_manager.add_constant_merge_pass();
-        self.pass_manager.add_dead_store_elimination_pass();
-        self.pass_manager.add_strip_dead_prototypes_pass();
-        self.pass_manager.run_on(module);
-        Ok(())
+    /// Apply optimization passes to a module
+    pub fn optimize_module(&self, module: &Module<'_>) -> Result<(), JitError> {
+        let pipeline = match self.optimization_level {
+            OptimizationLevel::None => return Ok(()),
+            OptimizationLevel::Less => "default<O1>",
+            OptimizationLevel::Default => "default<O2>",
+            OptimizationLevel::Aggressive => "default<O3>",
+            OptimizationLevel::Size => "default<Os>",
+        };
+        module
+            .run_passes(pipeline, &self.target_machine, PassBuilderOptions::create())
+            .map_err(|e| JitError::OptimizationFailed(e.to_string()))
     }
 }
 
@@ -80,10 +78,12 @@ pub struct JitOptimizer;
 
 #[cfg(not(feature = "jit"))]
 impl JitOptimizer {
-    pub fn new(_optimization_level: OptimizationLevel) -> Self {
-        JitOptimizer
+    pub fn new(_optimization_level: OptimizationLevel) -> Result<Self, JitError> {
+        Ok(JitOptimizer)
     }
 
+    pub fn set_optimization_level(&mut self, _optimization_level: OptimizationLevel) {}
+
     pub fn optimize_module(&self, _module: ()) -> Result<(), JitError> {
         Err(JitError::OptimizationFailed(
             "JIT optimization not available".to_string(),
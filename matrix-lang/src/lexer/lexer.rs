@@ -1,5 +1,5 @@
 use crate::ast::Span;
-use crate::lexer::tokens::Token;
+use crate::lexer::tokens::{invalid_digit_offset, Token};
 use logos::Logos;
 
 /// A token with position information
@@ -40,12 +40,23 @@ impl<'input> Lexer<'input> {
         match self.lexer.next() {
             Some(Ok(token)) => {
                 let span = self.current_span();
-                self.update_position();
                 TokenWithSpan::new(token, span)
             }
             Some(Err(_)) => {
-                let span = self.current_span();
-                self.update_position();
+                let mut span = self.current_span();
+                // Point at the bad digit of a malformed `0x`/`0b`/`0o` literal
+                if let Some(offset) = invalid_digit_offset(self.lexer.slice()) {
+                    let width = self.lexer.slice()[offset..]
+                        .chars()
+                        .next()
+                        .map_or(0, char::len_utf8);
+                    span = Span::new(
+                        span.start + offset,
+                        span.start + offset + width,
+                        span.line,
+                        span.column + offset,
+                    );
+                }
                 TokenWithSpan::new(Token::Error, span)
             }
             None => {
@@ -90,13 +101,17 @@ impl<'input> Lexer<'input> {
         Ok(tokens)
     }
 
-    fn current_span(&self) -> Span {
+    /// Span of the token just lexed. Skipped whitespace and comments before it
+    /// are counted first so the token reports its own line and column.
+    fn current_span(&mut self) -> Span {
         let range = self.lexer.span();
-        Span::new(range.start, range.end, self.line, self.column)
+        self.update_position(range.start);
+        let span = Span::new(range.start, range.end, self.line, self.column);
+        self.update_position(range.end);
+        span
     }
 
-    fn update_position(&mut self) {
-        let current_pos = self.lexer.span().end;
+    fn update_position(&mut self, current_pos: usize) {
         let slice = &self.input[self.last_pos..current_pos];
 
         for ch in slice.chars() {
@@ -176,6 +191,42 @@ mod tests {
         assert_eq!(tokens[2].token, Token::FloatLiteral(1.5e-10));
     }
 
+    #[test]
+    fn test_prefixed_integer_literals() {
+        let tokens = Lexer::new("0x1F 0XfF 0b1010 0o755 0").tokenize().unwrap();
+
+        assert_eq!(tokens[0].token, Token::IntLiteral(31));
+        assert_eq!(tokens[1].token, Token::IntLiteral(255));
+        assert_eq!(tokens[2].token, Token::IntLiteral(10));
+        assert_eq!(tokens[3].token, Token::IntLiteral(493));
+        assert_eq!(tokens[4].token, Token::IntLiteral(0));
+    }
+
+    #[test]
+    fn test_invalid_prefixed_digit_points_at_character() {
+        let mut lexer = Lexer::new("let m = 0b12");
+        let error = (0..4).map(|_| lexer.next_token()).last().unwrap();
+        assert_eq!(error.token, Token::Error);
+        assert_eq!((error.span.start, error.span.column), (11, 12));
+
+        let message = Lexer::new("0o78").tokenize().unwrap_err();
+        assert!(message.contains("column 4"), "{}", message);
+        assert!(Lexer::new("0x").tokenize().is_err());
+    }
+
+    #[test]
+    fn test_scientific_notation() {
+        let input = "1.0e3 1e3 2E-2";
+        let lexer = Lexer::new(input);
+
+        let tokens: Vec<_> = lexer.tokenize().unwrap();
+
+        assert_eq!(tokens[0].token, Token::FloatLiteral(1000.0));
+        assert_eq!(tokens[1].token, Token::FloatLiteral(1000.0));
+        assert_eq!(tokens[2].token, Token::FloatLiteral(0.02));
+        assert_eq!(tokens.len(), 4);
+    }
+
     #[test]
     fn test_operators() {
         let input = "+ - * / % ^ ?? => -> == != <= >=";
@@ -242,4 +293,13 @@ mod tests {
         assert_eq!(tokens[6].token, Token::Equal);
         assert_eq!(tokens[7].token, Token::FloatLiteral(3.14));
     }
+
+    #[test]
+    fn test_token_positions_after_newlines() {
+        let tokens = Lexer::new("let a = 1\n  let b = 2").tokenize().unwrap();
+
+        assert_eq!((tokens[0].span.line, tokens[0].span.column), (1, 1));
+        assert_eq!((tokens[4].span.line, tokens[4].span.column), (2, 3));
+        assert_eq!((tokens[5].span.line, tokens[5].span.column), (2, 7));
+    }
 }
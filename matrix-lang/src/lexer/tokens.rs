@@ -18,6 +18,10 @@ pub enum Token {
     Else,
     #[token("match")]
     Match,
+    #[token("while")]
+    While,
+    #[token("for")]
+    For,
     #[token("Some")]
     Some,
     #[token("None")]
@@ -38,6 +42,10 @@ pub enum Token {
     Plot,
     #[token("import")]
     Import,
+    #[token("module")]
+    Module,
+    #[token("pub")]
+    Pub,
     #[token("return")]
     Return,
     #[token("true")]
@@ -144,10 +152,14 @@ pub enum Token {
     Underscore,
 
     // Literals
-    #[regex(r"[0-9]+", |lex| lex.slice().parse::<i64>().ok())]
+    #[regex(r"[0-9][0-9_]*", |lex| lex.slice().replace('_', "").parse::<i64>().ok())]
+    #[regex(r"0[xX][0-9a-zA-Z]*", |lex| parse_prefixed_int(lex.slice(), 16))]
+    #[regex(r"0[bB][0-9a-zA-Z]*", |lex| parse_prefixed_int(lex.slice(), 2))]
+    #[regex(r"0[oO][0-9a-zA-Z]*", |lex| parse_prefixed_int(lex.slice(), 8))]
     IntLiteral(i64),
 
     #[regex(r"[0-9]+\.[0-9]+([eE][+-]?[0-9]+)?", |lex| lex.slice().parse::<f64>().ok())]
+    #[regex(r"[0-9]+[eE][+-]?[0-9]+", |lex| lex.slice().parse::<f64>().ok())]
     FloatLiteral(f64),
 
     #[regex(r#""([^"\\]|\\.)*""#, |lex| {
@@ -175,6 +187,32 @@ pub enum Token {
     Eof,
 }
 
+/// Value of a `0x`, `0b` or `0o` literal. The regexes take every alphanumeric
+/// character after the prefix, so a stray digit fails the whole literal instead of
+/// starting a new token.
+fn parse_prefixed_int(slice: &str, radix: u32) -> Option<i64> {
+    i64::from_str_radix(&slice[2..], radix).ok()
+}
+
+/// Byte offset of the first character that is not a digit of a prefixed literal's
+/// radix, or of its end when the prefix has no digits. `None` for other slices.
+pub fn invalid_digit_offset(slice: &str) -> Option<usize> {
+    let radix = match slice.get(..2)?.to_ascii_lowercase().as_str() {
+        "0x" => 16,
+        "0b" => 2,
+        "0o" => 8,
+        _ => return None,
+    };
+    let digits = &slice[2..];
+    if digits.is_empty() {
+        return Some(2);
+    }
+    digits
+        .char_indices()
+        .find(|(_, c)| !c.is_digit(radix))
+        .map(|(i, _)| i + 2)
+}
+
 impl fmt::Display for Token {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
@@ -185,6 +223,8 @@ impl fmt::Display for Token {
             Token::If => write!(f, "if"),
             Token::Else => write!(f, "else"),
             Token::Match => write!(f, "match"),
+            Token::While => write!(f, "while"),
+            Token::For => write!(f, "for"),
             Token::Some => write!(f, "Some"),
             Token::None => write!(f, "None"),
             Token::In => write!(f, "in"),
@@ -195,6 +235,8 @@ impl fmt::Display for Token {
             Token::Sim => write!(f, "sim"),
             Token::Plot => write!(f, "plot"),
             Token::Import => write!(f, "import"),
+            Token::Module => write!(f, "module"),
+            Token::Pub => write!(f, "pub"),
             Token::Return => write!(f, "return"),
             Token::True => write!(f, "true"),
             Token::False => write!(f, "false"),
@@ -273,6 +315,8 @@ impl Token {
                 | Token::If
                 | Token::Else
                 | Token::Match
+                | Token::While
+                | Token::For
                 | Token::Some
                 | Token::None
                 | Token::In
@@ -281,6 +325,8 @@ impl Token {
                 | Token::Wait
                 | Token::Gpu
                 | Token::Import
+                | Token::Module
+                | Token::Pub
                 | Token::Return
                 | Token::True
                 | Token::False
@@ -348,6 +394,8 @@ mod tests {
         assert_eq!(Token::lexer("if").next(), Some(Ok(Token::If)));
         assert_eq!(Token::lexer("else").next(), Some(Ok(Token::Else)));
         assert_eq!(Token::lexer("match").next(), Some(Ok(Token::Match)));
+        assert_eq!(Token::lexer("while").next(), Some(Ok(Token::While)));
+        assert_eq!(Token::lexer("for").next(), Some(Ok(Token::For)));
         assert_eq!(Token::lexer("Some").next(), Some(Ok(Token::Some)));
         assert_eq!(Token::lexer("None").next(), Some(Ok(Token::None)));
         assert_eq!(Token::lexer("in").next(), Some(Ok(Token::In)));
@@ -356,6 +404,8 @@ mod tests {
         assert_eq!(Token::lexer("wait").next(), Some(Ok(Token::Wait)));
         assert_eq!(Token::lexer("gpu").next(), Some(Ok(Token::Gpu)));
         assert_eq!(Token::lexer("import").next(), Some(Ok(Token::Import)));
+        assert_eq!(Token::lexer("module").next(), Some(Ok(Token::Module)));
+        assert_eq!(Token::lexer("pub").next(), Some(Ok(Token::Pub)));
         assert_eq!(Token::lexer("return").next(), Some(Ok(Token::Return)));
         assert_eq!(Token::lexer("true").next(), Some(Ok(Token::True)));
         assert_eq!(Token::lexer("false").next(), Some(Ok(Token::False)));
@@ -424,6 +474,11 @@ mod tests {
             panic!("Expected IntLiteral(42)");
         }
 
+        // Underscores separate digit groups
+        let mut lexer = Token::lexer("1_000_000");
+        assert_eq!(lexer.next(), Some(Ok(Token::IntLiteral(1_000_000))));
+        assert_eq!(lexer.next(), None);
+
         // Test float literals
         let mut lexer = Token::lexer("3.14");
         if let Some(Ok(Token::FloatLiteral(value))) = lexer.next() {
@@ -40,6 +40,16 @@ pub const DESCRIPTION: &str = env!("CARGO_PKG_DESCRIPTION");
 
 /// Initialize the Matrix Language runtime with default configuration
 pub fn init() -> Interpreter {
+    let mut interpreter = init_without_prelude();
+
+    // Helpers written in Matrix Language, built on the functions above
+    stdlib::prelude::load_prelude(&mut interpreter);
+
+    interpreter
+}
+
+/// Initialize with the standard library builtins only, leaving out the prelude
+pub fn init_without_prelude() -> Interpreter {
     let mut interpreter = Interpreter::new();
 
     // Register standard library functions
@@ -82,6 +92,12 @@ mod tests {
         assert_eq!(result, Value::Int(8));
     }
 
+    #[test]
+    fn test_prefixed_integer_literals() {
+        assert_eq!(execute("let v = 0xFF").unwrap(), Value::Int(255));
+        assert_eq!(execute("let mask = 0b1010 + 0o10").unwrap(), Value::Int(18));
+    }
+
     #[test]
     fn test_function_definition() {
         let source = r#"
@@ -92,6 +108,539 @@ mod tests {
         assert_eq!(result, Value::Int(30));
     }
 
+    #[test]
+    fn test_default_parameter_values() {
+        let source = r#"
+            let step = (x: Float, dt: Float = 0.5) => x + dt
+            let scaled = (x: Float, k: Float = x * 2.0) => x * k
+            let result = step(1.0) + step(1.0, 2.0) + scaled(3.0)
+        "#;
+        let result = execute(source).unwrap();
+        assert_eq!(result, Value::Float(22.5));
+
+        let missing = execute("let f = (a: Int, b: Int = 1) => a + b let r = f()");
+        assert!(missing.is_err());
+    }
+
+    #[test]
+    fn test_match_negative_literal() {
+        let source = r#"
+            let sign = (x: Int) => match x { -1 => 10, 0 => 20, _ => 30 }
+            let result = sign(-1) + sign(0) + sign(1 - 3)
+        "#;
+        let result = execute(source).unwrap();
+        assert_eq!(result, Value::Int(60));
+    }
+
+    #[test]
+    fn test_if_and_ternary_produce_values() {
+        let source = r#"
+            let limit = 3
+            let x = if limit > 2 { 10 } else { 20 }
+            let y = limit > 5 ? 1 : 2
+            let result = x + y
+        "#;
+        assert_eq!(execute(source).unwrap(), Value::Int(12));
+        assert_eq!(
+            execute("let nothing = if false { 1 }").unwrap(),
+            Value::Unit
+        );
+    }
+
+    #[test]
+    fn test_format_builtin_is_variadic() {
+        let result = execute(r#"let s = format("{0} and {1}", "a", "b")"#).unwrap();
+        assert!(matches!(result, Value::String(s) if s == "a and b"));
+        assert!(execute(r#"let s = format("{3}", 1)"#).is_err());
+    }
+
+    #[test]
+    fn test_eval_builtin() {
+        assert!(matches!(
+            execute(r#"let r = eval("2 + 3")"#).unwrap(),
+            Value::Int(5)
+        ));
+        assert!(matches!(
+            execute(r#"let x = 10 let r = eval("x * 2")"#).unwrap(),
+            Value::Int(20)
+        ));
+        // Programs run in the caller's environment
+        assert!(matches!(
+            execute(r#"let a = eval("let y = 4") let r = y + 1"#).unwrap(),
+            Value::Int(5)
+        ));
+
+        let error = execute(r#"let r = eval("2 +")"#).unwrap_err();
+        assert!(error.to_string().contains("Parse error in eval"));
+        assert!(execute(r#"let r = eval(42)"#).is_err());
+
+        // A host builtin that happens to be named eval runs its own function
+        let mut interpreter = init();
+        let host_eval = Value::BuiltinFunction {
+            name: "eval".to_string(),
+            arity: 1,
+            func: |_args| Ok(Value::Int(7)),
+        };
+        assert_eq!(
+            interpreter
+                .call_value(host_eval, &[Value::String("2 + 3".to_string())])
+                .unwrap(),
+            Value::Int(7)
+        );
+    }
+
+    #[test]
+    fn test_matrix_builders() {
+        let identity =
+            execute("let m = matrix_from_fn(3, 3, (i: Int, j: Int) => if i == j { 1 } else { 0 })")
+                .unwrap();
+        let expected: Vec<Vec<Value>> = (0..3)
+            .map(|i| (0..3).map(|j| Value::Int((i == j) as i64)).collect())
+            .collect();
+        assert_eq!(identity, Value::Matrix(expected));
+
+        assert_eq!(
+            execute("let m = matrix_fill(2, 3, 7.5)").unwrap(),
+            Value::Matrix(vec![vec![Value::Float(7.5); 3]; 2])
+        );
+
+        assert!(execute("let m = matrix_fill(0, 3, 1)").is_err());
+        assert!(execute("let m = matrix_from_fn(2, -1, (i: Int, j: Int) => i)").is_err());
+    }
+
+    #[test]
+    fn test_while_loop() {
+        // A `let` in the body rebinds the enclosing name for the next check
+        let source = r#"
+            let n = 0
+            let total = 0
+            let done = while n < 5 {
+                let n = n + 1
+                let total = total + n
+            }
+            let result = [n, total]
+        "#;
+        assert_eq!(
+            execute(source).unwrap(),
+            Value::Array(vec![Value::Int(5), Value::Int(15)])
+        );
+        assert_eq!(execute("let r = while false { 1 }").unwrap(), Value::Unit);
+        assert_eq!(
+            execute("let done = false\nlet r = while done {}").unwrap(),
+            Value::Unit
+        );
+
+        let err = execute("let r = while 1 { 0 }").unwrap_err();
+        assert!(
+            matches!(err.without_location(), RuntimeError::TypeError { .. }),
+            "{:?}",
+            err
+        );
+    }
+
+    #[test]
+    fn test_for_loop_body_shares_the_enclosing_scope() {
+        // There is no per-iteration scope. The language has no assignment, so `let`
+        // in the body is how totals carry across iterations.
+        let source = r#"
+            let sum = 0
+            let a = for i in 1..4 { let sum = sum + i }
+            let b = for x in [2, 3] { let sum = sum + x * x }
+            let total = sum
+        "#;
+        assert_eq!(execute(source).unwrap(), Value::Int(19));
+
+        // The same holds for names first bound inside the body
+        let source = r#"
+            let r = for i in [1, 2, 3] { let last = i * 10 }
+            let after = last
+        "#;
+        assert_eq!(execute(source).unwrap(), Value::Int(30));
+
+        // The loop variable is only bound inside the loop
+        let source = r#"
+            let x = "outer"
+            let xs = [1, 2]
+            let r = for x in xs {}
+            let after = x
+        "#;
+        assert_eq!(execute(source).unwrap(), Value::String("outer".to_string()));
+
+        let err = execute("let r = for x in 5 { x }").unwrap_err();
+        assert!(
+            matches!(err.without_location(), RuntimeError::TypeError { .. }),
+            "{:?}",
+            err
+        );
+    }
+
+    #[test]
+    fn test_array_combinators() {
+        assert_eq!(
+            execute("let doubled = map((x: Int) => x * 2, [1, 2, 3])").unwrap(),
+            Value::Array(vec![Value::Int(2), Value::Int(4), Value::Int(6)])
+        );
+        assert_eq!(
+            execute("let big = filter((x: Int) => x > 2, [3, 1, 4, 1, 5])").unwrap(),
+            Value::Array(vec![Value::Int(3), Value::Int(4), Value::Int(5)])
+        );
+        assert_eq!(
+            execute("let total = fold((acc: Int, x: Int) => acc + x, 10, [1, 2, 3])").unwrap(),
+            Value::Int(16)
+        );
+        // Builtins work as the function argument too
+        assert_eq!(
+            execute("let roots = map(sqrt, [4.0, 9.0])").unwrap(),
+            Value::Array(vec![Value::Float(2.0), Value::Float(3.0)])
+        );
+
+        assert!(execute("let bad = filter((x: Int) => x, [1, 2])").is_err());
+        assert!(execute("let bad = map((x: Int) => x, 3)").is_err());
+    }
+
+    #[test]
+    fn test_closures_capture_their_environment() {
+        let source = r#"
+            let make_adder = (n) => (x) => x + n
+            let add5 = make_adder(5)
+            let result = add5(3)
+        "#;
+        assert_eq!(execute(source).unwrap(), Value::Int(8));
+
+        // Captured by value: rebinding `y` afterwards does not change the closure
+        let source = r#"
+            let y = 1
+            let add_y = (x) => x + y
+            let y = 100
+            let result = add_y(1)
+        "#;
+        assert_eq!(execute(source).unwrap(), Value::Int(2));
+
+        assert_eq!(
+            execute("let product = fold((a, b) => a * b, 1, [2, 3, 4])").unwrap(),
+            Value::Int(24)
+        );
+        // Parenthesized expressions still parse as before
+        assert_eq!(
+            execute("let y = 3\nlet z = (y) * 2").unwrap(),
+            Value::Int(6)
+        );
+    }
+
+    #[test]
+    fn test_tail_calls_run_in_constant_stack() {
+        let source = r#"
+            let count_down = (n) => if n == 0 { 0 } else { count_down(n - 1) }
+            let result = count_down(1_000_000)
+        "#;
+        assert_eq!(execute(source).unwrap(), Value::Int(0));
+
+        let source = r#"
+            let factorial = (n, acc) => if n <= 1 { acc } else { factorial(n - 1, acc * n) }
+            let result = factorial(20, 1)
+        "#;
+        assert_eq!(
+            execute(source).unwrap(),
+            Value::Int(2_432_902_008_176_640_000)
+        );
+
+        // Recursion outside tail position still works, just on the native stack
+        let source = r#"
+            let fib = (n) => if n < 2 { n } else { fib(n - 1) + fib(n - 2) }
+            let result = fib(15)
+        "#;
+        assert_eq!(execute(source).unwrap(), Value::Int(610));
+
+        // Once the body rebinds the name, a tail call through it calls the new value
+        let source = r#"
+            let f = (n) => if n == 0 { 0 } else { { let f = (n) => n + 100; f(n) } }
+            let result = f(1)
+        "#;
+        assert_eq!(execute(source).unwrap(), Value::Int(101));
+    }
+
+    #[test]
+    fn test_option_and_result_values() {
+        assert_eq!(execute("let x = unwrap(Some(5))").unwrap(), Value::Int(5));
+        let err = execute("let x = unwrap(None)").unwrap_err();
+        assert!(matches!(
+            err.without_location(),
+            RuntimeError::Generic { message } if message.contains("None")
+        ));
+        assert_eq!(
+            execute(r#"let x = unwrap(Ok("fine"))"#).unwrap(),
+            Value::String("fine".to_string())
+        );
+        assert!(execute(r#"let x = unwrap(Err("broken"))"#).is_err());
+
+        assert_eq!(
+            execute("let x = [is_some(Some(1)), is_some(None)]").unwrap(),
+            Value::Array(vec![Value::Bool(true), Value::Bool(false)])
+        );
+        assert_eq!(
+            execute("let x = map_option((v) => v * 2, Some(21))").unwrap(),
+            Value::Option(Some(Box::new(Value::Int(42))))
+        );
+        assert_eq!(
+            execute("let x = map_option((v) => v * 2, None)").unwrap(),
+            Value::Option(None)
+        );
+
+        // match destructures both, binding only inside the arm
+        let source = r#"
+            let v = 100
+            let describe = (o) => match o { Some(v) => v + 1, None => 0 }
+            let checked = (r) => match r { Ok(v) if v > 0 => v, Ok(_) => 0, Err(e) => -1 }
+            let x = [describe(Some(4)), describe(None), checked(Ok(7)), checked(Ok(-7)), checked(Err("bad")), v]
+        "#;
+        assert_eq!(
+            execute(source).unwrap(),
+            Value::Array([5, 0, 7, 0, -1, 100].into_iter().map(Value::Int).collect())
+        );
+    }
+
+    #[test]
+    fn test_modules_namespace_their_members() {
+        let source = r#"
+            module Geometry {
+                let square = (x) => x * x
+                pub let area = (r) => 3.0 * square(r)
+                pub let scale = 2.0
+            }
+            module Physics {
+                pub let scale = 10.0
+            }
+            let result = Geometry::area(Geometry::scale) + Physics::scale
+        "#;
+        assert_eq!(execute(source).unwrap(), Value::Float(22.0));
+
+        // Members without pub stay inside the module
+        let err = execute(
+            "module Geometry {\n let square = (x) => x * x\n }\nlet s = Geometry::square(2)",
+        )
+        .unwrap_err();
+        assert!(matches!(
+            err.without_location(),
+            RuntimeError::PrivateMember { module, member }
+                if module == "Geometry" && member == "square"
+        ));
+
+        // Importing brings the exports into scope unqualified, but not private members
+        let source = r#"
+            module Units {
+                let factor = 100.0
+                pub let to_cm = (m) => m * factor
+            }
+            import Units
+            let cm = to_cm(1.5)
+        "#;
+        assert_eq!(execute(source).unwrap(), Value::Float(150.0));
+        assert!(execute("module M {\n let hidden = 1\n }\nimport M\nlet x = hidden").is_err());
+        assert!(execute("module M {\n let hidden = 1\n }\nimport M { hidden }").is_err());
+    }
+
+    #[test]
+    fn test_dict_literal_and_indexing() {
+        let source = r#"
+            let body = { shape: "box", "half size": 0.5, mass: 2.0 }
+            let heavier = dict_set(body, "mass", body["mass"] * 2.0)
+            let mass = heavier["mass"] + heavier["half size"]
+        "#;
+        assert_eq!(execute(source).unwrap(), Value::Float(4.5));
+        assert_eq!(
+            execute(r#"let keys = dict_keys({ b: 1, a: 2 })"#).unwrap(),
+            Value::Array(vec![
+                Value::String("a".to_string()),
+                Value::String("b".to_string())
+            ])
+        );
+        assert_eq!(
+            execute(r#"let has = dict_contains({ a: 1 }, "b")"#).unwrap(),
+            Value::Bool(false)
+        );
+        assert_eq!(execute("let x = [10, 20, 30][1]").unwrap(), Value::Int(20));
+
+        // Missing keys are errors, whether indexed or looked up
+        let err = execute(r#"let m = { a: 1 }["b"]"#).unwrap_err();
+        assert!(
+            matches!(err.without_location(), RuntimeError::FieldNotFound { .. }),
+            "{:?}",
+            err
+        );
+        assert!(execute(r#"let m = dict_get({ a: 1 }, "b")"#).is_err());
+        let err = execute("let x = [1, 2][2]").unwrap_err();
+        assert!(matches!(
+            err.without_location(),
+            RuntimeError::IndexOutOfBounds {
+                index: 2,
+                length: 2
+            }
+        ));
+    }
+
+    #[test]
+    fn test_array_slicing() {
+        let ints = |values: &[i64]| Value::Array(values.iter().map(|&i| Value::Int(i)).collect());
+
+        assert_eq!(
+            execute("let s = [10, 20, 30, 40][1..3]").unwrap(),
+            ints(&[20, 30])
+        );
+        assert_eq!(
+            execute("let s = [10, 20, 30, 40][1..=3]").unwrap(),
+            ints(&[20, 30, 40])
+        );
+        assert_eq!(
+            execute("let s = [10, 20, 30, 40][-3..-1]").unwrap(),
+            ints(&[20, 30])
+        );
+        assert_eq!(
+            execute("let xs = [10, 20, 30, 40]\nlet n = 2\nlet s = xs[n..n]").unwrap(),
+            ints(&[])
+        );
+
+        let err = execute("let s = [10, 20, 30, 40][2..5]").unwrap_err();
+        assert!(matches!(
+            err.without_location(),
+            RuntimeError::IndexOutOfBounds {
+                index: 5,
+                length: 4
+            }
+        ));
+        assert!(execute("let s = [10, 20][-3..1]").is_err());
+        assert!(execute("let s = [10, 20, 30][2..1]").is_err());
+    }
+
+    #[test]
+    fn test_prelude_helpers() {
+        let source = r#"
+            let a = clamp01(1.5) + clamp01(-2.0) + clamp01(0.25)
+            let r = a + lerp(0.0, 10.0, 0.5) + distance(vec3(1.0, 2.0, 3.0), vec3(4.0, 6.0, 3.0))
+        "#;
+        assert_eq!(execute(source).unwrap(), Value::Float(11.25));
+
+        let mut bare = init_without_prelude();
+        let program = Parser::new(Lexer::new("let r = clamp01(0.5)"))
+            .and_then(|mut parser| parser.parse_program())
+            .unwrap();
+        assert!(bare.eval_program(&program).is_err());
+    }
+
+    #[test]
+    fn test_builtin_introspection() {
+        match execute("let names = list_builtins()").unwrap() {
+            Value::Array(names) => {
+                let names: Vec<String> = names
+                    .into_iter()
+                    .map(|name| match name {
+                        Value::String(name) => name,
+                        other => panic!("expected a name, got {:?}", other),
+                    })
+                    .collect();
+                assert!(names.contains(&"sqrt".to_string()));
+                assert!(names.contains(&"vec3".to_string()));
+                assert!(names.windows(2).all(|pair| pair[0] < pair[1]));
+            }
+            other => panic!("expected an array, got {:?}", other),
+        }
+
+        assert!(matches!(
+            execute(r#"let n = builtin_arity("pow")"#).unwrap(),
+            Value::Int(2)
+        ));
+        assert!(matches!(
+            execute(r#"let n = builtin_arity("format")"#).unwrap(),
+            Value::Int(-1)
+        ));
+        assert!(execute(r#"let n = builtin_arity("no_such_builtin")"#).is_err());
+    }
+
+    #[test]
+    fn test_runtime_errors_report_their_position() {
+        let err = execute("let a = 1\nlet b = a + undefined_var").unwrap_err();
+        assert_eq!(err.location(), Some((2, 13)));
+        assert!(matches!(
+            err.without_location(),
+            RuntimeError::UndefinedVariable { name } if name == "undefined_var"
+        ));
+
+        // Errors inside a function point into its body, not at the call. Spans of
+        // compound expressions carry the column of their last token.
+        let source = "let f = (x) => {\n  x + true\n}\nlet r = f(1)";
+        assert_eq!(execute(source).unwrap_err().location(), Some((2, 7)));
+    }
+
+    #[test]
+    fn test_non_trailing_default_is_rejected() {
+        let source = "let f = (a: Int = 1, b: Int) => a + b let r = f(1, 2)";
+        let error = execute(source).unwrap_err();
+        assert!(error.to_string().contains("without a default"));
+    }
+
+    /// `let x = 0` followed by `n` items of `let x = x + 1`
+    fn counting_program(n: usize) -> Program {
+        let span = Span::new(0, 0, 1, 1);
+        let binding = |value| {
+            Item::LetBinding(LetBinding {
+                name: "x".to_string(),
+                type_annotation: None,
+                value,
+                span: span.clone(),
+            })
+        };
+        let increment = Expression::BinaryOp {
+            left: Box::new(Expression::Identifier("x".to_string(), span.clone())),
+            operator: BinaryOperator::Add,
+            right: Box::new(Expression::IntLiteral(1, span.clone())),
+            span: span.clone(),
+        };
+
+        let mut items = vec![binding(Expression::IntLiteral(0, span.clone()))];
+        items.extend((0..n).map(|_| binding(increment.clone())));
+        Program { items, span }
+    }
+
+    #[test]
+    fn test_cancel_flag_aborts_long_program() {
+        use std::sync::atomic::{AtomicBool, Ordering};
+        use std::sync::Arc;
+        use std::time::{Duration, Instant};
+
+        let program = counting_program(1_000_000);
+        let mut interpreter = init();
+        let flag = Arc::new(AtomicBool::new(false));
+        interpreter.set_cancel_flag(flag.clone());
+
+        let canceller = std::thread::spawn(move || {
+            std::thread::sleep(Duration::from_millis(20));
+            flag.store(true, Ordering::Relaxed);
+            Instant::now()
+        });
+        let result = interpreter.eval_program(&program);
+        let stopped = Instant::now();
+        let cancelled_at = canceller.join().unwrap();
+
+        assert_eq!(
+            result.as_ref().map_err(RuntimeError::without_location),
+            Err(&RuntimeError::Generic {
+                message: "cancelled".to_string()
+            })
+        );
+        assert!(stopped.duration_since(cancelled_at) < Duration::from_millis(100));
+        assert!(!matches!(
+            interpreter.environment.get("x"),
+            Some(Value::Int(1_000_000))
+        ));
+    }
+
+    #[test]
+    fn test_unset_cancel_flag_lets_program_finish() {
+        let mut interpreter = init();
+        interpreter.set_cancel_flag(Default::default());
+        let result = interpreter.eval_program(&counting_program(1_000));
+        assert_eq!(result, Ok(Value::Int(1_000)));
+    }
+
     #[test]
     fn test_matrix_operations() {
         let source = r#"
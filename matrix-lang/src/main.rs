@@ -45,6 +45,12 @@ fn main() {
                 .help("Use JIT compilation instead of interpretation")
                 .action(clap::ArgAction::SetTrue),
         )
+        .arg(
+            Arg::new("emit-ir")
+                .long("emit-ir")
+                .help("Print the IR for FILE, annotated with source lines, instead of running it")
+                .action(clap::ArgAction::SetTrue),
+        )
         .arg(
             Arg::new("physics-gui")
                 .long("physics-gui")
@@ -66,6 +72,11 @@ fn main() {
     } else if matches.get_flag("repl") || matches.get_one::<String>("file").is_none() {
         let use_jit = matches.get_flag("jit");
         run_repl(use_jit);
+    } else if let (true, Some(filename)) = (
+        matches.get_flag("emit-ir"),
+        matches.get_one::<String>("file"),
+    ) {
+        emit_ir(filename);
     } else if let Some(filename) = matches.get_one::<String>("file") {
         let use_jit = matches.get_flag("jit");
         run_file(filename, use_jit);
@@ -95,10 +106,26 @@ fn launch_physics_cli() {
     physics_cli::run_physics_repl();
 }
 
+/// Set up the JIT, saying whether it is in use; on failure the REPL keeps interpreting
+#[cfg(feature = "jit")]
+fn start_jit() -> Option<crate::jit::JitContext<'static>> {
+    match crate::jit::JitContext::new() {
+        Ok(jit) => {
+            println!("JIT compiler enabled - compiling to machine code");
+            Some(jit)
+        }
+        Err(e) => {
+            eprintln!("JIT compiler unavailable, using interpretation mode: {}", e);
+            None
+        }
+    }
+}
+
 fn run_repl(use_jit: bool) {
     if use_jit {
         println!("Matrix Language JIT-compiled REPL v0.1.0");
-        println!("JIT compilation enabled - compiling to machine code");
+        #[cfg(not(feature = "jit"))]
+        println!("JIT compilation not available in this build - using interpretation mode");
     } else {
         println!("Matrix Language Interpreter REPL v0.1.0");
         println!("Using interpretation mode");
@@ -114,14 +141,11 @@ fn run_repl(use_jit: bool) {
     // Create persistent interpreter for REPL session
     let mut interpreter = Interpreter::new();
     crate::stdlib::register_all(&mut interpreter);
+    crate::stdlib::prelude::load_prelude(&mut interpreter);
     let mut type_checker = TypeChecker::new();
 
     #[cfg(feature = "jit")]
-    let mut jit_compiler = if use_jit {
-        Some(crate::jit::JitCompiler::new())
-    } else {
-        None
-    };
+    let mut jit_compiler = if use_jit { start_jit() } else { None };
 
     loop {
         let readline = rl.readline(">> ");
@@ -148,11 +172,14 @@ fn run_repl(use_jit: bool) {
                         print!("\x1B[2J\x1B[1;1H");
                         continue;
                     }
+                    "builtins" => {
+                        print_builtins();
+                        continue;
+                    }
                     "jit" => {
                         #[cfg(feature = "jit")]
                         if jit_compiler.is_none() {
-                            jit_compiler = Some(crate::jit::JitCompiler::new());
-                            println!("JIT compiler enabled");
+                            jit_compiler = start_jit();
                         } else {
                             println!("JIT compiler already enabled");
                         }
@@ -229,8 +256,7 @@ fn run_file(filename: &str, use_jit: bool) {
     let result = if use_jit {
         #[cfg(feature = "jit")]
         {
-            let mut jit = crate::jit::JitCompiler::new();
-            execute_source_jit(&source, &mut jit)
+            execute_source_jit(&source)
         }
         #[cfg(not(feature = "jit"))]
         {
@@ -252,6 +278,28 @@ fn run_file(filename: &str, use_jit: bool) {
     }
 }
 
+fn emit_ir(filename: &str) {
+    let source = match fs::read_to_string(filename) {
+        Ok(content) => content,
+        Err(err) => {
+            eprintln!("Error reading file '{}': {}", filename, err);
+            std::process::exit(1);
+        }
+    };
+
+    let program = Parser::new(Lexer::new(&source)).and_then(|mut parser| parser.parse_program());
+    match program {
+        Ok(program) => {
+            let module = crate::ir::IrGenerator::new().generate(&program);
+            print!("{}", crate::ir::IrPrinter.print_module(&module));
+        }
+        Err(err) => {
+            eprintln!("Parse error: {}", err);
+            std::process::exit(1);
+        }
+    }
+}
+
 fn execute_repl_line_interpret(
     source: &str,
     interpreter: &mut Interpreter,
@@ -278,7 +326,7 @@ fn execute_repl_line_interpret(
                     }
                 }
             }
-            Err(e) => Err(format!("Runtime error: {}", e)),
+            Err(e) => Err(format_runtime_error(&e)),
         }
     } else {
         // Try parsing as a full program (for let bindings, function definitions, etc.)
@@ -290,15 +338,15 @@ fn execute_repl_line_interpret(
             .parse_program()
             .map_err(|e| format!("Parse error: {}", e))?;
 
-        // Type checking
+        // Type checking against the declarations from earlier lines
         type_checker
-            .check_program(&ast)
+            .check_incremental(&ast)
             .map_err(|e| format!("Type error: {}", e))?;
 
         // Evaluation/Interpretation
         let result = interpreter
             .eval_program(&ast)
-            .map_err(|e| format!("Runtime error: {}", e))?;
+            .map_err(|e| format_runtime_error(&e))?;
 
         // Only print result if it's not Unit (empty)
         match result {
@@ -316,7 +364,7 @@ fn execute_repl_line_interpret(
 #[cfg(feature = "jit")]
 fn execute_repl_line_jit(
     source: &str,
-    jit: &mut crate::jit::JitCompiler,
+    jit: &mut crate::jit::JitContext,
     type_checker: &mut TypeChecker,
 ) -> Result<(), Box<dyn std::error::Error>> {
     // Parse
@@ -327,9 +375,9 @@ fn execute_repl_line_jit(
         .parse_program()
         .map_err(|e| format!("Parse error: {}", e))?;
 
-    // Type checking
+    // Type checking against the declarations from earlier lines
     type_checker
-        .check_program(&ast)
+        .check_incremental(&ast)
         .map_err(|e| format!("Type error: {}", e))?;
 
     println!("✓ Type checking passed");
@@ -365,9 +413,10 @@ fn execute_source_interpret(source: &str) -> Result<(), Box<dyn std::error::Erro
     // Interpretation
     let mut interpreter = Interpreter::new();
     crate::stdlib::register_all(&mut interpreter);
+    crate::stdlib::prelude::load_prelude(&mut interpreter);
     let result = interpreter
         .eval_program(&ast)
-        .map_err(|e| format!("Runtime error: {}", e))?;
+        .map_err(|e| format_runtime_error(&e))?;
 
     // Only print result if it's not Unit (empty)
     match result {
@@ -379,10 +428,7 @@ fn execute_source_interpret(source: &str) -> Result<(), Box<dyn std::error::Erro
 }
 
 #[cfg(feature = "jit")]
-fn execute_source_jit(
-    source: &str,
-    jit: &mut crate::jit::JitCompiler,
-) -> Result<(), Box<dyn std::error::Error>> {
+fn execute_source_jit(source: &str) -> Result<(), Box<dyn std::error::Error>> {
     // Parse
     let lexer = Lexer::new(source);
     let mut parser =
@@ -400,6 +446,8 @@ fn execute_source_jit(
     println!("✓ Type checking passed");
 
     // JIT compilation and execution
+    let mut jit =
+        crate::jit::JitContext::new().map_err(|e| format!("JIT initialization error: {}", e))?;
     let result = jit
         .compile_and_execute(&ast)
         .map_err(|e| format!("JIT error: {}", e))?;
@@ -415,6 +463,7 @@ fn print_help() {
     println!("  help           - Show this help message");
     println!("  exit, quit     - Exit the REPL");
     println!("  clear          - Clear the screen");
+    println!("  builtins       - List builtin functions and their arities");
     println!("  jit            - Enable JIT compilation");
     println!("  interpret      - Switch to interpretation mode");
     println!();
@@ -429,6 +478,29 @@ fn print_help() {
     println!("  let comp = [i * j | i in 1..3, j in 1..3]");
 }
 
+fn print_builtins() {
+    for name in crate::stdlib::builtin_names() {
+        match crate::stdlib::builtin_arity(&name) {
+            Some(crate::eval::interpreter::VARIADIC) => println!("  {}(...)", name),
+            Some(arity) => println!("  {}/{}", name, arity),
+            None => {}
+        }
+    }
+}
+
+/// Runtime error message, with the position of the failing expression when known
+fn format_runtime_error(error: &crate::eval::interpreter::RuntimeError) -> String {
+    match error.location() {
+        Some((line, column)) => format!(
+            "Runtime error at line {}, col {}: {}",
+            line,
+            column,
+            error.without_location()
+        ),
+        None => format!("Runtime error: {}", error),
+    }
+}
+
 fn format_result(value: &crate::eval::interpreter::Value) -> String {
     match value {
         crate::eval::interpreter::Value::Int(i) => i.to_string(),
@@ -450,6 +522,24 @@ fn format_result(value: &crate::eval::interpreter::Value) -> String {
                 .collect();
             format!("[{}]", rows.join(", "))
         }
+        crate::eval::interpreter::Value::Dict(dict) => {
+            let mut entries: Vec<String> = dict
+                .iter()
+                .map(|(k, v)| format!("{}: {}", k, format_result(v)))
+                .collect();
+            entries.sort();
+            format!("{{{}}}", entries.join(", "))
+        }
+        crate::eval::interpreter::Value::Option(Some(value)) => {
+            format!("Some({})", format_result(value))
+        }
+        crate::eval::interpreter::Value::Option(None) => "None".to_string(),
+        crate::eval::interpreter::Value::Result(Ok(value)) => {
+            format!("Ok({})", format_result(value))
+        }
+        crate::eval::interpreter::Value::Result(Err(error)) => {
+            format!("Err({})", format_result(error))
+        }
         crate::eval::interpreter::Value::Struct { name, fields } => {
             let field_strs: Vec<String> = fields
                 .iter()
@@ -458,7 +548,8 @@ fn format_result(value: &crate::eval::interpreter::Value) -> String {
             format!("{} {{ {} }}", name, field_strs.join(", "))
         }
         crate::eval::interpreter::Value::Function { .. } => "<function>".to_string(),
-        crate::eval::interpreter::Value::BuiltinFunction { name, .. } => {
+        crate::eval::interpreter::Value::BuiltinFunction { name, .. }
+        | crate::eval::interpreter::Value::InterpreterFunction { name, .. } => {
             format!("<builtin: {}>", name)
         }
         crate::eval::interpreter::Value::AsyncHandle(task) => {
@@ -487,6 +578,14 @@ fn format_jit_result(result: &crate::jit::JitValue) -> String {
         crate::jit::JitValue::Float(f) => f.to_string(),
         crate::jit::JitValue::Bool(b) => b.to_string(),
         crate::jit::JitValue::Unit => "()".to_string(),
+        crate::jit::JitValue::Array(elements) => format!(
+            "[{}]",
+            elements
+                .iter()
+                .map(|x| x.to_string())
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
     }
 }
 
@@ -701,6 +800,7 @@ mod physics_cli {
                 // Create interpreter and load physics functions
                 let mut interpreter = Interpreter::new();
                 crate::stdlib::register_all(&mut interpreter);
+                crate::stdlib::prelude::load_prelude(&mut interpreter);
 
                 // Try to execute the script
                 match execute_matrix_script(&content, &mut interpreter) {
@@ -737,7 +837,7 @@ mod physics_cli {
                 // Standalone expression
                 interpreter
                     .eval_expression(&expr)
-                    .map_err(|e| format!("Runtime error: {}", e))?
+                    .map_err(|e| super::format_runtime_error(&e))?
             }
             Err(_) => {
                 // Try as full program
@@ -749,7 +849,7 @@ mod physics_cli {
                     .map_err(|e| format!("Parse error: {}", e))?;
                 interpreter
                     .eval_program(&ast)
-                    .map_err(|e| format!("Runtime error: {}", e))?
+                    .map_err(|e| super::format_runtime_error(&e))?
             }
         };
 
@@ -773,11 +873,23 @@ mod physics_cli {
 
     fn show_status() {
         println!("Physics Simulation Status:");
-        println!("- Time: 0.0 seconds");
-        println!("- Active Bodies: 0");
-        println!("- Constraints: 0");
-        println!("- Performance: 60 FPS");
-        println!("- Memory: 8.2 MB");
+        let worlds = crate::stdlib::world_statuses();
+        if worlds.is_empty() {
+            println!("- No physics worlds (load a script to create one)");
+        }
+        for world in worlds {
+            println!("- World {}:", world.id);
+            println!("  Time: {:.3} seconds", world.sim_time);
+            println!("  Bodies: {}", world.bodies);
+            println!("  Constraints: {}", world.constraints);
+            println!(
+                "  Last step: {:.3} ms ({} active, {} sleeping, {} contacts)",
+                world.stats.step_time_ms,
+                world.stats.active_bodies,
+                world.stats.sleeping_bodies,
+                world.stats.contacts_resolved
+            );
+        }
     }
 
     fn list_objects() {
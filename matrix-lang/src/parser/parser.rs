@@ -1,6 +1,7 @@
 use crate::ast::*;
 use crate::lexer::{Lexer, Token, TokenWithSpan};
 use crate::parser::error::{ParseError, ParseResult};
+use crate::types::units::Dimension;
 use std::collections::HashMap;
 
 pub struct Parser<'input> {
@@ -53,6 +54,7 @@ impl<'input> Parser<'input> {
             Token::Instance => Ok(Item::InstanceDef(self.parse_instance_def()?)),
             Token::Let => Ok(Item::LetBinding(self.parse_let_binding()?)),
             Token::Import => Ok(Item::Import(self.parse_import()?)),
+            Token::Module => Ok(Item::Module(self.parse_module_def()?)),
             Token::At => {
                 // Function with attributes
                 let attributes = self.parse_attributes()?;
@@ -63,7 +65,7 @@ impl<'input> Parser<'input> {
             }
             _ => {
                 return Err(ParseError::unexpected_token(
-                    "struct, typeclass, instance, let, or import",
+                    "struct, typeclass, instance, let, import, or module",
                     &self.current_token.token.to_string(),
                     &self.current_token.span,
                 ));
@@ -71,6 +73,62 @@ impl<'input> Parser<'input> {
         }
     }
 
+    fn parse_module_def(&mut self) -> ParseResult<ModuleDef> {
+        let start_span = self.current_token.span.clone();
+        self.expect(Token::Module)?;
+        let name = self.expect_identifier()?;
+        self.expect(Token::LeftBrace)?;
+
+        let mut items = Vec::new();
+        let mut exports = Vec::new();
+        while !self.check(&Token::RightBrace) && !self.is_at_end() {
+            let public = self.check(&Token::Pub);
+            if public {
+                self.advance();
+                // Only values can be exported
+                if !self.check(&Token::Let) && !self.check(&Token::At) {
+                    return Err(ParseError::unexpected_token(
+                        "let after pub",
+                        &self.current_token.token.to_string(),
+                        &self.current_token.span,
+                    ));
+                }
+            }
+            if self.check(&Token::Module) {
+                return Err(ParseError::unexpected_token(
+                    "module member (modules cannot be nested)",
+                    "module",
+                    &self.current_token.span,
+                ));
+            }
+
+            let item = self.parse_item()?;
+            if public {
+                match &item {
+                    Item::LetBinding(binding) => exports.push(binding.name.clone()),
+                    Item::FunctionDef(func_def) => exports.push(func_def.name.clone()),
+                    _ => unreachable!("pub is only accepted before let"),
+                }
+            }
+            items.push(item);
+        }
+
+        self.expect(Token::RightBrace)?;
+        let end_span = self.previous_span();
+
+        Ok(ModuleDef {
+            name,
+            items,
+            exports,
+            span: Span::new(
+                start_span.start,
+                end_span.end,
+                start_span.line,
+                end_span.column,
+            ),
+        })
+    }
+
     fn parse_struct_def(&mut self) -> ParseResult<StructDef> {
         let start_span = self.current_token.span.clone();
         self.expect(Token::Struct)?;
@@ -247,6 +305,7 @@ impl<'input> Parser<'input> {
         }
 
         self.expect(Token::RightParen)?;
+        Self::check_trailing_defaults(&params)?;
         self.expect(Token::Equal)?;
 
         let body = self.parse_expression()?;
@@ -319,6 +378,7 @@ impl<'input> Parser<'input> {
         }
 
         self.expect(Token::RightParen)?;
+        Self::check_trailing_defaults(&params)?;
 
         let mut return_type = None;
         if self.check(&Token::ThinArrow) {
@@ -360,13 +420,25 @@ impl<'input> Parser<'input> {
         let start_span = self.current_token.span.clone();
         let name = self.expect_identifier()?;
 
-        self.expect(Token::Colon)?;
-        let type_annotation = self.parse_type()?;
+        let type_annotation = if self.check(&Token::Colon) {
+            self.advance();
+            self.parse_type()?
+        } else {
+            Type::TypeVar(UNTYPED_PARAMETER.to_string())
+        };
+
+        let default = if self.check(&Token::Equal) {
+            self.advance();
+            Some(self.parse_expression()?)
+        } else {
+            None
+        };
         let end_span = self.previous_span();
 
         Ok(Parameter {
             name,
             type_annotation,
+            default,
             span: Span::new(
                 start_span.start,
                 end_span.end,
@@ -376,6 +448,23 @@ impl<'input> Parser<'input> {
         })
     }
 
+    /// Default values are only allowed on trailing parameters
+    fn check_trailing_defaults(params: &[Parameter]) -> ParseResult<()> {
+        if let Some(first_default) = params.iter().position(|p| p.default.is_some()) {
+            if let Some(param) = params[first_default..].iter().find(|p| p.default.is_none()) {
+                return Err(ParseError::InvalidSyntax {
+                    message: format!(
+                        "parameter '{}' without a default follows a parameter with a default",
+                        param.name
+                    ),
+                    line: param.span.line,
+                    column: param.span.column,
+                });
+            }
+        }
+        Ok(())
+    }
+
     fn parse_attributes(&mut self) -> ParseResult<Vec<Attribute>> {
         let mut attributes = Vec::new();
 
@@ -466,6 +555,70 @@ impl<'input> Parser<'input> {
         })
     }
 
+    // Unit expressions inside `Float<...>`: `kg*m/s^2`, `1/s`
+    fn parse_unit_expression(&mut self) -> ParseResult<Dimension> {
+        let mut dimension = self.parse_unit_term()?;
+
+        loop {
+            let span = self.current_token.span.clone();
+            let combined = if self.check(&Token::Star) {
+                self.advance();
+                dimension.mul(&self.parse_unit_term()?)
+            } else if self.check(&Token::Slash) {
+                self.advance();
+                dimension.div(&self.parse_unit_term()?)
+            } else {
+                return Ok(dimension);
+            };
+            dimension = combined
+                .ok_or_else(|| ParseError::invalid_syntax("Unit exponent out of range", &span))?;
+        }
+    }
+
+    fn parse_unit_term(&mut self) -> ParseResult<Dimension> {
+        let span = self.current_token.span.clone();
+        let base = match &self.current_token.token {
+            Token::IntLiteral(1) => Dimension::dimensionless(),
+            Token::Identifier(symbol) => Dimension::from_unit(symbol).ok_or_else(|| {
+                ParseError::invalid_syntax(&format!("Unknown unit '{}'", symbol), &span)
+            })?,
+            other => {
+                return Err(ParseError::unexpected_token(
+                    "unit",
+                    &other.to_string(),
+                    &span,
+                ))
+            }
+        };
+        self.advance();
+
+        if !self.check(&Token::Caret) {
+            return Ok(base);
+        }
+        self.advance();
+
+        let negative = self.check(&Token::Minus);
+        if negative {
+            self.advance();
+        }
+        match self.current_token.token {
+            Token::IntLiteral(power) => {
+                let span = self.current_token.span.clone();
+                self.advance();
+                let power = if negative { -power } else { power };
+                i8::try_from(power)
+                    .ok()
+                    .and_then(|power| base.powi(power))
+                    .ok_or_else(|| ParseError::invalid_syntax("Unit exponent out of range", &span))
+            }
+            _ => Err(ParseError::unexpected_token(
+                "integer exponent",
+                &self.current_token.token.to_string(),
+                &self.current_token.span,
+            )),
+        }
+    }
+
     // Type parsing
     fn parse_type(&mut self) -> ParseResult<Type> {
         match &self.current_token.token {
@@ -475,7 +628,20 @@ impl<'input> Parser<'input> {
             }
             Token::FloatType => {
                 self.advance();
-                Ok(Type::Float)
+
+                // Optional unit annotation: Float<m/s^2>
+                if self.check(&Token::Less) {
+                    self.advance();
+                    let dimension = self.parse_unit_expression()?;
+                    self.expect(Token::Greater)?;
+                    if dimension.is_dimensionless() {
+                        Ok(Type::Float)
+                    } else {
+                        Ok(Type::Quantity(dimension))
+                    }
+                } else {
+                    Ok(Type::Float)
+                }
             }
             Token::BoolType => {
                 self.advance();
@@ -562,7 +728,34 @@ impl<'input> Parser<'input> {
     }
 
     fn parse_assignment(&mut self) -> ParseResult<Expression> {
-        self.parse_logical_or()
+        self.parse_ternary()
+    }
+
+    /// `cond ? a : b`, lowered to an if-expression; right-associative
+    fn parse_ternary(&mut self) -> ParseResult<Expression> {
+        let condition = self.parse_logical_or()?;
+        if !self.check(&Token::Question) {
+            return Ok(condition);
+        }
+        self.advance();
+
+        let then_branch = self.parse_expression()?;
+        self.expect(Token::Colon)?;
+        let else_branch = self.parse_expression()?;
+
+        let start_span = condition.span().clone();
+        let end_span = else_branch.span().clone();
+        Ok(Expression::IfExpression {
+            condition: Box::new(condition),
+            then_branch: Box::new(then_branch),
+            else_branch: Some(Box::new(else_branch)),
+            span: Span::new(
+                start_span.start,
+                end_span.end,
+                start_span.line,
+                end_span.column,
+            ),
+        })
     }
     fn parse_logical_or(&mut self) -> ParseResult<Expression> {
         let mut expr = self.parse_logical_and()?;
@@ -761,17 +954,27 @@ impl<'input> Parser<'input> {
                 self.advance();
                 let operand = self.parse_unary()?;
                 let end_span = operand.span().clone();
+                let span = Span::new(
+                    start_span.start,
+                    end_span.end,
+                    start_span.line,
+                    end_span.column,
+                );
 
-                Ok(Expression::UnaryOp {
-                    operator,
-                    operand: Box::new(operand),
-                    span: Span::new(
-                        start_span.start,
-                        end_span.end,
-                        start_span.line,
-                        end_span.column,
-                    ),
-                })
+                // Fold `-<number>` into a negative literal; `-x` stays a negation
+                match (&operator, operand) {
+                    (UnaryOperator::Neg, Expression::IntLiteral(value, _)) => {
+                        Ok(Expression::IntLiteral(-value, span))
+                    }
+                    (UnaryOperator::Neg, Expression::FloatLiteral(value, _)) => {
+                        Ok(Expression::FloatLiteral(-value, span))
+                    }
+                    (_, operand) => Ok(Expression::UnaryOp {
+                        operator,
+                        operand: Box::new(operand),
+                        span,
+                    }),
+                }
             }
             _ => self.parse_postfix(),
         }
@@ -890,18 +1093,38 @@ impl<'input> Parser<'input> {
                     }
                 }
                 Token::LeftBracket => {
-                    // Array/matrix indexing                    self.advance();
+                    // Array/matrix indexing and dictionary lookup, or slicing with
+                    // `a[start..end]` / `a[start..=end]`
+                    self.advance();
                     let index = self.parse_expression()?;
+                    let (function, args) =
+                        if self.check(&Token::DotDot) || self.check(&Token::DotDotEqual) {
+                            let inclusive = self.check(&Token::DotDotEqual);
+                            let inclusive_span = self.current_token.span.clone();
+                            self.advance();
+                            let end = self.parse_expression()?;
+                            (
+                                "slice",
+                                vec![
+                                    expr.clone(),
+                                    index,
+                                    end,
+                                    Expression::BoolLiteral(inclusive, inclusive_span),
+                                ],
+                            )
+                        } else {
+                            ("index", vec![expr.clone(), index])
+                        };
                     let end_span = self.current_token.span.clone();
                     self.expect(Token::RightBracket)?;
                     let start_span = expr.span().clone();
 
                     expr = Expression::FunctionCall {
                         function: Box::new(Expression::Identifier(
-                            "index".to_string(),
+                            function.to_string(),
                             self.current_token.span.clone(),
                         )),
-                        args: vec![expr.clone(), index],
+                        args,
                         span: Span::new(
                             start_span.start,
                             end_span.end,
@@ -918,6 +1141,14 @@ impl<'input> Parser<'input> {
                         break;
                     }
                     // Struct creation with brace syntax: Vector2 { x: 1, y: 2 }
+                    // Anything else, e.g. the arms in `match x { -1 => ... }`, ends the expression
+                    let at_fields = match &expr {
+                        Expression::Identifier(name, _) => self.at_struct_fields(name),
+                        _ => false,
+                    };
+                    if !at_fields {
+                        break;
+                    }
                     if let Expression::Identifier(name, start_span) = expr {
                         expr = self.parse_struct_creation(name, start_span)?;
                     } else {
@@ -970,6 +1201,14 @@ impl<'input> Parser<'input> {
                 self.advance();
                 Ok(Expression::Identifier(name, span))
             }
+            // The Option constructors are keywords, but evaluate like the builtins `Ok`
+            // and `Err`: `Some` is a function and `None` a value
+            Token::Some | Token::None => {
+                let name = self.current_token.token.to_string();
+                let span = self.current_token.span.clone();
+                self.advance();
+                Ok(Expression::Identifier(name, span))
+            }
             Token::LeftParen => {
                 self.advance();
 
@@ -984,8 +1223,11 @@ impl<'input> Parser<'input> {
                 }
             }
             Token::LeftBracket => self.parse_array_or_matrix(),
+            Token::LeftBrace if self.at_dict_entries() => self.parse_dict_literal(),
             Token::LeftBrace => self.parse_block(),
             Token::If => self.parse_if_expression(),
+            Token::While => self.parse_while_expression(),
+            Token::For => self.parse_for_expression(),
             Token::Match => self.parse_match_expression(),
             Token::Let => self.parse_let_expression(),
             Token::Parallel => self.parse_parallel(),
@@ -1016,6 +1258,74 @@ impl<'input> Parser<'input> {
         }
     }
 
+    /// Whether the `{` after `name` opens struct fields (`{ name: ...`, or `{}` after a
+    /// capitalised type name so that `while done {}` keeps its empty block)
+    fn at_struct_fields(&self, name: &str) -> bool {
+        match self.peek_token.token {
+            Token::RightBrace => name.starts_with(|c: char| c.is_ascii_uppercase()),
+            Token::Identifier(_) => self.lexer.peek_token() == Token::Colon,
+            _ => false,
+        }
+    }
+
+    /// At `{` followed by `key:`, starting a dictionary literal rather than a block
+    fn at_dict_entries(&self) -> bool {
+        matches!(
+            self.peek_token.token,
+            Token::Identifier(_) | Token::StringLiteral(_)
+        ) && self.lexer.peek_token() == Token::Colon
+    }
+
+    /// `{ key: value, "other key": value }`, with an optional trailing comma
+    fn parse_dict_literal(&mut self) -> ParseResult<Expression> {
+        let start_span = self.current_token.span.clone();
+        self.advance(); // consume {
+
+        let mut entries: Vec<(String, Expression)> = Vec::new();
+        while !self.check(&Token::RightBrace) && !self.is_at_end() {
+            let key = match &self.current_token.token {
+                Token::Identifier(name) | Token::StringLiteral(name) => name.clone(),
+                _ => {
+                    return Err(ParseError::unexpected_token(
+                        "dictionary key",
+                        &self.current_token.token.to_string(),
+                        &self.current_token.span,
+                    ))
+                }
+            };
+            self.advance();
+            self.expect(Token::Colon)?;
+            let value = self.parse_expression()?;
+
+            // A repeated key keeps its last value
+            entries.retain(|(existing, _)| existing != &key);
+            entries.push((key, value));
+
+            if self.check(&Token::Comma) {
+                self.advance();
+            } else if !self.check(&Token::RightBrace) {
+                return Err(ParseError::unexpected_token(
+                    "comma or }",
+                    &self.current_token.token.to_string(),
+                    &self.current_token.span,
+                ));
+            }
+        }
+
+        let end_span = self.current_token.span.clone();
+        self.expect(Token::RightBrace)?;
+
+        Ok(Expression::DictLiteral(
+            entries,
+            Span::new(
+                start_span.start,
+                end_span.end,
+                start_span.line,
+                end_span.column,
+            ),
+        ))
+    }
+
     fn parse_struct_creation(&mut self, name: String, start_span: Span) -> ParseResult<Expression> {
         let mut fields = HashMap::new();
 
@@ -1099,10 +1409,15 @@ impl<'input> Parser<'input> {
             }
         }
 
-        // Look for parameter pattern: identifier : type
+        // Look for parameter pattern: `identifier : type`, `identifier ,` (there are no
+        // tuples) or a lone `identifier ) =>`
         if let Token::Identifier(_) = self.current_token.token {
-            if let Token::Colon = self.peek_token.token {
-                return true;
+            match self.peek_token.token {
+                Token::Colon | Token::Comma => return true,
+                Token::RightParen => {
+                    return matches!(self.lexer.peek_token(), Token::Arrow | Token::ThinArrow)
+                }
+                _ => {}
             }
         }
         false
@@ -1127,6 +1442,7 @@ impl<'input> Parser<'input> {
         }
 
         self.expect(Token::RightParen)?;
+        Self::check_trailing_defaults(&params)?;
 
         // Accept both => and -> for lambda body
         if self.check(&Token::Arrow) {
@@ -1341,6 +1657,86 @@ impl<'input> Parser<'input> {
             ),
         })
     }
+    fn parse_while_expression(&mut self) -> ParseResult<Expression> {
+        let start_span = self.current_token.span.clone();
+        self.advance(); // consume while
+
+        let condition = self.parse_expression()?;
+
+        if !self.check(&Token::LeftBrace) {
+            return Err(ParseError::unexpected_token(
+                "{",
+                &self.current_token.token.to_string(),
+                &self.current_token.span,
+            ));
+        }
+        let body = self.parse_block()?;
+        let end_span = body.span().clone();
+
+        Ok(Expression::While {
+            condition: Box::new(condition),
+            body: Box::new(body),
+            span: Span::new(
+                start_span.start,
+                end_span.end,
+                start_span.line,
+                end_span.column,
+            ),
+        })
+    }
+
+    fn parse_for_expression(&mut self) -> ParseResult<Expression> {
+        let start_span = self.current_token.span.clone();
+        self.advance(); // consume for
+
+        let variable = self.expect_identifier()?;
+        self.expect(Token::In)?;
+
+        // Either an expression giving an array, or a `start..end` / `start..=end` range
+        let iterable = self.parse_expression()?;
+        let iterable = if self.check(&Token::DotDot) || self.check(&Token::DotDotEqual) {
+            let inclusive = self.check(&Token::DotDotEqual);
+            self.advance();
+            let end = self.parse_expression()?;
+            let span = Span::new(
+                iterable.span().start,
+                end.span().end,
+                iterable.span().line,
+                end.span().column,
+            );
+            Expression::Range {
+                start: Box::new(iterable),
+                end: Box::new(end),
+                inclusive,
+                span,
+            }
+        } else {
+            iterable
+        };
+
+        if !self.check(&Token::LeftBrace) {
+            return Err(ParseError::unexpected_token(
+                "{",
+                &self.current_token.token.to_string(),
+                &self.current_token.span,
+            ));
+        }
+        let body = self.parse_block()?;
+        let end_span = body.span().clone();
+
+        Ok(Expression::For {
+            variable,
+            iterable: Box::new(iterable),
+            body: Box::new(body),
+            span: Span::new(
+                start_span.start,
+                end_span.end,
+                start_span.line,
+                end_span.column,
+            ),
+        })
+    }
+
     fn parse_if_expression(&mut self) -> ParseResult<Expression> {
         let start_span = self.current_token.span.clone();
         self.advance(); // consume if
@@ -1462,64 +1858,101 @@ impl<'input> Parser<'input> {
                 self.advance();
                 Ok(Pattern::Wildcard(span))
             }
+            Token::Some => {
+                let span = self.current_token.span.clone();
+                self.advance();
+                self.expect(Token::LeftParen)?;
+                let inner_pattern = self.parse_pattern()?;
+                let end_span = self.current_token.span.clone();
+                self.expect(Token::RightParen)?;
+
+                Ok(Pattern::Some(
+                    Box::new(inner_pattern),
+                    Span::new(span.start, end_span.end, span.line, end_span.column),
+                ))
+            }
+            Token::None => {
+                let span = self.current_token.span.clone();
+                self.advance();
+                Ok(Pattern::None(span))
+            }
             Token::Identifier(name) => {
                 let name = name.clone();
                 let span = self.current_token.span.clone();
                 self.advance();
 
-                match name.as_str() {
-                    "Some" => {
-                        self.expect(Token::LeftParen)?;
-                        let inner_pattern = self.parse_pattern()?;
-                        let end_span = self.current_token.span.clone();
-                        self.expect(Token::RightParen)?;
-
-                        Ok(Pattern::Some(
-                            Box::new(inner_pattern),
-                            Span::new(span.start, end_span.end, span.line, end_span.column),
-                        ))
-                    }
-                    "None" => Ok(Pattern::None(span)),
-                    _ => {
-                        // Check for struct pattern
-                        if self.check(&Token::LeftBrace) {
+                if matches!(name.as_str(), "Ok" | "Err") && self.check(&Token::LeftParen) {
+                    self.advance();
+                    let inner_pattern = Box::new(self.parse_pattern()?);
+                    let end_span = self.current_token.span.clone();
+                    self.expect(Token::RightParen)?;
+                    let span = Span::new(span.start, end_span.end, span.line, end_span.column);
+
+                    return Ok(if name == "Ok" {
+                        Pattern::Ok(inner_pattern, span)
+                    } else {
+                        Pattern::Err(inner_pattern, span)
+                    });
+                }
+
+                // Check for struct pattern
+                if self.check(&Token::LeftBrace) {
+                    self.advance();
+                    let mut fields = HashMap::new();
+
+                    while !self.check(&Token::RightBrace) && !self.is_at_end() {
+                        let field_name = self.expect_identifier()?;
+                        self.expect(Token::Colon)?;
+                        let field_pattern = self.parse_pattern()?;
+                        fields.insert(field_name, field_pattern);
+
+                        if self.check(&Token::Comma) {
                             self.advance();
-                            let mut fields = HashMap::new();
-
-                            while !self.check(&Token::RightBrace) && !self.is_at_end() {
-                                let field_name = self.expect_identifier()?;
-                                self.expect(Token::Colon)?;
-                                let field_pattern = self.parse_pattern()?;
-                                fields.insert(field_name, field_pattern);
-
-                                if self.check(&Token::Comma) {
-                                    self.advance();
-                                } else if !self.check(&Token::RightBrace) {
-                                    return Err(ParseError::unexpected_token(
-                                        "comma or }",
-                                        &self.current_token.token.to_string(),
-                                        &self.current_token.span,
-                                    ));
-                                }
-                            }
-
-                            let end_span = self.current_token.span.clone();
-                            self.expect(Token::RightBrace)?;
-
-                            Ok(Pattern::Struct {
-                                name,
-                                fields,
-                                span: Span::new(
-                                    span.start,
-                                    end_span.end,
-                                    span.line,
-                                    end_span.column,
-                                ),
-                            })
-                        } else {
-                            Ok(Pattern::Identifier(name, span))
+                        } else if !self.check(&Token::RightBrace) {
+                            return Err(ParseError::unexpected_token(
+                                "comma or }",
+                                &self.current_token.token.to_string(),
+                                &self.current_token.span,
+                            ));
                         }
                     }
+
+                    let end_span = self.current_token.span.clone();
+                    self.expect(Token::RightBrace)?;
+
+                    Ok(Pattern::Struct {
+                        name,
+                        fields,
+                        span: Span::new(span.start, end_span.end, span.line, end_span.column),
+                    })
+                } else {
+                    Ok(Pattern::Identifier(name, span))
+                }
+            }
+            Token::Minus => {
+                let start_span = self.current_token.span.clone();
+                self.advance();
+                let end_span = self.current_token.span.clone();
+                let span = Span::new(
+                    start_span.start,
+                    end_span.end,
+                    start_span.line,
+                    end_span.column,
+                );
+                match self.current_token.token {
+                    Token::IntLiteral(value) => {
+                        self.advance();
+                        Ok(Pattern::IntLiteral(-value, span))
+                    }
+                    Token::FloatLiteral(value) => {
+                        self.advance();
+                        Ok(Pattern::FloatLiteral(-value, span))
+                    }
+                    _ => Err(ParseError::unexpected_token(
+                        "numeric literal",
+                        &self.current_token.token.to_string(),
+                        &self.current_token.span,
+                    )),
                 }
             }
             Token::IntLiteral(value) => {
@@ -1759,7 +2192,7 @@ impl<'input> Parser<'input> {
     }
 
     // Helper methods for parsing
-    fn is_at_end(&self) -> bool {
+    pub fn is_at_end(&self) -> bool {
         matches!(self.current_token.token, Token::Eof)
     }
 
@@ -1863,4 +2296,157 @@ mod tests {
             _ => panic!("Expected struct definition"),
         }
     }
+
+    #[test]
+    fn test_parse_negative_literals() {
+        let lexer = Lexer::new("[-1, -2.5, 1e3, -x]");
+        let mut parser = Parser::new(lexer).unwrap();
+        let expr = parser.parse_expression().unwrap();
+
+        match expr {
+            Expression::ArrayLiteral(elements, _) => {
+                assert!(matches!(elements[0], Expression::IntLiteral(-1, _)));
+                assert!(matches!(elements[1], Expression::FloatLiteral(v, _) if v == -2.5));
+                assert!(matches!(elements[2], Expression::FloatLiteral(v, _) if v == 1000.0));
+                assert!(matches!(
+                    elements[3],
+                    Expression::UnaryOp {
+                        operator: UnaryOperator::Neg,
+                        ..
+                    }
+                ));
+            }
+            other => panic!("Expected array literal, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_parse_negative_literal_pattern() {
+        let lexer = Lexer::new("match x { -1 => 0, -0.5 => 1, _ => 2 }");
+        let mut parser = Parser::new(lexer).unwrap();
+        let expr = parser.parse_expression().unwrap();
+
+        match expr {
+            Expression::Match { arms, .. } => {
+                assert!(matches!(arms[0].pattern, Pattern::IntLiteral(-1, _)));
+                assert!(matches!(arms[1].pattern, Pattern::FloatLiteral(v, _) if v == -0.5));
+            }
+            other => panic!("Expected match expression, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_parse_ternary_lowers_to_if() {
+        let lexer = Lexer::new("a > b ? a : b ? 1 : 2");
+        let mut parser = Parser::new(lexer).unwrap();
+        let expr = parser.parse_expression().unwrap();
+
+        match expr {
+            Expression::IfExpression {
+                condition,
+                else_branch: Some(else_branch),
+                ..
+            } => {
+                assert!(matches!(
+                    *condition,
+                    Expression::BinaryOp {
+                        operator: BinaryOperator::Gt,
+                        ..
+                    }
+                ));
+                assert!(matches!(*else_branch, Expression::IfExpression { .. }));
+            }
+            other => panic!("Expected if expression, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_parse_while_loop() {
+        let lexer = Lexer::new("while n < 10 { step(n) }");
+        let mut parser = Parser::new(lexer).unwrap();
+        let expr = parser.parse_expression().unwrap();
+
+        match expr {
+            Expression::While {
+                condition, body, ..
+            } => {
+                assert!(matches!(
+                    *condition,
+                    Expression::BinaryOp {
+                        operator: BinaryOperator::Lt,
+                        ..
+                    }
+                ));
+                assert!(matches!(*body, Expression::Block { .. }));
+            }
+            other => panic!("Expected while loop, got {:?}", other),
+        }
+
+        // The body must be a block
+        let mut parser = Parser::new(Lexer::new("while n < 10 step(n)")).unwrap();
+        assert!(parser.parse_expression().is_err());
+    }
+
+    #[test]
+    fn test_parse_for_loop() {
+        let lexer = Lexer::new("for i in 1..=n { f(i) }");
+        let mut parser = Parser::new(lexer).unwrap();
+        let expr = parser.parse_expression().unwrap();
+
+        match expr {
+            Expression::For {
+                variable,
+                iterable,
+                body,
+                ..
+            } => {
+                assert_eq!(variable, "i");
+                assert!(matches!(
+                    *iterable,
+                    Expression::Range {
+                        inclusive: true,
+                        ..
+                    }
+                ));
+                assert!(matches!(*body, Expression::Block { .. }));
+            }
+            other => panic!("Expected for loop, got {:?}", other),
+        }
+
+        let mut parser = Parser::new(Lexer::new("for x in xs { g(x) }")).unwrap();
+        match parser.parse_expression().unwrap() {
+            Expression::For { iterable, .. } => {
+                assert!(matches!(*iterable, Expression::Identifier(ref name, _) if name == "xs"));
+            }
+            other => panic!("Expected for loop, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_empty_loop_bodies_are_blocks() {
+        let mut parser = Parser::new(Lexer::new("while done {}")).unwrap();
+        match parser.parse_expression().unwrap() {
+            Expression::While { condition, .. } => {
+                assert!(
+                    matches!(*condition, Expression::Identifier(ref name, _) if name == "done")
+                );
+            }
+            other => panic!("Expected while loop, got {:?}", other),
+        }
+
+        let mut parser = Parser::new(Lexer::new("for x in xs {}")).unwrap();
+        match parser.parse_expression().unwrap() {
+            Expression::For { iterable, .. } => {
+                assert!(matches!(*iterable, Expression::Identifier(ref name, _) if name == "xs"));
+            }
+            other => panic!("Expected for loop, got {:?}", other),
+        }
+
+        // A type name before `{}` is still an empty struct
+        let mut parser = Parser::new(Lexer::new("Empty {}")).unwrap();
+        assert!(matches!(
+            parser.parse_expression().unwrap(),
+            Expression::StructCreation { ref name, .. } if name == "Empty"
+        ));
+    }
 }
@@ -1,4 +1,10 @@
+// Runtime for Matrix Language
+// Frame-based execution of programs on the interpreter's values, sharing its operators
+// so both give the same results
+
 use crate::ast::nodes::*;
+pub use crate::eval::interpreter::Value;
+use crate::eval::interpreter::{self, Environment};
 use std::collections::HashMap;
 use std::sync::mpsc;
 use std::sync::{Arc, Mutex};
@@ -40,7 +46,8 @@ pub struct HeapObject {
 /// Thread pool for parallel execution
 pub struct ThreadPool {
     workers: Vec<Worker>,
-    sender: mpsc::Sender<Job>,
+    /// Dropped on shutdown, which tells the workers to exit once the queue is empty
+    sender: Option<mpsc::Sender<Job>>,
 }
 
 type Job = Box<dyn FnOnce() + Send + 'static>;
@@ -79,7 +86,7 @@ impl Runtime {
         // Initialize global scope
         self.push_frame("main".to_string());
 
-        let mut result = Value::Null;
+        let mut result = Value::Unit;
 
         for item in &program.items {
             result = self.execute_item(item)?;
@@ -100,16 +107,16 @@ impl Runtime {
             Item::FunctionDef(func_def) => {
                 // Store function definition for later calls
                 let function_value = Value::Function {
-                    name: func_def.name.clone(),
-                    params: func_def.params.iter().map(|p| p.name.clone()).collect(),
-                    body: func_def.body.clone(),
+                    params: func_def.params.clone(),
+                    body: Arc::new(func_def.body.clone()),
+                    closure: Environment::new(),
                 };
                 self.set_variable(func_def.name.clone(), function_value);
-                Ok(Value::Null)
+                Ok(Value::Unit)
             }
             _ => {
                 // For now, other items are not executable at runtime
-                Ok(Value::Null)
+                Ok(Value::Unit)
             }
         }
     }
@@ -129,9 +136,9 @@ impl Runtime {
     /// Evaluate an expression
     pub fn evaluate_expression(&mut self, expr: &Expression) -> Result<Value, RuntimeError> {
         match expr {
-            Expression::IntLiteral(n, _) => Ok(Value::Number(*n as f64)),
-            Expression::FloatLiteral(n, _) => Ok(Value::Number(*n)),
-            Expression::BoolLiteral(b, _) => Ok(Value::Boolean(*b)),
+            Expression::IntLiteral(n, _) => Ok(Value::Int(*n)),
+            Expression::FloatLiteral(n, _) => Ok(Value::Float(*n)),
+            Expression::BoolLiteral(b, _) => Ok(Value::Bool(*b)),
             Expression::StringLiteral(s, _) => Ok(Value::String(s.clone())),
             Expression::Identifier(name, _) => self
                 .get_variable(name)
@@ -144,7 +151,7 @@ impl Runtime {
             } => {
                 let left_val = self.evaluate_expression(left)?;
                 let right_val = self.evaluate_expression(right)?;
-                self.apply_binary_op(&left_val, operator, &right_val)
+                Ok(Value::apply_binary_op(left_val, operator, right_val)?)
             }
             Expression::UnaryOp {
                 operator, operand, ..
@@ -173,32 +180,18 @@ impl Runtime {
                 for row in rows {
                     let mut matrix_row = Vec::new();
                     for expr in row {
-                        let val = self.evaluate_expression(expr)?;
-                        if let Value::Number(n) = val {
-                            matrix_row.push(n);
-                        } else {
-                            return Err(RuntimeError::TypeError(
-                                "Matrix elements must be numbers".to_string(),
-                            ));
-                        }
+                        matrix_row.push(self.evaluate_expression(expr)?);
                     }
                     matrix_rows.push(matrix_row);
                 }
                 Ok(Value::Matrix(matrix_rows))
             }
             Expression::ArrayLiteral(exprs, _) => {
-                let mut vector = Vec::new();
+                let mut elements = Vec::new();
                 for expr in exprs {
-                    let val = self.evaluate_expression(expr)?;
-                    if let Value::Number(n) = val {
-                        vector.push(n);
-                    } else {
-                        return Err(RuntimeError::TypeError(
-                            "Array elements must be numbers".to_string(),
-                        ));
-                    }
+                    elements.push(self.evaluate_expression(expr)?);
                 }
-                Ok(Value::Vector(vector))
+                Ok(Value::Array(elements))
             }
             Expression::IfExpression {
                 condition,
@@ -207,18 +200,18 @@ impl Runtime {
                 ..
             } => {
                 let cond_value = self.evaluate_expression(condition)?;
-                if self.is_truthy(&cond_value) {
+                if cond_value.is_truthy() {
                     self.evaluate_expression(then_branch)
                 } else if let Some(else_expr) = else_branch {
                     self.evaluate_expression(else_expr)
                 } else {
-                    Ok(Value::Null)
+                    Ok(Value::Unit)
                 }
             }
             Expression::Block {
                 statements, result, ..
             } => {
-                let mut last_value = Value::Null;
+                let mut last_value = Value::Unit;
                 for stmt in statements {
                     last_value = self.execute_statement(stmt)?;
                 }
@@ -230,97 +223,17 @@ impl Runtime {
             }
             _ => {
                 // For other expression types, return null for now
-                Ok(Value::Null)
+                Ok(Value::Unit)
             }
         }
     }
 
-    /// Apply binary operations
-    fn apply_binary_op(
-        &self,
-        left: &Value,
-        op: &BinaryOperator,
-        right: &Value,
-    ) -> Result<Value, RuntimeError> {
-        match (left, right) {
-            (Value::Number(a), Value::Number(b)) => match op {
-                BinaryOperator::Add => Ok(Value::Number(a + b)),
-                BinaryOperator::Sub => Ok(Value::Number(a - b)),
-                BinaryOperator::Mul => Ok(Value::Number(a * b)),
-                BinaryOperator::Div => {
-                    if *b == 0.0 {
-                        Err(RuntimeError::DivisionByZero)
-                    } else {
-                        Ok(Value::Number(a / b))
-                    }
-                }
-                BinaryOperator::Pow => Ok(Value::Number(a.powf(*b))),
-                BinaryOperator::Mod => Ok(Value::Number(a % b)),
-                BinaryOperator::Eq => Ok(Value::Boolean((a - b).abs() < f64::EPSILON)),
-                BinaryOperator::Ne => Ok(Value::Boolean((a - b).abs() >= f64::EPSILON)),
-                BinaryOperator::Lt => Ok(Value::Boolean(a < b)),
-                BinaryOperator::Le => Ok(Value::Boolean(a <= b)),
-                BinaryOperator::Gt => Ok(Value::Boolean(a > b)),
-                BinaryOperator::Ge => Ok(Value::Boolean(a >= b)),
-                _ => Err(RuntimeError::TypeError(format!(
-                    "Invalid operator {:?} for numbers",
-                    op
-                ))),
-            },
-            (Value::String(a), Value::String(b)) => match op {
-                BinaryOperator::Add => Ok(Value::String(format!("{}{}", a, b))),
-                BinaryOperator::Eq => Ok(Value::Boolean(a == b)),
-                BinaryOperator::Ne => Ok(Value::Boolean(a != b)),
-                _ => Err(RuntimeError::TypeError(format!(
-                    "Invalid operator {:?} for strings",
-                    op
-                ))),
-            },
-            (Value::Boolean(a), Value::Boolean(b)) => match op {
-                BinaryOperator::And => Ok(Value::Boolean(*a && *b)),
-                BinaryOperator::Or => Ok(Value::Boolean(*a || *b)),
-                BinaryOperator::Eq => Ok(Value::Boolean(a == b)),
-                BinaryOperator::Ne => Ok(Value::Boolean(a != b)),
-                _ => Err(RuntimeError::TypeError(format!(
-                    "Invalid operator {:?} for booleans",
-                    op
-                ))),
-            },
-            (Value::Vector(a), Value::Vector(b)) => match op {
-                BinaryOperator::Add => {
-                    if a.len() != b.len() {
-                        return Err(RuntimeError::TypeError(
-                            "Vector addition requires same length".to_string(),
-                        ));
-                    }
-                    let result = a.iter().zip(b.iter()).map(|(x, y)| x + y).collect();
-                    Ok(Value::Vector(result))
-                }
-                BinaryOperator::Sub => {
-                    if a.len() != b.len() {
-                        return Err(RuntimeError::TypeError(
-                            "Vector subtraction requires same length".to_string(),
-                        ));
-                    }
-                    let result = a.iter().zip(b.iter()).map(|(x, y)| x - y).collect();
-                    Ok(Value::Vector(result))
-                }
-                _ => Err(RuntimeError::TypeError(format!(
-                    "Invalid operator {:?} for vectors",
-                    op
-                ))),
-            },
-            _ => Err(RuntimeError::TypeError(
-                "Type mismatch in binary operation".to_string(),
-            )),
-        }
-    }
-
     /// Apply unary operations
     fn apply_unary_op(&self, op: &UnaryOperator, operand: &Value) -> Result<Value, RuntimeError> {
         match (op, operand) {
-            (UnaryOperator::Neg, Value::Number(n)) => Ok(Value::Number(-n)),
-            (UnaryOperator::Not, Value::Boolean(b)) => Ok(Value::Boolean(!b)),
+            (UnaryOperator::Neg, Value::Int(n)) => Ok(Value::Int(-n)),
+            (UnaryOperator::Neg, Value::Float(n)) => Ok(Value::Float(-n)),
+            (UnaryOperator::Not, Value::Bool(b)) => Ok(Value::Bool(!b)),
             _ => Err(RuntimeError::TypeError(
                 "Invalid unary operation".to_string(),
             )),
@@ -343,14 +256,12 @@ impl Runtime {
                         print!(" ");
                     }
                     match arg {
-                        Value::Number(n) => print!("{}", n),
                         Value::String(s) => print!("{}", s),
-                        Value::Boolean(b) => print!("{}", b),
-                        _ => print!("{:?}", arg),
+                        _ => print!("{}", arg),
                     }
                 }
                 println!();
-                Ok(Value::Null)
+                Ok(Value::Unit)
             }
             "sin" => {
                 if arg_values.len() != 1 {
@@ -358,8 +269,8 @@ impl Runtime {
                         "sin expects 1 argument".to_string(),
                     ));
                 }
-                if let Value::Number(n) = &arg_values[0] {
-                    Ok(Value::Number(n.sin()))
+                if let Some(n) = as_number(&arg_values[0]) {
+                    Ok(Value::Float(n.sin()))
                 } else {
                     Err(RuntimeError::TypeError("sin expects a number".to_string()))
                 }
@@ -370,8 +281,8 @@ impl Runtime {
                         "cos expects 1 argument".to_string(),
                     ));
                 }
-                if let Value::Number(n) = &arg_values[0] {
-                    Ok(Value::Number(n.cos()))
+                if let Some(n) = as_number(&arg_values[0]) {
+                    Ok(Value::Float(n.cos()))
                 } else {
                     Err(RuntimeError::TypeError("cos expects a number".to_string()))
                 }
@@ -382,8 +293,8 @@ impl Runtime {
                         "sqrt expects 1 argument".to_string(),
                     ));
                 }
-                if let Value::Number(n) = &arg_values[0] {
-                    Ok(Value::Number(n.sqrt()))
+                if let Some(n) = as_number(&arg_values[0]) {
+                    Ok(Value::Float(n.sqrt()))
                 } else {
                     Err(RuntimeError::TypeError("sqrt expects a number".to_string()))
                 }
@@ -394,9 +305,10 @@ impl Runtime {
                         "pow expects 2 arguments".to_string(),
                     ));
                 }
-                if let (Value::Number(base), Value::Number(exp)) = (&arg_values[0], &arg_values[1])
+                if let (Some(base), Some(exp)) =
+                    (as_number(&arg_values[0]), as_number(&arg_values[1]))
                 {
-                    Ok(Value::Number(base.powf(*exp)))
+                    Ok(Value::Float(base.powf(exp)))
                 } else {
                     Err(RuntimeError::TypeError(
                         "pow expects two numbers".to_string(),
@@ -413,18 +325,18 @@ impl Runtime {
     /// Index access for arrays/vectors/matrices
     fn index_access(&self, object: &Value, index: &Value) -> Result<Value, RuntimeError> {
         match (object, index) {
-            (Value::Vector(vec), Value::Number(idx)) => {
+            (Value::Array(elements), Value::Int(idx)) => {
                 let i = *idx as usize;
-                if i < vec.len() {
-                    Ok(Value::Number(vec[i]))
+                if i < elements.len() {
+                    Ok(elements[i].clone())
                 } else {
                     Err(RuntimeError::IndexOutOfBounds)
                 }
             }
-            (Value::Matrix(matrix), Value::Number(row_idx)) => {
+            (Value::Matrix(matrix), Value::Int(row_idx)) => {
                 let row = *row_idx as usize;
                 if row < matrix.len() {
-                    Ok(Value::Vector(matrix[row].clone()))
+                    Ok(Value::Array(matrix[row].clone()))
                 } else {
                     Err(RuntimeError::IndexOutOfBounds)
                 }
@@ -435,17 +347,6 @@ impl Runtime {
         }
     }
 
-    /// Check if a value is truthy
-    fn is_truthy(&self, value: &Value) -> bool {
-        match value {
-            Value::Boolean(b) => *b,
-            Value::Number(n) => *n != 0.0,
-            Value::String(s) => !s.is_empty(),
-            Value::Null => false,
-            _ => true,
-        }
-    }
-
     /// Push a new stack frame
     fn push_frame(&mut self, function_name: String) {
         self.stack.push(StackFrame {
@@ -586,7 +487,10 @@ impl ThreadPool {
             workers.push(Worker::new(id, Arc::clone(&receiver)));
         }
 
-        ThreadPool { workers, sender }
+        ThreadPool {
+            workers,
+            sender: Some(sender),
+        }
     }
 
     /// Execute a closure on the thread pool
@@ -595,7 +499,7 @@ impl ThreadPool {
         F: FnOnce() + Send + 'static,
     {
         let job = Box::new(f);
-        self.sender.send(job).unwrap();
+        self.sender.as_ref().unwrap().send(job).unwrap();
     }
 
     /// Get information about all workers
@@ -610,8 +514,13 @@ impl Worker {
         let thread = thread::spawn(move || {
             println!("Worker {} started", worker_id);
             loop {
-                let job = receiver.lock().unwrap().recv().unwrap();
-                job();
+                // Release the lock before running the job so other workers can take one
+                let message = receiver.lock().unwrap().recv();
+                match message {
+                    Ok(job) => job(),
+                    // The pool dropped its sender and the queue is empty
+                    Err(_) => break,
+                }
             }
         });
 
@@ -629,6 +538,7 @@ impl Worker {
 
 impl Drop for ThreadPool {
     fn drop(&mut self) {
+        drop(self.sender.take());
         for worker in &mut self.workers {
             if let Some(thread) = worker.thread.take() {
                 thread.join().unwrap();
@@ -665,18 +575,82 @@ impl std::fmt::Display for RuntimeError {
 
 impl std::error::Error for RuntimeError {}
 
-/// Runtime value representation
-#[derive(Debug, Clone, PartialEq)]
-pub enum Value {
-    Null,
-    Boolean(bool),
-    Number(f64),
-    String(String),
-    Vector(Vec<f64>),
-    Matrix(Vec<Vec<f64>>),
-    Function {
-        name: String,
-        params: Vec<String>,
-        body: Expression,
-    },
+impl From<interpreter::RuntimeError> for RuntimeError {
+    fn from(error: interpreter::RuntimeError) -> Self {
+        match error.without_location().clone() {
+            interpreter::RuntimeError::UndefinedVariable { name } => {
+                RuntimeError::UndefinedVariable(name)
+            }
+            interpreter::RuntimeError::TypeError { message } => RuntimeError::TypeError(message),
+            interpreter::RuntimeError::DivisionByZero => RuntimeError::DivisionByZero,
+            interpreter::RuntimeError::IndexOutOfBounds { .. } => RuntimeError::IndexOutOfBounds,
+            other => RuntimeError::CustomError(other.to_string()),
+        }
+    }
+}
+
+/// Numeric value of an `Int` or `Float`
+fn as_number(value: &Value) -> Option<f64> {
+    match value {
+        Value::Int(n) => Some(*n as f64),
+        Value::Float(n) => Some(*n),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::eval::Interpreter;
+    use crate::{Lexer, Parser};
+
+    fn parse(source: &str) -> Program {
+        Parser::new(Lexer::new(source))
+            .and_then(|mut parser| parser.parse_program())
+            .unwrap()
+    }
+
+    #[test]
+    fn test_runtime_and_interpreter_agree() {
+        let programs = [
+            "let r = 7 / 2 + 1.5 * 2.0 - 10 % 4",
+            "let a = 7\nlet b = 2\nlet r = if a > b { [a * b, a - b, -a] } else { [] }",
+            "let m = [[1, 2], [3, 4.5]]\nlet r = { let s = \"sum\"; s + \"!\" }",
+            "let r = 2 ^ 10 == 1024 && !(3 <= 2.5)",
+        ];
+        for source in programs {
+            let program = parse(source);
+            let from_runtime = Runtime::new().execute(&program).unwrap();
+            let from_interpreter = Interpreter::new().eval_program(&program).unwrap();
+            assert_eq!(from_runtime, from_interpreter, "{}", source);
+        }
+
+        let program = parse("let r = [1, 2] + 1");
+        assert!(matches!(
+            Runtime::new().execute(&program),
+            Err(RuntimeError::TypeError(_))
+        ));
+        assert!(matches!(
+            Runtime::new().execute(&parse("let r = 1 % 0")),
+            Err(RuntimeError::DivisionByZero)
+        ));
+    }
+
+    #[test]
+    fn test_thread_pool_finishes_jobs_and_shuts_down_on_drop() {
+        let pool = ThreadPool::new(3);
+        let (done, finished) = mpsc::channel();
+        for job in 0..8 {
+            let done = done.clone();
+            pool.execute(move || done.send(job).unwrap());
+        }
+        drop(done);
+
+        // Joins every worker; before shutdown was signalled this never returned
+        drop(pool);
+
+        let mut jobs: Vec<i32> = finished.iter().collect();
+        jobs.sort();
+        assert_eq!(jobs, (0..8).collect::<Vec<_>>());
+    }
 }
@@ -0,0 +1,185 @@
+// Assertions for Matrix Language
+// Tolerant numeric comparison for script tests
+
+use crate::eval::interpreter::{RuntimeError, Value};
+use crate::stdlib::physics::expect_number;
+
+pub fn register_assertion_functions(interpreter: &mut crate::eval::Interpreter) {
+    // Fails unless |a - b| <= epsilon, element-wise for arrays and matrices of the
+    // same shape
+    interpreter.environment.define(
+        "assert_near".to_string(),
+        Value::BuiltinFunction {
+            name: "assert_near".to_string(),
+            arity: 3,
+            func: |args| {
+                let epsilon = expect_number(&args[2], "Epsilon")?;
+                if !(epsilon.is_finite() && epsilon >= 0.0) {
+                    return Err(RuntimeError::Generic {
+                        message: format!(
+                            "assert_near needs a finite non-negative epsilon, got {}",
+                            epsilon
+                        ),
+                    });
+                }
+                assert_near(&args[0], &args[1], epsilon, "")?;
+                Ok(Value::Unit)
+            },
+        },
+    );
+}
+
+/// Compare `a` and `b` within `epsilon`; `path` locates nested elements in messages,
+/// e.g. `[1][0]`
+pub fn assert_near(a: &Value, b: &Value, epsilon: f64, path: &str) -> Result<(), RuntimeError> {
+    match (a, b) {
+        (Value::Array(xs), Value::Array(ys)) => assert_all_near(xs, ys, epsilon, path),
+        (Value::Matrix(xs), Value::Matrix(ys)) => {
+            if xs.len() != ys.len() {
+                return Err(shape_mismatch(path, "rows", xs.len(), ys.len()));
+            }
+            for (i, (x, y)) in xs.iter().zip(ys).enumerate() {
+                assert_all_near(x, y, epsilon, &format!("{}[{}]", path, i))?;
+            }
+            Ok(())
+        }
+        (Value::Int(_) | Value::Float(_), Value::Int(_) | Value::Float(_)) => {
+            let x = expect_number(a, "Value")?;
+            let y = expect_number(b, "Value")?;
+            let difference = (x - y).abs();
+            // NaN differences fail as well
+            if difference <= epsilon {
+                Ok(())
+            } else {
+                Err(RuntimeError::Generic {
+                    message: format!(
+                        "assert_near failed{}: {} and {} differ by {}, more than {}",
+                        at(path),
+                        x,
+                        y,
+                        difference,
+                        epsilon
+                    ),
+                })
+            }
+        }
+        _ => Err(RuntimeError::TypeError {
+            message: format!(
+                "assert_near cannot compare {} with {}{}",
+                a.type_name(),
+                b.type_name(),
+                at(path)
+            ),
+        }),
+    }
+}
+
+fn assert_all_near(
+    xs: &[Value],
+    ys: &[Value],
+    epsilon: f64,
+    path: &str,
+) -> Result<(), RuntimeError> {
+    if xs.len() != ys.len() {
+        return Err(shape_mismatch(path, "elements", xs.len(), ys.len()));
+    }
+    for (i, (x, y)) in xs.iter().zip(ys).enumerate() {
+        assert_near(x, y, epsilon, &format!("{}[{}]", path, i))?;
+    }
+    Ok(())
+}
+
+fn shape_mismatch(path: &str, what: &str, left: usize, right: usize) -> RuntimeError {
+    RuntimeError::Generic {
+        message: format!(
+            "assert_near shape mismatch{}: {} {} against {}",
+            at(path),
+            left,
+            what,
+            right
+        ),
+    }
+}
+
+fn at(path: &str) -> String {
+    if path.is_empty() {
+        String::new()
+    } else {
+        format!(" at {}", path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::eval::Interpreter;
+
+    fn check(a: Value, b: Value, epsilon: f64) -> Result<Value, RuntimeError> {
+        let mut interpreter = Interpreter::new();
+        register_assertion_functions(&mut interpreter);
+        let assert_near = interpreter.environment.get("assert_near").cloned().unwrap();
+        interpreter.call_value(assert_near, &[a, b, Value::Float(epsilon)])
+    }
+
+    fn floats(values: &[f64]) -> Value {
+        Value::Array(values.iter().map(|&x| Value::Float(x)).collect())
+    }
+
+    #[test]
+    fn test_scalars_within_epsilon_pass() {
+        assert_eq!(
+            check(Value::Float(1.0), Value::Float(1.0001), 0.001).unwrap(),
+            Value::Unit
+        );
+        assert!(check(Value::Int(3), Value::Float(3.0), 0.0).is_ok());
+
+        let err = check(Value::Float(1.0), Value::Float(1.5), 0.001).unwrap_err();
+        let message = err.to_string();
+        assert!(message.contains("differ by 0.5"), "{}", message);
+        assert!(check(Value::Float(f64::NAN), Value::Float(1.0), 1.0).is_err());
+        assert!(check(Value::Float(1.0), Value::Float(1.0), -1.0).is_err());
+    }
+
+    #[test]
+    fn test_arrays_and_matrices_compare_element_wise() {
+        assert!(check(
+            floats(&[1.0, 2.0, 3.0]),
+            floats(&[1.0005, 2.0, 2.9995]),
+            0.001
+        )
+        .is_ok());
+
+        let err = check(floats(&[1.0, 2.0, 3.0]), floats(&[1.0, 2.25, 3.0]), 0.001).unwrap_err();
+        let message = err.to_string();
+        assert!(message.contains("at [1]"), "{}", message);
+        assert!(message.contains("differ by 0.25"), "{}", message);
+
+        let matrix = |rows: &[[f64; 2]]| {
+            Value::Matrix(
+                rows.iter()
+                    .map(|row| row.iter().map(|&x| Value::Float(x)).collect())
+                    .collect(),
+            )
+        };
+        assert!(check(
+            matrix(&[[1.0, 0.0], [0.0, 1.0]]),
+            matrix(&[[1.0, 0.0], [0.0, 1.0]]),
+            1e-9
+        )
+        .is_ok());
+        let err = check(
+            matrix(&[[1.0, 0.0], [0.0, 1.0]]),
+            matrix(&[[1.0, 0.0], [0.1, 1.0]]),
+            1e-9,
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("at [1][0]"), "{}", err);
+    }
+
+    #[test]
+    fn test_shape_mismatch_is_an_error() {
+        let err = check(floats(&[1.0, 2.0]), floats(&[1.0, 2.0, 3.0]), 1.0).unwrap_err();
+        assert!(err.to_string().contains("shape mismatch"), "{}", err);
+        assert!(check(floats(&[1.0]), Value::Float(1.0), 1.0).is_err());
+    }
+}
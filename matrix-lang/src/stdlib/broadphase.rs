@@ -0,0 +1,128 @@
+// Broad Phase for Matrix Language
+// Uniform spatial hash grid producing candidate body pairs for the narrow phase
+
+use crate::stdlib::Vec3;
+use std::collections::{HashMap, HashSet};
+
+/// Bodies spanning more cells than this (e.g. large floors) are kept out of the grid
+/// and paired with every other body instead
+const MAX_CELLS_PER_BODY: i64 = 1024;
+
+type Cell = (i64, i64, i64);
+
+/// Bodies bucketed by the grid cells their bounds overlap. Two bodies whose bounds
+/// overlap always share a cell, so pairs that share none can be skipped.
+#[derive(Debug, Clone)]
+pub struct SpatialHash {
+    cell_size: f64,
+    cells: HashMap<Cell, Vec<usize>>,
+    /// Bodies too large to bucket
+    oversized: Vec<usize>,
+    len: usize,
+}
+
+impl SpatialHash {
+    /// An empty grid; `cell_size` must be positive and finite
+    pub fn new(cell_size: f64) -> Self {
+        Self {
+            cell_size,
+            cells: HashMap::new(),
+            oversized: Vec::new(),
+            len: 0,
+        }
+    }
+
+    pub fn cell_size(&self) -> f64 {
+        self.cell_size
+    }
+
+    fn cell_of(&self, point: Vec3) -> Cell {
+        (
+            (point.x / self.cell_size).floor() as i64,
+            (point.y / self.cell_size).floor() as i64,
+            (point.z / self.cell_size).floor() as i64,
+        )
+    }
+
+    /// Add body `index` with bounds `min`..`max`
+    pub fn insert(&mut self, index: usize, min: Vec3, max: Vec3) {
+        self.len += 1;
+        let (lo, hi) = (self.cell_of(min), self.cell_of(max));
+        let span = |lo: i64, hi: i64| hi.saturating_sub(lo).saturating_add(1);
+        let count = span(lo.0, hi.0)
+            .saturating_mul(span(lo.1, hi.1))
+            .saturating_mul(span(lo.2, hi.2));
+        // Non-finite bounds land in no sensible cell either
+        let finite = |v: Vec3| v.x.is_finite() && v.y.is_finite() && v.z.is_finite();
+        if !finite(min) || !finite(max) || count > MAX_CELLS_PER_BODY {
+            self.oversized.push(index);
+            return;
+        }
+
+        for x in lo.0..=hi.0 {
+            for y in lo.1..=hi.1 {
+                for z in lo.2..=hi.2 {
+                    self.cells.entry((x, y, z)).or_default().push(index);
+                }
+            }
+        }
+    }
+
+    /// Index pairs `(i, j)` with `i < j` that share a cell or involve an oversized body,
+    /// each listed once and in ascending order so results do not depend on hashing
+    pub fn pairs(&self) -> Vec<(usize, usize)> {
+        let mut pairs = HashSet::new();
+        for bucket in self.cells.values() {
+            for (k, &a) in bucket.iter().enumerate() {
+                for &b in &bucket[k + 1..] {
+                    pairs.insert((a.min(b), a.max(b)));
+                }
+            }
+        }
+
+        let bucketed: HashSet<usize> = self.cells.values().flatten().copied().collect();
+        for (k, &big) in self.oversized.iter().enumerate() {
+            for &other in bucketed.iter().chain(&self.oversized[k + 1..]) {
+                pairs.insert((big.min(other), big.max(other)));
+            }
+        }
+
+        let mut pairs: Vec<(usize, usize)> = pairs.into_iter().collect();
+        pairs.sort_unstable();
+        pairs
+    }
+
+    /// Number of bodies inserted
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_only_bodies_sharing_a_cell_are_paired() {
+        let mut grid = SpatialHash::new(1.0);
+        let unit = Vec3::new(0.4, 0.4, 0.4);
+        for (index, x) in [0.0, 0.5, 5.0].into_iter().enumerate() {
+            let center = Vec3::new(x, 0.0, 0.0);
+            grid.insert(index, center - unit, center + unit);
+        }
+        assert_eq!(grid.len(), 3);
+        assert_eq!(grid.pairs(), vec![(0, 1)]);
+
+        // A floor spanning far more cells than allowed meets every body
+        grid.insert(
+            3,
+            Vec3::new(-100.0, -2.0, -100.0),
+            Vec3::new(100.0, -1.0, 100.0),
+        );
+        assert_eq!(grid.pairs(), vec![(0, 1), (0, 3), (1, 3), (2, 3)]);
+    }
+}
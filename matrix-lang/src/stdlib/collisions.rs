@@ -0,0 +1,702 @@
+// Physics Collisions for Matrix Language
+// Body-body contact detection (spheres and axis-aligned boxes), sequential-impulse response with
+// material restitution and friction, and per-step events
+
+use crate::stdlib::{Material, PhysicsObject, PhysicsWorld, SpatialHash, Vec3};
+use std::collections::{HashMap, HashSet};
+
+/// Extra distance (m) around each body when choosing pairs for the collision filter
+const FILTER_MARGIN: f64 = 0.05;
+/// Impacts slower than this (m/s) do not bounce, so resting contacts stay at rest
+const RESTING_SPEED: f64 = 1.0;
+
+/// A contact between two bodies found during a step
+#[derive(Debug, Clone, PartialEq)]
+pub struct CollisionEvent {
+    pub body_a: usize,
+    pub body_b: usize,
+    pub point: Vec3,
+    /// Contact normal pointing from `body_a` towards `body_b`
+    pub normal: Vec3,
+    pub depth: f64,
+}
+
+/// One point of a contact manifold
+#[derive(Debug, Clone, PartialEq)]
+pub struct ContactPoint {
+    pub position: Vec3,
+    pub depth: f64,
+}
+
+/// All contact points between two bodies, sharing one normal
+#[derive(Debug, Clone, PartialEq)]
+pub struct Manifold {
+    pub body_a: usize,
+    pub body_b: usize,
+    /// Contact normal pointing from `body_a` towards `body_b`
+    pub normal: Vec3,
+    pub points: Vec<ContactPoint>,
+}
+
+impl PhysicsWorld {
+    /// Contact manifold between two bodies that touched during the last step, or `None`
+    /// if they were not in contact. Box pairs report the corners of their overlapping
+    /// faces; contacts involving a sphere have a single point.
+    pub fn manifold(&self, body_a: usize, body_b: usize) -> Option<Manifold> {
+        let event = self.collision_events.iter().find(|e| {
+            (e.body_a, e.body_b) == (body_a, body_b) || (e.body_b, e.body_a) == (body_a, body_b)
+        })?;
+        let normal = if event.body_a == body_a {
+            event.normal
+        } else {
+            event.normal * -1.0
+        };
+
+        let (a, b) = (&self.objects[body_a], &self.objects[body_b]);
+        let points = if a.shape != "sphere" && b.shape != "sphere" {
+            box_face_points(a, b, normal, event.depth)
+        } else {
+            vec![ContactPoint {
+                position: event.point,
+                depth: event.depth,
+            }]
+        };
+
+        Some(Manifold {
+            body_a,
+            body_b,
+            normal,
+            points,
+        })
+    }
+
+    /// Find all overlapping body pairs. Only bodies sharing a broad-phase grid cell are
+    /// tested; pairs of static or frozen bodies, constrained pairs and
+    /// segments of the same rope are skipped.
+    pub fn detect_collisions(&self) -> Vec<CollisionEvent> {
+        self.find_contacts().0
+    }
+
+    /// Contacts and the number of pairs that reached the narrow phase
+    fn find_contacts(&self) -> (Vec<CollisionEvent>, usize) {
+        let mut events = Vec::new();
+        let mut pairs_tested = 0;
+        for (i, j) in self.collidable_pairs(PhysicsObject::aabb) {
+            let (a, b) = (&self.objects[i], &self.objects[j]);
+            if self.filtered_pairs.contains(&(a.id, b.id)) {
+                continue;
+            }
+
+            pairs_tested += 1;
+            if let Some((point, normal, depth)) = contact(a, b) {
+                if !one_way_allows(a, b, normal) || !one_way_allows(b, a, normal * -1.0) {
+                    continue;
+                }
+                events.push(CollisionEvent {
+                    body_a: a.id,
+                    body_b: b.id,
+                    point,
+                    normal,
+                    depth,
+                });
+            }
+        }
+        (events, pairs_tested)
+    }
+
+    /// Index pairs `(i, j)` with `i < j` whose `bounds` share a cell of the spatial hash
+    /// and that are not excluded by the built-in rules: pairs of immovable bodies,
+    /// constrained pairs and parts of the same rope or cloth. The grid is rebuilt on
+    /// every call, so it always matches the current positions.
+    fn collidable_pairs(
+        &self,
+        bounds: impl Fn(&PhysicsObject) -> (Vec3, Vec3),
+    ) -> Vec<(usize, usize)> {
+        let linked: HashSet<(usize, usize)> = self
+            .constraints
+            .iter()
+            .map(|c| (c.body_a, c.body_b))
+            .chain(self.hinges.iter().map(|h| (h.body_a, h.body_b)))
+            .map(|(a, b)| (a.min(b), a.max(b)))
+            .collect();
+        // Composite each body belongs to, keyed by (kind, id): 0 for ropes, 1 for cloths
+        let composite_of: HashMap<usize, (u8, usize)> = self
+            .ropes
+            .iter()
+            .flat_map(|rope| rope.segments.iter().map(move |&id| (id, (0, rope.id))))
+            .chain(
+                self.cloths
+                    .iter()
+                    .flat_map(|cloth| cloth.particles.iter().map(move |&id| (id, (1, cloth.id)))),
+            )
+            .collect();
+
+        let mut grid = SpatialHash::new(self.settings.spatial_hash_cell_size);
+        for (i, obj) in self.objects.iter().enumerate() {
+            let (min, max) = bounds(obj);
+            grid.insert(i, min, max);
+        }
+
+        grid.pairs()
+            .into_iter()
+            .filter(|&(i, j)| {
+                let (a, b) = (&self.objects[i], &self.objects[j]);
+                if a.is_immovable() && b.is_immovable() {
+                    return false;
+                }
+                if linked.contains(&(a.id.min(b.id), a.id.max(b.id))) {
+                    return false;
+                }
+                match (composite_of.get(&a.id), composite_of.get(&b.id)) {
+                    (Some(owner_a), Some(owner_b)) => owner_a != owner_b,
+                    _ => true,
+                }
+            })
+            .collect()
+    }
+
+    /// Body id pairs that may touch during the next step: collidable pairs whose bounds,
+    /// grown by how far each body can travel in one step, overlap. These are the pairs
+    /// a collision filter is asked about.
+    pub fn filter_candidates(&self) -> Vec<(usize, usize)> {
+        let swept = |obj: &PhysicsObject| {
+            let (min, max) = obj.aabb();
+            let reach = obj.velocity.length() * self.dt
+                + self.effective_gravity().length() * self.dt * self.dt
+                + FILTER_MARGIN;
+            let reach = Vec3::new(reach, reach, reach);
+            (min - reach, max + reach)
+        };
+
+        self.collidable_pairs(swept)
+            .into_iter()
+            .filter(|&(i, j)| {
+                let (min_a, max_a) = swept(&self.objects[i]);
+                let (min_b, max_b) = swept(&self.objects[j]);
+                min_a.x <= max_b.x
+                    && min_b.x <= max_a.x
+                    && min_a.y <= max_b.y
+                    && min_b.y <= max_a.y
+                    && min_a.z <= max_b.z
+                    && min_b.z <= max_a.z
+            })
+            .map(|(i, j)| (self.objects[i].id, self.objects[j].id))
+            .collect()
+    }
+
+    /// Detect contacts and solve them with sequential impulses, so resting stacks settle
+    /// instead of jittering. Returns the contacts that were solved.
+    pub(crate) fn solve_contacts(&mut self, dt: f64) -> Vec<CollisionEvent> {
+        let (events, pairs_tested) = self.find_contacts();
+        self.stats.pairs_tested += pairs_tested;
+
+        // A moving body striking a sleeping one wakes it
+        for event in &events {
+            let (a, b) = (event.body_a, event.body_b);
+            let approach_speed =
+                (self.objects[b].velocity - self.objects[a].velocity).dot(&event.normal);
+            if -approach_speed > self.settings.sleep_velocity_threshold {
+                for id in [a, b] {
+                    if !self.objects[id].is_immovable() {
+                        self.objects[id].wake();
+                    }
+                }
+            }
+        }
+
+        // Sleeping bodies act as static supports until woken
+        let inverse_mass = |obj: &PhysicsObject| {
+            if obj.is_sleeping {
+                0.0
+            } else {
+                obj.inverse_mass()
+            }
+        };
+
+        let materials: Vec<Material> = events
+            .iter()
+            .map(|event| {
+                let a = &self.objects[event.body_a].material;
+                a.combine(&self.objects[event.body_b].material)
+            })
+            .collect();
+
+        // Target separating speed: bounce for real impacts, otherwise push out of overlap
+        let (baumgarte, slop) = (self.settings.baumgarte, self.settings.penetration_slop);
+        let targets: Vec<f64> = events
+            .iter()
+            .zip(&materials)
+            .map(|(event, material)| {
+                let relative_velocity =
+                    self.objects[event.body_b].velocity - self.objects[event.body_a].velocity;
+                let approach_speed = relative_velocity.dot(&event.normal);
+                let bounce = if -approach_speed > RESTING_SPEED {
+                    -material.restitution * approach_speed
+                } else {
+                    0.0
+                };
+                let bias = baumgarte * (event.depth - slop).max(0.0) / dt;
+                bounce.max(bias)
+            })
+            .collect();
+
+        let mut impulses: Vec<f64> = events
+            .iter()
+            .map(|event| {
+                if self.settings.warm_starting {
+                    let key = (event.body_a, event.body_b);
+                    self.contact_impulses.get(&key).copied().unwrap_or(0.0)
+                } else {
+                    0.0
+                }
+            })
+            .collect();
+
+        for (event, &impulse) in events.iter().zip(&impulses) {
+            let (a, b) = (event.body_a, event.body_b);
+            let (inv_mass_a, inv_mass_b) = (
+                inverse_mass(&self.objects[a]),
+                inverse_mass(&self.objects[b]),
+            );
+            self.objects[a].velocity =
+                self.objects[a].velocity - event.normal * (impulse * inv_mass_a);
+            self.objects[b].velocity =
+                self.objects[b].velocity + event.normal * (impulse * inv_mass_b);
+        }
+
+        // Friction impulse per contact, accumulated along the sliding direction
+        let mut friction_impulses = vec![Vec3::zero(); events.len()];
+
+        for _ in 0..self.settings.velocity_iterations.max(1) {
+            for (k, event) in events.iter().enumerate() {
+                let (a, b) = (event.body_a, event.body_b);
+                let inv_mass_a = inverse_mass(&self.objects[a]);
+                let inv_mass_b = inverse_mass(&self.objects[b]);
+                let total_inv_mass = inv_mass_a + inv_mass_b;
+                if total_inv_mass == 0.0 {
+                    continue;
+                }
+
+                let relative_velocity = self.objects[b].velocity - self.objects[a].velocity;
+                let normal_speed = relative_velocity.dot(&event.normal);
+
+                // Contacts can only push, so the accumulated impulse stays non-negative
+                let accumulated =
+                    (impulses[k] + (targets[k] - normal_speed) / total_inv_mass).max(0.0);
+                let delta = event.normal * (accumulated - impulses[k]);
+                impulses[k] = accumulated;
+
+                self.objects[a].velocity = self.objects[a].velocity - delta * inv_mass_a;
+                self.objects[b].velocity = self.objects[b].velocity + delta * inv_mass_b;
+
+                // Oppose sliding, with total friction bounded by the normal impulse
+                let relative_velocity = self.objects[b].velocity - self.objects[a].velocity;
+                let sliding =
+                    relative_velocity - event.normal * relative_velocity.dot(&event.normal);
+                let mut friction = friction_impulses[k] - sliding * (1.0 / total_inv_mass);
+                let limit = materials[k].friction * impulses[k];
+                if friction.length() > limit {
+                    friction = friction * (limit / friction.length());
+                }
+                let delta = friction - friction_impulses[k];
+                friction_impulses[k] = friction;
+
+                self.objects[a].velocity = self.objects[a].velocity - delta * inv_mass_a;
+                self.objects[b].velocity = self.objects[b].velocity + delta * inv_mass_b;
+            }
+        }
+
+        self.contact_impulses = events
+            .iter()
+            .map(|event| (event.body_a, event.body_b))
+            .zip(impulses)
+            .collect();
+        events
+    }
+}
+
+/// Whether a one-way `platform` blocks `other`: only when `other` is on the solid side
+/// (the contact normal from the platform follows its direction) and is not moving away
+/// from the platform along that direction, so bodies rising through it pass
+fn one_way_allows(platform: &PhysicsObject, other: &PhysicsObject, normal: Vec3) -> bool {
+    match platform.one_way {
+        Some(direction) => {
+            normal.dot(&direction) > 0.0
+                && (other.velocity - platform.velocity).dot(&direction) <= 0.0
+        }
+        None => true,
+    }
+}
+
+/// Contact point, normal (a -> b) and penetration depth of two overlapping bodies
+fn contact(a: &PhysicsObject, b: &PhysicsObject) -> Option<(Vec3, Vec3, f64)> {
+    match (a.shape == "sphere", b.shape == "sphere") {
+        (true, true) => sphere_sphere(a.position, a.half_extents.x, b.position, b.half_extents.x),
+        (true, false) => sphere_box(a.position, a.half_extents.x, b.position, b.half_extents)
+            .map(|(point, normal, depth)| (point, normal * -1.0, depth)),
+        (false, true) => sphere_box(b.position, b.half_extents.x, a.position, a.half_extents),
+        (false, false) => box_box(a.position, a.half_extents, b.position, b.half_extents),
+    }
+}
+
+fn sphere_sphere(a: Vec3, radius_a: f64, b: Vec3, radius_b: f64) -> Option<(Vec3, Vec3, f64)> {
+    let delta = b - a;
+    let distance = delta.length();
+    let depth = radius_a + radius_b - distance;
+    if depth <= 0.0 {
+        return None;
+    }
+
+    let normal = if distance > 1e-9 {
+        delta * (1.0 / distance)
+    } else {
+        Vec3::new(0.0, 1.0, 0.0)
+    };
+    Some((a + normal * radius_a, normal, depth))
+}
+
+/// Sphere against box; the normal points from the box towards the sphere
+fn sphere_box(
+    center: Vec3,
+    radius: f64,
+    box_center: Vec3,
+    half_extents: Vec3,
+) -> Option<(Vec3, Vec3, f64)> {
+    let local = center - box_center;
+    let closest = Vec3::new(
+        local.x.clamp(-half_extents.x, half_extents.x),
+        local.y.clamp(-half_extents.y, half_extents.y),
+        local.z.clamp(-half_extents.z, half_extents.z),
+    );
+    let offset = local - closest;
+    let distance = offset.length();
+    if distance >= radius {
+        return None;
+    }
+
+    if distance > 1e-9 {
+        let normal = offset * (1.0 / distance);
+        return Some((box_center + closest, normal, radius - distance));
+    }
+
+    // Sphere centre inside the box: push out through the nearest face
+    let (_, normal, depth) = box_box(box_center, half_extents, center, Vec3::zero())?;
+    Some((center, normal, depth + radius))
+}
+
+fn box_box(a: Vec3, half_a: Vec3, b: Vec3, half_b: Vec3) -> Option<(Vec3, Vec3, f64)> {
+    let delta = b - a;
+    let overlap = Vec3::new(
+        half_a.x + half_b.x - delta.x.abs(),
+        half_a.y + half_b.y - delta.y.abs(),
+        half_a.z + half_b.z - delta.z.abs(),
+    );
+    if overlap.x < 0.0 || overlap.y < 0.0 || overlap.z < 0.0 {
+        return None;
+    }
+
+    let sign = |v: f64| if v < 0.0 { -1.0 } else { 1.0 };
+    let (normal, depth) = if overlap.x <= overlap.y && overlap.x <= overlap.z {
+        (Vec3::new(sign(delta.x), 0.0, 0.0), overlap.x)
+    } else if overlap.y <= overlap.z {
+        (Vec3::new(0.0, sign(delta.y), 0.0), overlap.y)
+    } else {
+        (Vec3::new(0.0, 0.0, sign(delta.z)), overlap.z)
+    };
+    if depth == 0.0 {
+        return None;
+    }
+    Some((a + delta * 0.5, normal, depth))
+}
+
+/// Corners of the region where two boxes touch along an axis-aligned normal,
+/// placed midway through the overlap
+fn box_face_points(
+    a: &PhysicsObject,
+    b: &PhysicsObject,
+    normal: Vec3,
+    depth: f64,
+) -> Vec<ContactPoint> {
+    let axes = |v: Vec3| [v.x, v.y, v.z];
+    let (pa, ha) = (axes(a.position), axes(a.half_extents));
+    let (pb, hb) = (axes(b.position), axes(b.half_extents));
+    let overlap: [(f64, f64); 3] = std::array::from_fn(|k| {
+        (
+            (pa[k] - ha[k]).max(pb[k] - hb[k]),
+            (pa[k] + ha[k]).min(pb[k] + hb[k]),
+        )
+    });
+
+    let axis = axes(normal).iter().position(|n| n.abs() > 0.5).unwrap_or(1);
+    let (u, v) = ((axis + 1) % 3, (axis + 2) % 3);
+    let middle = (overlap[axis].0 + overlap[axis].1) / 2.0;
+
+    let mut points: Vec<ContactPoint> = Vec::new();
+    for cu in [overlap[u].0, overlap[u].1] {
+        for cv in [overlap[v].0, overlap[v].1] {
+            let mut corner = [0.0; 3];
+            corner[axis] = middle;
+            corner[u] = cu;
+            corner[v] = cv;
+            let position = Vec3::new(corner[0], corner[1], corner[2]);
+
+            // Edge or corner contacts collapse to fewer distinct points
+            if !points
+                .iter()
+                .any(|p| (p.position - position).length() < 1e-9)
+            {
+                points.push(ContactPoint { position, depth });
+            }
+        }
+    }
+    points
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_spheres_collide_and_bounce_apart() {
+        let mut world = PhysicsWorld::new();
+        world.gravity = Vec3::zero();
+        let a = world.add_object("sphere".to_string(), 1.0, Vec3::new(0.0, 5.0, 0.0));
+        let b = world.add_object("sphere".to_string(), 1.0, Vec3::new(3.0, 5.0, 0.0));
+        world.objects[a].velocity = Vec3::new(5.0, 0.0, 0.0);
+        world.objects[b].velocity = Vec3::new(-5.0, 0.0, 0.0);
+
+        let mut events = Vec::new();
+        for _ in 0..60 {
+            world.step();
+            events.extend(world.collision_events.iter().cloned());
+        }
+
+        assert!(!events.is_empty());
+        assert_eq!((events[0].body_a, events[0].body_b), (a, b));
+        assert!(events[0].normal.x > 0.99);
+        assert!(world.objects[a].velocity.x < 0.0);
+        assert!(world.objects[b].velocity.x > 0.0);
+    }
+
+    #[test]
+    fn test_sphere_resting_against_static_box() {
+        let mut world = PhysicsWorld::new();
+        let wall = world.add_object("box".to_string(), 0.0, Vec3::new(1.0, 5.0, 0.0));
+        let ball = world.add_object("sphere".to_string(), 1.0, Vec3::new(0.2, 5.0, 0.0));
+
+        let events = world.detect_collisions();
+        assert_eq!(events.len(), 1);
+        assert_eq!((events[0].body_a, events[0].body_b), (wall, ball));
+        assert!(events[0].normal.x < -0.99);
+        assert!((events[0].depth - 0.2).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_sphere_dropped_onto_static_box_comes_to_rest_on_top() {
+        let mut world = PhysicsWorld::new();
+        let block = world.add_object("box".to_string(), 0.0, Vec3::new(0.0, 2.0, 0.0));
+        let ball = world.add_object("sphere".to_string(), 1.0, Vec3::new(0.0, 6.0, 0.0));
+
+        for _ in 0..600 {
+            world.step();
+            // Never sinks more than a little into the box on the way down
+            assert!(world.objects[ball].position.y > 2.9);
+        }
+
+        // Box top at 2.5 plus the 0.5 radius
+        let ball = &world.objects[ball];
+        assert!((ball.position.y - 3.0).abs() < 0.05, "{:?}", ball.position);
+        assert!(ball.velocity.length() < 0.1, "{:?}", ball.velocity);
+        assert_eq!(world.objects[block].position, Vec3::new(0.0, 2.0, 0.0));
+    }
+
+    /// Heights of successive bounce peaks of a ball dropped onto a static box
+    fn bounce_peaks(restitution: f64) -> Vec<f64> {
+        let mut world = PhysicsWorld::new();
+        let floor = world.add_object("box".to_string(), 0.0, Vec3::new(0.0, 1.0, 0.0));
+        world.set_size(floor, Vec3::new(5.0, 0.5, 5.0));
+        world.set_material(
+            floor,
+            Material {
+                restitution: 0.0,
+                friction: 0.0,
+            },
+        );
+        let ball = world.add_object("sphere".to_string(), 1.0, Vec3::new(0.0, 12.0, 0.0));
+        world.set_material(
+            ball,
+            Material {
+                restitution,
+                friction: 0.0,
+            },
+        );
+
+        // Heights above the resting position, at each top of the flight
+        let mut peaks = Vec::new();
+        let mut previous = world.objects[ball].velocity.y;
+        for _ in 0..600 {
+            world.step();
+            let velocity = world.objects[ball].velocity.y;
+            if previous > 0.0 && velocity <= 0.0 {
+                peaks.push(world.objects[ball].position.y - 2.0);
+            }
+            previous = velocity;
+        }
+        peaks
+    }
+
+    #[test]
+    fn test_restitution_scales_bounce_heights() {
+        // Each bounce keeps 0.8 of the speed, so 0.64 of the height
+        let mut heights = vec![10.0];
+        heights.extend(bounce_peaks(0.8));
+        assert!(heights.len() >= 4, "{:?}", heights);
+        for pair in heights[..4].windows(2) {
+            let ratio = pair[1] / pair[0];
+            assert!((ratio - 0.64).abs() < 0.03, "{:?}", heights);
+        }
+
+        // A dead ball only rises as far as pushing it out of the floor lifts it
+        let dead = bounce_peaks(0.0);
+        assert!(dead.iter().all(|&h| h < 0.1), "{:?}", dead);
+    }
+
+    #[test]
+    fn test_friction_stops_sliding_boxes() {
+        let mut world = PhysicsWorld::new();
+        let floor = world.add_object("box".to_string(), 0.0, Vec3::new(0.0, 1.0, 0.0));
+        world.set_size(floor, Vec3::new(50.0, 0.5, 50.0));
+        world.set_material(
+            floor,
+            Material {
+                restitution: 0.0,
+                friction: 1.0,
+            },
+        );
+
+        let slide = |world: &mut PhysicsWorld, z: f64, friction: f64| {
+            let id = world.add_object("box".to_string(), 1.0, Vec3::new(0.0, 2.0, z));
+            world.set_material(
+                id,
+                Material {
+                    restitution: 0.0,
+                    friction,
+                },
+            );
+            world.objects[id].velocity = Vec3::new(4.0, 0.0, 0.0);
+            id
+        };
+        let icy = slide(&mut world, -5.0, 0.0);
+        let rough = slide(&mut world, 5.0, 0.8);
+
+        for _ in 0..120 {
+            world.step();
+        }
+
+        // Coulomb friction stops the rough box after about v^2 / (2 mu g) = 1 m
+        let rough = &world.objects[rough];
+        assert!(rough.velocity.x.abs() < 1e-3, "{:?}", rough.velocity);
+        assert!((rough.position.x - 1.0).abs() < 0.2, "{:?}", rough.position);
+        assert!((world.objects[icy].velocity.x - 4.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_frozen_body_is_a_static_obstacle() {
+        let mut world = PhysicsWorld::new();
+        world.gravity = Vec3::zero();
+        let frozen = world.add_object("box".to_string(), 1.0, Vec3::new(2.0, 5.0, 0.0));
+        let ball = world.add_object("sphere".to_string(), 1.0, Vec3::new(0.0, 5.0, 0.0));
+        world.set_active(frozen, false);
+        world.objects[ball].velocity = Vec3::new(4.0, 0.0, 0.0);
+
+        for _ in 0..60 {
+            world.step();
+        }
+
+        assert_eq!(world.objects[frozen].position, Vec3::new(2.0, 5.0, 0.0));
+        assert!(world.objects[ball].velocity.x < 0.0);
+    }
+
+    #[test]
+    fn test_separated_bodies_do_not_collide() {
+        let mut world = PhysicsWorld::new();
+        world.add_object("box".to_string(), 1.0, Vec3::new(0.0, 5.0, 0.0));
+        world.add_object("sphere".to_string(), 1.0, Vec3::new(3.0, 5.0, 0.0));
+        assert!(world.detect_collisions().is_empty());
+    }
+
+    #[test]
+    fn test_spatial_hash_keeps_narrow_phase_far_below_all_pairs() {
+        // A 10 x 10 x 10 lattice of unit spheres 0.9 apart: only axis neighbours overlap
+        let mut world = PhysicsWorld::new();
+        for i in 0..1000 {
+            let (x, y, z) = ((i % 10) as f64, (i / 10 % 10) as f64, (i / 100) as f64);
+            world.add_object(
+                "sphere".to_string(),
+                1.0,
+                Vec3::new(x * 0.9, y * 0.9 + 5.0, z * 0.9),
+            );
+        }
+
+        let n = world.objects.len();
+        let (events, pairs_tested) = world.find_contacts();
+        assert_eq!(events.len(), 3 * 10 * 10 * 9);
+        assert!(
+            pairs_tested < n * n / 50,
+            "{} pairs tested for {} bodies",
+            pairs_tested,
+            n
+        );
+
+        world.step();
+        let substeps = world.settings.substeps.max(1);
+        assert!(world.stats().pairs_tested < substeps * n * n / 50);
+    }
+
+    #[test]
+    fn test_stacked_boxes_report_four_point_manifold() {
+        let mut world = PhysicsWorld::new();
+        let bottom = world.add_object("box".to_string(), 0.0, Vec3::new(0.0, 0.5, 0.0));
+        let top = world.add_object("box".to_string(), 1.0, Vec3::new(0.2, 1.5, 0.0));
+        let ball = world.add_object("sphere".to_string(), 1.0, Vec3::new(5.0, 3.0, 0.0));
+
+        for _ in 0..60 {
+            world.step();
+        }
+
+        let manifold = world.manifold(bottom, top).unwrap();
+        assert_eq!(manifold.points.len(), 4);
+        assert!((manifold.normal.y - 1.0).abs() < 1e-9);
+        for point in &manifold.points {
+            assert!((point.position.y - 1.0).abs() < 0.01);
+            assert!(point.position.x >= -0.3 && point.position.x <= 0.5 + 1e-9);
+            assert!(point.depth > 0.0);
+        }
+
+        // Reversed order flips the normal; bodies apart have no manifold
+        assert!((world.manifold(top, bottom).unwrap().normal.y + 1.0).abs() < 1e-9);
+        assert!(world.manifold(top, ball).is_none());
+    }
+
+    #[test]
+    fn test_one_way_platform_passes_rising_bodies_and_catches_falling_ones() {
+        let mut world = PhysicsWorld::new();
+        let platform = world.add_object("box".to_string(), 0.0, Vec3::new(0.0, 5.0, 0.0));
+        world.objects[platform].half_extents = Vec3::new(2.0, 0.25, 2.0);
+        assert!(world.set_one_way(platform, Some(Vec3::new(0.0, 2.0, 0.0))));
+
+        // Launched upwards from below the platform
+        let ball = world.add_object("sphere".to_string(), 1.0, Vec3::new(0.0, 3.5, 0.0));
+        world.objects[ball].velocity = Vec3::new(0.0, 10.0, 0.0);
+
+        let mut highest: f64 = 0.0;
+        for _ in 0..600 {
+            world.step();
+            highest = highest.max(world.objects[ball].position.y);
+        }
+
+        // Rose through the platform, then landed on its top face (5.25) and stayed there
+        assert!(highest > 8.0);
+        assert!((world.objects[ball].position.y - 5.75).abs() < 0.05);
+    }
+}
@@ -0,0 +1,575 @@
+// Physics Constraints for Matrix Language
+// Position-based distance and hinge constraints and composite bodies (ropes/chains, cloth) built from them
+
+use crate::stdlib::{PhysicsWorld, Vec3};
+use std::collections::HashSet;
+
+/// Stretch, as a fraction of rest length, beyond which a cloth link tears
+pub const CLOTH_TEAR_STRAIN: f64 = 0.5;
+
+/// Keeps two bodies at a fixed distance from each other
+#[derive(Debug, Clone)]
+pub struct DistanceConstraint {
+    pub body_a: usize,
+    pub body_b: usize,
+    pub rest_length: f64,
+    /// Stretch ratio `(length - rest_length) / rest_length` past which the constraint
+    /// breaks and is removed; `None` never breaks
+    pub max_strain: Option<f64>,
+}
+
+/// Lets `body_b` swing about an axis through a pivot carried by `body_a`, like a door
+/// on its hinge: `body_b` keeps its distance from the axis and its offset along it
+#[derive(Debug, Clone)]
+pub struct HingeConstraint {
+    pub body_a: usize,
+    pub body_b: usize,
+    /// Pivot position relative to `body_a`
+    pub pivot: Vec3,
+    /// Unit direction of the hinge axis
+    pub axis: Vec3,
+    /// Distance of `body_b` from the axis
+    pub radius: f64,
+    /// Offset of `body_b` from the pivot along the axis
+    pub height: f64,
+}
+
+impl HingeConstraint {
+    /// Where `body_b` belongs given both current positions, or `None` while it sits on
+    /// the axis and has no direction to swing in
+    fn target(&self, a: Vec3, b: Vec3) -> Option<Vec3> {
+        let pivot = a + self.pivot;
+        let offset = b - pivot;
+        let radial = offset - self.axis * offset.dot(&self.axis);
+        let distance = radial.length();
+        if distance < 1e-9 {
+            return None;
+        }
+        Some(pivot + self.axis * self.height + radial * (self.radius / distance))
+    }
+}
+
+/// A chain of small bodies linked by distance constraints
+#[derive(Debug, Clone)]
+pub struct Rope {
+    pub id: usize,
+    pub segments: Vec<usize>,
+    pub segment_length: f64,
+}
+
+/// A square grid of particles held together by tearable structural, shear and
+/// bend constraints
+#[derive(Debug, Clone)]
+pub struct Cloth {
+    pub id: usize,
+    /// Particle body ids in row-major order, `(resolution + 1)` per row
+    pub particles: Vec<usize>,
+    pub resolution: usize,
+}
+
+impl Cloth {
+    /// Body id of the particle in `row` and `column`
+    pub fn particle(&self, row: usize, column: usize) -> usize {
+        self.particles[row * (self.resolution + 1) + column]
+    }
+
+    /// Body ids of the four corner particles
+    pub fn corners(&self) -> [usize; 4] {
+        let last = self.resolution;
+        [
+            self.particle(0, 0),
+            self.particle(0, last),
+            self.particle(last, 0),
+            self.particle(last, last),
+        ]
+    }
+}
+
+impl PhysicsWorld {
+    /// Link two bodies with a distance constraint at their current separation
+    pub fn add_distance_constraint(&mut self, body_a: usize, body_b: usize) -> Option<usize> {
+        let a = self.objects.get(body_a)?.position;
+        let b = self.objects.get(body_b)?.position;
+
+        self.constraints.push(DistanceConstraint {
+            body_a,
+            body_b,
+            rest_length: (b - a).length(),
+            max_strain: None,
+        });
+        Some(self.constraints.len() - 1)
+    }
+
+    /// Hinge `body_b` to `body_a` about `axis` through the world point `pivot`, keeping
+    /// their current arrangement. `None` if either body is missing or the axis is zero.
+    pub fn add_hinge(
+        &mut self,
+        body_a: usize,
+        body_b: usize,
+        pivot: Vec3,
+        axis: Vec3,
+    ) -> Option<usize> {
+        let a = self.objects.get(body_a)?.position;
+        let b = self.objects.get(body_b)?.position;
+        let length = axis.length();
+        if !(length > 0.0 && length.is_finite()) {
+            return None;
+        }
+
+        let axis = axis * (1.0 / length);
+        let offset = b - pivot;
+        let height = offset.dot(&axis);
+        self.hinges.push(HingeConstraint {
+            body_a,
+            body_b,
+            pivot: pivot - a,
+            axis,
+            radius: (offset - axis * height).length(),
+            height,
+        });
+        Some(self.hinges.len() - 1)
+    }
+
+    /// Build a rope of `segments` links between `start` and `end`, returning its id.
+    /// The rope is created taut; pin its end segments with `set_pinned`.
+    pub fn add_rope(&mut self, start: Vec3, end: Vec3, segments: usize) -> usize {
+        let segments = segments.max(1);
+        let segment_mass = 0.1;
+        let mut ids = Vec::with_capacity(segments + 1);
+
+        for i in 0..=segments {
+            let t = i as f64 / segments as f64;
+            let position = start + (end - start) * t;
+            ids.push(self.add_object("rope_segment".to_string(), segment_mass, position));
+        }
+
+        for pair in ids.windows(2) {
+            self.add_distance_constraint(pair[0], pair[1]);
+        }
+
+        let id = self.ropes.len();
+        self.ropes.push(Rope {
+            id,
+            segments: ids,
+            segment_length: (end - start).length() / segments as f64,
+        });
+        id
+    }
+
+    /// Build a flat cloth of `resolution` x `resolution` cells from `corner`, spanning
+    /// `width` along x and `height` along z, returning its id. Every particle is linked
+    /// to its neighbours along rows and columns (structural), across cell diagonals
+    /// (shear) and two cells away (bend); links tear past `CLOTH_TEAR_STRAIN`. The cloth
+    /// is created flat and unpinned; pin its corners with `set_pinned`.
+    pub fn add_cloth(&mut self, corner: Vec3, width: f64, height: f64, resolution: usize) -> usize {
+        let resolution = resolution.max(1);
+        let particle_mass = 0.1;
+        let mut particles = Vec::with_capacity((resolution + 1) * (resolution + 1));
+
+        for row in 0..=resolution {
+            for column in 0..=resolution {
+                let offset = Vec3::new(
+                    width * column as f64 / resolution as f64,
+                    0.0,
+                    height * row as f64 / resolution as f64,
+                );
+                particles.push(self.add_object(
+                    "cloth_particle".to_string(),
+                    particle_mass,
+                    corner + offset,
+                ));
+            }
+        }
+
+        let id = self.cloths.len();
+        let cloth = Cloth {
+            id,
+            particles,
+            resolution,
+        };
+
+        // (row step, column step) of each link, so every pair is linked once
+        let links: [(usize, isize); 6] = [(0, 1), (1, 0), (1, 1), (1, -1), (0, 2), (2, 0)];
+        for row in 0..=resolution {
+            for column in 0..=resolution {
+                for &(row_step, column_step) in &links {
+                    let other_row = row + row_step;
+                    let other_column = column as isize + column_step;
+                    if other_row > resolution
+                        || other_column < 0
+                        || other_column > resolution as isize
+                    {
+                        continue;
+                    }
+                    let a = cloth.particle(row, column);
+                    let b = cloth.particle(other_row, other_column as usize);
+                    if let Some(index) = self.add_distance_constraint(a, b) {
+                        self.constraints[index].max_strain = Some(CLOTH_TEAR_STRAIN);
+                    }
+                }
+            }
+        }
+
+        self.cloths.push(cloth);
+        id
+    }
+
+    /// Number of constraints still linking particles of a cloth, or `None` if there is
+    /// no such cloth
+    pub fn cloth_constraint_count(&self, cloth_id: usize) -> Option<usize> {
+        let cloth = self.cloths.get(cloth_id)?;
+        let particles: HashSet<usize> = cloth.particles.iter().copied().collect();
+        Some(
+            self.constraints
+                .iter()
+                .filter(|c| particles.contains(&c.body_a) && particles.contains(&c.body_b))
+                .count(),
+        )
+    }
+
+    /// Iteratively project all distance and hinge constraints, then update the
+    /// velocities of the bodies they touch from their corrected positions.
+    /// Breakable constraints that this substep's motion stretched past their limit
+    /// tear first and are not projected.
+    pub(crate) fn solve_constraints(&mut self, previous_positions: &[Vec3], dt: f64) {
+        let objects = &self.objects;
+        self.constraints.retain(|constraint| {
+            let Some(max_strain) = constraint.max_strain else {
+                return true;
+            };
+            match (
+                objects.get(constraint.body_a),
+                objects.get(constraint.body_b),
+            ) {
+                (Some(a), Some(b)) if constraint.rest_length > 0.0 => {
+                    let length = (b.position - a.position).length();
+                    (length - constraint.rest_length) / constraint.rest_length <= max_strain
+                }
+                _ => true,
+            }
+        });
+
+        for _ in 0..self.settings.position_iterations {
+            self.stats.constraint_iterations += 1;
+            for constraint in &self.constraints {
+                let (a, b) = (constraint.body_a, constraint.body_b);
+                if a >= self.objects.len() || b >= self.objects.len() || a == b {
+                    continue;
+                }
+
+                let inv_mass_a = self.objects[a].inverse_mass();
+                let inv_mass_b = self.objects[b].inverse_mass();
+                let total_inv_mass = inv_mass_a + inv_mass_b;
+                if total_inv_mass == 0.0 {
+                    continue;
+                }
+
+                let delta = self.objects[b].position - self.objects[a].position;
+                let distance = delta.length();
+                if distance < 1e-9 {
+                    continue;
+                }
+
+                let error = distance - constraint.rest_length;
+                let correction = delta * (error / (distance * total_inv_mass));
+
+                let pos_a = self.objects[a].position + correction * inv_mass_a;
+                let pos_b = self.objects[b].position - correction * inv_mass_b;
+                self.objects[a].position = pos_a;
+                self.objects[b].position = pos_b;
+            }
+
+            for hinge in &self.hinges {
+                let (a, b) = (hinge.body_a, hinge.body_b);
+                if a >= self.objects.len() || b >= self.objects.len() || a == b {
+                    continue;
+                }
+
+                let inv_mass_a = self.objects[a].inverse_mass();
+                let inv_mass_b = self.objects[b].inverse_mass();
+                let total_inv_mass = inv_mass_a + inv_mass_b;
+                if total_inv_mass == 0.0 {
+                    continue;
+                }
+
+                let (pos_a, pos_b) = (self.objects[a].position, self.objects[b].position);
+                let Some(target) = hinge.target(pos_a, pos_b) else {
+                    continue;
+                };
+                // Moving `body_a` carries the target along with it
+                let correction = (pos_b - target) * (1.0 / total_inv_mass);
+                self.objects[a].position = pos_a + correction * inv_mass_a;
+                self.objects[b].position = pos_b - correction * inv_mass_b;
+            }
+        }
+
+        let constrained: HashSet<usize> = self
+            .constraints
+            .iter()
+            .map(|c| (c.body_a, c.body_b))
+            .chain(self.hinges.iter().map(|h| (h.body_a, h.body_b)))
+            .flat_map(|(a, b)| [a, b])
+            .collect();
+
+        let retained = 1.0 - self.settings.constraint_damping;
+        for id in constrained {
+            if let (Some(obj), Some(previous)) =
+                (self.objects.get_mut(id), previous_positions.get(id))
+            {
+                if !obj.is_immovable() {
+                    obj.velocity = (obj.position - *previous) * (retained / dt);
+                }
+            }
+        }
+
+        // A hinged body turns with its swing about the axis
+        for hinge in &self.hinges {
+            let (Some(a), Some(b)) = (
+                self.objects.get(hinge.body_a),
+                self.objects.get(hinge.body_b),
+            ) else {
+                continue;
+            };
+            if b.is_immovable() || hinge.radius < 1e-9 {
+                continue;
+            }
+            let arm = b.position - (a.position + hinge.pivot);
+            let swing = arm.cross(&(b.velocity - a.velocity)).dot(&hinge.axis)
+                / (hinge.radius * hinge.radius);
+            let spin = a.angular_velocity.dot(&hinge.axis);
+            self.objects[hinge.body_b].angular_velocity = hinge.axis * (swing + spin);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn segment_lengths(world: &PhysicsWorld, rope: &Rope) -> Vec<f64> {
+        rope.segments
+            .windows(2)
+            .map(|pair| {
+                (world.objects[pair[1]].position - world.objects[pair[0]].position).length()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_distance_constraint_settles_to_rest_length() {
+        let mut world = PhysicsWorld::new();
+        world.gravity = Vec3::zero();
+        let a = world.add_object("sphere".to_string(), 1.0, Vec3::new(0.0, 5.0, 0.0));
+        let b = world.add_object("sphere".to_string(), 3.0, Vec3::new(3.0, 5.0, 0.0));
+        let index = world.add_distance_constraint(a, b).unwrap();
+        world.constraints[index].rest_length = 2.0;
+
+        for _ in 0..120 {
+            world.step();
+        }
+
+        let length = (world.objects[b].position - world.objects[a].position).length();
+        assert!((length - 2.0).abs() < 1e-3, "length {}", length);
+        // The heavier body moved a third as far
+        assert!((world.objects[a].position.x - 0.75).abs() < 1e-3);
+        assert!(world.objects[a].velocity.length() < 1e-3);
+    }
+
+    /// Kinetic plus potential energy of a body
+    fn energy(world: &PhysicsWorld, id: usize) -> f64 {
+        let obj = &world.objects[id];
+        obj.mass * (0.5 * obj.velocity.dot(&obj.velocity) - world.gravity.y * obj.position.y)
+    }
+
+    #[test]
+    fn test_undamped_pendulum_conserves_energy() {
+        let mut world = PhysicsWorld::new();
+        // Projection bleeds energy each substep, so take small ones
+        world.settings.constraint_damping = 0.0;
+        world.settings.substeps = 20;
+        let pivot = world.add_object("sphere".to_string(), 1.0, Vec3::new(0.0, 20.0, 0.0));
+        let bob = world.add_object("sphere".to_string(), 1.0, Vec3::new(3.0, 20.0, 0.0));
+        world.set_pinned(pivot, true);
+        world.add_distance_constraint(pivot, bob);
+
+        let initial = energy(&world, bob);
+        let mut lowest = f64::INFINITY;
+        for _ in 0..600 {
+            world.step();
+            lowest = lowest.min(world.objects[bob].position.y);
+            let length = (world.objects[bob].position - world.objects[pivot].position).length();
+            assert!((length - 3.0).abs() < 1e-6, "length {}", length);
+        }
+
+        // Ten seconds is several full swings down to the bottom of the arc
+        assert!((lowest - 17.0).abs() < 0.01, "lowest {}", lowest);
+        let drift = (energy(&world, bob) - initial).abs() / (9.81 * 3.0);
+        assert!(
+            drift < 0.1,
+            "energy drifted by {:.1}% of the swing",
+            drift * 100.0
+        );
+    }
+
+    #[test]
+    fn test_hinged_door_swings_about_its_axis() {
+        let mut world = PhysicsWorld::new();
+        world.gravity = Vec3::zero();
+        let frame = world.add_object("box".to_string(), 1.0, Vec3::new(0.0, 5.0, 0.0));
+        let door = world.add_object("box".to_string(), 1.0, Vec3::new(2.0, 6.0, 0.0));
+        world.set_pinned(frame, true);
+        world
+            .add_hinge(
+                frame,
+                door,
+                Vec3::new(0.0, 5.0, 0.0),
+                Vec3::new(0.0, 2.0, 0.0),
+            )
+            .unwrap();
+        assert!(world
+            .add_hinge(frame, door, Vec3::zero(), Vec3::zero())
+            .is_none());
+
+        world.objects[door].velocity = Vec3::new(0.0, 1.0, -2.0);
+        for _ in 0..60 {
+            world.step();
+            let door = &world.objects[door];
+            let arm = door.position - Vec3::new(0.0, 5.0, 0.0);
+            assert!((arm.y - 1.0).abs() < 1e-9, "left its height: {:?}", arm);
+            assert!((Vec3::new(arm.x, 0.0, arm.z).length() - 2.0).abs() < 1e-6);
+        }
+
+        // Swung round the axis, turning with it
+        let door = &world.objects[door];
+        assert!(door.position.z < -1.0, "{:?}", door.position);
+        assert!(door.angular_velocity.y > 0.0, "{:?}", door.angular_velocity);
+        assert!(door.angular_velocity.x.abs() < 1e-9 && door.angular_velocity.z.abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_rope_pinned_at_both_ends_sags() {
+        let mut world = PhysicsWorld::new();
+        let rope_id = world.add_rope(Vec3::new(0.0, 10.0, 0.0), Vec3::new(10.0, 10.0, 0.0), 10);
+        let rope = world.ropes[rope_id].clone();
+        let first = rope.segments[0];
+        let last = *rope.segments.last().unwrap();
+
+        // Bring the ends closer together so the rope has slack to hang
+        world.objects[last].position = Vec3::new(6.0, 10.0, 0.0);
+        world.set_pinned(first, true);
+        world.set_pinned(last, true);
+
+        for _ in 0..1200 {
+            world.step();
+        }
+
+        let heights: Vec<f64> = rope
+            .segments
+            .iter()
+            .map(|&id| world.objects[id].position.y)
+            .collect();
+
+        // Ends stay fixed
+        assert_eq!(world.objects[first].position, Vec3::new(0.0, 10.0, 0.0));
+        assert_eq!(world.objects[last].position, Vec3::new(6.0, 10.0, 0.0));
+
+        // Lowest point in the middle, symmetric, descending towards it
+        let middle = heights[5];
+        assert!(middle < 8.0, "middle should sag, got {}", middle);
+        for i in 0..5 {
+            assert!(heights[i] > heights[i + 1] - 1e-3);
+            assert!((heights[i] - heights[10 - i]).abs() < 0.1, "{:?}", heights);
+        }
+
+        for length in segment_lengths(&world, &rope) {
+            assert!((length - rope.segment_length).abs() < 0.1 * rope.segment_length);
+        }
+    }
+
+    #[test]
+    fn test_free_rope_swings_without_separating() {
+        let mut world = PhysicsWorld::new();
+        let rope_id = world.add_rope(Vec3::new(0.0, 10.0, 0.0), Vec3::new(5.0, 10.0, 0.0), 10);
+        let rope = world.ropes[rope_id].clone();
+        let tip = *rope.segments.last().unwrap();
+        world.set_pinned(rope.segments[0], true);
+
+        let mut min_tip_x = f64::INFINITY;
+        for _ in 0..240 {
+            world.step();
+            min_tip_x = min_tip_x.min(world.objects[tip].position.x);
+
+            for length in segment_lengths(&world, &rope) {
+                assert!(length < 1.5 * rope.segment_length);
+            }
+        }
+
+        // The chain swung down and through past the pivot
+        assert!(min_tip_x < 0.0, "tip never swung past pivot: {}", min_tip_x);
+        assert!(world.objects[tip].position.y < 10.0);
+    }
+
+    fn pinned_cloth(world: &mut PhysicsWorld) -> Cloth {
+        let cloth_id = world.add_cloth(Vec3::new(0.0, 10.0, 0.0), 4.0, 4.0, 8);
+        let cloth = world.cloths[cloth_id].clone();
+        for corner in cloth.corners() {
+            world.set_pinned(corner, true);
+        }
+        cloth
+    }
+
+    #[test]
+    fn test_cloth_pinned_at_corners_sags() {
+        let mut world = PhysicsWorld::new();
+        let cloth = pinned_cloth(&mut world);
+        let links = world.cloth_constraint_count(cloth.id).unwrap();
+        // 2 * 8 * 9 structural, 2 * 8 * 8 shear, 2 * 7 * 9 bend
+        assert_eq!(links, 144 + 128 + 126);
+
+        for _ in 0..600 {
+            world.step();
+        }
+
+        let center = world.objects[cloth.particle(4, 4)].position;
+        let edge = world.objects[cloth.particle(0, 4)].position;
+        for corner in cloth.corners() {
+            assert_eq!(world.objects[corner].position.y, 10.0);
+        }
+        assert!(center.y < 9.9, "center should sag, got {}", center.y);
+        assert!(
+            center.y < edge.y,
+            "center {} above edge {}",
+            center.y,
+            edge.y
+        );
+        assert!((center.x - 2.0).abs() < 0.01 && (center.z - 2.0).abs() < 0.01);
+
+        // Gravity alone does not tear the cloth
+        assert_eq!(world.cloth_constraint_count(cloth.id), Some(links));
+    }
+
+    #[test]
+    fn test_strong_pull_tears_cloth() {
+        let mut world = PhysicsWorld::new();
+        let cloth = pinned_cloth(&mut world);
+        let links = world.cloth_constraint_count(cloth.id).unwrap();
+        let grabbed = cloth.particle(4, 4);
+
+        for _ in 0..240 {
+            world.apply_force(grabbed, Vec3::new(0.0, -2000.0, 0.0));
+            world.step();
+        }
+
+        let remaining = world.cloth_constraint_count(cloth.id).unwrap();
+        assert!(remaining < links, "no links tore");
+
+        // The surviving links hold their length instead of stretching without bound
+        let ids: HashSet<usize> = cloth.particles.iter().copied().collect();
+        for constraint in world.constraints.iter().filter(|c| ids.contains(&c.body_a)) {
+            let length = (world.objects[constraint.body_b].position
+                - world.objects[constraint.body_a].position)
+                .length();
+            assert!(length <= constraint.rest_length * (1.0 + CLOTH_TEAR_STRAIN));
+        }
+    }
+}
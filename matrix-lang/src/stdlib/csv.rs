@@ -0,0 +1,169 @@
+// CSV Parsing for Matrix Language
+// Splits CSV text into rows of numbers and strings, or into a numeric matrix
+
+use crate::eval::interpreter::{RuntimeError, Value};
+
+pub fn register_csv_functions(interpreter: &mut crate::eval::Interpreter) {
+    // Rows of cells; numeric cells become floats, anything else stays a string
+    interpreter.environment.define(
+        "parse_csv".to_string(),
+        Value::BuiltinFunction {
+            name: "parse_csv".to_string(),
+            arity: 1,
+            func: |args| {
+                let text = expect_text(&args[0])?;
+                Ok(Value::Array(
+                    parse_rows(text)?
+                        .into_iter()
+                        .map(|row| Value::Array(row.into_iter().map(cell_value).collect()))
+                        .collect(),
+                ))
+            },
+        },
+    );
+
+    // Numeric matrix; errors on non-numeric cells and ragged rows
+    interpreter.environment.define(
+        "parse_csv_numeric".to_string(),
+        Value::BuiltinFunction {
+            name: "parse_csv_numeric".to_string(),
+            arity: 1,
+            func: |args| {
+                let text = expect_text(&args[0])?;
+                let rows = parse_numeric(text)?;
+                Ok(Value::Matrix(
+                    rows.into_iter()
+                        .map(|row| row.into_iter().map(Value::Float).collect())
+                        .collect(),
+                ))
+            },
+        },
+    );
+}
+
+fn expect_text(value: &Value) -> Result<&str, RuntimeError> {
+    match value {
+        Value::String(s) => Ok(s),
+        other => Err(RuntimeError::TypeError {
+            message: format!("CSV input must be a string, found {}", other.type_name()),
+        }),
+    }
+}
+
+fn cell_value(cell: String) -> Value {
+    match cell.trim().parse::<f64>() {
+        Ok(number) => Value::Float(number),
+        Err(_) => Value::String(cell),
+    }
+}
+
+/// Split CSV text into rows of raw cells. Blank lines are skipped; fields may be
+/// double-quoted to contain commas, with `""` standing for a literal quote.
+pub fn parse_rows(text: &str) -> Result<Vec<Vec<String>>, RuntimeError> {
+    let mut rows = Vec::new();
+
+    for (index, line) in text.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let mut cells = Vec::new();
+        let mut cell = String::new();
+        let mut in_quotes = false;
+        let mut chars = line.chars().peekable();
+
+        while let Some(c) = chars.next() {
+            match c {
+                '"' if in_quotes && chars.peek() == Some(&'"') => {
+                    cell.push('"');
+                    chars.next();
+                }
+                '"' => in_quotes = !in_quotes,
+                ',' if !in_quotes => cells.push(std::mem::take(&mut cell)),
+                _ => cell.push(c),
+            }
+        }
+
+        if in_quotes {
+            return Err(RuntimeError::Generic {
+                message: format!("Unterminated quoted field on CSV line {}", index + 1),
+            });
+        }
+        cells.push(cell);
+        rows.push(cells);
+    }
+
+    Ok(rows)
+}
+
+/// Parse CSV text into rows of floats, rejecting non-numeric cells and ragged rows
+pub fn parse_numeric(text: &str) -> Result<Vec<Vec<f64>>, RuntimeError> {
+    let rows = parse_rows(text)?;
+    let width = rows.first().map_or(0, |row| row.len());
+
+    rows.into_iter()
+        .enumerate()
+        .map(|(r, row)| {
+            if row.len() != width {
+                return Err(RuntimeError::Generic {
+                    message: format!(
+                        "CSV row {} has {} fields, expected {}",
+                        r + 1,
+                        row.len(),
+                        width
+                    ),
+                });
+            }
+
+            row.iter()
+                .enumerate()
+                .map(|(c, cell)| {
+                    cell.trim()
+                        .parse::<f64>()
+                        .map_err(|_| RuntimeError::Generic {
+                            message: format!(
+                                "Non-numeric CSV cell '{}' at row {}, column {}",
+                                cell,
+                                r + 1,
+                                c + 1
+                            ),
+                        })
+                })
+                .collect()
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_numeric_csv_to_matrix() {
+        let rows = parse_numeric("1,2,3\n4.5, -6 ,7e1\n").unwrap();
+        assert_eq!(rows, vec![vec![1.0, 2.0, 3.0], vec![4.5, -6.0, 70.0]]);
+    }
+
+    #[test]
+    fn test_mixed_csv_keeps_strings() {
+        let rows: Vec<Vec<Value>> = parse_rows("name,mass\n\"ball, red\",2.5\n\nbox,heavy")
+            .unwrap()
+            .into_iter()
+            .map(|row| row.into_iter().map(cell_value).collect())
+            .collect();
+
+        assert_eq!(rows.len(), 3);
+        assert!(matches!(&rows[0][1], Value::String(s) if s == "mass"));
+        assert!(matches!(&rows[1][0], Value::String(s) if s == "ball, red"));
+        assert!(matches!(rows[1][1], Value::Float(m) if m == 2.5));
+        assert!(matches!(&rows[2][1], Value::String(s) if s == "heavy"));
+    }
+
+    #[test]
+    fn test_numeric_csv_rejects_ragged_and_text_cells() {
+        let ragged = parse_numeric("1,2,3\n4,5\n").unwrap_err();
+        assert!(ragged.to_string().contains("row 2 has 2 fields"));
+        assert!(parse_numeric("1,2\n3,x\n").is_err());
+        assert!(parse_rows("\"open,1\n").is_err());
+    }
+}
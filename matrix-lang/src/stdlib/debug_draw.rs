@@ -0,0 +1,111 @@
+// Debug Drawing for Matrix Language
+// Renderer-agnostic shapes describing bounds, contacts and velocities of a world
+
+use crate::stdlib::{PhysicsWorld, Vec3};
+
+/// Which categories of debug geometry to produce
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct DebugDrawOptions {
+    pub aabbs: bool,
+    pub contacts: bool,
+    pub velocities: bool,
+}
+
+/// A primitive for a renderer to draw over the scene
+#[derive(Debug, Clone, PartialEq)]
+pub enum DebugShape {
+    /// Axis-aligned bounds of a body
+    Aabb { body: usize, min: Vec3, max: Vec3 },
+    /// A contact point from the last step
+    ContactPoint { position: Vec3 },
+    /// A segment, used for velocity vectors
+    Line { from: Vec3, to: Vec3 },
+}
+
+impl PhysicsWorld {
+    /// Debug geometry for the current state. Contacts come from the last step's
+    /// collision events; velocity lines span one second of travel.
+    pub fn debug_geometry(&self, options: &DebugDrawOptions) -> Vec<DebugShape> {
+        let mut shapes = Vec::new();
+
+        if options.aabbs {
+            shapes.extend(self.objects.iter().enumerate().map(|(body, obj)| {
+                let (min, max) = obj.aabb();
+                DebugShape::Aabb { body, min, max }
+            }));
+        }
+
+        if options.contacts {
+            for event in &self.collision_events {
+                match self.manifold(event.body_a, event.body_b) {
+                    Some(manifold) => shapes.extend(manifold.points.into_iter().map(|p| {
+                        DebugShape::ContactPoint {
+                            position: p.position,
+                        }
+                    })),
+                    None => shapes.push(DebugShape::ContactPoint {
+                        position: event.point,
+                    }),
+                }
+            }
+        }
+
+        if options.velocities {
+            shapes.extend(
+                self.objects
+                    .iter()
+                    .filter(|obj| obj.velocity.length() > 0.0)
+                    .map(|obj| DebugShape::Line {
+                        from: obj.position,
+                        to: obj.position + obj.velocity,
+                    }),
+            );
+        }
+
+        shapes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_debug_geometry_categories() {
+        let mut world = PhysicsWorld::new();
+        world.add_object("sphere".to_string(), 1.0, Vec3::new(0.0, 5.0, 0.0));
+        let moving = world.add_object("box".to_string(), 1.0, Vec3::new(4.0, 5.0, 0.0));
+        world.objects[moving].half_extents = Vec3::new(1.0, 0.5, 1.0);
+        world.objects[moving].velocity = Vec3::new(2.0, 0.0, 0.0);
+
+        let aabbs = world.debug_geometry(&DebugDrawOptions {
+            aabbs: true,
+            ..Default::default()
+        });
+        assert_eq!(aabbs.len(), 2);
+        assert_eq!(
+            aabbs[1],
+            DebugShape::Aabb {
+                body: moving,
+                min: Vec3::new(3.0, 4.5, -1.0),
+                max: Vec3::new(5.0, 5.5, 1.0),
+            }
+        );
+
+        let velocities = world.debug_geometry(&DebugDrawOptions {
+            velocities: true,
+            ..Default::default()
+        });
+        assert_eq!(
+            velocities,
+            vec![DebugShape::Line {
+                from: Vec3::new(4.0, 5.0, 0.0),
+                to: Vec3::new(6.0, 5.0, 0.0),
+            }]
+        );
+
+        assert!(world
+            .debug_geometry(&DebugDrawOptions::default())
+            .is_empty());
+    }
+}
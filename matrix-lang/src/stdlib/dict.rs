@@ -0,0 +1,171 @@
+// Dictionaries for Matrix Language
+// Lookup, update and key listing for string-keyed `Dict` values
+
+use crate::eval::interpreter::{RuntimeError, Value};
+use std::collections::HashMap;
+
+pub fn register_dict_functions(interpreter: &mut crate::eval::Interpreter) {
+    // Empty dictionary; `{}` is an empty block, not a dictionary
+    interpreter.environment.define(
+        "dict_new".to_string(),
+        Value::BuiltinFunction {
+            name: "dict_new".to_string(),
+            arity: 0,
+            func: |_| Ok(Value::Dict(HashMap::new())),
+        },
+    );
+
+    // Value stored under a key; a missing key is an error, like `d[key]`
+    interpreter.environment.define(
+        "dict_get".to_string(),
+        Value::BuiltinFunction {
+            name: "dict_get".to_string(),
+            arity: 2,
+            func: |args| lookup(expect_dict(&args[0])?, expect_key(&args[1])?),
+        },
+    );
+
+    // Copy of the dictionary with `key` set to `value`
+    interpreter.environment.define(
+        "dict_set".to_string(),
+        Value::BuiltinFunction {
+            name: "dict_set".to_string(),
+            arity: 3,
+            func: |args| {
+                let mut dict = expect_dict(&args[0])?.clone();
+                dict.insert(expect_key(&args[1])?.to_string(), args[2].clone());
+                Ok(Value::Dict(dict))
+            },
+        },
+    );
+
+    // Keys in sorted order
+    interpreter.environment.define(
+        "dict_keys".to_string(),
+        Value::BuiltinFunction {
+            name: "dict_keys".to_string(),
+            arity: 1,
+            func: |args| {
+                let mut keys: Vec<&String> = expect_dict(&args[0])?.keys().collect();
+                keys.sort();
+                Ok(Value::Array(
+                    keys.into_iter()
+                        .map(|key| Value::String(key.clone()))
+                        .collect(),
+                ))
+            },
+        },
+    );
+
+    interpreter.environment.define(
+        "dict_contains".to_string(),
+        Value::BuiltinFunction {
+            name: "dict_contains".to_string(),
+            arity: 2,
+            func: |args| {
+                let dict = expect_dict(&args[0])?;
+                Ok(Value::Bool(dict.contains_key(expect_key(&args[1])?)))
+            },
+        },
+    );
+}
+
+/// Value under `key`, or a `FieldNotFound` error naming the key
+pub fn lookup(dict: &HashMap<String, Value>, key: &str) -> Result<Value, RuntimeError> {
+    dict.get(key)
+        .cloned()
+        .ok_or_else(|| RuntimeError::FieldNotFound {
+            field: key.to_string(),
+            type_name: "Dict".to_string(),
+        })
+}
+
+fn expect_dict(value: &Value) -> Result<&HashMap<String, Value>, RuntimeError> {
+    match value {
+        Value::Dict(dict) => Ok(dict),
+        other => Err(RuntimeError::TypeError {
+            message: format!("Expected dictionary, found {}", other.type_name()),
+        }),
+    }
+}
+
+fn expect_key(value: &Value) -> Result<&str, RuntimeError> {
+    match value {
+        Value::String(key) => Ok(key),
+        other => Err(RuntimeError::TypeError {
+            message: format!(
+                "Dictionary keys must be strings, found {}",
+                other.type_name()
+            ),
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::eval::Interpreter;
+
+    fn text(s: &str) -> Value {
+        Value::String(s.to_string())
+    }
+
+    #[test]
+    fn test_dict_functions_update_and_query() {
+        let mut interpreter = Interpreter::new();
+        register_dict_functions(&mut interpreter);
+        let mut call = |name: &str, args: &[Value]| {
+            let function = interpreter.environment.get(name).cloned().unwrap();
+            interpreter.call_value(function, args)
+        };
+
+        let empty = call("dict_new", &[]).unwrap();
+        let one = call(
+            "dict_set",
+            &[empty.clone(), text("mass"), Value::Float(2.0)],
+        )
+        .unwrap();
+        let two = call("dict_set", &[one.clone(), text("id"), Value::Int(7)]).unwrap();
+
+        // Setting returns a new dictionary and leaves the original alone
+        assert_eq!(call("dict_keys", &[empty]).unwrap(), Value::Array(vec![]));
+        assert_eq!(
+            call("dict_keys", std::slice::from_ref(&two)).unwrap(),
+            Value::Array(vec![text("id"), text("mass")])
+        );
+        assert_eq!(
+            call("dict_get", &[two.clone(), text("mass")]).unwrap(),
+            Value::Float(2.0)
+        );
+        assert_eq!(
+            call("dict_contains", &[one.clone(), text("id")]).unwrap(),
+            Value::Bool(false)
+        );
+        assert_eq!(
+            call("dict_contains", &[two.clone(), text("id")]).unwrap(),
+            Value::Bool(true)
+        );
+
+        let overwritten = call("dict_set", &[two, text("mass"), Value::Float(3.0)]).unwrap();
+        assert_eq!(
+            call("dict_get", &[overwritten, text("mass")]).unwrap(),
+            Value::Float(3.0)
+        );
+    }
+
+    #[test]
+    fn test_missing_key_is_an_error() {
+        let mut interpreter = Interpreter::new();
+        register_dict_functions(&mut interpreter);
+        let dict_get = interpreter.environment.get("dict_get").cloned().unwrap();
+        let dict = Value::Dict(HashMap::from([("a".to_string(), Value::Int(1))]));
+
+        let missing = interpreter.call_value(dict_get.clone(), &[dict.clone(), text("b")]);
+        assert!(matches!(
+            missing,
+            Err(RuntimeError::FieldNotFound { ref field, .. }) if field == "b"
+        ));
+        let bad_key = interpreter.call_value(dict_get, &[dict, Value::Int(0)]);
+        assert!(matches!(bad_key, Err(RuntimeError::TypeError { .. })));
+    }
+}
@@ -0,0 +1,213 @@
+// Step Events for Matrix Language
+// Observers notified before and after every world step, for recorders, renderers and networking
+
+use crate::stdlib::PhysicsWorld;
+
+/// Receives a world's step notifications. Both hooks default to doing nothing.
+/// Hooks may modify the world, including subscribing or unsubscribing observers;
+/// observers subscribed during a step are first notified on the next one.
+pub trait StepObserver: Send {
+    fn on_pre_step(&mut self, _world: &mut PhysicsWorld) {}
+    fn on_post_step(&mut self, _world: &mut PhysicsWorld) {}
+}
+
+/// Handle returned by `PhysicsWorld::subscribe`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SubscriptionId(usize);
+
+/// A world's observers in registration order
+#[derive(Default)]
+pub struct StepObservers {
+    next_id: usize,
+    entries: Vec<(SubscriptionId, Box<dyn StepObserver>)>,
+    /// Ids of the entries taken out of the world for the current notification
+    dispatching: Vec<SubscriptionId>,
+    /// Unsubscribed during the current notification, removed once it finishes
+    cancelled: Vec<SubscriptionId>,
+}
+
+impl StepObservers {
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+/// Observers are not duplicated: a cloned world starts with none
+impl Clone for StepObservers {
+    fn clone(&self) -> Self {
+        Self {
+            next_id: self.next_id,
+            ..Self::default()
+        }
+    }
+}
+
+impl std::fmt::Debug for StepObservers {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("StepObservers")
+            .field("subscribers", &self.entries.len())
+            .finish()
+    }
+}
+
+#[derive(Clone, Copy)]
+enum StepPhase {
+    Pre,
+    Post,
+}
+
+impl PhysicsWorld {
+    /// Notify `observer` around every step until it is unsubscribed
+    pub fn subscribe(&mut self, observer: Box<dyn StepObserver>) -> SubscriptionId {
+        let observers = &mut self.step_observers;
+        let id = SubscriptionId(observers.next_id);
+        observers.next_id += 1;
+        observers.entries.push((id, observer));
+        id
+    }
+
+    /// Stop notifying a subscriber, returning whether it was subscribed. Safe to call
+    /// from inside a hook, including for the observer being notified.
+    pub fn unsubscribe(&mut self, id: SubscriptionId) -> bool {
+        let observers = &mut self.step_observers;
+        if let Some(index) = observers.entries.iter().position(|(i, _)| *i == id) {
+            observers.entries.remove(index);
+            return true;
+        }
+        if observers.dispatching.contains(&id) && !observers.cancelled.contains(&id) {
+            // Dropped once the current notification finishes
+            observers.cancelled.push(id);
+            return true;
+        }
+        false
+    }
+
+    pub(crate) fn notify_pre_step(&mut self) {
+        self.notify_observers(StepPhase::Pre);
+    }
+
+    pub(crate) fn notify_post_step(&mut self) {
+        self.notify_observers(StepPhase::Post);
+    }
+
+    fn notify_observers(&mut self, phase: StepPhase) {
+        // Hooks that step the world themselves do not notify again
+        if self.step_observers.entries.is_empty() || !self.step_observers.dispatching.is_empty() {
+            return;
+        }
+
+        // Take the list out so hooks can borrow the world mutably
+        let mut entries = std::mem::take(&mut self.step_observers.entries);
+        self.step_observers.dispatching = entries.iter().map(|(id, _)| *id).collect();
+
+        for (id, observer) in &mut entries {
+            if self.step_observers.cancelled.contains(id) {
+                continue;
+            }
+            match phase {
+                StepPhase::Pre => observer.on_pre_step(self),
+                StepPhase::Post => observer.on_post_step(self),
+            }
+        }
+
+        let observers = &mut self.step_observers;
+        observers.dispatching.clear();
+        let cancelled = std::mem::take(&mut observers.cancelled);
+        entries.retain(|(id, _)| !cancelled.contains(id));
+        // Keep subscriptions made during dispatch after the existing ones
+        entries.append(&mut observers.entries);
+        observers.entries = entries;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+
+    type Log = Arc<Mutex<Vec<String>>>;
+
+    struct Recorder {
+        name: &'static str,
+        log: Log,
+    }
+
+    impl StepObserver for Recorder {
+        fn on_pre_step(&mut self, _world: &mut PhysicsWorld) {
+            self.log.lock().unwrap().push(format!("{} pre", self.name));
+        }
+
+        fn on_post_step(&mut self, _world: &mut PhysicsWorld) {
+            self.log.lock().unwrap().push(format!("{} post", self.name));
+        }
+    }
+
+    fn recorder(name: &'static str, log: &Log) -> Box<dyn StepObserver> {
+        Box::new(Recorder {
+            name,
+            log: log.clone(),
+        })
+    }
+
+    fn take(log: &Log) -> Vec<String> {
+        std::mem::take(&mut *log.lock().unwrap())
+    }
+
+    #[test]
+    fn test_subscribers_notified_in_registration_order() {
+        let log = Log::default();
+        let mut world = PhysicsWorld::new();
+        let first = world.subscribe(recorder("a", &log));
+        world.subscribe(recorder("b", &log));
+
+        world.step();
+        world.step();
+        assert_eq!(
+            take(&log),
+            ["a pre", "b pre", "a post", "b post", "a pre", "b pre", "a post", "b post"]
+        );
+
+        assert!(world.unsubscribe(first));
+        assert!(!world.unsubscribe(first));
+        world.step();
+        assert_eq!(take(&log), ["b pre", "b post"]);
+    }
+
+    /// Unsubscribes another observer from inside its own pre-step hook
+    struct Canceller {
+        target: Arc<Mutex<Option<SubscriptionId>>>,
+    }
+
+    impl StepObserver for Canceller {
+        fn on_pre_step(&mut self, world: &mut PhysicsWorld) {
+            if let Some(id) = self.target.lock().unwrap().take() {
+                assert!(world.unsubscribe(id));
+            }
+        }
+    }
+
+    #[test]
+    fn test_unsubscribe_during_notification() {
+        let log = Log::default();
+        let target = Arc::new(Mutex::new(None));
+        let mut world = PhysicsWorld::new();
+        world.subscribe(Box::new(Canceller {
+            target: target.clone(),
+        }));
+        let victim = world.subscribe(recorder("victim", &log));
+        world.subscribe(recorder("other", &log));
+        *target.lock().unwrap() = Some(victim);
+
+        world.step();
+        // The victim is skipped from the moment it is unsubscribed
+        assert_eq!(take(&log), ["other pre", "other post"]);
+        assert_eq!(world.step_observers.len(), 2);
+
+        world.step();
+        assert_eq!(take(&log), ["other pre", "other post"]);
+    }
+}
@@ -0,0 +1,139 @@
+// Fluid Volumes for Matrix Language
+// Axis-aligned regions of fluid applying buoyancy and drag to the bodies inside them
+
+use crate::stdlib::{PhysicsObject, PhysicsWorld, Vec3};
+
+/// Linear drag (1/s) applied to a fully submerged body unless changed
+const DEFAULT_FLUID_DRAG: f64 = 1.0;
+
+/// A box of fluid. Bodies overlapping it are pushed against gravity by the weight of
+/// the fluid they displace and slowed in proportion to how deep they are.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FluidVolume {
+    pub id: usize,
+    pub min: Vec3,
+    pub max: Vec3,
+    /// Fluid density in kg/m^3 (water is about 1000)
+    pub density: f64,
+    /// Fraction of velocity removed per second when fully submerged
+    pub linear_drag: f64,
+}
+
+impl FluidVolume {
+    pub fn new(id: usize, min: Vec3, max: Vec3, density: f64) -> Self {
+        Self {
+            id,
+            min,
+            max,
+            density,
+            linear_drag: DEFAULT_FLUID_DRAG,
+        }
+    }
+
+    /// Fraction of the body's bounds inside the volume, in [0, 1]. Spheres are
+    /// approximated by their bounding box.
+    pub fn submerged_fraction(&self, obj: &PhysicsObject) -> f64 {
+        let (min, max) = obj.aabb();
+        let overlap = |lo: f64, hi: f64, fluid_lo: f64, fluid_hi: f64| {
+            let size = hi - lo;
+            if size <= 0.0 {
+                return 0.0;
+            }
+            ((hi.min(fluid_hi) - lo.max(fluid_lo)) / size).clamp(0.0, 1.0)
+        };
+        overlap(min.x, max.x, self.min.x, self.max.x)
+            * overlap(min.y, max.y, self.min.y, self.max.y)
+            * overlap(min.z, max.z, self.min.z, self.max.z)
+    }
+}
+
+/// Volume of the body's shape in m^3
+fn body_volume(obj: &PhysicsObject) -> f64 {
+    let e = obj.half_extents;
+    if obj.shape == "sphere" {
+        4.0 / 3.0 * std::f64::consts::PI * e.x.powi(3)
+    } else {
+        8.0 * e.x * e.y * e.z
+    }
+}
+
+impl PhysicsWorld {
+    /// Add a fluid volume spanning `min`..`max`, returning its id
+    pub fn add_fluid_volume(&mut self, min: Vec3, max: Vec3, density: f64) -> usize {
+        let id = self.fluid_volumes.len();
+        self.fluid_volumes
+            .push(FluidVolume::new(id, min, max, density));
+        id
+    }
+
+    /// Apply buoyancy and drag from every fluid volume over `h` seconds
+    pub(crate) fn apply_fluid_volumes(&mut self, h: f64) {
+        let gravity = self.effective_gravity();
+        for obj in &mut self.objects {
+            if obj.is_immovable() || obj.is_sleeping {
+                continue;
+            }
+
+            for fluid in &self.fluid_volumes {
+                let fraction = fluid.submerged_fraction(obj);
+                if fraction <= 0.0 {
+                    continue;
+                }
+
+                // Archimedes: the displaced fluid's weight, acting against gravity
+                let displaced_mass = fluid.density * body_volume(obj) * fraction;
+                obj.velocity = obj.velocity - gravity * (displaced_mass * obj.inverse_mass() * h);
+
+                // Implicit so large drag or steps never reverse the velocity
+                obj.velocity = obj.velocity * (1.0 / (1.0 + fluid.linear_drag * fraction * h));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A 1 m cube of the given density dropped from 2 m above the surface
+    fn world_with_cube(density: f64) -> (PhysicsWorld, usize) {
+        let mut world = PhysicsWorld::new();
+        world.add_fluid_volume(
+            Vec3::new(-10.0, 0.0, -10.0),
+            Vec3::new(10.0, 10.0, 10.0),
+            1000.0,
+        );
+        let cube = world.add_object("box".to_string(), density, Vec3::new(0.0, 12.0, 0.0));
+        world.objects[cube].half_extents = Vec3::new(0.5, 0.5, 0.5);
+        (world, cube)
+    }
+
+    #[test]
+    fn test_light_box_floats_half_submerged() {
+        let (mut world, cube) = world_with_cube(500.0);
+
+        for _ in 0..1800 {
+            world.step();
+        }
+
+        // Half the fluid's density floats with half its height below the surface
+        let cube = &world.objects[cube];
+        assert!(
+            (cube.position.y - 10.0).abs() < 0.05,
+            "y = {}",
+            cube.position.y
+        );
+        assert!((world.fluid_volumes[0].submerged_fraction(cube) - 0.5).abs() < 0.05);
+    }
+
+    #[test]
+    fn test_dense_box_sinks() {
+        let (mut world, cube) = world_with_cube(3000.0);
+
+        for _ in 0..300 {
+            world.step();
+        }
+
+        assert!(world.objects[cube].position.y < 1.0);
+    }
+}
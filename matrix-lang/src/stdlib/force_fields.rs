@@ -0,0 +1,119 @@
+// Force Fields for Matrix Language
+// World-wide environmental forces such as wind and point attractors
+
+use crate::stdlib::{PhysicsWorld, Vec3};
+
+/// A force applied to every movable body at the start of each step
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ForceField {
+    /// The same force everywhere, like a steady wind
+    Uniform { force: Vec3 },
+    /// A force of constant magnitude towards `center`; negative strength repels
+    Radial { center: Vec3, strength: f64 },
+}
+
+impl ForceField {
+    pub fn force_at(&self, position: Vec3) -> Vec3 {
+        match *self {
+            ForceField::Uniform { force } => force,
+            ForceField::Radial { center, strength } => {
+                let offset = center - position;
+                let distance = offset.length();
+                if distance < 1e-9 {
+                    Vec3::zero()
+                } else {
+                    offset * (strength / distance)
+                }
+            }
+        }
+    }
+}
+
+impl PhysicsWorld {
+    /// Replace the world's force field; `None` applies no environmental force
+    pub fn set_force_field(&mut self, field: Option<ForceField>) {
+        self.force_field = field;
+    }
+
+    /// Add the force field's force to every movable body
+    pub(crate) fn apply_force_field(&mut self) {
+        let Some(field) = self.force_field else {
+            return;
+        };
+        for id in 0..self.objects.len() {
+            if self.objects[id].is_immovable() {
+                continue;
+            }
+            let force = field.force_at(self.objects[id].position);
+            self.apply_force(id, force);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn floating_world(positions: &[Vec3]) -> PhysicsWorld {
+        let mut world = PhysicsWorld::new();
+        world.gravity = Vec3::zero();
+        for &position in positions {
+            world.add_object("sphere".to_string(), 2.0, position);
+        }
+        world
+    }
+
+    #[test]
+    fn test_uniform_wind_pushes_bodies_downwind() {
+        let start = [Vec3::new(0.0, 5.0, 0.0), Vec3::new(10.0, 5.0, 0.0)];
+        let mut world = floating_world(&start);
+        let anchor = world.add_object("box".to_string(), 1.0, Vec3::new(0.0, 5.0, 10.0));
+        world.objects[anchor].is_static = true;
+        world.set_force_field(Some(ForceField::Uniform {
+            force: Vec3::new(4.0, 0.0, 0.0),
+        }));
+
+        for _ in 0..60 {
+            world.step();
+        }
+
+        for (obj, start) in world.objects.iter().zip(start) {
+            // a = F / m = 2 m/s^2 for one second
+            assert!((obj.velocity.x - 2.0).abs() < 1e-6, "{}", obj.velocity.x);
+            assert!(obj.position.x > start.x + 0.9);
+            assert_eq!(obj.position.y, start.y);
+        }
+        assert_eq!(world.objects[anchor].position, Vec3::new(0.0, 5.0, 10.0));
+
+        world.set_force_field(None);
+        world.step();
+        assert!((world.objects[0].velocity.x - 2.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_radial_attractor_pulls_towards_center() {
+        let center = Vec3::new(0.0, 10.0, 0.0);
+        let mut world = floating_world(&[Vec3::new(6.0, 10.0, 0.0), Vec3::new(0.0, 10.0, -8.0)]);
+        world.set_force_field(Some(ForceField::Radial {
+            center,
+            strength: 5.0,
+        }));
+        let initial: Vec<f64> = world
+            .objects
+            .iter()
+            .map(|obj| (obj.position - center).length())
+            .collect();
+
+        for _ in 0..60 {
+            world.step();
+        }
+
+        for (obj, initial) in world.objects.iter().zip(initial) {
+            assert!((obj.position - center).length() < initial - 0.5);
+            // Moving straight at the center
+            let towards = center - obj.position;
+            let alignment = obj.velocity.dot(&towards) / (obj.velocity.length() * towards.length());
+            assert!(alignment > 0.999, "{}", alignment);
+        }
+    }
+}
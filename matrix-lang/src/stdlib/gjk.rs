@@ -0,0 +1,305 @@
+// GJK Distance Queries for Matrix Language
+// Separation distance and closest points between two convex bodies
+
+use crate::stdlib::{PhysicsObject, PhysicsWorld, Vec3};
+
+const GJK_MAX_ITERATIONS: usize = 64;
+/// Relative progress below which the search direction has converged
+const GJK_TOLERANCE: f64 = 1e-10;
+
+/// Result of a distance query between two bodies
+#[derive(Debug, Clone, PartialEq)]
+pub struct ClosestPoints {
+    /// Gap between the surfaces; zero when the bodies overlap
+    pub distance: f64,
+    pub point_a: Vec3,
+    pub point_b: Vec3,
+}
+
+/// A vertex of the Minkowski difference together with the body points it came from
+#[derive(Debug, Clone, Copy)]
+struct SupportPoint {
+    w: Vec3,
+    a: Vec3,
+    b: Vec3,
+}
+
+impl PhysicsWorld {
+    /// Distance and closest points between two bodies, or `None` if either id is unknown
+    pub fn distance_between(&self, body_a: usize, body_b: usize) -> Option<ClosestPoints> {
+        let a = self.objects.get(body_a)?;
+        let b = self.objects.get(body_b)?;
+        Some(gjk_distance(a, b))
+    }
+}
+
+/// Spheres are handled as their centre point plus a radius, so GJK only
+/// ever works on points and boxes and the radius is added back afterwards
+fn margin(obj: &PhysicsObject) -> f64 {
+    if obj.shape == "sphere" {
+        obj.half_extents.x
+    } else {
+        0.0
+    }
+}
+
+/// Furthest point of a body's core shape in `direction`
+fn core_support(obj: &PhysicsObject, direction: Vec3) -> Vec3 {
+    if obj.shape == "sphere" {
+        return obj.position;
+    }
+    let sign = |d: f64| if d >= 0.0 { 1.0 } else { -1.0 };
+    obj.position
+        + Vec3::new(
+            sign(direction.x) * obj.half_extents.x,
+            sign(direction.y) * obj.half_extents.y,
+            sign(direction.z) * obj.half_extents.z,
+        )
+}
+
+fn support(a: &PhysicsObject, b: &PhysicsObject, direction: Vec3) -> SupportPoint {
+    let pa = core_support(a, direction);
+    let pb = core_support(b, direction * -1.0);
+    SupportPoint {
+        w: pa - pb,
+        a: pa,
+        b: pb,
+    }
+}
+
+pub fn gjk_distance(a: &PhysicsObject, b: &PhysicsObject) -> ClosestPoints {
+    let overlapping = || ClosestPoints {
+        distance: 0.0,
+        point_a: a.position,
+        point_b: b.position,
+    };
+
+    let initial = b.position - a.position;
+    let initial = if initial.length() > 0.0 {
+        initial
+    } else {
+        Vec3::new(1.0, 0.0, 0.0)
+    };
+    let mut simplex = vec![support(a, b, initial)];
+    let mut closest = (simplex[0].w, vec![1.0]);
+
+    for _ in 0..GJK_MAX_ITERATIONS {
+        let Some((point, weights)) = closest_on_simplex(&simplex) else {
+            return overlapping(); // Origin enclosed by the simplex
+        };
+
+        // Keep only the vertices that support the closest point
+        let (kept, kept_weights): (Vec<SupportPoint>, Vec<f64>) = simplex
+            .iter()
+            .zip(&weights)
+            .filter(|(_, &weight)| weight > 0.0)
+            .map(|(s, &weight)| (*s, weight))
+            .unzip();
+        simplex = kept;
+        closest = (point, kept_weights);
+
+        let distance_sq = point.dot(&point);
+        if distance_sq < GJK_TOLERANCE {
+            return overlapping();
+        }
+
+        let next = support(a, b, point * -1.0);
+        if distance_sq - point.dot(&next.w) <= GJK_TOLERANCE * distance_sq.max(1.0) {
+            break; // No vertex gets closer to the origin
+        }
+        simplex.push(next);
+    }
+
+    let (point, weights) = closest;
+    let mut point_a = Vec3::zero();
+    let mut point_b = Vec3::zero();
+    for (s, weight) in simplex.iter().zip(&weights) {
+        point_a = point_a + s.a * *weight;
+        point_b = point_b + s.b * *weight;
+    }
+
+    // Push the core points out to the sphere surfaces
+    let core_distance = point.length();
+    let direction = (point_b - point_a) * (1.0 / core_distance);
+    let distance = core_distance - margin(a) - margin(b);
+    if distance <= 0.0 {
+        return overlapping();
+    }
+    ClosestPoints {
+        distance,
+        point_a: point_a + direction * margin(a),
+        point_b: point_b - direction * margin(b),
+    }
+}
+
+/// Closest point of a simplex (1-4 vertices) to the origin, with one barycentric
+/// weight per vertex. Returns `None` when the origin lies inside a tetrahedron.
+fn closest_on_simplex(simplex: &[SupportPoint]) -> Option<(Vec3, Vec<f64>)> {
+    match simplex {
+        [p] => Some((p.w, vec![1.0])),
+        [p, q] => {
+            let (point, weights) = closest_on_segment(p.w, q.w);
+            Some((point, weights.to_vec()))
+        }
+        [p, q, r] => {
+            let (point, weights) = closest_on_triangle(p.w, q.w, r.w);
+            Some((point, weights.to_vec()))
+        }
+        [p, q, r, s] => closest_on_tetrahedron([p.w, q.w, r.w, s.w]),
+        _ => unreachable!("GJK simplex has 1 to 4 vertices"),
+    }
+}
+
+fn closest_on_segment(a: Vec3, b: Vec3) -> (Vec3, [f64; 2]) {
+    let ab = b - a;
+    let length_sq = ab.dot(&ab);
+    if length_sq == 0.0 {
+        return (a, [1.0, 0.0]);
+    }
+    let t = (a * -1.0).dot(&ab) / length_sq;
+    if t <= 0.0 {
+        (a, [1.0, 0.0])
+    } else if t >= 1.0 {
+        (b, [0.0, 1.0])
+    } else {
+        (a + ab * t, [1.0 - t, t])
+    }
+}
+
+/// Voronoi-region test of the origin against triangle `abc`
+fn closest_on_triangle(a: Vec3, b: Vec3, c: Vec3) -> (Vec3, [f64; 3]) {
+    let ab = b - a;
+    let ac = c - a;
+
+    let ap = a * -1.0;
+    let d1 = ab.dot(&ap);
+    let d2 = ac.dot(&ap);
+    if d1 <= 0.0 && d2 <= 0.0 {
+        return (a, [1.0, 0.0, 0.0]);
+    }
+
+    let bp = b * -1.0;
+    let d3 = ab.dot(&bp);
+    let d4 = ac.dot(&bp);
+    if d3 >= 0.0 && d4 <= d3 {
+        return (b, [0.0, 1.0, 0.0]);
+    }
+
+    let vc = d1 * d4 - d3 * d2;
+    if vc <= 0.0 && d1 >= 0.0 && d3 <= 0.0 {
+        let v = d1 / (d1 - d3);
+        return (a + ab * v, [1.0 - v, v, 0.0]);
+    }
+
+    let cp = c * -1.0;
+    let d5 = ab.dot(&cp);
+    let d6 = ac.dot(&cp);
+    if d6 >= 0.0 && d5 <= d6 {
+        return (c, [0.0, 0.0, 1.0]);
+    }
+
+    let vb = d5 * d2 - d1 * d6;
+    if vb <= 0.0 && d2 >= 0.0 && d6 <= 0.0 {
+        let w = d2 / (d2 - d6);
+        return (a + ac * w, [1.0 - w, 0.0, w]);
+    }
+
+    let va = d3 * d6 - d5 * d4;
+    if va <= 0.0 && (d4 - d3) >= 0.0 && (d5 - d6) >= 0.0 {
+        let w = (d4 - d3) / ((d4 - d3) + (d5 - d6));
+        return (b + (c - b) * w, [0.0, 1.0 - w, w]);
+    }
+
+    let denom = va + vb + vc;
+    if denom.abs() < f64::MIN_POSITIVE {
+        // Degenerate (collinear) triangle: fall back to the edge spanning it
+        let (point, [u, v]) = closest_on_segment(a, c);
+        return (point, [u, 0.0, v]);
+    }
+    let v = vb / denom;
+    let w = vc / denom;
+    (a + ab * v + ac * w, [1.0 - v - w, v, w])
+}
+
+fn closest_on_tetrahedron(p: [Vec3; 4]) -> Option<(Vec3, Vec<f64>)> {
+    // Each face with the index of the vertex opposite it
+    const FACES: [([usize; 3], usize); 4] = [
+        ([0, 1, 2], 3),
+        ([0, 2, 3], 1),
+        ([0, 3, 1], 2),
+        ([1, 3, 2], 0),
+    ];
+
+    let mut best: Option<(Vec3, Vec<f64>)> = None;
+    for (face, opposite) in FACES {
+        let [i, j, k] = face;
+        let normal = (p[j] - p[i]).cross(&(p[k] - p[i]));
+        let origin_side = normal.dot(&(p[i] * -1.0));
+        let opposite_side = normal.dot(&(p[opposite] - p[i]));
+        if origin_side * opposite_side > 0.0 {
+            continue; // Origin is on the inner side of this face
+        }
+
+        let (point, [u, v, w]) = closest_on_triangle(p[i], p[j], p[k]);
+        if best
+            .as_ref()
+            .is_none_or(|(b, _)| point.dot(&point) < b.dot(b))
+        {
+            let mut weights = vec![0.0; 4];
+            weights[i] = u;
+            weights[j] = v;
+            weights[k] = w;
+            best = Some((point, weights));
+        }
+    }
+    best
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_separated_spheres_distance() {
+        let mut world = PhysicsWorld::new();
+        let a = world.add_object("sphere".to_string(), 1.0, Vec3::new(0.0, 2.0, 0.0));
+        let b = world.add_object("sphere".to_string(), 1.0, Vec3::new(3.0, 6.0, 0.0));
+        world.set_size(b, Vec3::new(1.0, 1.0, 1.0));
+
+        let result = world.distance_between(a, b).unwrap();
+        // Centres are 5 apart, radii 0.5 and 1.0
+        assert!((result.distance - 3.5).abs() < 1e-9);
+        assert!((result.point_a.x - 0.3).abs() < 1e-9 && (result.point_a.y - 2.4).abs() < 1e-9);
+        assert!((result.point_b.x - 2.4).abs() < 1e-9 && (result.point_b.y - 5.2).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_box_distance_and_closest_points() {
+        let mut world = PhysicsWorld::new();
+        let a = world.add_object("box".to_string(), 1.0, Vec3::new(0.0, 0.0, 0.0));
+        let b = world.add_object("box".to_string(), 1.0, Vec3::new(3.0, 0.2, 0.0));
+        let ball = world.add_object("sphere".to_string(), 1.0, Vec3::new(0.0, 3.0, 0.0));
+
+        let boxes = world.distance_between(a, b).unwrap();
+        assert!((boxes.distance - 2.0).abs() < 1e-9);
+        assert!((boxes.point_a.x - 0.5).abs() < 1e-9);
+        assert!((boxes.point_b.x - 2.5).abs() < 1e-9);
+
+        let sphere_box = world.distance_between(ball, a).unwrap();
+        assert!((sphere_box.distance - 2.0).abs() < 1e-9);
+        assert!((sphere_box.point_b.y - 0.5).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_overlapping_bodies_report_zero_distance() {
+        let mut world = PhysicsWorld::new();
+        let a = world.add_object("sphere".to_string(), 1.0, Vec3::new(0.0, 2.0, 0.0));
+        let b = world.add_object("sphere".to_string(), 1.0, Vec3::new(0.8, 2.0, 0.0));
+        let c = world.add_object("box".to_string(), 1.0, Vec3::new(0.2, 2.3, 0.1));
+
+        assert_eq!(world.distance_between(a, b).unwrap().distance, 0.0);
+        assert_eq!(world.distance_between(a, c).unwrap().distance, 0.0);
+        assert_eq!(world.distance_between(b, c).unwrap().distance, 0.0);
+        assert!(world.distance_between(a, 7).is_none());
+    }
+}
@@ -0,0 +1,177 @@
+// Rotational Inertia for Matrix Language
+// Inertia tensors, angular momentum and torque-free rigid body rotation
+
+use crate::stdlib::linalg::{euler_to_matrix, matrix_to_euler};
+use crate::stdlib::{PhysicsObject, PhysicsWorld, Vec3};
+
+type Mat3 = [[f64; 3]; 3];
+
+impl PhysicsObject {
+    /// `I * v` with the world-space inertia tensor. Bodies without principal
+    /// moments have unit inertia per unit mass about every axis.
+    pub fn apply_inertia(&self, v: Vec3) -> Vec3 {
+        match self.inertia {
+            None => v * self.mass,
+            Some(moments) => self.rotate_by_body_diagonal(moments, v),
+        }
+    }
+
+    /// `I^-1 * v` with the world-space inertia tensor; zero for immovable bodies
+    pub fn apply_inverse_inertia(&self, v: Vec3) -> Vec3 {
+        if self.inverse_mass() == 0.0 {
+            return Vec3::zero();
+        }
+        match self.inertia {
+            None => v * self.inverse_mass(),
+            Some(moments) => {
+                let inverse = Vec3::new(1.0 / moments.x, 1.0 / moments.y, 1.0 / moments.z);
+                self.rotate_by_body_diagonal(inverse, v)
+            }
+        }
+    }
+
+    pub fn angular_momentum(&self) -> Vec3 {
+        self.apply_inertia(self.angular_velocity)
+    }
+
+    /// Set the angular velocity that gives angular momentum `momentum`
+    pub fn set_angular_momentum(&mut self, momentum: Vec3) {
+        self.angular_velocity = self.apply_inverse_inertia(momentum);
+    }
+
+    /// Advance the orientation by the angular velocity over `h` seconds. Bodies with an
+    /// inertia tensor rotate as rigid bodies, keeping their angular momentum while the
+    /// angular velocity follows the turning tensor, so asymmetric bodies precess.
+    pub(crate) fn integrate_rotation(&mut self, h: f64) {
+        if self.inertia.is_none() {
+            self.rotation = self.rotation + self.angular_velocity * h;
+            return;
+        }
+
+        let momentum = self.angular_momentum();
+        let speed = self.angular_velocity.length();
+        if speed > 0.0 {
+            let turn = axis_angle(self.angular_velocity * (1.0 / speed), speed * h);
+            self.rotation = matrix_to_euler(&mat_mul(&turn, &euler_to_matrix(self.rotation)));
+        }
+        self.set_angular_momentum(momentum);
+    }
+
+    /// `R * diag(d) * R^T * v` for the body's orientation `R`
+    fn rotate_by_body_diagonal(&self, d: Vec3, v: Vec3) -> Vec3 {
+        let r = euler_to_matrix(self.rotation);
+        let local = mat_vec_transposed(&r, v);
+        mat_vec(&r, Vec3::new(local.x * d.x, local.y * d.y, local.z * d.z))
+    }
+}
+
+impl PhysicsWorld {
+    /// Give a body principal moments of inertia about its local axes, or `None` for
+    /// the default unit inertia per unit mass. Angular momentum is kept.
+    pub fn set_inertia(&mut self, object_id: usize, moments: Option<Vec3>) -> bool {
+        match self.objects.get_mut(object_id) {
+            Some(obj) => {
+                let momentum = obj.angular_momentum();
+                obj.inertia = moments;
+                obj.set_angular_momentum(momentum);
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn set_angular_momentum(&mut self, object_id: usize, momentum: Vec3) -> bool {
+        match self.objects.get_mut(object_id) {
+            Some(obj) => {
+                obj.wake();
+                obj.set_angular_momentum(momentum);
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+/// Rotation by `angle` radians about the unit vector `axis` (Rodrigues' formula)
+fn axis_angle(axis: Vec3, angle: f64) -> Mat3 {
+    let (s, c) = angle.sin_cos();
+    let t = 1.0 - c;
+    let Vec3 { x, y, z } = axis;
+    [
+        [t * x * x + c, t * x * y - s * z, t * x * z + s * y],
+        [t * x * y + s * z, t * y * y + c, t * y * z - s * x],
+        [t * x * z - s * y, t * y * z + s * x, t * z * z + c],
+    ]
+}
+
+fn mat_mul(a: &Mat3, b: &Mat3) -> Mat3 {
+    std::array::from_fn(|i| std::array::from_fn(|j| (0..3).map(|k| a[i][k] * b[k][j]).sum()))
+}
+
+fn mat_vec(m: &Mat3, v: Vec3) -> Vec3 {
+    let row = |r: &[f64; 3]| r[0] * v.x + r[1] * v.y + r[2] * v.z;
+    Vec3::new(row(&m[0]), row(&m[1]), row(&m[2]))
+}
+
+fn mat_vec_transposed(m: &Mat3, v: Vec3) -> Vec3 {
+    let column = |j: usize| m[0][j] * v.x + m[1][j] * v.y + m[2][j] * v.z;
+    Vec3::new(column(0), column(1), column(2))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spinning_body(moments: Option<Vec3>, angular_velocity: Vec3) -> (PhysicsWorld, usize) {
+        let mut world = PhysicsWorld::new();
+        world.gravity = Vec3::zero();
+        let id = world.add_object("box".to_string(), 1.0, Vec3::new(0.0, 10.0, 0.0));
+        world.set_inertia(id, moments);
+        world.objects[id].angular_velocity = angular_velocity;
+        (world, id)
+    }
+
+    fn angle_between(a: Vec3, b: Vec3) -> f64 {
+        (a.dot(&b) / (a.length() * b.length()))
+            .clamp(-1.0, 1.0)
+            .acos()
+    }
+
+    #[test]
+    fn test_asymmetric_body_precesses_with_constant_momentum() {
+        // Spin mostly about the major axis with a small wobble
+        let (mut world, id) =
+            spinning_body(Some(Vec3::new(1.0, 2.0, 3.0)), Vec3::new(0.3, 0.0, 5.0));
+        let momentum = world.objects[id].angular_momentum();
+        let initial_axis = world.objects[id].angular_velocity;
+
+        let mut largest_tilt: f64 = 0.0;
+        for _ in 0..120 {
+            world.step();
+            let obj = &world.objects[id];
+            let drift = (obj.angular_momentum() - momentum).length();
+            assert!(
+                drift < 1e-9 * momentum.length(),
+                "momentum drifted by {}",
+                drift
+            );
+            largest_tilt = largest_tilt.max(angle_between(initial_axis, obj.angular_velocity));
+        }
+        // The spin axis wanders instead of staying fixed
+        assert!(largest_tilt > 0.05, "{}", largest_tilt);
+    }
+
+    #[test]
+    fn test_symmetric_body_spins_about_fixed_axis() {
+        let spin = Vec3::new(0.3, 0.0, 5.0);
+        let (mut world, id) = spinning_body(Some(Vec3::new(2.0, 2.0, 2.0)), spin);
+        for _ in 0..120 {
+            world.step();
+        }
+        assert!((world.objects[id].angular_velocity - spin).length() < 1e-9);
+
+        // Default bodies keep unit inertia per unit mass
+        let (world, id) = spinning_body(None, spin);
+        assert_eq!(world.objects[id].angular_momentum(), spin);
+    }
+}
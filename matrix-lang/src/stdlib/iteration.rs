@@ -0,0 +1,170 @@
+// Iteration Helpers for Matrix Language
+// Array builders for functional scripts: pairing, indexing and stepped ranges
+
+use crate::eval::interpreter::{RuntimeError, Value};
+use crate::stdlib::physics::expect_number;
+
+pub fn register_iteration_functions(interpreter: &mut crate::eval::Interpreter) {
+    // Pairs of corresponding elements, truncated to the shorter array
+    interpreter.environment.define(
+        "zip".to_string(),
+        Value::BuiltinFunction {
+            name: "zip".to_string(),
+            arity: 2,
+            func: |args| {
+                let a = expect_array(&args[0])?;
+                let b = expect_array(&args[1])?;
+                Ok(Value::Array(zip(a, b)))
+            },
+        },
+    );
+
+    // `[index, value]` for every element
+    interpreter.environment.define(
+        "enumerate".to_string(),
+        Value::BuiltinFunction {
+            name: "enumerate".to_string(),
+            arity: 1,
+            func: |args| Ok(Value::Array(enumerate(expect_array(&args[0])?))),
+        },
+    );
+
+    // Numbers from `start` up to but excluding `end`
+    interpreter.environment.define(
+        "range_array".to_string(),
+        Value::BuiltinFunction {
+            name: "range_array".to_string(),
+            arity: 3,
+            func: |args| range_array(&args[0], &args[1], &args[2]).map(Value::Array),
+        },
+    );
+}
+
+fn expect_array(value: &Value) -> Result<&[Value], RuntimeError> {
+    match value {
+        Value::Array(items) => Ok(items),
+        other => Err(RuntimeError::TypeError {
+            message: format!("Expected an array, found {}", other.type_name()),
+        }),
+    }
+}
+
+/// `[a[i], b[i]]` pairs. Extra elements of the longer array are dropped, as with
+/// iterator zipping, so the result has the length of the shorter input.
+pub fn zip(a: &[Value], b: &[Value]) -> Vec<Value> {
+    a.iter()
+        .zip(b)
+        .map(|(x, y)| Value::Array(vec![x.clone(), y.clone()]))
+        .collect()
+}
+
+pub fn enumerate(items: &[Value]) -> Vec<Value> {
+    items
+        .iter()
+        .enumerate()
+        .map(|(i, item)| Value::Array(vec![Value::Int(i as i64), item.clone()]))
+        .collect()
+}
+
+/// Half-open range `start, start + step, ...` stopping before `end`; a negative step
+/// counts down. Integers stay integers unless any argument is a float. Empty when
+/// the step points away from `end`.
+pub fn range_array(start: &Value, end: &Value, step: &Value) -> Result<Vec<Value>, RuntimeError> {
+    if let (Value::Int(start), Value::Int(end), Value::Int(step)) = (start, end, step) {
+        let (start, end, step) = (*start, *end, *step);
+        if step == 0 {
+            return Err(zero_step());
+        }
+        let mut values = Vec::new();
+        let mut current = start;
+        while (step > 0 && current < end) || (step < 0 && current > end) {
+            values.push(Value::Int(current));
+            current = match current.checked_add(step) {
+                Some(next) => next,
+                None => break,
+            };
+        }
+        return Ok(values);
+    }
+
+    let start = expect_number(start, "Range start")?;
+    let end = expect_number(end, "Range end")?;
+    let step = expect_number(step, "Range step")?;
+    if step == 0.0 {
+        return Err(zero_step());
+    }
+    if !(start.is_finite() && end.is_finite() && step.is_finite()) {
+        return Err(RuntimeError::Generic {
+            message: "Range bounds and step must be finite".to_string(),
+        });
+    }
+    // Multiply rather than accumulate so long ranges do not drift
+    let count = ((end - start) / step).ceil().max(0.0) as usize;
+    Ok((0..count)
+        .map(|i| Value::Float(start + i as f64 * step))
+        .collect())
+}
+
+fn zero_step() -> RuntimeError {
+    RuntimeError::Generic {
+        message: "Range step must not be zero".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ints(values: &[i64]) -> Vec<Value> {
+        values.iter().map(|&v| Value::Int(v)).collect()
+    }
+
+    #[test]
+    fn test_zip_truncates_to_shorter_array() {
+        let names = vec![
+            Value::String("a".to_string()),
+            Value::String("b".to_string()),
+        ];
+        assert_eq!(
+            zip(&ints(&[1, 2, 3]), &names),
+            vec![
+                Value::Array(vec![Value::Int(1), Value::String("a".to_string())]),
+                Value::Array(vec![Value::Int(2), Value::String("b".to_string())]),
+            ]
+        );
+        assert!(zip(&[], &names).is_empty());
+    }
+
+    #[test]
+    fn test_enumerate_pairs_index_and_value() {
+        assert_eq!(
+            enumerate(&ints(&[10, 20])),
+            vec![Value::Array(ints(&[0, 10])), Value::Array(ints(&[1, 20]))]
+        );
+    }
+
+    #[test]
+    fn test_range_array_steps() {
+        let range = |a, b, c| range_array(&Value::Int(a), &Value::Int(b), &Value::Int(c));
+        assert_eq!(range(0, 10, 3).unwrap(), ints(&[0, 3, 6, 9]));
+        assert_eq!(range(5, 0, -2).unwrap(), ints(&[5, 3, 1]));
+        assert!(range(0, 5, -1).unwrap().is_empty());
+
+        assert_eq!(
+            range_array(&Value::Float(0.0), &Value::Int(1), &Value::Float(0.25)).unwrap(),
+            vec![
+                Value::Float(0.0),
+                Value::Float(0.25),
+                Value::Float(0.5),
+                Value::Float(0.75),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_range_array_rejects_zero_step() {
+        let err = range_array(&Value::Int(0), &Value::Int(5), &Value::Int(0)).unwrap_err();
+        assert!(err.to_string().contains("must not be zero"));
+        assert!(range_array(&Value::Float(0.0), &Value::Int(5), &Value::Float(0.0)).is_err());
+    }
+}
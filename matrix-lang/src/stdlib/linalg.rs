@@ -0,0 +1,600 @@
+// Linear Algebra for Matrix Language
+// Eigen decomposition of symmetric matrices (Jacobi rotation method), Householder QR
+// and least squares, and Euler angle / rotation matrix conversion
+
+use crate::eval::interpreter::{RuntimeError, Value};
+use crate::stdlib::physics::{expect_number, expect_vec3, vec3_to_value};
+use crate::stdlib::Vec3;
+
+/// Off-diagonal magnitude below which the Jacobi sweep is considered converged
+const JACOBI_TOLERANCE: f64 = 1e-12;
+const JACOBI_MAX_SWEEPS: usize = 100;
+/// Allowed |a_ij - a_ji| for a matrix to count as symmetric
+const SYMMETRY_TOLERANCE: f64 = 1e-9;
+/// cos(pitch) below which Euler extraction treats the rotation as gimbal-locked
+const GIMBAL_LOCK_TOLERANCE: f64 = 1e-9;
+/// |R_ii| relative to the largest diagonal entry below which `lstsq` calls A rank-deficient
+const RANK_TOLERANCE: f64 = 1e-10;
+
+pub fn register_linalg_functions(interpreter: &mut crate::eval::Interpreter) {
+    // Eigenvalues of a symmetric matrix, ascending
+    interpreter.environment.define(
+        "eigenvalues_symmetric".to_string(),
+        Value::BuiltinFunction {
+            name: "eigenvalues_symmetric".to_string(),
+            arity: 1,
+            func: |args| {
+                let matrix = matrix_from_value(&args[0])?;
+                let (values, _) = symmetric_eigen(&matrix)?;
+                Ok(Value::Array(values.into_iter().map(Value::Float).collect()))
+            },
+        },
+    );
+
+    // Eigenvectors of a symmetric matrix as the columns of a matrix,
+    // in the same order as `eigenvalues_symmetric`
+    interpreter.environment.define(
+        "eigenvectors_symmetric".to_string(),
+        Value::BuiltinFunction {
+            name: "eigenvectors_symmetric".to_string(),
+            arity: 1,
+            func: |args| {
+                let matrix = matrix_from_value(&args[0])?;
+                let (_, vectors) = symmetric_eigen(&matrix)?;
+                Ok(matrix_to_value(vectors))
+            },
+        },
+    );
+
+    // [Q, R] with Q orthogonal and R upper triangular, Q * R = m
+    interpreter.environment.define(
+        "qr".to_string(),
+        Value::BuiltinFunction {
+            name: "qr".to_string(),
+            arity: 1,
+            func: |args| {
+                let (q, r) = qr_decompose(&matrix_from_value(&args[0])?)?;
+                Ok(Value::Array(vec![matrix_to_value(q), matrix_to_value(r)]))
+            },
+        },
+    );
+
+    // x minimizing |a * x - b| for a with at least as many rows as columns
+    interpreter.environment.define(
+        "lstsq".to_string(),
+        Value::BuiltinFunction {
+            name: "lstsq".to_string(),
+            arity: 2,
+            func: |args| {
+                let a = matrix_from_value(&args[0])?;
+                let b = match &args[1] {
+                    Value::Array(items) => items
+                        .iter()
+                        .map(|item| expect_number(item, "Right-hand side"))
+                        .collect::<Result<Vec<_>, _>>()?,
+                    other => {
+                        return Err(RuntimeError::TypeError {
+                            message: format!(
+                                "Right-hand side must be an array, found {}",
+                                other.type_name()
+                            ),
+                        })
+                    }
+                };
+                let x = least_squares(&a, &b)?;
+                Ok(Value::Array(x.into_iter().map(Value::Float).collect()))
+            },
+        },
+    );
+
+    // A rows x cols matrix with every cell set to `value`
+    interpreter.environment.define(
+        "matrix_fill".to_string(),
+        Value::BuiltinFunction {
+            name: "matrix_fill".to_string(),
+            arity: 3,
+            func: |args| {
+                let (rows, cols) = expect_dimensions(&args[0], &args[1])?;
+                Ok(Value::Matrix(vec![vec![args[2].clone(); cols]; rows]))
+            },
+        },
+    );
+
+    // 3x3 rotation matrix from [x, y, z] Euler angles in radians (see `euler_to_matrix`)
+    interpreter.environment.define(
+        "euler_to_matrix".to_string(),
+        Value::BuiltinFunction {
+            name: "euler_to_matrix".to_string(),
+            arity: 1,
+            func: |args| {
+                let euler = expect_vec3(&args[0], "Euler angles")?;
+                Ok(Value::Matrix(
+                    euler_to_matrix(euler)
+                        .iter()
+                        .map(|row| row.iter().map(|&x| Value::Float(x)).collect())
+                        .collect(),
+                ))
+            },
+        },
+    );
+
+    // [x, y, z] Euler angles from a 3x3 rotation matrix (see `matrix_to_euler`)
+    interpreter.environment.define(
+        "matrix_to_euler".to_string(),
+        Value::BuiltinFunction {
+            name: "matrix_to_euler".to_string(),
+            arity: 1,
+            func: |args| {
+                let matrix = matrix_from_value(&args[0])?;
+                if matrix.len() != 3 || matrix.iter().any(|row| row.len() != 3) {
+                    return Err(RuntimeError::Generic {
+                        message: "matrix_to_euler requires a 3x3 matrix".to_string(),
+                    });
+                }
+                let rows = [
+                    [matrix[0][0], matrix[0][1], matrix[0][2]],
+                    [matrix[1][0], matrix[1][1], matrix[1][2]],
+                    [matrix[2][0], matrix[2][1], matrix[2][2]],
+                ];
+                Ok(vec3_to_value(matrix_to_euler(&rows)))
+            },
+        },
+    );
+}
+
+/// Matrix dimensions from row and column count arguments, both at least 1
+pub(crate) fn expect_dimensions(
+    rows: &Value,
+    cols: &Value,
+) -> Result<(usize, usize), RuntimeError> {
+    let dimension = |value: &Value, what: &str| match value {
+        Value::Int(n) if *n >= 1 => Ok(*n as usize),
+        Value::Int(n) => Err(RuntimeError::Generic {
+            message: format!("Matrix {} must be at least 1, got {}", what, n),
+        }),
+        other => Err(RuntimeError::TypeError {
+            message: format!(
+                "Matrix {} must be integer, found {}",
+                what,
+                other.type_name()
+            ),
+        }),
+    };
+    Ok((dimension(rows, "rows")?, dimension(cols, "columns")?))
+}
+
+/// Convert a `Matrix` or array of numeric arrays into rows of f64
+pub(crate) fn matrix_from_value(value: &Value) -> Result<Vec<Vec<f64>>, RuntimeError> {
+    let rows: Vec<&[Value]> = match value {
+        Value::Matrix(rows) => rows.iter().map(|r| r.as_slice()).collect(),
+        Value::Array(rows) => rows
+            .iter()
+            .map(|row| match row {
+                Value::Array(r) => Ok(r.as_slice()),
+                other => Err(RuntimeError::TypeError {
+                    message: format!("Expected matrix row, found {}", other.type_name()),
+                }),
+            })
+            .collect::<Result<_, _>>()?,
+        other => {
+            return Err(RuntimeError::TypeError {
+                message: format!("Expected matrix, found {}", other.type_name()),
+            })
+        }
+    };
+
+    rows.into_iter()
+        .map(|row| {
+            row.iter()
+                .map(|v| match v {
+                    Value::Float(f) => Ok(*f),
+                    Value::Int(i) => Ok(*i as f64),
+                    other => Err(RuntimeError::TypeError {
+                        message: format!(
+                            "Matrix elements must be numbers, found {}",
+                            other.type_name()
+                        ),
+                    }),
+                })
+                .collect()
+        })
+        .collect()
+}
+
+pub(crate) fn matrix_to_value(rows: Vec<Vec<f64>>) -> Value {
+    Value::Matrix(
+        rows.into_iter()
+            .map(|row| row.into_iter().map(Value::Float).collect())
+            .collect(),
+    )
+}
+
+/// Jacobi eigenvalue algorithm for a real symmetric matrix.
+/// Returns eigenvalues in ascending order and a matrix whose columns are the
+/// matching unit eigenvectors.
+pub fn symmetric_eigen(matrix: &[Vec<f64>]) -> Result<(Vec<f64>, Vec<Vec<f64>>), RuntimeError> {
+    let n = matrix.len();
+    if n == 0 || matrix.iter().any(|row| row.len() != n) {
+        return Err(RuntimeError::Generic {
+            message: "Eigen decomposition requires a non-empty square matrix".to_string(),
+        });
+    }
+    let asymmetric = (0..n)
+        .any(|i| ((i + 1)..n).any(|j| (matrix[i][j] - matrix[j][i]).abs() > SYMMETRY_TOLERANCE));
+    if asymmetric {
+        return Err(RuntimeError::Generic {
+            message: "Matrix is not symmetric".to_string(),
+        });
+    }
+
+    let mut a: Vec<Vec<f64>> = matrix.to_vec();
+    let mut v: Vec<Vec<f64>> = (0..n)
+        .map(|i| (0..n).map(|j| if i == j { 1.0 } else { 0.0 }).collect())
+        .collect();
+
+    for _ in 0..JACOBI_MAX_SWEEPS {
+        let off_diagonal: f64 = (0..n)
+            .flat_map(|i| (0..n).filter(move |&j| j != i).map(move |j| (i, j)))
+            .map(|(i, j)| a[i][j] * a[i][j])
+            .sum();
+        if off_diagonal.sqrt() < JACOBI_TOLERANCE {
+            break;
+        }
+
+        for p in 0..n {
+            for q in (p + 1)..n {
+                if a[p][q].abs() < f64::MIN_POSITIVE {
+                    continue;
+                }
+
+                // Rotation angle that zeroes a[p][q]
+                let theta = (a[q][q] - a[p][p]) / (2.0 * a[p][q]);
+                let t = theta.signum() / (theta.abs() + (theta * theta + 1.0).sqrt());
+                let c = 1.0 / (t * t + 1.0).sqrt();
+                let s = t * c;
+
+                for row in a.iter_mut() {
+                    let (akp, akq) = (row[p], row[q]);
+                    row[p] = c * akp - s * akq;
+                    row[q] = s * akp + c * akq;
+                }
+                let (upper, lower) = a.split_at_mut(q);
+                for (apk, aqk) in upper[p].iter_mut().zip(lower[0].iter_mut()) {
+                    let (x, y) = (*apk, *aqk);
+                    *apk = c * x - s * y;
+                    *aqk = s * x + c * y;
+                }
+                for row in v.iter_mut() {
+                    let (vkp, vkq) = (row[p], row[q]);
+                    row[p] = c * vkp - s * vkq;
+                    row[q] = s * vkp + c * vkq;
+                }
+            }
+        }
+    }
+
+    let mut order: Vec<usize> = (0..n).collect();
+    order.sort_by(|&i, &j| a[i][i].total_cmp(&a[j][j]));
+
+    let values = order.iter().map(|&i| a[i][i]).collect();
+    let vectors = v
+        .iter()
+        .map(|row| order.iter().map(|&i| row[i]).collect())
+        .collect();
+    Ok((values, vectors))
+}
+
+/// `(Q, R)` factors of a QR decomposition
+pub type QrFactors = (Vec<Vec<f64>>, Vec<Vec<f64>>);
+
+/// Householder QR of an m x n matrix: `Q` is m x m and orthogonal, `R` is m x n
+/// and upper triangular, and `Q * R` reproduces the input.
+pub fn qr_decompose(matrix: &[Vec<f64>]) -> Result<QrFactors, RuntimeError> {
+    let m = matrix.len();
+    let n = matrix.first().map_or(0, Vec::len);
+    if n == 0 || matrix.iter().any(|row| row.len() != n) {
+        return Err(RuntimeError::Generic {
+            message: "QR decomposition requires a non-empty rectangular matrix".to_string(),
+        });
+    }
+
+    let mut r = matrix.to_vec();
+    let mut q: Vec<Vec<f64>> = (0..m)
+        .map(|i| (0..m).map(|j| if i == j { 1.0 } else { 0.0 }).collect())
+        .collect();
+
+    for k in 0..n.min(m - 1) {
+        // Reflect column k onto the axis, choosing the sign that avoids cancellation
+        let norm = (k..m).map(|i| r[i][k] * r[i][k]).sum::<f64>().sqrt();
+        if norm == 0.0 {
+            continue;
+        }
+        let alpha = if r[k][k] > 0.0 { -norm } else { norm };
+        let mut v: Vec<f64> = (k..m).map(|i| r[i][k]).collect();
+        v[0] -= alpha;
+        let v_norm_sq: f64 = v.iter().map(|x| x * x).sum();
+
+        // R <- H * R and Q <- Q * H with H = I - 2 v v^T / (v^T v)
+        for j in 0..n {
+            let dot: f64 = v.iter().zip(&r[k..]).map(|(vi, row)| vi * row[j]).sum();
+            let scale = 2.0 * dot / v_norm_sq;
+            for (vi, row) in v.iter().zip(&mut r[k..]) {
+                row[j] -= scale * vi;
+            }
+        }
+        for row in q.iter_mut() {
+            let dot: f64 = v.iter().zip(&row[k..]).map(|(vi, x)| vi * x).sum();
+            let scale = 2.0 * dot / v_norm_sq;
+            for (vi, x) in v.iter().zip(&mut row[k..]) {
+                *x -= scale * vi;
+            }
+        }
+        for row in &mut r[k + 1..] {
+            row[k] = 0.0;
+        }
+    }
+
+    Ok((q, r))
+}
+
+/// Least-squares solution of `a * x = b` via QR, for `a` with at least as many rows as
+/// columns. Errors if `a` does not have full column rank.
+pub fn least_squares(a: &[Vec<f64>], b: &[f64]) -> Result<Vec<f64>, RuntimeError> {
+    let (m, n) = (a.len(), a.first().map_or(0, Vec::len));
+    if b.len() != m {
+        return Err(RuntimeError::Generic {
+            message: format!(
+                "Right-hand side has {} entries but the matrix has {} rows",
+                b.len(),
+                m
+            ),
+        });
+    }
+    if m < n {
+        return Err(RuntimeError::Generic {
+            message: format!(
+                "Least squares needs at least as many rows as columns, got {}x{}",
+                m, n
+            ),
+        });
+    }
+
+    let (q, r) = qr_decompose(a)?;
+    let largest = (0..n).map(|i| r[i][i].abs()).fold(0.0, f64::max);
+    if (0..n).any(|i| r[i][i].abs() <= RANK_TOLERANCE * largest) || largest == 0.0 {
+        return Err(RuntimeError::Generic {
+            message: "Matrix is rank-deficient".to_string(),
+        });
+    }
+
+    // Solve R x = Q^T b over the first n rows by back substitution
+    let qtb: Vec<f64> = (0..n)
+        .map(|j| (0..m).map(|i| q[i][j] * b[i]).sum())
+        .collect();
+    let mut x = vec![0.0; n];
+    for i in (0..n).rev() {
+        let known: f64 = ((i + 1)..n).map(|j| r[i][j] * x[j]).sum();
+        x[i] = (qtb[i] - known) / r[i][i];
+    }
+    Ok(x)
+}
+
+/// Rotation matrix for Euler angles in XYZ order: rotate about the fixed X axis,
+/// then Y, then Z, so `R = Rz(z) * Ry(y) * Rx(x)`.
+pub fn euler_to_matrix(euler: Vec3) -> [[f64; 3]; 3] {
+    let (sx, cx) = euler.x.sin_cos();
+    let (sy, cy) = euler.y.sin_cos();
+    let (sz, cz) = euler.z.sin_cos();
+
+    [
+        [cy * cz, sx * sy * cz - cx * sz, cx * sy * cz + sx * sz],
+        [cy * sz, sx * sy * sz + cx * cz, cx * sy * sz - sx * cz],
+        [-sy, sx * cy, cx * cy],
+    ]
+}
+
+/// Inverse of `euler_to_matrix`, with `y` in [-pi/2, pi/2] and `x`, `z` in [-pi, pi].
+/// At gimbal lock (`y` = +-pi/2) only `x + z` or `x - z` is defined, so `z` is set to 0.
+/// Non-rotation matrices are not rejected: the same entries are read as if the matrix
+/// were a rotation, giving a best-effort result.
+pub fn matrix_to_euler(m: &[[f64; 3]; 3]) -> Vec3 {
+    let y = (-m[2][0]).clamp(-1.0, 1.0).asin();
+
+    if y.cos() > GIMBAL_LOCK_TOLERANCE {
+        Vec3::new(m[2][1].atan2(m[2][2]), y, m[1][0].atan2(m[0][0]))
+    } else {
+        Vec3::new((-m[1][2]).atan2(m[1][1]), y, 0.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn column(vectors: &[Vec<f64>], j: usize) -> Vec<f64> {
+        vectors.iter().map(|row| row[j]).collect()
+    }
+
+    fn assert_eigenpairs(matrix: &[Vec<f64>], values: &[f64], vectors: &[Vec<f64>]) {
+        let n = matrix.len();
+        for (j, &lambda) in values.iter().enumerate() {
+            let v = column(vectors, j);
+            for (row, vi) in matrix.iter().zip(&v) {
+                let av: f64 = row.iter().zip(&v).map(|(m, x)| m * x).sum();
+                assert!((av - lambda * vi).abs() < 1e-9);
+            }
+        }
+        for a in 0..n {
+            for b in 0..n {
+                let dot: f64 = column(vectors, a)
+                    .iter()
+                    .zip(column(vectors, b))
+                    .map(|(x, y)| x * y)
+                    .sum();
+                let expected = if a == b { 1.0 } else { 0.0 };
+                assert!((dot - expected).abs() < 1e-9);
+            }
+        }
+    }
+
+    #[test]
+    fn test_symmetric_2x2_eigen() {
+        let matrix = vec![vec![2.0, 1.0], vec![1.0, 2.0]];
+        let (values, vectors) = symmetric_eigen(&matrix).unwrap();
+
+        assert!((values[0] - 1.0).abs() < 1e-9);
+        assert!((values[1] - 3.0).abs() < 1e-9);
+        assert_eigenpairs(&matrix, &values, &vectors);
+    }
+
+    #[test]
+    fn test_symmetric_3x3_eigen() {
+        let matrix = vec![
+            vec![2.0, -1.0, 0.0],
+            vec![-1.0, 2.0, -1.0],
+            vec![0.0, -1.0, 2.0],
+        ];
+        let (values, vectors) = symmetric_eigen(&matrix).unwrap();
+
+        let sqrt2 = 2.0_f64.sqrt();
+        let expected = [2.0 - sqrt2, 2.0, 2.0 + sqrt2];
+        for (value, expected) in values.iter().zip(expected) {
+            assert!((value - expected).abs() < 1e-9);
+        }
+        assert_eigenpairs(&matrix, &values, &vectors);
+    }
+
+    #[test]
+    fn test_rejects_non_symmetric_and_non_square() {
+        assert!(symmetric_eigen(&[vec![1.0, 2.0], vec![0.0, 1.0]]).is_err());
+        assert!(symmetric_eigen(&[vec![1.0, 2.0, 3.0], vec![2.0, 1.0, 0.0]]).is_err());
+    }
+
+    #[test]
+    fn test_matrix_value_conversion() {
+        let value = Value::Matrix(vec![
+            vec![Value::Int(4), Value::Float(0.0)],
+            vec![Value::Float(0.0), Value::Int(9)],
+        ]);
+        let matrix = matrix_from_value(&value).unwrap();
+        assert_eq!(matrix, vec![vec![4.0, 0.0], vec![0.0, 9.0]]);
+        assert!(matrix_from_value(&Value::Int(1)).is_err());
+    }
+
+    fn multiply(a: &[Vec<f64>], b: &[Vec<f64>]) -> Vec<Vec<f64>> {
+        a.iter()
+            .map(|row| {
+                (0..b[0].len())
+                    .map(|j| row.iter().zip(b).map(|(x, b_row)| x * b_row[j]).sum())
+                    .collect()
+            })
+            .collect()
+    }
+
+    fn transpose(a: &[Vec<f64>]) -> Vec<Vec<f64>> {
+        (0..a[0].len())
+            .map(|j| a.iter().map(|row| row[j]).collect())
+            .collect()
+    }
+
+    fn assert_close(a: &[Vec<f64>], b: &[Vec<f64>]) {
+        for (row_a, row_b) in a.iter().zip(b) {
+            for (x, y) in row_a.iter().zip(row_b) {
+                assert!((x - y).abs() < 1e-10, "{:?} != {:?}", a, b);
+            }
+        }
+    }
+
+    #[test]
+    fn test_qr_reconstructs_matrix() {
+        let a = vec![
+            vec![12.0, -51.0, 4.0],
+            vec![6.0, 167.0, -68.0],
+            vec![-4.0, 24.0, -41.0],
+            vec![1.0, 2.0, 3.0],
+        ];
+        let (q, r) = qr_decompose(&a).unwrap();
+        assert_eq!((q.len(), q[0].len(), r.len(), r[0].len()), (4, 4, 4, 3));
+        assert_close(&multiply(&q, &r), &a);
+        for (i, row) in r.iter().enumerate() {
+            assert!(row[..i.min(3)].iter().all(|&x| x == 0.0));
+        }
+    }
+
+    #[test]
+    fn test_qr_q_is_orthogonal() {
+        let a = vec![
+            vec![2.0, -1.0, 0.0],
+            vec![-1.0, 2.0, -1.0],
+            vec![0.0, -1.0, 2.0],
+        ];
+        let (q, _) = qr_decompose(&a).unwrap();
+        let identity: Vec<Vec<f64>> = (0..3)
+            .map(|i| (0..3).map(|j| if i == j { 1.0 } else { 0.0 }).collect())
+            .collect();
+        assert_close(&multiply(&transpose(&q), &q), &identity);
+    }
+
+    #[test]
+    fn test_lstsq_fits_noisy_line() {
+        // y = 2x + 1 with alternating noise of +-0.05
+        let xs: Vec<f64> = (0..20).map(|i| i as f64 * 0.5).collect();
+        let a: Vec<Vec<f64>> = xs.iter().map(|&x| vec![x, 1.0]).collect();
+        let b: Vec<f64> = xs
+            .iter()
+            .enumerate()
+            .map(|(i, &x)| 2.0 * x + 1.0 + if i % 2 == 0 { 0.05 } else { -0.05 })
+            .collect();
+
+        let fit = least_squares(&a, &b).unwrap();
+        assert!((fit[0] - 2.0).abs() < 0.01, "slope {}", fit[0]);
+        assert!((fit[1] - 1.0).abs() < 0.05, "intercept {}", fit[1]);
+
+        // Duplicate columns leave the fit undetermined
+        let rank_deficient: Vec<Vec<f64>> = xs.iter().map(|&x| vec![x, 2.0 * x]).collect();
+        let err = least_squares(&rank_deficient, &b).unwrap_err();
+        assert!(err.to_string().contains("rank-deficient"));
+    }
+
+    #[test]
+    fn test_euler_round_trip() {
+        let triples = [
+            Vec3::new(0.0, 0.0, 0.0),
+            Vec3::new(0.3, -0.2, 1.1),
+            Vec3::new(-2.5, 1.2, -0.7),
+            Vec3::new(3.0, -1.5, 2.9),
+        ];
+
+        for euler in triples {
+            let recovered = matrix_to_euler(&euler_to_matrix(euler));
+            assert!((recovered - euler).length() < 1e-9, "{:?}", recovered);
+        }
+    }
+
+    #[test]
+    fn test_quarter_turn_about_z() {
+        let matrix = euler_to_matrix(Vec3::new(0.0, 0.0, std::f64::consts::FRAC_PI_2));
+        let expected = [[0.0, -1.0, 0.0], [1.0, 0.0, 0.0], [0.0, 0.0, 1.0]];
+        for (row, expected_row) in matrix.iter().zip(expected) {
+            for (value, want) in row.iter().zip(expected_row) {
+                assert!((value - want).abs() < 1e-12);
+            }
+        }
+
+        let euler = matrix_to_euler(&expected);
+        assert!((euler.z - std::f64::consts::FRAC_PI_2).abs() < 1e-12);
+        assert!(euler.x.abs() < 1e-12 && euler.y.abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_gimbal_lock_and_non_rotation_input() {
+        let locked = euler_to_matrix(Vec3::new(0.4, std::f64::consts::FRAC_PI_2, 0.0));
+        let euler = matrix_to_euler(&locked);
+        assert!((euler.x - 0.4).abs() < 1e-9 && euler.z == 0.0);
+
+        // A scaled matrix still yields finite, best-effort angles
+        let scaled = [[2.0, 0.0, 0.0], [0.0, 2.0, 0.0], [0.0, 0.0, 2.0]];
+        let euler = matrix_to_euler(&scaled);
+        assert!(euler.length() < 1e-12);
+    }
+}
@@ -1,24 +1,131 @@
 // Standard library implementation for Matrix Language
 // Focus: Physics simulation and mathematical functions
-use crate::eval::interpreter::{RuntimeError, Value};
-use std::collections::HashMap;
+use crate::eval::interpreter::{RuntimeError, Value, VARIADIC};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::sync::{LazyLock, Mutex};
 
+/// Pivot magnitude, relative to the largest entry, below which `inverse` calls a
+/// matrix singular
+const SINGULAR_TOLERANCE: f64 = 1e-12;
+
+pub mod assertions;
+pub mod broadphase;
+pub mod collisions;
+pub mod constraints;
+pub mod csv;
+pub mod debug_draw;
+pub mod dict;
+pub mod events;
+pub mod fluids;
+pub mod force_fields;
+pub mod gjk;
+pub mod inertia;
+pub mod iteration;
+pub mod linalg;
+pub mod noise;
+pub mod option;
+pub mod particles;
 pub mod physics;
+pub mod polynomial;
+pub mod prelude;
 pub mod quantum;
+pub mod random;
+pub mod sensors;
+pub mod settings;
+pub mod signal;
+pub mod state_hash;
+pub mod stats;
+pub mod trajectory;
+pub mod vector;
+
+pub use broadphase::SpatialHash;
+pub use collisions::{CollisionEvent, ContactPoint, Manifold};
+pub use constraints::{Cloth, DistanceConstraint, HingeConstraint, Rope};
+pub use debug_draw::{DebugDrawOptions, DebugShape};
+pub use events::{StepObserver, StepObservers, SubscriptionId};
+pub use fluids::FluidVolume;
+pub use force_fields::ForceField;
+pub use gjk::ClosestPoints;
+pub use particles::{Particle, ParticleSystem};
+pub use sensors::{RayHit, Sensor};
+pub use settings::{IntegrationMethod, PhysicsSettings, QualityPreset};
+pub use stats::PhysicsStats;
+pub use trajectory::{Playback, Trajectory, TrajectorySample};
 
 // Physics engine integration
 static PHYSICS_WORLDS: LazyLock<Mutex<HashMap<usize, PhysicsWorld>>> =
     LazyLock::new(|| Mutex::new(HashMap::new()));
 static NEXT_WORLD_ID: LazyLock<Mutex<usize>> = LazyLock::new(|| Mutex::new(0));
+/// Builtin names and arities recorded by `register_all`, for `list_builtins`/`builtin_arity`
+static BUILTIN_REGISTRY: LazyLock<Mutex<BTreeMap<String, usize>>> =
+    LazyLock::new(|| Mutex::new(BTreeMap::new()));
+/// Summary of a script-created world for status displays
+#[derive(Debug, Clone, PartialEq)]
+pub struct WorldStatus {
+    pub id: usize,
+    pub sim_time: f64,
+    pub bodies: usize,
+    pub constraints: usize,
+    pub stats: PhysicsStats,
+}
+
+/// Status of every world created from scripts, ordered by id
+pub fn world_statuses() -> Vec<WorldStatus> {
+    let worlds = PHYSICS_WORLDS.lock().unwrap();
+    let mut statuses: Vec<WorldStatus> = worlds
+        .values()
+        .map(|world| WorldStatus {
+            id: world.id,
+            sim_time: world.time,
+            bodies: world.objects.len(),
+            constraints: world.constraints.len() + world.hinges.len() + world.ropes.len(),
+            stats: world.stats(),
+        })
+        .collect();
+    statuses.sort_by_key(|status| status.id);
+    statuses
+}
 
 #[derive(Debug, Clone)]
 pub struct PhysicsWorld {
     pub id: usize,
     pub objects: Vec<PhysicsObject>,
     pub gravity: Vec3,
+    /// When false the simulation ignores `gravity`, which is kept for re-enabling
+    pub gravity_enabled: bool,
+    /// Simulated seconds accumulated by `step`
     pub time: f64,
     pub dt: f64,
+    /// Constrain all motion to the XY plane and rotation to the Z axis
+    pub is_2d: bool,
+    pub constraints: Vec<DistanceConstraint>,
+    pub hinges: Vec<HingeConstraint>,
+    pub ropes: Vec<Rope>,
+    pub cloths: Vec<Cloth>,
+    pub settings: PhysicsSettings,
+    /// Scheme used to advance positions; semi-implicit Euler unless changed
+    pub integrator: IntegrationMethod,
+    pub sensors: Vec<Sensor>,
+    /// Contacts found during the most recent step
+    pub collision_events: Vec<CollisionEvent>,
+    /// Accumulated normal impulse per touching pair, for warm starting
+    pub contact_impulses: HashMap<(usize, usize), f64>,
+    pub particle_systems: Vec<ParticleSystem>,
+    pub fluid_volumes: Vec<FluidVolume>,
+    /// Script values attached to bodies with `set_user_data`, by body id
+    pub user_data: HashMap<usize, Value>,
+    /// Subscribers notified around each step; see `subscribe`
+    pub step_observers: StepObservers,
+    /// Counters from the most recent step; see `stats`
+    pub stats: PhysicsStats,
+    /// Body id pairs (lower id first) a collision filter rejected for the next step
+    pub filtered_pairs: HashSet<(usize, usize)>,
+    /// Environmental force applied to movable bodies each step
+    pub force_field: Option<ForceField>,
+    /// Positions sampled after each step, by body id; see `start_recording`
+    pub recordings: HashMap<usize, Vec<TrajectorySample>>,
+    /// Trajectories being replayed onto kinematic bodies
+    pub playbacks: Vec<Playback>,
 }
 
 #[derive(Debug, Clone)]
@@ -29,15 +136,213 @@ pub struct PhysicsObject {
     pub position: Vec3,
     pub velocity: Vec3,
     pub is_static: bool,
+    pub rotation: Vec3, // Euler angles in radians
+    pub angular_velocity: Vec3,
+    pub force: Vec3,            // Accumulated force, cleared after each step
+    pub torque: Vec3,           // Accumulated torque, cleared after each step
+    pub half_extents: Vec3,     // Box half-size; `x` is the radius for spheres
+    pub is_active: bool,        // Inactive bodies are frozen in place but still collide
+    pub is_sleeping: bool,      // Resting bodies skip integration until disturbed
+    pub sleep_timer: f64,       // Seconds spent below the sleep velocity threshold
+    pub max_linear_speed: f64,  // Linear speed cap; 0 means unlimited
+    pub max_angular_speed: f64, // Angular speed cap; 0 means unlimited
+    pub one_way: Option<Vec3>,  // Unit normal of a one-way platform's solid side
+    pub dof_lock: DofLock,      // Axes the body may not move along or rotate about
+    pub inertia: Option<Vec3>,  // Principal moments about the local axes; unit per mass if None
+    pub material: Material,     // Surface response in contacts
 }
 
-#[derive(Debug, Clone)]
+/// Initial state for a body added with `PhysicsWorld::add_objects`
+#[derive(Debug, Clone, PartialEq)]
+pub struct BodyDescriptor {
+    pub shape: String,
+    pub mass: f64,
+    pub position: Vec3,
+    pub velocity: Vec3,
+    /// Collision half-extents; the default size when `None`
+    pub half_extents: Option<Vec3>,
+}
+
+/// How a body's surface responds in contacts
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Material {
+    /// Fraction of the approach speed kept when bouncing off, 0 to 1
+    pub restitution: f64,
+    /// Coulomb friction coefficient; 0 slides freely
+    pub friction: f64,
+}
+
+impl Material {
+    /// Material of a contact between two bodies: the bouncier restitution and the
+    /// geometric mean of the frictions, so a frictionless body slides on anything
+    pub fn combine(&self, other: &Material) -> Material {
+        Material {
+            restitution: self.restitution.max(other.restitution),
+            friction: (self.friction * other.friction).sqrt(),
+        }
+    }
+}
+
+impl Default for Material {
+    fn default() -> Self {
+        Self {
+            restitution: 0.8,
+            friction: 0.0,
+        }
+    }
+}
+
+/// Per-axis motion locks, `[x, y, z]`; locked velocity components are held at zero
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct DofLock {
+    pub linear: [bool; 3],
+    pub angular: [bool; 3],
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub struct Vec3 {
     pub x: f64,
     pub y: f64,
     pub z: f64,
 }
 
+impl Vec3 {
+    pub fn new(x: f64, y: f64, z: f64) -> Self {
+        Self { x, y, z }
+    }
+
+    pub fn zero() -> Self {
+        Self::new(0.0, 0.0, 0.0)
+    }
+
+    pub fn dot(&self, other: &Vec3) -> f64 {
+        self.x * other.x + self.y * other.y + self.z * other.z
+    }
+
+    pub fn cross(&self, other: &Vec3) -> Vec3 {
+        Vec3::new(
+            self.y * other.z - self.z * other.y,
+            self.z * other.x - self.x * other.z,
+            self.x * other.y - self.y * other.x,
+        )
+    }
+
+    pub fn length(&self) -> f64 {
+        self.dot(self).sqrt()
+    }
+}
+
+impl std::ops::Add for Vec3 {
+    type Output = Vec3;
+    fn add(self, other: Vec3) -> Vec3 {
+        Vec3::new(self.x + other.x, self.y + other.y, self.z + other.z)
+    }
+}
+
+impl std::ops::Sub for Vec3 {
+    type Output = Vec3;
+    fn sub(self, other: Vec3) -> Vec3 {
+        Vec3::new(self.x - other.x, self.y - other.y, self.z - other.z)
+    }
+}
+
+impl std::ops::Mul<f64> for Vec3 {
+    type Output = Vec3;
+    fn mul(self, scalar: f64) -> Vec3 {
+        Vec3::new(self.x * scalar, self.y * scalar, self.z * scalar)
+    }
+}
+
+impl PhysicsObject {
+    pub fn new(id: usize, shape: String, mass: f64, position: Vec3) -> Self {
+        Self {
+            id,
+            shape,
+            mass,
+            position,
+            velocity: Vec3::zero(),
+            is_static: mass == 0.0,
+            rotation: Vec3::zero(),
+            angular_velocity: Vec3::zero(),
+            force: Vec3::zero(),
+            torque: Vec3::zero(),
+            half_extents: Vec3::new(0.5, 0.5, 0.5),
+            is_active: true,
+            is_sleeping: false,
+            sleep_timer: 0.0,
+            max_linear_speed: 0.0,
+            max_angular_speed: 0.0,
+            one_way: None,
+            dof_lock: DofLock::default(),
+            inertia: None,
+            material: Material::default(),
+        }
+    }
+
+    /// Scale velocities down to the configured speed limits
+    pub fn clamp_velocities(&mut self) {
+        if self.max_linear_speed > 0.0 {
+            self.velocity = vector::clamp_magnitude(self.velocity, self.max_linear_speed);
+        }
+        if self.max_angular_speed > 0.0 {
+            self.angular_velocity =
+                vector::clamp_magnitude(self.angular_velocity, self.max_angular_speed);
+        }
+    }
+
+    /// Zero the velocity components frozen by `dof_lock`
+    pub fn apply_dof_lock(&mut self) {
+        let lock = self.dof_lock;
+        for (locked, component) in lock.linear.iter().zip([
+            &mut self.velocity.x,
+            &mut self.velocity.y,
+            &mut self.velocity.z,
+        ]) {
+            if *locked {
+                *component = 0.0;
+            }
+        }
+        for (locked, component) in lock.angular.iter().zip([
+            &mut self.angular_velocity.x,
+            &mut self.angular_velocity.y,
+            &mut self.angular_velocity.z,
+        ]) {
+            if *locked {
+                *component = 0.0;
+            }
+        }
+    }
+
+    pub fn wake(&mut self) {
+        self.is_sleeping = false;
+        self.sleep_timer = 0.0;
+    }
+
+    /// Axis-aligned bounds as (min, max); rotation is ignored like in collision tests
+    pub fn aabb(&self) -> (Vec3, Vec3) {
+        let extents = if self.shape == "sphere" {
+            let r = self.half_extents.x;
+            Vec3::new(r, r, r)
+        } else {
+            self.half_extents
+        };
+        (self.position - extents, self.position + extents)
+    }
+
+    /// Static or frozen bodies are never moved by the simulation
+    pub fn is_immovable(&self) -> bool {
+        self.is_static || !self.is_active
+    }
+
+    pub fn inverse_mass(&self) -> f64 {
+        if self.is_immovable() || self.mass <= 0.0 {
+            0.0
+        } else {
+            1.0 / self.mass
+        }
+    }
+}
+
 impl Default for PhysicsWorld {
     fn default() -> Self {
         Self::new()
@@ -58,57 +363,530 @@ impl PhysicsWorld {
                 y: -9.81,
                 z: 0.0,
             },
+            gravity_enabled: true,
             time: 0.0,
             dt: 1.0 / 60.0, // 60 FPS
+            is_2d: false,
+            constraints: Vec::new(),
+            hinges: Vec::new(),
+            ropes: Vec::new(),
+            cloths: Vec::new(),
+            settings: PhysicsSettings::default(),
+            integrator: IntegrationMethod::default(),
+            sensors: Vec::new(),
+            collision_events: Vec::new(),
+            contact_impulses: HashMap::new(),
+            particle_systems: Vec::new(),
+            fluid_volumes: Vec::new(),
+            user_data: HashMap::new(),
+            step_observers: StepObservers::default(),
+            stats: PhysicsStats::default(),
+            filtered_pairs: HashSet::new(),
+            force_field: None,
+            recordings: HashMap::new(),
+            playbacks: Vec::new(),
+        }
+    }
+
+    /// Create a world whose bodies move in the XY plane and rotate only about Z
+    pub fn new_2d() -> Self {
+        Self {
+            is_2d: true,
+            ..Self::new()
         }
     }
 
     pub fn add_object(&mut self, shape: String, mass: f64, position: Vec3) -> usize {
         let id = self.objects.len();
-        self.objects.push(PhysicsObject {
-            id,
-            shape,
-            mass,
-            position,
-            velocity: Vec3 {
-                x: 0.0,
-                y: 0.0,
-                z: 0.0,
-            },
-            is_static: mass == 0.0,
-        });
+        let mut position = position;
+        if self.is_2d {
+            position.z = 0.0;
+        }
+        self.objects
+            .push(PhysicsObject::new(id, shape, mass, position));
         id
     }
 
-    pub fn step(&mut self) {
-        // Simple physics integration
+    /// Add many bodies at once, returning their ids in order
+    pub fn add_objects(&mut self, descriptors: Vec<BodyDescriptor>) -> Vec<usize> {
+        self.objects.reserve(descriptors.len());
+        descriptors
+            .into_iter()
+            .map(|descriptor| {
+                let id = self.add_object(descriptor.shape, descriptor.mass, descriptor.position);
+                let obj = &mut self.objects[id];
+                obj.velocity = descriptor.velocity;
+                if self.is_2d {
+                    obj.velocity.z = 0.0;
+                }
+                if let Some(half_extents) = descriptor.half_extents {
+                    obj.half_extents = half_extents;
+                }
+                id
+            })
+            .collect()
+    }
+
+    /// Copy a body's shape, mass, size, orientation and limits to a new body at `position`,
+    /// at rest. Constraints and ropes attached to the original are not copied.
+    pub fn duplicate_object(&mut self, object_id: usize, position: Vec3) -> Option<usize> {
+        let mut copy = self.objects.get(object_id)?.clone();
+        copy.id = self.objects.len();
+        copy.position = position;
+        if self.is_2d {
+            copy.position.z = 0.0;
+        }
+        copy.velocity = Vec3::zero();
+        copy.angular_velocity = Vec3::zero();
+        copy.force = Vec3::zero();
+        copy.torque = Vec3::zero();
+        copy.wake();
+
+        let id = copy.id;
+        self.objects.push(copy);
+        Some(id)
+    }
+
+    /// Accumulate a force on an object for the next step
+    pub fn apply_force(&mut self, object_id: usize, force: Vec3) -> bool {
+        match self.objects.get_mut(object_id) {
+            Some(obj) if !obj.is_active => true, // Frozen bodies don't accumulate forces
+            Some(obj) => {
+                obj.wake();
+                obj.force.x += force.x;
+                obj.force.y += force.y;
+                obj.force.z += force.z;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Accumulate a torque on an object for the next step
+    pub fn apply_torque(&mut self, object_id: usize, torque: Vec3) -> bool {
+        match self.objects.get_mut(object_id) {
+            Some(obj) if !obj.is_active => true, // Frozen bodies don't accumulate forces
+            Some(obj) => {
+                obj.wake();
+                obj.torque.x += torque.x;
+                obj.torque.y += torque.y;
+                obj.torque.z += torque.z;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Change an object's velocity immediately by `impulse / mass`.
+    /// Static and frozen bodies are unaffected.
+    pub fn apply_impulse(&mut self, object_id: usize, impulse: Vec3) -> bool {
+        let is_2d = self.is_2d;
+        match self.objects.get_mut(object_id) {
+            Some(obj) => {
+                let inv_mass = obj.inverse_mass();
+                if inv_mass > 0.0 {
+                    obj.wake();
+                    obj.velocity = obj.velocity + impulse * inv_mass;
+                    if is_2d {
+                        obj.velocity.z = 0.0;
+                    }
+                }
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Change an object's angular velocity immediately, with the same inertia that
+    /// `step` uses for torques
+    pub fn apply_angular_impulse(&mut self, object_id: usize, impulse: Vec3) -> bool {
+        let is_2d = self.is_2d;
+        match self.objects.get_mut(object_id) {
+            Some(obj) => {
+                let inv_mass = obj.inverse_mass();
+                if inv_mass > 0.0 {
+                    obj.wake();
+                    obj.angular_velocity =
+                        obj.angular_velocity + obj.apply_inverse_inertia(impulse);
+                    if is_2d {
+                        obj.angular_velocity.x = 0.0;
+                        obj.angular_velocity.y = 0.0;
+                    }
+                }
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Resize an object's collision extents
+    pub fn set_size(&mut self, object_id: usize, half_extents: Vec3) -> bool {
+        match self.objects.get_mut(object_id) {
+            Some(obj) => {
+                obj.half_extents = half_extents;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Freeze (`false`) or resume (`true`) a single body while the world keeps running.
+    /// A frozen body keeps its velocity for when it resumes.
+    pub fn set_active(&mut self, object_id: usize, active: bool) -> bool {
+        match self.objects.get_mut(object_id) {
+            Some(obj) => {
+                obj.is_active = active;
+                obj.wake();
+                obj.force = Vec3::zero();
+                obj.torque = Vec3::zero();
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Pin an object in place (or release it) without changing its mass
+    pub fn set_pinned(&mut self, object_id: usize, pinned: bool) -> bool {
+        match self.objects.get_mut(object_id) {
+            Some(obj) => {
+                obj.is_static = pinned || obj.mass == 0.0;
+                if pinned {
+                    obj.velocity = Vec3::zero();
+                }
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Replace a body's per-axis motion locks, zeroing the newly locked velocities
+    pub fn set_dof_lock(&mut self, object_id: usize, lock: DofLock) -> bool {
+        match self.objects.get_mut(object_id) {
+            Some(obj) => {
+                obj.dof_lock = lock;
+                obj.apply_dof_lock();
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Attach an arbitrary script value to a body, replacing any previous one
+    pub fn set_user_data(&mut self, object_id: usize, value: Value) -> bool {
+        if object_id >= self.objects.len() {
+            return false;
+        }
+        self.user_data.insert(object_id, value);
+        true
+    }
+
+    /// Make a body a one-way platform that only blocks bodies approaching against
+    /// `direction`; `None` or a zero vector makes it solid from every side again
+    pub fn set_one_way(&mut self, object_id: usize, direction: Option<Vec3>) -> bool {
+        match self.objects.get_mut(object_id) {
+            Some(obj) => {
+                obj.one_way = direction
+                    .filter(|d| d.length() > 0.0)
+                    .map(|d| d * (1.0 / d.length()));
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Set a body's restitution and friction
+    pub fn set_material(&mut self, object_id: usize, material: Material) -> bool {
+        match self.objects.get_mut(object_id) {
+            Some(obj) => {
+                obj.material = material;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Set a body's linear and angular speed limits; 0 removes a limit
+    pub fn set_velocity_limits(
+        &mut self,
+        object_id: usize,
+        max_linear: f64,
+        max_angular: f64,
+    ) -> bool {
+        match self.objects.get_mut(object_id) {
+            Some(obj) => {
+                obj.max_linear_speed = max_linear.max(0.0);
+                obj.max_angular_speed = max_angular.max(0.0);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Apply a named quality preset to the solver settings
+    pub fn set_quality_preset(&mut self, preset: QualityPreset) {
+        self.settings = PhysicsSettings::preset(preset);
         for obj in &mut self.objects {
-            if !obj.is_static {
-                // Apply gravity
-                obj.velocity.y += self.gravity.y * self.dt;
-
-                // Update position
-                obj.position.x += obj.velocity.x * self.dt;
-                obj.position.y += obj.velocity.y * self.dt;
-                obj.position.z += obj.velocity.z * self.dt;
-
-                // Simple ground collision
-                if obj.position.y < 0.0 {
-                    obj.position.y = 0.0;
-                    obj.velocity.y = -obj.velocity.y * 0.8; // Bounce with damping
+            obj.wake();
+        }
+    }
+
+    pub fn step(&mut self) {
+        self.notify_pre_step();
+        let started = std::time::Instant::now();
+        self.stats = PhysicsStats::default();
+        self.apply_force_field();
+        if !self.playbacks.is_empty() {
+            self.advance_playbacks();
+        }
+
+        let substeps = self.settings.substeps.max(1);
+        let h = self.dt / substeps as f64;
+        let mut events: Vec<CollisionEvent> = Vec::new();
+
+        for _ in 0..substeps {
+            for contact in self.substep(h) {
+                // Report each touching pair once per step
+                if !events
+                    .iter()
+                    .any(|e| (e.body_a, e.body_b) == (contact.body_a, contact.body_b))
+                {
+                    events.push(contact);
                 }
             }
         }
 
+        for obj in &mut self.objects {
+            obj.force = Vec3::zero();
+            obj.torque = Vec3::zero();
+        }
+
+        self.update_sleep_states();
+        self.collision_events = events;
+
+        if !self.sensors.is_empty() {
+            self.update_sensors();
+        }
+        if !self.particle_systems.is_empty() {
+            self.update_particles();
+        }
+
         self.time += self.dt;
+        if !self.recordings.is_empty() {
+            self.record_trajectories();
+        }
+        self.finish_stats(started);
+        self.notify_post_step();
+    }
+
+    /// Turn gravity off or back on without losing the configured vector
+    pub fn set_gravity_enabled(&mut self, enabled: bool) {
+        self.gravity_enabled = enabled;
+        if enabled {
+            for obj in &mut self.objects {
+                obj.wake();
+            }
+        }
+    }
+
+    /// Gravity acting on bodies this step: `gravity`, or zero while disabled
+    pub fn effective_gravity(&self) -> Vec3 {
+        if self.gravity_enabled {
+            self.gravity
+        } else {
+            Vec3::zero()
+        }
+    }
+
+    /// Restart the simulation clock without touching body state
+    pub fn reset_time(&mut self) {
+        self.time = 0.0;
+    }
+
+    /// Advance the simulation by `h` seconds, returning the contacts solved
+    fn substep(&mut self, h: f64) -> Vec<CollisionEvent> {
+        let is_2d = self.is_2d;
+        let gravity = self.effective_gravity();
+        let previous_positions: Vec<Vec3> = self.objects.iter().map(|o| o.position).collect();
+        let mut accelerations = vec![Vec3::zero(); self.objects.len()];
+
+        for (obj, acceleration) in self.objects.iter_mut().zip(&mut accelerations) {
+            if obj.is_immovable() || obj.is_sleeping {
+                continue;
+            }
+
+            if is_2d {
+                // Discard anything that would leave the XY plane
+                obj.force.z = 0.0;
+                obj.torque.x = 0.0;
+                obj.torque.y = 0.0;
+            }
+
+            // Apply gravity and accumulated forces
+            let inv_mass = 1.0 / obj.mass;
+            let initial_velocity = obj.velocity;
+            obj.velocity.x += (gravity.x + obj.force.x * inv_mass) * h;
+            obj.velocity.y += (gravity.y + obj.force.y * inv_mass) * h;
+            obj.velocity.z += (gravity.z + obj.force.z * inv_mass) * h;
+
+            let angular_acceleration = obj.apply_inverse_inertia(obj.torque);
+            obj.angular_velocity = obj.angular_velocity + angular_acceleration * h;
+            obj.apply_dof_lock();
+            // What the integrator sees, so locked axes do not drift
+            *acceleration = (obj.velocity - initial_velocity) * (1.0 / h);
+        }
+
+        if !self.fluid_volumes.is_empty() {
+            self.apply_fluid_volumes(h);
+        }
+
+        // Contacts correct velocities before positions advance
+        let contacts = self.solve_contacts(h);
+
+        let integrator = self.integrator;
+        for (obj, &(mut acceleration)) in self.objects.iter_mut().zip(&accelerations) {
+            if obj.is_immovable() || obj.is_sleeping {
+                continue;
+            }
+
+            if is_2d {
+                obj.velocity.z = 0.0;
+                acceleration.z = 0.0;
+                obj.angular_velocity.x = 0.0;
+                obj.angular_velocity.y = 0.0;
+            }
+            // Contacts may have pushed along a locked axis
+            obj.apply_dof_lock();
+            obj.clamp_velocities();
+
+            // Update position
+            obj.position = obj.position + integrator.displacement(obj.velocity, acceleration, h);
+
+            // Update rotation
+            obj.integrate_rotation(h);
+        }
+
+        // Project constraints and derive velocities of the constrained bodies
+        if !self.constraints.is_empty() || !self.hinges.is_empty() {
+            self.solve_constraints(&previous_positions, h);
+        }
+
+        // Simple ground collision, bouncing and rubbing against the body's own material
+        for obj in &mut self.objects {
+            if !obj.is_immovable() && obj.position.y < 0.0 {
+                obj.position.y = 0.0;
+                let impact = obj.velocity.y.abs() * (1.0 + obj.material.restitution);
+                obj.velocity.y = -obj.velocity.y * obj.material.restitution;
+
+                let sliding = Vec3::new(obj.velocity.x, 0.0, obj.velocity.z);
+                let speed = sliding.length();
+                if speed > 0.0 {
+                    let slowed = (speed - obj.material.friction * impact).max(0.0);
+                    obj.velocity.x *= slowed / speed;
+                    obj.velocity.z *= slowed / speed;
+                }
+            }
+        }
+
+        contacts
+    }
+
+    /// Put bodies that have stayed slow long enough to sleep
+    fn update_sleep_states(&mut self) {
+        let threshold = self.settings.sleep_velocity_threshold;
+        for obj in &mut self.objects {
+            if obj.is_immovable() || threshold <= 0.0 {
+                obj.wake();
+                continue;
+            }
+
+            if obj.velocity.length() < threshold && obj.angular_velocity.length() < threshold {
+                obj.sleep_timer += self.dt;
+                if obj.sleep_timer >= self.settings.sleep_time {
+                    obj.is_sleeping = true;
+                    obj.velocity = Vec3::zero();
+                    obj.angular_velocity = Vec3::zero();
+                }
+            } else {
+                obj.wake();
+            }
+        }
     }
 }
 
 /// Register all standard library functions with an interpreter
 pub fn register_all(interpreter: &mut crate::eval::Interpreter) {
     register_math_functions(interpreter);
+    linalg::register_linalg_functions(interpreter);
+    csv::register_csv_functions(interpreter);
+    dict::register_dict_functions(interpreter);
+    option::register_option_functions(interpreter);
+    signal::register_signal_functions(interpreter);
+    iteration::register_iteration_functions(interpreter);
+    noise::register_noise_functions(interpreter);
+    random::register_random_functions(interpreter);
+    polynomial::register_polynomial_functions(interpreter);
+    vector::register_vector_functions(interpreter);
     physics::register_physics_functions(interpreter);
     quantum::register_quantum_functions(interpreter);
+    assertions::register_assertion_functions(interpreter);
+    register_introspection_functions(interpreter);
+
+    // Recorded last so every builtin above, including the introspection ones, is listed
+    *BUILTIN_REGISTRY.lock().unwrap() = interpreter.environment.builtins().into_iter().collect();
+}
+
+/// Sorted names of all registered builtin functions
+pub fn builtin_names() -> Vec<String> {
+    BUILTIN_REGISTRY.lock().unwrap().keys().cloned().collect()
+}
+
+/// Expected argument count of a builtin (`VARIADIC` for any), or `None` if unknown
+pub fn builtin_arity(name: &str) -> Option<usize> {
+    BUILTIN_REGISTRY.lock().unwrap().get(name).copied()
+}
+
+fn register_introspection_functions(interpreter: &mut crate::eval::Interpreter) {
+    interpreter.environment.define(
+        "list_builtins".to_string(),
+        Value::BuiltinFunction {
+            name: "list_builtins".to_string(),
+            arity: 0,
+            func: |_args| {
+                Ok(Value::Array(
+                    builtin_names().into_iter().map(Value::String).collect(),
+                ))
+            },
+        },
+    );
+
+    // -1 for variadic builtins
+    interpreter.environment.define(
+        "builtin_arity".to_string(),
+        Value::BuiltinFunction {
+            name: "builtin_arity".to_string(),
+            arity: 1,
+            func: |args| {
+                let name = match &args[0] {
+                    Value::String(name) => name,
+                    other => {
+                        return Err(RuntimeError::TypeError {
+                            message: format!(
+                                "Builtin name must be a string, found {}",
+                                other.type_name()
+                            ),
+                        })
+                    }
+                };
+                match builtin_arity(name) {
+                    Some(VARIADIC) => Ok(Value::Int(-1)),
+                    Some(arity) => Ok(Value::Int(arity as i64)),
+                    None => Err(RuntimeError::Generic {
+                        message: format!("Unknown builtin '{}'", name),
+                    }),
+                }
+            },
+        },
+    );
 }
 
 fn register_math_functions(interpreter: &mut crate::eval::Interpreter) {
@@ -303,6 +1081,35 @@ fn register_math_functions(interpreter: &mut crate::eval::Interpreter) {
         },
     );
 
+    // Signed shortest rotation from `a` to `b` in radians, in (-pi, pi]
+    interpreter.environment.define(
+        "angle_difference".to_string(),
+        Value::BuiltinFunction {
+            name: "angle_difference".to_string(),
+            arity: 2,
+            func: |args| {
+                let a = physics::expect_number(&args[0], "Angle")?;
+                let b = physics::expect_number(&args[1], "Angle")?;
+                Ok(Value::Float(angle_difference(a, b)))
+            },
+        },
+    );
+
+    // Interpolate between two angles in radians along the shortest arc
+    interpreter.environment.define(
+        "lerp_angle".to_string(),
+        Value::BuiltinFunction {
+            name: "lerp_angle".to_string(),
+            arity: 3,
+            func: |args| {
+                let a = physics::expect_number(&args[0], "Angle")?;
+                let b = physics::expect_number(&args[1], "Angle")?;
+                let t = physics::expect_number(&args[2], "Interpolation factor")?;
+                Ok(Value::Float(lerp_angle(a, b, t)))
+            },
+        },
+    );
+
     // println is a stdlib-only function (print is in builtins with different arity)
     interpreter.environment.define(
         "println".to_string(),
@@ -335,10 +1142,335 @@ fn register_math_functions(interpreter: &mut crate::eval::Interpreter) {
         },
     );
 
+    // format(template, args...) with `{0}` positional and `{}` sequential placeholders
+    interpreter.environment.define(
+        "format".to_string(),
+        Value::BuiltinFunction {
+            name: "format".to_string(),
+            arity: VARIADIC,
+            func: |args| {
+                let Some((template, values)) = args.split_first() else {
+                    return Err(RuntimeError::FunctionCallError {
+                        message: "format requires a template string".to_string(),
+                    });
+                };
+                match template {
+                    Value::String(template) => {
+                        Ok(Value::String(format_template(template, values)?))
+                    }
+                    other => Err(RuntimeError::TypeError {
+                        message: format!(
+                            "format template must be a string, found {}",
+                            other.type_name()
+                        ),
+                    }),
+                }
+            },
+        },
+    );
+
+    // Number of characters in a string
+    interpreter.environment.define(
+        "str_len".to_string(),
+        Value::BuiltinFunction {
+            name: "str_len".to_string(),
+            arity: 1,
+            func: |args| {
+                let text = expect_string(&args[0], "str_len argument")?;
+                Ok(Value::Int(text.chars().count() as i64))
+            },
+        },
+    );
+
+    interpreter.environment.define(
+        "str_concat".to_string(),
+        Value::BuiltinFunction {
+            name: "str_concat".to_string(),
+            arity: 2,
+            func: |args| {
+                let a = expect_string(&args[0], "str_concat argument")?;
+                let b = expect_string(&args[1], "str_concat argument")?;
+                Ok(Value::String(format!("{}{}", a, b)))
+            },
+        },
+    );
+
+    // Array of the pieces between occurrences of `separator`
+    interpreter.environment.define(
+        "str_split".to_string(),
+        Value::BuiltinFunction {
+            name: "str_split".to_string(),
+            arity: 2,
+            func: |args| {
+                let text = expect_string(&args[0], "str_split argument")?;
+                let separator = expect_string(&args[1], "Separator")?;
+                if separator.is_empty() {
+                    return Err(RuntimeError::Generic {
+                        message: "str_split separator must not be empty".to_string(),
+                    });
+                }
+                Ok(Value::Array(
+                    text.split(separator)
+                        .map(|piece| Value::String(piece.to_string()))
+                        .collect(),
+                ))
+            },
+        },
+    );
+
+    interpreter.environment.define(
+        "str_upper".to_string(),
+        Value::BuiltinFunction {
+            name: "str_upper".to_string(),
+            arity: 1,
+            func: |args| {
+                let text = expect_string(&args[0], "str_upper argument")?;
+                Ok(Value::String(text.to_uppercase()))
+            },
+        },
+    );
+
+    interpreter.environment.define(
+        "str_lower".to_string(),
+        Value::BuiltinFunction {
+            name: "str_lower".to_string(),
+            arity: 1,
+            func: |args| {
+                let text = expect_string(&args[0], "str_lower argument")?;
+                Ok(Value::String(text.to_lowercase()))
+            },
+        },
+    );
+
+    // Characters from `start` up to but not including `end`
+    interpreter.environment.define(
+        "substring".to_string(),
+        Value::BuiltinFunction {
+            name: "substring".to_string(),
+            arity: 3,
+            func: |args| {
+                let text = expect_string(&args[0], "substring argument")?;
+                let start = physics::expect_id(&args[1], "Start index")?;
+                let end = physics::expect_id(&args[2], "End index")?;
+                Ok(Value::String(substring(text, start, end)?))
+            },
+        },
+    );
+
+    // Determinant of a square matrix
+    interpreter.environment.define(
+        "determinant".to_string(),
+        Value::BuiltinFunction {
+            name: "determinant".to_string(),
+            arity: 1,
+            func: |args| {
+                let matrix = linalg::matrix_from_value(&args[0])?;
+                Ok(Value::Float(determinant(&matrix)?))
+            },
+        },
+    );
+
+    // Inverse of a square matrix; singular matrices are a domain error
+    interpreter.environment.define(
+        "inverse".to_string(),
+        Value::BuiltinFunction {
+            name: "inverse".to_string(),
+            arity: 1,
+            func: |args| {
+                let matrix = linalg::matrix_from_value(&args[0])?;
+                Ok(linalg::matrix_to_value(inverse(&matrix)?))
+            },
+        },
+    );
+
     // Register physics computing functions
     physics::register_physics_functions(interpreter);
 }
 
+/// Fill `{0}`-style positional and `{}` sequential placeholders with stringified
+/// values; `{{` and `}}` produce literal braces
+pub fn format_template(template: &str, values: &[Value]) -> Result<String, RuntimeError> {
+    let format_error = |message: String| RuntimeError::Generic { message };
+    let mut output = String::new();
+    let mut next_sequential = 0;
+    let mut chars = template.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '{' if chars.peek() == Some(&'{') => {
+                chars.next();
+                output.push('{');
+            }
+            '}' if chars.peek() == Some(&'}') => {
+                chars.next();
+                output.push('}');
+            }
+            '{' => {
+                let mut placeholder = String::new();
+                loop {
+                    match chars.next() {
+                        Some('}') => break,
+                        Some(c) => placeholder.push(c),
+                        None => {
+                            return Err(format_error("Unclosed '{' in format template".to_string()))
+                        }
+                    }
+                }
+
+                let index = if placeholder.is_empty() {
+                    next_sequential += 1;
+                    next_sequential - 1
+                } else {
+                    placeholder.trim().parse::<usize>().map_err(|_| {
+                        format_error(format!("Invalid format placeholder '{{{}}}'", placeholder))
+                    })?
+                };
+                let value = values.get(index).ok_or_else(|| {
+                    format_error(format!(
+                        "Format placeholder {} is out of range for {} argument(s)",
+                        index,
+                        values.len()
+                    ))
+                })?;
+                output.push_str(&value_to_string(value));
+            }
+            '}' => return Err(format_error("Unmatched '}' in format template".to_string())),
+            c => output.push(c),
+        }
+    }
+
+    Ok(output)
+}
+
+fn expect_string<'a>(value: &'a Value, what: &str) -> Result<&'a str, RuntimeError> {
+    match value {
+        Value::String(s) => Ok(s),
+        other => Err(RuntimeError::TypeError {
+            message: format!("{} must be a string, found {}", what, other.type_name()),
+        }),
+    }
+}
+
+/// Characters `start..end` of `text`, counted in chars rather than bytes
+pub fn substring(text: &str, start: usize, end: usize) -> Result<String, RuntimeError> {
+    let length = text.chars().count();
+    if end > length {
+        return Err(RuntimeError::IndexOutOfBounds { index: end, length });
+    }
+    if start > end {
+        return Err(RuntimeError::IndexOutOfBounds {
+            index: start,
+            length: end,
+        });
+    }
+    Ok(text.chars().skip(start).take(end - start).collect())
+}
+
+/// Signed shortest rotation from `a` to `b`, wrapped into (-pi, pi]
+pub fn angle_difference(a: f64, b: f64) -> f64 {
+    use std::f64::consts::{PI, TAU};
+    let difference = (b - a).rem_euclid(TAU);
+    if difference > PI {
+        difference - TAU
+    } else {
+        difference
+    }
+}
+
+/// Interpolate from angle `a` towards `b` along the shortest arc
+pub fn lerp_angle(a: f64, b: f64, t: f64) -> f64 {
+    a + angle_difference(a, b) * t
+}
+
+/// Side length of a non-empty square matrix
+fn square_size(matrix: &[Vec<f64>], operation: &str) -> Result<usize, RuntimeError> {
+    let n = matrix.len();
+    if n == 0 || matrix.iter().any(|row| row.len() != n) {
+        return Err(RuntimeError::DomainError {
+            message: format!("{} requires a non-empty square matrix", operation),
+        });
+    }
+    Ok(n)
+}
+
+/// Row of the largest-magnitude entry in `column`, from `column` down
+fn pivot_row(a: &[Vec<f64>], column: usize) -> usize {
+    (column..a.len())
+        .max_by(|&i, &j| a[i][column].abs().total_cmp(&a[j][column].abs()))
+        .unwrap_or(column)
+}
+
+/// Gaussian elimination with partial pivoting; the determinant is the product of the
+/// pivots, negated for each row swap
+pub fn determinant(matrix: &[Vec<f64>]) -> Result<f64, RuntimeError> {
+    let n = square_size(matrix, "determinant")?;
+    let mut a = matrix.to_vec();
+    let mut det = 1.0;
+
+    for column in 0..n {
+        let pivot = pivot_row(&a, column);
+        if a[pivot][column] == 0.0 {
+            return Ok(0.0);
+        }
+        if pivot != column {
+            a.swap(pivot, column);
+            det = -det;
+        }
+        det *= a[column][column];
+
+        let (upper, lower) = a.split_at_mut(column + 1);
+        let top = &upper[column];
+        for row in lower {
+            let factor = row[column] / top[column];
+            for (x, t) in row[column..].iter_mut().zip(&top[column..]) {
+                *x -= factor * t;
+            }
+        }
+    }
+    Ok(det)
+}
+
+/// Gauss-Jordan elimination with partial pivoting on `[matrix | I]`
+pub fn inverse(matrix: &[Vec<f64>]) -> Result<Vec<Vec<f64>>, RuntimeError> {
+    let n = square_size(matrix, "inverse")?;
+    let scale = matrix
+        .iter()
+        .flatten()
+        .fold(0.0_f64, |max, x| max.max(x.abs()));
+    let mut a = matrix.to_vec();
+    let mut inv: Vec<Vec<f64>> = (0..n)
+        .map(|i| (0..n).map(|j| if i == j { 1.0 } else { 0.0 }).collect())
+        .collect();
+
+    for column in 0..n {
+        let pivot = pivot_row(&a, column);
+        if a[pivot][column].abs() <= SINGULAR_TOLERANCE * scale {
+            return Err(RuntimeError::DomainError {
+                message: "Matrix is singular and has no inverse".to_string(),
+            });
+        }
+        a.swap(pivot, column);
+        inv.swap(pivot, column);
+
+        let p = a[column][column];
+        for c in 0..n {
+            a[column][c] /= p;
+            inv[column][c] /= p;
+        }
+        for row in 0..n {
+            let factor = a[row][column];
+            if row == column || factor == 0.0 {
+                continue;
+            }
+            for c in 0..n {
+                a[row][c] -= factor * a[column][c];
+                inv[row][c] -= factor * inv[column][c];
+            }
+        }
+    }
+    Ok(inv)
+}
+
 // Helper function to convert Value to string representation
 fn value_to_string(value: &Value) -> String {
     match value {
@@ -361,6 +1493,18 @@ fn value_to_string(value: &Value) -> String {
                 .collect();
             format!("[{}]", rows.join(", "))
         }
+        Value::Dict(dict) => {
+            let mut entries: Vec<String> = dict
+                .iter()
+                .map(|(k, v)| format!("{}: {}", k, value_to_string(v)))
+                .collect();
+            entries.sort();
+            format!("{{{}}}", entries.join(", "))
+        }
+        Value::Option(Some(value)) => format!("Some({})", value_to_string(value)),
+        Value::Option(None) => "None".to_string(),
+        Value::Result(Ok(value)) => format!("Ok({})", value_to_string(value)),
+        Value::Result(Err(error)) => format!("Err({})", value_to_string(error)),
         Value::Struct { name, fields } => {
             let field_strs: Vec<String> = fields
                 .iter()
@@ -369,7 +1513,9 @@ fn value_to_string(value: &Value) -> String {
             format!("{} {{ {} }}", name, field_strs.join(", "))
         }
         Value::Function { .. } => "<function>".to_string(),
-        Value::BuiltinFunction { name, .. } => format!("<builtin: {}>", name),
+        Value::BuiltinFunction { name, .. } | Value::InterpreterFunction { name, .. } => {
+            format!("<builtin: {}>", name)
+        }
         Value::AsyncHandle(_) => "<async handle>".to_string(),
         Value::PhysicsWorld(world) => {
             format!(
@@ -383,3 +1529,319 @@ fn value_to_string(value: &Value) -> String {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_determinant_and_inverse_of_3x3() {
+        let m = vec![
+            vec![0.0, 2.0, 1.0],
+            vec![1.0, 1.0, 0.0],
+            vec![3.0, 0.0, 4.0],
+        ];
+        // Zero top-left entry forces a row swap
+        assert!((determinant(&m).unwrap() - -11.0).abs() < 1e-12);
+
+        let inv = inverse(&m).unwrap();
+        let product = linalg::matrix_to_value(m)
+            .matrix_multiply(&linalg::matrix_to_value(inv))
+            .unwrap();
+        let product = linalg::matrix_from_value(&product).unwrap();
+        for (i, row) in product.iter().enumerate() {
+            for (j, &x) in row.iter().enumerate() {
+                let expected = if i == j { 1.0 } else { 0.0 };
+                assert!((x - expected).abs() < 1e-12, "({}, {}) = {}", i, j, x);
+            }
+        }
+    }
+
+    #[test]
+    fn test_singular_matrix_has_no_inverse() {
+        let singular = vec![
+            vec![1.0, 2.0, 3.0],
+            vec![2.0, 4.0, 6.0],
+            vec![0.0, 1.0, 1.0],
+        ];
+        assert_eq!(determinant(&singular).unwrap(), 0.0);
+        assert!(matches!(
+            inverse(&singular),
+            Err(RuntimeError::DomainError { .. })
+        ));
+        assert!(matches!(
+            determinant(&[vec![1.0, 2.0]]),
+            Err(RuntimeError::DomainError { .. })
+        ));
+    }
+
+    #[test]
+    fn test_2d_world_ignores_z_forces() {
+        let mut world = PhysicsWorld::new_2d();
+        let id = world.add_object("box".to_string(), 1.0, Vec3::new(0.0, 5.0, 0.0));
+
+        for _ in 0..60 {
+            world.apply_force(id, Vec3::new(1.0, 0.0, 50.0));
+            world.step();
+        }
+
+        let obj = &world.objects[id];
+        assert_eq!(obj.velocity.z, 0.0);
+        assert_eq!(obj.position.z, 0.0);
+        assert!(obj.velocity.x > 0.0);
+    }
+
+    #[test]
+    fn test_2d_world_rotates_only_about_z() {
+        let mut world = PhysicsWorld::new_2d();
+        let id = world.add_object("box".to_string(), 1.0, Vec3::new(0.0, 5.0, 0.0));
+
+        for _ in 0..30 {
+            world.apply_torque(id, Vec3::new(3.0, -2.0, 1.0));
+            world.step();
+        }
+
+        let obj = &world.objects[id];
+        assert_eq!(obj.rotation.x, 0.0);
+        assert_eq!(obj.rotation.y, 0.0);
+        assert!(obj.rotation.z > 0.0);
+    }
+
+    #[test]
+    fn test_frozen_body_holds_position_until_resumed() {
+        let mut world = PhysicsWorld::new();
+        let frozen = world.add_object("box".to_string(), 1.0, Vec3::new(0.0, 10.0, 0.0));
+        let falling = world.add_object("box".to_string(), 1.0, Vec3::new(5.0, 10.0, 0.0));
+        world.set_active(frozen, false);
+
+        for _ in 0..30 {
+            world.apply_force(frozen, Vec3::new(0.0, 100.0, 0.0));
+            world.step();
+        }
+        assert_eq!(world.objects[frozen].position, Vec3::new(0.0, 10.0, 0.0));
+        assert!(world.objects[falling].position.y < 10.0);
+
+        world.set_active(frozen, true);
+        world.step();
+        let y = world.objects[frozen].position.y;
+        assert!(y < 10.0 && y > 9.9);
+    }
+
+    #[test]
+    fn test_3d_world_keeps_z_motion() {
+        let mut world = PhysicsWorld::new();
+        let id = world.add_object("box".to_string(), 1.0, Vec3::new(0.0, 5.0, 0.0));
+
+        world.apply_force(id, Vec3::new(0.0, 0.0, 60.0));
+        world.step();
+
+        assert!(world.objects[id].velocity.z > 0.0);
+    }
+
+    #[test]
+    fn test_velocity_limits_cap_speed() {
+        let mut world = PhysicsWorld::new();
+        world.gravity = Vec3::zero();
+        let limited = world.add_object("sphere".to_string(), 1.0, Vec3::new(0.0, 5.0, 0.0));
+        let unlimited = world.add_object("sphere".to_string(), 1.0, Vec3::new(0.0, 5.0, 10.0));
+        assert!(world.set_velocity_limits(limited, 20.0, 3.0));
+        assert!(!world.set_velocity_limits(99, 1.0, 1.0));
+
+        // A 1e6 N push over one 1/60 s step is a huge impulse
+        for id in [limited, unlimited] {
+            world.apply_force(id, Vec3::new(1.0e6, 0.0, 0.0));
+            world.apply_torque(id, Vec3::new(0.0, 1.0e6, 0.0));
+        }
+        world.step();
+
+        let limited = &world.objects[limited];
+        assert!((limited.velocity.length() - 20.0).abs() < 1e-9);
+        assert!((limited.angular_velocity.length() - 3.0).abs() < 1e-9);
+        assert!((limited.position.x - 20.0 * world.dt).abs() < 1e-9);
+
+        let unlimited = &world.objects[unlimited];
+        assert!((unlimited.velocity.x - 1.0e6 * world.dt).abs() < 1e-6);
+        assert!((unlimited.angular_velocity.y - 1.0e6 * world.dt).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_lerp_angle_takes_shortest_arc() {
+        let halfway = lerp_angle(350f64.to_radians(), 10f64.to_radians(), 0.5);
+        let wrapped = halfway.rem_euclid(std::f64::consts::TAU);
+        assert!(wrapped < 1e-9 || std::f64::consts::TAU - wrapped < 1e-9);
+        assert!((lerp_angle(0.0, 1.0, 0.25) - 0.25).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_angle_difference_wraps_across_boundary() {
+        use std::f64::consts::PI;
+        let degrees =
+            |a: f64, b: f64| angle_difference(a.to_radians(), b.to_radians()).to_degrees();
+
+        assert!((degrees(350.0, 10.0) - 20.0).abs() < 1e-9);
+        assert!((degrees(10.0, 350.0) + 20.0).abs() < 1e-9);
+        assert!((degrees(-170.0, 170.0) + 20.0).abs() < 1e-9);
+        assert!((angle_difference(0.0, PI) - PI).abs() < 1e-12);
+        assert!((angle_difference(PI, 0.0) - PI).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_world_time_accumulates_and_resets() {
+        let mut world = PhysicsWorld::new();
+        world.dt = 1.0 / 60.0;
+        for _ in 0..60 {
+            world.step();
+        }
+        assert!((world.time - 1.0).abs() < 1e-9);
+
+        world.reset_time();
+        assert_eq!(world.time, 0.0);
+    }
+
+    #[test]
+    fn test_impulse_changes_velocity_at_once() {
+        let mut world = PhysicsWorld::new();
+        world.gravity = Vec3::zero();
+        let kicked = world.add_object("sphere".to_string(), 2.0, Vec3::new(0.0, 5.0, 0.0));
+        let pushed = world.add_object("sphere".to_string(), 2.0, Vec3::new(5.0, 5.0, 0.0));
+        let wall = world.add_object("box".to_string(), 0.0, Vec3::new(-5.0, 5.0, 0.0));
+
+        assert!(world.apply_impulse(kicked, Vec3::new(0.0, 10.0, 0.0)));
+        assert!((world.objects[kicked].velocity.y - 5.0).abs() < 1e-12);
+
+        // The same magnitude as a force only acts for one step
+        world.apply_force(pushed, Vec3::new(0.0, 10.0, 0.0));
+        world.step();
+        let pushed_speed = world.objects[pushed].velocity.y;
+        assert!((pushed_speed - 5.0 * world.dt).abs() < 1e-12);
+        assert!(pushed_speed < world.objects[kicked].velocity.y);
+
+        assert!(world.apply_impulse(wall, Vec3::new(100.0, 0.0, 0.0)));
+        assert_eq!(world.objects[wall].velocity, Vec3::zero());
+        assert!(world.apply_angular_impulse(kicked, Vec3::new(0.0, 0.0, 4.0)));
+        assert!((world.objects[kicked].angular_velocity.z - 2.0).abs() < 1e-12);
+        assert!(!world.apply_impulse(99, Vec3::zero()));
+    }
+
+    #[test]
+    fn test_duplicate_object_is_independent() {
+        let mut world = PhysicsWorld::new();
+        let original = world.add_object("box".to_string(), 3.0, Vec3::new(0.0, 5.0, 0.0));
+        world.set_size(original, Vec3::new(1.0, 0.25, 2.0));
+        world.set_velocity_limits(original, 7.0, 0.0);
+        world.objects[original].rotation = Vec3::new(0.0, 1.0, 0.0);
+        world.objects[original].velocity = Vec3::new(1.0, 0.0, 0.0);
+
+        let copy = world
+            .duplicate_object(original, Vec3::new(4.0, 5.0, 0.0))
+            .unwrap();
+        assert_ne!(copy, original);
+        {
+            let (a, b) = (&world.objects[original], &world.objects[copy]);
+            assert_eq!(b.shape, a.shape);
+            assert_eq!(b.mass, a.mass);
+            assert_eq!(b.half_extents, a.half_extents);
+            assert_eq!(b.rotation, a.rotation);
+            assert_eq!(b.max_linear_speed, 7.0);
+            assert_eq!(b.position, Vec3::new(4.0, 5.0, 0.0));
+            assert_eq!(b.velocity, Vec3::zero());
+        }
+
+        world.objects[copy].velocity = Vec3::new(0.0, 9.0, 0.0);
+        assert_eq!(world.objects[original].velocity, Vec3::new(1.0, 0.0, 0.0));
+        assert!(world.duplicate_object(99, Vec3::zero()).is_none());
+    }
+
+    #[test]
+    fn test_string_functions_split_and_slice() {
+        let mut interpreter = crate::eval::Interpreter::new();
+        register_all(&mut interpreter);
+        let mut call = |name: &str, args: Vec<Value>| {
+            let function = interpreter.environment.get(name).cloned().unwrap();
+            interpreter.call_value(function, &args)
+        };
+        let text = |s: &str| Value::String(s.to_string());
+
+        let fields = call("str_split", vec![text("box,2.5,,Red"), text(",")]).unwrap();
+        assert_eq!(
+            fields,
+            Value::Array(vec![text("box"), text("2.5"), text(""), text("Red")])
+        );
+        assert_eq!(call("str_upper", vec![text("Red")]).unwrap(), text("RED"));
+        assert_eq!(call("str_lower", vec![text("Red")]).unwrap(), text("red"));
+        assert_eq!(
+            call("str_concat", vec![text("box"), text("_1")]).unwrap(),
+            text("box_1")
+        );
+        assert_eq!(call("str_len", vec![text("héllo")]).unwrap(), Value::Int(5));
+
+        let mut slice = |start, end| {
+            call(
+                "substring",
+                vec![text("héllo world"), Value::Int(start), Value::Int(end)],
+            )
+        };
+        assert_eq!(slice(1, 5).unwrap(), text("éllo"));
+        assert_eq!(slice(6, 11).unwrap(), text("world"));
+        assert_eq!(slice(3, 3).unwrap(), text(""));
+        assert!(matches!(
+            slice(6, 12),
+            Err(RuntimeError::IndexOutOfBounds {
+                index: 12,
+                length: 11
+            })
+        ));
+        assert!(matches!(
+            slice(4, 2),
+            Err(RuntimeError::IndexOutOfBounds { .. })
+        ));
+    }
+
+    #[test]
+    fn test_format_template_placeholders() {
+        let strings = [
+            Value::String("a".to_string()),
+            Value::String("b".to_string()),
+        ];
+        assert_eq!(format_template("{0} and {1}", &strings).unwrap(), "a and b");
+        assert_eq!(format_template("{1}{0}{1}", &strings).unwrap(), "bab");
+
+        let mixed = [Value::Int(3), Value::Float(1.5), Value::Bool(true)];
+        assert_eq!(
+            format_template("{} / {} / {} {{ok}}", &mixed).unwrap(),
+            "3 / 1.5 / true {ok}"
+        );
+
+        let out_of_range = format_template("{2}", &strings).unwrap_err();
+        assert!(out_of_range.to_string().contains("out of range"));
+        assert!(format_template("{}{}{}", &strings).is_err());
+        assert!(format_template("{0", &strings).is_err());
+        assert!(format_template("0}", &strings).is_err());
+    }
+
+    #[test]
+    fn test_dof_lock_restricts_motion_to_unlocked_axes() {
+        let mut world = PhysicsWorld::new();
+        let id = world.add_object("box".to_string(), 1.0, Vec3::new(1.0, 20.0, -2.0));
+        world.set_dof_lock(
+            id,
+            DofLock {
+                linear: [true, false, true],
+                angular: [true, true, true],
+            },
+        );
+        world.objects[id].angular_velocity = Vec3::new(1.0, 2.0, 3.0);
+
+        for _ in 0..30 {
+            world.apply_force(id, Vec3::new(7.0, 3.0, -4.0));
+            world.objects[id].torque = Vec3::new(2.0, -1.0, 5.0);
+            world.step();
+        }
+
+        let obj = &world.objects[id];
+        assert_eq!((obj.position.x, obj.position.z), (1.0, -2.0));
+        assert!(obj.position.y < 20.0);
+        assert_eq!(obj.rotation, Vec3::zero());
+        assert_eq!(obj.angular_velocity, Vec3::zero());
+    }
+}
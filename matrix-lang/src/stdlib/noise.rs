@@ -0,0 +1,274 @@
+// Coherent Noise for Matrix Language
+// Seeded Perlin noise in two and three dimensions, and fractal sums of it
+
+use crate::eval::interpreter::{RuntimeError, Value};
+use crate::stdlib::physics::{expect_id, expect_number};
+use crate::stdlib::random::{self, splitmix64};
+use std::sync::{LazyLock, Mutex};
+
+/// Seed shared by every noise builtin, set with `set_seed`
+static RANDOM_SEED: Mutex<u64> = Mutex::new(0);
+/// Noise for the seed it was built from, rebuilt when the seed changes
+static NOISE: LazyLock<Mutex<(u64, Perlin)>> = LazyLock::new(|| Mutex::new((0, Perlin::new(0))));
+
+/// Seed the noise functions and restart the random samplers from the same seed
+pub fn set_random_seed(seed: u64) {
+    *RANDOM_SEED.lock().unwrap() = seed;
+    random::reseed(seed);
+}
+
+pub fn random_seed() -> u64 {
+    *RANDOM_SEED.lock().unwrap()
+}
+
+/// Run `f` with the noise for the current seed
+fn with_noise<T>(f: impl FnOnce(&Perlin) -> T) -> T {
+    let seed = random_seed();
+    let mut noise = NOISE.lock().unwrap();
+    if noise.0 != seed {
+        *noise = (seed, Perlin::new(seed));
+    }
+    f(&noise.1)
+}
+
+pub fn register_noise_functions(interpreter: &mut crate::eval::Interpreter) {
+    // Reseed the noise functions and random samplers; the same seed always gives the
+    // same values
+    interpreter.environment.define(
+        "set_seed".to_string(),
+        Value::BuiltinFunction {
+            name: "set_seed".to_string(),
+            arity: 1,
+            func: |args| {
+                set_random_seed(expect_id(&args[0], "Seed")? as u64);
+                Ok(Value::Unit)
+            },
+        },
+    );
+
+    // 2D Perlin noise in roughly [-1, 1]
+    interpreter.environment.define(
+        "perlin2".to_string(),
+        Value::BuiltinFunction {
+            name: "perlin2".to_string(),
+            arity: 2,
+            func: |args| {
+                let x = expect_number(&args[0], "x")?;
+                let y = expect_number(&args[1], "y")?;
+                Ok(Value::Float(with_noise(|noise| noise.noise2(x, y))))
+            },
+        },
+    );
+
+    // 3D Perlin noise in roughly [-1, 1]
+    interpreter.environment.define(
+        "perlin3".to_string(),
+        Value::BuiltinFunction {
+            name: "perlin3".to_string(),
+            arity: 3,
+            func: |args| {
+                let x = expect_number(&args[0], "x")?;
+                let y = expect_number(&args[1], "y")?;
+                let z = expect_number(&args[2], "z")?;
+                Ok(Value::Float(with_noise(|noise| noise.noise3(x, y, z))))
+            },
+        },
+    );
+
+    // Fractal Brownian motion: octaves of 2D noise, each twice the frequency and half
+    // the amplitude of the last
+    interpreter.environment.define(
+        "fbm".to_string(),
+        Value::BuiltinFunction {
+            name: "fbm".to_string(),
+            arity: 3,
+            func: |args| {
+                let x = expect_number(&args[0], "x")?;
+                let y = expect_number(&args[1], "y")?;
+                let octaves = expect_id(&args[2], "Octaves")?;
+                if octaves == 0 {
+                    return Err(RuntimeError::Generic {
+                        message: "fbm needs at least one octave".to_string(),
+                    });
+                }
+                Ok(Value::Float(with_noise(|noise| noise.fbm(x, y, octaves))))
+            },
+        },
+    );
+}
+
+/// Improved Perlin noise over a permutation table shuffled by the seed
+#[derive(Debug, Clone)]
+pub struct Perlin {
+    /// Shuffled 0..256, repeated so lookups of `i + 1` need no wrapping
+    permutation: [u8; 512],
+}
+
+impl Perlin {
+    pub fn new(seed: u64) -> Self {
+        let mut table: [u8; 256] = std::array::from_fn(|i| i as u8);
+
+        // Fisher-Yates with splitmix64, so nearby seeds give unrelated tables
+        let mut state = seed;
+        for i in (1..256).rev() {
+            let j = (splitmix64(&mut state) % (i as u64 + 1)) as usize;
+            table.swap(i, j);
+        }
+
+        Self {
+            permutation: std::array::from_fn(|i| table[i % 256]),
+        }
+    }
+
+    fn hash(&self, i: usize) -> usize {
+        self.permutation[i] as usize
+    }
+
+    pub fn noise2(&self, x: f64, y: f64) -> f64 {
+        let (xi, yi) = (lattice(x), lattice(y));
+        let (xf, yf) = (x - x.floor(), y - y.floor());
+        let (u, v) = (fade(xf), fade(yf));
+
+        let corner = |dx: usize, dy: usize| {
+            let h = self.hash(self.hash(xi + dx) + yi + dy);
+            gradient2(h, xf - dx as f64, yf - dy as f64)
+        };
+        let bottom = lerp(u, corner(0, 0), corner(1, 0));
+        let top = lerp(u, corner(0, 1), corner(1, 1));
+        lerp(v, bottom, top)
+    }
+
+    pub fn noise3(&self, x: f64, y: f64, z: f64) -> f64 {
+        let (xi, yi, zi) = (lattice(x), lattice(y), lattice(z));
+        let (xf, yf, zf) = (x - x.floor(), y - y.floor(), z - z.floor());
+        let (u, v, w) = (fade(xf), fade(yf), fade(zf));
+
+        let corner = |dx: usize, dy: usize, dz: usize| {
+            let h = self.hash(self.hash(self.hash(xi + dx) + yi + dy) + zi + dz);
+            gradient3(h, xf - dx as f64, yf - dy as f64, zf - dz as f64)
+        };
+        let face = |dz: usize| {
+            let bottom = lerp(u, corner(0, 0, dz), corner(1, 0, dz));
+            let top = lerp(u, corner(0, 1, dz), corner(1, 1, dz));
+            lerp(v, bottom, top)
+        };
+        lerp(w, face(0), face(1))
+    }
+
+    /// Sum of `octaves` layers of 2D noise, normalized back into the range of one layer
+    pub fn fbm(&self, x: f64, y: f64, octaves: usize) -> f64 {
+        let (mut sum, mut total_amplitude) = (0.0, 0.0);
+        let (mut amplitude, mut frequency) = (1.0, 1.0);
+        for _ in 0..octaves {
+            sum += amplitude * self.noise2(x * frequency, y * frequency);
+            total_amplitude += amplitude;
+            amplitude *= 0.5;
+            frequency *= 2.0;
+        }
+        sum / total_amplitude
+    }
+}
+
+/// Lattice cell of a coordinate, wrapped to the table size
+fn lattice(t: f64) -> usize {
+    (t.floor() as i64).rem_euclid(256) as usize
+}
+
+/// 6t^5 - 15t^4 + 10t^3, flat at both ends so cells join smoothly
+fn fade(t: f64) -> f64 {
+    t * t * t * (t * (t * 6.0 - 15.0) + 10.0)
+}
+
+fn lerp(t: f64, a: f64, b: f64) -> f64 {
+    a + t * (b - a)
+}
+
+/// Dot product with one of eight unit gradients
+fn gradient2(hash: usize, x: f64, y: f64) -> f64 {
+    const DIAGONAL: f64 = std::f64::consts::FRAC_1_SQRT_2;
+    match hash & 7 {
+        0 => x,
+        1 => -x,
+        2 => y,
+        3 => -y,
+        4 => (x + y) * DIAGONAL,
+        5 => (-x + y) * DIAGONAL,
+        6 => (x - y) * DIAGONAL,
+        _ => (-x - y) * DIAGONAL,
+    }
+}
+
+/// Dot product with one of the twelve cube-edge gradients
+fn gradient3(hash: usize, x: f64, y: f64, z: f64) -> f64 {
+    let h = hash & 15;
+    let u = if h < 8 { x } else { y };
+    let v = if h < 4 {
+        y
+    } else if h == 12 || h == 14 {
+        x
+    } else {
+        z
+    };
+    (if h & 1 == 0 { u } else { -u }) + (if h & 2 == 0 { v } else { -v })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Points spread over several lattice cells, including negative coordinates
+    fn sample_points() -> impl Iterator<Item = (f64, f64, f64)> {
+        (0..2000).map(|i| {
+            let t = i as f64;
+            (t * 0.137 - 50.0, t * 0.291 - 80.0, t * 0.053 + 3.0)
+        })
+    }
+
+    #[test]
+    fn test_noise_is_deterministic_for_a_seed() {
+        let (a, b) = (Perlin::new(42), Perlin::new(42));
+        let other = Perlin::new(43);
+        let mut differs = false;
+        for (x, y, z) in sample_points() {
+            assert_eq!(a.noise2(x, y), b.noise2(x, y));
+            assert_eq!(a.noise3(x, y, z), b.noise3(x, y, z));
+            assert_eq!(a.fbm(x, y, 5), b.fbm(x, y, 5));
+            differs |= a.noise2(x, y) != other.noise2(x, y);
+        }
+        assert!(differs);
+    }
+
+    #[test]
+    fn test_noise_is_bounded() {
+        let perlin = Perlin::new(7);
+        for (x, y, z) in sample_points() {
+            for value in [
+                perlin.noise2(x, y),
+                perlin.noise3(x, y, z),
+                perlin.fbm(x, y, 6),
+            ] {
+                assert!(
+                    (-1.0..=1.0).contains(&value),
+                    "{} at ({}, {}, {})",
+                    value,
+                    x,
+                    y,
+                    z
+                );
+            }
+        }
+        // Zero at lattice points
+        assert_eq!(perlin.noise2(3.0, -4.0), 0.0);
+    }
+
+    #[test]
+    fn test_noise_is_continuous() {
+        let perlin = Perlin::new(11);
+        let step = 1e-4;
+        for (x, y, z) in sample_points() {
+            assert!((perlin.noise2(x, y) - perlin.noise2(x + step, y)).abs() < 1e-3);
+            assert!((perlin.noise3(x, y, z) - perlin.noise3(x, y, z + step)).abs() < 1e-3);
+            assert!((perlin.fbm(x, y, 4) - perlin.fbm(x, y + step, 4)).abs() < 1e-2);
+        }
+    }
+}
@@ -0,0 +1,134 @@
+// Option and Result for Matrix Language
+// Constructors and helpers for `Some`/`None` and `Ok`/`Err` values
+
+use crate::eval::interpreter::{RuntimeError, Value};
+use crate::eval::Interpreter;
+
+pub fn register_option_functions(interpreter: &mut crate::eval::Interpreter) {
+    interpreter
+        .environment
+        .define("None".to_string(), Value::Option(None));
+
+    interpreter.environment.define(
+        "Some".to_string(),
+        Value::BuiltinFunction {
+            name: "Some".to_string(),
+            arity: 1,
+            func: |args| Ok(Value::Option(Some(Box::new(args[0].clone())))),
+        },
+    );
+
+    interpreter.environment.define(
+        "Ok".to_string(),
+        Value::BuiltinFunction {
+            name: "Ok".to_string(),
+            arity: 1,
+            func: |args| Ok(Value::Result(Ok(Box::new(args[0].clone())))),
+        },
+    );
+
+    interpreter.environment.define(
+        "Err".to_string(),
+        Value::BuiltinFunction {
+            name: "Err".to_string(),
+            arity: 1,
+            func: |args| Ok(Value::Result(Err(Box::new(args[0].clone())))),
+        },
+    );
+
+    // Value inside `Some` or `Ok`; `None` and `Err` are runtime errors
+    interpreter.environment.define(
+        "unwrap".to_string(),
+        Value::BuiltinFunction {
+            name: "unwrap".to_string(),
+            arity: 1,
+            func: |args| unwrap(&args[0]),
+        },
+    );
+
+    interpreter.environment.define(
+        "is_some".to_string(),
+        Value::BuiltinFunction {
+            name: "is_some".to_string(),
+            arity: 1,
+            func: |args| Ok(Value::Bool(expect_option(&args[0])?.is_some())),
+        },
+    );
+
+    // map_option(f, option)
+    interpreter.environment.define(
+        "map_option".to_string(),
+        Value::InterpreterFunction {
+            name: "map_option".to_string(),
+            arity: 2,
+            func: |interpreter, args| map_option(interpreter, &args[0], &args[1]),
+        },
+    );
+}
+
+pub fn unwrap(value: &Value) -> Result<Value, RuntimeError> {
+    match value {
+        Value::Option(Some(inner)) | Value::Result(Ok(inner)) => Ok(inner.as_ref().clone()),
+        Value::Option(None) => Err(RuntimeError::Generic {
+            message: "unwrap called on None".to_string(),
+        }),
+        Value::Result(Err(error)) => Err(RuntimeError::Generic {
+            message: format!("unwrap called on Err({})", error),
+        }),
+        other => Err(RuntimeError::TypeError {
+            message: format!(
+                "unwrap expects an Option or Result, found {}",
+                other.type_name()
+            ),
+        }),
+    }
+}
+
+/// `Some(f(x))` for `Some(x)`, and `None` for `None`
+pub fn map_option(
+    interpreter: &mut Interpreter,
+    function: &Value,
+    option: &Value,
+) -> Result<Value, RuntimeError> {
+    match expect_option(option)? {
+        Some(inner) => {
+            let mapped = interpreter.call_value(function.clone(), std::slice::from_ref(inner))?;
+            Ok(Value::Option(Some(Box::new(mapped))))
+        }
+        None => Ok(Value::Option(None)),
+    }
+}
+
+fn expect_option(value: &Value) -> Result<Option<&Value>, RuntimeError> {
+    match value {
+        Value::Option(option) => Ok(option.as_deref()),
+        other => Err(RuntimeError::TypeError {
+            message: format!("Expected Option, found {}", other.type_name()),
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unwrap_and_is_some() {
+        let five = Value::Option(Some(Box::new(Value::Int(5))));
+        assert_eq!(unwrap(&five).unwrap(), Value::Int(5));
+        assert!(unwrap(&Value::Option(None)).is_err());
+
+        let ok = Value::Result(Ok(Box::new(Value::Float(1.5))));
+        assert_eq!(unwrap(&ok).unwrap(), Value::Float(1.5));
+        let err = Value::Result(Err(Box::new(Value::String("no contact".to_string()))));
+        let message = unwrap(&err).unwrap_err().to_string();
+        assert!(message.contains("no contact"), "{}", message);
+        assert!(matches!(
+            unwrap(&Value::Int(5)),
+            Err(RuntimeError::TypeError { .. })
+        ));
+
+        assert_eq!(expect_option(&five).unwrap(), Some(&Value::Int(5)));
+        assert!(expect_option(&ok).is_err());
+    }
+}
@@ -0,0 +1,198 @@
+// Particle Systems for Matrix Language
+// Deterministic point particles (sparks, smoke) emitted at a fixed rate and stepped with the world
+
+use crate::stdlib::{PhysicsWorld, Vec3};
+
+/// A single particle; dead slots are kept and reused by later emissions
+#[derive(Debug, Clone, PartialEq)]
+pub struct Particle {
+    pub position: Vec3,
+    pub velocity: Vec3,
+    /// Seconds since the particle was emitted
+    pub age: f64,
+    pub alive: bool,
+}
+
+/// An emitter of short-lived particles
+#[derive(Debug, Clone)]
+pub struct ParticleSystem {
+    pub id: usize,
+    pub position: Vec3,
+    /// Particles emitted per second
+    pub emission_rate: f64,
+    /// Seconds each particle lives
+    pub lifetime: f64,
+    pub initial_velocity: Vec3,
+    /// Maximum random offset added to each component of the initial velocity
+    pub velocity_spread: f64,
+    /// Fraction of world gravity applied to particles
+    pub gravity_scale: f64,
+    pub particles: Vec<Particle>,
+    /// Fractional particles carried over between steps
+    emission_debt: f64,
+    /// xorshift state seeded from the id, so runs replay identically
+    rng_state: u64,
+}
+
+impl ParticleSystem {
+    pub fn new(id: usize, position: Vec3, emission_rate: f64, lifetime: f64) -> Self {
+        Self {
+            id,
+            position,
+            emission_rate: emission_rate.max(0.0),
+            lifetime: lifetime.max(0.0),
+            initial_velocity: Vec3::zero(),
+            velocity_spread: 0.0,
+            gravity_scale: 1.0,
+            particles: Vec::new(),
+            emission_debt: 0.0,
+            rng_state: 0x9E37_79B9_7F4A_7C15 ^ (id as u64 + 1),
+        }
+    }
+
+    pub fn alive_count(&self) -> usize {
+        self.particles.iter().filter(|p| p.alive).count()
+    }
+
+    /// Positions of the live particles, for rendering
+    pub fn positions(&self) -> Vec<Vec3> {
+        self.particles
+            .iter()
+            .filter(|p| p.alive)
+            .map(|p| p.position)
+            .collect()
+    }
+
+    /// Age and move live particles, retire expired ones, then emit new ones
+    pub fn step(&mut self, dt: f64, gravity: Vec3) {
+        let acceleration = gravity * self.gravity_scale;
+        for particle in self.particles.iter_mut().filter(|p| p.alive) {
+            particle.age += dt;
+            if particle.age >= self.lifetime {
+                particle.alive = false;
+                continue;
+            }
+            particle.velocity = particle.velocity + acceleration * dt;
+            particle.position = particle.position + particle.velocity * dt;
+        }
+
+        self.emission_debt += self.emission_rate * dt;
+        while self.emission_debt >= 1.0 {
+            self.emission_debt -= 1.0;
+            self.emit();
+        }
+    }
+
+    fn emit(&mut self) {
+        let velocity = self.initial_velocity
+            + Vec3::new(
+                self.next_signed_unit(),
+                self.next_signed_unit(),
+                self.next_signed_unit(),
+            ) * self.velocity_spread;
+        let particle = Particle {
+            position: self.position,
+            velocity,
+            age: 0.0,
+            alive: true,
+        };
+
+        match self.particles.iter_mut().find(|p| !p.alive) {
+            Some(slot) => *slot = particle,
+            None => self.particles.push(particle),
+        }
+    }
+
+    /// Next pseudo-random value in [-1, 1]
+    fn next_signed_unit(&mut self) -> f64 {
+        self.rng_state ^= self.rng_state << 13;
+        self.rng_state ^= self.rng_state >> 7;
+        self.rng_state ^= self.rng_state << 17;
+        (self.rng_state >> 11) as f64 / (1u64 << 53) as f64 * 2.0 - 1.0
+    }
+}
+
+impl PhysicsWorld {
+    /// Add a particle emitter, returning its id
+    pub fn add_particle_system(
+        &mut self,
+        position: Vec3,
+        emission_rate: f64,
+        lifetime: f64,
+    ) -> usize {
+        let id = self.particle_systems.len();
+        self.particle_systems
+            .push(ParticleSystem::new(id, position, emission_rate, lifetime));
+        id
+    }
+
+    pub(crate) fn update_particles(&mut self) {
+        let mut gravity = self.effective_gravity();
+        if self.is_2d {
+            gravity.z = 0.0;
+        }
+        for system in &mut self.particle_systems {
+            system.step(self.dt, gravity);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_emission_count_over_time() {
+        let mut world = PhysicsWorld::new();
+        world.dt = 0.1;
+        let system = world.add_particle_system(Vec3::zero(), 20.0, 10.0);
+
+        for _ in 0..10 {
+            world.step();
+        }
+
+        // 20 per second for one second, none expired yet
+        assert_eq!(world.particle_systems[system].alive_count(), 20);
+    }
+
+    #[test]
+    fn test_particles_expire_and_slots_are_recycled() {
+        let mut world = PhysicsWorld::new();
+        world.dt = 0.1;
+        let system = world.add_particle_system(Vec3::zero(), 10.0, 0.5);
+        world.particle_systems[system].velocity_spread = 1.0;
+
+        for _ in 0..100 {
+            world.step();
+        }
+
+        let particles = &world.particle_systems[system];
+        // One particle per step, each living five steps
+        assert_eq!(particles.alive_count(), 5);
+        assert!(particles.particles.len() <= 6);
+
+        world.particle_systems[system].emission_rate = 0.0;
+        for _ in 0..5 {
+            world.step();
+        }
+        assert_eq!(world.particle_systems[system].alive_count(), 0);
+        assert!(world.particle_systems[system].positions().is_empty());
+    }
+
+    #[test]
+    fn test_particles_fall_under_scaled_gravity() {
+        let mut world = PhysicsWorld::new();
+        world.dt = 0.1;
+        let system = world.add_particle_system(Vec3::zero(), 10.0, 5.0);
+        world.particle_systems[system].gravity_scale = 0.0;
+        world.particle_systems[system].initial_velocity = Vec3::new(1.0, 0.0, 0.0);
+
+        for _ in 0..3 {
+            world.step();
+        }
+
+        let oldest = world.particle_systems[system].positions()[0];
+        assert!((oldest.x - 0.2).abs() < 1e-9);
+        assert_eq!(oldest.y, 0.0);
+    }
+}
@@ -1,8 +1,108 @@
 // Physics Standard Library for Matrix Language
 // Provides physics world creation, object management, and simulation functions
 
+use crate::eval::interpreter::VARIADIC;
 use crate::eval::{Interpreter, RuntimeError, Value};
-use crate::stdlib::{PhysicsWorld, Vec3, PHYSICS_WORLDS};
+use crate::stdlib::{
+    DofLock, ForceField, IntegrationMethod, Material, PhysicsObject, PhysicsWorld, QualityPreset,
+    Trajectory, TrajectorySample, Vec3, PHYSICS_WORLDS,
+};
+use std::collections::{HashMap, HashSet};
+use std::sync::{LazyLock, Mutex};
+
+/// Script functions registered with `on_collision`, per world id
+static COLLISION_CALLBACKS: LazyLock<Mutex<HashMap<usize, Vec<Value>>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// Script functions registered with `set_collision_filter`, per world id
+static COLLISION_FILTERS: LazyLock<Mutex<HashMap<usize, Value>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// Script functions registered with `set_force_field`, per world id
+static SCRIPT_FORCE_FIELDS: LazyLock<Mutex<HashMap<usize, Value>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// A script function and the arguments to call it with
+pub type PendingCallback = (Value, Vec<Value>);
+
+/// A world's collision filter and the body pairs to ask it about before the next step,
+/// or `None` if the world has no filter. The interpreter calls the filter itself, since
+/// builtins cannot call back into it while the step runs.
+pub fn collision_filter_candidates(world_id: usize) -> Option<(Value, Vec<(usize, usize)>)> {
+    let filter = COLLISION_FILTERS.lock().unwrap().get(&world_id)?.clone();
+    let candidates = with_world(world_id, |world| Ok(world.filter_candidates())).ok()?;
+    Some((filter, candidates))
+}
+
+/// Keep the pairs a collision filter rejected from colliding during the world's next step
+pub fn set_filtered_pairs(world_id: usize, pairs: HashSet<(usize, usize)>) {
+    let _ = with_world(world_id, |world| {
+        world.filtered_pairs = pairs;
+        Ok(())
+    });
+}
+
+/// A world's script force field and the movable bodies to evaluate it at, as
+/// (body id, position), or `None` if the world has no script field
+pub fn script_force_field_targets(world_id: usize) -> Option<(Value, Vec<(usize, Vec3)>)> {
+    let field = SCRIPT_FORCE_FIELDS.lock().unwrap().get(&world_id)?.clone();
+    let targets = with_world(world_id, |world| {
+        Ok(world
+            .objects
+            .iter()
+            .filter(|obj| !obj.is_immovable())
+            .map(|obj| (obj.id, obj.position))
+            .collect())
+    })
+    .ok()?;
+    Some((field, targets))
+}
+
+/// Apply the forces a script force field returned, ahead of the world's next step
+pub fn apply_script_forces(world_id: usize, forces: Vec<(usize, Vec3)>) {
+    let _ = with_world(world_id, |world| {
+        for (id, force) in forces {
+            world.apply_force(id, force);
+        }
+        Ok(())
+    });
+}
+
+/// Each of a world's collision callbacks with its arguments, once per collision event
+/// of the world's last step. The interpreter runs them after `physics_step` returns,
+/// since builtins cannot call back into it.
+pub fn collision_callbacks(world_id: usize) -> Vec<PendingCallback> {
+    let callbacks = match COLLISION_CALLBACKS.lock().unwrap().get(&world_id) {
+        Some(callbacks) if !callbacks.is_empty() => callbacks.clone(),
+        _ => return Vec::new(),
+    };
+
+    with_world(world_id, |world| {
+        let user_data = |body: usize| world.user_data.get(&body).cloned().unwrap_or(Value::Unit);
+        let mut pending = Vec::new();
+        for event in &world.collision_events {
+            let mut contact = HashMap::new();
+            contact.insert("point".to_string(), vec3_to_value(event.point));
+            contact.insert("normal".to_string(), vec3_to_value(event.normal));
+            contact.insert("depth".to_string(), Value::Float(event.depth));
+            contact.insert("user_data_a".to_string(), user_data(event.body_a));
+            contact.insert("user_data_b".to_string(), user_data(event.body_b));
+            let args = vec![
+                Value::Int(event.body_a as i64),
+                Value::Int(event.body_b as i64),
+                Value::Struct {
+                    name: "Contact".to_string(),
+                    fields: contact,
+                },
+            ];
+            for callback in &callbacks {
+                pending.push((callback.clone(), args.clone()));
+            }
+        }
+        Ok(pending)
+    })
+    .unwrap_or_default()
+}
 
 pub fn register_physics_functions(interpreter: &mut Interpreter) {
     // Physics world creation
@@ -23,6 +123,24 @@ pub fn register_physics_functions(interpreter: &mut Interpreter) {
         },
     );
 
+    // 2D physics world creation (motion in XY plane, rotation about Z)
+    interpreter.environment.define(
+        "create_physics_world_2d".to_string(),
+        Value::BuiltinFunction {
+            name: "create_physics_world_2d".to_string(),
+            arity: 0,
+            func: |_args| {
+                let world = PhysicsWorld::new_2d();
+                let world_id = world.id;
+
+                let mut worlds = PHYSICS_WORLDS.lock().unwrap();
+                worlds.insert(world_id, world);
+
+                Ok(Value::Int(world_id as i64))
+            },
+        },
+    );
+
     // Add rigid body to physics world
     interpreter.environment.define(
         "add_rigid_body".to_string(),
@@ -122,38 +240,13 @@ pub fn register_physics_functions(interpreter: &mut Interpreter) {
         },
     );
 
-    // Step physics simulation
+    // Step physics simulation, with the world's script callbacks run around the step
     interpreter.environment.define(
         "physics_step".to_string(),
-        Value::BuiltinFunction {
+        Value::InterpreterFunction {
             name: "physics_step".to_string(),
             arity: 1,
-            func: |args| {
-                if args.len() != 1 {
-                    return Err(RuntimeError::TypeError {
-                        message: "physics_step expects 1 argument: world_id".to_string(),
-                    });
-                }
-
-                let world_id = match &args[0] {
-                    Value::Int(id) => *id as usize,
-                    _ => {
-                        return Err(RuntimeError::TypeError {
-                            message: "World ID must be integer".to_string(),
-                        })
-                    }
-                };
-
-                let mut worlds = PHYSICS_WORLDS.lock().unwrap();
-                if let Some(world) = worlds.get_mut(&world_id) {
-                    world.step();
-                    Ok(Value::Unit)
-                } else {
-                    Err(RuntimeError::Generic {
-                        message: "Physics world not found".to_string(),
-                    })
-                }
-            },
+            func: |interpreter, args| interpreter.physics_step(args),
         },
     );
 
@@ -487,4 +580,2301 @@ pub fn register_physics_functions(interpreter: &mut Interpreter) {
             },
         },
     );
+
+    // Apply a force to an object for the next step
+    interpreter.environment.define(
+        "apply_force".to_string(),
+        Value::BuiltinFunction {
+            name: "apply_force".to_string(),
+            arity: 3,
+            func: |args| {
+                let world_id = expect_id(&args[0], "World ID")?;
+                let object_id = expect_id(&args[1], "Object ID")?;
+                let force = expect_vec3(&args[2], "Force")?;
+
+                with_world(world_id, |world| {
+                    if world.apply_force(object_id, force) {
+                        Ok(Value::Unit)
+                    } else {
+                        Err(object_not_found())
+                    }
+                })
+            },
+        },
+    );
+
+    // Copy a body to a new position at rest; constraints are not copied
+    interpreter.environment.define(
+        "duplicate_body".to_string(),
+        Value::BuiltinFunction {
+            name: "duplicate_body".to_string(),
+            arity: 3,
+            func: |args| {
+                let world_id = expect_id(&args[0], "World ID")?;
+                let object_id = expect_id(&args[1], "Object ID")?;
+                let position = expect_vec3(&args[2], "Position")?;
+
+                with_world(world_id, |world| {
+                    match world.duplicate_object(object_id, position) {
+                        Some(id) => Ok(Value::Int(id as i64)),
+                        None => Err(object_not_found()),
+                    }
+                })
+            },
+        },
+    );
+
+    // Apply a torque to an object for the next step
+    interpreter.environment.define(
+        "apply_torque".to_string(),
+        Value::BuiltinFunction {
+            name: "apply_torque".to_string(),
+            arity: 3,
+            func: |args| {
+                let world_id = expect_id(&args[0], "World ID")?;
+                let object_id = expect_id(&args[1], "Object ID")?;
+                let torque = expect_vec3(&args[2], "Torque")?;
+
+                with_world(world_id, |world| {
+                    if world.apply_torque(object_id, torque) {
+                        Ok(Value::Unit)
+                    } else {
+                        Err(object_not_found())
+                    }
+                })
+            },
+        },
+    );
+
+    // Change an object's velocity at once by impulse / mass (jumps, hits)
+    interpreter.environment.define(
+        "apply_impulse".to_string(),
+        Value::BuiltinFunction {
+            name: "apply_impulse".to_string(),
+            arity: 3,
+            func: |args| {
+                let world_id = expect_id(&args[0], "World ID")?;
+                let object_id = expect_id(&args[1], "Object ID")?;
+                let impulse = expect_vec3(&args[2], "Impulse")?;
+
+                with_world(world_id, |world| {
+                    if world.apply_impulse(object_id, impulse) {
+                        Ok(Value::Unit)
+                    } else {
+                        Err(object_not_found())
+                    }
+                })
+            },
+        },
+    );
+
+    // Change an object's angular velocity at once
+    interpreter.environment.define(
+        "apply_angular_impulse".to_string(),
+        Value::BuiltinFunction {
+            name: "apply_angular_impulse".to_string(),
+            arity: 3,
+            func: |args| {
+                let world_id = expect_id(&args[0], "World ID")?;
+                let object_id = expect_id(&args[1], "Object ID")?;
+                let impulse = expect_vec3(&args[2], "Angular impulse")?;
+
+                with_world(world_id, |world| {
+                    if world.apply_angular_impulse(object_id, impulse) {
+                        Ok(Value::Unit)
+                    } else {
+                        Err(object_not_found())
+                    }
+                })
+            },
+        },
+    );
+
+    // Principal moments of inertia about the body's local axes; `null` restores unit
+    // inertia per unit mass
+    interpreter.environment.define(
+        "set_inertia".to_string(),
+        Value::BuiltinFunction {
+            name: "set_inertia".to_string(),
+            arity: 3,
+            func: |args| {
+                let world_id = expect_id(&args[0], "World ID")?;
+                let object_id = expect_id(&args[1], "Object ID")?;
+                let moments = match &args[2] {
+                    Value::Unit => None,
+                    value => {
+                        let moments = expect_vec3(value, "Moments of inertia")?;
+                        if ![moments.x, moments.y, moments.z]
+                            .iter()
+                            .all(|m| m.is_finite() && *m > 0.0)
+                        {
+                            return Err(RuntimeError::Generic {
+                                message: "Moments of inertia must be positive".to_string(),
+                            });
+                        }
+                        Some(moments)
+                    }
+                };
+
+                with_world(world_id, |world| {
+                    if world.set_inertia(object_id, moments) {
+                        Ok(Value::Unit)
+                    } else {
+                        Err(object_not_found())
+                    }
+                })
+            },
+        },
+    );
+
+    // World-space angular momentum, conserved while no torque acts
+    interpreter.environment.define(
+        "get_angular_momentum".to_string(),
+        Value::BuiltinFunction {
+            name: "get_angular_momentum".to_string(),
+            arity: 2,
+            func: |args| {
+                let world_id = expect_id(&args[0], "World ID")?;
+                let object_id = expect_id(&args[1], "Object ID")?;
+                with_world(world_id, |world| {
+                    world
+                        .objects
+                        .get(object_id)
+                        .map(|obj| vec3_to_value(obj.angular_momentum()))
+                        .ok_or_else(object_not_found)
+                })
+            },
+        },
+    );
+
+    // Spin a body so it has the given world-space angular momentum
+    interpreter.environment.define(
+        "set_angular_momentum".to_string(),
+        Value::BuiltinFunction {
+            name: "set_angular_momentum".to_string(),
+            arity: 3,
+            func: |args| {
+                let world_id = expect_id(&args[0], "World ID")?;
+                let object_id = expect_id(&args[1], "Object ID")?;
+                let momentum = expect_vec3(&args[2], "Angular momentum")?;
+                with_world(world_id, |world| {
+                    if world.set_angular_momentum(object_id, momentum) {
+                        Ok(Value::Unit)
+                    } else {
+                        Err(object_not_found())
+                    }
+                })
+            },
+        },
+    );
+
+    // Keep two objects at their current distance: (world, object_a, object_b)
+    interpreter.environment.define(
+        "add_distance_constraint".to_string(),
+        Value::BuiltinFunction {
+            name: "add_distance_constraint".to_string(),
+            arity: 3,
+            func: |args| {
+                let world_id = expect_id(&args[0], "World ID")?;
+                let body_a = expect_id(&args[1], "Object ID")?;
+                let body_b = expect_id(&args[2], "Object ID")?;
+
+                with_world(world_id, |world| {
+                    world
+                        .add_distance_constraint(body_a, body_b)
+                        .map(|index| Value::Int(index as i64))
+                        .ok_or_else(object_not_found)
+                })
+            },
+        },
+    );
+
+    // Let object_b swing about an axis through a pivot on object_a:
+    // (world, object_a, object_b, pivot, axis)
+    interpreter.environment.define(
+        "add_hinge".to_string(),
+        Value::BuiltinFunction {
+            name: "add_hinge".to_string(),
+            arity: 5,
+            func: |args| {
+                let world_id = expect_id(&args[0], "World ID")?;
+                let body_a = expect_id(&args[1], "Object ID")?;
+                let body_b = expect_id(&args[2], "Object ID")?;
+                let pivot = expect_vec3(&args[3], "Hinge pivot")?;
+                let axis = expect_vec3(&args[4], "Hinge axis")?;
+                if axis.length() == 0.0 {
+                    return Err(RuntimeError::Generic {
+                        message: "Hinge axis must not be zero".to_string(),
+                    });
+                }
+
+                with_world(world_id, |world| {
+                    world
+                        .add_hinge(body_a, body_b, pivot, axis)
+                        .map(|index| Value::Int(index as i64))
+                        .ok_or_else(object_not_found)
+                })
+            },
+        },
+    );
+
+    // Create a rope/chain between two points
+    interpreter.environment.define(
+        "add_rope".to_string(),
+        Value::BuiltinFunction {
+            name: "add_rope".to_string(),
+            arity: 4,
+            func: |args| {
+                let world_id = expect_id(&args[0], "World ID")?;
+                let start = expect_vec3(&args[1], "Rope start")?;
+                let end = expect_vec3(&args[2], "Rope end")?;
+                let segments = expect_id(&args[3], "Segment count")?;
+                if segments == 0 {
+                    return Err(RuntimeError::Generic {
+                        message: "Rope needs at least one segment".to_string(),
+                    });
+                }
+
+                with_world(world_id, |world| {
+                    Ok(Value::Int(world.add_rope(start, end, segments) as i64))
+                })
+            },
+        },
+    );
+
+    // List the body ids making up a rope, from start to end
+    interpreter.environment.define(
+        "rope_segments".to_string(),
+        Value::BuiltinFunction {
+            name: "rope_segments".to_string(),
+            arity: 2,
+            func: |args| {
+                let world_id = expect_id(&args[0], "World ID")?;
+                let rope_id = expect_id(&args[1], "Rope ID")?;
+
+                with_world(world_id, |world| match world.ropes.get(rope_id) {
+                    Some(rope) => Ok(Value::Array(
+                        rope.segments
+                            .iter()
+                            .map(|&id| Value::Int(id as i64))
+                            .collect(),
+                    )),
+                    None => Err(RuntimeError::Generic {
+                        message: "Rope not found".to_string(),
+                    }),
+                })
+            },
+        },
+    );
+
+    // Create a flat tearable cloth from a corner, `width` along x and `height` along z
+    interpreter.environment.define(
+        "add_cloth".to_string(),
+        Value::BuiltinFunction {
+            name: "add_cloth".to_string(),
+            arity: 5,
+            func: |args| {
+                let world_id = expect_id(&args[0], "World ID")?;
+                let corner = expect_vec3(&args[1], "Cloth corner")?;
+                let width = expect_number(&args[2], "Cloth width")?;
+                let height = expect_number(&args[3], "Cloth height")?;
+                let resolution = expect_id(&args[4], "Cloth resolution")?;
+                if !(width > 0.0 && height > 0.0) {
+                    return Err(RuntimeError::Generic {
+                        message: "Cloth width and height must be positive".to_string(),
+                    });
+                }
+                if resolution == 0 {
+                    return Err(RuntimeError::Generic {
+                        message: "Cloth needs a resolution of at least one cell".to_string(),
+                    });
+                }
+
+                with_world(world_id, |world| {
+                    Ok(Value::Int(
+                        world.add_cloth(corner, width, height, resolution) as i64,
+                    ))
+                })
+            },
+        },
+    );
+
+    // List the body ids making up a cloth, row by row
+    interpreter.environment.define(
+        "cloth_particles".to_string(),
+        Value::BuiltinFunction {
+            name: "cloth_particles".to_string(),
+            arity: 2,
+            func: |args| {
+                let world_id = expect_id(&args[0], "World ID")?;
+                let cloth_id = expect_id(&args[1], "Cloth ID")?;
+
+                with_world(world_id, |world| match world.cloths.get(cloth_id) {
+                    Some(cloth) => Ok(Value::Array(
+                        cloth
+                            .particles
+                            .iter()
+                            .map(|&id| Value::Int(id as i64))
+                            .collect(),
+                    )),
+                    None => Err(cloth_not_found()),
+                })
+            },
+        },
+    );
+
+    // Number of cloth links that have not torn
+    interpreter.environment.define(
+        "cloth_constraint_count".to_string(),
+        Value::BuiltinFunction {
+            name: "cloth_constraint_count".to_string(),
+            arity: 2,
+            func: |args| {
+                let world_id = expect_id(&args[0], "World ID")?;
+                let cloth_id = expect_id(&args[1], "Cloth ID")?;
+
+                with_world(world_id, |world| {
+                    world
+                        .cloth_constraint_count(cloth_id)
+                        .map(|count| Value::Int(count as i64))
+                        .ok_or_else(cloth_not_found)
+                })
+            },
+        },
+    );
+
+    // Sample a body's position after every step from now on
+    interpreter.environment.define(
+        "record_trajectory".to_string(),
+        Value::BuiltinFunction {
+            name: "record_trajectory".to_string(),
+            arity: 2,
+            func: |args| {
+                let world_id = expect_id(&args[0], "World ID")?;
+                let object_id = expect_id(&args[1], "Object ID")?;
+                with_world(world_id, |world| {
+                    if world.start_recording(object_id) {
+                        Ok(Value::Unit)
+                    } else {
+                        Err(object_not_found())
+                    }
+                })
+            },
+        },
+    );
+
+    // Samples recorded so far as `[time, x, y, z]` rows, time counted from the start
+    interpreter.environment.define(
+        "recorded_trajectory".to_string(),
+        Value::BuiltinFunction {
+            name: "recorded_trajectory".to_string(),
+            arity: 2,
+            func: |args| {
+                let world_id = expect_id(&args[0], "World ID")?;
+                let object_id = expect_id(&args[1], "Object ID")?;
+                with_world(world_id, |world| {
+                    match world.recorded_trajectory(object_id) {
+                        Some(trajectory) => Ok(trajectory_to_value(&trajectory)),
+                        None => Err(RuntimeError::Generic {
+                            message: "Body is not being recorded".to_string(),
+                        }),
+                    }
+                })
+            },
+        },
+    );
+
+    // play_trajectory(world, body, trajectory, speed[, looping]): drive a body along
+    // `[time, x, y, z]` samples as a kinematic body, stopping at the end unless looping
+    interpreter.environment.define(
+        "play_trajectory".to_string(),
+        Value::BuiltinFunction {
+            name: "play_trajectory".to_string(),
+            arity: VARIADIC,
+            func: |args| {
+                if !(4..=5).contains(&args.len()) {
+                    return Err(RuntimeError::FunctionCallError {
+                        message: format!(
+                            "play_trajectory expects a world, a body, a trajectory, a speed \
+                             and an optional looping flag, got {} arguments",
+                            args.len()
+                        ),
+                    });
+                }
+                let world_id = expect_id(&args[0], "World ID")?;
+                let object_id = expect_id(&args[1], "Object ID")?;
+                let trajectory = expect_trajectory(&args[2])?;
+                let speed = expect_number(&args[3], "Playback speed")?;
+                if !(speed.is_finite() && speed > 0.0) {
+                    return Err(RuntimeError::Generic {
+                        message: format!("Playback speed must be positive, got {}", speed),
+                    });
+                }
+                let looping = match args.get(4) {
+                    Some(flag) => expect_bool(flag, "Looping flag")?,
+                    None => false,
+                };
+
+                with_world(world_id, |world| {
+                    if world.play_trajectory(object_id, trajectory, speed, looping) {
+                        Ok(Value::Unit)
+                    } else {
+                        Err(object_not_found())
+                    }
+                })
+            },
+        },
+    );
+
+    interpreter.environment.define(
+        "is_playing_trajectory".to_string(),
+        Value::BuiltinFunction {
+            name: "is_playing_trajectory".to_string(),
+            arity: 2,
+            func: |args| {
+                let world_id = expect_id(&args[0], "World ID")?;
+                let object_id = expect_id(&args[1], "Object ID")?;
+                with_world(world_id, |world| {
+                    Ok(Value::Bool(world.is_playing(object_id)))
+                })
+            },
+        },
+    );
+
+    // Pin an object in place, e.g. a rope endpoint
+    interpreter.environment.define(
+        "set_pinned".to_string(),
+        Value::BuiltinFunction {
+            name: "set_pinned".to_string(),
+            arity: 3,
+            func: |args| {
+                let world_id = expect_id(&args[0], "World ID")?;
+                let object_id = expect_id(&args[1], "Object ID")?;
+                let pinned = match &args[2] {
+                    Value::Bool(b) => *b,
+                    _ => {
+                        return Err(RuntimeError::TypeError {
+                            message: "Pinned flag must be boolean".to_string(),
+                        })
+                    }
+                };
+
+                with_world(world_id, |world| {
+                    if world.set_pinned(object_id, pinned) {
+                        Ok(Value::Unit)
+                    } else {
+                        Err(object_not_found())
+                    }
+                })
+            },
+        },
+    );
+
+    // Call `callback(body_a, body_b, contact)` for every collision during `physics_step`
+    interpreter.environment.define(
+        "on_collision".to_string(),
+        Value::BuiltinFunction {
+            name: "on_collision".to_string(),
+            arity: 2,
+            func: |args| {
+                let world_id = expect_id(&args[0], "World ID")?;
+                let takes_three_args = match &args[1] {
+                    Value::Function { params, .. } => params.len() == 3,
+                    Value::BuiltinFunction { arity, .. }
+                    | Value::InterpreterFunction { arity, .. } => *arity == 3,
+                    _ => false,
+                };
+                if !takes_three_args {
+                    return Err(RuntimeError::TypeError {
+                        message:
+                            "Collision callback must be a function of (body_a, body_b, contact)"
+                                .to_string(),
+                    });
+                }
+
+                with_world(world_id, |_| Ok(()))?;
+                COLLISION_CALLBACKS
+                    .lock()
+                    .unwrap()
+                    .entry(world_id)
+                    .or_default()
+                    .push(args[1].clone());
+                Ok(Value::Unit)
+            },
+        },
+    );
+
+    // Decide per pair whether two bodies collide: `filter(body_a, body_b)` returns false to
+    // let them pass through each other. Only pairs close enough to touch are asked.
+    interpreter.environment.define(
+        "set_collision_filter".to_string(),
+        Value::BuiltinFunction {
+            name: "set_collision_filter".to_string(),
+            arity: 2,
+            func: |args| {
+                let world_id = expect_id(&args[0], "World ID")?;
+                let takes_two_args = match &args[1] {
+                    Value::Function { params, .. } => params.len() == 2,
+                    Value::BuiltinFunction { arity, .. }
+                    | Value::InterpreterFunction { arity, .. } => *arity == 2,
+                    _ => false,
+                };
+                if !takes_two_args {
+                    return Err(RuntimeError::TypeError {
+                        message: "Collision filter must be a function of (body_a, body_b)"
+                            .to_string(),
+                    });
+                }
+
+                with_world(world_id, |_| Ok(()))?;
+                COLLISION_FILTERS
+                    .lock()
+                    .unwrap()
+                    .insert(world_id, args[1].clone());
+                Ok(Value::Unit)
+            },
+        },
+    );
+
+    // Environmental force from a script function `field(position) -> force`, evaluated
+    // for every movable body each step; `null` removes the world's field
+    interpreter.environment.define(
+        "set_force_field".to_string(),
+        Value::BuiltinFunction {
+            name: "set_force_field".to_string(),
+            arity: 2,
+            func: |args| {
+                let world_id = expect_id(&args[0], "World ID")?;
+                let field = match &args[1] {
+                    Value::Unit => None,
+                    Value::Function { params, .. } if params.len() == 1 => Some(args[1].clone()),
+                    Value::BuiltinFunction { arity: 1, .. }
+                    | Value::InterpreterFunction { arity: 1, .. } => Some(args[1].clone()),
+                    _ => {
+                        return Err(RuntimeError::TypeError {
+                            message: "Force field must be a function of (position) or null"
+                                .to_string(),
+                        })
+                    }
+                };
+
+                with_world(world_id, |world| {
+                    world.set_force_field(None);
+                    Ok(())
+                })?;
+                let mut fields = SCRIPT_FORCE_FIELDS.lock().unwrap();
+                match field {
+                    Some(field) => fields.insert(world_id, field),
+                    None => fields.remove(&world_id),
+                };
+                Ok(Value::Unit)
+            },
+        },
+    );
+
+    // Uniform wind: the same force on every movable body
+    interpreter.environment.define(
+        "set_wind".to_string(),
+        Value::BuiltinFunction {
+            name: "set_wind".to_string(),
+            arity: 2,
+            func: |args| {
+                let world_id = expect_id(&args[0], "World ID")?;
+                let force = expect_vec3(&args[1], "Wind force")?;
+                set_preset_force_field(world_id, ForceField::Uniform { force })
+            },
+        },
+    );
+
+    // Pull every movable body towards `center` with a force of `strength` (negative repels)
+    interpreter.environment.define(
+        "set_attractor".to_string(),
+        Value::BuiltinFunction {
+            name: "set_attractor".to_string(),
+            arity: 3,
+            func: |args| {
+                let world_id = expect_id(&args[0], "World ID")?;
+                let center = expect_vec3(&args[1], "Attractor center")?;
+                let strength = expect_number(&args[2], "Attractor strength")?;
+                set_preset_force_field(world_id, ForceField::Radial { center, strength })
+            },
+        },
+    );
+
+    // A body's full dynamic state as one `BodyState` struct
+    interpreter.environment.define(
+        "get_body_state".to_string(),
+        Value::BuiltinFunction {
+            name: "get_body_state".to_string(),
+            arity: 2,
+            func: |args| {
+                let world_id = expect_id(&args[0], "World ID")?;
+                let object_id = expect_id(&args[1], "Object ID")?;
+                with_world(world_id, |world| {
+                    let object = world.objects.get(object_id).ok_or_else(object_not_found)?;
+                    Ok(body_state(object))
+                })
+            },
+        },
+    );
+
+    // Apply the fields present in a `BodyState`-like struct; all are checked before any
+    // is written
+    interpreter.environment.define(
+        "set_body_state".to_string(),
+        Value::BuiltinFunction {
+            name: "set_body_state".to_string(),
+            arity: 3,
+            func: |args| {
+                let world_id = expect_id(&args[0], "World ID")?;
+                let object_id = expect_id(&args[1], "Object ID")?;
+                let update = BodyStateUpdate::from_value(&args[2])?;
+                with_world(world_id, |world| {
+                    let object = world
+                        .objects
+                        .get_mut(object_id)
+                        .ok_or_else(object_not_found)?;
+                    update.apply(object);
+                    Ok(Value::Unit)
+                })
+            },
+        },
+    );
+
+    // Stable hash of all body state, for checking a scenario against a recorded end
+    // state; the u64 hash is returned with its bits reinterpreted as an Int
+    interpreter.environment.define(
+        "physics_state_hash".to_string(),
+        Value::BuiltinFunction {
+            name: "physics_state_hash".to_string(),
+            arity: 1,
+            func: |args| {
+                let world_id = expect_id(&args[0], "World ID")?;
+                with_world(world_id, |world| Ok(Value::Int(world.state_hash() as i64)))
+            },
+        },
+    );
+
+    // Switch gravity off and on again; the gravity vector itself is kept
+    interpreter.environment.define(
+        "set_gravity_enabled".to_string(),
+        Value::BuiltinFunction {
+            name: "set_gravity_enabled".to_string(),
+            arity: 2,
+            func: |args| {
+                let world_id = expect_id(&args[0], "World ID")?;
+                let enabled = expect_bool(&args[1], "Gravity flag")?;
+                with_world(world_id, |world| {
+                    world.set_gravity_enabled(enabled);
+                    Ok(Value::Unit)
+                })
+            },
+        },
+    );
+
+    // Freeze or resume a single body while the rest of the world simulates
+    interpreter.environment.define(
+        "set_body_active".to_string(),
+        Value::BuiltinFunction {
+            name: "set_body_active".to_string(),
+            arity: 3,
+            func: |args| {
+                let world_id = expect_id(&args[0], "World ID")?;
+                let object_id = expect_id(&args[1], "Object ID")?;
+                let active = match &args[2] {
+                    Value::Bool(b) => *b,
+                    _ => {
+                        return Err(RuntimeError::TypeError {
+                            message: "Active flag must be boolean".to_string(),
+                        })
+                    }
+                };
+
+                with_world(world_id, |world| {
+                    if world.set_active(object_id, active) {
+                        Ok(Value::Unit)
+                    } else {
+                        Err(object_not_found())
+                    }
+                })
+            },
+        },
+    );
+
+    // Freeze individual axes: (world, body, lock_x, lock_y, lock_z, lock_rot_x,
+    // lock_rot_y, lock_rot_z)
+    interpreter.environment.define(
+        "set_dof_lock".to_string(),
+        Value::BuiltinFunction {
+            name: "set_dof_lock".to_string(),
+            arity: 8,
+            func: |args| {
+                let world_id = expect_id(&args[0], "World ID")?;
+                let object_id = expect_id(&args[1], "Object ID")?;
+                let mut flags = [false; 6];
+                for (flag, arg) in flags.iter_mut().zip(&args[2..]) {
+                    *flag = expect_bool(arg, "Axis lock")?;
+                }
+                let lock = DofLock {
+                    linear: [flags[0], flags[1], flags[2]],
+                    angular: [flags[3], flags[4], flags[5]],
+                };
+
+                with_world(world_id, |world| {
+                    if world.set_dof_lock(object_id, lock) {
+                        Ok(Value::Unit)
+                    } else {
+                        Err(object_not_found())
+                    }
+                })
+            },
+        },
+    );
+
+    // Attach any script value (e.g. a struct of game state) to a body
+    interpreter.environment.define(
+        "set_user_data".to_string(),
+        Value::BuiltinFunction {
+            name: "set_user_data".to_string(),
+            arity: 3,
+            func: |args| {
+                let world_id = expect_id(&args[0], "World ID")?;
+                let object_id = expect_id(&args[1], "Object ID")?;
+
+                with_world(world_id, |world| {
+                    if world.set_user_data(object_id, args[2].clone()) {
+                        Ok(Value::Unit)
+                    } else {
+                        Err(object_not_found())
+                    }
+                })
+            },
+        },
+    );
+
+    // The value attached with `set_user_data`, or unit if there is none
+    interpreter.environment.define(
+        "get_user_data".to_string(),
+        Value::BuiltinFunction {
+            name: "get_user_data".to_string(),
+            arity: 2,
+            func: |args| {
+                let world_id = expect_id(&args[0], "World ID")?;
+                let object_id = expect_id(&args[1], "Object ID")?;
+
+                with_world(world_id, |world| {
+                    if object_id >= world.objects.len() {
+                        return Err(object_not_found());
+                    }
+                    Ok(world
+                        .user_data
+                        .get(&object_id)
+                        .cloned()
+                        .unwrap_or(Value::Unit))
+                })
+            },
+        },
+    );
+
+    // Make a body a one-way platform that only blocks bodies approaching against the
+    // given direction (e.g. [0, 1, 0] to land on top); a zero vector clears it
+    interpreter.environment.define(
+        "set_one_way".to_string(),
+        Value::BuiltinFunction {
+            name: "set_one_way".to_string(),
+            arity: 3,
+            func: |args| {
+                let world_id = expect_id(&args[0], "World ID")?;
+                let object_id = expect_id(&args[1], "Object ID")?;
+                let direction = expect_vec3(&args[2], "One-way direction")?;
+
+                with_world(world_id, |world| {
+                    if world.set_one_way(object_id, Some(direction)) {
+                        Ok(Value::Unit)
+                    } else {
+                        Err(object_not_found())
+                    }
+                })
+            },
+        },
+    );
+
+    // Surface response: (world, object, restitution in [0, 1], friction coefficient)
+    interpreter.environment.define(
+        "set_material".to_string(),
+        Value::BuiltinFunction {
+            name: "set_material".to_string(),
+            arity: 4,
+            func: |args| {
+                let world_id = expect_id(&args[0], "World ID")?;
+                let object_id = expect_id(&args[1], "Object ID")?;
+                let restitution = expect_number(&args[2], "Restitution")?;
+                let friction = expect_number(&args[3], "Friction")?;
+
+                if !(0.0..=1.0).contains(&restitution) {
+                    return Err(RuntimeError::Generic {
+                        message: format!("Restitution must be in [0, 1], got {}", restitution),
+                    });
+                }
+                if !(friction.is_finite() && friction >= 0.0) {
+                    return Err(RuntimeError::Generic {
+                        message: format!("Friction must be non-negative, got {}", friction),
+                    });
+                }
+
+                with_world(world_id, |world| {
+                    let material = Material {
+                        restitution,
+                        friction,
+                    };
+                    if world.set_material(object_id, material) {
+                        Ok(Value::Unit)
+                    } else {
+                        Err(object_not_found())
+                    }
+                })
+            },
+        },
+    );
+
+    // Cap a body's linear and angular speed; 0 means unlimited
+    interpreter.environment.define(
+        "set_velocity_limits".to_string(),
+        Value::BuiltinFunction {
+            name: "set_velocity_limits".to_string(),
+            arity: 4,
+            func: |args| {
+                let world_id = expect_id(&args[0], "World ID")?;
+                let object_id = expect_id(&args[1], "Object ID")?;
+                let max_linear = expect_number(&args[2], "Max linear speed")?;
+                let max_angular = expect_number(&args[3], "Max angular speed")?;
+
+                with_world(world_id, |world| {
+                    if world.set_velocity_limits(object_id, max_linear, max_angular) {
+                        Ok(Value::Unit)
+                    } else {
+                        Err(object_not_found())
+                    }
+                })
+            },
+        },
+    );
+
+    // A body's speed limits as [max_linear, max_angular]
+    interpreter.environment.define(
+        "get_velocity_limits".to_string(),
+        Value::BuiltinFunction {
+            name: "get_velocity_limits".to_string(),
+            arity: 2,
+            func: |args| {
+                let world_id = expect_id(&args[0], "World ID")?;
+                let object_id = expect_id(&args[1], "Object ID")?;
+
+                with_world(world_id, |world| match world.objects.get(object_id) {
+                    Some(obj) => Ok(Value::Array(vec![
+                        Value::Float(obj.max_linear_speed),
+                        Value::Float(obj.max_angular_speed),
+                    ])),
+                    None => Err(object_not_found()),
+                })
+            },
+        },
+    );
+
+    // Switch a world's solver settings to the "fast", "balanced" or "accurate" preset
+    interpreter.environment.define(
+        "set_quality_preset".to_string(),
+        Value::BuiltinFunction {
+            name: "set_quality_preset".to_string(),
+            arity: 2,
+            func: |args| {
+                let world_id = expect_id(&args[0], "World ID")?;
+                let preset = match &args[1] {
+                    Value::String(name) => {
+                        QualityPreset::from_name(name).ok_or_else(|| RuntimeError::Generic {
+                            message: format!(
+                                "Unknown quality preset '{}', expected fast, balanced or accurate",
+                                name
+                            ),
+                        })?
+                    }
+                    _ => {
+                        return Err(RuntimeError::TypeError {
+                            message: "Quality preset must be a string".to_string(),
+                        })
+                    }
+                };
+
+                with_world(world_id, |world| {
+                    world.set_quality_preset(preset);
+                    Ok(Value::Unit)
+                })
+            },
+        },
+    );
+
+    // Choose how positions are advanced: "explicit_euler", "semi_implicit_euler" or "rk4"
+    interpreter.environment.define(
+        "set_integrator".to_string(),
+        Value::BuiltinFunction {
+            name: "set_integrator".to_string(),
+            arity: 2,
+            func: |args| {
+                let world_id = expect_id(&args[0], "World ID")?;
+                let method = match &args[1] {
+                    Value::String(name) => IntegrationMethod::from_name(name).ok_or_else(|| {
+                        RuntimeError::Generic {
+                            message: format!(
+                                "Unknown integrator '{}', expected explicit_euler, semi_implicit_euler or rk4",
+                                name
+                            ),
+                        }
+                    })?,
+                    _ => {
+                        return Err(RuntimeError::TypeError {
+                            message: "Integrator must be a string".to_string(),
+                        })
+                    }
+                };
+
+                with_world(world_id, |world| {
+                    world.integrator = method;
+                    Ok(Value::Unit)
+                })
+            },
+        },
+    );
+
+    // Tune contact stabilization: Baumgarte factor, allowed overlap and solver passes
+    interpreter.environment.define(
+        "set_solver_params".to_string(),
+        Value::BuiltinFunction {
+            name: "set_solver_params".to_string(),
+            arity: 5,
+            func: |args| {
+                let world_id = expect_id(&args[0], "World ID")?;
+                let baumgarte = expect_number(&args[1], "Baumgarte factor")?;
+                let slop = expect_number(&args[2], "Penetration slop")?;
+                let velocity_iterations = expect_iterations(&args[3], "Velocity iterations")?;
+                let position_iterations = expect_iterations(&args[4], "Position iterations")?;
+
+                if !(0.0..=1.0).contains(&baumgarte) {
+                    return Err(RuntimeError::Generic {
+                        message: format!("Baumgarte factor must be in [0, 1], got {}", baumgarte),
+                    });
+                }
+                if slop.is_nan() || slop < 0.0 {
+                    return Err(RuntimeError::Generic {
+                        message: format!("Penetration slop must be non-negative, got {}", slop),
+                    });
+                }
+
+                with_world(world_id, |world| {
+                    world.set_solver_params(
+                        baumgarte,
+                        slop,
+                        velocity_iterations,
+                        position_iterations,
+                    );
+                    Ok(Value::Unit)
+                })
+            },
+        },
+    );
+
+    // Edge length of the broad-phase grid cells; roughly the size of a typical body
+    interpreter.environment.define(
+        "set_spatial_hash_cell_size".to_string(),
+        Value::BuiltinFunction {
+            name: "set_spatial_hash_cell_size".to_string(),
+            arity: 2,
+            func: |args| {
+                let world_id = expect_id(&args[0], "World ID")?;
+                let cell_size = expect_number(&args[1], "Cell size")?;
+                if !(cell_size.is_finite() && cell_size > 0.0) {
+                    return Err(RuntimeError::Generic {
+                        message: format!(
+                            "Spatial hash cell size must be positive and finite, got {}",
+                            cell_size
+                        ),
+                    });
+                }
+
+                with_world(world_id, |world| {
+                    world.settings.spatial_hash_cell_size = cell_size;
+                    Ok(Value::Unit)
+                })
+            },
+        },
+    );
+
+    // Set an object's collision half-extents (radius in x for spheres)
+    interpreter.environment.define(
+        "set_object_size".to_string(),
+        Value::BuiltinFunction {
+            name: "set_object_size".to_string(),
+            arity: 3,
+            func: |args| {
+                let world_id = expect_id(&args[0], "World ID")?;
+                let object_id = expect_id(&args[1], "Object ID")?;
+                let half_extents = expect_vec3(&args[2], "Half extents")?;
+
+                with_world(world_id, |world| {
+                    if world.set_size(object_id, half_extents) {
+                        Ok(Value::Unit)
+                    } else {
+                        Err(object_not_found())
+                    }
+                })
+            },
+        },
+    );
+
+    // Attach a fan of distance rays to an object: (world, object, ray_count, fan_angle, range)
+    interpreter.environment.define(
+        "add_sensor".to_string(),
+        Value::BuiltinFunction {
+            name: "add_sensor".to_string(),
+            arity: 5,
+            func: |args| {
+                let world_id = expect_id(&args[0], "World ID")?;
+                let object_id = expect_id(&args[1], "Object ID")?;
+                let ray_count = expect_id(&args[2], "Ray count")?;
+                let fan_angle = expect_number(&args[3], "Fan angle")?;
+                let range = expect_number(&args[4], "Sensor range")?;
+                if ray_count == 0 || range <= 0.0 {
+                    return Err(RuntimeError::Generic {
+                        message: "Sensor needs at least one ray and a positive range".to_string(),
+                    });
+                }
+
+                with_world(world_id, |world| {
+                    match world.add_sensor(object_id, ray_count, fan_angle, range) {
+                        Some(id) => Ok(Value::Int(id as i64)),
+                        None => Err(object_not_found()),
+                    }
+                })
+            },
+        },
+    );
+
+    // Latest hit distance per sensor ray (the sensor range where nothing was hit)
+    interpreter.environment.define(
+        "sensor_readings".to_string(),
+        Value::BuiltinFunction {
+            name: "sensor_readings".to_string(),
+            arity: 2,
+            func: |args| {
+                let world_id = expect_id(&args[0], "World ID")?;
+                let sensor_id = expect_id(&args[1], "Sensor ID")?;
+
+                with_world(world_id, |world| match world.sensors.get(sensor_id) {
+                    Some(sensor) => Ok(Value::Array(
+                        sensor.readings.iter().map(|&d| Value::Float(d)).collect(),
+                    )),
+                    None => Err(RuntimeError::Generic {
+                        message: "Sensor not found".to_string(),
+                    }),
+                })
+            },
+        },
+    );
+
+    // Ids of bodies whose bounds touch or intersect a box region: (world, center, half_extents)
+    interpreter.environment.define(
+        "overlap_box".to_string(),
+        Value::BuiltinFunction {
+            name: "overlap_box".to_string(),
+            arity: 3,
+            func: |args| {
+                let world_id = expect_id(&args[0], "World ID")?;
+                let center = expect_vec3(&args[1], "Region center")?;
+                let half_extents = expect_vec3(&args[2], "Region half extents")?;
+                if half_extents.x < 0.0 || half_extents.y < 0.0 || half_extents.z < 0.0 {
+                    return Err(RuntimeError::Generic {
+                        message: "Region half extents must not be negative".to_string(),
+                    });
+                }
+
+                with_world(world_id, |world| {
+                    Ok(Value::Array(
+                        world
+                            .overlap_box(center, half_extents)
+                            .into_iter()
+                            .map(|id| Value::Int(id as i64))
+                            .collect(),
+                    ))
+                })
+            },
+        },
+    );
+
+    // Nearest hit along a ray as a `RayHit` struct with `object` (`None` for the ground),
+    // `distance`, `point` and `normal`, or unit when nothing is hit:
+    // (world, origin, direction, max_distance)
+    interpreter.environment.define(
+        "raycast".to_string(),
+        Value::BuiltinFunction {
+            name: "raycast".to_string(),
+            arity: 4,
+            func: |args| {
+                let world_id = expect_id(&args[0], "World ID")?;
+                let origin = expect_vec3(&args[1], "Ray origin")?;
+                let direction = expect_vec3(&args[2], "Ray direction")?;
+                let max_distance = expect_number(&args[3], "Max distance")?;
+                if direction.length() == 0.0 {
+                    return Err(RuntimeError::Generic {
+                        message: "Ray direction must not be zero".to_string(),
+                    });
+                }
+
+                with_world(world_id, |world| {
+                    let Some(hit) = world.raycast(origin, direction, max_distance) else {
+                        return Ok(Value::Unit);
+                    };
+                    let object = hit.object_id.map(|id| Box::new(Value::Int(id as i64)));
+                    Ok(Value::Struct {
+                        name: "RayHit".to_string(),
+                        fields: HashMap::from([
+                            ("object".to_string(), Value::Option(object)),
+                            ("distance".to_string(), Value::Float(hit.distance)),
+                            ("point".to_string(), vec3_to_value(hit.point)),
+                            ("normal".to_string(), vec3_to_value(hit.normal)),
+                        ]),
+                    })
+                })
+            },
+        },
+    );
+
+    // Contact manifold between two bodies from the last step as a struct with
+    // `normal`, `points` and `depths`, or unit when they are not touching
+    interpreter.environment.define(
+        "get_manifold".to_string(),
+        Value::BuiltinFunction {
+            name: "get_manifold".to_string(),
+            arity: 3,
+            func: |args| {
+                let world_id = expect_id(&args[0], "World ID")?;
+                let body_a = expect_id(&args[1], "Object ID")?;
+                let body_b = expect_id(&args[2], "Object ID")?;
+
+                with_world(world_id, |world| {
+                    if body_a >= world.objects.len() || body_b >= world.objects.len() {
+                        return Err(object_not_found());
+                    }
+                    let Some(manifold) = world.manifold(body_a, body_b) else {
+                        return Ok(Value::Unit);
+                    };
+
+                    let mut fields = HashMap::new();
+                    fields.insert("normal".to_string(), vec3_to_value(manifold.normal));
+                    fields.insert(
+                        "points".to_string(),
+                        Value::Array(
+                            manifold
+                                .points
+                                .iter()
+                                .map(|p| vec3_to_value(p.position))
+                                .collect(),
+                        ),
+                    );
+                    fields.insert(
+                        "depths".to_string(),
+                        Value::Array(
+                            manifold
+                                .points
+                                .iter()
+                                .map(|p| Value::Float(p.depth))
+                                .collect(),
+                        ),
+                    );
+                    Ok(Value::Struct {
+                        name: "Manifold".to_string(),
+                        fields,
+                    })
+                })
+            },
+        },
+    );
+
+    // Gap between two bodies and the closest point on each, as [distance, point_a, point_b]
+    interpreter.environment.define(
+        "distance_between".to_string(),
+        Value::BuiltinFunction {
+            name: "distance_between".to_string(),
+            arity: 3,
+            func: |args| {
+                let world_id = expect_id(&args[0], "World ID")?;
+                let body_a = expect_id(&args[1], "Object ID")?;
+                let body_b = expect_id(&args[2], "Object ID")?;
+
+                with_world(world_id, |world| {
+                    match world.distance_between(body_a, body_b) {
+                        Some(result) => Ok(Value::Array(vec![
+                            Value::Float(result.distance),
+                            vec3_to_value(result.point_a),
+                            vec3_to_value(result.point_b),
+                        ])),
+                        None => Err(object_not_found()),
+                    }
+                })
+            },
+        },
+    );
+
+    // Simulated seconds accumulated by stepping a world
+    interpreter.environment.define(
+        "get_time".to_string(),
+        Value::BuiltinFunction {
+            name: "get_time".to_string(),
+            arity: 1,
+            func: |args| {
+                let world_id = expect_id(&args[0], "World ID")?;
+                with_world(world_id, |world| Ok(Value::Float(world.time)))
+            },
+        },
+    );
+
+    // Restart a world's simulation clock at zero
+    interpreter.environment.define(
+        "reset_time".to_string(),
+        Value::BuiltinFunction {
+            name: "reset_time".to_string(),
+            arity: 1,
+            func: |args| {
+                let world_id = expect_id(&args[0], "World ID")?;
+                with_world(world_id, |world| {
+                    world.reset_time();
+                    Ok(Value::Unit)
+                })
+            },
+        },
+    );
+
+    // Particle emitter stepped with the world: (world, position, rate_per_second, lifetime)
+    interpreter.environment.define(
+        "add_particle_system".to_string(),
+        Value::BuiltinFunction {
+            name: "add_particle_system".to_string(),
+            arity: 4,
+            func: |args| {
+                let world_id = expect_id(&args[0], "World ID")?;
+                let position = expect_vec3(&args[1], "Emitter position")?;
+                let rate = expect_number(&args[2], "Emission rate")?;
+                let lifetime = expect_number(&args[3], "Particle lifetime")?;
+                if rate < 0.0 || lifetime <= 0.0 {
+                    return Err(RuntimeError::Generic {
+                        message: "Emission rate must be non-negative and lifetime positive"
+                            .to_string(),
+                    });
+                }
+
+                with_world(world_id, |world| {
+                    Ok(Value::Int(
+                        world.add_particle_system(position, rate, lifetime) as i64,
+                    ))
+                })
+            },
+        },
+    );
+
+    // Initial particle velocity, per-component random spread and gravity scale:
+    // (world, system, velocity, spread, gravity_scale)
+    interpreter.environment.define(
+        "set_particle_emission".to_string(),
+        Value::BuiltinFunction {
+            name: "set_particle_emission".to_string(),
+            arity: 5,
+            func: |args| {
+                let world_id = expect_id(&args[0], "World ID")?;
+                let system_id = expect_id(&args[1], "Particle system ID")?;
+                let velocity = expect_vec3(&args[2], "Initial velocity")?;
+                let spread = expect_number(&args[3], "Velocity spread")?;
+                let gravity_scale = expect_number(&args[4], "Gravity scale")?;
+
+                with_world(world_id, |world| {
+                    match world.particle_systems.get_mut(system_id) {
+                        Some(system) => {
+                            system.initial_velocity = velocity;
+                            system.velocity_spread = spread.abs();
+                            system.gravity_scale = gravity_scale;
+                            Ok(Value::Unit)
+                        }
+                        None => Err(particle_system_not_found()),
+                    }
+                })
+            },
+        },
+    );
+
+    // Positions of a particle system's live particles, for rendering
+    interpreter.environment.define(
+        "get_particle_positions".to_string(),
+        Value::BuiltinFunction {
+            name: "get_particle_positions".to_string(),
+            arity: 2,
+            func: |args| {
+                let world_id = expect_id(&args[0], "World ID")?;
+                let system_id = expect_id(&args[1], "Particle system ID")?;
+
+                with_world(world_id, |world| {
+                    match world.particle_systems.get(system_id) {
+                        Some(system) => Ok(Value::Array(
+                            system.positions().into_iter().map(vec3_to_value).collect(),
+                        )),
+                        None => Err(particle_system_not_found()),
+                    }
+                })
+            },
+        },
+    );
+
+    // Box of fluid applying buoyancy and drag: (world, min, max, density)
+    interpreter.environment.define(
+        "add_fluid_volume".to_string(),
+        Value::BuiltinFunction {
+            name: "add_fluid_volume".to_string(),
+            arity: 4,
+            func: |args| {
+                let world_id = expect_id(&args[0], "World ID")?;
+                let min = expect_vec3(&args[1], "Fluid minimum corner")?;
+                let max = expect_vec3(&args[2], "Fluid maximum corner")?;
+                let density = expect_number(&args[3], "Fluid density")?;
+                if density <= 0.0 || min.x > max.x || min.y > max.y || min.z > max.z {
+                    return Err(RuntimeError::Generic {
+                        message: "Fluid density must be positive and min must not exceed max"
+                            .to_string(),
+                    });
+                }
+
+                with_world(world_id, |world| {
+                    Ok(Value::Int(world.add_fluid_volume(min, max, density) as i64))
+                })
+            },
+        },
+    );
+}
+
+fn particle_system_not_found() -> RuntimeError {
+    RuntimeError::Generic {
+        message: "Particle system not found".to_string(),
+    }
+}
+
+/// Snapshot of a body for `get_body_state`
+fn body_state(object: &PhysicsObject) -> Value {
+    let fields = HashMap::from([
+        ("position".to_string(), vec3_to_value(object.position)),
+        ("orientation".to_string(), vec3_to_value(object.rotation)),
+        ("velocity".to_string(), vec3_to_value(object.velocity)),
+        (
+            "angular_velocity".to_string(),
+            vec3_to_value(object.angular_velocity),
+        ),
+        ("mass".to_string(), Value::Float(object.mass)),
+        ("sleeping".to_string(), Value::Bool(object.is_sleeping)),
+    ]);
+    Value::Struct {
+        name: "BodyState".to_string(),
+        fields,
+    }
+}
+
+/// Fields of a `set_body_state` struct; `None` leaves the body's value unchanged
+#[derive(Debug, Default)]
+struct BodyStateUpdate {
+    position: Option<Vec3>,
+    orientation: Option<Vec3>,
+    velocity: Option<Vec3>,
+    angular_velocity: Option<Vec3>,
+    mass: Option<f64>,
+    sleeping: Option<bool>,
+}
+
+impl BodyStateUpdate {
+    fn from_value(value: &Value) -> Result<Self, RuntimeError> {
+        let Value::Struct { fields, .. } = value else {
+            return Err(RuntimeError::TypeError {
+                message: format!("Body state must be a struct, found {}", value.type_name()),
+            });
+        };
+
+        let mut update = Self::default();
+        for (name, value) in fields {
+            match name.as_str() {
+                "position" => update.position = Some(expect_vec3(value, "Position")?),
+                "orientation" => update.orientation = Some(expect_vec3(value, "Orientation")?),
+                "velocity" => update.velocity = Some(expect_vec3(value, "Velocity")?),
+                "angular_velocity" => {
+                    update.angular_velocity = Some(expect_vec3(value, "Angular velocity")?)
+                }
+                "mass" => {
+                    let mass = expect_number(value, "Mass")?;
+                    if !(mass.is_finite() && mass >= 0.0) {
+                        return Err(RuntimeError::Generic {
+                            message: format!("Mass must be finite and non-negative, got {}", mass),
+                        });
+                    }
+                    update.mass = Some(mass);
+                }
+                "sleeping" => update.sleeping = Some(expect_bool(value, "Sleeping flag")?),
+                other => {
+                    return Err(RuntimeError::Generic {
+                        message: format!("Body state has no field '{}'", other),
+                    })
+                }
+            }
+        }
+        Ok(update)
+    }
+
+    /// Write the given fields. Moving a body wakes it unless `sleeping` says otherwise.
+    fn apply(&self, object: &mut PhysicsObject) {
+        let moved = self.position.is_some()
+            || self.orientation.is_some()
+            || self.velocity.is_some()
+            || self.angular_velocity.is_some();
+
+        if let Some(position) = self.position {
+            object.position = position;
+        }
+        if let Some(orientation) = self.orientation {
+            object.rotation = orientation;
+        }
+        if let Some(velocity) = self.velocity {
+            object.velocity = velocity;
+        }
+        if let Some(angular_velocity) = self.angular_velocity {
+            object.angular_velocity = angular_velocity;
+        }
+        if let Some(mass) = self.mass {
+            object.mass = mass;
+        }
+        match self.sleeping {
+            Some(sleeping) if sleeping != object.is_sleeping => {
+                object.wake();
+                object.is_sleeping = sleeping;
+            }
+            None if moved => object.wake(),
+            _ => {}
+        }
+    }
+}
+
+/// Advance a world by one step. Script callbacks are run around it by
+/// `Interpreter::physics_step`.
+pub(crate) fn step_world(world_id: &Value) -> Result<Value, RuntimeError> {
+    let world_id = match world_id {
+        Value::Int(id) => *id as usize,
+        _ => {
+            return Err(RuntimeError::TypeError {
+                message: "World ID must be integer".to_string(),
+            })
+        }
+    };
+
+    with_world(world_id, |world| {
+        world.step();
+        Ok(Value::Unit)
+    })
+}
+
+/// Run a closure against a registered physics world
+pub(crate) fn with_world<T>(
+    world_id: usize,
+    f: impl FnOnce(&mut PhysicsWorld) -> Result<T, RuntimeError>,
+) -> Result<T, RuntimeError> {
+    let mut worlds = PHYSICS_WORLDS.lock().unwrap();
+    match worlds.get_mut(&world_id) {
+        Some(world) => f(world),
+        None => Err(RuntimeError::Generic {
+            message: "Physics world not found".to_string(),
+        }),
+    }
+}
+
+/// Give a world a built-in force field in place of any script one
+fn set_preset_force_field(world_id: usize, field: ForceField) -> Result<Value, RuntimeError> {
+    with_world(world_id, |world| {
+        world.set_force_field(Some(field));
+        Ok(())
+    })?;
+    SCRIPT_FORCE_FIELDS.lock().unwrap().remove(&world_id);
+    Ok(Value::Unit)
+}
+
+pub(crate) fn object_not_found() -> RuntimeError {
+    RuntimeError::Generic {
+        message: "Physics object not found".to_string(),
+    }
+}
+
+fn cloth_not_found() -> RuntimeError {
+    RuntimeError::Generic {
+        message: "Cloth not found".to_string(),
+    }
+}
+
+/// Extract a world/object id argument
+pub(crate) fn expect_id(value: &Value, what: &str) -> Result<usize, RuntimeError> {
+    match value {
+        Value::Int(id) if *id >= 0 => Ok(*id as usize),
+        _ => Err(RuntimeError::TypeError {
+            message: format!("{} must be integer", what),
+        }),
+    }
+}
+
+/// Extract a numeric argument as f64
+pub(crate) fn expect_number(value: &Value, what: &str) -> Result<f64, RuntimeError> {
+    match value {
+        Value::Float(f) => Ok(*f),
+        Value::Int(i) => Ok(*i as f64),
+        _ => Err(RuntimeError::TypeError {
+            message: format!("{} must be number", what),
+        }),
+    }
+}
+
+/// Extract a solver iteration count, rejecting negative values
+fn expect_iterations(value: &Value, what: &str) -> Result<usize, RuntimeError> {
+    match value {
+        Value::Int(n) if *n >= 0 => Ok(*n as usize),
+        Value::Int(n) => Err(RuntimeError::Generic {
+            message: format!("{} must be non-negative, got {}", what, n),
+        }),
+        _ => Err(RuntimeError::TypeError {
+            message: format!("{} must be integer", what),
+        }),
+    }
+}
+
+pub(crate) fn expect_bool(value: &Value, what: &str) -> Result<bool, RuntimeError> {
+    match value {
+        Value::Bool(b) => Ok(*b),
+        _ => Err(RuntimeError::TypeError {
+            message: format!("{} must be boolean", what),
+        }),
+    }
+}
+
+/// Convert a trajectory to `[time, x, y, z]` rows
+fn trajectory_to_value(trajectory: &Trajectory) -> Value {
+    Value::Array(
+        trajectory
+            .samples()
+            .iter()
+            .map(|s| {
+                Value::Array(vec![
+                    Value::Float(s.time),
+                    Value::Float(s.position.x),
+                    Value::Float(s.position.y),
+                    Value::Float(s.position.z),
+                ])
+            })
+            .collect(),
+    )
+}
+
+/// Extract a trajectory from `[time, x, y, z]` rows, as an array of arrays or a matrix
+fn expect_trajectory(value: &Value) -> Result<Trajectory, RuntimeError> {
+    let rows: Vec<&[Value]> = match value {
+        Value::Array(items) => items
+            .iter()
+            .map(|item| match item {
+                Value::Array(row) => Ok(row.as_slice()),
+                other => Err(RuntimeError::TypeError {
+                    message: format!(
+                        "Trajectory samples must be [time, x, y, z] arrays, found {}",
+                        other.type_name()
+                    ),
+                }),
+            })
+            .collect::<Result<_, _>>()?,
+        Value::Matrix(rows) => rows.iter().map(|row| row.as_slice()).collect(),
+        other => {
+            return Err(RuntimeError::TypeError {
+                message: format!("Trajectory must be an array, found {}", other.type_name()),
+            })
+        }
+    };
+
+    let samples = rows
+        .into_iter()
+        .map(|row| match row {
+            [time, x, y, z] => Ok(TrajectorySample {
+                time: expect_number(time, "Sample time")?,
+                position: Vec3::new(
+                    expect_number(x, "Sample x")?,
+                    expect_number(y, "Sample y")?,
+                    expect_number(z, "Sample z")?,
+                ),
+            }),
+            _ => Err(RuntimeError::Generic {
+                message: format!(
+                    "Trajectory samples need 4 values [time, x, y, z], got {}",
+                    row.len()
+                ),
+            }),
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    Trajectory::new(samples)
+}
+
+/// Convert a vector to an `[x, y, z]` array of floats
+pub(crate) fn vec3_to_value(v: Vec3) -> Value {
+    Value::Array(vec![
+        Value::Float(v.x),
+        Value::Float(v.y),
+        Value::Float(v.z),
+    ])
+}
+
+/// Extract a vector argument: an `[x, y, z]` array, or a struct (such as `Vec3`)
+/// with numeric `x`, `y` and `z` fields
+pub(crate) fn expect_vec3(value: &Value, what: &str) -> Result<Vec3, RuntimeError> {
+    match value {
+        Value::Array(arr) if arr.len() == 3 => Ok(Vec3::new(
+            expect_number(&arr[0], what)?,
+            expect_number(&arr[1], what)?,
+            expect_number(&arr[2], what)?,
+        )),
+        Value::Struct { name, fields } => {
+            let component = |axis: &str| match fields.get(axis) {
+                Some(value) => expect_number(value, what),
+                None => Err(RuntimeError::TypeError {
+                    message: format!("{} struct {} has no field '{}'", what, name, axis),
+                }),
+            };
+            Ok(Vec3::new(component("x")?, component("y")?, component("z")?))
+        }
+        _ => Err(RuntimeError::TypeError {
+            message: format!("{} must be [x, y, z] array or x/y/z struct", what),
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    static COLLISIONS_SEEN: Mutex<Vec<(i64, i64)>> = Mutex::new(Vec::new());
+
+    fn record_collision(args: &[Value]) -> Result<Value, RuntimeError> {
+        if let (Value::Int(a), Value::Int(b)) = (&args[0], &args[1]) {
+            COLLISIONS_SEEN.lock().unwrap().push((*a, *b));
+        }
+        Ok(Value::Unit)
+    }
+
+    fn world_with_spheres(separation: f64) -> (usize, usize, usize) {
+        let mut world = PhysicsWorld::new();
+        world.gravity = Vec3::zero();
+        let a = world.add_object("sphere".to_string(), 1.0, Vec3::new(0.0, 5.0, 0.0));
+        let b = world.add_object("sphere".to_string(), 1.0, Vec3::new(separation, 5.0, 0.0));
+        world.objects[a].velocity = Vec3::new(3.0, 0.0, 0.0);
+        world.objects[b].velocity = Vec3::new(-3.0, 0.0, 0.0);
+
+        let id = world.id;
+        PHYSICS_WORLDS.lock().unwrap().insert(id, world);
+        (id, a, b)
+    }
+
+    #[test]
+    fn test_collision_callback_fires_through_interpreter() {
+        let mut interpreter = Interpreter::new();
+        register_physics_functions(&mut interpreter);
+        let on_collision = interpreter
+            .environment
+            .get("on_collision")
+            .cloned()
+            .unwrap();
+        let physics_step = interpreter
+            .environment
+            .get("physics_step")
+            .cloned()
+            .unwrap();
+        let callback = Value::BuiltinFunction {
+            name: "record_collision".to_string(),
+            arity: 3,
+            func: record_collision,
+        };
+
+        // Far apart and moving for only a few steps: no contact
+        let (quiet_world, _, _) = world_with_spheres(100.0);
+        interpreter
+            .call_value(
+                on_collision.clone(),
+                &[Value::Int(quiet_world as i64), callback.clone()],
+            )
+            .unwrap();
+        for _ in 0..10 {
+            interpreter
+                .call_value(physics_step.clone(), &[Value::Int(quiet_world as i64)])
+                .unwrap();
+        }
+        assert!(COLLISIONS_SEEN.lock().unwrap().is_empty());
+
+        // Head-on approach
+        let (world_id, a, b) = world_with_spheres(2.0);
+        interpreter
+            .call_value(on_collision, &[Value::Int(world_id as i64), callback])
+            .unwrap();
+        for _ in 0..30 {
+            interpreter
+                .call_value(physics_step.clone(), &[Value::Int(world_id as i64)])
+                .unwrap();
+        }
+
+        let seen = COLLISIONS_SEEN.lock().unwrap();
+        assert!(!seen.is_empty());
+        assert!(seen.iter().all(|&pair| pair == (a as i64, b as i64)));
+    }
+
+    static COLLISIONS_AFTER_FAILURE: Mutex<usize> = Mutex::new(0);
+
+    fn failing_collision(_args: &[Value]) -> Result<Value, RuntimeError> {
+        Err(RuntimeError::Generic {
+            message: "collision handler failed".to_string(),
+        })
+    }
+
+    fn count_collision(_args: &[Value]) -> Result<Value, RuntimeError> {
+        *COLLISIONS_AFTER_FAILURE.lock().unwrap() += 1;
+        Ok(Value::Unit)
+    }
+
+    #[test]
+    fn test_collision_callbacks_run_only_after_their_world_steps() {
+        let mut interpreter = Interpreter::new();
+        register_physics_functions(&mut interpreter);
+        let get = |name: &str| interpreter.environment.get(name).cloned().unwrap();
+        let (on_collision, physics_step, state_hash) = (
+            get("on_collision"),
+            get("physics_step"),
+            get("physics_state_hash"),
+        );
+        let builtin = |name: &str, func: fn(&[Value]) -> Result<Value, RuntimeError>| {
+            Value::BuiltinFunction {
+                name: name.to_string(),
+                arity: 3,
+                func,
+            }
+        };
+
+        // A world stepped outside the interpreter leaves nothing for later calls to run
+        let (outside_world, _, _) = world_with_spheres(2.0);
+        let outside_id = Value::Int(outside_world as i64);
+        interpreter
+            .call_value(
+                on_collision.clone(),
+                &[outside_id.clone(), builtin("count", count_collision)],
+            )
+            .unwrap();
+        for _ in 0..30 {
+            step_world(&outside_id).unwrap();
+        }
+        interpreter.call_value(state_hash, &[outside_id]).unwrap();
+        assert_eq!(*COLLISIONS_AFTER_FAILURE.lock().unwrap(), 0);
+
+        // A failing handler is reported without skipping the ones after it
+        let (world_id, _, _) = world_with_spheres(2.0);
+        let world = Value::Int(world_id as i64);
+        for callback in [
+            builtin("fail", failing_collision),
+            builtin("count", count_collision),
+        ] {
+            interpreter
+                .call_value(on_collision.clone(), &[world.clone(), callback])
+                .unwrap();
+        }
+        let failed_step = (0..30)
+            .find(|_| {
+                interpreter
+                    .call_value(physics_step.clone(), std::slice::from_ref(&world))
+                    .is_err()
+            })
+            .is_some();
+        assert!(failed_step);
+        assert!(*COLLISIONS_AFTER_FAILURE.lock().unwrap() > 0);
+    }
+
+    #[test]
+    fn test_on_collision_rejects_wrong_callback_arity() {
+        let (world_id, _, _) = world_with_spheres(100.0);
+        let callback = Value::BuiltinFunction {
+            name: "record_collision".to_string(),
+            arity: 1,
+            func: record_collision,
+        };
+        let mut interpreter = Interpreter::new();
+        register_physics_functions(&mut interpreter);
+        let on_collision = interpreter
+            .environment
+            .get("on_collision")
+            .cloned()
+            .unwrap();
+
+        assert!(interpreter
+            .call_value(on_collision, &[Value::Int(world_id as i64), callback])
+            .is_err());
+    }
+
+    static CONTACT_USER_DATA: Mutex<Vec<(Value, Value)>> = Mutex::new(Vec::new());
+
+    fn record_contact_user_data(args: &[Value]) -> Result<Value, RuntimeError> {
+        if let Value::Struct { fields, .. } = &args[2] {
+            CONTACT_USER_DATA
+                .lock()
+                .unwrap()
+                .push((fields["user_data_a"].clone(), fields["user_data_b"].clone()));
+        }
+        Ok(Value::Unit)
+    }
+
+    static FILTER_CALLS: Mutex<Vec<(i64, i64)>> = Mutex::new(Vec::new());
+
+    /// Lets everything collide except bodies 0 and 1
+    fn ignore_first_pair(args: &[Value]) -> Result<Value, RuntimeError> {
+        let (Value::Int(a), Value::Int(b)) = (&args[0], &args[1]) else {
+            return Err(RuntimeError::Generic {
+                message: "expected body ids".to_string(),
+            });
+        };
+        FILTER_CALLS.lock().unwrap().push((*a, *b));
+        Ok(Value::Bool((*a, *b) != (0, 1)))
+    }
+
+    #[test]
+    fn test_collision_filter_lets_rejected_pair_pass_through() {
+        let mut interpreter = Interpreter::new();
+        register_physics_functions(&mut interpreter);
+        let get = |name: &str| interpreter.environment.get(name).cloned().unwrap();
+        let (set_collision_filter, physics_step) =
+            (get("set_collision_filter"), get("physics_step"));
+
+        // Two head-on pairs of spheres far apart from each other
+        let mut world = PhysicsWorld::new();
+        world.gravity = Vec3::zero();
+        for (z, direction) in [(0.0, 1.0), (0.0, -1.0), (20.0, 1.0), (20.0, -1.0)] {
+            let id = world.add_object(
+                "sphere".to_string(),
+                1.0,
+                Vec3::new(-1.5 * direction, 5.0, z),
+            );
+            world.objects[id].velocity = Vec3::new(3.0 * direction, 0.0, 0.0);
+        }
+        let world_id = world.id;
+        PHYSICS_WORLDS.lock().unwrap().insert(world_id, world);
+
+        let filter = Value::BuiltinFunction {
+            name: "ignore_first_pair".to_string(),
+            arity: 2,
+            func: ignore_first_pair,
+        };
+        interpreter
+            .call_value(set_collision_filter, &[Value::Int(world_id as i64), filter])
+            .unwrap();
+        for _ in 0..90 {
+            interpreter
+                .call_value(physics_step.clone(), &[Value::Int(world_id as i64)])
+                .unwrap();
+        }
+
+        let worlds = PHYSICS_WORLDS.lock().unwrap();
+        let x = |id: usize| worlds[&world_id].objects[id].position.x;
+        // The filtered pair crossed over; the other bounced apart
+        assert!(x(0) > x(1), "{} {}", x(0), x(1));
+        assert!(x(2) < x(3), "{} {}", x(2), x(3));
+
+        // Only nearby pairs were offered to the filter
+        let calls = FILTER_CALLS.lock().unwrap();
+        assert!(calls.contains(&(0, 1)) && calls.contains(&(2, 3)));
+        assert!(calls.iter().all(|pair| [(0, 1), (2, 3)].contains(pair)));
+    }
+
+    /// Blows along +x above y = 5 and along -x below it
+    fn shear_wind(args: &[Value]) -> Result<Value, RuntimeError> {
+        let position = expect_vec3(&args[0], "Position")?;
+        let x = if position.y > 5.0 { 1.0 } else { -1.0 };
+        Ok(vec3_to_value(Vec3::new(x, 0.0, 0.0)))
+    }
+
+    #[test]
+    fn test_script_force_field_uses_body_position() {
+        let mut interpreter = Interpreter::new();
+        register_physics_functions(&mut interpreter);
+        let get = |name: &str| interpreter.environment.get(name).cloned().unwrap();
+        let (set_force_field, physics_step) = (get("set_force_field"), get("physics_step"));
+
+        let mut world = PhysicsWorld::new();
+        world.gravity = Vec3::zero();
+        let high = world.add_object("sphere".to_string(), 1.0, Vec3::new(0.0, 8.0, 0.0));
+        let low = world.add_object("sphere".to_string(), 1.0, Vec3::new(0.0, 2.0, 0.0));
+        let world_id = world.id;
+        PHYSICS_WORLDS.lock().unwrap().insert(world_id, world);
+        let world = Value::Int(world_id as i64);
+
+        let field = Value::BuiltinFunction {
+            name: "shear_wind".to_string(),
+            arity: 1,
+            func: shear_wind,
+        };
+        interpreter
+            .call_value(set_force_field.clone(), &[world.clone(), field])
+            .unwrap();
+        for _ in 0..30 {
+            interpreter
+                .call_value(physics_step.clone(), std::slice::from_ref(&world))
+                .unwrap();
+        }
+        let velocity = |id: usize| PHYSICS_WORLDS.lock().unwrap()[&world_id].objects[id].velocity;
+        assert!((velocity(high).x - 0.5).abs() < 1e-6);
+        assert!((velocity(low).x + 0.5).abs() < 1e-6);
+
+        // Removing the field leaves bodies coasting
+        interpreter
+            .call_value(set_force_field, &[world.clone(), Value::Unit])
+            .unwrap();
+        interpreter.call_value(physics_step, &[world]).unwrap();
+        assert!((velocity(high).x - 0.5).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_body_state_round_trip_and_partial_set() {
+        let mut interpreter = Interpreter::new();
+        register_physics_functions(&mut interpreter);
+        let get = |name: &str| interpreter.environment.get(name).cloned().unwrap();
+        let (get_state, set_state) = (get("get_body_state"), get("set_body_state"));
+
+        let mut world = PhysicsWorld::new();
+        let body = world.add_object("box".to_string(), 2.5, Vec3::new(1.0, 2.0, 3.0));
+        let object = &mut world.objects[body];
+        object.velocity = Vec3::new(0.5, -1.0, 0.0);
+        object.rotation = Vec3::new(0.1, 0.2, 0.3);
+        object.angular_velocity = Vec3::new(0.0, 4.0, 0.0);
+        object.is_sleeping = true;
+        object.sleep_timer = 1.5;
+        let original = object.clone();
+        let world_id = world.id;
+        PHYSICS_WORLDS.lock().unwrap().insert(world_id, world);
+        let ids = [Value::Int(world_id as i64), Value::Int(body as i64)];
+        let object = || PHYSICS_WORLDS.lock().unwrap()[&world_id].objects[body].clone();
+        let dynamics = |obj: &PhysicsObject| {
+            let (p, r, v, w) = (
+                obj.position,
+                obj.rotation,
+                obj.velocity,
+                obj.angular_velocity,
+            );
+            (p, r, v, w, obj.mass, obj.is_sleeping, obj.sleep_timer)
+        };
+
+        let state = interpreter.call_value(get_state.clone(), &ids).unwrap();
+        interpreter
+            .call_value(
+                set_state.clone(),
+                &[ids[0].clone(), ids[1].clone(), state.clone()],
+            )
+            .unwrap();
+        assert_eq!(dynamics(&object()), dynamics(&original));
+        assert_eq!(interpreter.call_value(get_state, &ids).unwrap(), state);
+
+        // Only the position changes; a teleport wakes the body
+        let position_only = Value::Struct {
+            name: "BodyState".to_string(),
+            fields: HashMap::from([(
+                "position".to_string(),
+                vec3_to_value(Vec3::new(-4.0, 0.0, 9.0)),
+            )]),
+        };
+        interpreter
+            .call_value(
+                set_state.clone(),
+                &[ids[0].clone(), ids[1].clone(), position_only],
+            )
+            .unwrap();
+        let moved = object();
+        assert_eq!(moved.position, Vec3::new(-4.0, 0.0, 9.0));
+        assert_eq!(moved.velocity, original.velocity);
+        assert_eq!(moved.angular_velocity, original.angular_velocity);
+        assert_eq!(moved.rotation, original.rotation);
+        assert_eq!(moved.mass, original.mass);
+        assert!(!moved.is_sleeping);
+
+        // A bad field rejects the whole update
+        let bad = Value::Struct {
+            name: "BodyState".to_string(),
+            fields: HashMap::from([
+                ("velocity".to_string(), vec3_to_value(Vec3::zero())),
+                ("mass".to_string(), Value::Float(-1.0)),
+            ]),
+        };
+        assert!(interpreter
+            .call_value(set_state, &[ids[0].clone(), ids[1].clone(), bad])
+            .is_err());
+        assert_eq!(dynamics(&object()), dynamics(&moved));
+    }
+
+    #[test]
+    fn test_raycast_builtin_returns_hit_struct_or_unit() {
+        let mut interpreter = Interpreter::new();
+        register_physics_functions(&mut interpreter);
+        let raycast = interpreter.environment.get("raycast").cloned().unwrap();
+
+        let mut world = PhysicsWorld::new();
+        let ball = world.add_object("sphere".to_string(), 1.0, Vec3::new(0.0, 5.0, 0.0));
+        let world_id = world.id;
+        PHYSICS_WORLDS.lock().unwrap().insert(world_id, world);
+        let mut cast = |origin: Vec3, direction: Vec3, max_distance: f64| {
+            interpreter.call_value(
+                raycast.clone(),
+                &[
+                    Value::Int(world_id as i64),
+                    vec3_to_value(origin),
+                    vec3_to_value(direction),
+                    Value::Float(max_distance),
+                ],
+            )
+        };
+
+        let Value::Struct { name, fields } =
+            cast(Vec3::new(0.0, 5.0, -10.0), Vec3::new(0.0, 0.0, 1.0), 100.0).unwrap()
+        else {
+            panic!("expected a hit");
+        };
+        assert_eq!(name, "RayHit");
+        assert_eq!(
+            fields["object"],
+            Value::Option(Some(Box::new(Value::Int(ball as i64))))
+        );
+        assert_eq!(fields["distance"], Value::Float(9.5));
+        assert_eq!(fields["normal"], vec3_to_value(Vec3::new(0.0, 0.0, -1.0)));
+
+        // Straight down onto the ground
+        let Value::Struct { fields, .. } =
+            cast(Vec3::new(4.0, 3.0, 0.0), Vec3::new(0.0, -2.0, 0.0), 100.0).unwrap()
+        else {
+            panic!("expected the ground");
+        };
+        assert_eq!(fields["object"], Value::Option(None));
+        assert_eq!(fields["point"], vec3_to_value(Vec3::new(4.0, 0.0, 0.0)));
+
+        assert_eq!(
+            cast(Vec3::new(4.0, 3.0, 0.0), Vec3::new(1.0, 0.0, 0.0), 100.0).unwrap(),
+            Value::Unit
+        );
+        assert!(cast(Vec3::zero(), Vec3::zero(), 1.0).is_err());
+    }
+
+    #[test]
+    fn test_gravity_toggle_keeps_gravity_vector() {
+        let mut interpreter = Interpreter::new();
+        register_physics_functions(&mut interpreter);
+        let get = |name: &str| interpreter.environment.get(name).cloned().unwrap();
+        let (set_enabled, physics_step) = (get("set_gravity_enabled"), get("physics_step"));
+
+        let mut world = PhysicsWorld::new();
+        world.gravity = Vec3::new(0.0, -5.0, 1.0);
+        let body = world.add_object("sphere".to_string(), 1.0, Vec3::new(0.0, 50.0, 0.0));
+        let world_id = world.id;
+        PHYSICS_WORLDS.lock().unwrap().insert(world_id, world);
+        let world = Value::Int(world_id as i64);
+        let state = || {
+            let worlds = PHYSICS_WORLDS.lock().unwrap();
+            let world = &worlds[&world_id];
+            (world.gravity, world.objects[body].velocity)
+        };
+        let step = |interpreter: &mut Interpreter, n: usize| {
+            for _ in 0..n {
+                interpreter
+                    .call_value(physics_step.clone(), std::slice::from_ref(&world))
+                    .unwrap();
+            }
+        };
+
+        interpreter
+            .call_value(set_enabled.clone(), &[world.clone(), Value::Bool(false)])
+            .unwrap();
+        step(&mut interpreter, 30);
+        let (gravity, velocity) = state();
+        assert_eq!(gravity, Vec3::new(0.0, -5.0, 1.0));
+        assert_eq!(velocity, Vec3::zero());
+
+        interpreter
+            .call_value(set_enabled, &[world.clone(), Value::Bool(true)])
+            .unwrap();
+        step(&mut interpreter, 30);
+        let (_, velocity) = state();
+        assert!((velocity.y + 2.5).abs() < 1e-9, "{:?}", velocity);
+        assert!((velocity.z - 0.5).abs() < 1e-9, "{:?}", velocity);
+    }
+
+    #[test]
+    fn test_set_solver_params_validates_arguments() {
+        let mut interpreter = Interpreter::new();
+        register_physics_functions(&mut interpreter);
+        let set_solver_params = interpreter
+            .environment
+            .get("set_solver_params")
+            .cloned()
+            .unwrap();
+        let (world_id, _, _) = world_with_spheres(100.0);
+        let mut call = |baumgarte: f64, slop: f64, velocity: i64, position: i64| {
+            interpreter.call_value(
+                set_solver_params.clone(),
+                &[
+                    Value::Int(world_id as i64),
+                    Value::Float(baumgarte),
+                    Value::Float(slop),
+                    Value::Int(velocity),
+                    Value::Int(position),
+                ],
+            )
+        };
+
+        assert!(call(0.3, 0.01, 8, 4).is_ok());
+        assert!(call(0.3, 0.01, -1, 4).is_err());
+        assert!(call(0.3, 0.01, 8, -2).is_err());
+        assert!(call(1.5, 0.01, 8, 4).is_err());
+        assert!(call(0.3, -0.01, 8, 4).is_err());
+
+        let settings = PHYSICS_WORLDS.lock().unwrap()[&world_id].settings.clone();
+        assert_eq!(settings.baumgarte, 0.3);
+        assert_eq!(settings.velocity_iterations, 8);
+        assert_eq!(settings.position_iterations, 4);
+    }
+
+    #[test]
+    fn test_user_data_survives_collision() {
+        let mut interpreter = Interpreter::new();
+        register_physics_functions(&mut interpreter);
+        let get = |name: &str| interpreter.environment.get(name).cloned().unwrap();
+        let (set_user_data, get_user_data, on_collision, physics_step) = (
+            get("set_user_data"),
+            get("get_user_data"),
+            get("on_collision"),
+            get("physics_step"),
+        );
+
+        let (world_id, a, b) = world_with_spheres(2.0);
+        let world = Value::Int(world_id as i64);
+        let player = Value::Struct {
+            name: "Player".to_string(),
+            fields: HashMap::from([
+                ("health".to_string(), Value::Int(100)),
+                ("name".to_string(), Value::String("hero".to_string())),
+            ]),
+        };
+        interpreter
+            .call_value(
+                set_user_data,
+                &[world.clone(), Value::Int(a as i64), player.clone()],
+            )
+            .unwrap();
+        let callback = Value::BuiltinFunction {
+            name: "record_contact_user_data".to_string(),
+            arity: 3,
+            func: record_contact_user_data,
+        };
+        interpreter
+            .call_value(on_collision, &[world.clone(), callback])
+            .unwrap();
+        for _ in 0..30 {
+            interpreter
+                .call_value(physics_step.clone(), std::slice::from_ref(&world))
+                .unwrap();
+        }
+
+        let seen = CONTACT_USER_DATA.lock().unwrap();
+        assert!(!seen.is_empty());
+        assert!(seen
+            .iter()
+            .all(|(data_a, data_b)| *data_a == player && *data_b == Value::Unit));
+        assert_eq!(
+            interpreter
+                .call_value(
+                    get_user_data.clone(),
+                    &[world.clone(), Value::Int(a as i64)]
+                )
+                .unwrap(),
+            player
+        );
+        assert_eq!(
+            interpreter
+                .call_value(get_user_data, &[world, Value::Int(b as i64)])
+                .unwrap(),
+            Value::Unit
+        );
+    }
+
+    #[test]
+    fn test_trajectory_builtins_record_and_replay() {
+        let mut interpreter = Interpreter::new();
+        register_physics_functions(&mut interpreter);
+        let get = |name: &str| interpreter.environment.get(name).cloned().unwrap();
+        let (record, recorded, play, playing, physics_step) = (
+            get("record_trajectory"),
+            get("recorded_trajectory"),
+            get("play_trajectory"),
+            get("is_playing_trajectory"),
+            get("physics_step"),
+        );
+
+        let mut world = PhysicsWorld::new();
+        let ball = world.add_object("sphere".to_string(), 1.0, Vec3::new(0.0, 20.0, 0.0));
+        let ghost = world.add_object("sphere".to_string(), 1.0, Vec3::new(4.0, 0.0, 0.0));
+        let world_id = world.id;
+        PHYSICS_WORLDS.lock().unwrap().insert(world_id, world);
+        let world = Value::Int(world_id as i64);
+        let (ball_id, ghost_id) = (Value::Int(ball as i64), Value::Int(ghost as i64));
+
+        interpreter
+            .call_value(record, &[world.clone(), ball_id.clone()])
+            .unwrap();
+        for _ in 0..10 {
+            interpreter
+                .call_value(physics_step.clone(), std::slice::from_ref(&world))
+                .unwrap();
+        }
+        let trajectory = interpreter
+            .call_value(recorded, &[world.clone(), ball_id])
+            .unwrap();
+        let Value::Array(rows) = &trajectory else {
+            panic!("expected rows, got {:?}", trajectory);
+        };
+        assert_eq!(rows.len(), 11);
+
+        let args = [
+            world.clone(),
+            ghost_id.clone(),
+            trajectory.clone(),
+            Value::Float(1.0),
+        ];
+        interpreter.call_value(play.clone(), &args).unwrap();
+        for _ in 0..10 {
+            interpreter
+                .call_value(physics_step.clone(), std::slice::from_ref(&world))
+                .unwrap();
+        }
+        // The ghost ends where the ball was at the last recorded sample
+        let last = expect_trajectory(&trajectory).unwrap().samples()[10].position;
+        let position = PHYSICS_WORLDS.lock().unwrap()[&world_id].objects[ghost].position;
+        assert!((position - last).length() < 1e-9);
+        assert!(last.y < 20.0);
+        assert_eq!(
+            interpreter
+                .call_value(playing, &[world.clone(), ghost_id.clone()])
+                .unwrap(),
+            Value::Bool(true)
+        );
+
+        // Rows must be [time, x, y, z] with increasing times, and the speed positive
+        let bad_rows = Value::Array(vec![Value::Array(vec![Value::Float(0.0)])]);
+        let args = [world.clone(), ghost_id.clone(), bad_rows, Value::Float(1.0)];
+        assert!(interpreter.call_value(play.clone(), &args).is_err());
+        let args = [world, ghost_id, trajectory, Value::Float(0.0)];
+        assert!(interpreter.call_value(play, &args).is_err());
+    }
 }
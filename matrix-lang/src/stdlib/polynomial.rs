@@ -0,0 +1,217 @@
+// Polynomials for Matrix Language
+// Horner evaluation and real root finding (closed form up to cubics, Durand-Kerner above)
+
+use crate::eval::interpreter::{RuntimeError, Value};
+use crate::stdlib::physics::expect_number;
+
+/// Imaginary part below which a Durand-Kerner root counts as real
+const REAL_ROOT_TOLERANCE: f64 = 1e-7;
+/// Roots closer than this are reported once
+const DUPLICATE_ROOT_TOLERANCE: f64 = 1e-9;
+const DURAND_KERNER_TOLERANCE: f64 = 1e-14;
+const DURAND_KERNER_MAX_ITERATIONS: usize = 500;
+
+pub fn register_polynomial_functions(interpreter: &mut crate::eval::Interpreter) {
+    // Value of the polynomial at x; coefficients run from highest degree to constant
+    interpreter.environment.define(
+        "poly_eval".to_string(),
+        Value::BuiltinFunction {
+            name: "poly_eval".to_string(),
+            arity: 2,
+            func: |args| {
+                let coeffs = expect_coefficients(&args[0])?;
+                let x = expect_number(&args[1], "x")?;
+                Ok(Value::Float(poly_eval(&coeffs, x)))
+            },
+        },
+    );
+
+    // Distinct real roots, ascending
+    interpreter.environment.define(
+        "poly_roots_real".to_string(),
+        Value::BuiltinFunction {
+            name: "poly_roots_real".to_string(),
+            arity: 1,
+            func: |args| {
+                let coeffs = expect_coefficients(&args[0])?;
+                Ok(Value::Array(
+                    poly_roots_real(&coeffs)
+                        .into_iter()
+                        .map(Value::Float)
+                        .collect(),
+                ))
+            },
+        },
+    );
+}
+
+fn expect_coefficients(value: &Value) -> Result<Vec<f64>, RuntimeError> {
+    match value {
+        Value::Array(items) => items
+            .iter()
+            .map(|item| expect_number(item, "Coefficient"))
+            .collect(),
+        other => Err(RuntimeError::TypeError {
+            message: format!("Coefficients must be an array, found {}", other.type_name()),
+        }),
+    }
+}
+
+/// Evaluate with Horner's method; `coeffs` run from highest degree to constant
+pub fn poly_eval(coeffs: &[f64], x: f64) -> f64 {
+    coeffs.iter().fold(0.0, |acc, &c| acc * x + c)
+}
+
+/// Distinct real roots in ascending order. Leading zero coefficients are ignored, so
+/// constant (including all-zero) polynomials have no roots.
+pub fn poly_roots_real(coeffs: &[f64]) -> Vec<f64> {
+    let start = coeffs
+        .iter()
+        .position(|&c| c != 0.0)
+        .unwrap_or(coeffs.len());
+    let coeffs = &coeffs[start..];
+
+    let mut roots = match coeffs.len() {
+        0 | 1 => Vec::new(),
+        2 => vec![-coeffs[1] / coeffs[0]],
+        3 => quadratic_roots(coeffs[0], coeffs[1], coeffs[2]),
+        4 => cubic_roots(coeffs[0], coeffs[1], coeffs[2], coeffs[3]),
+        _ => durand_kerner(coeffs)
+            .into_iter()
+            .filter(|&(_, im)| im.abs() < REAL_ROOT_TOLERANCE)
+            .map(|(re, _)| re)
+            .collect(),
+    };
+
+    roots.sort_by(|a, b| a.total_cmp(b));
+    roots.dedup_by(|a, b| (*a - *b).abs() < DUPLICATE_ROOT_TOLERANCE);
+    roots
+}
+
+fn quadratic_roots(a: f64, b: f64, c: f64) -> Vec<f64> {
+    let discriminant = b * b - 4.0 * a * c;
+    if discriminant < 0.0 {
+        return Vec::new();
+    }
+    // Avoid cancellation between -b and the square root
+    let q = -0.5 * (b + b.signum() * discriminant.sqrt());
+    if q == 0.0 {
+        return vec![0.0];
+    }
+    vec![q / a, c / q]
+}
+
+/// Cardano's method on the depressed cubic, with the trigonometric form when all
+/// three roots are real
+fn cubic_roots(a: f64, b: f64, c: f64, d: f64) -> Vec<f64> {
+    let (b, c, d) = (b / a, c / a, d / a);
+    // x = t - b/3 gives t^3 + p t + q = 0
+    let shift = b / 3.0;
+    let p = c - b * b / 3.0;
+    let q = 2.0 * b * b * b / 27.0 - b * c / 3.0 + d;
+    let discriminant = (q / 2.0).powi(2) + (p / 3.0).powi(3);
+
+    let depressed = if p.abs() < DUPLICATE_ROOT_TOLERANCE && q.abs() < DUPLICATE_ROOT_TOLERANCE {
+        vec![0.0]
+    } else if discriminant.abs() < DUPLICATE_ROOT_TOLERANCE {
+        // A double root and a simple one
+        vec![3.0 * q / p, -1.5 * q / p]
+    } else if discriminant > 0.0 {
+        let sqrt_disc = discriminant.sqrt();
+        vec![(-q / 2.0 + sqrt_disc).cbrt() + (-q / 2.0 - sqrt_disc).cbrt()]
+    } else {
+        let radius = 2.0 * (-p / 3.0).sqrt();
+        let angle = ((3.0 * q / (2.0 * p)) * (-3.0 / p).sqrt())
+            .clamp(-1.0, 1.0)
+            .acos()
+            / 3.0;
+        (0..3)
+            .map(|k| radius * (angle - 2.0 * std::f64::consts::PI * k as f64 / 3.0).cos())
+            .collect()
+    };
+
+    depressed.into_iter().map(|t| t - shift).collect()
+}
+
+/// All complex roots as (re, im) pairs by simultaneous Durand-Kerner iteration
+fn durand_kerner(coeffs: &[f64]) -> Vec<(f64, f64)> {
+    let degree = coeffs.len() - 1;
+    let monic: Vec<f64> = coeffs.iter().map(|c| c / coeffs[0]).collect();
+
+    let mul = |(a, b): (f64, f64), (c, d): (f64, f64)| (a * c - b * d, a * d + b * c);
+    let div = |(a, b): (f64, f64), (c, d): (f64, f64)| {
+        let denom = c * c + d * d;
+        ((a * c + b * d) / denom, (b * c - a * d) / denom)
+    };
+    let eval = |z: (f64, f64)| {
+        monic.iter().fold((0.0, 0.0), |acc, &c| {
+            let (re, im) = mul(acc, z);
+            (re + c, im)
+        })
+    };
+
+    // Standard starting points: powers of a complex number that is not a root of unity
+    let mut roots: Vec<(f64, f64)> = Vec::with_capacity(degree);
+    let seed = (0.4, 0.9);
+    let mut z = (1.0, 0.0);
+    for _ in 0..degree {
+        roots.push(z);
+        z = mul(z, seed);
+    }
+
+    for _ in 0..DURAND_KERNER_MAX_ITERATIONS {
+        let mut largest_step: f64 = 0.0;
+        for i in 0..degree {
+            let mut denom = (1.0, 0.0);
+            for j in (0..degree).filter(|&j| j != i) {
+                denom = mul(denom, (roots[i].0 - roots[j].0, roots[i].1 - roots[j].1));
+            }
+            let step = div(eval(roots[i]), denom);
+            roots[i] = (roots[i].0 - step.0, roots[i].1 - step.1);
+            largest_step = largest_step.max(step.0.hypot(step.1));
+        }
+        if largest_step < DURAND_KERNER_TOLERANCE {
+            break;
+        }
+    }
+    roots
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_roots(coeffs: &[f64], expected: &[f64]) {
+        let roots = poly_roots_real(coeffs);
+        assert_eq!(roots.len(), expected.len(), "roots {:?}", roots);
+        for (root, want) in roots.iter().zip(expected) {
+            assert!((root - want).abs() < 1e-6, "roots {:?}", roots);
+        }
+    }
+
+    #[test]
+    fn test_poly_eval_horner() {
+        assert_eq!(poly_eval(&[1.0, -3.0, 2.0], 1.0), 0.0);
+        assert_eq!(poly_eval(&[1.0, -3.0, 2.0], 3.0), 2.0);
+        assert_eq!(poly_eval(&[], 5.0), 0.0);
+    }
+
+    #[test]
+    fn test_real_roots_by_degree() {
+        assert_roots(&[1.0, -3.0, 2.0], &[1.0, 2.0]);
+        assert_roots(&[2.0, -4.0], &[2.0]);
+        // (x - 1)(x - 2)(x - 3) and a cubic with one real root
+        assert_roots(&[1.0, -6.0, 11.0, -6.0], &[1.0, 2.0, 3.0]);
+        assert_roots(&[1.0, 0.0, 0.0, -8.0], &[2.0]);
+        // (x^2 - 1)(x^2 + 1) via Durand-Kerner
+        assert_roots(&[1.0, 0.0, 0.0, 0.0, -1.0], &[-1.0, 1.0]);
+        assert_roots(&[1.0, 0.0, 1.0], &[]);
+    }
+
+    #[test]
+    fn test_constant_polynomial_has_no_roots() {
+        assert!(poly_roots_real(&[5.0]).is_empty());
+        assert!(poly_roots_real(&[0.0, 0.0, 7.0]).is_empty());
+        assert!(poly_roots_real(&[]).is_empty());
+    }
+}
@@ -0,0 +1,19 @@
+// Prelude for Matrix Language
+// Helper functions written in Matrix Language itself, shipped with the crate
+
+use crate::lexer::Lexer;
+use crate::parser::Parser;
+
+/// Source of the prelude evaluated by `init`
+pub const PRELUDE_SOURCE: &str = include_str!("prelude.matrix");
+
+/// Define the prelude helpers in the interpreter's global environment. The prelude
+/// ships with the crate, so failing to parse or run it is a bug and panics.
+pub fn load_prelude(interpreter: &mut crate::eval::Interpreter) {
+    let program = Parser::new(Lexer::new(PRELUDE_SOURCE))
+        .and_then(|mut parser| parser.parse_program())
+        .unwrap_or_else(|e| panic!("Prelude failed to parse: {}", e));
+    if let Err(e) = interpreter.eval_program(&program) {
+        panic!("Prelude failed to evaluate: {}", e);
+    }
+}
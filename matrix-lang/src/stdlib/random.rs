@@ -0,0 +1,321 @@
+// Random Sampling for Matrix Language
+// Seeded uniform, normal, integer and Poisson samplers
+
+use crate::eval::interpreter::{RuntimeError, Value};
+use crate::stdlib::physics::{expect_id, expect_number};
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Largest mean sampled directly; bigger means are split into chunks of this size
+const POISSON_CHUNK: f64 = 30.0;
+
+/// Generator shared by the sampling builtins, reseeded by `seed` and `set_seed`.
+/// Until one of them is called it starts from the clock on first use.
+static RNG: Mutex<Option<Rng>> = Mutex::new(None);
+
+/// Restart the shared generator; the same seed always gives the same samples
+pub fn reseed(seed: u64) {
+    *RNG.lock().unwrap() = Some(Rng::new(seed));
+}
+
+/// Draw from the shared generator
+fn with_rng<T>(draw: impl FnOnce(&mut Rng) -> T) -> T {
+    let mut rng = RNG.lock().unwrap();
+    draw(rng.get_or_insert_with(Rng::from_clock))
+}
+
+pub fn register_random_functions(interpreter: &mut crate::eval::Interpreter) {
+    // Reseed the random samplers and noise functions, like `set_seed`
+    interpreter.environment.define(
+        "seed".to_string(),
+        Value::BuiltinFunction {
+            name: "seed".to_string(),
+            arity: 1,
+            func: |args| {
+                crate::stdlib::noise::set_random_seed(expect_id(&args[0], "Seed")? as u64);
+                Ok(Value::Unit)
+            },
+        },
+    );
+
+    // Uniform float in [0, 1)
+    interpreter.environment.define(
+        "random".to_string(),
+        Value::BuiltinFunction {
+            name: "random".to_string(),
+            arity: 0,
+            func: |_args| Ok(Value::Float(with_rng(Rng::next_f64))),
+        },
+    );
+
+    // Uniform float in [lo, hi)
+    interpreter.environment.define(
+        "random_range".to_string(),
+        Value::BuiltinFunction {
+            name: "random_range".to_string(),
+            arity: 2,
+            func: |args| {
+                let lo = expect_number(&args[0], "Lower bound")?;
+                let hi = expect_number(&args[1], "Upper bound")?;
+                Ok(Value::Float(with_rng(|rng| rng.range(lo, hi))?))
+            },
+        },
+    );
+
+    // Normally distributed float
+    interpreter.environment.define(
+        "random_normal".to_string(),
+        Value::BuiltinFunction {
+            name: "random_normal".to_string(),
+            arity: 2,
+            func: |args| {
+                let mean = expect_number(&args[0], "Mean")?;
+                let stddev = expect_number(&args[1], "Standard deviation")?;
+                Ok(Value::Float(with_rng(|rng| rng.normal(mean, stddev))?))
+            },
+        },
+    );
+
+    // Uniform integer between `lo` and `hi`, both included
+    interpreter.environment.define(
+        "random_int".to_string(),
+        Value::BuiltinFunction {
+            name: "random_int".to_string(),
+            arity: 2,
+            func: |args| {
+                let (lo, hi) = match (&args[0], &args[1]) {
+                    (Value::Int(lo), Value::Int(hi)) => (*lo, *hi),
+                    _ => {
+                        return Err(RuntimeError::TypeError {
+                            message: "random_int bounds must be integers".to_string(),
+                        })
+                    }
+                };
+                Ok(Value::Int(with_rng(|rng| rng.int(lo, hi))?))
+            },
+        },
+    );
+
+    // Poisson-distributed count with mean `lambda`
+    interpreter.environment.define(
+        "random_poisson".to_string(),
+        Value::BuiltinFunction {
+            name: "random_poisson".to_string(),
+            arity: 1,
+            func: |args| {
+                let lambda = expect_number(&args[0], "Poisson mean")?;
+                Ok(Value::Int(with_rng(|rng| rng.poisson(lambda))? as i64))
+            },
+        },
+    );
+}
+
+/// One step of splitmix64, which turns consecutive states into well-mixed outputs
+pub fn splitmix64(state: &mut u64) -> u64 {
+    *state = state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    let mut z = *state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+/// Splitmix64 generator with distribution samplers
+#[derive(Debug, Clone)]
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    pub const fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    /// Generator seeded from the current time, for runs that were never seeded
+    pub fn from_clock() -> Self {
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |elapsed| elapsed.as_nanos() as u64);
+        Self::new(nanos)
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        splitmix64(&mut self.state)
+    }
+
+    /// Uniform float in [0, 1)
+    pub fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    pub fn range(&mut self, lo: f64, hi: f64) -> Result<f64, RuntimeError> {
+        if !(lo.is_finite() && hi.is_finite() && lo <= hi) {
+            return Err(RuntimeError::Generic {
+                message: format!("random_range needs finite lo <= hi, got {} and {}", lo, hi),
+            });
+        }
+        Ok(lo + (hi - lo) * self.next_f64())
+    }
+
+    /// Box-Muller transform of two uniform samples
+    pub fn normal(&mut self, mean: f64, stddev: f64) -> Result<f64, RuntimeError> {
+        if !(mean.is_finite() && stddev.is_finite() && stddev >= 0.0) {
+            return Err(RuntimeError::Generic {
+                message: format!(
+                    "random_normal needs a finite mean and non-negative stddev, got {} and {}",
+                    mean, stddev
+                ),
+            });
+        }
+        // 1 - u lies in (0, 1], keeping the logarithm finite
+        let u1 = 1.0 - self.next_f64();
+        let u2 = self.next_f64();
+        let z = (-2.0 * u1.ln()).sqrt() * (std::f64::consts::TAU * u2).cos();
+        Ok(mean + stddev * z)
+    }
+
+    pub fn int(&mut self, lo: i64, hi: i64) -> Result<i64, RuntimeError> {
+        if lo > hi {
+            return Err(RuntimeError::Generic {
+                message: format!("random_int needs lo <= hi, got {} > {}", lo, hi),
+            });
+        }
+        // Scale a 64-bit sample onto the range with a widening multiply
+        let span = (hi as i128 - lo as i128 + 1) as u128;
+        let offset = (self.next_u64() as u128 * span) >> 64;
+        Ok((lo as i128 + offset as i128) as i64)
+    }
+
+    /// Knuth's multiplication method. Large means are sampled as a sum of smaller
+    /// Poisson draws, since `exp(-lambda)` underflows for them.
+    pub fn poisson(&mut self, lambda: f64) -> Result<u64, RuntimeError> {
+        if !(lambda.is_finite() && lambda >= 0.0) {
+            return Err(RuntimeError::Generic {
+                message: format!(
+                    "random_poisson needs a finite non-negative mean, got {}",
+                    lambda
+                ),
+            });
+        }
+
+        let mut remaining = lambda;
+        let mut count = 0;
+        while remaining > 0.0 {
+            let chunk = remaining.min(POISSON_CHUNK);
+            remaining -= chunk;
+
+            let limit = (-chunk).exp();
+            let mut product = self.next_f64();
+            while product > limit {
+                count += 1;
+                product *= self.next_f64();
+            }
+        }
+        Ok(count)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mean_and_stddev(samples: &[f64]) -> (f64, f64) {
+        let n = samples.len() as f64;
+        let mean = samples.iter().sum::<f64>() / n;
+        let variance = samples.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / n;
+        (mean, variance.sqrt())
+    }
+
+    #[test]
+    fn test_normal_has_requested_moments() {
+        let mut rng = Rng::new(1);
+        let samples: Vec<f64> = (0..50_000).map(|_| rng.normal(0.0, 1.0).unwrap()).collect();
+        let (mean, stddev) = mean_and_stddev(&samples);
+        assert!(mean.abs() < 0.02, "mean {}", mean);
+        assert!((stddev - 1.0).abs() < 0.02, "stddev {}", stddev);
+
+        let shifted: Vec<f64> = (0..50_000).map(|_| rng.normal(5.0, 0.5).unwrap()).collect();
+        let (mean, stddev) = mean_and_stddev(&shifted);
+        assert!((mean - 5.0).abs() < 0.02, "mean {}", mean);
+        assert!((stddev - 0.5).abs() < 0.02, "stddev {}", stddev);
+
+        assert!(rng.normal(0.0, -1.0).is_err());
+    }
+
+    #[test]
+    fn test_int_stays_in_range_and_covers_it() {
+        let mut rng = Rng::new(2);
+        let mut seen = [false; 7];
+        for _ in 0..1000 {
+            let n = rng.int(-3, 3).unwrap();
+            assert!((-3..=3).contains(&n));
+            seen[(n + 3) as usize] = true;
+        }
+        assert!(seen.iter().all(|&s| s));
+
+        assert_eq!(rng.int(4, 4).unwrap(), 4);
+        let extreme = rng.int(i64::MIN, i64::MAX);
+        assert!(extreme.is_ok());
+        assert!(rng.int(5, 4).is_err());
+    }
+
+    #[test]
+    fn test_poisson_mean_matches_lambda() {
+        let mut rng = Rng::new(3);
+        for lambda in [0.5, 4.0, 120.0] {
+            let samples: Vec<f64> = (0..20_000)
+                .map(|_| rng.poisson(lambda).unwrap() as f64)
+                .collect();
+            let (mean, stddev) = mean_and_stddev(&samples);
+            assert!(
+                (mean - lambda).abs() < 0.05 * lambda.max(1.0),
+                "mean {}",
+                mean
+            );
+            assert!((stddev - lambda.sqrt()).abs() < 0.05 * lambda.sqrt().max(1.0));
+        }
+        assert_eq!(rng.poisson(0.0).unwrap(), 0);
+        assert!(rng.poisson(-1.0).is_err());
+    }
+
+    #[test]
+    fn test_samples_are_deterministic_for_a_seed() {
+        let draw = |seed| {
+            let mut rng = Rng::new(seed);
+            (
+                rng.normal(0.0, 1.0).unwrap(),
+                rng.int(0, 100).unwrap(),
+                rng.poisson(3.0).unwrap(),
+            )
+        };
+        assert_eq!(draw(42), draw(42));
+        assert_ne!(draw(42), draw(43));
+    }
+
+    #[test]
+    fn test_seed_makes_random_reproducible() {
+        let mut interpreter = crate::eval::Interpreter::new();
+        register_random_functions(&mut interpreter);
+        let mut call = |name: &str, args: &[Value]| {
+            let function = interpreter.environment.get(name).cloned().unwrap();
+            interpreter.call_value(function, args).unwrap()
+        };
+        let mut run = |seed| {
+            call("seed", &[Value::Int(seed)]);
+            let samples: Vec<Value> = (0..5).map(|_| call("random", &[])).collect();
+            let ranged = call("random_range", &[Value::Float(-2.0), Value::Int(2)]);
+            (samples, ranged)
+        };
+
+        let (first, ranged) = run(42);
+        assert_eq!(run(42), (first.clone(), ranged.clone()));
+        assert_ne!(run(43).0, first);
+        for sample in &first {
+            assert!(matches!(sample, Value::Float(x) if (0.0..1.0).contains(x)));
+        }
+        assert!(matches!(ranged, Value::Float(x) if (-2.0..2.0).contains(&x)));
+
+        let mut rng = Rng::new(7);
+        assert!(rng.range(1.0, 0.0).is_err());
+        assert_eq!(rng.range(3.0, 3.0).unwrap(), 3.0);
+    }
+}
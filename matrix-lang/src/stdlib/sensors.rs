@@ -0,0 +1,376 @@
+// Physics Sensors for Matrix Language
+// Ray casting and region queries against world bodies, and ray-fan sensors attached to bodies for steering/AI
+
+use crate::stdlib::{PhysicsObject, PhysicsWorld, Vec3};
+
+/// Nearest intersection of a ray with a body or the ground
+#[derive(Debug, Clone, PartialEq)]
+pub struct RayHit {
+    /// Body hit, or `None` for the ground plane at y = 0
+    pub object_id: Option<usize>,
+    pub distance: f64,
+    pub point: Vec3,
+    pub normal: Vec3,
+}
+
+/// A fan of rays cast from a body every step
+#[derive(Debug, Clone)]
+pub struct Sensor {
+    pub id: usize,
+    pub body: usize,
+    pub ray_count: usize,
+    /// Total angle covered by the fan, in radians, centred on the body's heading
+    pub fan_angle: f64,
+    pub range: f64,
+    /// Nearest hit distance per ray, ordered counter-clockwise; `range` when nothing was hit
+    pub readings: Vec<f64>,
+}
+
+impl Sensor {
+    /// Ray directions for a body, fanned around its heading.
+    /// 2D worlds turn about Z in the XY plane; 3D worlds turn about Y in the XZ plane.
+    fn ray_directions(&self, body: &PhysicsObject, is_2d: bool) -> Vec<Vec3> {
+        let heading = if is_2d {
+            body.rotation.z
+        } else {
+            body.rotation.y
+        };
+
+        (0..self.ray_count)
+            .map(|i| {
+                let offset = if self.ray_count == 1 {
+                    0.0
+                } else {
+                    -self.fan_angle / 2.0 + self.fan_angle * i as f64 / (self.ray_count - 1) as f64
+                };
+                let angle = heading + offset;
+                if is_2d {
+                    Vec3::new(angle.cos(), angle.sin(), 0.0)
+                } else {
+                    Vec3::new(angle.cos(), 0.0, -angle.sin())
+                }
+            })
+            .collect()
+    }
+}
+
+impl PhysicsWorld {
+    /// Cast a ray and return the nearest body or ground hit within `max_distance`
+    pub fn raycast(&self, origin: Vec3, direction: Vec3, max_distance: f64) -> Option<RayHit> {
+        let ground = ray_ground(origin, direction).filter(|hit| hit.distance <= max_distance);
+        self.raycast_excluding(origin, direction, max_distance, None)
+            .into_iter()
+            .chain(ground)
+            .min_by(|a, b| a.distance.total_cmp(&b.distance))
+    }
+
+    /// Cast a ray against bodies only, skipping one (e.g. the one the ray starts inside).
+    /// Sensors use this, so 2D worlds laid out below y = 0 still read their walls.
+    pub fn raycast_excluding(
+        &self,
+        origin: Vec3,
+        direction: Vec3,
+        max_distance: f64,
+        exclude: Option<usize>,
+    ) -> Option<RayHit> {
+        let length = direction.length();
+        if length == 0.0 {
+            return None;
+        }
+        let direction = direction * (1.0 / length);
+
+        self.objects
+            .iter()
+            .filter(|obj| Some(obj.id) != exclude)
+            .filter_map(|obj| {
+                let (distance, normal) = if obj.shape == "sphere" {
+                    ray_sphere(origin, direction, obj.position, obj.half_extents.x)?
+                } else {
+                    ray_box(origin, direction, obj.position, obj.half_extents)?
+                };
+                (distance <= max_distance).then(|| RayHit {
+                    object_id: Some(obj.id),
+                    distance,
+                    point: origin + direction * distance,
+                    normal,
+                })
+            })
+            .min_by(|a, b| a.distance.total_cmp(&b.distance))
+    }
+
+    /// Ids of bodies whose bounding boxes intersect the query box. The test is inclusive,
+    /// so a body exactly touching the query boundary is returned.
+    pub fn overlap_box(&self, center: Vec3, half_extents: Vec3) -> Vec<usize> {
+        self.objects
+            .iter()
+            .filter(|obj| {
+                let (min, max) = obj.aabb();
+                let (query_min, query_max) = (center - half_extents, center + half_extents);
+                min.x <= query_max.x
+                    && max.x >= query_min.x
+                    && min.y <= query_max.y
+                    && max.y >= query_min.y
+                    && min.z <= query_max.z
+                    && max.z >= query_min.z
+            })
+            .map(|obj| obj.id)
+            .collect()
+    }
+
+    /// Attach a ray-fan sensor to a body, returning its id
+    pub fn add_sensor(
+        &mut self,
+        body: usize,
+        ray_count: usize,
+        fan_angle: f64,
+        range: f64,
+    ) -> Option<usize> {
+        self.objects.get(body)?;
+
+        let id = self.sensors.len();
+        let mut sensor = Sensor {
+            id,
+            body,
+            ray_count: ray_count.max(1),
+            fan_angle,
+            range,
+            readings: Vec::new(),
+        };
+        sensor.readings = vec![range; sensor.ray_count];
+        self.sensors.push(sensor);
+        self.update_sensors();
+        Some(id)
+    }
+
+    /// Recast every sensor's rays from its body's current pose
+    pub(crate) fn update_sensors(&mut self) {
+        let mut sensors = std::mem::take(&mut self.sensors);
+
+        for sensor in &mut sensors {
+            let Some(body) = self.objects.get(sensor.body) else {
+                continue;
+            };
+            let origin = body.position;
+
+            sensor.readings = sensor
+                .ray_directions(body, self.is_2d)
+                .into_iter()
+                .map(|direction| {
+                    self.raycast_excluding(origin, direction, sensor.range, Some(sensor.body))
+                        .map_or(sensor.range, |hit| hit.distance)
+                })
+                .collect();
+        }
+
+        self.sensors = sensors;
+    }
+}
+
+/// Hit on the ground plane that bodies are kept above, for rays coming down onto it
+fn ray_ground(origin: Vec3, direction: Vec3) -> Option<RayHit> {
+    if origin.y < 0.0 || direction.y >= 0.0 {
+        return None;
+    }
+    let direction = direction * (1.0 / direction.length());
+    let distance = -origin.y / direction.y;
+    Some(RayHit {
+        object_id: None,
+        distance,
+        point: Vec3::new(
+            origin.x + direction.x * distance,
+            0.0,
+            origin.z + direction.z * distance,
+        ),
+        normal: Vec3::new(0.0, 1.0, 0.0),
+    })
+}
+
+/// Ray/sphere intersection for a normalized direction; rays starting inside are ignored
+fn ray_sphere(origin: Vec3, direction: Vec3, center: Vec3, radius: f64) -> Option<(f64, Vec3)> {
+    let oc = origin - center;
+    let b = oc.dot(&direction);
+    let c = oc.dot(&oc) - radius * radius;
+    let discriminant = b * b - c;
+    if discriminant < 0.0 {
+        return None;
+    }
+
+    let t = -b - discriminant.sqrt();
+    if t < 0.0 {
+        return None;
+    }
+    let normal = (origin + direction * t - center) * (1.0 / radius);
+    Some((t, normal))
+}
+
+/// Ray/axis-aligned box intersection (slab method); rays starting inside are ignored
+fn ray_box(origin: Vec3, direction: Vec3, center: Vec3, half_extents: Vec3) -> Option<(f64, Vec3)> {
+    let o = [
+        origin.x - center.x,
+        origin.y - center.y,
+        origin.z - center.z,
+    ];
+    let d = [direction.x, direction.y, direction.z];
+    let h = [half_extents.x, half_extents.y, half_extents.z];
+
+    let mut t_near = f64::NEG_INFINITY;
+    let mut t_far = f64::INFINITY;
+    let mut hit_axis = 0;
+
+    for axis in 0..3 {
+        if d[axis].abs() < 1e-12 {
+            if o[axis].abs() > h[axis] {
+                return None;
+            }
+            continue;
+        }
+
+        let t1 = (-h[axis] - o[axis]) / d[axis];
+        let t2 = (h[axis] - o[axis]) / d[axis];
+        let (t_min, t_max) = if t1 < t2 { (t1, t2) } else { (t2, t1) };
+
+        if t_min > t_near {
+            t_near = t_min;
+            hit_axis = axis;
+        }
+        t_far = t_far.min(t_max);
+    }
+
+    if t_near > t_far || t_near < 0.0 {
+        return None;
+    }
+
+    let mut normal = [0.0; 3];
+    normal[hit_axis] = -d[hit_axis].signum();
+    Some((t_near, Vec3::new(normal[0], normal[1], normal[2])))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f64::consts::PI;
+
+    fn add_wall(world: &mut PhysicsWorld, position: Vec3, half_extents: Vec3) -> usize {
+        let id = world.add_object("box".to_string(), 0.0, position);
+        world.set_size(id, half_extents);
+        id
+    }
+
+    #[test]
+    fn test_forward_ray_distance_decreases_approaching_wall() {
+        let mut world = PhysicsWorld::new_2d();
+        world.gravity = Vec3::zero();
+        let body = world.add_object("sphere".to_string(), 1.0, Vec3::new(0.0, 0.0, 0.0));
+        world.objects[body].velocity = Vec3::new(2.0, 0.0, 0.0);
+        add_wall(
+            &mut world,
+            Vec3::new(10.0, 0.0, 0.0),
+            Vec3::new(0.5, 1.0, 1.0),
+        );
+
+        let sensor = world.add_sensor(body, 1, 0.0, 20.0).unwrap();
+        assert!((world.sensors[sensor].readings[0] - 9.5).abs() < 1e-9);
+
+        let mut last = world.sensors[sensor].readings[0];
+        for _ in 0..60 {
+            world.step();
+            let reading = world.sensors[sensor].readings[0];
+            assert!(reading < last);
+            last = reading;
+        }
+
+        let expected = 9.5 - world.objects[body].position.x;
+        assert!((last - expected).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_lateral_rays_measure_side_obstacles() {
+        let mut world = PhysicsWorld::new_2d();
+        world.gravity = Vec3::zero();
+        let body = world.add_object("sphere".to_string(), 1.0, Vec3::new(0.0, 0.0, 0.0));
+        // Right-hand wall 1.5 below, left-hand wall 2.5 above, nothing ahead
+        add_wall(
+            &mut world,
+            Vec3::new(0.0, -2.0, 0.0),
+            Vec3::new(20.0, 0.5, 1.0),
+        );
+        add_wall(
+            &mut world,
+            Vec3::new(0.0, 3.0, 0.0),
+            Vec3::new(20.0, 0.5, 1.0),
+        );
+
+        let sensor = world.add_sensor(body, 3, PI, 10.0).unwrap();
+        world.step();
+
+        let readings = &world.sensors[sensor].readings;
+        assert!((readings[0] - 1.5).abs() < 1e-9);
+        assert_eq!(readings[1], 10.0);
+        assert!((readings[2] - 2.5).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_raycast_reports_hit_point_and_normal() {
+        let mut world = PhysicsWorld::new();
+        let sphere = world.add_object("sphere".to_string(), 0.0, Vec3::new(5.0, 0.0, 0.0));
+
+        let hit = world
+            .raycast(Vec3::zero(), Vec3::new(2.0, 0.0, 0.0), 100.0)
+            .unwrap();
+        assert_eq!(hit.object_id, Some(sphere));
+        assert!((hit.distance - 4.5).abs() < 1e-9);
+        assert!((hit.normal.x + 1.0).abs() < 1e-9);
+        assert!(world
+            .raycast(Vec3::zero(), Vec3::new(0.0, 0.0, 1.0), 100.0)
+            .is_none());
+    }
+
+    #[test]
+    fn test_downward_ray_hits_ground_below_bodies_it_misses() {
+        let mut world = PhysicsWorld::new();
+        let block = world.add_object("box".to_string(), 0.0, Vec3::new(0.0, 2.0, 0.0));
+
+        // Straight down past the box
+        let hit = world
+            .raycast(Vec3::new(3.0, 10.0, -1.0), Vec3::new(0.0, -1.0, 0.0), 100.0)
+            .unwrap();
+        assert_eq!(hit.object_id, None);
+        assert_eq!(hit.point, Vec3::new(3.0, 0.0, -1.0));
+        assert_eq!(hit.normal, Vec3::new(0.0, 1.0, 0.0));
+        assert_eq!(hit.distance, 10.0);
+
+        // The box top is nearer than the ground, and a short ray reaches neither
+        let hit = world
+            .raycast(Vec3::new(0.0, 10.0, 0.0), Vec3::new(0.0, -1.0, 0.0), 100.0)
+            .unwrap();
+        assert_eq!(hit.object_id, Some(block));
+        assert!((hit.point.y - 2.5).abs() < 1e-9);
+        assert!(world
+            .raycast(Vec3::new(3.0, 10.0, 0.0), Vec3::new(0.0, -1.0, 0.0), 9.0)
+            .is_none());
+
+        // Slanted rays land where they cross y = 0
+        let hit = world
+            .raycast(Vec3::new(0.0, 4.0, 5.0), Vec3::new(0.0, -1.0, 1.0), 100.0)
+            .unwrap();
+        assert_eq!(hit.object_id, None);
+        assert!((hit.point - Vec3::new(0.0, 0.0, 9.0)).length() < 1e-9);
+    }
+
+    #[test]
+    fn test_overlap_box_includes_inside_straddling_and_touching() {
+        let mut world = PhysicsWorld::new();
+        let inside = world.add_object("box".to_string(), 1.0, Vec3::new(0.5, 0.0, 0.0));
+        let straddling = world.add_object("sphere".to_string(), 1.0, Vec3::new(2.2, 0.0, 0.0));
+        // Unit box whose bottom face lies exactly on the query's y = 2 face
+        let touching = world.add_object("box".to_string(), 1.0, Vec3::new(0.0, 2.5, 0.0));
+        let outside = world.add_object("box".to_string(), 1.0, Vec3::new(0.0, 0.0, 5.0));
+
+        let hits = world.overlap_box(Vec3::zero(), Vec3::new(2.0, 2.0, 2.0));
+
+        assert!(hits.contains(&inside));
+        assert!(hits.contains(&straddling));
+        assert!(hits.contains(&touching));
+        assert!(!hits.contains(&outside));
+    }
+}
@@ -0,0 +1,303 @@
+// Physics Solver Settings for Matrix Language
+// Substepping, solver iterations, contact stabilization, sleeping and warm-starting, with quality presets,
+// and the choice of numerical integrator
+
+use crate::stdlib::{PhysicsWorld, Vec3};
+
+const DEFAULT_BAUMGARTE: f64 = 0.2;
+const DEFAULT_PENETRATION_SLOP: f64 = 0.005;
+const DEFAULT_SPATIAL_HASH_CELL_SIZE: f64 = 1.0;
+const DEFAULT_CONSTRAINT_DAMPING: f64 = 0.01;
+
+/// Named bundles of solver settings trading speed for stability
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QualityPreset {
+    Fast,
+    Balanced,
+    Accurate,
+}
+
+impl QualityPreset {
+    pub const ALL: [QualityPreset; 3] = [
+        QualityPreset::Fast,
+        QualityPreset::Balanced,
+        QualityPreset::Accurate,
+    ];
+
+    /// Parse a preset name, case-insensitively
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "fast" => Some(QualityPreset::Fast),
+            "balanced" => Some(QualityPreset::Balanced),
+            "accurate" => Some(QualityPreset::Accurate),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            QualityPreset::Fast => "fast",
+            QualityPreset::Balanced => "balanced",
+            QualityPreset::Accurate => "accurate",
+        }
+    }
+}
+
+/// Scheme advancing body positions each substep. Gravity and applied forces are held
+/// constant over a substep, and velocities always end at `v + a h`; the schemes differ
+/// in the velocity they move positions with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum IntegrationMethod {
+    /// Move with the velocity from the start of the substep
+    ExplicitEuler,
+    /// Move with the updated velocity, which keeps orbits and springs from gaining energy
+    #[default]
+    SemiImplicitEuler,
+    /// Fourth-order Runge-Kutta; exact for the constant acceleration of a substep
+    Rk4,
+}
+
+impl IntegrationMethod {
+    pub const ALL: [IntegrationMethod; 3] = [
+        IntegrationMethod::ExplicitEuler,
+        IntegrationMethod::SemiImplicitEuler,
+        IntegrationMethod::Rk4,
+    ];
+
+    /// Parse a method name, case-insensitively
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "euler" | "explicit_euler" => Some(IntegrationMethod::ExplicitEuler),
+            "semi_implicit_euler" => Some(IntegrationMethod::SemiImplicitEuler),
+            "rk4" => Some(IntegrationMethod::Rk4),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            IntegrationMethod::ExplicitEuler => "explicit_euler",
+            IntegrationMethod::SemiImplicitEuler => "semi_implicit_euler",
+            IntegrationMethod::Rk4 => "rk4",
+        }
+    }
+
+    /// Distance moved over a substep of length `h` by a body whose velocity is now
+    /// `velocity` after accelerating at `acceleration` during the substep
+    pub fn displacement(&self, velocity: Vec3, acceleration: Vec3, h: f64) -> Vec3 {
+        match self {
+            IntegrationMethod::ExplicitEuler => (velocity - acceleration * h) * h,
+            IntegrationMethod::SemiImplicitEuler => velocity * h,
+            IntegrationMethod::Rk4 => {
+                // Stages k1..k4 of x' = v, v' = a with a fixed over the substep
+                let start = velocity - acceleration * h;
+                let k1 = start;
+                let k2 = start + acceleration * (h / 2.0);
+                let k3 = start + acceleration * (h / 2.0);
+                let k4 = start + acceleration * h;
+                (k1 + k2 * 2.0 + k3 * 2.0 + k4) * (h / 6.0)
+            }
+        }
+    }
+}
+
+/// Solver parameters for a physics world
+#[derive(Debug, Clone, PartialEq)]
+pub struct PhysicsSettings {
+    /// Integration substeps per `step`
+    pub substeps: usize,
+    /// Contact velocity solver passes per substep
+    pub velocity_iterations: usize,
+    /// Distance constraint projection passes per substep
+    pub position_iterations: usize,
+    /// Fraction of contact overlap beyond the slop removed per substep (Baumgarte factor)
+    pub baumgarte: f64,
+    /// Overlap tolerated without correction, to keep resting contacts touching
+    pub penetration_slop: f64,
+    /// Speed below which a body starts counting towards sleep; 0 disables sleeping
+    pub sleep_velocity_threshold: f64,
+    /// Seconds a body must stay slow before it sleeps
+    pub sleep_time: f64,
+    /// Seed contacts with last step's impulses
+    pub warm_starting: bool,
+    /// Edge length of the broad-phase grid cells
+    pub spatial_hash_cell_size: f64,
+    /// Fraction of velocity removed from constrained bodies each substep so chains settle
+    pub constraint_damping: f64,
+}
+
+impl PhysicsSettings {
+    pub fn preset(preset: QualityPreset) -> Self {
+        match preset {
+            QualityPreset::Fast => Self {
+                substeps: 1,
+                velocity_iterations: 2,
+                position_iterations: 2,
+                baumgarte: DEFAULT_BAUMGARTE,
+                penetration_slop: DEFAULT_PENETRATION_SLOP,
+                sleep_velocity_threshold: 0.0,
+                sleep_time: 0.0,
+                warm_starting: false,
+                spatial_hash_cell_size: DEFAULT_SPATIAL_HASH_CELL_SIZE,
+                constraint_damping: DEFAULT_CONSTRAINT_DAMPING,
+            },
+            QualityPreset::Balanced => Self {
+                substeps: 1,
+                velocity_iterations: 20,
+                position_iterations: 20,
+                baumgarte: DEFAULT_BAUMGARTE,
+                penetration_slop: DEFAULT_PENETRATION_SLOP,
+                sleep_velocity_threshold: 0.05,
+                sleep_time: 0.5,
+                warm_starting: true,
+                spatial_hash_cell_size: DEFAULT_SPATIAL_HASH_CELL_SIZE,
+                constraint_damping: DEFAULT_CONSTRAINT_DAMPING,
+            },
+            QualityPreset::Accurate => Self {
+                substeps: 4,
+                velocity_iterations: 30,
+                position_iterations: 30,
+                baumgarte: DEFAULT_BAUMGARTE,
+                penetration_slop: DEFAULT_PENETRATION_SLOP,
+                sleep_velocity_threshold: 0.1,
+                sleep_time: 0.25,
+                warm_starting: true,
+                spatial_hash_cell_size: DEFAULT_SPATIAL_HASH_CELL_SIZE,
+                constraint_damping: DEFAULT_CONSTRAINT_DAMPING,
+            },
+        }
+    }
+}
+
+impl PhysicsWorld {
+    /// Tune contact stabilization and solver iteration counts, waking every body so
+    /// resting contacts are re-solved with the new values
+    pub fn set_solver_params(
+        &mut self,
+        baumgarte: f64,
+        penetration_slop: f64,
+        velocity_iterations: usize,
+        position_iterations: usize,
+    ) {
+        self.settings.baumgarte = baumgarte;
+        self.settings.penetration_slop = penetration_slop;
+        self.settings.velocity_iterations = velocity_iterations;
+        self.settings.position_iterations = position_iterations;
+        for obj in &mut self.objects {
+            obj.wake();
+        }
+    }
+}
+
+impl Default for PhysicsSettings {
+    fn default() -> Self {
+        Self::preset(QualityPreset::Balanced)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stdlib::Vec3;
+
+    const STACK_HEIGHT: usize = 10;
+
+    /// Simulate a column of unit boxes resting on a static floor and return the top box height
+    fn settle_box_stack(preset: QualityPreset) -> f64 {
+        let mut world = PhysicsWorld::new();
+        world.set_quality_preset(preset);
+        let floor = world.add_object("box".to_string(), 0.0, Vec3::new(0.0, -0.5, 0.0));
+        world.set_size(floor, Vec3::new(10.0, 0.5, 10.0));
+
+        let boxes: Vec<usize> = (0..STACK_HEIGHT)
+            .map(|i| world.add_object("box".to_string(), 1.0, Vec3::new(0.0, 0.5 + i as f64, 0.0)))
+            .collect();
+
+        for _ in 0..180 {
+            world.step();
+        }
+        world.objects[*boxes.last().unwrap()].position.y
+    }
+
+    #[test]
+    fn test_integrators_match_free_fall() {
+        // One second of free fall from rest: y = y0 - g t^2 / 2
+        let expected = 100.0 - 0.5 * 9.81;
+        let fall = |method: IntegrationMethod| {
+            let mut world = PhysicsWorld::new();
+            world.integrator = method;
+            let ball = world.add_object("sphere".to_string(), 1.0, Vec3::new(0.0, 100.0, 0.0));
+            for _ in 0..60 {
+                world.step();
+            }
+            assert!((world.objects[ball].velocity.y + 9.81).abs() < 1e-9);
+            world.objects[ball].position.y
+        };
+
+        // The Euler variants are first order: off by g h t / 2 in opposite directions
+        let first_order_error = 0.5 * 9.81 / 60.0;
+        let explicit = fall(IntegrationMethod::ExplicitEuler);
+        let semi_implicit = fall(IntegrationMethod::SemiImplicitEuler);
+        assert!((explicit - (expected + first_order_error)).abs() < 1e-6);
+        assert!((semi_implicit - (expected - first_order_error)).abs() < 1e-6);
+        assert!((fall(IntegrationMethod::Rk4) - expected).abs() < 1e-9);
+
+        assert_eq!(
+            PhysicsWorld::new().integrator,
+            IntegrationMethod::SemiImplicitEuler
+        );
+        for method in IntegrationMethod::ALL {
+            assert_eq!(IntegrationMethod::from_name(method.name()), Some(method));
+        }
+    }
+
+    #[test]
+    fn test_preset_names_round_trip() {
+        for preset in QualityPreset::ALL {
+            assert_eq!(QualityPreset::from_name(preset.name()), Some(preset));
+        }
+        assert_eq!(
+            QualityPreset::from_name("ACCURATE"),
+            Some(QualityPreset::Accurate)
+        );
+        assert_eq!(QualityPreset::from_name("ultra"), None);
+    }
+
+    #[test]
+    fn test_accurate_preset_keeps_tall_stack_standing() {
+        let resting_height = STACK_HEIGHT as f64 - 0.5;
+
+        let accurate = settle_box_stack(QualityPreset::Accurate);
+        let fast = settle_box_stack(QualityPreset::Fast);
+
+        assert!(
+            (accurate - resting_height).abs() < 0.1,
+            "accurate top at {}",
+            accurate
+        );
+        assert!((fast - resting_height).abs() > 0.1, "fast top at {}", fast);
+    }
+
+    /// Overlap left between two boxes that start 0.8 deep inside each other
+    fn overlap_after_steps(baumgarte: f64) -> f64 {
+        let mut world = PhysicsWorld::new();
+        world.gravity = Vec3::zero();
+        let a = world.add_object("box".to_string(), 1.0, Vec3::new(0.0, 5.0, 0.0));
+        let b = world.add_object("box".to_string(), 1.0, Vec3::new(0.2, 5.0, 0.0));
+        world.set_solver_params(baumgarte, 0.005, 20, 20);
+
+        for _ in 0..5 {
+            world.step();
+        }
+        let gap = world.objects[b].position.x - world.objects[a].position.x;
+        (1.0 - gap).max(0.0)
+    }
+
+    #[test]
+    fn test_higher_baumgarte_resolves_penetration_faster() {
+        let stiff = overlap_after_steps(0.8);
+        let soft = overlap_after_steps(0.05);
+        assert!(stiff < soft, "stiff {} soft {}", stiff, soft);
+        assert!(stiff < 0.1, "stiff {}", stiff);
+    }
+}
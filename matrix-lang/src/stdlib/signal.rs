@@ -0,0 +1,338 @@
+// Signal Processing for Matrix Language
+// Moving averages, exponential smoothing, convolution and correlation over arrays of samples
+
+use crate::eval::interpreter::{RuntimeError, Value, VARIADIC};
+use crate::stdlib::physics::{expect_id, expect_number};
+
+pub fn register_signal_functions(interpreter: &mut crate::eval::Interpreter) {
+    // Trailing moving average; the window shrinks over the first samples
+    interpreter.environment.define(
+        "moving_average".to_string(),
+        Value::BuiltinFunction {
+            name: "moving_average".to_string(),
+            arity: 2,
+            func: |args| {
+                let samples = expect_samples(&args[0])?;
+                let window = expect_id(&args[1], "Window size")?;
+                Ok(samples_to_value(moving_average(&samples, window)?))
+            },
+        },
+    );
+
+    // Exponential smoothing; `alpha` near 1 follows the signal, near 0 smooths heavily
+    interpreter.environment.define(
+        "exponential_smoothing".to_string(),
+        Value::BuiltinFunction {
+            name: "exponential_smoothing".to_string(),
+            arity: 2,
+            func: |args| {
+                let samples = expect_samples(&args[0])?;
+                let alpha = expect_number(&args[1], "Smoothing factor")?;
+                Ok(samples_to_value(exponential_smoothing(&samples, alpha)?))
+            },
+        },
+    );
+
+    // convolve(signal, kernel[, mode]) with mode "full" (default), "same" or "valid"
+    interpreter.environment.define(
+        "convolve".to_string(),
+        Value::BuiltinFunction {
+            name: "convolve".to_string(),
+            arity: VARIADIC,
+            func: |args| {
+                let (signal, kernel, mode) = convolution_args("convolve", args)?;
+                Ok(samples_to_value(convolve(&signal, &kernel, mode)?))
+            },
+        },
+    );
+
+    // correlate(signal, kernel[, mode]): convolution with the kernel reversed
+    interpreter.environment.define(
+        "correlate".to_string(),
+        Value::BuiltinFunction {
+            name: "correlate".to_string(),
+            arity: VARIADIC,
+            func: |args| {
+                let (signal, kernel, mode) = convolution_args("correlate", args)?;
+                Ok(samples_to_value(correlate(&signal, &kernel, mode)?))
+            },
+        },
+    );
+}
+
+/// Which part of the full convolution to keep
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConvolutionMode {
+    /// Every overlap of signal and kernel, `n + m - 1` samples
+    Full,
+    /// The centre of the full output, as long as the signal
+    Same,
+    /// Only complete overlaps, `max(n, m) - min(n, m) + 1` samples
+    Valid,
+}
+
+impl ConvolutionMode {
+    pub fn parse(name: &str) -> Result<Self, RuntimeError> {
+        match name {
+            "full" => Ok(ConvolutionMode::Full),
+            "same" => Ok(ConvolutionMode::Same),
+            "valid" => Ok(ConvolutionMode::Valid),
+            other => Err(RuntimeError::Generic {
+                message: format!(
+                    "Unknown convolution mode '{}', expected \"full\", \"same\" or \"valid\"",
+                    other
+                ),
+            }),
+        }
+    }
+}
+
+fn convolution_args(
+    name: &str,
+    args: &[Value],
+) -> Result<(Vec<f64>, Vec<f64>, ConvolutionMode), RuntimeError> {
+    let mode = match args {
+        [_, _] => ConvolutionMode::Full,
+        [_, _, Value::String(mode)] => ConvolutionMode::parse(mode)?,
+        [_, _, other] => {
+            return Err(RuntimeError::TypeError {
+                message: format!(
+                    "{} mode must be a string, found {}",
+                    name,
+                    other.type_name()
+                ),
+            })
+        }
+        _ => {
+            return Err(RuntimeError::FunctionCallError {
+                message: format!(
+                    "{} expects a signal, a kernel and an optional mode, got {} arguments",
+                    name,
+                    args.len()
+                ),
+            })
+        }
+    };
+    Ok((expect_samples(&args[0])?, expect_samples(&args[1])?, mode))
+}
+
+fn expect_samples(value: &Value) -> Result<Vec<f64>, RuntimeError> {
+    match value {
+        Value::Array(items) => items
+            .iter()
+            .map(|item| expect_number(item, "Sample"))
+            .collect(),
+        other => Err(RuntimeError::TypeError {
+            message: format!("Samples must be an array, found {}", other.type_name()),
+        }),
+    }
+}
+
+fn samples_to_value(samples: Vec<f64>) -> Value {
+    Value::Array(samples.into_iter().map(Value::Float).collect())
+}
+
+/// Mean of each sample and up to `window - 1` samples before it. The first
+/// `window - 1` outputs average over the shorter history available, so the
+/// output has the same length as the input and is never padded.
+pub fn moving_average(samples: &[f64], window: usize) -> Result<Vec<f64>, RuntimeError> {
+    if window == 0 || window > samples.len() {
+        return Err(RuntimeError::Generic {
+            message: format!(
+                "Moving average window must be between 1 and {}, got {}",
+                samples.len(),
+                window
+            ),
+        });
+    }
+
+    let mut sum = 0.0;
+    Ok(samples
+        .iter()
+        .enumerate()
+        .map(|(i, &sample)| {
+            sum += sample;
+            if i >= window {
+                sum -= samples[i - window];
+            }
+            sum / (i + 1).min(window) as f64
+        })
+        .collect())
+}
+
+/// `s[0] = x[0]`, then `s[i] = alpha * x[i] + (1 - alpha) * s[i - 1]`
+pub fn exponential_smoothing(samples: &[f64], alpha: f64) -> Result<Vec<f64>, RuntimeError> {
+    if !(0.0..=1.0).contains(&alpha) {
+        return Err(RuntimeError::Generic {
+            message: format!("Smoothing factor must be in [0, 1], got {}", alpha),
+        });
+    }
+
+    let mut smoothed: Option<f64> = None;
+    Ok(samples
+        .iter()
+        .map(|&sample| {
+            let next = match smoothed {
+                Some(previous) => alpha * sample + (1.0 - alpha) * previous,
+                None => sample,
+            };
+            smoothed = Some(next);
+            next
+        })
+        .collect())
+}
+
+/// Discrete convolution `y[n] = sum_k x[k] * h[n - k]`, trimmed to `mode`. Signal and
+/// kernel must both be non-empty.
+pub fn convolve(
+    signal: &[f64],
+    kernel: &[f64],
+    mode: ConvolutionMode,
+) -> Result<Vec<f64>, RuntimeError> {
+    if kernel.is_empty() {
+        return Err(RuntimeError::Generic {
+            message: "Convolution kernel must not be empty".to_string(),
+        });
+    }
+    if signal.is_empty() {
+        return Err(RuntimeError::Generic {
+            message: "Convolution signal must not be empty".to_string(),
+        });
+    }
+
+    let mut full = vec![0.0; signal.len() + kernel.len() - 1];
+    for (i, &x) in signal.iter().enumerate() {
+        for (j, &h) in kernel.iter().enumerate() {
+            full[i + j] += x * h;
+        }
+    }
+
+    let (start, length) = match mode {
+        ConvolutionMode::Full => (0, full.len()),
+        ConvolutionMode::Same => ((kernel.len() - 1) / 2, signal.len()),
+        ConvolutionMode::Valid => {
+            let shorter = signal.len().min(kernel.len());
+            let longer = signal.len().max(kernel.len());
+            (shorter - 1, longer - shorter + 1)
+        }
+    };
+    Ok(full[start..start + length].to_vec())
+}
+
+/// Cross-correlation `c[n] = sum_k x[n + k] * h[k]`, i.e. convolution with the kernel
+/// reversed, trimmed to `mode` the same way
+pub fn correlate(
+    signal: &[f64],
+    kernel: &[f64],
+    mode: ConvolutionMode,
+) -> Result<Vec<f64>, RuntimeError> {
+    let reversed: Vec<f64> = kernel.iter().rev().copied().collect();
+    convolve(signal, &reversed, mode)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn variance(samples: &[f64]) -> f64 {
+        let mean = samples.iter().sum::<f64>() / samples.len() as f64;
+        samples.iter().map(|s| (s - mean).powi(2)).sum::<f64>() / samples.len() as f64
+    }
+
+    /// A sine wave with alternating +/- noise on top
+    fn noisy_signal() -> Vec<f64> {
+        (0..200)
+            .map(|i| {
+                let noise = if i % 2 == 0 { 0.5 } else { -0.5 };
+                (i as f64 * 0.05).sin() + noise
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_constant_signal_is_unchanged() {
+        let constant = vec![3.0; 10];
+        for value in moving_average(&constant, 4).unwrap() {
+            assert!((value - 3.0).abs() < 1e-12);
+        }
+        for value in exponential_smoothing(&constant, 0.3).unwrap() {
+            assert!((value - 3.0).abs() < 1e-12);
+        }
+        assert_eq!(
+            moving_average(&[1.0, 2.0, 3.0], 2).unwrap(),
+            vec![1.0, 1.5, 2.5]
+        );
+    }
+
+    #[test]
+    fn test_smoothing_reduces_noise_variance() {
+        let signal = noisy_signal();
+        let raw = variance(&signal);
+
+        assert!(variance(&moving_average(&signal, 4).unwrap()) < raw);
+        assert!(variance(&exponential_smoothing(&signal, 0.2).unwrap()) < raw);
+    }
+
+    #[test]
+    fn test_invalid_window_and_alpha_are_rejected() {
+        let samples = [1.0, 2.0, 3.0];
+        assert!(moving_average(&samples, 4).is_err());
+        assert!(moving_average(&samples, 0).is_err());
+        assert!(exponential_smoothing(&samples, 1.5).is_err());
+        assert!(exponential_smoothing(&samples, -0.1).is_err());
+    }
+
+    fn assert_samples(actual: &[f64], expected: &[f64]) {
+        assert_eq!(actual.len(), expected.len(), "{:?}", actual);
+        for (a, e) in actual.iter().zip(expected) {
+            assert!((a - e).abs() < 1e-12, "{:?} != {:?}", actual, expected);
+        }
+    }
+
+    #[test]
+    fn test_convolve_with_averaging_kernel_in_each_mode() {
+        let signal = [3.0, 6.0, 9.0, 12.0, 15.0];
+        let average = [1.0 / 3.0; 3];
+
+        let full = convolve(&signal, &average, ConvolutionMode::Full).unwrap();
+        assert_samples(&full, &[1.0, 3.0, 6.0, 9.0, 12.0, 9.0, 5.0]);
+        let same = convolve(&signal, &average, ConvolutionMode::Same).unwrap();
+        assert_samples(&same, &[3.0, 6.0, 9.0, 12.0, 9.0]);
+        let valid = convolve(&signal, &average, ConvolutionMode::Valid).unwrap();
+        assert_samples(&valid, &[6.0, 9.0, 12.0]);
+
+        // A kernel longer than the signal still has one complete overlap per offset
+        let valid = convolve(&[1.0, 2.0], &[1.0, 1.0, 1.0], ConvolutionMode::Valid).unwrap();
+        assert_samples(&valid, &[3.0, 3.0]);
+    }
+
+    #[test]
+    fn test_correlate_reverses_the_kernel() {
+        let signal = [1.0, 2.0, 3.0];
+        let kernel = [0.0, 1.0, 0.5];
+
+        // Textbook values: c[n] = sum_k x[n + k] * h[k] over the overlaps
+        let full = correlate(&signal, &kernel, ConvolutionMode::Full).unwrap();
+        assert_samples(&full, &[0.5, 2.0, 3.5, 3.0, 0.0]);
+        let same = correlate(&signal, &kernel, ConvolutionMode::Same).unwrap();
+        assert_samples(&same, &[2.0, 3.5, 3.0]);
+        let valid = correlate(&signal, &kernel, ConvolutionMode::Valid).unwrap();
+        assert_samples(&valid, &[3.5]);
+    }
+
+    #[test]
+    fn test_empty_kernel_and_unknown_mode_are_rejected() {
+        let signal = [1.0, 2.0, 3.0];
+        for mode in [
+            ConvolutionMode::Full,
+            ConvolutionMode::Same,
+            ConvolutionMode::Valid,
+        ] {
+            let err = convolve(&signal, &[], mode).unwrap_err();
+            assert!(err.to_string().contains("kernel"), "{}", err);
+            assert!(correlate(&signal, &[], mode).is_err());
+        }
+        assert!(convolve(&[], &[1.0], ConvolutionMode::Full).is_err());
+        assert!(ConvolutionMode::parse("circular").is_err());
+    }
+}
@@ -0,0 +1,94 @@
+// World State Hashing for Matrix Language
+// Stable fingerprints of body state, for checking that a scenario reproduces a known end state
+
+use crate::stdlib::{PhysicsWorld, Vec3};
+
+/// Grid that state values are rounded to before hashing, absorbing float noise
+/// between platforms
+pub const STATE_HASH_QUANTUM: f64 = 1e-6;
+
+const FNV_OFFSET_BASIS: u64 = 0xCBF2_9CE4_8422_2325;
+const FNV_PRIME: u64 = 0x0000_0100_0000_01B3;
+
+impl PhysicsWorld {
+    /// FNV-1a hash of every body's position, velocity, rotation and angular
+    /// velocity, quantized to `STATE_HASH_QUANTUM`. Unlike `std`'s hashers the
+    /// result never changes between builds, so it can be recorded in tests.
+    pub fn state_hash(&self) -> u64 {
+        let mut hash = StateHasher::new();
+        hash.write(self.objects.len() as u64);
+        for obj in &self.objects {
+            for v in [
+                obj.position,
+                obj.velocity,
+                obj.rotation,
+                obj.angular_velocity,
+            ] {
+                hash.write_vec3(v);
+            }
+        }
+        hash.finish()
+    }
+}
+
+struct StateHasher(u64);
+
+impl StateHasher {
+    fn new() -> Self {
+        Self(FNV_OFFSET_BASIS)
+    }
+
+    fn write(&mut self, value: u64) {
+        for byte in value.to_le_bytes() {
+            self.0 ^= byte as u64;
+            self.0 = self.0.wrapping_mul(FNV_PRIME);
+        }
+    }
+
+    fn write_vec3(&mut self, v: Vec3) {
+        for x in [v.x, v.y, v.z] {
+            // Rounding also maps -0.0 to the same bucket as 0.0
+            self.write((x / STATE_HASH_QUANTUM).round() as i64 as u64);
+        }
+    }
+
+    fn finish(&self) -> u64 {
+        self.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Two boxes dropped onto a third, then stepped for two seconds
+    fn stacking_scenario(offset: f64) -> PhysicsWorld {
+        let mut world = PhysicsWorld::new();
+        let floor = world.add_object("box".to_string(), 0.0, Vec3::new(0.0, 0.5, 0.0));
+        world.set_size(floor, Vec3::new(5.0, 0.5, 5.0));
+        world.add_object("box".to_string(), 1.0, Vec3::new(offset, 3.0, 0.0));
+        world.add_object("box".to_string(), 2.0, Vec3::new(0.3, 5.0, 0.2));
+        for _ in 0..120 {
+            world.step();
+        }
+        world
+    }
+
+    #[test]
+    fn test_state_hash_matches_recorded_scenario() {
+        let world = stacking_scenario(0.0);
+        assert_eq!(world.state_hash(), stacking_scenario(0.0).state_hash());
+        assert_eq!(world.state_hash(), 4711221321188987580);
+    }
+
+    #[test]
+    fn test_perturbed_scenario_hashes_differently() {
+        let reference = stacking_scenario(0.0).state_hash();
+        assert_ne!(stacking_scenario(1e-3).state_hash(), reference);
+
+        // Differences far below the quantum are ignored
+        let mut world = stacking_scenario(0.0);
+        world.objects[1].position.x += STATE_HASH_QUANTUM * 1e-4;
+        assert_eq!(world.state_hash(), reference);
+    }
+}
@@ -0,0 +1,78 @@
+// Simulation Statistics for Matrix Language
+// Per-step counters and timing for performance monitors
+
+use crate::stdlib::PhysicsWorld;
+
+/// Work done by the most recent `step`
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct PhysicsStats {
+    /// Awake bodies that are neither static nor frozen
+    pub active_bodies: usize,
+    pub sleeping_bodies: usize,
+    /// Body pairs checked for contact, summed over substeps
+    pub pairs_tested: usize,
+    /// Touching pairs resolved during the step
+    pub contacts_resolved: usize,
+    /// Distance constraint solver passes, summed over substeps
+    pub constraint_iterations: usize,
+    /// Wall-clock time of the step in milliseconds
+    pub step_time_ms: f64,
+}
+
+impl PhysicsWorld {
+    /// Statistics for the last step; all zero before the first one
+    pub fn stats(&self) -> PhysicsStats {
+        self.stats
+    }
+
+    /// Fill in the body and contact counts once a step has finished
+    pub(crate) fn finish_stats(&mut self, started: std::time::Instant) {
+        let (active, sleeping) = self.objects.iter().filter(|obj| !obj.is_immovable()).fold(
+            (0, 0),
+            |(active, sleeping), obj| {
+                if obj.is_sleeping {
+                    (active, sleeping + 1)
+                } else {
+                    (active + 1, sleeping)
+                }
+            },
+        );
+        self.stats.active_bodies = active;
+        self.stats.sleeping_bodies = sleeping;
+        self.stats.contacts_resolved = self.collision_events.len();
+        self.stats.step_time_ms = started.elapsed().as_secs_f64() * 1000.0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stdlib::Vec3;
+
+    #[test]
+    fn test_stats_count_bodies_and_contacts() {
+        let mut world = PhysicsWorld::new();
+        assert_eq!(world.stats(), PhysicsStats::default());
+
+        // Two overlapping boxes, one far away and a static floor well below it
+        let a = world.add_object("box".to_string(), 1.0, Vec3::new(0.0, 5.0, 0.0));
+        let b = world.add_object("box".to_string(), 1.0, Vec3::new(0.5, 5.0, 0.0));
+        world.add_object("box".to_string(), 1.0, Vec3::new(20.0, 5.0, 0.0));
+        let floor = world.add_object("box".to_string(), 1.0, Vec3::new(20.0, -5.0, 0.0));
+        world.objects[floor].is_static = true;
+        for id in [a, b] {
+            world.objects[id].half_extents = Vec3::new(0.5, 0.5, 0.5);
+        }
+
+        world.step();
+
+        let stats = world.stats();
+        assert_eq!(stats.active_bodies, 3);
+        assert_eq!(stats.sleeping_bodies, 0);
+        assert_eq!(stats.contacts_resolved, 1);
+        // Only the overlapping boxes share a broad-phase cell
+        assert_eq!(stats.pairs_tested, world.settings.substeps.max(1));
+        assert_eq!(stats.constraint_iterations, 0);
+        assert!(stats.step_time_ms > 0.0);
+    }
+}
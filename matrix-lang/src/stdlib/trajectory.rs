@@ -0,0 +1,305 @@
+// Trajectories for Matrix Language
+// Recording body positions over time and replaying them onto kinematic bodies
+
+use crate::eval::interpreter::RuntimeError;
+use crate::stdlib::{PhysicsWorld, Vec3};
+
+/// A body's position at a time, in seconds from the start of its trajectory
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TrajectorySample {
+    pub time: f64,
+    pub position: Vec3,
+}
+
+/// Samples with strictly increasing times, starting at time 0
+#[derive(Debug, Clone, PartialEq)]
+pub struct Trajectory {
+    samples: Vec<TrajectorySample>,
+}
+
+impl Trajectory {
+    /// Check and normalise samples so the first is at time 0
+    pub fn new(samples: Vec<TrajectorySample>) -> Result<Self, RuntimeError> {
+        let Some(first) = samples.first().map(|s| s.time) else {
+            return Err(RuntimeError::Generic {
+                message: "Trajectory needs at least one sample".to_string(),
+            });
+        };
+        if let Some(bad) = samples.iter().find(|s| !s.time.is_finite()) {
+            return Err(RuntimeError::Generic {
+                message: format!("Trajectory sample times must be finite, got {}", bad.time),
+            });
+        }
+        for pair in samples.windows(2) {
+            if pair[1].time <= pair[0].time {
+                return Err(RuntimeError::Generic {
+                    message: format!(
+                        "Trajectory sample times must increase, got {} after {}",
+                        pair[1].time, pair[0].time
+                    ),
+                });
+            }
+        }
+
+        Ok(Self {
+            samples: samples
+                .into_iter()
+                .map(|s| TrajectorySample {
+                    time: s.time - first,
+                    position: s.position,
+                })
+                .collect(),
+        })
+    }
+
+    pub fn samples(&self) -> &[TrajectorySample] {
+        &self.samples
+    }
+
+    /// Time of the last sample
+    pub fn duration(&self) -> f64 {
+        self.samples.last().map_or(0.0, |s| s.time)
+    }
+
+    /// Position at `time`, interpolated linearly between the surrounding samples and
+    /// held at the ends outside the recorded span
+    pub fn position_at(&self, time: f64) -> Vec3 {
+        let next = self.samples.partition_point(|s| s.time <= time);
+        if next == 0 {
+            return self.samples[0].position;
+        }
+        if next == self.samples.len() {
+            return self.samples[next - 1].position;
+        }
+
+        let (a, b) = (self.samples[next - 1], self.samples[next]);
+        let t = (time - a.time) / (b.time - a.time);
+        a.position + (b.position - a.position) * t
+    }
+}
+
+/// A trajectory being replayed onto a body
+#[derive(Debug, Clone)]
+pub struct Playback {
+    pub body: usize,
+    pub trajectory: Trajectory,
+    /// Trajectory seconds played per simulated second
+    pub speed: f64,
+    /// Start over at the end instead of stopping
+    pub looping: bool,
+    /// Trajectory time reached so far
+    pub elapsed: f64,
+    /// Whether the body was static before playback took it over
+    was_static: bool,
+}
+
+impl PhysicsWorld {
+    /// Start sampling a body's position after every step, beginning with its current
+    /// position. Restarting discards the samples recorded so far.
+    pub fn start_recording(&mut self, body: usize) -> bool {
+        let Some(obj) = self.objects.get(body) else {
+            return false;
+        };
+        let sample = TrajectorySample {
+            time: self.time,
+            position: obj.position,
+        };
+        self.recordings.insert(body, vec![sample]);
+        true
+    }
+
+    /// Samples recorded for a body so far, or `None` if it is not being recorded
+    pub fn recorded_trajectory(&self, body: usize) -> Option<Trajectory> {
+        Trajectory::new(self.recordings.get(&body)?.clone()).ok()
+    }
+
+    /// Append the current position of every recorded body
+    pub(crate) fn record_trajectories(&mut self) {
+        for (&body, samples) in &mut self.recordings {
+            if let Some(obj) = self.objects.get(body) {
+                samples.push(TrajectorySample {
+                    time: self.time,
+                    position: obj.position,
+                });
+            }
+        }
+    }
+
+    /// Drive a body along `trajectory`, moving it to the first sample now and along
+    /// the trajectory by `speed` trajectory seconds per simulated second from then on.
+    /// The body is kinematic while playing: static to the solver, but carrying the
+    /// playback velocity so it pushes what it touches. At the end it loops, or stops at
+    /// the last sample and gets its previous static flag back on the following step.
+    /// Replaces any playback already running on the body.
+    pub fn play_trajectory(
+        &mut self,
+        body: usize,
+        trajectory: Trajectory,
+        speed: f64,
+        looping: bool,
+    ) -> bool {
+        let Some(obj) = self.objects.get_mut(body) else {
+            return false;
+        };
+        let was_static = match self.playbacks.iter().position(|p| p.body == body) {
+            Some(index) => self.playbacks.remove(index).was_static,
+            None => obj.is_static,
+        };
+
+        obj.is_static = true;
+        obj.position = trajectory.position_at(0.0);
+        obj.velocity = Vec3::zero();
+        self.playbacks.push(Playback {
+            body,
+            trajectory,
+            speed,
+            looping,
+            elapsed: 0.0,
+            was_static,
+        });
+        true
+    }
+
+    /// Whether a trajectory is currently playing on a body
+    pub fn is_playing(&self, body: usize) -> bool {
+        self.playbacks.iter().any(|p| p.body == body)
+    }
+
+    /// Advance every playback by one step, before the bodies are simulated
+    pub(crate) fn advance_playbacks(&mut self) {
+        let dt = self.dt;
+        let objects = &mut self.objects;
+        self.playbacks.retain_mut(|playback| {
+            let Some(obj) = objects.get_mut(playback.body) else {
+                return false;
+            };
+
+            // A body that reached the last sample last step is released where it is
+            let duration = playback.trajectory.duration();
+            if !playback.looping && playback.elapsed >= duration {
+                obj.velocity = Vec3::zero();
+                obj.is_static = playback.was_static;
+                obj.wake();
+                return false;
+            }
+
+            playback.elapsed += dt * playback.speed;
+            if playback.looping && duration > 0.0 {
+                playback.elapsed %= duration;
+            }
+
+            let target = playback.trajectory.position_at(playback.elapsed);
+            obj.velocity = (target - obj.position) * (1.0 / dt);
+            obj.position = target;
+            true
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Record `steps` steps of a ball dropped from rest at height 50
+    fn recorded_fall(steps: usize) -> Trajectory {
+        let mut world = PhysicsWorld::new();
+        let ball = world.add_object("sphere".to_string(), 1.0, Vec3::new(0.0, 50.0, 0.0));
+        world.start_recording(ball);
+        for _ in 0..steps {
+            world.step();
+        }
+        world.recorded_trajectory(ball).unwrap()
+    }
+
+    #[test]
+    fn test_playback_reproduces_recorded_free_fall() {
+        let trajectory = recorded_fall(60);
+        assert_eq!(trajectory.samples().len(), 61);
+        assert!(trajectory.samples()[60].position.y < 46.0);
+
+        let mut world = PhysicsWorld::new();
+        let ghost = world.add_object("sphere".to_string(), 1.0, Vec3::new(5.0, 0.0, 0.0));
+        assert!(world.play_trajectory(ghost, trajectory.clone(), 1.0, false));
+        assert_eq!(world.objects[ghost].position, Vec3::new(0.0, 50.0, 0.0));
+
+        // At full speed every step lands on the next recorded sample
+        for sample in &trajectory.samples()[1..] {
+            world.step();
+            let error = (world.objects[ghost].position - sample.position).length();
+            assert!(error < 1e-9, "off by {} at t = {}", error, sample.time);
+        }
+
+        // Past the end playback stops and the body is dynamic again
+        world.step();
+        assert!(!world.is_playing(ghost));
+        assert!(!world.objects[ghost].is_static);
+    }
+
+    #[test]
+    fn test_half_speed_playback_interpolates_between_samples() {
+        let trajectory = recorded_fall(60);
+        let samples = trajectory.samples();
+
+        let mut world = PhysicsWorld::new();
+        let ghost = world.add_object("sphere".to_string(), 1.0, Vec3::zero());
+        world.play_trajectory(ghost, trajectory.clone(), 0.5, false);
+
+        for step in 1..=120 {
+            world.step();
+            let position = world.objects[ghost].position;
+            // Two steps per sample: on a sample, then halfway to the next
+            let expected = if step % 2 == 0 {
+                samples[step / 2].position
+            } else {
+                (samples[step / 2].position + samples[step / 2 + 1].position) * 0.5
+            };
+            assert!(
+                (position - expected).length() < 1e-9,
+                "step {}: {:?} vs {:?}",
+                step,
+                position,
+                expected
+            );
+        }
+    }
+
+    #[test]
+    fn test_looping_playback_wraps_around() {
+        let trajectory = Trajectory::new(vec![
+            TrajectorySample {
+                time: 2.0,
+                position: Vec3::zero(),
+            },
+            TrajectorySample {
+                time: 3.0,
+                position: Vec3::new(6.0, 0.0, 0.0),
+            },
+        ])
+        .unwrap();
+        assert_eq!(trajectory.duration(), 1.0);
+
+        let mut world = PhysicsWorld::new();
+        world.gravity = Vec3::zero();
+        let body = world.add_object("box".to_string(), 1.0, Vec3::new(0.0, 5.0, 0.0));
+        world.play_trajectory(body, trajectory, 1.0, true);
+        for _ in 0..90 {
+            world.step();
+        }
+
+        // 1.5 s into a 1 s loop is halfway along
+        assert!(world.is_playing(body));
+        assert!((world.objects[body].position.x - 3.0).abs() < 1e-6);
+        assert!((world.objects[body].velocity.x - 6.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_invalid_trajectories_are_rejected() {
+        assert!(Trajectory::new(vec![]).is_err());
+        let sample = |time: f64| TrajectorySample {
+            time,
+            position: Vec3::zero(),
+        };
+        assert!(Trajectory::new(vec![sample(0.0), sample(0.0)]).is_err());
+        assert!(Trajectory::new(vec![sample(1.0), sample(0.5)]).is_err());
+    }
+}
@@ -0,0 +1,293 @@
+// Vector Standard Library for Matrix Language
+// Construction, distances, products, normalization, magnitude clamping and
+// projection/rejection on [x, y, z] arrays and x/y/z structs
+
+use crate::eval::{Interpreter, RuntimeError, Value};
+use crate::stdlib::physics::{expect_number, expect_vec3, vec3_to_value};
+use crate::stdlib::Vec3;
+
+pub fn register_vector_functions(interpreter: &mut Interpreter) {
+    // Build an [x, y, z] vector from three numbers
+    interpreter.environment.define(
+        "vec3".to_string(),
+        Value::BuiltinFunction {
+            name: "vec3".to_string(),
+            arity: 3,
+            func: |args| {
+                Ok(vec3_to_value(Vec3::new(
+                    expect_number(&args[0], "x")?,
+                    expect_number(&args[1], "y")?,
+                    expect_number(&args[2], "z")?,
+                )))
+            },
+        },
+    );
+
+    // Distance between two points
+    interpreter.environment.define(
+        "distance".to_string(),
+        Value::BuiltinFunction {
+            name: "distance".to_string(),
+            arity: 2,
+            func: |args| {
+                let a = expect_vec3(&args[0], "Point")?;
+                let b = expect_vec3(&args[1], "Point")?;
+                Ok(Value::Float((a - b).length()))
+            },
+        },
+    );
+
+    // Scale a vector down so its length is at most `max`
+    interpreter.environment.define(
+        "clamp_magnitude".to_string(),
+        Value::BuiltinFunction {
+            name: "clamp_magnitude".to_string(),
+            arity: 2,
+            func: |args| {
+                let v = expect_vec3(&args[0], "Vector")?;
+                let max = expect_number(&args[1], "Maximum magnitude")?;
+                if max < 0.0 {
+                    return Err(RuntimeError::Generic {
+                        message: "Maximum magnitude must not be negative".to_string(),
+                    });
+                }
+                Ok(same_representation(&args[0], clamp_magnitude(v, max)))
+            },
+        },
+    );
+
+    interpreter.environment.define(
+        "dot".to_string(),
+        Value::BuiltinFunction {
+            name: "dot".to_string(),
+            arity: 2,
+            func: |args| {
+                let a = expect_vec3(&args[0], "Vector")?;
+                let b = expect_vec3(&args[1], "Vector")?;
+                Ok(Value::Float(a.dot(&b)))
+            },
+        },
+    );
+
+    interpreter.environment.define(
+        "cross".to_string(),
+        Value::BuiltinFunction {
+            name: "cross".to_string(),
+            arity: 2,
+            func: |args| {
+                let a = expect_vec3(&args[0], "Vector")?;
+                let b = expect_vec3(&args[1], "Vector")?;
+                Ok(same_representation(&args[0], a.cross(&b)))
+            },
+        },
+    );
+
+    // Unit vector in the same direction
+    interpreter.environment.define(
+        "normalize".to_string(),
+        Value::BuiltinFunction {
+            name: "normalize".to_string(),
+            arity: 1,
+            func: |args| {
+                let v = expect_vec3(&args[0], "Vector")?;
+                let length = v.length();
+                if length == 0.0 {
+                    return Err(RuntimeError::Generic {
+                        message: "Cannot normalize a zero vector".to_string(),
+                    });
+                }
+                Ok(same_representation(&args[0], v * (1.0 / length)))
+            },
+        },
+    );
+
+    // Component of `a` along `onto`
+    interpreter.environment.define(
+        "project".to_string(),
+        Value::BuiltinFunction {
+            name: "project".to_string(),
+            arity: 2,
+            func: |args| {
+                let a = expect_vec3(&args[0], "Vector")?;
+                let onto = expect_vec3(&args[1], "Projection target")?;
+                project(a, onto)
+                    .map(|v| same_representation(&args[0], v))
+                    .ok_or_else(zero_projection_target)
+            },
+        },
+    );
+
+    // Component of `a` perpendicular to `onto`
+    interpreter.environment.define(
+        "reject".to_string(),
+        Value::BuiltinFunction {
+            name: "reject".to_string(),
+            arity: 2,
+            func: |args| {
+                let a = expect_vec3(&args[0], "Vector")?;
+                let onto = expect_vec3(&args[1], "Projection target")?;
+                reject(a, onto)
+                    .map(|v| same_representation(&args[0], v))
+                    .ok_or_else(zero_projection_target)
+            },
+        },
+    );
+}
+
+/// `v` as a value of the same kind as `template`: structs keep their name and any
+/// other fields, everything else becomes an `[x, y, z]` array
+fn same_representation(template: &Value, v: Vec3) -> Value {
+    match template {
+        Value::Struct { name, fields } => {
+            let mut fields = fields.clone();
+            fields.insert("x".to_string(), Value::Float(v.x));
+            fields.insert("y".to_string(), Value::Float(v.y));
+            fields.insert("z".to_string(), Value::Float(v.z));
+            Value::Struct {
+                name: name.clone(),
+                fields,
+            }
+        }
+        _ => vec3_to_value(v),
+    }
+}
+
+fn zero_projection_target() -> RuntimeError {
+    RuntimeError::Generic {
+        message: "Cannot project onto a zero vector".to_string(),
+    }
+}
+
+pub fn clamp_magnitude(v: Vec3, max: f64) -> Vec3 {
+    let length = v.length();
+    if length > max {
+        v * (max / length)
+    } else {
+        v
+    }
+}
+
+/// Projection of `a` onto `onto`, or `None` for a zero-length target
+pub fn project(a: Vec3, onto: Vec3) -> Option<Vec3> {
+    let length_squared = onto.dot(&onto);
+    if length_squared == 0.0 {
+        return None;
+    }
+    Some(onto * (a.dot(&onto) / length_squared))
+}
+
+/// Part of `a` perpendicular to `onto`, or `None` for a zero-length target
+pub fn reject(a: Vec3, onto: Vec3) -> Option<Vec3> {
+    project(a, onto).map(|p| a - p)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_clamp_magnitude_scales_long_vectors() {
+        let clamped = clamp_magnitude(Vec3::new(3.0, 4.0, 0.0), 2.5);
+        assert!((clamped.length() - 2.5).abs() < 1e-12);
+        assert!((clamped.x - 1.5).abs() < 1e-12);
+
+        let short = Vec3::new(0.3, 0.4, 0.0);
+        assert_eq!(clamp_magnitude(short, 2.5), short);
+    }
+
+    #[test]
+    fn test_project_plus_reject_reconstructs_vector() {
+        let a = Vec3::new(2.0, -1.0, 4.0);
+        let onto = Vec3::new(1.0, 1.0, 0.0);
+        let projected = project(a, onto).unwrap();
+        let rejected = reject(a, onto).unwrap();
+
+        let sum = projected + rejected;
+        assert!((sum - a).length() < 1e-12);
+        assert!(rejected.dot(&onto).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_project_onto_zero_vector_errors() {
+        let mut interpreter = Interpreter::new();
+        register_vector_functions(&mut interpreter);
+        let project = interpreter.environment.get("project").cloned().unwrap();
+        let v = vec3_to_value(Vec3::new(1.0, 2.0, 3.0));
+        let zero = vec3_to_value(Vec3::zero());
+
+        assert!(interpreter.call_value(project, &[v, zero]).is_err());
+    }
+
+    fn vec3_struct(x: f64, y: f64, z: f64) -> Value {
+        Value::Struct {
+            name: "Vec3".to_string(),
+            fields: [("x", x), ("y", y), ("z", z)]
+                .into_iter()
+                .map(|(axis, value)| (axis.to_string(), Value::Float(value)))
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_vector_builtins_accept_arrays_and_structs() {
+        let mut interpreter = Interpreter::new();
+        register_vector_functions(&mut interpreter);
+        let get = |name: &str| interpreter.environment.get(name).cloned().unwrap();
+        let (dot, cross) = (get("dot"), get("cross"));
+
+        let structs = [vec3_struct(1.0, 2.0, 3.0), vec3_struct(4.0, -5.0, 6.0)];
+        let arrays = [
+            vec3_to_value(Vec3::new(1.0, 2.0, 3.0)),
+            vec3_to_value(Vec3::new(4.0, -5.0, 6.0)),
+        ];
+        assert_eq!(
+            interpreter.call_value(dot.clone(), &structs).unwrap(),
+            Value::Float(12.0)
+        );
+        assert_eq!(
+            interpreter.call_value(dot.clone(), &arrays).unwrap(),
+            Value::Float(12.0)
+        );
+
+        // Results keep the representation of the first argument
+        assert_eq!(
+            interpreter.call_value(cross.clone(), &structs).unwrap(),
+            vec3_struct(27.0, 6.0, -13.0)
+        );
+        assert_eq!(
+            interpreter.call_value(cross, &arrays).unwrap(),
+            vec3_to_value(Vec3::new(27.0, 6.0, -13.0))
+        );
+
+        let flat = Value::Struct {
+            name: "Vec2".to_string(),
+            fields: [("x", 1.0), ("y", 2.0)]
+                .into_iter()
+                .map(|(axis, value)| (axis.to_string(), Value::Float(value)))
+                .collect(),
+        };
+        assert!(interpreter
+            .call_value(dot, &[flat, structs[0].clone()])
+            .is_err());
+    }
+
+    #[test]
+    fn test_distance_between_points() {
+        let mut interpreter = Interpreter::new();
+        register_vector_functions(&mut interpreter);
+        let distance = interpreter.environment.get("distance").cloned().unwrap();
+
+        let a = vec3_struct(1.0, 2.0, 3.0);
+        let b = vec3_to_value(Vec3::new(4.0, 6.0, 3.0));
+        assert_eq!(
+            interpreter
+                .call_value(distance.clone(), &[a.clone(), b])
+                .unwrap(),
+            Value::Float(5.0)
+        );
+        assert_eq!(
+            interpreter.call_value(distance, &[a.clone(), a]).unwrap(),
+            Value::Float(0.0)
+        );
+    }
+}
@@ -1,4 +1,7 @@
 use crate::ast::nodes::*;
+use crate::lexer::Lexer;
+use crate::parser::Parser;
+use crate::stdlib::prelude::PRELUDE_SOURCE;
 use crate::types::*;
 use std::collections::HashMap;
 
@@ -33,10 +36,26 @@ impl Unifier {
             | (Type::String, Type::String)
             | (Type::Unit, Type::Unit) => Ok(()),
 
+            // Dimension-tagged floats must agree; untagged floats are unit-agnostic
+            (Type::Quantity(d1), Type::Quantity(d2)) => {
+                if d1 == d2 {
+                    Ok(())
+                } else {
+                    Err(TypeError::DimensionMismatch {
+                        expected: d1.to_string(),
+                        found: d2.to_string(),
+                        line: 0,
+                        column: 0,
+                    })
+                }
+            }
+            (Type::Quantity(_), Type::Float) | (Type::Float, Type::Quantity(_)) => Ok(()),
+
             // Struct types
             (Type::Struct(name1), Type::Struct(name2)) if name1 == name2 => Ok(()),
 
             // Type variables
+            (Type::TypeVar(var1), Type::TypeVar(var2)) if var1 == var2 => Ok(()),
             (Type::TypeVar(var), ty) | (ty, Type::TypeVar(var)) => self.bind_type_var(var, ty),
 
             // Arrays
@@ -163,6 +182,10 @@ pub struct TypeChecker {
     unifier: Unifier,
     import_stack: Vec<String>,
     warnings: Vec<String>,
+    /// Minimum argument count of named functions with default parameters
+    required_args: HashMap<String, usize>,
+    /// Types of the exported members of each declared module
+    modules: HashMap<String, HashMap<String, InferredType>>,
 }
 
 impl Default for TypeChecker {
@@ -173,11 +196,28 @@ impl Default for TypeChecker {
 
 impl TypeChecker {
     pub fn new() -> Self {
-        Self {
-            context: TypeContext::new(),
+        let context = TypeContext::new();
+        let required_args = context.builtin_required_args.clone();
+        let mut checker = Self {
+            context,
             unifier: Unifier::new(),
             import_stack: Vec::new(),
             warnings: Vec::new(),
+            required_args,
+            modules: HashMap::new(),
+        };
+        checker.check_prelude();
+        checker
+    }
+
+    /// Bind the prelude helpers that `init` defines in every interpreter. The prelude
+    /// ships with the crate, so failing to parse or type check it is a bug and panics.
+    fn check_prelude(&mut self) {
+        let program = Parser::new(Lexer::new(PRELUDE_SOURCE))
+            .and_then(|mut parser| parser.parse_program())
+            .unwrap_or_else(|e| panic!("Prelude failed to parse: {}", e));
+        if let Err(e) = self.check_program(&program) {
+            panic!("Prelude failed to type check: {}", e);
         }
     }
 
@@ -193,6 +233,38 @@ impl TypeChecker {
         })
     }
 
+    /// Check one REPL entry against the declarations accepted so far, without
+    /// re-walking earlier entries. Warnings are reported for this entry only, later
+    /// definitions replace earlier ones, and a rejected entry leaves the environment
+    /// as it was. Returns the type of the entry's last item.
+    pub fn check_incremental(&mut self, program: &Program) -> TypeResult<InferredType> {
+        let context = self.context.clone();
+        let unifier = self.unifier.clone();
+        let required_args = self.required_args.clone();
+        let modules = self.modules.clone();
+        self.warnings.clear();
+
+        let mut last = InferredType {
+            ty: Type::Unit,
+            constraints: Vec::new(),
+        };
+        for item in &program.items {
+            match self.check_item(item) {
+                Ok(inferred) => last = inferred,
+                Err(error) => {
+                    self.context = context;
+                    self.unifier = unifier;
+                    self.required_args = required_args;
+                    self.modules = modules;
+                    return Err(error);
+                }
+            }
+        }
+
+        last.ty = self.unifier.finalize_type(&last.ty);
+        Ok(last)
+    }
+
     pub fn check_item(&mut self, item: &Item) -> TypeResult<InferredType> {
         match item {
             Item::StructDef(struct_def) => {
@@ -227,11 +299,45 @@ impl TypeChecker {
 
             Item::LetBinding(let_binding) => self.check_let_binding(let_binding),
 
+            Item::Module(module_def) => self.check_module_def(module_def),
+
             Item::Import(import) => {
                 // Implement import checking
                 // Validate that the imported module exists and check for circular imports
                 let module_name = import.module_path.clone();
 
+                // Declared modules bring their exports into scope unqualified
+                if let Some(exports) = self.modules.get(&module_name) {
+                    let names: Vec<&String> = match &import.items {
+                        Some(items) => items.iter().collect(),
+                        None => exports.keys().collect(),
+                    };
+                    let mut imported = Vec::new();
+                    for name in names {
+                        let Some(ty) = exports.get(name) else {
+                            return Err(TypeError::UnknownIdentifier {
+                                name: format!("{}::{}", module_name, name),
+                                line: import.span.line,
+                                column: import.span.column,
+                            });
+                        };
+                        imported.push((name.clone(), ty.clone()));
+                    }
+                    for (name, ty) in imported {
+                        if let Some(&required) = self
+                            .required_args
+                            .get(&format!("{}::{}", module_name, name))
+                        {
+                            self.required_args.insert(name.clone(), required);
+                        }
+                        self.context.env.bind(name, ty);
+                    }
+                    return Ok(InferredType {
+                        ty: Type::Unit,
+                        constraints: Vec::new(),
+                    });
+                }
+
                 // Check if the module is a built-in module
                 let is_builtin = matches!(module_name.as_str(), "std" | "math" | "io" | "fs");
 
@@ -260,18 +366,58 @@ impl TypeChecker {
         }
     }
 
+    /// Check a module body in its own scope, then bind its exports as `Module::name`.
+    /// Private members are left out, so naming them from outside is an unknown
+    /// identifier.
+    fn check_module_def(&mut self, module_def: &ModuleDef) -> TypeResult<InferredType> {
+        let outer_required_args = self.required_args.clone();
+        let outer_variadic_builtins = self.context.variadic_builtins.clone();
+        self.context.push_scope();
+        let result = module_def
+            .items
+            .iter()
+            .try_for_each(|item| self.check_item(item).map(drop));
+        let members = std::mem::take(&mut self.context.env.bindings);
+        self.context.pop_scope();
+        let module_required_args = std::mem::replace(&mut self.required_args, outer_required_args);
+        self.context.variadic_builtins = outer_variadic_builtins;
+        result?;
+
+        let mut exports = HashMap::new();
+        for name in &module_def.exports {
+            let Some(ty) = members.get(name) else {
+                continue;
+            };
+            let qualified = format!("{}::{}", module_def.name, name);
+            if let Some(&required) = module_required_args.get(name) {
+                self.required_args.insert(qualified.clone(), required);
+            }
+            self.context.env.bind(qualified, ty.clone());
+            exports.insert(name.clone(), ty.clone());
+        }
+        self.modules.insert(module_def.name.clone(), exports);
+
+        Ok(InferredType {
+            ty: Type::Unit,
+            constraints: Vec::new(),
+        })
+    }
+
     fn check_function_def(&mut self, func_def: &FunctionDef) -> TypeResult<InferredType> {
         self.context.push_scope();
 
         // Add parameters to environment
         let mut param_types = Vec::new();
         for param in &func_def.params {
-            let param_type = InferredType {
-                ty: param.type_annotation.clone(),
-                constraints: Vec::new(),
-            };
-            param_types.push(param.type_annotation.clone());
-            self.context.env.bind(param.name.clone(), param_type);
+            let ty = self.param_type(param)?;
+            param_types.push(ty.clone());
+            self.context.env.bind(
+                param.name.clone(),
+                InferredType {
+                    ty,
+                    constraints: Vec::new(),
+                },
+            );
         }
 
         // Check function body
@@ -294,22 +440,89 @@ impl TypeChecker {
         self.context
             .env
             .bind(func_def.name.clone(), func_inferred_type.clone());
+        self.record_required_args(&func_def.name, &func_def.params);
 
         Ok(func_inferred_type)
     }
 
+    /// A parameter's type, fresh for unannotated parameters, checked against its
+    /// default value if it has one
+    fn param_type(&mut self, param: &Parameter) -> TypeResult<Type> {
+        let ty = if param.is_untyped() {
+            self.context.fresh_type_var()
+        } else {
+            param.type_annotation.clone()
+        };
+        if let Some(default) = &param.default {
+            let default_type = self.check_expression(default)?;
+            self.unifier.unify(&default_type.ty, &ty)?;
+        }
+        Ok(ty)
+    }
+
+    /// Remember how many arguments a named function needs when some have defaults
+    fn record_required_args(&mut self, name: &str, params: &[Parameter]) {
+        self.context.variadic_builtins.remove(name);
+        if params.iter().any(|p| p.default.is_some()) {
+            let required = params.iter().filter(|p| p.default.is_none()).count();
+            self.required_args.insert(name.to_string(), required);
+        } else {
+            self.required_args.remove(name);
+        }
+    }
+
+    fn record_binding_required_args(&mut self, binding: &LetBinding) {
+        match &binding.value {
+            Expression::Lambda { params, .. } => self.record_required_args(&binding.name, params),
+            _ => {
+                self.required_args.remove(&binding.name);
+                self.context.variadic_builtins.remove(&binding.name);
+            }
+        }
+    }
+
+    /// Type of a let-bound value. A lambda may call itself by the name it is bound to,
+    /// so while its body is checked that name has a function type with the lambda's
+    /// parameters, which is then unified with the lambda's own type.
+    fn check_binding_value(&mut self, binding: &LetBinding) -> TypeResult<InferredType> {
+        let Expression::Lambda { params, .. } = &binding.value else {
+            return self.check_expression(&binding.value);
+        };
+
+        let param_types = params
+            .iter()
+            .map(|param| self.param_type(param))
+            .collect::<TypeResult<Vec<_>>>()?;
+        let return_type = self.context.fresh_type_var();
+        let own_type = Type::Function(param_types, Box::new(return_type));
+        self.context.env.bind(
+            binding.name.clone(),
+            InferredType {
+                ty: own_type.clone(),
+                constraints: Vec::new(),
+            },
+        );
+        self.record_binding_required_args(binding);
+
+        let value_type = self.check_expression(&binding.value)?;
+        self.unifier.unify(&own_type, &value_type.ty)?;
+        Ok(value_type)
+    }
+
     fn check_let_binding(&mut self, let_binding: &LetBinding) -> TypeResult<InferredType> {
-        let value_type = self.check_expression(&let_binding.value)?;
+        let value_type = self.check_binding_value(let_binding)?;
 
         // Unify with type annotation if present
         if let Some(ref type_annotation) = let_binding.type_annotation {
             self.unifier.unify(&value_type.ty, type_annotation)?;
         }
+        let value_type = Self::with_annotated_dimension(value_type, &let_binding.type_annotation);
 
         // Add binding to environment
         self.context
             .env
             .bind(let_binding.name.clone(), value_type.clone());
+        self.record_binding_required_args(let_binding);
 
         Ok(value_type)
     }
@@ -335,6 +548,11 @@ impl TypeChecker {
                 constraints: Vec::new(),
             }),
 
+            Expression::Identifier(name, _) if name == "None" => Ok(InferredType {
+                ty: Type::TypeApp("Option".to_string(), vec![self.context.fresh_type_var()]),
+                constraints: Vec::new(),
+            }),
+
             Expression::Identifier(name, span) => {
                 if let Some(inferred_type) = self.context.env.lookup(name) {
                     Ok(inferred_type.clone())
@@ -379,6 +597,7 @@ impl TypeChecker {
             Expression::ArrayLiteral(elements, span) => self.check_array_literal(elements, span),
 
             Expression::MatrixLiteral(rows, span) => self.check_matrix_literal(rows, span),
+            Expression::DictLiteral(entries, _) => self.check_dict_literal(entries),
 
             Expression::MatrixComprehension {
                 element,
@@ -393,6 +612,49 @@ impl TypeChecker {
                 span,
             } => self.check_if_expression(condition, then_branch, else_branch, span),
 
+            Expression::While {
+                condition, body, ..
+            } => {
+                let cond_type = self.check_expression(condition)?;
+                self.unifier.unify(&cond_type.ty, &Type::Bool)?;
+                let body_type = self.check_expression(body)?;
+                Ok(InferredType {
+                    ty: Type::Unit,
+                    constraints: [cond_type.constraints, body_type.constraints].concat(),
+                })
+            }
+
+            Expression::For {
+                variable,
+                iterable,
+                body,
+                ..
+            } => {
+                let iterable_type = self.check_expression(iterable)?;
+                let element_type = self.context.fresh_type_var();
+                self.unifier.unify(
+                    &iterable_type.ty,
+                    &Type::Array(Box::new(element_type.clone())),
+                )?;
+
+                self.context.push_scope();
+                self.context.env.bind(
+                    variable.clone(),
+                    InferredType {
+                        ty: element_type,
+                        constraints: Vec::new(),
+                    },
+                );
+                let body_type = self.check_expression(body);
+                self.context.pop_scope();
+                let body_type = body_type?;
+
+                Ok(InferredType {
+                    ty: Type::Unit,
+                    constraints: [iterable_type.constraints, body_type.constraints].concat(),
+                })
+            }
+
             Expression::Match {
                 expression,
                 arms,
@@ -628,6 +890,11 @@ impl TypeChecker {
         let left_type = self.check_expression(left)?;
         let right_type = self.check_expression(right)?;
 
+        if let Some(result) = self.check_dimensions(&left_type.ty, op, right, &right_type.ty, span)
+        {
+            return result;
+        }
+
         match op {
             BinaryOperator::Add
             | BinaryOperator::Sub
@@ -789,6 +1056,125 @@ impl TypeChecker {
         }
     }
 
+    /// Dimensional analysis for arithmetic on `Float<unit>` operands.
+    /// Returns `None` when neither side carries a dimension.
+    fn check_dimensions(
+        &self,
+        left: &Type,
+        op: &BinaryOperator,
+        right_expr: &Expression,
+        right: &Type,
+        span: &Span,
+    ) -> Option<TypeResult<InferredType>> {
+        let dimension_of = |ty: &Type| match ty {
+            Type::Quantity(d) => Some(*d),
+            Type::Float => Some(Dimension::dimensionless()),
+            _ => None,
+        };
+
+        if !matches!(left, Type::Quantity(_)) && !matches!(right, Type::Quantity(_)) {
+            return None;
+        }
+        let right_dim = match (op, right) {
+            // Integer exponents are plain numbers
+            (BinaryOperator::Pow, Type::Int) => Dimension::dimensionless(),
+            _ => dimension_of(right)?,
+        };
+        let left_dim = dimension_of(left)?;
+
+        let quantity = |d: Dimension| {
+            Ok(InferredType {
+                ty: if d.is_dimensionless() {
+                    Type::Float
+                } else {
+                    Type::Quantity(d)
+                },
+                constraints: Vec::new(),
+            })
+        };
+        // Exponents are stored as `i8`; a result outside that range is an error
+        let checked = |d: Option<Dimension>, found: String| match d {
+            Some(d) => quantity(d),
+            None => Err(TypeError::DimensionMismatch {
+                expected: "unit exponents between -128 and 127".to_string(),
+                found,
+                line: span.line,
+                column: span.column,
+            }),
+        };
+
+        match op {
+            BinaryOperator::Add | BinaryOperator::Sub | BinaryOperator::Mod => {
+                match (left, right) {
+                    (Type::Quantity(l), Type::Quantity(r)) if l != r => {
+                        Some(Err(TypeError::DimensionMismatch {
+                            expected: l.to_string(),
+                            found: r.to_string(),
+                            line: span.line,
+                            column: span.column,
+                        }))
+                    }
+                    // An untagged float takes on the other operand's dimension
+                    (Type::Quantity(d), _) | (_, Type::Quantity(d)) => Some(quantity(*d)),
+                    _ => None,
+                }
+            }
+            BinaryOperator::Mul => Some(checked(
+                left_dim.mul(&right_dim),
+                format!("({})*({})", left_dim, right_dim),
+            )),
+            BinaryOperator::Div => Some(checked(
+                left_dim.div(&right_dim),
+                format!("({})/({})", left_dim, right_dim),
+            )),
+            BinaryOperator::Pow => match right_expr {
+                Expression::IntLiteral(power, _) if right_dim.is_dimensionless() => Some(checked(
+                    i8::try_from(*power)
+                        .ok()
+                        .and_then(|power| left_dim.powi(power)),
+                    format!("({})^{}", left_dim, power),
+                )),
+                _ => Some(Err(TypeError::DimensionMismatch {
+                    expected: "dimensionless integer exponent".to_string(),
+                    found: right.to_string(),
+                    line: span.line,
+                    column: span.column,
+                })),
+            },
+            BinaryOperator::Eq
+            | BinaryOperator::Ne
+            | BinaryOperator::Lt
+            | BinaryOperator::Le
+            | BinaryOperator::Gt
+            | BinaryOperator::Ge => match (left, right) {
+                (Type::Quantity(l), Type::Quantity(r)) if l != r => {
+                    Some(Err(TypeError::DimensionMismatch {
+                        expected: l.to_string(),
+                        found: r.to_string(),
+                        line: span.line,
+                        column: span.column,
+                    }))
+                }
+                _ => Some(Ok(InferredType {
+                    ty: Type::Bool,
+                    constraints: Vec::new(),
+                })),
+            },
+            _ => None,
+        }
+    }
+
+    /// A `Float<unit>` annotation is more precise than the inferred `Float`
+    fn with_annotated_dimension(inferred: InferredType, annotation: &Option<Type>) -> InferredType {
+        match annotation {
+            Some(ty @ Type::Quantity(_)) if inferred.ty == Type::Float => InferredType {
+                ty: ty.clone(),
+                constraints: inferred.constraints,
+            },
+            _ => inferred,
+        }
+    }
+
     fn check_unary_op(
         &mut self,
         op: &UnaryOperator,
@@ -807,9 +1193,10 @@ impl TypeChecker {
             }
 
             UnaryOperator::Neg => {
-                // Require numeric type
-                match &expr_type.ty {
-                    Type::Int | Type::Float => Ok(expr_type),
+                // Require numeric type; an operand whose type is not known yet, such as
+                // an unannotated parameter, keeps its type variable
+                match self.unifier.finalize_type(&expr_type.ty) {
+                    Type::Int | Type::Float | Type::TypeVar(_) => Ok(expr_type),
                     _ => Err(TypeError::TypeMismatch {
                         expected: "Numeric type".to_string(),
                         found: expr_type.ty.to_string(),
@@ -847,7 +1234,17 @@ impl TypeChecker {
 
         match &func_type.ty {
             Type::Function(param_types, return_type) => {
-                if args.len() != param_types.len() {
+                // Functions with defaults may omit trailing arguments, and variadic
+                // builtins take any number of extra ones
+                let (required, variadic) = match func {
+                    Expression::Identifier(name, _) => (
+                        self.required_args.get(name).copied(),
+                        self.context.variadic_builtins.contains(name),
+                    ),
+                    _ => (None, false),
+                };
+                let required = required.unwrap_or(param_types.len());
+                if args.len() < required || (args.len() > param_types.len() && !variadic) {
                     return Err(TypeError::WrongArgumentCount {
                         expected: param_types.len(),
                         found: args.len(),
@@ -865,6 +1262,9 @@ impl TypeChecker {
                     let arg_type = self.check_expression(arg)?;
                     self.unifier.unify(&arg_type.ty, param_type)?;
                 }
+                for arg in args.iter().skip(fresh_param_types.len()) {
+                    self.check_expression(arg)?;
+                }
 
                 Ok(InferredType {
                     ty: fresh_return_type,
@@ -887,8 +1287,9 @@ impl TypeChecker {
         span: &Span,
     ) -> TypeResult<InferredType> {
         let expr_type = self.check_expression(expr)?;
+        let ty = self.unifier.finalize_type(&expr_type.ty);
 
-        match &expr_type.ty {
+        match &ty {
             Type::Struct(struct_name) => {
                 if let Some(field_type) = self.context.structs.get_field_type(struct_name, field) {
                     Ok(InferredType {
@@ -905,9 +1306,16 @@ impl TypeChecker {
                 }
             }
 
+            // Builtins returning records (raycast hits, body states) leave their
+            // result open, so fields of an unresolved value are not checked
+            Type::TypeVar(_) => Ok(InferredType {
+                ty: self.context.fresh_type_var(),
+                constraints: Vec::new(),
+            }),
+
             _ => Err(TypeError::TypeMismatch {
                 expected: "Struct type".to_string(),
-                found: expr_type.ty.to_string(),
+                found: ty.to_string(),
                 line: span.line,
                 column: span.column,
             }),
@@ -993,6 +1401,22 @@ impl TypeChecker {
         })
     }
 
+    /// Dictionaries have string keys and values of a single type, `Dict<T>`
+    fn check_dict_literal(&mut self, entries: &[(String, Expression)]) -> TypeResult<InferredType> {
+        let value_type = self.context.fresh_type_var();
+        let mut constraints = Vec::new();
+        for (_, value) in entries {
+            let inferred = self.check_expression(value)?;
+            self.unifier.unify(&value_type, &inferred.ty)?;
+            constraints.extend(inferred.constraints);
+        }
+
+        Ok(InferredType {
+            ty: Type::TypeApp("Dict".to_string(), vec![value_type]),
+            constraints,
+        })
+    }
+
     fn check_matrix_literal(
         &mut self,
         rows: &[Vec<Expression>],
@@ -1125,9 +1549,11 @@ impl TypeChecker {
                 constraints: [then_type.constraints, else_type.constraints].concat(),
             })
         } else {
-            // If no else branch, return Option type
+            // Without an else branch the expression is Unit, so the then branch must be
+            // Unit too; `let x = if c { 1 }` is a type error
+            self.unifier.unify(&then_type.ty, &Type::Unit)?;
             Ok(InferredType {
-                ty: Type::Option(Box::new(then_type.ty)),
+                ty: Type::Unit,
                 constraints: then_type.constraints,
             })
         }
@@ -1137,7 +1563,7 @@ impl TypeChecker {
         &mut self,
         expr: &Expression,
         arms: &[MatchArm],
-        _span: &Span,
+        span: &Span,
     ) -> TypeResult<InferredType> {
         let expr_type = self.check_expression(expr)?;
 
@@ -1178,9 +1604,84 @@ impl TypeChecker {
             self.context.pop_scope();
         }
 
+        // Guarded arms may not match, so only unguarded ones count towards coverage
+        let unguarded: Vec<&Pattern> = arms
+            .iter()
+            .filter(|arm| arm.guard.is_none())
+            .map(|arm| &arm.pattern)
+            .collect();
+        let scrutinee_type = self.unifier.finalize_type(&expr_type.ty);
+        if let Some(missing) = Self::missing_case(&unguarded, &scrutinee_type) {
+            return Err(TypeError::NonExhaustiveMatch {
+                missing,
+                line: span.line,
+                column: span.column,
+            });
+        }
+
         Ok(first_result_type)
     }
 
+    /// A value of `ty` that none of `patterns` matches, written as a pattern, or `None`
+    /// if they cover every value. Bool, Option and Result are split into their cases;
+    /// numbers, strings and arrays need a catch-all arm. Other types are not checked.
+    fn missing_case(patterns: &[&Pattern], ty: &Type) -> Option<String> {
+        if patterns.is_empty() {
+            return Some("_".to_string());
+        }
+        if patterns
+            .iter()
+            .any(|pattern| matches!(pattern, Pattern::Wildcard(_) | Pattern::Identifier(..)))
+        {
+            return None;
+        }
+
+        match ty {
+            Type::Bool => [true, false]
+                .into_iter()
+                .find(|&value| {
+                    !patterns
+                        .iter()
+                        .any(|pattern| matches!(pattern, Pattern::BoolLiteral(b, _) if *b == value))
+                })
+                .map(|value| value.to_string()),
+            Type::TypeApp(name, args) if name == "Option" && args.len() == 1 => {
+                if !patterns
+                    .iter()
+                    .any(|pattern| matches!(pattern, Pattern::None(_)))
+                {
+                    return Some("None".to_string());
+                }
+                let inner: Vec<&Pattern> = patterns
+                    .iter()
+                    .filter_map(|pattern| match pattern {
+                        Pattern::Some(inner, _) => Some(inner.as_ref()),
+                        _ => None,
+                    })
+                    .collect();
+                Self::missing_case(&inner, &args[0]).map(|missing| format!("Some({})", missing))
+            }
+            Type::TypeApp(name, args) if name == "Result" && args.len() == 2 => {
+                let (mut values, mut errors) = (Vec::new(), Vec::new());
+                for pattern in patterns {
+                    match pattern {
+                        Pattern::Ok(inner, _) => values.push(inner.as_ref()),
+                        Pattern::Err(inner, _) => errors.push(inner.as_ref()),
+                        _ => {}
+                    }
+                }
+                Self::missing_case(&values, &args[0])
+                    .map(|missing| format!("Ok({})", missing))
+                    .or_else(|| {
+                        Self::missing_case(&errors, &args[1])
+                            .map(|missing| format!("Err({})", missing))
+                    })
+            }
+            Type::Int | Type::Float | Type::String | Type::Array(_) => Some("_".to_string()),
+            _ => None,
+        }
+    }
+
     fn check_pattern(&mut self, pattern: &Pattern, expected_type: &Type) -> TypeResult<()> {
         match pattern {
             Pattern::Identifier(name, _) => {
@@ -1242,48 +1743,33 @@ impl TypeChecker {
 
             Pattern::Some(inner_pattern, _) => {
                 // Expect Option<T> type
-                if let Type::TypeApp(name, args) = expected_type {
-                    if name == "Option" && args.len() == 1 {
-                        self.check_pattern(inner_pattern, &args[0])
-                    } else {
-                        Err(TypeError::TypeMismatch {
-                            expected: "Option<T>".to_string(),
-                            found: expected_type.to_string(),
-                            line: 0,
-                            column: 0,
-                        })
-                    }
-                } else {
-                    Err(TypeError::TypeMismatch {
-                        expected: "Option<T>".to_string(),
-                        found: expected_type.to_string(),
-                        line: 0,
-                        column: 0,
-                    })
-                }
+                let inner_type = self.context.fresh_type_var();
+                let option_type = Type::TypeApp("Option".to_string(), vec![inner_type.clone()]);
+                self.unifier.unify(expected_type, &option_type)?;
+                self.check_pattern(inner_pattern, &inner_type)
             }
 
             Pattern::None(_) => {
                 // Expect Option<T> type
-                if let Type::TypeApp(name, _) = expected_type {
-                    if name == "Option" {
-                        Ok(())
-                    } else {
-                        Err(TypeError::TypeMismatch {
-                            expected: "Option<T>".to_string(),
-                            found: expected_type.to_string(),
-                            line: 0,
-                            column: 0,
-                        })
-                    }
-                } else {
-                    Err(TypeError::TypeMismatch {
-                        expected: "Option<T>".to_string(),
-                        found: expected_type.to_string(),
-                        line: 0,
-                        column: 0,
-                    })
-                }
+                let option_type =
+                    Type::TypeApp("Option".to_string(), vec![self.context.fresh_type_var()]);
+                self.unifier.unify(expected_type, &option_type)
+            }
+
+            Pattern::Ok(inner_pattern, _) | Pattern::Err(inner_pattern, _) => {
+                // Expect Result<T, E> type
+                let value_type = self.context.fresh_type_var();
+                let error_type = self.context.fresh_type_var();
+                let result_type = Type::TypeApp(
+                    "Result".to_string(),
+                    vec![value_type.clone(), error_type.clone()],
+                );
+                self.unifier.unify(expected_type, &result_type)?;
+                let inner_type = match pattern {
+                    Pattern::Ok(..) => value_type,
+                    _ => error_type,
+                };
+                self.check_pattern(inner_pattern, &inner_type)
             }
 
             Pattern::Array(patterns, _) => {
@@ -1337,12 +1823,15 @@ impl TypeChecker {
         // Add parameters to environment
         let mut param_types = Vec::new();
         for param in params {
-            let param_type = InferredType {
-                ty: param.type_annotation.clone(),
-                constraints: Vec::new(),
-            };
-            param_types.push(param.type_annotation.clone());
-            self.context.env.bind(param.name.clone(), param_type);
+            let ty = self.param_type(param)?;
+            param_types.push(ty.clone());
+            self.context.env.bind(
+                param.name.clone(),
+                InferredType {
+                    ty,
+                    constraints: Vec::new(),
+                },
+            );
         }
 
         // Check body
@@ -1370,13 +1859,16 @@ impl TypeChecker {
                     self.check_expression(expr)?;
                 }
                 Statement::LetBinding(binding) => {
-                    let expr_type = self.check_expression(&binding.value)?;
+                    let expr_type = self.check_binding_value(binding)?;
                     // For now, we'll just check if the expression type matches any annotation
                     if let Some(annotation) = &binding.type_annotation {
                         self.unifier.unify(&expr_type.ty, annotation)?;
                     }
+                    let expr_type =
+                        Self::with_annotated_dimension(expr_type, &binding.type_annotation);
                     // Add the binding to the environment
                     self.context.env.bind(binding.name.clone(), expr_type);
+                    self.record_binding_required_args(binding);
                 } // Add other statement types as needed
             }
         }
@@ -1475,6 +1967,7 @@ impl TypeChecker {
             // Base types have no type variables
             Type::Int
             | Type::Float
+            | Type::Quantity(_)
             | Type::Bool
             | Type::String
             | Type::Unit
@@ -1570,6 +2063,79 @@ mod tests {
         assert!(result.is_ok());
     }
 
+    #[test]
+    fn test_adding_velocity_to_position_is_flagged() {
+        let result = parse_and_check(
+            r#"
+            let position: Float<m> = 3.0
+            let velocity: Float<m/s> = 2.0
+            let wrong = position + velocity
+        "#,
+        );
+        assert!(matches!(result, Err(TypeError::DimensionMismatch { .. })));
+    }
+
+    #[test]
+    fn test_dimension_exponent_overflow_is_flagged() {
+        let result = parse_and_check(
+            r#"
+            let d: Float<m^100> = 1.0
+            let squared = d * d
+        "#,
+        );
+        assert!(matches!(result, Err(TypeError::DimensionMismatch { .. })));
+
+        let result = parse_and_check(
+            r#"
+            let d: Float<m> = 1.0
+            let huge = d ^ 300
+        "#,
+        );
+        assert!(matches!(result, Err(TypeError::DimensionMismatch { .. })));
+
+        let result = parse_and_check(
+            r#"
+            let d: Float<m> = 1.0
+            let area: Float<m^2> = d ^ 2
+        "#,
+        );
+        assert!(result.is_ok(), "{:?}", result);
+    }
+
+    #[test]
+    fn test_distance_over_time_is_velocity() {
+        let lexer = Lexer::new(
+            r#"
+            let distance: Float<m> = 100.0
+            let time: Float<s> = 9.58
+            let speed = distance / time
+            let checked: Float<m/s> = speed
+        "#,
+        );
+        let mut parser = Parser::new(lexer).unwrap();
+        let ast = parser.parse_program().unwrap();
+
+        let mut checker = TypeChecker::new();
+        checker.check_program(&ast).unwrap();
+
+        let m = Dimension::from_unit("m").unwrap();
+        let s = Dimension::from_unit("s").unwrap();
+        let speed = checker.context.env.lookup("speed").unwrap();
+        assert_eq!(speed.ty, Type::Quantity(m.div(&s).unwrap()));
+    }
+
+    #[test]
+    fn test_untagged_floats_stay_compatible() {
+        let result = parse_and_check(
+            r#"
+            let x: Float<m> = 1.0
+            let y = x + 2.0
+            let z = 1.5 * 2.0
+        "#,
+        );
+        assert!(result.is_ok());
+    }
+
     #[test]
     fn test_matrix_operations() {
         let result = parse_and_check(
@@ -1581,4 +2147,361 @@ mod tests {
         );
         assert!(result.is_ok());
     }
+
+    #[test]
+    fn test_incremental_repl_lines() {
+        let parse = |input: &str| {
+            Parser::new(Lexer::new(input))
+                .unwrap()
+                .parse_program()
+                .unwrap()
+        };
+        let mut checker = TypeChecker::new();
+
+        checker
+            .check_incremental(&parse(
+                "import physics\nlet add = (a: Int, b: Int) => a + b",
+            ))
+            .unwrap();
+        assert_eq!(checker.get_warnings().len(), 1);
+
+        // Uses the earlier definition without reporting it again
+        let total = checker
+            .check_incremental(&parse("let total = add(1, 2)"))
+            .unwrap();
+        assert_eq!(total.ty, Type::Int);
+        assert!(checker.get_warnings().is_empty());
+
+        // Errors on the new line are still caught, and leave no binding behind
+        assert!(checker
+            .check_incremental(&parse("let bad = add(1, true)"))
+            .is_err());
+        assert!(checker.context.env.lookup("bad").is_none());
+
+        // Redefinitions replace the earlier binding
+        let scaled = checker
+            .check_incremental(&parse(
+                "let add = (a: Float, b: Float) => a * b\nlet scaled = add(2.0, 3.0)",
+            ))
+            .unwrap();
+        assert_eq!(scaled.ty, Type::Float);
+    }
+
+    #[test]
+    fn test_if_expression_values() {
+        let mut checker = TypeChecker::new();
+        let parse = |input: &str| {
+            Parser::new(Lexer::new(input))
+                .unwrap()
+                .parse_program()
+                .unwrap()
+        };
+
+        let branch = checker
+            .check_incremental(&parse("let x = if 1 < 2 { 1 } else { 2 }"))
+            .unwrap();
+        assert_eq!(branch.ty, Type::Int);
+        let ternary = checker
+            .check_incremental(&parse("let y = x > 1 ? 1.5 : 2.5"))
+            .unwrap();
+        assert_eq!(ternary.ty, Type::Float);
+
+        assert!(parse_and_check("let z = if true { 1 }").is_err());
+        assert!(parse_and_check("let z = true ? 1 : false").is_err());
+    }
+
+    #[test]
+    fn test_match_exhaustiveness() {
+        let missing = |input: &str| match parse_and_check(input) {
+            Err(TypeError::NonExhaustiveMatch { missing, .. }) => Some(missing),
+            Ok(_) => None,
+            Err(other) => panic!("unexpected error for {}: {}", input, other),
+        };
+
+        assert_eq!(
+            missing("let f = (x: Option<Int>) => match x { Some(v) => v }"),
+            Some("None".to_string())
+        );
+        assert_eq!(
+            missing("let f = (x: Option<Int>) => match x { Some(v) => v, None => 0 }"),
+            None
+        );
+        // Unannotated scrutinees are inferred from the patterns
+        assert_eq!(
+            missing("let f = (x) => match x { None => 0, Some(v) => v + 1 }"),
+            None
+        );
+        assert_eq!(
+            missing("let f = (x: Option<Int>) => match x { Some(v) if v > 0 => v, None => 0 }"),
+            Some("Some(_)".to_string())
+        );
+        assert_eq!(
+            missing("let f = (x: Option<Bool>) => match x { Some(true) => 1, None => 0 }"),
+            Some("Some(false)".to_string())
+        );
+
+        assert_eq!(
+            missing("let f = (b: Bool) => match b { true => 1 }"),
+            Some("false".to_string())
+        );
+        assert_eq!(
+            missing("let f = (b: Bool) => match b { true => 1, false => 0 }"),
+            None
+        );
+        assert_eq!(
+            missing("let f = (n: Int) => match n { 0 => 1, 1 => 1 }"),
+            Some("_".to_string())
+        );
+        assert_eq!(
+            missing("let f = (n: Int) => match n { 0 => 1, m => m }"),
+            None
+        );
+
+        assert_eq!(
+            missing("let f = (r: Result<Int, String>) => match r { Ok(v) => v }"),
+            Some("Err(_)".to_string())
+        );
+        assert_eq!(
+            missing("let f = (r) => match r { Err(e) => 0 }"),
+            Some("Ok(_)".to_string())
+        );
+        assert_eq!(
+            missing("let f = (r) => match r { Ok(v) => v, Err(e) => 0 }"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_option_and_result_constructors() {
+        let program = Parser::new(Lexer::new("let x = Some(1)"))
+            .unwrap()
+            .parse_program()
+            .unwrap();
+        let some = TypeChecker::new().check_incremental(&program).unwrap();
+        assert_eq!(
+            some.ty,
+            Type::TypeApp("Option".to_string(), vec![Type::Int])
+        );
+        // Each None gets its own element type
+        assert!(parse_and_check(
+            "let a = is_some(None)\nlet b = [Some(1), None]\nlet c = [Some(true), None]"
+        )
+        .is_ok());
+        assert!(parse_and_check("let a = [Some(1), Some(true)]").is_err());
+        assert!(parse_and_check("let r = map_option((v: Int) => v * 2, Some(3))").is_ok());
+        assert!(parse_and_check("let r = is_some(Ok(3))").is_err());
+    }
+
+    #[test]
+    fn test_physics_builtins_type_check() {
+        let result = parse_and_check(
+            r#"
+            let w = create_physics_world_2d()
+            let b = add_rigid_body(w, "sphere", 1.0, [0.0, 2.0, 0.0])
+            let f = apply_force(w, b, [1.0, 0.0, 0.0])
+            let r = record_trajectory(w, b)
+            let p = play_trajectory(w, b, recorded_trajectory(w, b), 1.0)
+            let hit = raycast(w, [0.0, 5.0, 0.0], [0.0, -1.0, 0.0], 10.0)
+            let d = hit.distance
+        "#,
+        );
+        assert!(result.is_ok(), "{:?}", result);
+
+        assert!(parse_and_check("let b = apply_torque(create_physics_world_2d(), 0)").is_err());
+        assert!(parse_and_check("let g = set_gravity_enabled(0, 1.5)").is_err());
+    }
+
+    #[test]
+    fn test_linear_algebra_builtins_type_check() {
+        let result = parse_and_check(
+            r#"
+            let m = [[2.0, 1.0], [1.0, 3.0]]
+            let d = determinant(m) + poly_eval([1.0, 0.0, -2.0], 1.5)
+            let vals = eigenvalues_symmetric(m)
+            let full = convolve([1.0, 2.0, 3.0], [0.5, 0.5])
+            let same = correlate([1.0, 2.0, 3.0], [0.5, 0.5], "same")
+            let inv = inverse(m)
+            let grid = matrix_from_fn(3, 3, (i: Int, j: Int) => i + j)
+            let zeros = matrix_fill(2, 2, 0.0)
+        "#,
+        );
+        assert!(result.is_ok(), "{:?}", result);
+
+        assert!(parse_and_check("let d = determinant(2.0)").is_err());
+        assert!(parse_and_check("let c = convolve([1.0])").is_err());
+        assert!(parse_and_check("let g = matrix_from_fn(2, 2, (i: Int) => i)").is_err());
+    }
+
+    #[test]
+    fn test_utility_builtins_type_check() {
+        let result = parse_and_check(
+            r#"
+            let a = lerp_angle(0.0, 3.0, 0.5) + angle_difference(0.1, 6.2)
+            let v = clamp_magnitude([3.0, 4.0, 0.0], 1.0)
+            let s = seed(42)
+            let n = fbm(1.5, 2.5, 4) + perlin3(0.1, 0.2, 0.3) + random_normal(0.0, 1.0)
+            let k = random_poisson(3.0) + str_len(str_upper("abc"))
+            let rows = parse_csv("a,1\nb,2")
+            let names = list_builtins()
+            let pairs = zip(enumerate([1.0, 2.0]), range_array(0, 4, 2))
+            let d = dict_set({ mass: 1.0 }, "size", 2.0)
+            let m = dict_get(d, "mass") + d["size"]
+            let has = dict_contains(dict_new(), "id")
+            let window = [1.0, 2.0, 3.0, 4.0][1..=2]
+            let ok = assert_near(3, 3.0, 0.001)
+        "#,
+        );
+        assert!(result.is_ok(), "{:?}", result);
+
+        assert!(parse_and_check("let n = perlin2(\"x\", 1.0)").is_err());
+        assert!(parse_and_check("let s = str_concat(\"a\")").is_err());
+        assert!(parse_and_check("let p = zip([1, 2], 3)").is_err());
+        assert!(parse_and_check("let d = dict_set({ mass: 1.0 }, \"id\", \"x\")").is_err());
+        assert!(parse_and_check("let k = dict_keys([1, 2])").is_err());
+        assert!(parse_and_check("let w = [1, 2, 3][0..1.5]").is_err());
+    }
+
+    #[test]
+    fn test_format_accepts_any_number_of_values() {
+        let lexer = Lexer::new(
+            r#"
+            let greeting = format("hello")
+            let line = format("{} has {} items at {}", "cart", 3, 1.5)
+        "#,
+        );
+        let ast = Parser::new(lexer).unwrap().parse_program().unwrap();
+        let mut checker = TypeChecker::new();
+        checker.check_program(&ast).unwrap();
+        assert_eq!(checker.context.env.lookup("line").unwrap().ty, Type::String);
+
+        assert!(parse_and_check("let s = format()").is_err());
+        assert!(parse_and_check("let s = format(3, 4)").is_err());
+        // A user definition replaces the builtin, extra arguments included
+        assert!(
+            parse_and_check("let format = (s: String) => s\nlet t = format(\"a\", 1)").is_err()
+        );
+    }
+
+    #[test]
+    fn test_prelude_helpers_type_check() {
+        let result = parse_and_check(
+            r#"
+            let t = clamp01(lerp(0.0, 2.0, 0.25))
+            let l = length([3.0, 4.0, 0.0]) + distance([0.0, 0.0, 0.0], [3.0, 4.0, 0.0])
+        "#,
+        );
+        assert!(result.is_ok(), "{:?}", result);
+
+        assert!(parse_and_check("let t = clamp01(\"high\")").is_err());
+        assert!(parse_and_check("let t = lerp(0.0, 1.0)").is_err());
+    }
+
+    #[test]
+    fn test_array_combinators_are_polymorphic() {
+        let lexer = Lexer::new(
+            r#"
+            let names = map((x: Int) => str(x), [1, 2, 3])
+            let big = filter((x: Float) => x > 1.5, [1.0, 2.0])
+            let total = fold((acc: Int, s: String) => acc + str_len(s), 0, names)
+        "#,
+        );
+        let ast = Parser::new(lexer).unwrap().parse_program().unwrap();
+        let mut checker = TypeChecker::new();
+        checker.check_program(&ast).unwrap();
+        let ty = |name: &str| {
+            let inferred = checker.context.env.lookup(name).unwrap().clone();
+            checker.unifier.finalize_type(&inferred.ty)
+        };
+        assert_eq!(ty("names"), Type::Array(Box::new(Type::String)));
+        assert_eq!(ty("big"), Type::Array(Box::new(Type::Float)));
+        assert_eq!(ty("total"), Type::Int);
+
+        assert!(parse_and_check("let b = filter((x: Int) => x + 1, [1, 2])").is_err());
+        assert!(parse_and_check("let m = map((x: Int) => x, [\"a\"])").is_err());
+    }
+
+    #[test]
+    fn test_let_bound_lambdas_can_recurse() {
+        let lexer = Lexer::new(
+            r#"
+            let count_down = (n) => if n == 0 { 0 } else { count_down(n - 1) }
+            let result = count_down(1_000_000)
+            let factorial = (n: Int, acc: Int) => if n <= 1 { acc } else { factorial(n - 1, acc * n) }
+            let big = factorial(20, 1)
+        "#,
+        );
+        let ast = Parser::new(lexer).unwrap().parse_program().unwrap();
+        let mut checker = TypeChecker::new();
+        checker.check_program(&ast).unwrap();
+        let big = checker.context.env.lookup("big").unwrap().clone();
+        assert_eq!(checker.unifier.finalize_type(&big.ty), Type::Int);
+
+        // The recursive call is checked against the lambda's own parameters
+        assert!(
+            parse_and_check("let f = (n: Int) => if n == 0 { 0 } else { f(\"again\") }").is_err()
+        );
+        assert!(parse_and_check("let g = (n: Int) => if n == 0 { 0 } else { g(n, n) }").is_err());
+        assert!(parse_and_check(
+            "let h = (n: Int) => {\n let inner = (k: Int) => if k == 0 { 0 } else { inner(k - 1) }\n inner(n)\n}"
+        )
+        .is_ok());
+    }
+
+    #[test]
+    fn test_untyped_parameters_can_appear_on_both_sides_of_an_operator() {
+        let lexer = Lexer::new(
+            r#"
+            let square = (x) => x * x
+            let negate = (x) => -x
+            let area = square(2.5)
+            let back = negate(3)
+        "#,
+        );
+        let ast = Parser::new(lexer).unwrap().parse_program().unwrap();
+        let mut checker = TypeChecker::new();
+        checker.check_program(&ast).unwrap();
+        let ty = |name: &str| {
+            let inferred = checker.context.env.lookup(name).unwrap().clone();
+            checker.unifier.finalize_type(&inferred.ty)
+        };
+        assert_eq!(ty("area"), Type::Float);
+        assert_eq!(ty("back"), Type::Int);
+    }
+
+    #[test]
+    fn test_module_exports() {
+        let mut checker = TypeChecker::new();
+        let parse = |input: &str| {
+            Parser::new(Lexer::new(input))
+                .unwrap()
+                .parse_program()
+                .unwrap()
+        };
+
+        checker
+            .check_incremental(&parse(
+                "module A {\n let helper = (x: Int) => x + 1\n pub let twice = (x: Int) => helper(helper(x))\n }\nmodule B {\n pub let helper = 2.5\n }",
+            ))
+            .unwrap();
+        let qualified = checker
+            .check_incremental(&parse("let y = A::twice(1)"))
+            .unwrap();
+        assert_eq!(qualified.ty, Type::Int);
+        let same_name = checker
+            .check_incremental(&parse("let z = B::helper"))
+            .unwrap();
+        assert_eq!(same_name.ty, Type::Float);
+
+        // Private members are neither qualified names nor importable
+        assert!(matches!(
+            checker.check_incremental(&parse("let w = A::helper(1)")),
+            Err(TypeError::UnknownIdentifier { ref name, .. }) if name == "A::helper"
+        ));
+        assert!(checker
+            .check_incremental(&parse("import A { helper }"))
+            .is_err());
+        assert!(checker.context.env.lookup("helper").is_none());
+        checker.check_incremental(&parse("import A")).unwrap();
+        assert!(checker.context.env.lookup("twice").is_some());
+    }
 }
@@ -1,5 +1,7 @@
 pub mod checker;
 pub mod types;
+pub mod units;
 
 pub use checker::*;
 pub use types::*;
+pub use units::Dimension;
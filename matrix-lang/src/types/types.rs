@@ -1,5 +1,5 @@
 use crate::ast::*;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use thiserror::Error;
 
 #[derive(Error, Debug, Clone, PartialEq)]
@@ -57,6 +57,23 @@ pub enum TypeError {
         column: usize,
     },
 
+    #[error(
+        "Dimension mismatch: expected {expected}, found {found} at line {line}, column {column}"
+    )]
+    DimensionMismatch {
+        expected: String,
+        found: String,
+        line: usize,
+        column: usize,
+    },
+
+    #[error("Non-exhaustive match: {missing} is not covered at line {line}, column {column}")]
+    NonExhaustiveMatch {
+        missing: String,
+        line: usize,
+        column: usize,
+    },
+
     #[error("Circular import detected: module {module} is already being imported")]
     CircularImport { module: String, chain: Vec<String> },
 }
@@ -198,6 +215,10 @@ pub struct TypeContext {
     pub structs: StructRegistry,
     pub typeclasses: TypeclassRegistry,
     pub next_type_var: usize,
+    /// Minimum argument count of builtins whose trailing parameters are optional
+    pub builtin_required_args: HashMap<String, usize>,
+    /// Builtins accepting any number of arguments, of any type, after their parameters
+    pub variadic_builtins: HashSet<String>,
 }
 
 impl Default for TypeContext {
@@ -213,6 +234,8 @@ impl TypeContext {
             structs: StructRegistry::new(),
             typeclasses: TypeclassRegistry::new(),
             next_type_var: 0,
+            builtin_required_args: HashMap::new(),
+            variadic_builtins: HashSet::new(),
         };
 
         // Register built-in types and typeclasses
@@ -252,11 +275,13 @@ impl TypeContext {
                     Parameter {
                         name: "a".to_string(),
                         type_annotation: Type::Int,
+                        default: None,
                         span: Span::new(0, 0, 0, 0),
                     },
                     Parameter {
                         name: "b".to_string(),
                         type_annotation: Type::Int,
+                        default: None,
                         span: Span::new(0, 0, 0, 0),
                     },
                 ],
@@ -279,11 +304,13 @@ impl TypeContext {
                     Parameter {
                         name: "a".to_string(),
                         type_annotation: Type::Float,
+                        default: None,
                         span: Span::new(0, 0, 0, 0),
                     },
                     Parameter {
                         name: "b".to_string(),
                         type_annotation: Type::Float,
+                        default: None,
                         span: Span::new(0, 0, 0, 0),
                     },
                 ],
@@ -366,6 +393,74 @@ impl TypeContext {
             },
         );
 
+        // format function: a template string followed by the values to substitute
+        self.env.bind(
+            "format".to_string(),
+            InferredType {
+                ty: Type::Function(vec![Type::String], Box::new(Type::String)),
+                constraints: Vec::new(),
+            },
+        );
+        self.variadic_builtins.insert("format".to_string());
+
+        // eval function: runs source in the caller's scope, so its result is only
+        // known at run time
+        self.env.bind(
+            "eval".to_string(),
+            InferredType {
+                ty: Type::Function(vec![Type::String], Box::new(Type::TypeVar("T".to_string()))),
+                constraints: Vec::new(),
+            },
+        );
+
+        // Option and Result constructors and helpers. `None` is typed where it is
+        // used, with a fresh Option type each time.
+        let var = |name: &str| Type::TypeVar(name.to_string());
+        let option = |inner: Type| Type::TypeApp("Option".to_string(), vec![inner]);
+        let result = || Type::TypeApp("Result".to_string(), vec![var("T"), var("E")]);
+        let builtins = [
+            ("Some", vec![var("T")], option(var("T"))),
+            ("Ok", vec![var("T")], result()),
+            ("Err", vec![var("E")], result()),
+            // Accepts a Result as well, so the argument is left unconstrained
+            ("unwrap", vec![var("T")], var("U")),
+            ("is_some", vec![option(var("T"))], Type::Bool),
+            (
+                "map_option",
+                vec![
+                    Type::Function(vec![var("T")], Box::new(var("U"))),
+                    option(var("T")),
+                ],
+                option(var("U")),
+            ),
+        ];
+        self.bind_functions(builtins);
+
+        // Array combinators: map(f, array), filter(predicate, array), fold(f, initial, array)
+        let array = |elem: Type| Type::Array(Box::new(elem));
+        let function = |params: Vec<Type>, ret: Type| Type::Function(params, Box::new(ret));
+        self.bind_functions([
+            (
+                "map",
+                vec![function(vec![var("T")], var("U")), array(var("T"))],
+                array(var("U")),
+            ),
+            (
+                "filter",
+                vec![function(vec![var("T")], Type::Bool), array(var("T"))],
+                array(var("T")),
+            ),
+            (
+                "fold",
+                vec![
+                    function(vec![var("A"), var("T")], var("A")),
+                    var("A"),
+                    array(var("T")),
+                ],
+                var("A"),
+            ),
+        ]);
+
         // Math functions from interpreter builtins
         self.env.bind(
             "abs".to_string(),
@@ -856,6 +951,302 @@ impl TypeContext {
                 constraints: Vec::new(),
             },
         );
+
+        self.register_physics_functions();
+        self.register_linear_algebra_functions();
+        self.register_utility_functions();
+    }
+
+    /// Bind each `(name, parameter types, return type)` entry as a builtin function
+    fn bind_functions<const N: usize>(&mut self, functions: [(&str, Vec<Type>, Type); N]) {
+        for (name, params, ret) in functions {
+            self.env.bind(
+                name.to_string(),
+                InferredType {
+                    ty: Type::Function(params, Box::new(ret)),
+                    constraints: Vec::new(),
+                },
+            );
+        }
+    }
+
+    /// Physics builtins beyond the original world/body calls. Worlds, bodies and
+    /// constraints are Int ids. Vectors may be `[x, y, z]` arrays or x/y/z structs,
+    /// and callbacks, states and query results are left open as type variables.
+    fn register_physics_functions(&mut self) {
+        let var = |name: &str| Type::TypeVar(name.to_string());
+        let vector = || var("V");
+        let array = |elem: Type| Type::Array(Box::new(elem));
+        let (int, float, boolean, unit) =
+            (|| Type::Int, || Type::Float, || Type::Bool, || Type::Unit);
+        self.bind_functions([
+            ("create_physics_world_2d", vec![], int()),
+            // Forces, impulses and angular state
+            ("apply_force", vec![int(), int(), vector()], unit()),
+            ("apply_impulse", vec![int(), int(), vector()], unit()),
+            ("apply_torque", vec![int(), int(), vector()], unit()),
+            (
+                "apply_angular_impulse",
+                vec![int(), int(), vector()],
+                unit(),
+            ),
+            ("set_inertia", vec![int(), int(), var("T")], unit()),
+            ("get_angular_momentum", vec![int(), int()], array(float())),
+            ("set_angular_momentum", vec![int(), int(), vector()], unit()),
+            ("duplicate_body", vec![int(), int(), vector()], int()),
+            // Constraints, ropes and cloth
+            ("add_distance_constraint", vec![int(), int(), int()], int()),
+            (
+                "add_hinge",
+                vec![int(), int(), int(), vector(), var("A")],
+                int(),
+            ),
+            ("add_rope", vec![int(), vector(), var("W"), int()], int()),
+            ("rope_segments", vec![int(), int()], array(int())),
+            (
+                "add_cloth",
+                vec![int(), vector(), float(), float(), int()],
+                int(),
+            ),
+            ("cloth_particles", vec![int(), int()], array(int())),
+            ("cloth_constraint_count", vec![int(), int()], int()),
+            ("set_pinned", vec![int(), int(), boolean()], unit()),
+            // Trajectories
+            ("record_trajectory", vec![int(), int()], unit()),
+            (
+                "recorded_trajectory",
+                vec![int(), int()],
+                array(array(float())),
+            ),
+            (
+                "play_trajectory",
+                vec![int(), int(), var("T"), float(), boolean()],
+                unit(),
+            ),
+            ("is_playing_trajectory", vec![int(), int()], boolean()),
+            // Callbacks and fields
+            ("on_collision", vec![int(), var("F")], unit()),
+            (
+                "set_collision_filter",
+                vec![
+                    int(),
+                    Type::Function(vec![int(), int()], Box::new(boolean())),
+                ],
+                unit(),
+            ),
+            ("set_force_field", vec![int(), var("F")], unit()),
+            ("set_wind", vec![int(), vector()], unit()),
+            ("set_attractor", vec![int(), vector(), float()], unit()),
+            ("set_gravity_enabled", vec![int(), boolean()], unit()),
+            // Body state and properties
+            ("get_body_state", vec![int(), int()], var("S")),
+            ("set_body_state", vec![int(), int(), var("S")], unit()),
+            ("physics_state_hash", vec![int()], int()),
+            ("set_body_active", vec![int(), int(), boolean()], unit()),
+            (
+                "set_dof_lock",
+                [vec![int(), int()], vec![boolean(); 6]].concat(),
+                unit(),
+            ),
+            ("set_user_data", vec![int(), int(), var("T")], unit()),
+            ("get_user_data", vec![int(), int()], var("T")),
+            ("set_one_way", vec![int(), int(), vector()], unit()),
+            ("set_material", vec![int(), int(), float(), float()], unit()),
+            (
+                "set_velocity_limits",
+                vec![int(), int(), float(), float()],
+                unit(),
+            ),
+            ("get_velocity_limits", vec![int(), int()], array(float())),
+            ("set_object_size", vec![int(), int(), vector()], unit()),
+            // Solver settings
+            ("set_quality_preset", vec![int(), Type::String], unit()),
+            ("set_integrator", vec![int(), Type::String], unit()),
+            (
+                "set_solver_params",
+                vec![int(), float(), float(), int(), int()],
+                unit(),
+            ),
+            ("set_spatial_hash_cell_size", vec![int(), float()], unit()),
+            ("get_time", vec![int()], float()),
+            ("reset_time", vec![int()], unit()),
+            // Queries and sensors
+            (
+                "add_sensor",
+                vec![int(), int(), int(), float(), float()],
+                int(),
+            ),
+            ("sensor_readings", vec![int(), int()], array(float())),
+            ("overlap_box", vec![int(), vector(), var("W")], array(int())),
+            (
+                "raycast",
+                vec![int(), vector(), var("W"), float()],
+                var("R"),
+            ),
+            ("get_manifold", vec![int(), int(), int()], var("R")),
+            ("distance_between", vec![int(), int(), int()], var("R")),
+            // Particles and fluids
+            (
+                "add_particle_system",
+                vec![int(), vector(), float(), float()],
+                int(),
+            ),
+            (
+                "set_particle_emission",
+                vec![int(), int(), vector(), float(), float()],
+                unit(),
+            ),
+            (
+                "get_particle_positions",
+                vec![int(), int()],
+                array(array(float())),
+            ),
+            (
+                "add_fluid_volume",
+                vec![int(), vector(), var("W"), float()],
+                int(),
+            ),
+        ]);
+        // `looping` defaults to false
+        self.builtin_required_args
+            .insert("play_trajectory".to_string(), 4);
+    }
+
+    /// Matrix decompositions, rotations, signal filters and polynomial helpers.
+    /// Results are computed in floating point whatever the input element type.
+    fn register_linear_algebra_functions(&mut self) {
+        let var = |name: &str| Type::TypeVar(name.to_string());
+        let matrix = |elem: Type| Type::Matrix(Box::new(elem), None, None);
+        let floats = || Type::Array(Box::new(Type::Float));
+        self.bind_functions([
+            ("eigenvalues_symmetric", vec![matrix(var("T"))], floats()),
+            (
+                "eigenvectors_symmetric",
+                vec![matrix(var("T"))],
+                matrix(Type::Float),
+            ),
+            (
+                "qr",
+                vec![matrix(var("T"))],
+                Type::Array(Box::new(matrix(Type::Float))),
+            ),
+            ("lstsq", vec![matrix(var("T")), var("U")], floats()),
+            ("inverse", vec![matrix(var("T"))], matrix(Type::Float)),
+            // Builders: a `rows` x `cols` matrix of one value, or of `f(row, col)`
+            (
+                "matrix_fill",
+                vec![Type::Int, Type::Int, var("T")],
+                matrix(var("T")),
+            ),
+            (
+                "matrix_from_fn",
+                vec![
+                    Type::Int,
+                    Type::Int,
+                    Type::Function(vec![Type::Int, Type::Int], Box::new(var("T"))),
+                ],
+                matrix(var("T")),
+            ),
+            ("determinant", vec![matrix(var("T"))], Type::Float),
+            ("euler_to_matrix", vec![var("V")], matrix(Type::Float)),
+            ("matrix_to_euler", vec![matrix(var("T"))], floats()),
+            ("moving_average", vec![floats(), Type::Int], floats()),
+            (
+                "exponential_smoothing",
+                vec![floats(), Type::Float],
+                floats(),
+            ),
+            ("convolve", vec![floats(), floats(), Type::String], floats()),
+            (
+                "correlate",
+                vec![floats(), floats(), Type::String],
+                floats(),
+            ),
+            ("poly_eval", vec![floats(), Type::Float], Type::Float),
+            ("poly_roots_real", vec![floats()], floats()),
+        ]);
+        // The output `mode` defaults to "full"
+        for name in ["convolve", "correlate"] {
+            self.builtin_required_args.insert(name.to_string(), 2);
+        }
+    }
+
+    /// Vector math, noise, random numbers, iteration, dictionaries and indexing,
+    /// strings, CSV parsing and introspection. Vectors may be `[x, y, z]` arrays or
+    /// x/y/z structs, as for physics.
+    fn register_utility_functions(&mut self) {
+        let var = |name: &str| Type::TypeVar(name.to_string());
+        let vector = || var("V");
+        let array = |elem: Type| Type::Array(Box::new(elem));
+        let (int, float, string, unit) =
+            (|| Type::Int, || Type::Float, || Type::String, || Type::Unit);
+        let dict = || Type::TypeApp("Dict".to_string(), vec![var("D")]);
+        self.bind_functions([
+            // Angles and vectors
+            ("lerp_angle", vec![float(), float(), float()], float()),
+            ("angle_difference", vec![float(), float()], float()),
+            ("clamp_magnitude", vec![vector(), float()], vector()),
+            ("project", vec![vector(), var("W")], vector()),
+            ("reject", vec![vector(), var("W")], vector()),
+            ("cross", vec![vector(), var("W")], vector()),
+            ("dot", vec![vector(), var("W")], float()),
+            ("distance", vec![vector(), var("W")], float()),
+            ("normalize", vec![vector()], vector()),
+            ("vec3", vec![float(), float(), float()], array(float())),
+            // Noise and random numbers
+            ("perlin2", vec![float(), float()], float()),
+            ("perlin3", vec![float(), float(), float()], float()),
+            ("fbm", vec![float(), float(), int()], float()),
+            ("set_seed", vec![int()], unit()),
+            ("seed", vec![int()], unit()),
+            ("random", vec![], float()),
+            ("random_range", vec![float(), float()], float()),
+            ("random_int", vec![int(), int()], int()),
+            ("random_normal", vec![float(), float()], float()),
+            ("random_poisson", vec![float()], int()),
+            // Iteration. Pairs are two-element arrays whose elements may differ in
+            // type, so they are left open.
+            (
+                "zip",
+                vec![array(var("T")), array(var("U"))],
+                array(var("P")),
+            ),
+            ("enumerate", vec![array(var("T"))], array(var("P"))),
+            (
+                "range_array",
+                vec![var("N"), var("N"), var("N")],
+                array(var("N")),
+            ),
+            // Dictionaries, which have string keys and values of one type
+            ("dict_new", vec![], dict()),
+            ("dict_get", vec![dict(), string()], var("D")),
+            ("dict_set", vec![dict(), string(), var("D")], dict()),
+            ("dict_keys", vec![dict()], array(string())),
+            ("dict_contains", vec![dict(), string()], Type::Bool),
+            // `c[i]` on an array, matrix or dictionary. The element type depends on
+            // the container, so the result is left open.
+            ("index", vec![var("C"), var("I")], var("E")),
+            // `c[start..end]` (or `..=`) on an array or matrix, keeping its type
+            ("slice", vec![var("C"), int(), int(), Type::Bool], var("C")),
+            // Strings and CSV
+            ("str_len", vec![string()], int()),
+            ("str_concat", vec![string(), string()], string()),
+            ("str_split", vec![string(), string()], array(string())),
+            ("str_upper", vec![string()], string()),
+            ("str_lower", vec![string()], string()),
+            ("substring", vec![string(), int(), int()], string()),
+            // Cells are numbers where they parse as one and strings otherwise
+            ("parse_csv", vec![string()], array(array(var("T")))),
+            (
+                "parse_csv_numeric",
+                vec![string()],
+                Type::Matrix(Box::new(float()), None, None),
+            ),
+            // Testing and introspection
+            ("assert_near", vec![var("T"), var("U"), float()], unit()),
+            ("list_builtins", vec![], array(string())),
+            ("builtin_arity", vec![string()], int()),
+        ]);
     }
 
     pub fn fresh_type_var(&mut self) -> Type {
@@ -941,6 +1332,7 @@ impl Type {
         match self {
             Type::Int => "Int".to_string(),
             Type::Float => "Float".to_string(),
+            Type::Quantity(dimension) => format!("Float<{}>", dimension),
             Type::Bool => "Bool".to_string(),
             Type::String => "String".to_string(),
             Type::Unit => "Unit".to_string(),
@@ -0,0 +1,146 @@
+// Dimensional analysis support for unit-tagged float types (e.g. `Float<m/s>`)
+use serde::{Deserialize, Serialize};
+
+/// SI base units, in the order their exponents are stored in a `Dimension`
+pub const BASE_UNITS: [&str; 7] = ["kg", "m", "s", "A", "K", "mol", "cd"];
+
+/// Physical dimension as integer exponents over the SI base units
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+pub struct Dimension {
+    pub exponents: [i8; 7],
+}
+
+impl Dimension {
+    pub fn dimensionless() -> Self {
+        Self::default()
+    }
+
+    pub fn is_dimensionless(&self) -> bool {
+        self.exponents.iter().all(|&e| e == 0)
+    }
+
+    /// Look up a base or derived unit symbol
+    pub fn from_unit(symbol: &str) -> Option<Self> {
+        if let Some(index) = BASE_UNITS.iter().position(|&u| u == symbol) {
+            let mut exponents = [0; 7];
+            exponents[index] = 1;
+            return Some(Self { exponents });
+        }
+
+        // Derived units, as [kg, m, s, A, K, mol, cd]
+        let exponents = match symbol {
+            "N" => [1, 1, -2, 0, 0, 0, 0],
+            "J" => [1, 2, -2, 0, 0, 0, 0],
+            "W" => [1, 2, -3, 0, 0, 0, 0],
+            "Pa" => [1, -1, -2, 0, 0, 0, 0],
+            "Hz" => [0, 0, -1, 0, 0, 0, 0],
+            "C" => [0, 0, 1, 1, 0, 0, 0],
+            "V" => [1, 2, -3, -1, 0, 0, 0],
+            "rad" => [0; 7],
+            _ => return None,
+        };
+        Some(Self { exponents })
+    }
+
+    /// Product of two dimensions; `None` if an exponent leaves the `i8` range
+    pub fn mul(&self, other: &Dimension) -> Option<Dimension> {
+        let mut exponents = self.exponents;
+        for (e, o) in exponents.iter_mut().zip(other.exponents.iter()) {
+            *e = e.checked_add(*o)?;
+        }
+        Some(Dimension { exponents })
+    }
+
+    pub fn div(&self, other: &Dimension) -> Option<Dimension> {
+        self.mul(&other.powi(-1)?)
+    }
+
+    /// Dimension raised to `power`; `None` if an exponent leaves the `i8` range
+    pub fn powi(&self, power: i8) -> Option<Dimension> {
+        let mut exponents = self.exponents;
+        for e in exponents.iter_mut() {
+            *e = e.checked_mul(power)?;
+        }
+        Some(Dimension { exponents })
+    }
+}
+
+impl std::fmt::Display for Dimension {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let format_part = |unit: &str, exp: i8| {
+            if exp == 1 {
+                unit.to_string()
+            } else {
+                format!("{}^{}", unit, exp)
+            }
+        };
+
+        let numerator: Vec<String> = BASE_UNITS
+            .iter()
+            .zip(self.exponents.iter())
+            .filter(|(_, &e)| e > 0)
+            .map(|(u, &e)| format_part(u, e))
+            .collect();
+        let denominator: Vec<String> = BASE_UNITS
+            .iter()
+            .zip(self.exponents.iter())
+            .filter(|(_, &e)| e < 0)
+            .map(|(u, &e)| format_part(u, -e))
+            .collect();
+
+        let numerator = if numerator.is_empty() {
+            "1".to_string()
+        } else {
+            numerator.join("*")
+        };
+
+        if denominator.is_empty() {
+            write!(f, "{}", numerator)
+        } else {
+            write!(f, "{}/{}", numerator, denominator.join("*"))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_velocity_dimension() {
+        let m = Dimension::from_unit("m").unwrap();
+        let s = Dimension::from_unit("s").unwrap();
+        let velocity = m.div(&s).unwrap();
+
+        assert_eq!(velocity.to_string(), "m/s");
+        assert_ne!(velocity, m);
+        assert!(velocity
+            .mul(&s)
+            .and_then(|d| d.div(&m))
+            .unwrap()
+            .is_dimensionless());
+    }
+
+    #[test]
+    fn test_derived_units() {
+        let kg = Dimension::from_unit("kg").unwrap();
+        let m = Dimension::from_unit("m").unwrap();
+        let s = Dimension::from_unit("s").unwrap();
+        let newton = kg.mul(&m).unwrap().div(&s.powi(2).unwrap()).unwrap();
+
+        assert_eq!(Dimension::from_unit("N"), Some(newton));
+        assert_eq!(newton.to_string(), "kg*m/s^2");
+        assert_eq!(Dimension::from_unit("furlong"), None);
+    }
+
+    #[test]
+    fn test_exponent_overflow_is_none() {
+        let m = Dimension::from_unit("m").unwrap();
+        let big = m.powi(100).unwrap();
+
+        assert_eq!(big.mul(&big), None);
+        assert_eq!(m.powi(127).unwrap().div(&m.powi(-1).unwrap()), None);
+        assert_eq!(big.powi(2), None);
+        assert_eq!(m.powi(-128).unwrap().powi(-1), None);
+    }
+}